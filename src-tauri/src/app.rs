@@ -1,25 +1,128 @@
-use crate::file_cleaner::enhanced_engine::{CleaningResult, DeletionPreparation};
+use crate::capabilities::{self, Capabilities};
+use crate::config::{AppSettings, AuditModeSettings, SettingsStore};
+use crate::container_cleaner::{
+    self, ArtifactKind, ContainerEngine, ContainerScanReport, PruneResult,
+};
+use crate::file_cleaner::app_data::{self, AppDataApp, AppDataReport};
+use crate::file_cleaner::app_quit::{
+    self, AppCacheCleanResult, AppQuitCleanupMode, AppQuitSettings, HeavyApp,
+};
+use crate::file_cleaner::app_slimmer::{self, AppSlimReport, AppSlimResult, SlimSettings};
+use crate::file_cleaner::app_uninstaller::{AppFootprint, AppUninstaller, UninstallResult};
+use crate::file_cleaner::cache_growth::{
+    CacheGrowthLog, CacheGrowthReport, CacheGrowthTracker, CACHE_GROWTH_SWEEP,
+    DEFAULT_CACHE_GROWTH_DAYS,
+};
+use crate::file_cleaner::custom_path::{self, CustomScanOptions};
+use crate::file_cleaner::disk_analyzer::{self, DiskNode};
+use crate::file_cleaner::duplicate_detector::{DuplicateGroup, DuplicateScanResult, KeepStrategy};
+use crate::file_cleaner::enhanced_engine::{
+    CleaningResult, DeletionPreparation, FailedDeletion, SafetyExplanation,
+};
+use crate::file_cleaner::large_files::{self, LargeFile, LargeFileQuery, LargeFileScan};
+use crate::file_cleaner::photos_library::{self, PhotosLibraryReport};
+use crate::file_cleaner::process_snapshot::ProcessSnapshot;
+use crate::file_cleaner::project_artifacts::{ProjectArtifactQuery, ProjectArtifactReport};
+use crate::file_cleaner::quarantine::{
+    QuarantineEntry, QuarantinePurgeResult, QuarantineRestoreResult, QuarantineSettings,
+};
+use crate::file_cleaner::quick_clean::{self, QuickCleanResult, QUICK_CLEAN_BUDGET};
+use crate::file_cleaner::recommendations::StorageRecommendation;
+use crate::file_cleaner::report_delta::ReportDelta;
+use crate::file_cleaner::report_export::{self, ReportFormat, ScanReport};
+use crate::file_cleaner::rule_packs::{RulePack, RulePackStore};
+use crate::file_cleaner::safety_policies::{
+    CategorySafetyPolicy, SafetyPolicyStore, SafetyThresholds,
+};
 use crate::file_cleaner::smart_cache::AppActivityChecker;
-use crate::file_cleaner::telemetry::TelemetrySnapshot;
+use crate::file_cleaner::spotlight::set_spotlight_discovery;
+use crate::file_cleaner::system_load::SystemLoad;
+use crate::file_cleaner::telemetry::{
+    TelemetryInsights, TelemetryQuery, TelemetrySnapshot, TelemetryStore,
+};
+use crate::file_cleaner::trash_restore::TrashRestoreResult;
+use crate::file_cleaner::volumes::{self, VolumeInfo, VolumeScope};
 #[cfg(feature = "cache-refresh")]
 use crate::file_cleaner::CacheRefresher;
 use crate::file_cleaner::{
-    CleanableFile, CleaningReport, DryRunReport, EnhancedCleaningReport, EnhancedDeletionProgress,
-    EnhancedFileCleaner, FileCleaner, RuleConflict, UserAction,
+    self, CleanableFile, CleaningReport, DryRunReport, EnhancedCleaningReport,
+    EnhancedDeletionProgress, EnhancedFileCleaner, FileCleaner, RuleConflict, ScanProgress,
+    TrashEmptyProgress, TrashEmptyResult, TrashItem, TrashSummary, TrashVolume, UndoResult,
+    UserAction,
+};
+use crate::formatting::{self, FormatPreferences};
+use crate::health_score::{
+    self, CacheSample, CacheSizeLog, FailedOperation, HealthInputs, HealthScore, OperationTally,
+    WeeklySummary,
 };
-use crate::memory_optimizer::{MemoryOptimizationResult, MemoryOptimizer};
-use crate::metrics::MemoryStats;
-use crate::metrics::{CpuSnapshot, DiskSnapshot, MetricsSampler, MetricsSnapshot, SampleEnvelope};
-use crate::ops::{OpState, OperationKind, OperationRegistry, ThroughputTracker};
+use crate::instance_lock;
+use crate::lazy_init::{InitTiming, InitTimings, LazySubsystem};
+use crate::logging::{self, LogEntry};
+use crate::memory_optimizer::{
+    MemoryOptimizationPlan, MemoryOptimizationResult, MemoryOptimizedEvent, MemoryOptimizer,
+    MemoryPlanResult, MemoryStrategy, MemoryWatchdog, PressureLevel, WatchdogConfig,
+    WatchdogStatus,
+};
+use crate::metrics::{
+    self, CpuSnapshot, DiskSnapshot, HistoryPoint, HistoryRange, MetricsHistory, MetricsSampler,
+    MetricsSnapshot, SampleEnvelope,
+};
+use crate::metrics::{MemoryPressureBreakdown, MemoryStats};
+use crate::network_tools::{
+    self, ConnectivityReport, InterfaceStats, NetworkAction, NetworkConnection,
+    NetworkMaintenanceResult,
+};
+use crate::notifications::{NotificationSettings, Notifier};
+use crate::ops::history::{
+    self, HistoryFilter, HistoryPage, OperationHistory, OperationOutcome, OperationRecord,
+    Pagination,
+};
+use crate::ops::{
+    OpState, OperationKind, OperationRegistry, OperationStatus, PhaseTimings, ThroughputTracker,
+    INSPECTOR_MODE_REFUSAL,
+};
+use crate::performance::{self, PerformanceProfile, PerformanceProfileStore, PerformanceSettings};
+use crate::permissions::{self, PermissionReport};
+use crate::privileged_helper::{self, HelperStatus, HELPER_BINARY_NAME};
+use crate::scheduler::{
+    NewSchedule, Schedule, ScheduleAction, ScheduleStore, TrashPurgeLog, TrashPurgeRecord,
+};
+use crate::session::{SessionRecorder, SessionStore, WeeklyDigest};
+use crate::startup_manager::{StartupItem, StartupManager};
+use crate::system_info::apfs;
+use crate::system_info::app_usage::AppResourceUsage;
+use crate::system_info::gpu_info::{self, GpuInfo};
+use crate::system_info::network_usage::{self, ProcessNetworkUsage};
+use crate::system_info::power_info::{self, BatteryInfo};
+use crate::system_info::process_detail::ProcessDetail;
 use crate::system_info::{
     CpuInfo, DiskInfo, MemoryInfo, NetworkInfo, ProcessInfo, SystemInfo, SystemMonitor,
     TemperatureInfo,
 };
+use crate::system_maintenance::{self, MaintenanceResult, MaintenanceTask, MaintenanceTaskInfo};
+use crate::system_snapshot::{
+    self, AppEntry, SnapshotDiff, SnapshotSources, SnapshotStore, SnapshotSummary, SystemSnapshot,
+};
+use crate::tray::{self, TrayAction, TrayReadings, TraySettings};
+use crate::usage::{self, UsageFeature, UsageStore};
 
+use crate::file_cleaner::activity_heatmap::{ActivityHeatmap, ActivityIndex};
+use crate::file_cleaner::age_thresholds::{AgeThreshold, AgeThresholdStore};
+use crate::file_cleaner::custom_rules::{CustomRule, CustomRuleStore, NewCustomRule};
+use crate::file_cleaner::developer_junk::DeveloperJunkReport;
+use crate::file_cleaner::exclusions::{Exclusion, ExclusionStore, NewExclusion};
+use crate::file_cleaner::extension_stats::{self, ExtensionFile, ExtensionStat};
+use crate::file_cleaner::external_volumes::{self, ExternalVolume, ExternalVolumeStore};
+use crate::file_cleaner::hashing::{self, HashBenchmark};
+use crate::file_cleaner::installer_sources::{self, InstallerSourceReport};
+use crate::file_cleaner::ios_backups::{self, IosBackup};
+use crate::file_cleaner::learned_exceptions::{ExceptionDecision, LearnedException};
+use crate::file_cleaner::protection_packs::{ProtectionPack, ProtectionPackStore};
 use crate::file_cleaner::{load_rules_result, DynamicRuleEngine, RuleValidator};
 use serde::Serialize;
 use tauri::{Emitter, LogicalSize, Manager, State};
 use tokio::sync::RwLock;
+use tokio_util::sync::CancellationToken;
 
 // Progress event types for real-time operation feedback
 #[derive(Clone, Serialize)]
@@ -47,6 +150,29 @@ struct OperationCompleteEvent {
     message: String,
     duration: u32, // actual duration in ms
     canceled: Option<bool>,
+    /// Where the time went; absent once the operation is no longer tracked.
+    phase_timings: Option<PhaseTimings>,
+}
+
+/// One group of a running duplicate scan.
+#[derive(Clone, Serialize)]
+struct DuplicateFoundEvent {
+    operation_id: String,
+    group: DuplicateGroup,
+}
+
+/// One match of a running large file search.
+#[derive(Clone, Serialize)]
+struct LargeFileFoundEvent {
+    operation_id: String,
+    file: LargeFile,
+}
+
+#[derive(Clone, Serialize)]
+struct ModeChangedEvent {
+    inspector: bool,
+    /// Inspector mode is held on by the persisted audit mode.
+    audit: bool,
 }
 
 #[derive(Clone, Serialize)]
@@ -57,15 +183,65 @@ struct Throughput {
 
 const MIN_WINDOW_WIDTH: f64 = 600.0;
 const MIN_WINDOW_HEIGHT: f64 = 600.0;
+/// How often the background scheduler checks for due jobs.
+const SCHEDULER_TICK: std::time::Duration = std::time::Duration::from_secs(60);
+/// Log entries `get_recent_logs` returns unless asked for another number.
+const RECENT_LOG_ENTRIES: usize = 200;
+/// How often free space is checked for low disk space notifications.
+const DISK_SPACE_CHECK: std::time::Duration = std::time::Duration::from_secs(60);
+/// Pause between batches of deferred safety analysis, so other commands
+/// get the cleaner in between.
+const DEFERRED_SAFETY_PAUSE: std::time::Duration = std::time::Duration::from_millis(250);
 
 // Create a state to manage our system monitor
 struct AppState {
-    system_monitor: RwLock<SystemMonitor>,
+    /// Built on first use or by `warmup`; both take long enough to delay
+    /// the first paint.
+    system_monitor: LazySubsystem<SystemMonitor>,
     file_cleaner: RwLock<FileCleaner>,
-    enhanced_file_cleaner: RwLock<EnhancedFileCleaner>,
+    enhanced_file_cleaner: LazySubsystem<EnhancedFileCleaner>,
+    init_timings: std::sync::Arc<InitTimings>,
+    /// Quick clean candidates from the last enhanced scan, kept outside the
+    /// cleaner so a quick clean does not wait for a running scan.
+    quick_clean_items: RwLock<Vec<CleanableFile>>,
     memory_optimizer: RwLock<MemoryOptimizer>,
+    memory_watchdog: RwLock<MemoryWatchdog>,
+    notifier: Notifier,
     metrics_sampler: MetricsSampler,
+    /// Stops the running `metrics:sample` stream, if any.
+    metrics_stream: std::sync::Mutex<Option<CancellationToken>>,
+    /// Stops the background analysis of files the last scan deferred, if any.
+    safety_continuation: std::sync::Mutex<Option<CancellationToken>>,
+    metrics_history: RwLock<MetricsHistory>,
     ops: OperationRegistry,
+    performance_profile: RwLock<PerformanceProfileStore>,
+    schedules: RwLock<ScheduleStore>,
+    trash_purges: RwLock<TrashPurgeLog>,
+    /// Heatmaps built this session, reused for incremental rebuilds.
+    activity_index: std::sync::Arc<std::sync::Mutex<ActivityIndex>>,
+    custom_rules: RwLock<CustomRuleStore>,
+    rule_packs: RwLock<RulePackStore>,
+    exclusions: RwLock<ExclusionStore>,
+    age_thresholds: RwLock<AgeThresholdStore>,
+    external_volumes: RwLock<ExternalVolumeStore>,
+    safety_policies: RwLock<SafetyPolicyStore>,
+    protection_packs: RwLock<ProtectionPackStore>,
+    session: SessionRecorder,
+    sessions: RwLock<SessionStore>,
+    /// Cache size after each enhanced scan, for the health score.
+    cache_sizes: RwLock<CacheSizeLog>,
+    /// Per-app cache growth between sweeps of the watched cache folders.
+    cache_growth: RwLock<CacheGrowthLog>,
+    usage: RwLock<UsageStore>,
+    /// Local scan and cleaning history for the insights screen, collected
+    /// under the same opt-in as `usage`.
+    telemetry: RwLock<TelemetryStore>,
+    app_quit: RwLock<AppQuitSettings>,
+    tray: RwLock<TraySettings>,
+    audit_mode: RwLock<AuditModeSettings>,
+    settings: RwLock<SettingsStore>,
+    /// Footprints analyzed this session, by app path, for storage recommendations.
+    app_footprints: RwLock<std::collections::HashMap<String, AppFootprint>>,
     #[cfg(feature = "cache-refresh")]
     cache_refresher: std::sync::Arc<CacheRefresher>,
 }
@@ -102,30 +278,51 @@ fn cpu_info_from_snapshot(cpu: &CpuSnapshot) -> CpuInfo {
     }
 }
 
+/// Runs `diskutil` for APFS volumes, so call it off the async runtime.
 fn disks_from_snapshots(disks: Vec<DiskSnapshot>) -> Vec<DiskInfo> {
     disks
         .into_iter()
-        .map(|disk| DiskInfo {
-            name: disk.name,
-            mount_point: disk.mount_point,
-            total_space: disk.total_space,
-            available_space: disk.available_space,
-            used_space: disk.used_space,
-            file_system: disk.file_system,
-            is_removable: disk.is_removable,
-            device: disk.device,
-            kind: disk.kind,
-            is_system: disk.is_system,
+        .map(|disk| {
+            let is_apfs = disk.file_system.eq_ignore_ascii_case("apfs");
+            let free_space = apfs::free_space(&disk.mount_point);
+            // Finder's available space counts what macOS can purge; statvfs does not
+            let purgeable_space = free_space
+                .filter(|_| is_apfs)
+                .map(|free| disk.available_space.saturating_sub(free));
+            let apfs = is_apfs
+                .then(|| apfs::volume_info(&disk.mount_point, &disk.device))
+                .flatten();
+            DiskInfo {
+                name: disk.name,
+                mount_point: disk.mount_point,
+                total_space: disk.total_space,
+                available_space: disk.available_space,
+                used_space: disk.used_space,
+                file_system: disk.file_system,
+                is_removable: disk.is_removable,
+                device: disk.device,
+                kind: disk.kind,
+                is_system: disk.is_system,
+                free_space,
+                purgeable_space,
+                apfs,
+            }
         })
         .collect()
 }
 
+async fn disk_infos(disks: Vec<DiskSnapshot>) -> Result<Vec<DiskInfo>, String> {
+    tokio::task::spawn_blocking(move || disks_from_snapshots(disks))
+        .await
+        .map_err(|e| format!("Failed to read disk details: {}", e))
+}
+
 #[tauri::command]
 async fn get_system_info(state: State<'_, AppState>) -> Result<SystemInfo, String> {
     state.metrics_sampler.wait_until_ready().await;
     let snapshot = state.metrics_sampler.latest_snapshot().await;
 
-    let mut monitor = state.system_monitor.write().await;
+    let mut monitor = state.system_monitor.get().await.write().await;
     monitor.refresh();
     let mut info = monitor.get_system_info();
 
@@ -157,7 +354,7 @@ async fn get_cpu_info(state: State<'_, AppState>) -> Result<CpuInfo, String> {
 
 #[tauri::command]
 async fn get_processes(state: State<'_, AppState>) -> Result<Vec<ProcessInfo>, String> {
-    let mut monitor = state.system_monitor.write().await;
+    let mut monitor = state.system_monitor.get().await.write().await;
     monitor.refresh();
     Ok(monitor.get_processes())
 }
@@ -167,7 +364,7 @@ async fn get_top_memory_processes(
     state: State<'_, AppState>,
     limit: usize,
 ) -> Result<Vec<ProcessInfo>, String> {
-    let mut monitor = state.system_monitor.write().await;
+    let mut monitor = state.system_monitor.get().await.write().await;
     monitor.refresh();
     Ok(monitor.get_top_memory_processes(limit))
 }
@@ -178,7 +375,7 @@ async fn get_disks(state: State<'_, AppState>) -> Result<Vec<DiskInfo>, String>
     let snapshot = state.metrics_sampler.latest_snapshot().await;
     let disks = sample_value(&snapshot.disks, "disks")?;
 
-    Ok(disks_from_snapshots(disks))
+    disk_infos(disks).await
 }
 
 #[tauri::command]
@@ -187,18 +384,236 @@ async fn get_metrics_snapshot(state: State<'_, AppState>) -> Result<MetricsSnaps
     Ok(state.metrics_sampler.latest_snapshot().await)
 }
 
+/// Push `metrics:sample` events at most every `interval_ms` until
+/// `stop_metrics_stream`; replaces a stream that is already running.
+#[tauri::command]
+async fn start_metrics_stream(
+    app_handle: tauri::AppHandle,
+    state: State<'_, AppState>,
+    interval_ms: Option<u64>,
+) -> Result<(), String> {
+    let token = CancellationToken::new();
+    if let Some(previous) = state
+        .metrics_stream
+        .lock()
+        .map_err(|_| "Metrics stream state is poisoned".to_string())?
+        .replace(token.clone())
+    {
+        previous.cancel();
+    }
+
+    let mut updates = state.metrics_sampler.subscribe();
+    // Send the current snapshot straight away instead of waiting for a new sample.
+    updates.mark_changed();
+    let mut ticker = tokio::time::interval(metrics::stream_interval(interval_ms));
+    ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+    tauri::async_runtime::spawn(async move {
+        loop {
+            tokio::select! {
+                _ = token.cancelled() => break,
+                _ = ticker.tick() => {}
+            }
+            match updates.has_changed() {
+                Ok(true) => {}
+                Ok(false) => continue,
+                Err(_) => break,
+            }
+            let snapshot = updates.borrow_and_update().clone();
+            if app_handle.emit("metrics:sample", snapshot).is_err() {
+                break;
+            }
+        }
+    });
+    Ok(())
+}
+
+/// Memory, CPU and disk usage over `range`, averaged into `resolution`-second
+/// buckets for charting.
+#[tauri::command]
+async fn get_metrics_history(
+    state: State<'_, AppState>,
+    range: HistoryRange,
+    resolution: Option<u64>,
+) -> Result<Vec<HistoryPoint>, String> {
+    Ok(state
+        .metrics_history
+        .read()
+        .await
+        .query(range, resolution, chrono::Utc::now().timestamp()))
+}
+
+/// Append the sampler's latest snapshot to the metrics history once a minute.
+async fn run_metrics_history(app_handle: tauri::AppHandle) {
+    let state = app_handle.state::<AppState>();
+    state.metrics_sampler.wait_until_ready().await;
+    let mut ticker = tokio::time::interval(std::time::Duration::from_secs(
+        metrics::RECORD_INTERVAL_SECS,
+    ));
+    loop {
+        ticker.tick().await;
+        let snapshot = state.metrics_sampler.latest_snapshot().await;
+        let point = HistoryPoint::from_snapshot(&snapshot);
+        if let Err(err) = state.metrics_history.write().await.record(point) {
+            log::warn!("Failed to record metrics history: {}", err);
+        }
+    }
+}
+
+/// Notifies when a volume runs low on space, from the sampler's disk readings.
+async fn run_disk_space_notifications(app_handle: tauri::AppHandle) {
+    let state = app_handle.state::<AppState>();
+    state.metrics_sampler.wait_until_ready().await;
+    let mut ticker = tokio::time::interval(DISK_SPACE_CHECK);
+    loop {
+        ticker.tick().await;
+        let snapshot = state.metrics_sampler.latest_snapshot().await;
+        for disk in snapshot.disks.value.iter().flatten() {
+            let volume = if disk.name.is_empty() {
+                &disk.mount_point
+            } else {
+                &disk.name
+            };
+            state
+                .notifier
+                .disk_space(volume, disk.available_space, disk.total_space);
+        }
+    }
+}
+
+/// Operations the user started and may have walked away from.
+fn notify_operation_finished(notifier: &Notifier, record: &OperationRecord) {
+    if record.kind == OperationKind::DashboardRefresh {
+        return;
+    }
+    let (outcome, detail) = match record.status {
+        OperationStatus::Completed if record.outcome.bytes_freed > 0 => (
+            "finished",
+            Some(format!(
+                "Freed {}",
+                formatting::format_size(record.outcome.bytes_freed)
+            )),
+        ),
+        OperationStatus::Completed => ("finished", None),
+        OperationStatus::Failed => ("failed", record.error.clone()),
+        OperationStatus::Canceled => ("was canceled", None),
+        OperationStatus::Pending | OperationStatus::Running => return,
+    };
+    notifier.operation_finished(
+        record.kind.label(),
+        outcome,
+        detail.as_deref(),
+        std::time::Duration::from_millis(record.duration_ms),
+    );
+}
+
+#[tauri::command]
+async fn get_notification_settings(
+    state: State<'_, AppState>,
+) -> Result<NotificationSettings, String> {
+    Ok(state.notifier.settings())
+}
+
+/// Turn notifications for finished operations, low disk space and memory
+/// pressure on or off, and set their thresholds.
+#[tauri::command]
+async fn configure_notifications(
+    state: State<'_, AppState>,
+    settings: NotificationSettings,
+) -> Result<NotificationSettings, String> {
+    state.notifier.configure(settings)?;
+    Ok(state.notifier.settings())
+}
+
+#[tauri::command]
+async fn stop_metrics_stream(state: State<'_, AppState>) -> Result<(), String> {
+    if let Some(token) = state
+        .metrics_stream
+        .lock()
+        .map_err(|_| "Metrics stream state is poisoned".to_string())?
+        .take()
+    {
+        token.cancel();
+    }
+    Ok(())
+}
+
 #[tauri::command]
 async fn kill_process(state: State<'_, AppState>, pid: u32) -> Result<(), String> {
-    let mut monitor = state.system_monitor.write().await;
+    state.ops.refuse_in_inspector_mode()?;
+    let mut monitor = state.system_monitor.get().await.write().await;
     monitor.kill_process(pid)
 }
 
+#[tauri::command]
+async fn get_app_resource_usage(
+    state: State<'_, AppState>,
+) -> Result<Vec<AppResourceUsage>, String> {
+    let mut monitor = state.system_monitor.get().await.write().await;
+    Ok(monitor.get_app_resource_usage())
+}
+
+/// Bytes each process sent and received over a short sampling window,
+/// `window_secs` long (two seconds by default).
+#[tauri::command]
+async fn get_process_network_usage(
+    window_secs: Option<u64>,
+) -> Result<Vec<ProcessNetworkUsage>, String> {
+    let window = std::time::Duration::from_secs(window_secs.unwrap_or(2));
+    tokio::task::spawn_blocking(move || network_usage::sample_process_network_usage(window))
+        .await
+        .map_err(|e| format!("Network usage task failed: {}", e))?
+}
+
+#[tauri::command]
+async fn suspend_process(state: State<'_, AppState>, pid: u32) -> Result<(), String> {
+    state.ops.refuse_in_inspector_mode()?;
+    let mut monitor = state.system_monitor.get().await.write().await;
+    monitor.suspend_process(pid)
+}
+
+#[tauri::command]
+async fn resume_process(state: State<'_, AppState>, pid: u32) -> Result<(), String> {
+    state.ops.refuse_in_inspector_mode()?;
+    let mut monitor = state.system_monitor.get().await.write().await;
+    monitor.resume_process(pid)
+}
+
+#[tauri::command]
+async fn set_process_priority(
+    state: State<'_, AppState>,
+    pid: u32,
+    nice: i32,
+) -> Result<(), String> {
+    state.ops.refuse_in_inspector_mode()?;
+    let mut monitor = state.system_monitor.get().await.write().await;
+    monitor.set_process_priority(pid, nice)
+}
+
+/// Process detail with threads, energy impact and open files, sampled
+/// after the monitor lock is released.
+#[tauri::command]
+async fn get_process_detail(state: State<'_, AppState>, pid: u32) -> Result<ProcessDetail, String> {
+    let detail = state
+        .system_monitor
+        .get()
+        .await
+        .write()
+        .await
+        .get_process_detail(pid)?;
+    tokio::task::spawn_blocking(move || detail.with_activity())
+        .await
+        .map_err(|e| format!("Process detail task failed: {}", e))
+}
+
+/// `incremental` reuses the previous scan's results outside the folders
+/// that changed since.
 #[tauri::command]
 async fn scan_cleanable_files(
     app_handle: tauri::AppHandle,
     state: State<'_, AppState>,
+    incremental: Option<bool>,
 ) -> Result<CleaningReport, String> {
-    let (operation_id, token) = state.ops.register(OperationKind::FileScan, true);
+    let (operation_id, token) = state.ops.register(OperationKind::FileScan, true)?;
     let start_time = std::time::Instant::now();
 
     // Emit start event
@@ -215,90 +630,79 @@ async fn scan_cleanable_files(
 
     let mut cleaner = state.file_cleaner.write().await;
 
-    app_handle
-        .emit(
-            "progress:update",
-            ProgressEvent {
-                operation_id: operation_id.clone(),
-                progress: 10.0,
-                message: "Starting file system scan...".to_string(),
-                stage: "initialization".to_string(),
-                can_cancel: true,
-                eta_ms: None,
-                throughput: None,
-            },
-        )
-        .ok();
-
-    tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
-
-    app_handle
-        .emit(
-            "progress:update",
-            ProgressEvent {
-                operation_id: operation_id.clone(),
-                progress: 25.0,
-                message: "Scanning caches directory...".to_string(),
-                stage: "caches".to_string(),
-                can_cancel: true,
-                eta_ms: None,
-                throughput: None,
-            },
-        )
-        .ok();
-
-    app_handle
-        .emit(
-            "progress:update",
-            ProgressEvent {
-                operation_id: operation_id.clone(),
-                progress: 50.0,
-                message: "Scanning temporary files...".to_string(),
-                stage: "temp_files".to_string(),
-                can_cancel: true,
-                eta_ms: None,
-                throughput: None,
-            },
-        )
-        .ok();
+    emit_progress(
+        &app_handle,
+        &state.ops,
+        ProgressEvent {
+            operation_id: operation_id.clone(),
+            progress: 0.0,
+            message: "Starting file system scan...".to_string(),
+            stage: "initialization".to_string(),
+            can_cancel: true,
+            eta_ms: None,
+            throughput: None,
+        },
+    );
 
-    app_handle
-        .emit(
-            "progress:update",
+    // Concurrency: limit scans
+    let _permit = state.ops.scan_sem.acquire().await;
+    state.ops.checkpoint(&operation_id, "discovery");
+    let op_id = operation_id.clone();
+    let app_for_cb = app_handle.clone();
+    let ops_for_cb = state.ops.clone();
+    let progress_cb = move |update: ScanProgress| {
+        let fraction = update.paths_done as f32 / update.paths_total.max(1) as f32;
+        emit_progress(
+            &app_for_cb,
+            &ops_for_cb,
             ProgressEvent {
-                operation_id: operation_id.clone(),
-                progress: 75.0,
-                message: "Analyzing file safety...".to_string(),
-                stage: "analysis".to_string(),
+                operation_id: op_id.clone(),
+                // The rest is exclusions and building the report.
+                progress: fraction * 95.0,
+                message: format!(
+                    "Scanning {} ({} of {} locations, {} found)",
+                    update.category,
+                    formatting::format_count(update.paths_done as u64),
+                    formatting::format_count(update.paths_total as u64),
+                    formatting::format_size(update.bytes_found)
+                ),
+                stage: "discovery".to_string(),
                 can_cancel: true,
-                eta_ms: None,
-                throughput: None,
+                eta_ms: update.eta_ms,
+                throughput: (update.entries_per_s.is_some() || update.mb_per_s.is_some()).then(
+                    || Throughput {
+                        files_per_s: update.entries_per_s,
+                        mb_per_s: update.mb_per_s,
+                    },
+                ),
             },
-        )
-        .ok();
-
-    // Concurrency: limit scans
-    let _permit = state.ops.scan_sem.acquire().await;
-    let result = cleaner.scan_system_with_cancel(&token).await;
+        );
+    };
+    let result = if incremental.unwrap_or(false) {
+        cleaner.scan_incremental(&token, Some(&progress_cb)).await
+    } else {
+        cleaner
+            .scan_system_with_cancel(&token, Some(&progress_cb))
+            .await
+    };
 
     let duration = start_time.elapsed().as_millis() as u32;
 
     match &result {
         Ok(_) => {
-            app_handle
-                .emit(
-                    "progress:update",
-                    ProgressEvent {
-                        operation_id: operation_id.clone(),
-                        progress: 100.0,
-                        message: "File scan completed successfully".to_string(),
-                        stage: "complete".to_string(),
-                        can_cancel: false,
-                        eta_ms: Some(0),
-                        throughput: None,
-                    },
-                )
-                .ok();
+            emit_progress(
+                &app_handle,
+                &state.ops,
+                ProgressEvent {
+                    operation_id: operation_id.clone(),
+                    progress: 100.0,
+                    message: "File scan completed successfully".to_string(),
+                    stage: "complete".to_string(),
+                    can_cancel: false,
+                    eta_ms: Some(0),
+                    throughput: None,
+                },
+            );
 
             app_handle
                 .emit(
@@ -309,6 +713,7 @@ async fn scan_cleanable_files(
                         message: "File scan completed".to_string(),
                         duration,
                         canceled: Some(false),
+                        phase_timings: state.ops.phase_timings(&operation_id),
                     },
                 )
                 .ok();
@@ -325,6 +730,7 @@ async fn scan_cleanable_files(
                         message: format!("File scan failed: {}", err),
                         duration,
                         canceled: Some(canceled),
+                        phase_timings: state.ops.phase_timings(&operation_id),
                     },
                 )
                 .ok();
@@ -339,12 +745,101 @@ async fn scan_cleanable_files(
     result
 }
 
+fn system_load(metrics: &MetricsSnapshot) -> SystemLoad {
+    SystemLoad {
+        cpu_percent: metrics.cpu.value.as_ref().map(|cpu| cpu.total_usage),
+        memory_pressure_percent: metrics
+            .memory
+            .value
+            .as_ref()
+            .map(|memory| memory.pressure_percent),
+        disk_read_bytes_per_sec: metrics
+            .disk_io
+            .value
+            .as_ref()
+            .map(|io| io.read_bytes_per_sec),
+        disk_write_bytes_per_sec: metrics
+            .disk_io
+            .value
+            .as_ref()
+            .map(|io| io.write_bytes_per_sec),
+    }
+}
+
+/// Stop the background analysis of the files a scan deferred, if running.
+fn stop_safety_continuation(state: &AppState) {
+    if let Some(token) = state
+        .safety_continuation
+        .lock()
+        .ok()
+        .and_then(|mut running| running.take())
+    {
+        token.cancel();
+    }
+}
+
+/// Keep analyzing the files whose safety analysis the last scan cut short,
+/// a batch at a time, and push each batch as a `safety:updated` event so
+/// the results upgrade in place. Stops when none remain or the next scan starts.
+fn start_safety_continuation(app_handle: tauri::AppHandle, state: &AppState) {
+    let token = CancellationToken::new();
+    match state.safety_continuation.lock() {
+        Ok(mut running) => {
+            if let Some(previous) = running.replace(token.clone()) {
+                previous.cancel();
+            }
+        }
+        Err(_) => {
+            log::warn!("Safety continuation state is poisoned");
+            return;
+        }
+    }
+    tauri::async_runtime::spawn(async move {
+        let state = app_handle.state::<AppState>();
+        while !token.is_cancelled() {
+            let metrics = state.metrics_sampler.latest_snapshot().await;
+            let update = {
+                let mut cleaner = state.enhanced_file_cleaner.get().await.write().await;
+                if token.is_cancelled() {
+                    break;
+                }
+                cleaner.set_system_load(Some(system_load(&metrics)));
+                cleaner.analyze_deferred_safety(&token).await
+            };
+            match update {
+                Ok(update) => {
+                    let remaining = update.remaining;
+                    if !update.files.is_empty()
+                        && app_handle.emit("safety:updated", update).is_err()
+                    {
+                        break;
+                    }
+                    if remaining == 0 {
+                        break;
+                    }
+                }
+                Err(err) => {
+                    if !token.is_cancelled() {
+                        log::warn!("Deferred safety analysis stopped: {}", err);
+                    }
+                    break;
+                }
+            }
+            tokio::select! {
+                _ = token.cancelled() => break,
+                _ = tokio::time::sleep(DEFERRED_SAFETY_PAUSE) => {}
+            }
+        }
+    });
+}
+
 #[tauri::command]
 async fn scan_cleanable_files_enhanced(
     app_handle: tauri::AppHandle,
     state: State<'_, AppState>,
+    incremental: Option<bool>,
 ) -> Result<EnhancedCleaningReport, String> {
-    let (operation_id, token) = state.ops.register(OperationKind::FileScan, true);
+    let (operation_id, token) = state.ops.register(OperationKind::FileScan, true)?;
     let start_time = std::time::Instant::now();
 
     // Emit start event
@@ -361,30 +856,34 @@ async fn scan_cleanable_files_enhanced(
 
     // Concurrency: limit scans (same guard used by baseline scan)
     let _permit = state.ops.scan_sem.acquire().await;
-    let mut cleaner = state.enhanced_file_cleaner.write().await;
+    stop_safety_continuation(&state);
+    let metrics = state.metrics_sampler.latest_snapshot().await;
+    let mut cleaner = state.enhanced_file_cleaner.get().await.write().await;
+    cleaner.set_system_load(Some(system_load(&metrics)));
 
     // Progress updates for enhanced scan
-    app_handle
-        .emit(
-            "progress:update",
-            ProgressEvent {
-                operation_id: operation_id.clone(),
-                progress: 10.0,
-                message: "Starting enhanced file system scan with safety analysis...".to_string(),
-                stage: "initialization".to_string(),
-                can_cancel: true,
-                eta_ms: None,
-                throughput: None,
-            },
-        )
-        .ok();
+    emit_progress(
+        &app_handle,
+        &state.ops,
+        ProgressEvent {
+            operation_id: operation_id.clone(),
+            progress: 10.0,
+            message: "Starting enhanced file system scan with safety analysis...".to_string(),
+            stage: "initialization".to_string(),
+            can_cancel: true,
+            eta_ms: None,
+            throughput: None,
+        },
+    );
 
     // Perform the enhanced scan
     let op_id = operation_id.clone();
     let app_for_cb = app_handle.clone();
+    let ops_for_cb = state.ops.clone();
     let progress_cb = move |progress: f32, message: &str, stage: &str| {
-        let _ = app_for_cb.emit(
-            "progress:update",
+        emit_progress(
+            &app_for_cb,
+            &ops_for_cb,
             ProgressEvent {
                 operation_id: op_id.clone(),
                 progress,
@@ -396,28 +895,49 @@ async fn scan_cleanable_files_enhanced(
             },
         );
     };
-    let result = cleaner
-        .scan_system_enhanced_with_cancel(&token, Some(&progress_cb))
-        .await;
+    let result = if incremental.unwrap_or(false) {
+        cleaner
+            .scan_system_enhanced_incremental(&token, Some(&progress_cb))
+            .await
+    } else {
+        cleaner
+            .scan_system_enhanced_with_cancel(&token, Some(&progress_cb))
+            .await
+    };
 
     let duration = start_time.elapsed().as_millis() as u32;
 
+    if result.is_ok() && cleaner.deferred_safety_count() > 0 {
+        start_safety_continuation(app_handle.clone(), &state);
+    }
     match &result {
-        Ok(_) => {
-            app_handle
-                .emit(
-                    "progress:update",
-                    ProgressEvent {
-                        operation_id: operation_id.clone(),
-                        progress: 100.0,
-                        message: "Enhanced scan completed with safety analysis".to_string(),
-                        stage: "complete".to_string(),
-                        can_cancel: false,
-                        eta_ms: Some(0),
-                        throughput: None,
-                    },
-                )
-                .ok();
+        Ok(report) => {
+            *state.quick_clean_items.write().await = quick_clean::quick_clean_candidates(
+                &report.enhanced_files,
+                chrono::Utc::now().timestamp(),
+            );
+            record_usage(&state, UsageFeature::SystemScan).await;
+            record_scan_telemetry(&state, &cleaner).await;
+            let sample = CacheSample {
+                at: chrono::Utc::now().timestamp(),
+                bytes: health_score::cache_size(report.enhanced_files.iter().map(|f| &f.base)),
+            };
+            if let Err(err) = state.cache_sizes.write().await.record(sample) {
+                log::warn!("Failed to record cache size: {}", err);
+            }
+            emit_progress(
+                &app_handle,
+                &state.ops,
+                ProgressEvent {
+                    operation_id: operation_id.clone(),
+                    progress: 100.0,
+                    message: "Enhanced scan completed with safety analysis".to_string(),
+                    stage: "complete".to_string(),
+                    can_cancel: false,
+                    eta_ms: Some(0),
+                    throughput: None,
+                },
+            );
 
             app_handle
                 .emit(
@@ -428,6 +948,7 @@ async fn scan_cleanable_files_enhanced(
                         message: "Enhanced file scan completed".to_string(),
                         duration,
                         canceled: Some(false),
+                        phase_timings: state.ops.phase_timings(&operation_id),
                     },
                 )
                 .ok();
@@ -444,6 +965,7 @@ async fn scan_cleanable_files_enhanced(
                         message: format!("Enhanced scan failed: {}", err),
                         duration,
                         canceled: Some(canceled),
+                        phase_timings: state.ops.phase_timings(&operation_id),
                     },
                 )
                 .ok();
@@ -458,54 +980,347 @@ async fn scan_cleanable_files_enhanced(
     result
 }
 
+/// Rescan one category and merge it into the last enhanced scan, for when a
+/// category is toggled in the UI.
 #[tauri::command]
-async fn get_cleanable_files(state: State<'_, AppState>) -> Result<Vec<CleanableFile>, String> {
-    let cleaner = state.file_cleaner.read().await;
-    Ok(cleaner.get_cleanable_files().clone())
-}
-
-#[tauri::command]
-async fn get_auto_selectable_files(
-    state: State<'_, AppState>,
-) -> Result<Vec<CleanableFile>, String> {
-    let cleaner = state.file_cleaner.read().await;
-    Ok(cleaner.get_auto_selectable_files())
-}
-
-#[tauri::command]
-async fn get_files_by_safety(
-    state: State<'_, AppState>,
-    min_safety_score: u8,
-) -> Result<Vec<CleanableFile>, String> {
-    let cleaner = state.file_cleaner.read().await;
-    Ok(cleaner.get_files_by_safety(min_safety_score))
-}
-
-#[tauri::command]
-async fn clean_files_enhanced(
+async fn scan_category(
     app_handle: tauri::AppHandle,
     state: State<'_, AppState>,
-    file_paths: Vec<String>,
-    allow_low_safety: Option<bool>,
-) -> Result<CleaningResult, String> {
-    let (operation_id, token) = state.ops.register(OperationKind::FileClean, true);
+    category_name: String,
+) -> Result<EnhancedCleaningReport, String> {
+    let (operation_id, token) = state.ops.register(OperationKind::FileScan, true)?;
+    let start_time = std::time::Instant::now();
     app_handle
         .emit(
             "operation:start",
             OperationStartEvent {
                 operation_id: operation_id.clone(),
-                operation_type: "enhanced_file_clean".into(),
-                estimated_duration: None,
+                operation_type: "category_scan".to_string(),
+                estimated_duration: Some(3000),
             },
         )
         .ok();
 
-    let allow_low_safety = allow_low_safety.unwrap_or(false);
-
-    let ops_registry = state.ops.clone();
-    let op_id_for_cb = operation_id.clone();
+    let _permit = state.ops.scan_sem.acquire().await;
+    stop_safety_continuation(&state);
+    let mut cleaner = state.enhanced_file_cleaner.get().await.write().await;
+    let op_id = operation_id.clone();
     let app_for_cb = app_handle.clone();
-    let progress_cb = move |update: EnhancedDeletionProgress| {
+    let ops_for_cb = state.ops.clone();
+    let progress_cb = move |progress: f32, message: &str, stage: &str| {
+        emit_progress(
+            &app_for_cb,
+            &ops_for_cb,
+            ProgressEvent {
+                operation_id: op_id.clone(),
+                progress,
+                message: message.to_string(),
+                stage: stage.to_string(),
+                can_cancel: true,
+                eta_ms: None,
+                throughput: None,
+            },
+        );
+    };
+    let result = cleaner
+        .scan_category(&category_name, &token, Some(&progress_cb))
+        .await;
+
+    if result.is_ok() && cleaner.deferred_safety_count() > 0 {
+        start_safety_continuation(app_handle.clone(), &state);
+    }
+    if let Ok(report) = &result {
+        *state.quick_clean_items.write().await = quick_clean::quick_clean_candidates(
+            &report.enhanced_files,
+            chrono::Utc::now().timestamp(),
+        );
+    }
+    let canceled = matches!(&result, Err(err) if err.contains("cancelled"));
+    app_handle
+        .emit(
+            "operation:complete",
+            OperationCompleteEvent {
+                operation_id: operation_id.clone(),
+                success: result.is_ok(),
+                message: match &result {
+                    Ok(_) => format!("Rescanned {}", category_name),
+                    Err(err) => format!("Category scan failed: {}", err),
+                },
+                duration: start_time.elapsed().as_millis() as u32,
+                canceled: Some(canceled),
+                phase_timings: state.ops.phase_timings(&operation_id),
+            },
+        )
+        .ok();
+    match &result {
+        Ok(_) => state.ops.finish_success(&operation_id),
+        Err(_) if canceled => state.ops.finish_canceled(&operation_id),
+        Err(err) => state.ops.finish_failed(&operation_id, err),
+    }
+    result
+}
+
+/// Scan a folder the user picked, such as an external drive, with the rules
+/// and safety analysis of the system scan. Its results replace the last scan's.
+#[tauri::command]
+async fn scan_custom_path(
+    app_handle: tauri::AppHandle,
+    state: State<'_, AppState>,
+    path: String,
+    options: Option<CustomScanOptions>,
+) -> Result<EnhancedCleaningReport, String> {
+    let root = custom_path::resolve_scan_root(&path, dirs::home_dir().as_deref())?;
+    let options = options.unwrap_or_default();
+    let (operation_id, token) = state.ops.register(OperationKind::FileScan, true)?;
+    let start_time = std::time::Instant::now();
+    app_handle
+        .emit(
+            "operation:start",
+            OperationStartEvent {
+                operation_id: operation_id.clone(),
+                operation_type: "custom_path_scan".to_string(),
+                estimated_duration: None,
+            },
+        )
+        .ok();
+
+    let _permit = state.ops.scan_sem.acquire().await;
+    stop_safety_continuation(&state);
+    let metrics = state.metrics_sampler.latest_snapshot().await;
+    let mut cleaner = state.enhanced_file_cleaner.get().await.write().await;
+    cleaner.set_system_load(Some(system_load(&metrics)));
+    let op_id = operation_id.clone();
+    let app_for_cb = app_handle.clone();
+    let ops_for_cb = state.ops.clone();
+    let progress_cb = move |progress: f32, message: &str, stage: &str| {
+        emit_progress(
+            &app_for_cb,
+            &ops_for_cb,
+            ProgressEvent {
+                operation_id: op_id.clone(),
+                progress,
+                message: message.to_string(),
+                stage: stage.to_string(),
+                can_cancel: true,
+                eta_ms: None,
+                throughput: None,
+            },
+        );
+    };
+    let result = cleaner
+        .scan_custom_path(&root, &options, &token, Some(&progress_cb))
+        .await;
+    if result.is_ok() {
+        record_scan_telemetry(&state, &cleaner).await;
+    }
+
+    if result.is_ok() && cleaner.deferred_safety_count() > 0 {
+        start_safety_continuation(app_handle.clone(), &state);
+    }
+    let canceled = matches!(&result, Err(err) if err.contains("cancelled"));
+    app_handle
+        .emit(
+            "operation:complete",
+            OperationCompleteEvent {
+                operation_id: operation_id.clone(),
+                success: result.is_ok(),
+                message: match &result {
+                    Ok(report) => format!(
+                        "Scanned {}: {} in {} items",
+                        root.display(),
+                        formatting::format_size(report.base.total_size),
+                        formatting::format_count(report.base.files_count as u64)
+                    ),
+                    Err(err) => format!("Folder scan failed: {}", err),
+                },
+                duration: start_time.elapsed().as_millis() as u32,
+                canceled: Some(canceled),
+                phase_timings: state.ops.phase_timings(&operation_id),
+            },
+        )
+        .ok();
+    match &result {
+        Ok(_) => state.ops.finish_success(&operation_id),
+        Err(_) if canceled => state.ops.finish_canceled(&operation_id),
+        Err(err) => state.ops.finish_failed(&operation_id, err),
+    }
+    result
+}
+
+/// Remove only the pre-verified, highest-confidence items of the last scan
+/// (old Trash items, temporary files, QuickLook thumbnails), stopping after
+/// a fixed wall-clock budget. Items pass the pre-deletion validator first and
+/// all but old Trash items go to the Trash, undoable with `undo_last_clean`.
+#[tauri::command]
+async fn quick_clean(
+    app_handle: tauri::AppHandle,
+    state: State<'_, AppState>,
+) -> Result<QuickCleanResult, String> {
+    let items = state.quick_clean_items.read().await.clone();
+    if state.ops.inspector_mode() {
+        return Ok(QuickCleanResult::preview(&items));
+    }
+    let _instance_lock = instance_lock::acquire_destructive_lock("quick_clean")?;
+    let (operation_id, token) = state.ops.register(OperationKind::QuickClean, true)?;
+    let start_time = std::time::Instant::now();
+    app_handle
+        .emit(
+            "operation:start",
+            OperationStartEvent {
+                operation_id: operation_id.clone(),
+                operation_type: "quick_clean".into(),
+                estimated_duration: Some(QUICK_CLEAN_BUDGET.as_millis() as u32),
+            },
+        )
+        .ok();
+    let _permit = state.ops.quick_sem.acquire().await;
+
+    let result = state
+        .enhanced_file_cleaner
+        .get()
+        .await
+        .write()
+        .await
+        .quick_clean(items, QUICK_CLEAN_BUDGET, &token)
+        .await;
+    let canceled = match &result {
+        Ok(result) => result.canceled,
+        Err(err) => err == "cancelled",
+    };
+    let message = match &result {
+        Ok(result) if result.canceled => format!(
+            "Quick clean canceled: {} freed, {} items left",
+            formatting::format_size(result.freed),
+            formatting::format_count(result.remaining as u64)
+        ),
+        Ok(result) if result.timed_out => format!(
+            "Quick clean stopped at its time limit: {} freed, {} items left",
+            formatting::format_size(result.freed),
+            formatting::format_count(result.remaining as u64)
+        ),
+        Ok(result) => format!(
+            "Quick clean freed {} from {} items",
+            formatting::format_size(result.freed),
+            formatting::format_count(result.removed.len() as u64)
+        ),
+        Err(err) => format!("Quick clean failed: {}", err),
+    };
+    app_handle
+        .emit(
+            "operation:complete",
+            OperationCompleteEvent {
+                operation_id: operation_id.clone(),
+                success: result.is_ok() && !canceled,
+                message,
+                duration: start_time.elapsed().as_millis() as u32,
+                canceled: Some(canceled),
+                phase_timings: state.ops.phase_timings(&operation_id),
+            },
+        )
+        .ok();
+    if let Ok(result) = &result {
+        state
+            .session
+            .record_cleanup(result.freed, result.removed.len() as u64);
+        state.ops.record_outcome(
+            &operation_id,
+            OperationOutcome {
+                recovery_point_id: result.recovery_point_id.clone(),
+                ..OperationOutcome::new(result.removed.len() as u64, result.freed)
+                    .with_cleaned(result.removed.iter().cloned())
+                    .with_failures(result.failed.iter().map(failure_line))
+            },
+        );
+        let mut quick_clean_items = state.quick_clean_items.write().await;
+        record_cleaned_usage(
+            &state,
+            UsageFeature::QuickClean,
+            quick_clean_items
+                .iter()
+                .filter(|item| result.removed.contains(&item.path)),
+        )
+        .await;
+        quick_clean_items.retain(|item| !result.removed.contains(&item.path));
+    }
+    match &result {
+        Ok(_) if canceled => state.ops.finish_canceled(&operation_id),
+        Ok(_) => state.ops.finish_success(&operation_id),
+        Err(_) if canceled => state.ops.finish_canceled(&operation_id),
+        Err(err) => state.ops.finish_failed(&operation_id, err),
+    }
+    result
+}
+
+#[tauri::command]
+async fn get_cleanable_files(state: State<'_, AppState>) -> Result<Vec<CleanableFile>, String> {
+    let cleaner = state.file_cleaner.read().await;
+    Ok(cleaner.get_cleanable_files().clone())
+}
+
+#[tauri::command]
+async fn get_auto_selectable_files(
+    state: State<'_, AppState>,
+) -> Result<Vec<CleanableFile>, String> {
+    let cleaner = state.file_cleaner.read().await;
+    Ok(cleaner.get_auto_selectable_files())
+}
+
+#[tauri::command]
+async fn get_files_by_safety(
+    state: State<'_, AppState>,
+    min_safety_score: u8,
+) -> Result<Vec<CleanableFile>, String> {
+    let cleaner = state.file_cleaner.read().await;
+    Ok(cleaner.get_files_by_safety(min_safety_score))
+}
+
+/// The layered rationale behind a candidate's safety score and selection,
+/// from the rule check through the policy gates.
+#[tauri::command]
+async fn explain_file_safety(
+    state: State<'_, AppState>,
+    path: String,
+) -> Result<SafetyExplanation, String> {
+    let cleaner = state.enhanced_file_cleaner.get().await.read().await;
+    cleaner.explain_file_safety(&path).await
+}
+
+#[tauri::command]
+async fn clean_files_enhanced(
+    app_handle: tauri::AppHandle,
+    state: State<'_, AppState>,
+    file_paths: Vec<String>,
+    allow_low_safety: Option<bool>,
+) -> Result<CleaningResult, String> {
+    if state.ops.inspector_mode() {
+        let cleaner = state.enhanced_file_cleaner.get().await.read().await;
+        return Ok(CleaningResult::preview(
+            &cleaner.scanned_files(),
+            &file_paths,
+        ));
+    }
+    let _instance_lock = instance_lock::acquire_destructive_lock("clean_files_enhanced")?;
+    let (operation_id, token) = state.ops.register(OperationKind::FileClean, true)?;
+    app_handle
+        .emit(
+            "operation:start",
+            OperationStartEvent {
+                operation_id: operation_id.clone(),
+                operation_type: "enhanced_file_clean".into(),
+                estimated_duration: None,
+            },
+        )
+        .ok();
+
+    let allow_low_safety = match allow_low_safety {
+        Some(allow) => allow,
+        None => state.settings.read().await.settings().risky_mode,
+    };
+
+    let ops_registry = state.ops.clone();
+    let op_id_for_cb = operation_id.clone();
+    let app_for_cb = app_handle.clone();
+    let progress_cb = move |update: EnhancedDeletionProgress| {
         ops_registry.update(op_id_for_cb.as_str(), |state| {
             state.progress = update.progress;
             state.stage = update.stage.to_string();
@@ -522,8 +1337,9 @@ async fn clean_files_enhanced(
             None
         };
 
-        let _ = app_for_cb.emit(
-            "progress:update",
+        emit_progress(
+            &app_for_cb,
+            &ops_registry,
             ProgressEvent {
                 operation_id: op_id_for_cb.clone(),
                 progress: update.progress,
@@ -536,7 +1352,7 @@ async fn clean_files_enhanced(
         );
     };
 
-    let mut cleaner = state.enhanced_file_cleaner.write().await;
+    let mut cleaner = state.enhanced_file_cleaner.get().await.write().await;
 
     // Use enhanced cleaning with validation and recovery
     let result = cleaner
@@ -551,9 +1367,9 @@ async fn clean_files_enhanced(
     match &result {
         Ok(cleaning_result) => {
             let mut completion_message = format!(
-                "Enhanced cleaning completed: {} files deleted, {} MB freed",
-                cleaning_result.deleted_count,
-                cleaning_result.total_freed / (1024 * 1024)
+                "Enhanced cleaning completed: {} files deleted, {} freed",
+                formatting::format_count(cleaning_result.deleted_count as u64),
+                formatting::format_size(cleaning_result.total_freed)
             );
             if cleaning_result.failed_count > 0 {
                 completion_message.push_str(&format!(
@@ -576,9 +1392,26 @@ async fn clean_files_enhanced(
                         message: completion_message,
                         duration: 0,
                         canceled: Some(false),
+                        phase_timings: state.ops.phase_timings(&operation_id),
                     },
                 )
                 .ok();
+            state.session.record_cleanup(
+                cleaning_result.total_freed,
+                cleaning_result.deleted_count as u64,
+            );
+            state
+                .ops
+                .record_outcome(&operation_id, cleaning_outcome(cleaning_result));
+            let scanned = cleaner.scanned_files();
+            record_cleaned_usage(
+                &state,
+                UsageFeature::FileClean,
+                scanned
+                    .iter()
+                    .filter(|file| cleaning_result.deleted_files.contains(&file.path)),
+            )
+            .await;
             state.ops.finish_success(&operation_id);
         }
         Err(err) => {
@@ -592,6 +1425,7 @@ async fn clean_files_enhanced(
                         message: format!("Enhanced cleaning failed: {}", err),
                         duration: 0,
                         canceled: Some(canceled),
+                        phase_timings: state.ops.phase_timings(&operation_id),
                     },
                 )
                 .ok();
@@ -611,7 +1445,7 @@ async fn prepare_deletion_enhanced(
     state: State<'_, AppState>,
     file_paths: Vec<String>,
 ) -> Result<DeletionPreparation, String> {
-    let mut cleaner = state.enhanced_file_cleaner.write().await;
+    let mut cleaner = state.enhanced_file_cleaner.get().await.write().await;
     cleaner.prepare_deletion_by_paths(&file_paths).await
 }
 
@@ -621,7 +1455,7 @@ async fn record_user_feedback(
     file_path: String,
     action: String,
 ) -> Result<(), String> {
-    let mut cleaner = state.enhanced_file_cleaner.write().await;
+    let mut cleaner = state.enhanced_file_cleaner.get().await.write().await;
 
     let user_action = match action.as_str() {
         "selected" => UserAction::Selected,
@@ -630,15 +1464,49 @@ async fn record_user_feedback(
         _ => return Err("Invalid action".to_string()),
     };
 
+    if matches!(user_action, UserAction::Deselected) {
+        let now = chrono::Utc::now().timestamp();
+        if let Err(err) = state.telemetry.write().await.record_deselection(now) {
+            log::warn!("Failed to record telemetry: {}", err);
+        }
+    }
     cleaner.record_user_feedback(&file_path, user_action);
     Ok(())
 }
 
+/// Path shapes learned from repeated deselections of auto-selected items.
+/// Accepted shapes lower auto-select confidence in later scans.
+#[tauri::command]
+async fn review_learned_exceptions(
+    state: State<'_, AppState>,
+    decisions: Option<Vec<ExceptionDecision>>,
+) -> Result<Vec<LearnedException>, String> {
+    state
+        .enhanced_file_cleaner
+        .get()
+        .await
+        .write()
+        .await
+        .review_learned_exceptions(&decisions.unwrap_or_default())
+}
+
+/// Forget the selection patterns learned from user feedback.
+#[tauri::command]
+async fn reset_learning_data(state: State<'_, AppState>) -> Result<(), String> {
+    state
+        .enhanced_file_cleaner
+        .get()
+        .await
+        .write()
+        .await
+        .reset_learning_data()
+}
+
 #[tauri::command]
 async fn get_active_development_tools(_state: State<'_, AppState>) -> Result<Vec<String>, String> {
     // This provides information about active development tools
     let checker = AppActivityChecker::new();
-    Ok(checker.get_active_development_tools())
+    Ok(checker.get_active_development_tools(&ProcessSnapshot::capture().await))
 }
 
 #[tauri::command]
@@ -646,7 +1514,7 @@ async fn preview_rules() -> Result<(Vec<RuleConflict>, DryRunReport), String> {
     let base = load_rules_result()?;
     let dyn_eng = DynamicRuleEngine::new();
     let mut adapted = dyn_eng.adapt_rules_to_system(&base);
-    let mut extra = dyn_eng.generate_app_specific_rules();
+    let mut extra = dyn_eng.generate_app_specific_rules(&ProcessSnapshot::capture().await);
     adapted.categories.append(&mut extra);
     let validator = RuleValidator::new();
     let conflicts = validator.validate_rule_consistency(&adapted);
@@ -655,45 +1523,322 @@ async fn preview_rules() -> Result<(Vec<RuleConflict>, DryRunReport), String> {
 }
 
 #[tauri::command]
-async fn get_enhanced_telemetry(state: State<'_, AppState>) -> Result<TelemetrySnapshot, String> {
-    let cleaner = state.enhanced_file_cleaner.read().await;
-    Ok(cleaner.telemetry_snapshot())
+async fn list_exclusions(state: State<'_, AppState>) -> Result<Vec<Exclusion>, String> {
+    Ok(state.exclusions.read().await.list())
 }
 
 #[tauri::command]
-async fn clean_files(
+async fn add_exclusion(
+    state: State<'_, AppState>,
+    exclusion: NewExclusion,
+) -> Result<Exclusion, String> {
+    state.exclusions.write().await.add(exclusion)
+}
+
+#[tauri::command]
+async fn remove_exclusion(state: State<'_, AppState>, exclusion_id: String) -> Result<(), String> {
+    if state.exclusions.write().await.remove(&exclusion_id)? {
+        Ok(())
+    } else {
+        Err("Unknown exclusion".into())
+    }
+}
+
+#[tauri::command]
+async fn list_age_thresholds(state: State<'_, AppState>) -> Result<Vec<AgeThreshold>, String> {
+    Ok(state.age_thresholds.read().await.list())
+}
+
+/// Override a category's minimum age in days; `None` restores the rule default.
+#[tauri::command]
+async fn set_age_threshold(
+    state: State<'_, AppState>,
+    category: String,
+    min_age_days: Option<i64>,
+) -> Result<(), String> {
+    let known = load_rules_result()?
+        .categories
+        .iter()
+        .any(|rule| rule.name.eq_ignore_ascii_case(category.trim()));
+    if !known {
+        return Err(format!("Unknown category: {}", category));
+    }
+    state
+        .age_thresholds
+        .write()
+        .await
+        .set(&category, min_age_days)
+}
+
+#[tauri::command]
+async fn get_safety_policies(
+    state: State<'_, AppState>,
+) -> Result<Vec<CategorySafetyPolicy>, String> {
+    let categories: Vec<String> = load_rules_result()?
+        .categories
+        .into_iter()
+        .map(|rule| rule.name)
+        .collect();
+    Ok(state.safety_policies.read().await.list(&categories))
+}
+
+/// Override a category's auto-select and direct-delete thresholds; `None`
+/// restores the built-in policy. Applies from the next scan.
+#[tauri::command]
+async fn update_safety_policy(
+    state: State<'_, AppState>,
+    category: String,
+    thresholds: Option<SafetyThresholds>,
+) -> Result<(), String> {
+    let known = load_rules_result()?
+        .categories
+        .iter()
+        .any(|rule| rule.name.eq_ignore_ascii_case(category.trim()));
+    if !known {
+        return Err(format!("Unknown category: {}", category));
+    }
+    state
+        .safety_policies
+        .write()
+        .await
+        .update(&category, thresholds)
+}
+
+#[tauri::command]
+async fn get_performance_profile(
+    state: State<'_, AppState>,
+) -> Result<PerformanceSettings, String> {
+    Ok(state.performance_profile.read().await.profile().settings())
+}
+
+/// Switch presets; operations already running keep their permits and
+/// threads, the next ones use the new limits.
+#[tauri::command]
+async fn set_performance_profile(
     app_handle: tauri::AppHandle,
     state: State<'_, AppState>,
-    file_paths: Vec<String>,
-) -> Result<(u64, usize), String> {
-    let (operation_id, token) = state.ops.register(OperationKind::FileClean, true);
-    let start_time = std::time::Instant::now();
-    app_handle
-        .emit(
-            "operation:start",
-            OperationStartEvent {
-                operation_id: operation_id.clone(),
-                operation_type: "file_clean".into(),
-                estimated_duration: None,
-            },
-        )
-        .ok();
+    profile: PerformanceProfile,
+) -> Result<PerformanceSettings, String> {
+    let settings = apply_performance_profile(&state, profile).await?;
+    emit_settings_changed(&app_handle, &state).await;
+    Ok(settings)
+}
 
-    // Concurrency control
-    let _permit = state.ops.clean_sem.acquire().await;
+async fn apply_performance_profile(
+    state: &AppState,
+    profile: PerformanceProfile,
+) -> Result<PerformanceSettings, String> {
+    let settings = profile.settings();
+    performance::apply(&settings);
+    state.ops.resize_permits(
+        settings.scan_permits,
+        settings.clean_permits,
+        settings.opt_permits,
+    );
+    state.performance_profile.write().await.set(profile)?;
+    Ok(settings)
+}
 
-    // Pre-compute totals for ETA/throughput
-    use std::fs;
-    let total_files = file_paths.len() as u64;
-    let mut total_bytes: u64 = 0;
-    for p in &file_paths {
-        if let Ok(md) = fs::metadata(p) {
-            total_bytes = total_bytes.saturating_add(md.len());
-        }
+/// The settings, with the concurrency profile and telemetry opt-in read
+/// from the subsystems that own them.
+async fn current_settings(state: &AppState) -> AppSettings {
+    AppSettings {
+        telemetry_opt_in: state.usage.read().await.opted_in(),
+        concurrency_profile: state.performance_profile.read().await.profile(),
+        stored: state.settings.read().await.settings(),
     }
-    let mut files_done = 0u64;
-    let mut bytes_done = 0u64;
-    let mut tracker = ThroughputTracker::default();
+}
+
+async fn emit_settings_changed(app_handle: &tauri::AppHandle, state: &AppState) {
+    app_handle
+        .emit("settings:changed", current_settings(state).await)
+        .ok();
+}
+
+#[tauri::command]
+async fn get_settings(state: State<'_, AppState>) -> Result<AppSettings, String> {
+    Ok(current_settings(&state).await)
+}
+
+/// Apply the settings that changed, then save the rest and emit
+/// `settings:changed`.
+#[tauri::command]
+async fn update_settings(
+    app_handle: tauri::AppHandle,
+    state: State<'_, AppState>,
+    settings: AppSettings,
+) -> Result<AppSettings, String> {
+    settings.stored.validate()?;
+    let previous = current_settings(&state).await;
+    if settings.concurrency_profile != previous.concurrency_profile {
+        apply_performance_profile(&state, settings.concurrency_profile).await?;
+    }
+    if settings.telemetry_opt_in != previous.telemetry_opt_in {
+        set_telemetry_opt_in(&state, settings.telemetry_opt_in).await?;
+    }
+    if settings.stored.parallel_scan != previous.stored.parallel_scan {
+        performance::set_parallel_scan(settings.stored.parallel_scan);
+    }
+    if settings.stored.spotlight_discovery != previous.stored.spotlight_discovery {
+        set_spotlight_discovery(settings.stored.spotlight_discovery);
+    }
+    if settings.stored.size_units != previous.stored.size_units {
+        formatting::set_units(settings.stored.size_units);
+    }
+    state.settings.write().await.update(settings.stored)?;
+    emit_settings_changed(&app_handle, &state).await;
+    Ok(current_settings(&state).await)
+}
+
+#[tauri::command]
+async fn list_protection_packs(state: State<'_, AppState>) -> Result<Vec<ProtectionPack>, String> {
+    Ok(state.protection_packs.read().await.list())
+}
+
+/// Treat a pack's locations as sensitive from the next scan on.
+#[tauri::command]
+async fn enable_protection_pack(state: State<'_, AppState>, id: String) -> Result<(), String> {
+    state.protection_packs.write().await.enable(&id)
+}
+
+#[tauri::command]
+async fn disable_protection_pack(state: State<'_, AppState>, id: String) -> Result<(), String> {
+    state.protection_packs.write().await.disable(&id)
+}
+
+#[tauri::command]
+async fn list_custom_rules(state: State<'_, AppState>) -> Result<Vec<CustomRule>, String> {
+    Ok(state.custom_rules.read().await.list())
+}
+
+#[tauri::command]
+async fn add_custom_rule(
+    state: State<'_, AppState>,
+    rule: NewCustomRule,
+) -> Result<CustomRule, String> {
+    state.custom_rules.write().await.add(rule)
+}
+
+#[tauri::command]
+async fn update_custom_rule(
+    state: State<'_, AppState>,
+    rule_id: String,
+    rule: NewCustomRule,
+) -> Result<CustomRule, String> {
+    state.custom_rules.write().await.update(&rule_id, rule)
+}
+
+#[tauri::command]
+async fn remove_custom_rule(state: State<'_, AppState>, rule_id: String) -> Result<(), String> {
+    if state.custom_rules.write().await.remove(&rule_id)? {
+        Ok(())
+    } else {
+        Err("Unknown custom rule".into())
+    }
+}
+
+#[tauri::command]
+async fn list_rule_packs(state: State<'_, AppState>) -> Result<Vec<RulePack>, String> {
+    Ok(state.rule_packs.read().await.list())
+}
+
+/// Read the rule packs directory again and recheck every pack, e.g. after
+/// one was added or edited.
+#[tauri::command]
+async fn reload_rule_packs(state: State<'_, AppState>) -> Result<Vec<RulePack>, String> {
+    let mut packs = state.rule_packs.write().await;
+    packs.reload();
+    Ok(packs.list())
+}
+
+/// Enable or disable a pack; returns the packs as rechecked with the change.
+#[tauri::command]
+async fn set_rule_pack_enabled(
+    state: State<'_, AppState>,
+    pack_id: String,
+    enabled: bool,
+) -> Result<Vec<RulePack>, String> {
+    let mut packs = state.rule_packs.write().await;
+    packs.set_enabled(&pack_id, enabled)?;
+    packs.reload();
+    Ok(packs.list())
+}
+
+/// What the enhanced report changed since `since_revision`, for views that
+/// update in place instead of reloading the whole report.
+#[tauri::command]
+async fn get_report_delta(
+    state: State<'_, AppState>,
+    since_revision: u64,
+) -> Result<ReportDelta, String> {
+    let cleaner = state.enhanced_file_cleaner.get().await.read().await;
+    Ok(cleaner.report_delta(since_revision))
+}
+
+#[tauri::command]
+async fn get_enhanced_telemetry(state: State<'_, AppState>) -> Result<TelemetrySnapshot, String> {
+    let cleaner = state.enhanced_file_cleaner.get().await.read().await;
+    let mut snapshot = cleaner.telemetry_snapshot();
+    snapshot.subsystem_init = state.init_timings.list();
+    Ok(snapshot)
+}
+
+/// Build the lazily initialized subsystems ahead of first use; the UI calls
+/// this after its first paint. Returns every initialization timed so far.
+#[tauri::command]
+async fn warmup(state: State<'_, AppState>) -> Result<Vec<InitTiming>, String> {
+    tokio::join!(
+        state.system_monitor.get(),
+        state.enhanced_file_cleaner.get()
+    );
+    Ok(state.init_timings.list())
+}
+
+#[tauri::command]
+async fn clean_files(
+    app_handle: tauri::AppHandle,
+    state: State<'_, AppState>,
+    file_paths: Vec<String>,
+) -> Result<(u64, usize), String> {
+    if state.ops.inspector_mode() {
+        let cleaner = state.file_cleaner.read().await;
+        let selected = cleaner
+            .get_cleanable_files()
+            .iter()
+            .filter(|file| file_paths.contains(&file.path));
+        return Ok(selected.fold((0, 0), |(freed, count), file| {
+            (freed + file.size, count + 1)
+        }));
+    }
+    let _instance_lock = instance_lock::acquire_destructive_lock("clean_files")?;
+    let (operation_id, token) = state.ops.register(OperationKind::FileClean, true)?;
+    let start_time = std::time::Instant::now();
+    app_handle
+        .emit(
+            "operation:start",
+            OperationStartEvent {
+                operation_id: operation_id.clone(),
+                operation_type: "file_clean".into(),
+                estimated_duration: None,
+            },
+        )
+        .ok();
+
+    // Concurrency control
+    let _permit = state.ops.clean_sem.acquire().await;
+
+    // Pre-compute totals for ETA/throughput
+    use std::fs;
+    let total_files = file_paths.len() as u64;
+    let mut total_bytes: u64 = 0;
+    for p in &file_paths {
+        if let Ok(md) = fs::metadata(p) {
+            total_bytes = total_bytes.saturating_add(md.len());
+        }
+    }
+    let mut files_done = 0u64;
+    let mut bytes_done = 0u64;
+    let mut tracker = ThroughputTracker::default();
 
     let chunk_size = 50usize;
     let mut total_freed = 0u64;
@@ -720,6 +1865,7 @@ async fn clean_files(
                             message: format!("Cleaning failed: {}", err),
                             duration,
                             canceled: Some(canceled),
+                            phase_timings: state.ops.phase_timings(&operation_id),
                         },
                     )
                     .ok();
@@ -741,23 +1887,27 @@ async fn clean_files(
         } else {
             100.0
         };
-        app_handle
-            .emit(
-                "progress:update",
-                ProgressEvent {
-                    operation_id: operation_id.clone(),
-                    progress,
-                    message: format!("Cleaning files… {}/{}", files_done, total_files),
-                    stage: "deleting".into(),
-                    can_cancel: true,
-                    eta_ms,
-                    throughput: Some(Throughput {
-                        files_per_s: fps,
-                        mb_per_s: mbs,
-                    }),
-                },
-            )
-            .ok();
+        emit_progress(
+            &app_handle,
+            &state.ops,
+            ProgressEvent {
+                operation_id: operation_id.clone(),
+                progress,
+                message: format!(
+                    "Cleaning files… {}/{} ({})",
+                    formatting::format_count(files_done),
+                    formatting::format_count(total_files),
+                    formatting::format_size(bytes_done)
+                ),
+                stage: "deleting".into(),
+                can_cancel: true,
+                eta_ms,
+                throughput: Some(Throughput {
+                    files_per_s: fps,
+                    mb_per_s: mbs,
+                }),
+            },
+        );
     }
 
     let canceled = token.is_cancelled();
@@ -775,9 +1925,17 @@ async fn clean_files(
                 },
                 duration,
                 canceled: Some(canceled),
+                phase_timings: state.ops.phase_timings(&operation_id),
             },
         )
         .ok();
+    state
+        .session
+        .record_cleanup(total_freed, total_removed as u64);
+    state.ops.record_outcome(
+        &operation_id,
+        OperationOutcome::new(total_removed as u64, total_freed),
+    );
     if canceled {
         state.ops.finish_canceled(&operation_id);
     } else {
@@ -786,12 +1944,42 @@ async fn clean_files(
     Ok((total_freed, total_removed))
 }
 
+#[tauri::command]
+async fn get_trash_summary(state: State<'_, AppState>) -> Result<TrashSummary, String> {
+    state.file_cleaner.read().await.trash_summary().await
+}
+
+/// Empty the home Trash, plus the Trash of each volume in `volumes` (mount
+/// points from `get_trash_summary`).
 #[tauri::command]
 async fn empty_trash(
     app_handle: tauri::AppHandle,
     state: State<'_, AppState>,
-) -> Result<(u64, usize), String> {
-    let (operation_id, token) = state.ops.register(OperationKind::EmptyTrash, true);
+    volumes: Option<Vec<String>>,
+) -> Result<TrashEmptyResult, String> {
+    let volumes = volumes.unwrap_or_default();
+    if state.ops.inspector_mode() {
+        let summary = state.file_cleaner.read().await.trash_summary().await?;
+        let included: Vec<&TrashVolume> = summary
+            .volumes
+            .iter()
+            .filter(|trash| trash.volume == "/" || volumes.contains(&trash.volume))
+            .collect();
+        let total_items = included.iter().map(|trash| trash.items).sum();
+        let total_bytes = included.iter().map(|trash| trash.size).sum();
+        return Ok(TrashEmptyResult {
+            freed: total_bytes,
+            removed: total_items,
+            total_items,
+            total_bytes,
+            dry_run: true,
+            volumes: included.iter().map(|trash| trash.volume.clone()).collect(),
+            ..Default::default()
+        });
+    }
+    let _instance_lock = instance_lock::acquire_destructive_lock("empty_trash")?;
+    let (operation_id, token) = state.ops.register(OperationKind::EmptyTrash, true)?;
+    let start_time = std::time::Instant::now();
     app_handle
         .emit(
             "operation:start",
@@ -803,79 +1991,282 @@ async fn empty_trash(
         )
         .ok();
     let _permit = state.ops.clean_sem.acquire().await;
+
+    let ops_registry = state.ops.clone();
+    let op_id_for_cb = operation_id.clone();
+    let app_for_cb = app_handle.clone();
+    let progress_cb = move |update: TrashEmptyProgress| {
+        let progress = if update.items_total > 0 {
+            (update.items_done as f32 / update.items_total as f32) * 100.0
+        } else {
+            100.0
+        };
+        let fmt = formatting::current();
+        // The home Trash and each volume's Trash are separate stages.
+        let (stage, place) = if update.volume == "/" {
+            ("deleting", String::new())
+        } else {
+            ("volume_trash", format!(" on {}", update.volume))
+        };
+        let mut message = format!(
+            "Emptying Trash{}… {}/{} items",
+            place,
+            fmt.count(update.items_done as u64),
+            fmt.count(update.items_total as u64)
+        );
+        if let Some(eta_ms) = update.eta_ms {
+            message.push_str(&format!(", about {} left", fmt.eta(u64::from(eta_ms))));
+        }
+        ops_registry.update(op_id_for_cb.as_str(), |state| {
+            state.progress = progress;
+            state.stage = stage.into();
+            state.details = Some(message.clone());
+            state.eta_ms = update.eta_ms;
+        });
+        emit_progress(
+            &app_for_cb,
+            &ops_registry,
+            ProgressEvent {
+                operation_id: op_id_for_cb.clone(),
+                progress,
+                message,
+                stage: stage.into(),
+                can_cancel: true,
+                eta_ms: update.eta_ms,
+                throughput: Some(Throughput {
+                    files_per_s: update.items_per_s,
+                    mb_per_s: update.mb_per_s,
+                }),
+            },
+        );
+    };
+
     let cleaner = state.file_cleaner.read().await;
-    let res = cleaner.empty_trash_with_cancel(&token).await;
-    let canceled = token.is_cancelled();
+    let res = cleaner
+        .empty_trash_volumes(&volumes, &token, Some(&progress_cb))
+        .await;
+    let canceled = res.as_ref().map(|r| r.canceled).unwrap_or(false);
+    let message = match &res {
+        Ok(result) if result.canceled => format!(
+            "Trash empty canceled after removing {} of {} items ({} freed)",
+            formatting::format_count(result.removed as u64),
+            formatting::format_count(result.total_items as u64),
+            formatting::format_size(result.freed)
+        ),
+        Ok(result) if result.emptied_by_finder => "Trash emptied by Finder".to_string(),
+        Ok(result) if result.failed > 0 => format!(
+            "Trash emptied: {} items removed ({} freed), {} could not be removed",
+            formatting::format_count(result.removed as u64),
+            formatting::format_size(result.freed),
+            formatting::format_count(result.failed as u64)
+        ),
+        Ok(result) => format!(
+            "Trash emptied: {} items removed ({} freed)",
+            formatting::format_count(result.removed as u64),
+            formatting::format_size(result.freed)
+        ),
+        Err(err) => format!("Emptying Trash failed: {}", err),
+    };
     app_handle
         .emit(
             "operation:complete",
             OperationCompleteEvent {
                 operation_id: operation_id.clone(),
                 success: res.is_ok() && !canceled,
-                message: if canceled {
-                    "Trash empty canceled".into()
-                } else {
-                    "Trash emptied".into()
-                },
-                duration: 0,
+                message,
+                duration: start_time.elapsed().as_millis() as u32,
                 canceled: Some(canceled),
+                phase_timings: state.ops.phase_timings(&operation_id),
             },
         )
         .ok();
-    if canceled {
-        state.ops.finish_canceled(&operation_id);
-    } else if res.is_ok() {
-        state.ops.finish_success(&operation_id);
-    } else {
-        state.ops.finish_failed(&operation_id, "empty_trash failed");
+    if let Ok(result) = &res {
+        state
+            .session
+            .record_cleanup(result.freed, result.removed as u64);
+        state.ops.record_outcome(
+            &operation_id,
+            OperationOutcome::new(result.removed as u64, result.freed),
+        );
+        record_usage(&state, UsageFeature::EmptyTrash).await;
+    }
+    match &res {
+        Ok(_) if canceled => state.ops.finish_canceled(&operation_id),
+        Ok(_) => state.ops.finish_success(&operation_id),
+        Err(err) => state.ops.finish_failed(&operation_id, err),
     }
     res
 }
 
+/// Move items in the Trash, by name, back to the folders they were trashed
+/// from. Items with no record of their origin go to `destination` when one
+/// is given and are otherwise reported in `needs_destination`.
 #[tauri::command]
 async fn restore_from_trash(
-    _app_handle: tauri::AppHandle,
-    _state: State<'_, AppState>,
+    state: State<'_, AppState>,
     file_names: Vec<String>,
-) -> Result<usize, String> {
-    // Naive restore: move files from ~/.Trash back to user's Downloads directory
-    // This can be enhanced later to track original locations from recovery metadata
-    let home = dirs::home_dir().ok_or_else(|| "Could not find home directory".to_string())?;
-    let trash = home.join(".Trash");
-    let downloads = home.join("Downloads");
-    let mut restored = 0usize;
-
-    for name in file_names {
-        let src = trash.join(&name);
-        if !src.exists() {
-            continue;
-        }
-        let mut target = downloads.join(&name);
-        // Ensure unique name
-        let mut counter = 1u32;
-        while target.exists() {
-            let (base, ext) = {
-                if let Some(idx) = name.rfind('.') {
-                    let (b, e) = name.split_at(idx);
-                    (b.to_string(), e.trim_start_matches('.').to_string())
-                } else {
-                    (name.clone(), String::new())
-                }
-            };
-            let candidate = if ext.is_empty() {
-                format!("{} (restored-{})", base, counter)
-            } else {
-                format!("{} (restored-{}).{}", base, counter, ext)
-            };
-            target = downloads.join(candidate);
-            counter += 1;
+    destination: Option<String>,
+) -> Result<TrashRestoreResult, String> {
+    if state.ops.inspector_mode() {
+        return Err(INSPECTOR_MODE_REFUSAL.to_string());
+    }
+    let _instance_lock = instance_lock::acquire_destructive_lock("restore_from_trash")?;
+    state
+        .enhanced_file_cleaner
+        .get()
+        .await
+        .write()
+        .await
+        .restore_from_trash(
+            &file_names,
+            destination.as_deref().map(std::path::Path::new),
+        )
+        .await
+}
+
+#[tauri::command]
+async fn get_quarantine_settings(state: State<'_, AppState>) -> Result<QuarantineSettings, String> {
+    let cleaner = state.enhanced_file_cleaner.get().await.read().await;
+    Ok(cleaner.quarantine_settings())
+}
+
+/// Turn quarantine on or off and set how many days items are kept.
+#[tauri::command]
+async fn update_quarantine_settings(
+    state: State<'_, AppState>,
+    settings: QuarantineSettings,
+) -> Result<QuarantineSettings, String> {
+    let mut cleaner = state.enhanced_file_cleaner.get().await.write().await;
+    cleaner.update_quarantine_settings(settings)?;
+    Ok(cleaner.quarantine_settings())
+}
+
+/// Items cleaned into quarantine, newest first; expired ones are purged
+/// first.
+#[tauri::command]
+async fn list_quarantine(state: State<'_, AppState>) -> Result<Vec<QuarantineEntry>, String> {
+    let mut cleaner = state.enhanced_file_cleaner.get().await.write().await;
+    Ok(cleaner.list_quarantine())
+}
+
+#[tauri::command]
+async fn restore_from_quarantine(
+    state: State<'_, AppState>,
+    ids: Vec<String>,
+) -> Result<QuarantineRestoreResult, String> {
+    if state.ops.inspector_mode() {
+        return Err(INSPECTOR_MODE_REFUSAL.to_string());
+    }
+    let _instance_lock = instance_lock::acquire_destructive_lock("restore_from_quarantine")?;
+    state
+        .enhanced_file_cleaner
+        .get()
+        .await
+        .write()
+        .await
+        .restore_from_quarantine(&ids)
+        .await
+}
+
+/// Delete quarantined items for good: those in `ids`, or all of them.
+#[tauri::command]
+async fn purge_quarantine(
+    state: State<'_, AppState>,
+    ids: Option<Vec<String>>,
+) -> Result<QuarantinePurgeResult, String> {
+    if state.ops.inspector_mode() {
+        return Err(INSPECTOR_MODE_REFUSAL.to_string());
+    }
+    let _instance_lock = instance_lock::acquire_destructive_lock("purge_quarantine")?;
+    state
+        .enhanced_file_cleaner
+        .get()
+        .await
+        .write()
+        .await
+        .purge_quarantine(ids.as_deref())
+}
+
+/// Put the files of the most recent clean back where they were, using the
+/// recovery point its history record names.
+#[tauri::command]
+async fn undo_last_clean(
+    app_handle: tauri::AppHandle,
+    state: State<'_, AppState>,
+) -> Result<UndoResult, String> {
+    let recovery_point_id = state
+        .ops
+        .history()
+        .last_recovery_point_id()
+        .map(str::to_string)
+        .ok_or("No clean to undo")?;
+    let _instance_lock = instance_lock::acquire_destructive_lock("undo_last_clean")?;
+    let (operation_id, _token) = state.ops.register(OperationKind::UndoClean, false)?;
+    app_handle
+        .emit(
+            "operation:start",
+            OperationStartEvent {
+                operation_id: operation_id.clone(),
+                operation_type: "undo_clean".into(),
+                estimated_duration: None,
+            },
+        )
+        .ok();
+
+    let result = state
+        .enhanced_file_cleaner
+        .get()
+        .await
+        .write()
+        .await
+        .undo_clean(&recovery_point_id)
+        .await;
+
+    let phase_timings = state.ops.phase_timings(&operation_id);
+    let message = match &result {
+        Ok(undo) => {
+            let restored = undo
+                .files
+                .iter()
+                .filter(|file| file.error.is_none())
+                .map(|file| file.original_path.clone());
+            state.ops.record_outcome(
+                &operation_id,
+                OperationOutcome::new(undo.restored as u64, 0)
+                    .with_cleaned(restored)
+                    .with_failures(undo.files.iter().filter_map(|file| {
+                        file.error
+                            .as_ref()
+                            .map(|err| format!("{}: {}", file.original_path, err))
+                    })),
+            );
+            state.ops.finish_success(&operation_id);
+            format!(
+                "Restored {} of {} files",
+                formatting::format_count(undo.restored as u64),
+                formatting::format_count(undo.files.len() as u64)
+            )
         }
-        if std::fs::rename(&src, &target).is_ok() {
-            restored += 1;
+        Err(err) => {
+            state.ops.finish_failed(&operation_id, err);
+            format!("Undo failed: {}", err)
         }
-    }
+    };
+    app_handle
+        .emit(
+            "operation:complete",
+            OperationCompleteEvent {
+                operation_id,
+                success: result.is_ok(),
+                message,
+                duration: 0,
+                canceled: Some(false),
+                phase_timings,
+            },
+        )
+        .ok();
 
-    Ok(restored)
+    result
 }
 
 #[tauri::command]
@@ -883,7 +2274,10 @@ async fn optimize_memory(
     app_handle: tauri::AppHandle,
     state: State<'_, AppState>,
 ) -> Result<MemoryOptimizationResult, String> {
-    let (operation_id, token) = state.ops.register(OperationKind::MemOptimize, true);
+    if state.ops.inspector_mode() {
+        return MemoryOptimizer::preview_optimization(false).await;
+    }
+    let (operation_id, token) = state.ops.register(OperationKind::MemOptimize, true)?;
     let start_time = std::time::Instant::now();
 
     // Emit start event
@@ -901,38 +2295,36 @@ async fn optimize_memory(
     let optimizer = state.memory_optimizer.read().await;
 
     // Progress: Starting optimization
-    app_handle
-        .emit(
-            "progress:update",
-            ProgressEvent {
-                operation_id: operation_id.clone(),
-                progress: 10.0,
-                message: "Starting memory optimization...".to_string(),
-                stage: "initialization".to_string(),
-                can_cancel: true,
-                eta_ms: None,
-                throughput: None,
-            },
-        )
-        .ok();
+    emit_progress(
+        &app_handle,
+        &state.ops,
+        ProgressEvent {
+            operation_id: operation_id.clone(),
+            progress: 10.0,
+            message: "Starting memory optimization...".to_string(),
+            stage: "initialization".to_string(),
+            can_cancel: true,
+            eta_ms: None,
+            throughput: None,
+        },
+    );
 
     tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
 
     // Progress: Clearing caches
-    app_handle
-        .emit(
-            "progress:update",
-            ProgressEvent {
-                operation_id: operation_id.clone(),
-                progress: 30.0,
-                message: "Clearing application caches...".to_string(),
-                stage: "cache_clear".to_string(),
-                can_cancel: true,
-                eta_ms: None,
-                throughput: None,
-            },
-        )
-        .ok();
+    emit_progress(
+        &app_handle,
+        &state.ops,
+        ProgressEvent {
+            operation_id: operation_id.clone(),
+            progress: 30.0,
+            message: "Clearing application caches...".to_string(),
+            stage: "cache_clear".to_string(),
+            can_cancel: true,
+            eta_ms: None,
+            throughput: None,
+        },
+    );
 
     // Perform the actual optimization with cancel + concurrency guard
     let _permit = state.ops.opt_sem.acquire().await;
@@ -941,24 +2333,34 @@ async fn optimize_memory(
     let duration = start_time.elapsed().as_millis() as u32;
 
     match &result {
-        Ok(_) => {
+        Ok(optimization) => {
+            state
+                .session
+                .record_memory_optimization(optimization.freed_memory.max(0) as u64);
+            state.ops.record_outcome(
+                &operation_id,
+                OperationOutcome::new(0, optimization.freed_memory.max(0) as u64),
+            );
+            record_usage(&state, UsageFeature::MemoryOptimization).await;
             app_handle
-                .emit(
-                    "progress:update",
-                    ProgressEvent {
-                        operation_id: operation_id.clone(),
-                        progress: 100.0,
-                        message: "Memory optimization completed successfully".to_string(),
-                        stage: "complete".to_string(),
-                        can_cancel: false,
-                        eta_ms: Some(0),
-                        throughput: None,
-                    },
-                )
+                .emit("memory:optimized", MemoryOptimizedEvent::from(optimization))
                 .ok();
-
-            app_handle
-                .emit(
+            emit_progress(
+                &app_handle,
+                &state.ops,
+                ProgressEvent {
+                    operation_id: operation_id.clone(),
+                    progress: 100.0,
+                    message: "Memory optimization completed successfully".to_string(),
+                    stage: "complete".to_string(),
+                    can_cancel: false,
+                    eta_ms: Some(0),
+                    throughput: None,
+                },
+            );
+
+            app_handle
+                .emit(
                     "operation:complete",
                     OperationCompleteEvent {
                         operation_id: operation_id.clone(),
@@ -966,6 +2368,7 @@ async fn optimize_memory(
                         message: "Memory optimization completed".to_string(),
                         duration,
                         canceled: Some(false),
+                        phase_timings: state.ops.phase_timings(&operation_id),
                     },
                 )
                 .ok();
@@ -982,6 +2385,7 @@ async fn optimize_memory(
                         message: format!("Memory optimization failed: {}", err),
                         duration,
                         canceled: Some(canceled),
+                        phase_timings: state.ops.phase_timings(&operation_id),
                     },
                 )
                 .ok();
@@ -996,12 +2400,181 @@ async fn optimize_memory(
     result
 }
 
+#[tauri::command]
+async fn get_watchdog_status(state: State<'_, AppState>) -> Result<WatchdogStatus, String> {
+    Ok(state.memory_watchdog.read().await.status())
+}
+
+/// Change the memory watchdog's thresholds, cooldown and automatic optimization.
+#[tauri::command]
+async fn configure_memory_watchdog(
+    state: State<'_, AppState>,
+    config: WatchdogConfig,
+) -> Result<WatchdogStatus, String> {
+    let mut watchdog = state.memory_watchdog.write().await;
+    watchdog.configure(config)?;
+    Ok(watchdog.status())
+}
+
+/// Polls memory pressure while the watchdog is enabled, emitting
+/// `memory:pressure` and clearing inactive memory when it is allowed to.
+async fn run_memory_watchdog(app_handle: tauri::AppHandle) {
+    let state = app_handle.state::<AppState>();
+    state.metrics_sampler.wait_until_ready().await;
+    loop {
+        let poll_secs = state.memory_watchdog.read().await.config().poll_secs;
+        tokio::time::sleep(std::time::Duration::from_secs(poll_secs)).await;
+        if !state.memory_watchdog.read().await.config().enabled {
+            continue;
+        }
+        let snapshot = state.metrics_sampler.latest_snapshot().await;
+        let Some(memory) = snapshot.memory.value else {
+            continue;
+        };
+        let event = state
+            .memory_watchdog
+            .write()
+            .await
+            .observe(memory.pressure_percent, chrono::Utc::now().timestamp());
+        let Some(event) = event else {
+            continue;
+        };
+        app_handle.emit("memory:pressure", &event).ok();
+        if event.level != PressureLevel::Normal {
+            state.notifier.memory_pressure(
+                event.level.name(),
+                event.pressure_percent,
+                event.auto_optimizing,
+            );
+        }
+        if !event.auto_optimizing {
+            continue;
+        }
+        // Clearing inactive memory needs no authorization prompt.
+        let plan = MemoryOptimizationPlan {
+            strategies: vec![MemoryStrategy::InactiveMemory],
+            dry_run: false,
+        };
+        let summary = match optimize_memory_plan(app_handle.clone(), app_handle.state(), plan).await
+        {
+            Ok(result) if result.dry_run => format!(
+                "Inspector mode: would free up to {}",
+                formatting::format_size(result.estimated_total)
+            ),
+            Ok(result) => format!(
+                "Freed {}",
+                formatting::format_size(result.freed_memory.max(0) as u64)
+            ),
+            Err(err) => {
+                log::warn!("Automatic memory optimization failed: {}", err);
+                format!("Failed: {}", err)
+            }
+        };
+        state
+            .memory_watchdog
+            .write()
+            .await
+            .record_optimization(summary);
+    }
+}
+
+/// Run the chosen memory strategies, or only estimate them with
+/// `plan.dry_run` or in inspector mode.
+#[tauri::command]
+async fn optimize_memory_plan(
+    app_handle: tauri::AppHandle,
+    state: State<'_, AppState>,
+    mut plan: MemoryOptimizationPlan,
+) -> Result<MemoryPlanResult, String> {
+    plan.dry_run |= state.ops.inspector_mode();
+    let optimizer = state.memory_optimizer.read().await;
+    if plan.dry_run {
+        return optimizer
+            .run_plan_with_cancel(&plan, &CancellationToken::new())
+            .await;
+    }
+    let kind = if plan.strategies.iter().any(|s| s.requires_admin()) {
+        OperationKind::MemOptimizeAdmin
+    } else {
+        OperationKind::MemOptimize
+    };
+    let (operation_id, token) = state.ops.register(kind, true)?;
+    let start_time = std::time::Instant::now();
+    app_handle
+        .emit(
+            "operation:start",
+            OperationStartEvent {
+                operation_id: operation_id.clone(),
+                operation_type: "memory_optimization".to_string(),
+                estimated_duration: Some(3000),
+            },
+        )
+        .ok();
+
+    let _permit = state.ops.opt_sem.acquire().await;
+    let result = optimizer.run_plan_with_cancel(&plan, &token).await;
+    let duration = start_time.elapsed().as_millis() as u32;
+
+    match &result {
+        Ok(outcome) => {
+            state
+                .session
+                .record_memory_optimization(outcome.freed_memory.max(0) as u64);
+            state.ops.record_outcome(
+                &operation_id,
+                OperationOutcome::new(0, outcome.freed_memory.max(0) as u64),
+            );
+            record_usage(&state, UsageFeature::MemoryOptimization).await;
+            app_handle
+                .emit(
+                    "operation:complete",
+                    OperationCompleteEvent {
+                        operation_id: operation_id.clone(),
+                        success: true,
+                        message: "Memory optimization completed".to_string(),
+                        duration,
+                        canceled: Some(false),
+                        phase_timings: state.ops.phase_timings(&operation_id),
+                    },
+                )
+                .ok();
+            state.ops.finish_success(&operation_id);
+        }
+        Err(err) => {
+            let canceled = err.contains("cancelled");
+            app_handle
+                .emit(
+                    "operation:complete",
+                    OperationCompleteEvent {
+                        operation_id: operation_id.clone(),
+                        success: false,
+                        message: format!("Memory optimization failed: {}", err),
+                        duration,
+                        canceled: Some(canceled),
+                        phase_timings: state.ops.phase_timings(&operation_id),
+                    },
+                )
+                .ok();
+            if canceled {
+                state.ops.finish_canceled(&operation_id);
+            } else {
+                state.ops.finish_failed(&operation_id, err);
+            }
+        }
+    }
+
+    result
+}
+
 #[tauri::command]
 async fn optimize_memory_admin(
     app_handle: tauri::AppHandle,
     state: State<'_, AppState>,
 ) -> Result<MemoryOptimizationResult, String> {
-    let (operation_id, token) = state.ops.register(OperationKind::MemOptimizeAdmin, true);
+    if state.ops.inspector_mode() {
+        return MemoryOptimizer::preview_optimization(true).await;
+    }
+    let (operation_id, token) = state.ops.register(OperationKind::MemOptimizeAdmin, true)?;
     let start_time = std::time::Instant::now();
 
     // Emit start event
@@ -1016,178 +2589,2609 @@ async fn optimize_memory_admin(
         )
         .ok();
 
-    let optimizer = state.memory_optimizer.read().await;
-
-    // Progress stages for admin optimization
-    app_handle
-        .emit(
-            "progress:update",
+    let optimizer = state.memory_optimizer.read().await;
+
+    // Progress stages for admin optimization
+    emit_progress(
+        &app_handle,
+        &state.ops,
+        ProgressEvent {
+            operation_id: operation_id.clone(),
+            progress: 15.0,
+            message: "Requesting administrator privileges...".to_string(),
+            stage: "auth".to_string(),
+            can_cancel: true,
+            eta_ms: None,
+            throughput: None,
+        },
+    );
+
+    tokio::time::sleep(tokio::time::Duration::from_millis(200)).await;
+
+    emit_progress(
+        &app_handle,
+        &state.ops,
+        ProgressEvent {
+            operation_id: operation_id.clone(),
+            progress: 35.0,
+            message: "Purging disk caches...".to_string(),
+            stage: "disk_cache".to_string(),
+            can_cancel: true,
+            eta_ms: None,
+            throughput: None,
+        },
+    );
+
+    emit_progress(
+        &app_handle,
+        &state.ops,
+        ProgressEvent {
+            operation_id: operation_id.clone(),
+            progress: 55.0,
+            message: "Clearing DNS and network caches...".to_string(),
+            stage: "network_cache".to_string(),
+            can_cancel: true,
+            eta_ms: None,
+            throughput: None,
+        },
+    );
+
+    emit_progress(
+        &app_handle,
+        &state.ops,
+        ProgressEvent {
+            operation_id: operation_id.clone(),
+            progress: 75.0,
+            message: "Optimizing memory compression...".to_string(),
+            stage: "memory_compression".to_string(),
+            can_cancel: false,
+            eta_ms: None,
+            throughput: None,
+        },
+    );
+
+    // Perform the actual admin optimization
+    let _permit = state.ops.opt_sem.acquire().await;
+    let result = optimizer.optimize_memory_with_admin_cancel(&token).await;
+
+    let duration = start_time.elapsed().as_millis() as u32;
+
+    match &result {
+        Ok(optimization) => {
+            state
+                .session
+                .record_memory_optimization(optimization.freed_memory.max(0) as u64);
+            state.ops.record_outcome(
+                &operation_id,
+                OperationOutcome::new(0, optimization.freed_memory.max(0) as u64),
+            );
+            record_usage(&state, UsageFeature::MemoryOptimization).await;
+            app_handle
+                .emit("memory:optimized", MemoryOptimizedEvent::from(optimization))
+                .ok();
+            emit_progress(
+                &app_handle,
+                &state.ops,
+                ProgressEvent {
+                    operation_id: operation_id.clone(),
+                    progress: 100.0,
+                    message: "Deep clean optimization completed successfully".to_string(),
+                    stage: "complete".to_string(),
+                    can_cancel: false,
+                    eta_ms: Some(0),
+                    throughput: None,
+                },
+            );
+
+            app_handle
+                .emit(
+                    "operation:complete",
+                    OperationCompleteEvent {
+                        operation_id: operation_id.clone(),
+                        success: true,
+                        message: "Deep clean optimization completed".to_string(),
+                        duration,
+                        canceled: Some(false),
+                        phase_timings: state.ops.phase_timings(&operation_id),
+                    },
+                )
+                .ok();
+            state.ops.finish_success(&operation_id);
+        }
+        Err(err) => {
+            let canceled = err.contains("cancelled");
+            app_handle
+                .emit(
+                    "operation:complete",
+                    OperationCompleteEvent {
+                        operation_id: operation_id.clone(),
+                        success: false,
+                        message: format!("Deep clean optimization failed: {}", err),
+                        duration,
+                        canceled: Some(canceled),
+                        phase_timings: state.ops.phase_timings(&operation_id),
+                    },
+                )
+                .ok();
+            if canceled {
+                state.ops.finish_canceled(&operation_id);
+            } else {
+                state.ops.finish_failed(&operation_id, &err);
+            }
+        }
+    }
+
+    result
+}
+
+#[tauri::command]
+async fn clear_inactive_memory(state: State<'_, AppState>) -> Result<u64, String> {
+    if state.ops.inspector_mode() {
+        // Nothing is purged, so nothing is freed.
+        return Ok(0);
+    }
+    let optimizer = state.memory_optimizer.read().await;
+    optimizer.clear_inactive_memory().await
+}
+
+#[tauri::command]
+async fn get_memory_pressure(state: State<'_, AppState>) -> Result<f32, String> {
+    let optimizer = state.memory_optimizer.read().await;
+    optimizer.get_memory_pressure()
+}
+
+#[tauri::command]
+async fn get_memory_stats(state: State<'_, AppState>) -> Result<MemoryStats, String> {
+    state.metrics_sampler.wait_until_ready().await;
+    let snapshot = state.metrics_sampler.latest_snapshot().await;
+    sample_value(&snapshot.memory, "memory")
+}
+
+/// Memory split into app, wired, compressed and cached memory the way
+/// Activity Monitor's memory tab shows it, with swap and compressor traffic.
+#[tauri::command]
+async fn get_memory_pressure_breakdown(
+    state: State<'_, AppState>,
+) -> Result<MemoryPressureBreakdown, String> {
+    state.metrics_sampler.wait_until_ready().await;
+    let snapshot = state.metrics_sampler.latest_snapshot().await;
+    sample_value(&snapshot.memory, "memory")
+        .map(|stats| MemoryPressureBreakdown::from_stats(&stats))
+}
+
+#[tauri::command]
+async fn cancel_operation(state: State<'_, AppState>, operation_id: String) -> Result<(), String> {
+    if state.ops.cancel(&operation_id) {
+        Ok(())
+    } else {
+        Err("Unknown operation".into())
+    }
+}
+
+#[tauri::command]
+async fn get_operation_state(
+    state: State<'_, AppState>,
+    operation_id: String,
+) -> Result<Option<OpState>, String> {
+    Ok(state.ops.get(&operation_id))
+}
+
+#[tauri::command]
+async fn get_format_preferences() -> Result<FormatPreferences, String> {
+    Ok(formatting::preferences())
+}
+
+/// Apply `preferences` now; the unit system is also saved with the settings.
+#[tauri::command]
+async fn set_format_preferences(
+    app_handle: tauri::AppHandle,
+    state: State<'_, AppState>,
+    preferences: FormatPreferences,
+) -> Result<(), String> {
+    let mut store = state.settings.write().await;
+    let mut stored = store.settings();
+    if stored.size_units != preferences.units {
+        stored.size_units = preferences.units;
+        store.update(stored)?;
+        drop(store);
+        emit_settings_changed(&app_handle, &state).await;
+    }
+    formatting::set_preferences(preferences);
+    Ok(())
+}
+
+#[tauri::command]
+async fn create_schedule(
+    state: State<'_, AppState>,
+    request: NewSchedule,
+) -> Result<Schedule, String> {
+    state.schedules.write().await.create(request)
+}
+
+#[tauri::command]
+async fn list_schedules(state: State<'_, AppState>) -> Result<Vec<Schedule>, String> {
+    Ok(state.schedules.read().await.list())
+}
+
+#[tauri::command]
+async fn delete_schedule(state: State<'_, AppState>, schedule_id: String) -> Result<(), String> {
+    if state.schedules.write().await.delete(&schedule_id)? {
+        Ok(())
+    } else {
+        Err("Unknown schedule".into())
+    }
+}
+
+/// Background loop that runs due schedules through the regular commands, so
+/// scheduled work shows up with the same progress events as manual runs.
+async fn run_scheduler(app_handle: tauri::AppHandle) {
+    let mut ticker = tokio::time::interval(SCHEDULER_TICK);
+    loop {
+        ticker.tick().await;
+        let state = app_handle.state::<AppState>();
+        if state.settings.read().await.settings().schedules.paused {
+            continue;
+        }
+        let due = state
+            .schedules
+            .read()
+            .await
+            .due(chrono::Utc::now().timestamp());
+        for schedule in due {
+            log::info!("Running scheduled job '{}'", schedule.name);
+            let result = run_scheduled_job(&app_handle, &schedule.action).await;
+            let summary = match result {
+                Ok(summary) => {
+                    record_usage(&state, UsageFeature::ScheduledJob).await;
+                    summary
+                }
+                Err(err) => {
+                    log::warn!("Scheduled job '{}' failed: {}", schedule.name, err);
+                    format!("Failed: {}", err)
+                }
+            };
+            let now = chrono::Utc::now().timestamp();
+            if let Err(err) = state
+                .schedules
+                .write()
+                .await
+                .mark_run(&schedule.id, now, summary)
+            {
+                log::warn!("Failed to record run of '{}': {}", schedule.name, err);
+            }
+        }
+    }
+}
+
+async fn run_scheduled_job(
+    app_handle: &tauri::AppHandle,
+    action: &ScheduleAction,
+) -> Result<String, String> {
+    match action {
+        ScheduleAction::Scan => {
+            let report =
+                scan_cleanable_files_enhanced(app_handle.clone(), app_handle.state(), Some(true))
+                    .await?;
+            Ok(format!(
+                "Found {} ({} files)",
+                formatting::format_size(report.base.total_size),
+                formatting::format_count(report.base.files_count as u64)
+            ))
+        }
+        ScheduleAction::CleanCategories { categories } => {
+            // A full walk before deleting anything.
+            let report =
+                scan_cleanable_files_enhanced(app_handle.clone(), app_handle.state(), None).await?;
+            let paths: Vec<String> = report
+                .enhanced_files
+                .iter()
+                .filter(|file| file.base.auto_select)
+                .filter(|file| {
+                    categories.is_empty()
+                        || categories
+                            .iter()
+                            .any(|c| c.eq_ignore_ascii_case(&file.base.category))
+                })
+                .map(|file| file.base.path.clone())
+                .collect();
+            if paths.is_empty() {
+                return Ok("Nothing to clean".into());
+            }
+            let result =
+                clean_files_enhanced(app_handle.clone(), app_handle.state(), paths, Some(false))
+                    .await?;
+            Ok(format!(
+                "Cleaned {} files, {} freed",
+                formatting::format_count(result.deleted_count as u64),
+                formatting::format_size(result.total_freed)
+            ))
+        }
+        ScheduleAction::EmptyTrash => {
+            let result = empty_trash(app_handle.clone(), app_handle.state(), None).await?;
+            Ok(format!(
+                "Removed {} items, {} freed",
+                formatting::format_count(result.removed as u64),
+                formatting::format_size(result.freed)
+            ))
+        }
+        ScheduleAction::PurgeOldTrash { older_than_days } => {
+            purge_old_trash(app_handle, *older_than_days).await
+        }
+    }
+}
+
+/// Delete Trash entries older than `older_than_days` and log each one.
+async fn purge_old_trash(
+    app_handle: &tauri::AppHandle,
+    older_than_days: u32,
+) -> Result<String, String> {
+    let state = app_handle.state::<AppState>();
+    let now = chrono::Utc::now().timestamp();
+    let cutoff = now - i64::from(older_than_days) * 24 * 60 * 60;
+    if state.ops.inspector_mode() {
+        let expired: Vec<TrashItem> = state
+            .file_cleaner
+            .read()
+            .await
+            .enumerate_trash()
+            .await?
+            .into_iter()
+            .filter(|item| item.trashed_at < cutoff)
+            .collect();
+        return Ok(format!(
+            "Would remove {} items older than {} days ({})",
+            formatting::format_count(expired.len() as u64),
+            older_than_days,
+            formatting::format_size(expired.iter().map(|item| item.size).sum())
+        ));
+    }
+
+    let _instance_lock = instance_lock::acquire_destructive_lock("purge_old_trash")?;
+    let (operation_id, token) = state.ops.register(OperationKind::EmptyTrash, true)?;
+    let start_time = std::time::Instant::now();
+    app_handle
+        .emit(
+            "operation:start",
+            OperationStartEvent {
+                operation_id: operation_id.clone(),
+                operation_type: "purge_old_trash".into(),
+                estimated_duration: None,
+            },
+        )
+        .ok();
+    let _permit = state.ops.clean_sem.acquire().await;
+
+    let res = state
+        .file_cleaner
+        .read()
+        .await
+        .purge_trash_older_than(cutoff, &token)
+        .await;
+    let canceled = res.as_ref().map(|r| r.canceled).unwrap_or(false);
+    let message = match &res {
+        Ok(result) => format!(
+            "Removed {} Trash items older than {} days ({} freed), kept {}{}",
+            formatting::format_count(result.purged.len() as u64),
+            older_than_days,
+            formatting::format_size(result.freed),
+            formatting::format_count(result.kept as u64),
+            if result.failed > 0 {
+                format!(
+                    ", {} could not be removed",
+                    formatting::format_count(result.failed as u64)
+                )
+            } else {
+                String::new()
+            }
+        ),
+        Err(err) => format!("Purging old Trash items failed: {}", err),
+    };
+    if let Ok(result) = &res {
+        state.ops.record_outcome(
+            &operation_id,
+            OperationOutcome::new(result.purged.len() as u64, result.freed)
+                .with_cleaned(result.purged.iter().map(|item| item.path.clone())),
+        );
+    }
+    app_handle
+        .emit(
+            "operation:complete",
+            OperationCompleteEvent {
+                operation_id: operation_id.clone(),
+                success: res.is_ok() && !canceled,
+                message: message.clone(),
+                duration: start_time.elapsed().as_millis() as u32,
+                canceled: Some(canceled),
+                phase_timings: state.ops.phase_timings(&operation_id),
+            },
+        )
+        .ok();
+    match &res {
+        Ok(_) if canceled => state.ops.finish_canceled(&operation_id),
+        Ok(_) => state.ops.finish_success(&operation_id),
+        Err(err) => state.ops.finish_failed(&operation_id, err),
+    }
+    let result = res?;
+    for item in &result.purged {
+        log::info!("Purged old Trash item {} ({} bytes)", item.path, item.size);
+    }
+    state
+        .session
+        .record_cleanup(result.freed, result.purged.len() as u64);
+    state
+        .trash_purges
+        .write()
+        .await
+        .append(TrashPurgeRecord::new(now, older_than_days, result))?;
+    Ok(message)
+}
+
+/// Every Trash aging run with the entries it removed, oldest first.
+#[tauri::command]
+async fn get_trash_purge_history(
+    state: State<'_, AppState>,
+) -> Result<Vec<TrashPurgeRecord>, String> {
+    Ok(state.trash_purges.read().await.list())
+}
+
+#[tauri::command]
+async fn get_app_quit_cleanup_mode(
+    state: State<'_, AppState>,
+) -> Result<AppQuitCleanupMode, String> {
+    Ok(state.app_quit.read().await.mode)
+}
+
+#[tauri::command]
+async fn set_app_quit_cleanup_mode(
+    state: State<'_, AppState>,
+    mode: AppQuitCleanupMode,
+) -> Result<(), String> {
+    state.app_quit.write().await.set_mode(mode)
+}
+
+/// Clear one heavyweight app's caches while it is closed.
+#[tauri::command]
+async fn clean_app_quit_caches(
+    app_handle: tauri::AppHandle,
+    state: State<'_, AppState>,
+    app: String,
+) -> Result<AppCacheCleanResult, String> {
+    let heavy_app = app_quit::heavy_app(&app).ok_or_else(|| format!("Unknown app: {}", app))?;
+    let home = dirs::home_dir().ok_or("Home directory not found")?;
+    if state.ops.inspector_mode() {
+        return tokio::task::spawn_blocking(move || app_quit::preview_app_caches(heavy_app, &home))
+            .await
+            .map_err(|e| format!("Cache cleanup task failed: {}", e));
+    }
+    if heavy_app.is_running(&ProcessSnapshot::capture().await) {
+        return Err(format!(
+            "{} is running again; caches were left alone",
+            heavy_app.display_name
+        ));
+    }
+
+    let _instance_lock = instance_lock::acquire_destructive_lock("clean_app_quit_caches")?;
+    let (operation_id, _token) = state.ops.register(OperationKind::FileClean, false)?;
+    let start_time = std::time::Instant::now();
+    app_handle
+        .emit(
+            "operation:start",
+            OperationStartEvent {
+                operation_id: operation_id.clone(),
+                operation_type: "app_quit_cleanup".into(),
+                estimated_duration: None,
+            },
+        )
+        .ok();
+    let _permit = state.ops.clean_sem.acquire().await;
+
+    let result = tokio::task::spawn_blocking(move || app_quit::clean_app_caches(heavy_app, &home))
+        .await
+        .map_err(|e| format!("Cache cleanup task failed: {}", e));
+    let message = match &result {
+        Ok(result) => format!(
+            "Cleared {} caches: {} freed",
+            heavy_app.display_name,
+            formatting::format_size(result.freed)
+        ),
+        Err(err) => err.clone(),
+    };
+    app_handle
+        .emit(
+            "operation:complete",
+            OperationCompleteEvent {
+                operation_id: operation_id.clone(),
+                success: result.is_ok(),
+                message: message.clone(),
+                duration: start_time.elapsed().as_millis() as u32,
+                canceled: Some(false),
+                phase_timings: state.ops.phase_timings(&operation_id),
+            },
+        )
+        .ok();
+    if let Ok(result) = &result {
+        state.session.record_cleanup(result.freed, 1);
+        state.ops.record_outcome(
+            &operation_id,
+            OperationOutcome::new(result.removed as u64, result.freed),
+        );
+    }
+    match &result {
+        Ok(_) => state.ops.finish_success(&operation_id),
+        Err(err) => state.ops.finish_failed(&operation_id, err),
+    }
+    result
+}
+
+/// Measure the cache folders that changed since the last sweep and log how
+/// much each app's caches grew or shrank.
+async fn run_cache_growth_tracker(app_handle: tauri::AppHandle) {
+    let Some(home) = dirs::home_dir() else {
+        return;
+    };
+    let mut tracker = CacheGrowthTracker::watch(&home);
+    let mut ticker = tokio::time::interval(CACHE_GROWTH_SWEEP);
+    loop {
+        ticker.tick().await;
+        let sweep;
+        (tracker, sweep) = match tokio::task::spawn_blocking(move || {
+            let sweep = tracker.sweep();
+            (tracker, sweep)
+        })
+        .await
+        {
+            Ok(swept) => swept,
+            Err(err) => {
+                log::warn!("Cache growth tracking stopped: {}", err);
+                return;
+            }
+        };
+        let state = app_handle.state::<AppState>();
+        let now = chrono::Utc::now().timestamp();
+        if let Err(err) = state.cache_growth.write().await.record(now, sweep) {
+            log::warn!("Failed to record cache growth: {}", err);
+        }
+    }
+}
+
+/// How much each app's caches grew over the last `days` days (default 7,
+/// at most 30), fastest growing first, with what cleaning removed in the
+/// same window to show how quickly it comes back.
+#[tauri::command]
+async fn get_cache_growth_report(
+    state: State<'_, AppState>,
+    days: Option<u32>,
+) -> Result<CacheGrowthReport, String> {
+    Ok(state.cache_growth.read().await.report(
+        chrono::Utc::now().timestamp(),
+        days.unwrap_or(DEFAULT_CACHE_GROWTH_DAYS),
+    ))
+}
+
+/// When a heavyweight app quits, offer or run a cleanup of just that app's
+/// caches while it is safely closed.
+async fn run_app_quit_watcher(
+    app_handle: tauri::AppHandle,
+    mut quits: tokio::sync::mpsc::UnboundedReceiver<&'static HeavyApp>,
+) {
+    while let Some(heavy_app) = quits.recv().await {
+        let mode = app_handle.state::<AppState>().app_quit.read().await.mode;
+        if mode == AppQuitCleanupMode::Off {
+            continue;
+        }
+        let Some(home) = dirs::home_dir() else {
+            continue;
+        };
+        let Ok(offer) =
+            tokio::task::spawn_blocking(move || app_quit::cleanup_offer(heavy_app, &home)).await
+        else {
+            continue;
+        };
+        if offer.total_size == 0 {
+            continue;
+        }
+        match mode {
+            AppQuitCleanupMode::Auto => {
+                if let Err(err) =
+                    clean_app_quit_caches(app_handle.clone(), app_handle.state(), offer.app).await
+                {
+                    log::warn!("Automatic app-quit cleanup failed: {}", err);
+                }
+            }
+            _ => {
+                app_handle.emit("app-quit:cleanup-available", offer).ok();
+            }
+        }
+    }
+}
+
+/// Record installed apps, launch items, kexts and per-category disk usage
+/// (from the latest scan) so later snapshots can be compared against it.
+#[tauri::command]
+async fn capture_system_snapshot(
+    state: State<'_, AppState>,
+    label: String,
+) -> Result<SystemSnapshot, String> {
+    let disk_usage = {
+        let cleaner = state.file_cleaner.read().await;
+        system_snapshot::category_usage(cleaner.get_cleanable_files())
+    };
+    let store = SnapshotStore::open()?;
+    tokio::task::spawn_blocking(move || {
+        let snapshot = system_snapshot::capture_system_snapshot(
+            &label,
+            &SnapshotSources::system(),
+            disk_usage,
+        );
+        store.save(&snapshot)?;
+        Ok(snapshot)
+    })
+    .await
+    .map_err(|e| format!("Snapshot task failed: {}", e))?
+}
+
+#[tauri::command]
+async fn list_system_snapshots() -> Result<Vec<SnapshotSummary>, String> {
+    Ok(SnapshotStore::open()?.list())
+}
+
+#[tauri::command]
+async fn compare_snapshots(a: String, b: String) -> Result<SnapshotDiff, String> {
+    let store = SnapshotStore::open()?;
+    let before = store.load(&a)?;
+    let after = store.load(&b)?;
+    Ok(system_snapshot::compare_snapshots(&before, &after))
+}
+
+#[tauri::command]
+async fn list_installed_apps() -> Result<Vec<AppEntry>, String> {
+    let uninstaller = AppUninstaller::new()?;
+    tokio::task::spawn_blocking(move || uninstaller.list_installed_apps())
+        .await
+        .map_err(|e| format!("App listing failed: {}", e))
+}
+
+#[tauri::command]
+async fn analyze_app_footprint(
+    state: State<'_, AppState>,
+    app_path: String,
+) -> Result<AppFootprint, String> {
+    let footprint = AppUninstaller::new()?
+        .analyze_app_footprint(std::path::Path::new(&app_path))
+        .await?;
+    state
+        .app_footprints
+        .write()
+        .await
+        .insert(app_path, footprint.clone());
+    Ok(footprint)
+}
+
+#[tauri::command]
+async fn uninstall_app(
+    app_handle: tauri::AppHandle,
+    state: State<'_, AppState>,
+    app_path: String,
+    include_low_safety: Option<bool>,
+) -> Result<UninstallResult, String> {
+    if state.ops.inspector_mode() {
+        return AppUninstaller::new()?
+            .preview_uninstall(
+                std::path::Path::new(&app_path),
+                include_low_safety.unwrap_or(false),
+            )
+            .await;
+    }
+    let _instance_lock = instance_lock::acquire_destructive_lock("uninstall_app")?;
+    let (operation_id, _token) = state.ops.register(OperationKind::FileClean, false)?;
+    let start_time = std::time::Instant::now();
+    app_handle
+        .emit(
+            "operation:start",
+            OperationStartEvent {
+                operation_id: operation_id.clone(),
+                operation_type: "uninstall_app".into(),
+                estimated_duration: None,
+            },
+        )
+        .ok();
+    let _permit = state.ops.clean_sem.acquire().await;
+
+    let result = match AppUninstaller::new() {
+        Ok(uninstaller) => {
+            uninstaller
+                .uninstall_app(
+                    std::path::Path::new(&app_path),
+                    include_low_safety.unwrap_or(false),
+                )
+                .await
+        }
+        Err(err) => Err(err),
+    };
+    let message = match &result {
+        Ok(result) if result.skipped.is_empty() => format!(
+            "Uninstalled: {} items moved to Trash ({})",
+            formatting::format_count(result.moved_to_trash.len() as u64),
+            formatting::format_size(result.freed)
+        ),
+        Ok(result) => format!(
+            "Uninstalled: {} items moved to Trash ({}), {} left for review",
+            formatting::format_count(result.moved_to_trash.len() as u64),
+            formatting::format_size(result.freed),
+            formatting::format_count(result.skipped.len() as u64)
+        ),
+        Err(err) => format!("Uninstall failed: {}", err),
+    };
+    app_handle
+        .emit(
+            "operation:complete",
+            OperationCompleteEvent {
+                operation_id: operation_id.clone(),
+                success: result.is_ok(),
+                message,
+                duration: start_time.elapsed().as_millis() as u32,
+                canceled: Some(false),
+                phase_timings: state.ops.phase_timings(&operation_id),
+            },
+        )
+        .ok();
+    if let Ok(result) = &result {
+        state
+            .session
+            .record_cleanup(result.freed, result.moved_to_trash.len() as u64);
+        state.ops.record_outcome(
+            &operation_id,
+            OperationOutcome::new(result.moved_to_trash.len() as u64, result.freed)
+                .with_cleaned(result.moved_to_trash.iter().cloned()),
+        );
+        state.app_footprints.write().await.remove(&app_path);
+        record_usage(&state, UsageFeature::AppUninstall).await;
+    }
+    match &result {
+        Ok(_) => state.ops.finish_success(&operation_id),
+        Err(err) => state.ops.finish_failed(&operation_id, err),
+    }
+    result
+}
+
+/// Itemized breakdown of what Mail, Messages or Photos keeps on disk, so
+/// their review categories can be browsed item by item.
+#[tauri::command]
+async fn analyze_app_data(app: AppDataApp) -> Result<AppDataReport, String> {
+    tokio::task::spawn_blocking(move || app_data::analyze_app_data(app))
+        .await
+        .map_err(|e| format!("App data analysis failed: {}", e))?
+}
+
+/// Each device's iPhone or iPad backup, most recent first.
+#[tauri::command]
+async fn list_ios_backups() -> Result<Vec<IosBackup>, String> {
+    let root = ios_backups::default_backup_root().ok_or("Home directory not found")?;
+    tokio::task::spawn_blocking(move || ios_backups::list_backups(&root))
+        .await
+        .map_err(|e| format!("Backup listing task failed: {}", e))
+}
+
+/// Move the backup of one device to the Trash.
+#[tauri::command]
+async fn delete_ios_backup(state: State<'_, AppState>, udid: String) -> Result<IosBackup, String> {
+    let root = ios_backups::default_backup_root().ok_or("Home directory not found")?;
+    if state.ops.inspector_mode() {
+        return tokio::task::spawn_blocking(move || ios_backups::preview_delete(&root, &udid))
+            .await
+            .map_err(|e| format!("Backup preview task failed: {}", e))?;
+    }
+    let _instance_lock = instance_lock::acquire_destructive_lock("delete_ios_backup")?;
+    let backup = ios_backups::delete_backup(&root, &udid).await?;
+    state.session.record_cleanup(backup.size, 1);
+    Ok(backup)
+}
+
+/// Duplicate originals and large videos in a Photos library, the system
+/// one without `library`. Only reads the library; candidates are removed
+/// in Photos through [`open_in_photos`].
+#[tauri::command]
+async fn scan_photos_library(library: Option<String>) -> Result<PhotosLibraryReport, String> {
+    let library = photos_library::library_path(library.as_deref())?;
+    tokio::task::spawn_blocking(move || photos_library::analyze_library(&library))
+        .await
+        .map_err(|e| format!("Photos library scan failed: {}", e))?
+}
+
+/// Show an asset from [`scan_photos_library`] in Photos so it can be
+/// deleted there.
+#[tauri::command]
+async fn open_in_photos(uuid: String) -> Result<(), String> {
+    photos_library::open_in_photos(&uuid)
+}
+
+/// Unused localizations and foreign architectures in every installed app,
+/// most reclaimable first. Apps slimming would break are included with the
+/// reason they are blocked.
+#[tauri::command]
+async fn scan_app_bundles() -> Result<Vec<AppSlimReport>, String> {
+    let uninstaller = AppUninstaller::new()?;
+    let settings = SlimSettings::current().await;
+    tokio::task::spawn_blocking(move || {
+        let mut reports: Vec<AppSlimReport> = uninstaller
+            .list_installed_apps()
+            .iter()
+            .map(|app| app_slimmer::analyze_app(std::path::Path::new(&app.path), &settings))
+            .filter(|report| !report.items.is_empty())
+            .collect();
+        reports.sort_by(|a, b| b.reclaimable.cmp(&a.reclaimable));
+        reports
+    })
+    .await
+    .map_err(|e| format!("App bundle scan failed: {}", e))
+}
+
+/// Move an installed app's unused localizations to the Trash and thin its
+/// universal binaries, unless its code signature seals them.
+#[tauri::command]
+async fn slim_app_bundle(
+    app_handle: tauri::AppHandle,
+    state: State<'_, AppState>,
+    app_path: String,
+) -> Result<AppSlimResult, String> {
+    let installed = AppUninstaller::new()?
+        .list_installed_apps()
+        .into_iter()
+        .any(|app| app.path == app_path);
+    if !installed {
+        return Err(format!("{} is not an installed application", app_path));
+    }
+    let settings = SlimSettings::current().await;
+    let analyzed_path = app_path.clone();
+    let analysis_settings = settings.clone();
+    let report = tokio::task::spawn_blocking(move || {
+        app_slimmer::analyze_app(std::path::Path::new(&analyzed_path), &analysis_settings)
+    })
+    .await
+    .map_err(|e| format!("App bundle analysis failed: {}", e))?;
+    if state.ops.inspector_mode() {
+        return app_slimmer::preview_slim(&report);
+    }
+    let _instance_lock = instance_lock::acquire_destructive_lock("slim_app_bundle")?;
+    let (operation_id, _token) = state.ops.register(OperationKind::FileClean, false)?;
+    let start_time = std::time::Instant::now();
+    app_handle
+        .emit(
+            "operation:start",
+            OperationStartEvent {
+                operation_id: operation_id.clone(),
+                operation_type: "slim_app_bundle".into(),
+                estimated_duration: None,
+            },
+        )
+        .ok();
+    let _permit = state.ops.clean_sem.acquire().await;
+
+    let cleaner = state.file_cleaner.read().await;
+    let result = app_slimmer::slim_app(&report, &settings, &cleaner).await;
+    let message = match &result {
+        Ok(result) if result.failures.is_empty() => format!(
+            "Slimmed {}: {} freed",
+            report.app.name,
+            formatting::format_size(result.freed)
+        ),
+        Ok(result) => format!(
+            "Slimmed {}: {} freed, {} items left in place",
+            report.app.name,
+            formatting::format_size(result.freed),
+            formatting::format_count(result.failures.len() as u64)
+        ),
+        Err(err) => format!("Slimming failed: {}", err),
+    };
+    app_handle
+        .emit(
+            "operation:complete",
+            OperationCompleteEvent {
+                operation_id: operation_id.clone(),
+                success: result.is_ok(),
+                message,
+                duration: start_time.elapsed().as_millis() as u32,
+                canceled: Some(false),
+                phase_timings: state.ops.phase_timings(&operation_id),
+            },
+        )
+        .ok();
+    if let Ok(result) = &result {
+        state
+            .session
+            .record_cleanup(result.freed, result.trashed.len() as u64);
+        state.ops.record_outcome(
+            &operation_id,
+            OperationOutcome::new(result.trashed.len() as u64, result.freed)
+                .with_cleaned(result.trashed.iter().cloned())
+                .with_failures(result.failures.iter().cloned()),
+        );
+    }
+    match &result {
+        Ok(_) => state.ops.finish_success(&operation_id),
+        Err(err) => state.ops.finish_failed(&operation_id, err),
+    }
+    result
+}
+
+/// Ranked suggestions drawn from the last scan, duplicate groups, analyzed
+/// apps and disk usage.
+#[tauri::command]
+async fn get_storage_recommendations(
+    state: State<'_, AppState>,
+) -> Result<Vec<StorageRecommendation>, String> {
+    let volumes = tokio::task::spawn_blocking(volumes::list_volumes)
+        .await
+        .map_err(|e| format!("Volume listing task failed: {}", e))?;
+    let footprints: Vec<AppFootprint> = state
+        .app_footprints
+        .read()
+        .await
+        .values()
+        .cloned()
+        .collect();
+    Ok(state
+        .enhanced_file_cleaner
+        .get()
+        .await
+        .read()
+        .await
+        .storage_recommendations(&footprints, &volumes))
+}
+
+/// Audit entries returned with the helper's status.
+const HELPER_AUDIT_ENTRIES: usize = 50;
+
+/// Whether the privileged helper is installed and running, and what it
+/// did last.
+#[tauri::command]
+async fn get_privileged_helper_status() -> Result<HelperStatus, String> {
+    Ok(privileged_helper::status(HELPER_AUDIT_ENTRIES).await)
+}
+
+/// Install the privileged helper behind one administrator prompt, so later
+/// elevated operations run without one.
+#[tauri::command]
+async fn install_privileged_helper(state: State<'_, AppState>) -> Result<HelperStatus, String> {
+    if state.ops.inspector_mode() {
+        return Err(INSPECTOR_MODE_REFUSAL.into());
+    }
+    let binary = std::env::current_exe()
+        .map_err(|e| format!("Failed to locate the app: {}", e))?
+        .with_file_name(HELPER_BINARY_NAME);
+    privileged_helper::install(&binary, &CancellationToken::new()).await?;
+    Ok(privileged_helper::status(HELPER_AUDIT_ENTRIES).await)
+}
+
+/// Remove the privileged helper; elevated operations prompt again.
+#[tauri::command]
+async fn uninstall_privileged_helper(state: State<'_, AppState>) -> Result<HelperStatus, String> {
+    if state.ops.inspector_mode() {
+        return Err(INSPECTOR_MODE_REFUSAL.into());
+    }
+    privileged_helper::uninstall(&CancellationToken::new()).await?;
+    Ok(privileged_helper::status(HELPER_AUDIT_ENTRIES).await)
+}
+
+/// Whether the app has Full Disk Access, from probing the protected
+/// locations scans need; without it they quietly miss what is inside.
+#[tauri::command]
+async fn check_permissions() -> Result<PermissionReport, String> {
+    tokio::task::spawn_blocking(permissions::check_permissions)
+        .await
+        .map_err(|e| format!("Permission check failed: {}", e))
+}
+
+/// Open the Full Disk Access pane of System Settings.
+#[tauri::command]
+async fn request_full_disk_access() -> Result<(), String> {
+    permissions::request_full_disk_access()
+}
+
+/// Optional command-line tools and the checks that are skipped without them.
+/// `refresh` re-probes, e.g. after installing the command-line tools.
+#[tauri::command]
+async fn get_capabilities(refresh: Option<bool>) -> Result<Capabilities, String> {
+    let refresh = refresh.unwrap_or(false);
+    tokio::task::spawn_blocking(move || {
+        if refresh {
+            capabilities::refresh_capabilities()
+        } else {
+            capabilities::capabilities()
+        }
+    })
+    .await
+    .map_err(|e| format!("Capability probe failed: {}", e))
+}
+
+/// Size-annotated directory tree for the disk usage treemap, `max_depth`
+/// levels deep or as deep as the scan depth setting.
+#[tauri::command]
+async fn scan_directory_tree(
+    app_handle: tauri::AppHandle,
+    state: State<'_, AppState>,
+    path: String,
+    max_depth: Option<usize>,
+) -> Result<DiskNode, String> {
+    let root = match path.strip_prefix("~/") {
+        Some(rest) => dirs::home_dir()
+            .ok_or("Home directory not found")?
+            .join(rest),
+        None => std::path::PathBuf::from(&path),
+    };
+    let (operation_id, token) = state.ops.register(OperationKind::FileScan, true)?;
+    let start_time = std::time::Instant::now();
+    app_handle
+        .emit(
+            "operation:start",
+            OperationStartEvent {
+                operation_id: operation_id.clone(),
+                operation_type: "directory_tree_scan".into(),
+                estimated_duration: None,
+            },
+        )
+        .ok();
+    let _permit = state.ops.scan_sem.acquire().await;
+
+    let max_depth = max_depth.unwrap_or(state.settings.read().await.settings().scan_depth);
+    let tree_root = root.clone();
+    let tree_token = token.clone();
+    let result = tokio::task::spawn_blocking(move || {
+        disk_analyzer::scan_directory_tree(&tree_root, max_depth, &tree_token)
+    })
+    .await
+    .map_err(|e| format!("Directory scan task failed: {}", e))
+    .and_then(|result| result);
+
+    let canceled = matches!(&result, Err(err) if err.contains("cancelled"));
+    let message = match &result {
+        Ok(tree) => format!(
+            "Scanned {} ({})",
+            root.display(),
+            formatting::format_size(tree.size)
+        ),
+        Err(err) => format!("Directory scan failed: {}", err),
+    };
+    app_handle
+        .emit(
+            "operation:complete",
+            OperationCompleteEvent {
+                operation_id: operation_id.clone(),
+                success: result.is_ok(),
+                message,
+                duration: start_time.elapsed().as_millis() as u32,
+                canceled: Some(canceled),
+                phase_timings: state.ops.phase_timings(&operation_id),
+            },
+        )
+        .ok();
+    match &result {
+        Ok(_) => state.ops.finish_success(&operation_id),
+        Err(_) if canceled => state.ops.finish_canceled(&operation_id),
+        Err(err) => state.ops.finish_failed(&operation_id, err),
+    }
+    result
+}
+
+#[tauri::command]
+async fn list_startup_items() -> Result<Vec<StartupItem>, String> {
+    Ok(StartupManager::new()?.list_items().await)
+}
+
+#[tauri::command]
+async fn set_startup_item_enabled(
+    state: State<'_, AppState>,
+    path: String,
+    enabled: bool,
+) -> Result<(), String> {
+    if state.ops.inspector_mode() {
+        return Err(INSPECTOR_MODE_REFUSAL.into());
+    }
+    StartupManager::new()?
+        .set_enabled(std::path::Path::new(&path), enabled)
+        .await
+}
+
+#[tauri::command]
+async fn remove_startup_item(state: State<'_, AppState>, path: String) -> Result<(), String> {
+    if state.ops.inspector_mode() {
+        return Err(INSPECTOR_MODE_REFUSAL.into());
+    }
+    StartupManager::new()?
+        .remove(std::path::Path::new(&path))
+        .await
+}
+
+/// Compare hashing throughput of the duplicate detector's algorithms.
+#[tauri::command]
+async fn benchmark_hashing(size_mb: Option<u64>) -> Result<Vec<HashBenchmark>, String> {
+    let size_mb = size_mb.unwrap_or(hashing::DEFAULT_BENCHMARK_MB);
+    tokio::task::spawn_blocking(move || hashing::benchmark_hashing(size_mb))
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Per-extension totals over the last scan's results.
+#[tauri::command]
+async fn get_extension_stats(state: State<'_, AppState>) -> Result<Vec<ExtensionStat>, String> {
+    let files = state
+        .enhanced_file_cleaner
+        .get()
+        .await
+        .read()
+        .await
+        .scanned_files();
+    let (operation_id, token) = state.ops.register(OperationKind::FileScan, true)?;
+    let result =
+        tokio::task::spawn_blocking(move || extension_stats::extension_stats(&files, &token))
+            .await
+            .map_err(|e| e.to_string())
+            .and_then(|result| result);
+    finish_extension_operation(&state, &operation_id, &result);
+    result
+}
+
+/// Largest files with `extension` among the last scan's results.
+#[tauri::command]
+async fn get_extension_files(
+    state: State<'_, AppState>,
+    extension: String,
+    limit: Option<usize>,
+) -> Result<Vec<ExtensionFile>, String> {
+    let files = state
+        .enhanced_file_cleaner
+        .get()
+        .await
+        .read()
+        .await
+        .scanned_files();
+    let limit = limit.unwrap_or(extension_stats::DEFAULT_DRILL_DOWN_LIMIT);
+    let (operation_id, token) = state.ops.register(OperationKind::FileScan, true)?;
+    let result = tokio::task::spawn_blocking(move || {
+        extension_stats::files_with_extension(&files, &extension, limit, &token)
+    })
+    .await
+    .map_err(|e| e.to_string())
+    .and_then(|result| result);
+    finish_extension_operation(&state, &operation_id, &result);
+    result
+}
+
+/// Bytes modified per directory per week under `root`, for spotting
+/// abandoned areas worth archiving.
+#[tauri::command]
+async fn get_activity_heatmap(
+    state: State<'_, AppState>,
+    root: String,
+) -> Result<ActivityHeatmap, String> {
+    let root = match root.strip_prefix("~/") {
+        Some(rest) => dirs::home_dir()
+            .ok_or("Home directory not found")?
+            .join(rest),
+        None => std::path::PathBuf::from(&root),
+    };
+    let index = state.activity_index.clone();
+    let (operation_id, token) = state.ops.register(OperationKind::FileScan, true)?;
+    let _permit = state.ops.scan_sem.acquire().await;
+    let result = tokio::task::spawn_blocking(move || {
+        let mut index = index
+            .lock()
+            .map_err(|_| "Activity index is unavailable".to_string())?;
+        index.heatmap(&root, chrono::Utc::now().timestamp(), &token)
+    })
+    .await
+    .map_err(|e| e.to_string())
+    .and_then(|result| result);
+    match &result {
+        Ok(_) => state.ops.finish_success(&operation_id),
+        Err(err) if err == "cancelled" => state.ops.finish_canceled(&operation_id),
+        Err(err) => state.ops.finish_failed(&operation_id, err),
+    }
+    result
+}
+
+fn finish_extension_operation<T>(state: &AppState, operation_id: &str, result: &Result<T, String>) {
+    match result {
+        Ok(_) => state.ops.finish_success(operation_id),
+        Err(err) if err == "cancelled" => state.ops.finish_canceled(operation_id),
+        Err(err) => state.ops.finish_failed(operation_id, err),
+    }
+}
+
+/// Developer-mode scan: DerivedData, old device support, unavailable
+/// simulators, stale archives and module caches, grouped by tool.
+#[tauri::command]
+async fn scan_developer_junk(
+    app_handle: tauri::AppHandle,
+    state: State<'_, AppState>,
+) -> Result<DeveloperJunkReport, String> {
+    let (operation_id, token) = state.ops.register(OperationKind::FileScan, true)?;
+    let start_time = std::time::Instant::now();
+    app_handle
+        .emit(
+            "operation:start",
+            OperationStartEvent {
+                operation_id: operation_id.clone(),
+                operation_type: "developer_junk_scan".to_string(),
+                estimated_duration: Some(10000),
+            },
+        )
+        .ok();
+
+    let _permit = state.ops.scan_sem.acquire().await;
+    let result = state
+        .enhanced_file_cleaner
+        .get()
+        .await
+        .write()
+        .await
+        .scan_developer_junk(&token)
+        .await;
+    let canceled = matches!(&result, Err(err) if err == "cancelled");
+    let message = match &result {
+        Ok(report) => format!(
+            "Developer scan found {} ({} recommended)",
+            formatting::format_size(report.total_size),
+            formatting::format_size(report.recommended_size)
+        ),
+        Err(_) if canceled => "Developer scan canceled".to_string(),
+        Err(err) => format!("Developer scan failed: {}", err),
+    };
+    app_handle
+        .emit(
+            "operation:complete",
+            OperationCompleteEvent {
+                operation_id: operation_id.clone(),
+                success: result.is_ok(),
+                message,
+                duration: start_time.elapsed().as_millis() as u32,
+                canceled: Some(canceled),
+                phase_timings: state.ops.phase_timings(&operation_id),
+            },
+        )
+        .ok();
+    match &result {
+        Ok(_) => state.ops.finish_success(&operation_id),
+        Err(_) if canceled => state.ops.finish_canceled(&operation_id),
+        Err(err) => state.ops.finish_failed(&operation_id, err),
+    }
+    result
+}
+
+/// Stale node_modules, virtual environments, Cargo targets and build
+/// folders under the development roots, grouped by project.
+#[tauri::command]
+async fn scan_project_artifacts(
+    app_handle: tauri::AppHandle,
+    state: State<'_, AppState>,
+    query: Option<ProjectArtifactQuery>,
+) -> Result<ProjectArtifactReport, String> {
+    let (operation_id, token) = state.ops.register(OperationKind::FileScan, true)?;
+    let start_time = std::time::Instant::now();
+    app_handle
+        .emit(
+            "operation:start",
+            OperationStartEvent {
+                operation_id: operation_id.clone(),
+                operation_type: "project_artifact_scan".to_string(),
+                estimated_duration: None,
+            },
+        )
+        .ok();
+
+    let _permit = state.ops.scan_sem.acquire().await;
+    let result = state
+        .enhanced_file_cleaner
+        .get()
+        .await
+        .write()
+        .await
+        .scan_project_artifacts(query.unwrap_or_default(), &token)
+        .await;
+    let canceled = matches!(&result, Err(err) if err == "cancelled");
+    let message = match &result {
+        Ok(report) => format!(
+            "Found {} of project artifacts in {} projects ({} recommended)",
+            formatting::format_size(report.total_size),
+            formatting::format_count(report.projects.len() as u64),
+            formatting::format_size(report.recommended_size)
+        ),
+        Err(_) if canceled => "Project artifact scan canceled".to_string(),
+        Err(err) => format!("Project artifact scan failed: {}", err),
+    };
+    app_handle
+        .emit(
+            "operation:complete",
+            OperationCompleteEvent {
+                operation_id: operation_id.clone(),
+                success: result.is_ok(),
+                message,
+                duration: start_time.elapsed().as_millis() as u32,
+                canceled: Some(canceled),
+                phase_timings: state.ops.phase_timings(&operation_id),
+            },
+        )
+        .ok();
+    match &result {
+        Ok(_) => state.ops.finish_success(&operation_id),
+        Err(_) if canceled => state.ops.finish_canceled(&operation_id),
+        Err(err) => state.ops.finish_failed(&operation_id, err),
+    }
+    result
+}
+
+/// Find duplicate files within folders the user picked, e.g. ~/Downloads.
+/// Each group is emitted as a `duplicates:found` event as soon as it is
+/// confirmed.
+#[tauri::command]
+async fn scan_duplicates_in_paths(
+    app_handle: tauri::AppHandle,
+    state: State<'_, AppState>,
+    paths: Vec<String>,
+) -> Result<DuplicateScanResult, String> {
+    let (operation_id, token) = state.ops.register(OperationKind::FileScan, true)?;
+    let start_time = std::time::Instant::now();
+    app_handle
+        .emit(
+            "operation:start",
+            OperationStartEvent {
+                operation_id: operation_id.clone(),
+                operation_type: "duplicate_scan".to_string(),
+                estimated_duration: None,
+            },
+        )
+        .ok();
+
+    let _permit = state.ops.scan_sem.acquire().await;
+    let roots: Vec<std::path::PathBuf> = paths.iter().map(std::path::PathBuf::from).collect();
+    let app_for_cb = app_handle.clone();
+    let op_id_for_cb = operation_id.clone();
+    let on_group = move |group: &DuplicateGroup| {
+        app_for_cb
+            .emit(
+                "duplicates:found",
+                DuplicateFoundEvent {
+                    operation_id: op_id_for_cb.clone(),
+                    group: group.clone(),
+                },
+            )
+            .ok();
+    };
+    let result = state
+        .enhanced_file_cleaner
+        .get()
+        .await
+        .write()
+        .await
+        .scan_duplicates_in_paths(&roots, &token, on_group)
+        .await;
+    if result.is_ok() {
+        record_usage(&state, UsageFeature::DuplicateScan).await;
+    }
+    let canceled = matches!(&result, Err(err) if err == "cancelled");
+    let message = match &result {
+        Ok(scan) => format!(
+            "Found {} duplicate sets in {} files{}",
+            formatting::format_count(scan.groups.len() as u64),
+            formatting::format_count(scan.analyzed_files as u64),
+            if scan.truncated { " (partial)" } else { "" }
+        ),
+        Err(_) if canceled => "Duplicate scan canceled".to_string(),
+        Err(err) => format!("Duplicate scan failed: {}", err),
+    };
+    app_handle
+        .emit(
+            "operation:complete",
+            OperationCompleteEvent {
+                operation_id: operation_id.clone(),
+                success: result.is_ok(),
+                message,
+                duration: start_time.elapsed().as_millis() as u32,
+                canceled: Some(canceled),
+                phase_timings: state.ops.phase_timings(&operation_id),
+            },
+        )
+        .ok();
+    match &result {
+        Ok(_) => state.ops.finish_success(&operation_id),
+        Err(_) if canceled => state.ops.finish_canceled(&operation_id),
+        Err(err) => state.ops.finish_failed(&operation_id, err),
+    }
+    result
+}
+
+/// Find files of at least `min_size_mb` untouched for `min_age_days` under
+/// `roots`, or the home folder when none are given. Each match is emitted as
+/// a `large_files:found` event as soon as the walk reaches it.
+#[tauri::command]
+async fn find_large_files(
+    app_handle: tauri::AppHandle,
+    state: State<'_, AppState>,
+    min_size_mb: u64,
+    min_age_days: u64,
+    roots: Option<Vec<String>>,
+) -> Result<LargeFileScan, String> {
+    let (operation_id, token) = state.ops.register(OperationKind::FileScan, true)?;
+    let start_time = std::time::Instant::now();
+    app_handle
+        .emit(
+            "operation:start",
+            OperationStartEvent {
+                operation_id: operation_id.clone(),
+                operation_type: "large_file_search".to_string(),
+                estimated_duration: None,
+            },
+        )
+        .ok();
+
+    let _permit = state.ops.scan_sem.acquire().await;
+    let roots = match roots.filter(|roots| !roots.is_empty()) {
+        Some(roots) => roots.iter().map(std::path::PathBuf::from).collect(),
+        None => large_files::default_roots(),
+    };
+    emit_progress(
+        &app_handle,
+        &state.ops,
+        ProgressEvent {
+            operation_id: operation_id.clone(),
+            progress: 0.0,
+            message: "Searching for large files…".to_string(),
+            stage: "discovery".to_string(),
+            can_cancel: true,
+            eta_ms: None,
+            throughput: None,
+        },
+    );
+    let query = LargeFileQuery {
+        roots,
+        min_size: min_size_mb.saturating_mul(1024 * 1024),
+        min_age_days,
+    };
+    let app_for_cb = app_handle.clone();
+    let op_id_for_cb = operation_id.clone();
+    let on_match = move |file: &LargeFile| {
+        app_for_cb
+            .emit(
+                "large_files:found",
+                LargeFileFoundEvent {
+                    operation_id: op_id_for_cb.clone(),
+                    file: file.clone(),
+                },
+            )
+            .ok();
+    };
+    let result = state
+        .enhanced_file_cleaner
+        .get()
+        .await
+        .write()
+        .await
+        .find_large_files(query, &token, on_match)
+        .await;
+    let canceled = matches!(&result, Err(err) if err == "cancelled");
+    let message = match &result {
+        Ok(scan) => format!(
+            "Found {} large files ({}){}",
+            formatting::format_count(scan.files.len() as u64),
+            formatting::format_size(scan.total_size),
+            if scan.truncated { " (partial)" } else { "" }
+        ),
+        Err(_) if canceled => "Large file search canceled".to_string(),
+        Err(err) => format!("Large file search failed: {}", err),
+    };
+    app_handle
+        .emit(
+            "operation:complete",
+            OperationCompleteEvent {
+                operation_id: operation_id.clone(),
+                success: result.is_ok(),
+                message,
+                duration: start_time.elapsed().as_millis() as u32,
+                canceled: Some(canceled),
+                phase_timings: state.ops.phase_timings(&operation_id),
+            },
+        )
+        .ok();
+    match &result {
+        Ok(_) => state.ops.finish_success(&operation_id),
+        Err(_) if canceled => state.ops.finish_canceled(&operation_id),
+        Err(err) => state.ops.finish_failed(&operation_id, err),
+    }
+    result
+}
+
+/// Keep one copy of a duplicate or similar-image group, chosen by `strategy`,
+/// and remove the rest through the enhanced cleaning pipeline.
+#[tauri::command]
+async fn resolve_duplicates(
+    app_handle: tauri::AppHandle,
+    state: State<'_, AppState>,
+    group_id: String,
+    strategy: KeepStrategy,
+    allow_low_safety: Option<bool>,
+) -> Result<CleaningResult, String> {
+    let resolution = state
+        .enhanced_file_cleaner
+        .get()
+        .await
+        .write()
+        .await
+        .prepare_duplicate_resolution(&group_id, &strategy)
+        .await?;
+    if resolution.remove.is_empty() {
+        return Err("No other copies left to remove".into());
+    }
+    let result = clean_files_enhanced(
+        app_handle.clone(),
+        app_handle.state(),
+        resolution.remove,
+        allow_low_safety,
+    )
+    .await?;
+    if !result.dry_run {
+        state
+            .enhanced_file_cleaner
+            .get()
+            .await
+            .write()
+            .await
+            .settle_duplicate_group(&group_id, &result.deleted_files);
+        record_usage(&state, UsageFeature::DuplicateResolution).await;
+    }
+    Ok(result)
+}
+
+/// Mounted volumes a scan can be scoped to.
+#[tauri::command]
+async fn list_volumes() -> Result<Vec<VolumeInfo>, String> {
+    tokio::task::spawn_blocking(volumes::list_volumes)
+        .await
+        .map_err(|e| format!("Volume listing task failed: {}", e))
+}
+
+#[tauri::command]
+async fn get_volume_scope(state: State<'_, AppState>) -> Result<VolumeScope, String> {
+    Ok(state
+        .enhanced_file_cleaner
+        .get()
+        .await
+        .read()
+        .await
+        .volume_scope())
+}
+
+/// Restrict the next system scans to the given volumes.
+#[tauri::command]
+async fn set_volume_scope(state: State<'_, AppState>, scope: VolumeScope) -> Result<(), String> {
+    state
+        .enhanced_file_cleaner
+        .get()
+        .await
+        .write()
+        .await
+        .set_volume_scope(scope);
+    Ok(())
+}
+
+/// Mounted volumes other than the startup disk, with whether system scans
+/// include them and why one would be skipped.
+#[tauri::command]
+async fn list_external_volumes(state: State<'_, AppState>) -> Result<Vec<ExternalVolume>, String> {
+    let opted_in = state.external_volumes.read().await.list();
+    tokio::task::spawn_blocking(move || external_volumes::list_external_volumes(&opted_in))
+        .await
+        .map_err(|e| format!("Volume listing task failed: {}", e))
+}
+
+/// Include an external volume in system scans, or stop doing so.
+#[tauri::command]
+async fn set_external_volume_opt_in(
+    state: State<'_, AppState>,
+    mount: String,
+    opted_in: bool,
+) -> Result<(), String> {
+    state
+        .external_volumes
+        .write()
+        .await
+        .set_opted_in(&mount, opted_in)
+}
+
+/// Old installers in Downloads, with their download URL and whether the
+/// same file can still be fetched from it.
+#[tauri::command]
+async fn scan_installer_sources(
+    app_handle: tauri::AppHandle,
+    state: State<'_, AppState>,
+) -> Result<InstallerSourceReport, String> {
+    let (operation_id, token) = state.ops.register(OperationKind::FileScan, true)?;
+    let start_time = std::time::Instant::now();
+    app_handle
+        .emit(
+            "operation:start",
+            OperationStartEvent {
+                operation_id: operation_id.clone(),
+                operation_type: "installer_source_scan".to_string(),
+                estimated_duration: Some(10000),
+            },
+        )
+        .ok();
+
+    let _permit = state.ops.scan_sem.acquire().await;
+    let result = installer_sources::scan_installer_sources(&token).await;
+    let canceled = matches!(&result, Err(err) if err == "cancelled");
+    let message = match &result {
+        Ok(report) => format!(
+            "Found {} of old installers, {} re-downloadable",
+            formatting::format_size(report.total_size),
+            formatting::format_size(report.redownloadable_size)
+        ),
+        Err(_) if canceled => "Installer scan canceled".to_string(),
+        Err(err) => format!("Installer scan failed: {}", err),
+    };
+    app_handle
+        .emit(
+            "operation:complete",
+            OperationCompleteEvent {
+                operation_id: operation_id.clone(),
+                success: result.is_ok(),
+                message,
+                duration: start_time.elapsed().as_millis() as u32,
+                canceled: Some(canceled),
+                phase_timings: state.ops.phase_timings(&operation_id),
+            },
+        )
+        .ok();
+    match &result {
+        Ok(_) => state.ops.finish_success(&operation_id),
+        Err(_) if canceled => state.ops.finish_canceled(&operation_id),
+        Err(err) => state.ops.finish_failed(&operation_id, err),
+    }
+    result
+}
+
+/// Dangling images, stopped containers, unused volumes and builder cache
+/// for each installed container engine.
+#[tauri::command]
+async fn scan_docker_artifacts(
+    app_handle: tauri::AppHandle,
+    state: State<'_, AppState>,
+) -> Result<ContainerScanReport, String> {
+    let (operation_id, token) = state.ops.register(OperationKind::FileScan, true)?;
+    let start_time = std::time::Instant::now();
+    app_handle
+        .emit(
+            "operation:start",
+            OperationStartEvent {
+                operation_id: operation_id.clone(),
+                operation_type: "container_scan".to_string(),
+                estimated_duration: Some(5000),
+            },
+        )
+        .ok();
+
+    let _permit = state.ops.scan_sem.acquire().await;
+    let progress_cb = container_progress(&app_handle, &state.ops, &operation_id);
+    let result = container_cleaner::scan_container_artifacts(&token, Some(&progress_cb)).await;
+    let message = match &result {
+        Ok(report) => format!(
+            "Container scan found {} reclaimable",
+            formatting::format_size(report.reclaimable)
+        ),
+        Err(err) => format!("Container scan failed: {}", err),
+    };
+    finish_container_operation(
+        &app_handle,
+        &state,
+        &operation_id,
+        start_time,
+        &result,
+        message,
+    );
+    result
+}
+
+/// Prune the selected artifact kinds, on one engine or all reachable ones.
+#[tauri::command]
+async fn prune_docker_artifacts(
+    app_handle: tauri::AppHandle,
+    state: State<'_, AppState>,
+    kinds: Vec<ArtifactKind>,
+    engine: Option<ContainerEngine>,
+) -> Result<PruneResult, String> {
+    if state.ops.inspector_mode() {
+        let scan = container_cleaner::scan_container_artifacts(&Default::default(), None).await?;
+        return container_cleaner::preview_container_prune(&scan, engine, &kinds);
+    }
+    let _instance_lock = instance_lock::acquire_destructive_lock("prune_docker_artifacts")?;
+    let (operation_id, token) = state.ops.register(OperationKind::FileClean, true)?;
+    let start_time = std::time::Instant::now();
+    app_handle
+        .emit(
+            "operation:start",
+            OperationStartEvent {
+                operation_id: operation_id.clone(),
+                operation_type: "container_prune".to_string(),
+                estimated_duration: Some(15000),
+            },
+        )
+        .ok();
+
+    let _permit = state.ops.clean_sem.acquire().await;
+    let progress_cb = container_progress(&app_handle, &state.ops, &operation_id);
+    let result =
+        container_cleaner::prune_container_artifacts(engine, &kinds, &token, Some(&progress_cb))
+            .await;
+    let message = match &result {
+        Ok(pruned) => {
+            state
+                .session
+                .record_cleanup(pruned.freed, pruned.removed as u64);
+            state.ops.record_outcome(
+                &operation_id,
+                OperationOutcome::new(pruned.removed as u64, pruned.freed)
+                    .with_failures(pruned.steps.iter().filter_map(|step| step.error.clone())),
+            );
+            let failed = pruned
+                .steps
+                .iter()
+                .filter(|step| step.error.is_some())
+                .count();
+            let mut message = format!(
+                "Pruned {} container artifacts, {} freed",
+                formatting::format_count(pruned.removed as u64),
+                formatting::format_size(pruned.freed)
+            );
+            if failed > 0 {
+                message.push_str(&format!(" ({} step(s) failed)", failed));
+            }
+            message
+        }
+        Err(err) => format!("Container prune failed: {}", err),
+    };
+    finish_container_operation(
+        &app_handle,
+        &state,
+        &operation_id,
+        start_time,
+        &result,
+        message,
+    );
+    result
+}
+
+/// Flush DNS, renew the DHCP lease and/or restart mDNSResponder behind one
+/// administrator prompt. The lease is renewed on `interface`, or on the
+/// interface carrying the default route.
+#[tauri::command]
+async fn run_network_maintenance(
+    app_handle: tauri::AppHandle,
+    state: State<'_, AppState>,
+    actions: Vec<NetworkAction>,
+    interface: Option<String>,
+) -> Result<NetworkMaintenanceResult, String> {
+    if state.ops.inspector_mode() {
+        return network_tools::preview_network_maintenance(&actions, interface).await;
+    }
+    let (operation_id, token) = state
+        .ops
+        .register(OperationKind::NetworkMaintenance, true)?;
+    let start_time = std::time::Instant::now();
+    app_handle
+        .emit(
+            "operation:start",
+            OperationStartEvent {
+                operation_id: operation_id.clone(),
+                operation_type: "network_maintenance".to_string(),
+                estimated_duration: Some(5000),
+            },
+        )
+        .ok();
+
+    let _permit = state.ops.opt_sem.acquire().await;
+    let progress_cb = network_progress(&app_handle, &state.ops, &operation_id);
+    let result =
+        network_tools::run_network_maintenance(&actions, interface, &token, Some(&progress_cb))
+            .await;
+    let message = match &result {
+        Ok(maintenance) => {
+            let failures: Vec<String> = maintenance
+                .steps
+                .iter()
+                .filter_map(|step| step.error.clone())
+                .collect();
+            state.ops.record_outcome(
+                &operation_id,
+                OperationOutcome::new(0, 0).with_failures(failures.iter().cloned()),
+            );
+            if failures.is_empty() {
+                format!("Network maintenance finished ({} actions)", actions.len())
+            } else {
+                format!("Network maintenance finished: {}", failures.join(", "))
+            }
+        }
+        Err(err) => format!("Network maintenance failed: {}", err),
+    };
+    finish_network_operation(
+        &app_handle,
+        &state,
+        &operation_id,
+        start_time,
+        &result,
+        message,
+    );
+    result
+}
+
+/// Bytes, packets and errors per interface since boot.
+#[tauri::command]
+async fn get_interface_statistics() -> Result<Vec<InterfaceStats>, String> {
+    network_tools::interface_statistics().await
+}
+
+/// Open TCP and UDP sockets with the process that owns each.
+#[tauri::command]
+async fn list_network_connections() -> Result<Vec<NetworkConnection>, String> {
+    network_tools::active_connections().await
+}
+
+/// Time a DNS lookup and ping a few well-known hosts.
+#[tauri::command]
+async fn run_connectivity_test(
+    app_handle: tauri::AppHandle,
+    state: State<'_, AppState>,
+) -> Result<ConnectivityReport, String> {
+    let (operation_id, token) = state
+        .ops
+        .register(OperationKind::NetworkDiagnostics, true)?;
+    let start_time = std::time::Instant::now();
+    app_handle
+        .emit(
+            "operation:start",
+            OperationStartEvent {
+                operation_id: operation_id.clone(),
+                operation_type: "connectivity_test".to_string(),
+                estimated_duration: Some(15000),
+            },
+        )
+        .ok();
+
+    let progress_cb = network_progress(&app_handle, &state.ops, &operation_id);
+    let result = network_tools::connectivity_test(&token, Some(&progress_cb)).await;
+    let message = match &result {
+        Ok(report) if report.online => "Connectivity test finished: online".to_string(),
+        Ok(_) => "Connectivity test finished: no host answered".to_string(),
+        Err(err) => format!("Connectivity test failed: {}", err),
+    };
+    finish_network_operation(
+        &app_handle,
+        &state,
+        &operation_id,
+        start_time,
+        &result,
+        message,
+    );
+    result
+}
+
+fn network_progress(
+    app_handle: &tauri::AppHandle,
+    ops: &OperationRegistry,
+    operation_id: &str,
+) -> impl Fn(f32, &str) + Send + Sync {
+    let app_handle = app_handle.clone();
+    let ops = ops.clone();
+    let operation_id = operation_id.to_string();
+    move |progress: f32, message: &str| {
+        emit_progress(
+            &app_handle,
+            &ops,
+            ProgressEvent {
+                operation_id: operation_id.clone(),
+                progress,
+                message: message.to_string(),
+                stage: "network".to_string(),
+                can_cancel: true,
+                eta_ms: None,
+                throughput: None,
+            },
+        );
+    }
+}
+
+fn finish_network_operation<T>(
+    app_handle: &tauri::AppHandle,
+    state: &AppState,
+    operation_id: &str,
+    start_time: std::time::Instant,
+    result: &Result<T, String>,
+    message: String,
+) {
+    let canceled = matches!(result, Err(err) if err == "cancelled");
+    app_handle
+        .emit(
+            "operation:complete",
+            OperationCompleteEvent {
+                operation_id: operation_id.to_string(),
+                success: result.is_ok(),
+                message: if canceled {
+                    "Network operation canceled".to_string()
+                } else {
+                    message
+                },
+                duration: start_time.elapsed().as_millis() as u32,
+                canceled: Some(canceled),
+                phase_timings: state.ops.phase_timings(operation_id),
+            },
+        )
+        .ok();
+    match result {
+        Ok(_) => state.ops.finish_success(operation_id),
+        Err(_) if canceled => state.ops.finish_canceled(operation_id),
+        Err(err) => state.ops.finish_failed(operation_id, err),
+    }
+}
+
+/// Maintenance tasks and whether each applies to this macOS.
+#[tauri::command]
+async fn list_maintenance_tasks() -> Result<Vec<MaintenanceTaskInfo>, String> {
+    tokio::task::spawn_blocking(system_maintenance::list_maintenance_tasks)
+        .await
+        .map_err(|e| format!("Maintenance task listing failed: {}", e))
+}
+
+/// Run one maintenance task as its own cancellable operation.
+#[tauri::command]
+async fn run_maintenance_task(
+    app_handle: tauri::AppHandle,
+    state: State<'_, AppState>,
+    task: MaintenanceTask,
+) -> Result<MaintenanceResult, String> {
+    maintenance_operation(&app_handle, &state, &[task]).await
+}
+
+/// Run the selected maintenance tasks, asking for the administrator
+/// password once for all of them.
+#[tauri::command]
+async fn run_maintenance(
+    app_handle: tauri::AppHandle,
+    state: State<'_, AppState>,
+    tasks: Vec<MaintenanceTask>,
+) -> Result<MaintenanceResult, String> {
+    maintenance_operation(&app_handle, &state, &tasks).await
+}
+
+async fn maintenance_operation(
+    app_handle: &tauri::AppHandle,
+    state: &AppState,
+    tasks: &[MaintenanceTask],
+) -> Result<MaintenanceResult, String> {
+    if state.ops.inspector_mode() {
+        return system_maintenance::preview_maintenance(tasks);
+    }
+    let _instance_lock = instance_lock::acquire_destructive_lock("run_maintenance")?;
+    let (operation_id, token) = state.ops.register(OperationKind::SystemMaintenance, true)?;
+    let start_time = std::time::Instant::now();
+    app_handle
+        .emit(
+            "operation:start",
+            OperationStartEvent {
+                operation_id: operation_id.clone(),
+                operation_type: match tasks {
+                    [task] => format!("maintenance_{}", task.key()),
+                    _ => "maintenance".to_string(),
+                },
+                estimated_duration: None,
+            },
+        )
+        .ok();
+
+    let _permit = state.ops.opt_sem.acquire().await;
+    let app = app_handle.clone();
+    let ops = state.ops.clone();
+    let progress_id = operation_id.clone();
+    let progress_cb = move |progress: f32, message: &str| {
+        emit_progress(
+            &app,
+            &ops,
             ProgressEvent {
-                operation_id: operation_id.clone(),
-                progress: 15.0,
-                message: "Requesting administrator privileges...".to_string(),
-                stage: "auth".to_string(),
+                operation_id: progress_id.clone(),
+                progress,
+                message: message.to_string(),
+                stage: "maintenance".to_string(),
                 can_cancel: true,
                 eta_ms: None,
                 throughput: None,
             },
+        );
+    };
+    let result = system_maintenance::run_maintenance(tasks, &token, Some(&progress_cb)).await;
+    let canceled = matches!(&result, Err(err) if err == "cancelled");
+    let message = match &result {
+        Ok(maintenance) => {
+            let failures = maintenance.failures();
+            state.ops.record_outcome(
+                &operation_id,
+                OperationOutcome::new(0, 0).with_failures(failures.iter().cloned()),
+            );
+            if failures.is_empty() {
+                "Maintenance finished".to_string()
+            } else {
+                format!("Maintenance finished: {}", failures.join(", "))
+            }
+        }
+        Err(_) if canceled => "Maintenance canceled".to_string(),
+        Err(err) => format!("Maintenance failed: {}", err),
+    };
+    app_handle
+        .emit(
+            "operation:complete",
+            OperationCompleteEvent {
+                operation_id: operation_id.clone(),
+                success: result.is_ok(),
+                message,
+                duration: start_time.elapsed().as_millis() as u32,
+                canceled: Some(canceled),
+                phase_timings: state.ops.phase_timings(&operation_id),
+            },
         )
         .ok();
+    match &result {
+        Ok(_) => state.ops.finish_success(&operation_id),
+        Err(_) if canceled => state.ops.finish_canceled(&operation_id),
+        Err(err) => state.ops.finish_failed(&operation_id, err),
+    }
+    result
+}
 
-    tokio::time::sleep(tokio::time::Duration::from_millis(200)).await;
+/// Emit a progress event, checkpointing its stage for the phase timings.
+fn emit_progress(app_handle: &tauri::AppHandle, ops: &OperationRegistry, event: ProgressEvent) {
+    ops.checkpoint(&event.operation_id, &event.stage);
+    app_handle.emit("progress:update", event).ok();
+}
 
-    app_handle
-        .emit(
-            "progress:update",
+fn container_progress(
+    app_handle: &tauri::AppHandle,
+    ops: &OperationRegistry,
+    operation_id: &str,
+) -> impl Fn(f32, &str) + Send + Sync {
+    let app_handle = app_handle.clone();
+    let ops = ops.clone();
+    let operation_id = operation_id.to_string();
+    move |progress: f32, message: &str| {
+        emit_progress(
+            &app_handle,
+            &ops,
             ProgressEvent {
                 operation_id: operation_id.clone(),
-                progress: 35.0,
-                message: "Purging disk caches...".to_string(),
-                stage: "disk_cache".to_string(),
+                progress,
+                message: message.to_string(),
+                stage: "containers".to_string(),
                 can_cancel: true,
                 eta_ms: None,
                 throughput: None,
             },
+        );
+    }
+}
+
+fn finish_container_operation<T>(
+    app_handle: &tauri::AppHandle,
+    state: &AppState,
+    operation_id: &str,
+    start_time: std::time::Instant,
+    result: &Result<T, String>,
+    message: String,
+) {
+    let canceled = matches!(result, Err(err) if err == "cancelled");
+    app_handle
+        .emit(
+            "operation:complete",
+            OperationCompleteEvent {
+                operation_id: operation_id.to_string(),
+                success: result.is_ok(),
+                message: if canceled {
+                    "Container operation canceled".to_string()
+                } else {
+                    message
+                },
+                duration: start_time.elapsed().as_millis() as u32,
+                canceled: Some(canceled),
+                phase_timings: state.ops.phase_timings(&operation_id),
+            },
         )
         .ok();
+    match result {
+        Ok(_) => state.ops.finish_success(operation_id),
+        Err(_) if canceled => state.ops.finish_canceled(operation_id),
+        Err(err) => state.ops.finish_failed(operation_id, err),
+    }
+}
+
+#[tauri::command]
+async fn get_inspector_mode(state: State<'_, AppState>) -> Result<bool, String> {
+    Ok(state.ops.inspector_mode())
+}
 
+/// Toggle inspector mode, in which destructive commands only report what
+/// they would do. It cannot be turned off while audit mode is on.
+#[tauri::command]
+async fn set_inspector_mode(
+    app_handle: tauri::AppHandle,
+    state: State<'_, AppState>,
+    enabled: bool,
+) -> Result<(), String> {
+    let audit = state.audit_mode.read().await.enabled;
+    if audit && !enabled {
+        return Err("Audit mode is on; turn it off to leave inspector mode".into());
+    }
+    state.ops.set_inspector_mode(enabled);
     app_handle
         .emit(
-            "progress:update",
-            ProgressEvent {
-                operation_id: operation_id.clone(),
-                progress: 55.0,
-                message: "Clearing DNS and network caches...".to_string(),
-                stage: "network_cache".to_string(),
-                can_cancel: true,
-                eta_ms: None,
-                throughput: None,
+            "mode:changed",
+            ModeChangedEvent {
+                inspector: state.ops.inspector_mode(),
+                audit,
             },
         )
         .ok();
+    Ok(())
+}
+
+#[tauri::command]
+async fn get_audit_mode(state: State<'_, AppState>) -> Result<bool, String> {
+    Ok(state.audit_mode.read().await.enabled)
+}
 
+/// Toggle audit mode: inspector mode that is remembered across launches,
+/// so cleaning, Trash and memory commands keep reporting what they would
+/// do until it is turned off.
+#[tauri::command]
+async fn set_audit_mode(
+    app_handle: tauri::AppHandle,
+    state: State<'_, AppState>,
+    enabled: bool,
+) -> Result<(), String> {
+    state.audit_mode.write().await.set_enabled(enabled)?;
+    state.ops.set_audit_mode(enabled);
     app_handle
         .emit(
-            "progress:update",
-            ProgressEvent {
-                operation_id: operation_id.clone(),
-                progress: 75.0,
-                message: "Optimizing memory compression...".to_string(),
-                stage: "memory_compression".to_string(),
-                can_cancel: false,
-                eta_ms: None,
-                throughput: None,
+            "mode:changed",
+            ModeChangedEvent {
+                inspector: state.ops.inspector_mode(),
+                audit: enabled,
             },
         )
         .ok();
+    Ok(())
+}
 
-    // Perform the actual admin optimization
-    let _permit = state.ops.opt_sem.acquire().await;
-    let result = optimizer.optimize_memory_with_admin_cancel(&token).await;
+/// Totals for the past seven days, including the session in progress.
+#[tauri::command]
+async fn get_weekly_digest(state: State<'_, AppState>) -> Result<WeeklyDigest, String> {
+    let current = state.session.snapshot();
+    Ok(state
+        .sessions
+        .read()
+        .await
+        .weekly_digest(chrono::Utc::now().timestamp(), Some(&current)))
+}
 
-    let duration = start_time.elapsed().as_millis() as u32;
+/// What the health score is computed from, over the week before `now`.
+async fn health_inputs(state: &AppState, now: i64) -> HealthInputs {
+    let from = now - HistoryRange::Week.secs();
+    let snapshot = state.metrics_sampler.latest_snapshot().await;
+    let disk_free_percent = snapshot.disks.value.as_ref().and_then(|disks| {
+        disks
+            .iter()
+            .find(|disk| disk.is_system)
+            .or_else(|| disks.first())
+            .filter(|disk| disk.total_space > 0)
+            .map(|disk| disk.available_space as f64 * 100.0 / disk.total_space as f64)
+    });
+    let memory_pressure = state
+        .metrics_history
+        .read()
+        .await
+        .query(HistoryRange::Week, None, now)
+        .iter()
+        .filter_map(|point| point.memory_pressure)
+        .collect();
+    let login_items = match StartupManager::new() {
+        Ok(manager) => Some(
+            manager
+                .list_items()
+                .await
+                .iter()
+                .filter(|item| item.enabled)
+                .count(),
+        ),
+        Err(err) => {
+            log::warn!("Failed to list login items: {}", err);
+            None
+        }
+    };
+    let cache_samples = state.cache_sizes.read().await.since(from);
 
-    match &result {
-        Ok(_) => {
-            app_handle
-                .emit(
-                    "progress:update",
-                    ProgressEvent {
-                        operation_id: operation_id.clone(),
-                        progress: 100.0,
-                        message: "Deep clean optimization completed successfully".to_string(),
-                        stage: "complete".to_string(),
-                        can_cancel: false,
-                        eta_ms: Some(0),
-                        throughput: None,
-                    },
-                )
-                .ok();
+    let filter = HistoryFilter {
+        since_ms: Some(from * 1000),
+        ..Default::default()
+    };
+    let records = state
+        .ops
+        .history()
+        .query(
+            &filter,
+            Pagination {
+                offset: 0,
+                limit: usize::MAX,
+            },
+        )
+        .records;
+    let records: Vec<&OperationRecord> = records
+        .iter()
+        .filter(|record| record.kind != OperationKind::DashboardRefresh)
+        .collect();
+    let count = |status: OperationStatus| {
+        records
+            .iter()
+            .filter(|record| record.status == status)
+            .count()
+    };
+    let operations = OperationTally {
+        completed: count(OperationStatus::Completed),
+        failed: count(OperationStatus::Failed),
+        canceled: count(OperationStatus::Canceled),
+    };
+    let recent_failures = records
+        .iter()
+        .filter(|record| record.status == OperationStatus::Failed)
+        .map(|record| FailedOperation {
+            operation: record.kind.label().to_string(),
+            started_at_ms: record.started_at_ms,
+            error: record.error.clone(),
+        })
+        .collect();
 
-            app_handle
-                .emit(
-                    "operation:complete",
-                    OperationCompleteEvent {
-                        operation_id: operation_id.clone(),
-                        success: true,
-                        message: "Deep clean optimization completed".to_string(),
-                        duration,
-                        canceled: Some(false),
-                    },
-                )
-                .ok();
-            state.ops.finish_success(&operation_id);
+    HealthInputs {
+        disk_free_percent,
+        memory_pressure,
+        login_items,
+        cache_samples,
+        operations,
+        recent_failures,
+    }
+}
+
+/// Composite 0-100 score from disk space, memory pressure, login items,
+/// cache growth and failed operations over the past week.
+#[tauri::command]
+async fn get_health_score(state: State<'_, AppState>) -> Result<HealthScore, String> {
+    let inputs = health_inputs(&state, chrono::Utc::now().timestamp()).await;
+    Ok(health_score::compute_health_score(&inputs))
+}
+
+/// The past week's health, activity, failures and trends in one digest.
+#[tauri::command]
+async fn generate_weekly_summary(state: State<'_, AppState>) -> Result<WeeklySummary, String> {
+    let now = chrono::Utc::now().timestamp();
+    let inputs = health_inputs(&state, now).await;
+    let current = state.session.snapshot();
+    let activity = state
+        .sessions
+        .read()
+        .await
+        .weekly_digest(now, Some(&current));
+    Ok(health_score::generate_weekly_summary(
+        now, &inputs, activity,
+    ))
+}
+
+/// Count a completed run for the opt-in usage summary.
+async fn record_usage(state: &AppState, feature: UsageFeature) {
+    if let Err(err) = state.usage.write().await.record_feature(feature) {
+        log::warn!("Failed to record usage: {}", err);
+    }
+}
+
+/// Count a completed cleaning run and the bytes it cleaned per category.
+async fn record_cleaned_usage<'a>(
+    state: &AppState,
+    feature: UsageFeature,
+    cleaned: impl Iterator<Item = &'a CleanableFile>,
+) {
+    let cleaned: Vec<(&str, u64)> = cleaned
+        .map(|file| (file.category.as_str(), file.size))
+        .collect();
+    let mut usage = state.usage.write().await;
+    let recorded = usage
+        .record_feature(feature)
+        .and_then(|_| usage.record_cleaned(cleaned.iter().copied()));
+    if let Err(err) = recorded {
+        log::warn!("Failed to record usage: {}", err);
+    }
+    let now = chrono::Utc::now().timestamp();
+    if let Err(err) = state.telemetry.write().await.record_cleaned(now, cleaned) {
+        log::warn!("Failed to record telemetry: {}", err);
+    }
+}
+
+/// Add the scan `cleaner` just finished to the telemetry history.
+async fn record_scan_telemetry(state: &AppState, cleaner: &EnhancedFileCleaner) {
+    let Some(scan) = cleaner.last_scan_telemetry() else {
+        return;
+    };
+    let now = chrono::Utc::now().timestamp();
+    if let Err(err) = state.telemetry.write().await.record_scan(now, &scan) {
+        log::warn!("Failed to record telemetry: {}", err);
+    }
+}
+
+#[tauri::command]
+async fn get_usage_opt_in(state: State<'_, AppState>) -> Result<bool, String> {
+    Ok(state.usage.read().await.opted_in())
+}
+
+/// Start or stop collecting usage metrics; stopping deletes what was collected.
+#[tauri::command]
+async fn set_usage_opt_in(
+    app_handle: tauri::AppHandle,
+    state: State<'_, AppState>,
+    enabled: bool,
+) -> Result<(), String> {
+    set_telemetry_opt_in(&state, enabled).await?;
+    emit_settings_changed(&app_handle, &state).await;
+    Ok(())
+}
+
+/// Apply the telemetry opt-in to the usage metrics and the local history.
+async fn set_telemetry_opt_in(state: &AppState, enabled: bool) -> Result<(), String> {
+    let now = chrono::Utc::now().timestamp();
+    state.usage.write().await.set_opted_in(enabled, now)?;
+    state.telemetry.write().await.set_opted_in(enabled, now)
+}
+
+/// Scans, deselection rates, bytes freed per category and analysis cut
+/// short over the days `query` covers, grouped by day, week or month.
+/// Fails while telemetry is off.
+#[tauri::command]
+async fn get_telemetry_insights(
+    state: State<'_, AppState>,
+    query: Option<TelemetryQuery>,
+) -> Result<TelemetryInsights, String> {
+    state
+        .telemetry
+        .read()
+        .await
+        .insights(chrono::Utc::now().timestamp(), &query.unwrap_or_default())
+}
+
+/// Write the anonymized usage summary to `path`, or to Downloads, and
+/// return where it went.
+#[tauri::command]
+async fn export_usage_summary(
+    state: State<'_, AppState>,
+    path: Option<String>,
+) -> Result<String, String> {
+    let now = chrono::Utc::now().timestamp();
+    let summary = state.usage.write().await.export(now)?;
+    let path = match path {
+        Some(path) => std::path::PathBuf::from(path),
+        None => usage::default_export_path(now)
+            .ok_or("Could not find a folder to export the usage summary to")?,
+    };
+    summary.write_to(&path)?;
+    Ok(path.to_string_lossy().to_string())
+}
+
+/// Write the latest scan report to `destination`, or to Downloads, as JSON
+/// or CSV, and return where it went. The enhanced scan's report, with its
+/// safety analysis, is preferred to the basic scan's.
+#[tauri::command]
+async fn export_report(
+    state: State<'_, AppState>,
+    format: ReportFormat,
+    destination: Option<String>,
+) -> Result<String, String> {
+    let now = chrono::Utc::now();
+    let path = match destination {
+        Some(path) => std::path::PathBuf::from(path),
+        None => report_export::default_export_path(format, now)
+            .ok_or("Could not find a folder to export the report to")?,
+    };
+    let enhanced = state
+        .enhanced_file_cleaner
+        .get()
+        .await
+        .read()
+        .await
+        .latest_report();
+    match enhanced {
+        Some(report) => {
+            report_export::export_report(ScanReport::Enhanced(&report), format, &path, now)?
         }
-        Err(err) => {
-            let canceled = err.contains("cancelled");
-            app_handle
-                .emit(
-                    "operation:complete",
-                    OperationCompleteEvent {
-                        operation_id: operation_id.clone(),
-                        success: false,
-                        message: format!("Deep clean optimization failed: {}", err),
-                        duration,
-                        canceled: Some(canceled),
-                    },
-                )
-                .ok();
-            if canceled {
-                state.ops.finish_canceled(&operation_id);
-            } else {
-                state.ops.finish_failed(&operation_id, &err);
+        None => {
+            let cleaner = state.file_cleaner.read().await;
+            let files = cleaner.get_cleanable_files();
+            if files.is_empty() {
+                return Err("Run a scan before exporting its report".into());
             }
+            let report = cleaner.report();
+            let scan = ScanReport::Basic {
+                report: &report,
+                files,
+            };
+            report_export::export_report(scan, format, &path, now)?
         }
     }
+    Ok(path.to_string_lossy().to_string())
+}
 
-    result
+fn failure_line(failure: &FailedDeletion) -> String {
+    format!("{}: {}", failure.path, failure.reason)
+}
+
+fn cleaning_outcome(result: &CleaningResult) -> OperationOutcome {
+    OperationOutcome {
+        recovery_point_id: Some(result.recovery_point_id.clone()),
+        ..OperationOutcome::new(result.deleted_count as u64, result.total_freed)
+            .with_cleaned(result.deleted_files.iter().cloned())
+            .with_failures(result.failed_files.iter().map(failure_line))
+    }
 }
 
+/// Finished operations matching `filter`, newest first, one page at a time.
 #[tauri::command]
-async fn clear_inactive_memory(state: State<'_, AppState>) -> Result<u64, String> {
-    let optimizer = state.memory_optimizer.read().await;
-    optimizer.clear_inactive_memory().await
+async fn get_operation_history(
+    state: State<'_, AppState>,
+    filter: Option<HistoryFilter>,
+    pagination: Option<Pagination>,
+) -> Result<HistoryPage, String> {
+    Ok(state
+        .ops
+        .history()
+        .query(&filter.unwrap_or_default(), pagination.unwrap_or_default()))
 }
 
+/// Write the full operation history to `path`, or to Downloads, and return
+/// where it went.
 #[tauri::command]
-async fn get_memory_pressure(state: State<'_, AppState>) -> Result<f32, String> {
-    let optimizer = state.memory_optimizer.read().await;
-    optimizer.get_memory_pressure()
+async fn export_operation_history(
+    state: State<'_, AppState>,
+    path: Option<String>,
+) -> Result<String, String> {
+    let path = match path {
+        Some(path) => std::path::PathBuf::from(path),
+        None => history::default_export_path(chrono::Utc::now().timestamp())
+            .ok_or("Could not find a folder to export the operation history to")?,
+    };
+    state.ops.history().export_to(&path)?;
+    Ok(path.to_string_lossy().to_string())
 }
 
+/// The latest `limit` log entries (default 200), newest first, only those
+/// written for `operation_id` when given.
 #[tauri::command]
-async fn get_memory_stats(state: State<'_, AppState>) -> Result<MemoryStats, String> {
-    state.metrics_sampler.wait_until_ready().await;
-    let snapshot = state.metrics_sampler.latest_snapshot().await;
-    sample_value(&snapshot.memory, "memory")
+async fn get_recent_logs(
+    operation_id: Option<String>,
+    limit: Option<usize>,
+) -> Result<Vec<LogEntry>, String> {
+    let limit = limit.unwrap_or(RECENT_LOG_ENTRIES);
+    tokio::task::spawn_blocking(move || logging::recent_logs(operation_id.as_deref(), limit))
+        .await
+        .map_err(|e| format!("Log task failed: {}", e))?
+}
+
+/// Store the finished session and tell the UI what it achieved.
+fn finish_session(app_handle: &tauri::AppHandle) {
+    let state = app_handle.state::<AppState>();
+    let summary = state.session.finish();
+    if summary.is_empty() {
+        return;
+    }
+    app_handle.emit("session:summary", summary.clone()).ok();
+    let stored = state.sessions.blocking_write().append(summary);
+    if let Err(err) = stored {
+        log::warn!("Failed to store session summary: {}", err);
+    }
 }
 
+/// Show or hide the menu bar item. While it is shown, closing the window
+/// leaves the optimizer running in the background.
 #[tauri::command]
-async fn cancel_operation(state: State<'_, AppState>, operation_id: String) -> Result<(), String> {
-    if state.ops.cancel(&operation_id) {
-        Ok(())
+async fn set_tray_enabled(
+    app_handle: tauri::AppHandle,
+    state: State<'_, AppState>,
+    enabled: bool,
+) -> Result<(), String> {
+    if enabled {
+        tray::install(&app_handle, on_tray_action)?;
+        let snapshot = state.metrics_sampler.latest_snapshot().await;
+        tray::update(
+            &app_handle,
+            &TrayReadings::from_point(&HistoryPoint::from_snapshot(&snapshot)),
+        );
     } else {
-        Err("Unknown operation".into())
+        tray::remove(&app_handle);
+        // Without the menu bar item a hidden window could not be reopened.
+        if let Some(window) = app_handle.get_webview_window("main") {
+            window.show().ok();
+        }
+    }
+    state.tray.write().await.set_enabled(enabled)
+}
+
+fn on_tray_action(app_handle: &tauri::AppHandle, action: TrayAction) {
+    let app_handle = app_handle.clone();
+    match action {
+        TrayAction::OptimizeMemory => {
+            tauri::async_runtime::spawn(async move {
+                if let Err(err) = optimize_memory(app_handle.clone(), app_handle.state()).await {
+                    log::warn!("Menu bar memory optimization failed: {}", err);
+                }
+            });
+        }
+        TrayAction::QuickClean => {
+            tauri::async_runtime::spawn(async move {
+                if let Err(err) = quick_clean(app_handle.clone(), app_handle.state()).await {
+                    log::warn!("Menu bar quick clean failed: {}", err);
+                }
+            });
+        }
+        TrayAction::ShowWindow => {
+            if let Some(window) = app_handle.get_webview_window("main") {
+                window.show().ok();
+                window.set_focus().ok();
+            }
+        }
+        TrayAction::Quit => {
+            finish_session(&app_handle);
+            app_handle.exit(0);
+        }
+    }
+}
+
+async fn run_tray_updates(app_handle: tauri::AppHandle) {
+    let state = app_handle.state::<AppState>();
+    state.metrics_sampler.wait_until_ready().await;
+    let mut ticker = tokio::time::interval(tray::REFRESH_INTERVAL);
+    loop {
+        ticker.tick().await;
+        if !state.tray.read().await.enabled {
+            continue;
+        }
+        let snapshot = state.metrics_sampler.latest_snapshot().await;
+        tray::update(
+            &app_handle,
+            &TrayReadings::from_point(&HistoryPoint::from_snapshot(&snapshot)),
+        );
     }
 }
 
+/// Battery health and charging state; `None` on Macs without a battery.
 #[tauri::command]
-async fn get_operation_state(
-    state: State<'_, AppState>,
-    operation_id: String,
-) -> Result<Option<OpState>, String> {
-    Ok(state.ops.get(&operation_id))
+async fn get_battery_info() -> Result<Option<BatteryInfo>, String> {
+    tokio::task::spawn_blocking(power_info::get_battery_info)
+        .await
+        .map_err(|e| format!("Battery info task failed: {}", e))?
+}
+
+/// GPU model, utilization and memory use. With `allow_admin`, utilization
+/// IOKit does not report is sampled behind the administrator prompt.
+#[tauri::command]
+async fn get_gpu_info(allow_admin: Option<bool>) -> Result<Vec<GpuInfo>, String> {
+    gpu_info::get_gpu_info(allow_admin.unwrap_or(false)).await
 }
 
 #[tauri::command]
 async fn get_network_info(state: State<'_, AppState>) -> Result<Vec<NetworkInfo>, String> {
-    let monitor = state.system_monitor.read().await;
+    let monitor = state.system_monitor.get().await.read().await;
     Ok(monitor.get_network_info())
 }
 
 #[tauri::command]
 async fn get_temperatures(state: State<'_, AppState>) -> Result<Vec<TemperatureInfo>, String> {
-    let monitor = state.system_monitor.read().await;
+    let monitor = state.system_monitor.get().await.read().await;
     Ok(monitor.get_temperatures())
 }
 
@@ -1198,12 +5202,15 @@ async fn kill_memory_intensive_processes(
 ) -> Result<Vec<String>, String> {
     let optimizer = state.memory_optimizer.read().await;
     optimizer
-        .kill_memory_intensive_processes(threshold_mb)
+        .kill_memory_intensive_processes(threshold_mb, state.ops.inspector_mode())
         .await
 }
 
 #[tauri::command]
 async fn optimize_swap(state: State<'_, AppState>) -> Result<String, String> {
+    if state.ops.inspector_mode() {
+        return Ok("Inspector mode: swap optimization would clear inactive memory".to_string());
+    }
     let optimizer = state.memory_optimizer.read().await;
     optimizer.optimize_swap().await
 }
@@ -1218,15 +5225,22 @@ async fn get_dashboard_data(state: State<'_, AppState>) -> Result<DashboardData,
     let disk_snapshots = sample_value(&snapshot.disks, "disks")?;
 
     let top_processes = {
-        let mut monitor = state.system_monitor.write().await;
+        let mut monitor = state.system_monitor.get().await.write().await;
         monitor.get_top_memory_processes(5)
     };
 
+    // The dashboard shows what it can without GPU readings.
+    let gpus = gpu_info::get_gpu_info(false).await.unwrap_or_else(|err| {
+        log::warn!("Failed to read GPU info: {}", err);
+        Vec::new()
+    });
+
     Ok(DashboardData {
         memory: memory_info_from_stats(&memory_stats),
         cpu: cpu_info_from_snapshot(&cpu_snapshot),
-        disks: disks_from_snapshots(disk_snapshots),
+        disks: disk_infos(disk_snapshots).await?,
         top_processes,
+        gpus,
     })
 }
 
@@ -1236,20 +5250,87 @@ struct DashboardData {
     cpu: CpuInfo,
     disks: Vec<DiskInfo>,
     top_processes: Vec<ProcessInfo>,
+    gpus: Vec<GpuInfo>,
 }
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
+    if let Err(err) = logging::init() {
+        eprintln!("Logging to file disabled: {}", err);
+    }
     let metrics_sampler = MetricsSampler::spawn();
     #[cfg(feature = "cache-refresh")]
     let cache_refresher = std::sync::Arc::new(CacheRefresher::new());
+    let init_timings = std::sync::Arc::new(InitTimings::default());
+    let performance_profile = PerformanceProfileStore::load();
+    let performance_settings = performance_profile.profile().settings();
+    performance::apply(&performance_settings);
+    let notifier = Notifier::load();
+    let finished_notifier = notifier.clone();
+    let ops = OperationRegistry::new(
+        performance_settings.scan_permits,
+        performance_settings.clean_permits,
+        performance_settings.opt_permits,
+    )
+    .with_history(OperationHistory::load())
+    .with_finish_hook(std::sync::Arc::new(move |record| {
+        notify_operation_finished(&finished_notifier, record)
+    }));
+    let settings = SettingsStore::load();
+    performance::set_parallel_scan(settings.settings().parallel_scan);
+    set_spotlight_discovery(settings.settings().spotlight_discovery);
+    formatting::set_units(settings.settings().size_units);
+    let audit_mode = AuditModeSettings::load();
+    ops.set_audit_mode(audit_mode.enabled);
+    let usage = UsageStore::load();
+    let mut telemetry = TelemetryStore::load();
+    if let Err(err) = telemetry.set_opted_in(usage.opted_in(), chrono::Utc::now().timestamp()) {
+        log::warn!("Failed to apply the telemetry opt-in: {}", err);
+    }
     let app_state = AppState {
-        system_monitor: RwLock::new(SystemMonitor::new()),
+        system_monitor: LazySubsystem::new(
+            "system monitor",
+            SystemMonitor::new,
+            init_timings.clone(),
+        ),
         file_cleaner: RwLock::new(FileCleaner::new()),
-        enhanced_file_cleaner: RwLock::new(EnhancedFileCleaner::new()),
+        enhanced_file_cleaner: LazySubsystem::new(
+            "enhanced file cleaner",
+            EnhancedFileCleaner::new,
+            init_timings.clone(),
+        ),
+        init_timings,
+        quick_clean_items: RwLock::new(Vec::new()),
         memory_optimizer: RwLock::new(MemoryOptimizer::new()),
+        memory_watchdog: RwLock::new(MemoryWatchdog::load()),
+        notifier,
         metrics_sampler,
-        ops: OperationRegistry::new(1, 2, 1),
+        metrics_stream: std::sync::Mutex::new(None),
+        safety_continuation: std::sync::Mutex::new(None),
+        metrics_history: RwLock::new(MetricsHistory::load()),
+        ops,
+        performance_profile: RwLock::new(performance_profile),
+        schedules: RwLock::new(ScheduleStore::load()),
+        trash_purges: RwLock::new(TrashPurgeLog::load()),
+        activity_index: Default::default(),
+        custom_rules: RwLock::new(CustomRuleStore::load()),
+        rule_packs: RwLock::new(RulePackStore::load()),
+        exclusions: RwLock::new(ExclusionStore::load()),
+        age_thresholds: RwLock::new(AgeThresholdStore::load()),
+        external_volumes: RwLock::new(ExternalVolumeStore::load()),
+        safety_policies: RwLock::new(SafetyPolicyStore::load()),
+        protection_packs: RwLock::new(ProtectionPackStore::load()),
+        session: SessionRecorder::new(),
+        sessions: RwLock::new(SessionStore::load()),
+        cache_sizes: RwLock::new(CacheSizeLog::load()),
+        cache_growth: RwLock::new(CacheGrowthLog::load()),
+        usage: RwLock::new(usage),
+        telemetry: RwLock::new(telemetry),
+        app_quit: RwLock::new(AppQuitSettings::load()),
+        tray: RwLock::new(TraySettings::load()),
+        audit_mode: RwLock::new(audit_mode),
+        settings: RwLock::new(settings),
+        app_footprints: RwLock::new(std::collections::HashMap::new()),
         #[cfg(feature = "cache-refresh")]
         cache_refresher,
     };
@@ -1266,6 +5347,29 @@ pub fn run() {
                 let _ = win.set_focus();
             }
 
+            tauri::async_runtime::spawn_blocking(capabilities::refresh_capabilities);
+            tauri::async_runtime::spawn_blocking(|| {
+                let restored = file_cleaner::restore_dir_size_cache();
+                log::debug!("Restored {} directory sizes", restored);
+            });
+            tauri::async_runtime::spawn(run_scheduler(app.handle().clone()));
+            let (quits, quit_events) = tokio::sync::mpsc::unbounded_channel();
+            #[cfg(target_os = "macos")]
+            app_quit::watch_quits(quits);
+            #[cfg(not(target_os = "macos"))]
+            drop(quits);
+            tauri::async_runtime::spawn(run_app_quit_watcher(app.handle().clone(), quit_events));
+            tauri::async_runtime::spawn(run_cache_growth_tracker(app.handle().clone()));
+            tauri::async_runtime::spawn(run_metrics_history(app.handle().clone()));
+            tauri::async_runtime::spawn(run_memory_watchdog(app.handle().clone()));
+            tauri::async_runtime::spawn(run_disk_space_notifications(app.handle().clone()));
+            if app.state::<AppState>().tray.blocking_read().enabled {
+                if let Err(err) = tray::install(app.handle(), on_tray_action) {
+                    log::warn!("{}", err);
+                }
+            }
+            tauri::async_runtime::spawn(run_tray_updates(app.handle().clone()));
+
             #[cfg(feature = "cache-refresh")]
             {
                 let state = app.state::<AppState>();
@@ -1290,6 +5394,18 @@ pub fn run() {
             }
             Ok(())
         })
+        .on_window_event(|window, event| {
+            if let tauri::WindowEvent::CloseRequested { api, .. } = event {
+                // With the menu bar item shown the app keeps running in the background.
+                let state = window.state::<AppState>();
+                if state.tray.blocking_read().enabled {
+                    api.prevent_close();
+                    window.hide().ok();
+                    return;
+                }
+                finish_session(window.app_handle());
+            }
+        })
         .invoke_handler(tauri::generate_handler![
             get_system_info,
             get_memory_info,
@@ -1299,33 +5415,167 @@ pub fn run() {
             get_disks,
             get_metrics_snapshot,
             kill_process,
+            suspend_process,
+            resume_process,
+            set_process_priority,
+            get_process_detail,
+            get_app_resource_usage,
+            get_process_network_usage,
             scan_cleanable_files,
             scan_cleanable_files_enhanced,
+            scan_category,
+            scan_custom_path,
             get_cleanable_files,
             get_auto_selectable_files,
             get_files_by_safety,
+            explain_file_safety,
             clean_files,
             clean_files_enhanced,
             prepare_deletion_enhanced,
             preview_rules,
             get_enhanced_telemetry,
+            get_report_delta,
+            warmup,
             record_user_feedback,
             get_active_development_tools,
             empty_trash,
+            get_trash_summary,
             restore_from_trash,
+            get_quarantine_settings,
+            update_quarantine_settings,
+            list_quarantine,
+            restore_from_quarantine,
+            purge_quarantine,
             optimize_memory,
             optimize_memory_admin,
+            optimize_memory_plan,
+            get_watchdog_status,
+            configure_memory_watchdog,
+            get_notification_settings,
+            configure_notifications,
             clear_inactive_memory,
             get_memory_pressure,
             get_memory_stats,
+            get_memory_pressure_breakdown,
+            get_gpu_info,
             get_network_info,
             get_temperatures,
             kill_memory_intensive_processes,
             optimize_swap,
             get_dashboard_data,
             cancel_operation,
-            get_operation_state
+            get_operation_state,
+            get_format_preferences,
+            set_format_preferences,
+            create_schedule,
+            list_schedules,
+            delete_schedule,
+            get_app_quit_cleanup_mode,
+            set_app_quit_cleanup_mode,
+            get_cache_growth_report,
+            clean_app_quit_caches,
+            capture_system_snapshot,
+            list_system_snapshots,
+            compare_snapshots,
+            list_installed_apps,
+            analyze_app_footprint,
+            uninstall_app,
+            analyze_app_data,
+            list_ios_backups,
+            delete_ios_backup,
+            scan_photos_library,
+            open_in_photos,
+            scan_app_bundles,
+            slim_app_bundle,
+            get_capabilities,
+            check_permissions,
+            request_full_disk_access,
+            get_privileged_helper_status,
+            install_privileged_helper,
+            uninstall_privileged_helper,
+            scan_directory_tree,
+            list_startup_items,
+            set_startup_item_enabled,
+            remove_startup_item,
+            list_custom_rules,
+            add_custom_rule,
+            update_custom_rule,
+            remove_custom_rule,
+            list_rule_packs,
+            reload_rule_packs,
+            set_rule_pack_enabled,
+            benchmark_hashing,
+            list_exclusions,
+            add_exclusion,
+            remove_exclusion,
+            get_extension_stats,
+            get_extension_files,
+            get_weekly_digest,
+            get_health_score,
+            generate_weekly_summary,
+            scan_developer_junk,
+            scan_project_artifacts,
+            scan_docker_artifacts,
+            prune_docker_artifacts,
+            run_network_maintenance,
+            get_interface_statistics,
+            list_network_connections,
+            run_connectivity_test,
+            list_maintenance_tasks,
+            run_maintenance_task,
+            run_maintenance,
+            get_inspector_mode,
+            set_inspector_mode,
+            get_audit_mode,
+            set_audit_mode,
+            start_metrics_stream,
+            stop_metrics_stream,
+            scan_installer_sources,
+            get_metrics_history,
+            scan_duplicates_in_paths,
+            find_large_files,
+            resolve_duplicates,
+            list_volumes,
+            get_volume_scope,
+            set_volume_scope,
+            list_external_volumes,
+            set_external_volume_opt_in,
+            review_learned_exceptions,
+            get_storage_recommendations,
+            quick_clean,
+            get_settings,
+            update_settings,
+            get_usage_opt_in,
+            set_usage_opt_in,
+            get_telemetry_insights,
+            export_usage_summary,
+            export_report,
+            reset_learning_data,
+            get_battery_info,
+            list_age_thresholds,
+            set_age_threshold,
+            get_safety_policies,
+            update_safety_policy,
+            get_performance_profile,
+            set_performance_profile,
+            get_trash_purge_history,
+            get_activity_heatmap,
+            list_protection_packs,
+            enable_protection_pack,
+            disable_protection_pack,
+            set_tray_enabled,
+            get_operation_history,
+            export_operation_history,
+            get_recent_logs,
+            undo_last_clean
         ])
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .build(tauri::generate_context!())
+        .expect("error while building tauri application")
+        .run(|_app_handle, event| {
+            if let tauri::RunEvent::Exit = event {
+                if let Err(err) = file_cleaner::persist_dir_size_cache() {
+                    log::warn!("Failed to save directory sizes: {}", err);
+                }
+            }
+        });
 }
@@ -0,0 +1,240 @@
+//! Headless companion to the app, on the same cleaning and memory engine:
+//!
+//! `cargo run --features cli --bin macos-optimizer-cli -- report --json`
+//!
+//! Run without arguments for the list of commands. Ctrl-C cancels a scan or
+//! clean in progress.
+
+use std::process::ExitCode;
+
+use serde::Serialize;
+use tokio_util::sync::CancellationToken;
+
+use macos_optimizer_lib::{
+    acquire_destructive_lock, format_size, list_volumes, parse_cli_args, AuditModeSettings,
+    CliCommand, EnhancedCleaningReport, EnhancedFileCleaner, MemoryOptimizer, MemoryStats,
+    ProcessInfo, SystemInfo, SystemMonitor, VolumeInfo, CLI_USAGE,
+};
+
+/// Output of `report`.
+#[derive(Serialize)]
+struct SystemReport {
+    system: SystemInfo,
+    memory: MemoryStats,
+    volumes: Vec<VolumeInfo>,
+    top_memory_processes: Vec<ProcessInfo>,
+}
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    let invocation = match parse_cli_args(std::env::args().skip(1)) {
+        Ok(invocation) => invocation,
+        Err(err) => {
+            eprintln!("{}\n\n{}", err, CLI_USAGE);
+            return ExitCode::from(2);
+        }
+    };
+    let token = CancellationToken::new();
+    let on_interrupt = token.clone();
+    tokio::spawn(async move {
+        if tokio::signal::ctrl_c().await.is_ok() {
+            on_interrupt.cancel();
+        }
+    });
+
+    let json = invocation.json;
+    let result = match invocation.command {
+        CliCommand::Help => {
+            println!("{}", CLI_USAGE);
+            Ok(())
+        }
+        CliCommand::Scan => scan(&token, json).await,
+        CliCommand::Clean {
+            categories,
+            dry_run,
+        } => clean(&categories, dry_run, &token, json).await,
+        CliCommand::MemOptimize { admin } => optimize_memory(admin, &token, json).await,
+        CliCommand::Report => report(json),
+    };
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("error: {}", err);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+async fn run_scan(
+    cleaner: &mut EnhancedFileCleaner,
+    token: &CancellationToken,
+) -> Result<EnhancedCleaningReport, String> {
+    cleaner.scan_system_enhanced_with_cancel(token, None).await
+}
+
+async fn scan(token: &CancellationToken, json: bool) -> Result<(), String> {
+    let report = run_scan(&mut EnhancedFileCleaner::new(), token).await?;
+    if json {
+        return print_json(&report.category_summaries);
+    }
+    for category in &report.category_summaries {
+        println!(
+            "{:<32} {:>10} in {:>6} items ({} auto-selected)",
+            category.name,
+            format_size(category.total_size),
+            category.file_count,
+            format_size(category.auto_selected_size)
+        );
+    }
+    println!(
+        "{} in {} items",
+        format_size(report.base.total_size),
+        report.base.files_count
+    );
+    Ok(())
+}
+
+/// Only items the engine would auto-select as safe are cleaned.
+async fn clean(
+    categories: &[String],
+    dry_run: bool,
+    token: &CancellationToken,
+    json: bool,
+) -> Result<(), String> {
+    let dry_run = dry_run || AuditModeSettings::load().enabled;
+    let mut cleaner = EnhancedFileCleaner::new();
+    let report = run_scan(&mut cleaner, token).await?;
+    let selected: Vec<_> = report
+        .enhanced_files
+        .iter()
+        .map(|file| &file.base)
+        .filter(|file| {
+            file.auto_select
+                && file.safe_to_delete
+                && categories
+                    .iter()
+                    .any(|category| category.eq_ignore_ascii_case(&file.category))
+        })
+        .collect();
+
+    if dry_run || selected.is_empty() {
+        if json {
+            return print_json(&selected);
+        }
+        for file in &selected {
+            println!("{:>10}  {}", format_size(file.size), file.path);
+        }
+        let total: u64 = selected.iter().map(|file| file.size).sum();
+        let verb = if dry_run {
+            "Would clean"
+        } else {
+            "Nothing to clean:"
+        };
+        println!(
+            "{} {} in {} items",
+            verb,
+            format_size(total),
+            selected.len()
+        );
+        return Ok(());
+    }
+
+    let _lock = acquire_destructive_lock("cli_clean")?;
+    let paths = selected.iter().map(|file| file.path.clone()).collect();
+    let result = cleaner
+        .clean_files_enhanced(paths, Some(token), false, None)
+        .await?;
+    if json {
+        return print_json(&result);
+    }
+    for failed in &result.failed_files {
+        println!("skipped {}: {}", failed.path, failed.reason);
+    }
+    println!(
+        "Freed {} from {} items",
+        format_size(result.total_freed),
+        result.deleted_count
+    );
+    Ok(())
+}
+
+/// Audit mode only reports the steps that would run.
+async fn optimize_memory(admin: bool, token: &CancellationToken, json: bool) -> Result<(), String> {
+    if AuditModeSettings::load().enabled {
+        let preview = MemoryOptimizer::preview_optimization(admin).await?;
+        if json {
+            return print_json(&preview);
+        }
+        for step in &preview.optimizations_performed {
+            println!("would run  {}", step);
+        }
+        println!("{}", preview.message);
+        return Ok(());
+    }
+    let _lock = acquire_destructive_lock("cli_memory_optimize")?;
+    let optimizer = MemoryOptimizer::new();
+    let result = if admin {
+        optimizer.optimize_memory_with_admin_cancel(token).await?
+    } else {
+        optimizer.optimize_memory_with_cancel(token).await?
+    };
+    if json {
+        return print_json(&result);
+    }
+    for step in &result.strategies {
+        println!(
+            "{:<32} {:>+12} bytes available",
+            step.name, step.measured_delta
+        );
+    }
+    println!("{}", result.message);
+    Ok(())
+}
+
+fn report(json: bool) -> Result<(), String> {
+    let mut monitor = SystemMonitor::new();
+    let report = SystemReport {
+        system: monitor.get_system_info(),
+        memory: MemoryOptimizer::get_memory_stats()?,
+        volumes: list_volumes(),
+        top_memory_processes: monitor.get_top_memory_processes(5),
+    };
+    if json {
+        return print_json(&report);
+    }
+    println!(
+        "{} ({}), up {} s",
+        report.system.os_name, report.system.hostname, report.system.uptime
+    );
+    println!(
+        "Memory: {} used of {}, {} available, pressure {:.0}%",
+        format_size(report.memory.used),
+        format_size(report.memory.total),
+        format_size(report.memory.available),
+        report.memory.pressure_percent
+    );
+    for volume in &report.volumes {
+        println!(
+            "{:<24} {} free of {}",
+            volume.name,
+            format_size(volume.available_space),
+            format_size(volume.total_space)
+        );
+    }
+    for process in &report.top_memory_processes {
+        println!(
+            "{:>8} {:<32} {}",
+            process.pid,
+            process.name,
+            format_size(process.memory_usage)
+        );
+    }
+    Ok(())
+}
+
+fn print_json(value: &impl Serialize) -> Result<(), String> {
+    let text = serde_json::to_string_pretty(value)
+        .map_err(|e| format!("Failed to serialize output: {}", e))?;
+    println!("{}", text);
+    Ok(())
+}
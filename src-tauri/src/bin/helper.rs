@@ -0,0 +1,30 @@
+//! The root helper the app installs as a launch daemon, so elevated
+//! deletes, purges and maintenance ask for the password once:
+//!
+//! `cargo build --features helper --bin macos-optimizer-helper`
+//!
+//! The app copies it into place; launchd starts it with the uid of the user
+//! it serves.
+
+use std::process::ExitCode;
+
+use macos_optimizer_lib::run_privileged_helper;
+
+fn main() -> ExitCode {
+    let mut args = std::env::args().skip(1);
+    let allowed_uid = match (args.next().as_deref(), args.next()) {
+        (Some("--allowed-uid"), Some(uid)) => uid.parse::<u32>().ok(),
+        _ => None,
+    };
+    let Some(allowed_uid) = allowed_uid else {
+        eprintln!("usage: macos-optimizer-helper --allowed-uid <uid>");
+        return ExitCode::from(2);
+    };
+    match run_privileged_helper(allowed_uid) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("{}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
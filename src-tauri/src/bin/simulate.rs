@@ -0,0 +1,56 @@
+//! Run cleaning simulations against fixture trees:
+//!
+//! `cargo run --features simulation --bin simulate -- tests/fixtures/simulation/*.json`
+//!
+//! Prints one report per fixture and exits non-zero if any expectation fails.
+
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use macos_optimizer_lib::{format_size, run_simulation, FixtureSpec};
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    let fixtures: Vec<PathBuf> = std::env::args_os().skip(1).map(PathBuf::from).collect();
+    if fixtures.is_empty() {
+        eprintln!("usage: simulate <fixture.json>...");
+        return ExitCode::from(2);
+    }
+
+    let mut failed = 0;
+    for path in &fixtures {
+        let result = match FixtureSpec::from_file(path) {
+            Ok(spec) => run_simulation(&spec).await,
+            Err(err) => Err(err),
+        };
+        match result {
+            Ok(report) => {
+                let status = if report.passed() { "PASS" } else { "FAIL" };
+                println!(
+                    "{} {} ({} reported, {} removed, {} freed)",
+                    status,
+                    report.name,
+                    report.reported.len(),
+                    report.removed.len(),
+                    format_size(report.freed)
+                );
+                for failure in &report.failures {
+                    println!("    {}", failure);
+                }
+                if !report.passed() {
+                    failed += 1;
+                }
+            }
+            Err(err) => {
+                println!("ERROR {}: {}", path.display(), err);
+                failed += 1;
+            }
+        }
+    }
+
+    if failed == 0 {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    }
+}
@@ -0,0 +1,297 @@
+// src/capabilities.rs
+
+//! Availability of the command-line tools some checks shell out to.
+//!
+//! Tools are probed once at startup. Analyzers consult the result to skip
+//! checks whose tool is missing and flag their findings as reduced-confidence,
+//! instead of treating a failed spawn as a negative answer.
+
+use std::collections::BTreeMap;
+use std::env;
+use std::path::{Path, PathBuf};
+use std::sync::RwLock;
+
+use chrono::Utc;
+use lazy_static::lazy_static;
+use serde::{Deserialize, Serialize};
+
+#[cfg(test)]
+mod tests;
+
+/// GUI apps inherit a minimal PATH, so the standard system and Homebrew
+/// locations are always searched as well.
+const SYSTEM_BIN_DIRS: &[&str] = &[
+    "/usr/bin",
+    "/usr/sbin",
+    "/bin",
+    "/sbin",
+    "/usr/local/bin",
+    "/opt/homebrew/bin",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Tool {
+    Lsof,
+    Tmutil,
+    Brctl,
+    Mdls,
+    Plutil,
+    Osascript,
+    Launchctl,
+    Xcrun,
+    Docker,
+    Podman,
+    Colima,
+    Xattr,
+    Curl,
+    Md5,
+    Sips,
+    Fdesetup,
+    Brew,
+    Npm,
+    Pip3,
+    Lipo,
+    Sqlite3,
+    Netstat,
+    Ping,
+    Nettop,
+    Git,
+    Mdfind,
+    Mdutil,
+}
+
+impl Tool {
+    pub const ALL: [Tool; 27] = [
+        Tool::Lsof,
+        Tool::Tmutil,
+        Tool::Brctl,
+        Tool::Mdls,
+        Tool::Plutil,
+        Tool::Osascript,
+        Tool::Launchctl,
+        Tool::Xcrun,
+        Tool::Docker,
+        Tool::Podman,
+        Tool::Colima,
+        Tool::Xattr,
+        Tool::Curl,
+        Tool::Md5,
+        Tool::Sips,
+        Tool::Fdesetup,
+        Tool::Brew,
+        Tool::Npm,
+        Tool::Pip3,
+        Tool::Lipo,
+        Tool::Sqlite3,
+        Tool::Netstat,
+        Tool::Ping,
+        Tool::Nettop,
+        Tool::Git,
+        Tool::Mdfind,
+        Tool::Mdutil,
+    ];
+
+    pub fn command(self) -> &'static str {
+        match self {
+            Tool::Lsof => "lsof",
+            Tool::Tmutil => "tmutil",
+            Tool::Brctl => "brctl",
+            Tool::Mdls => "mdls",
+            Tool::Plutil => "plutil",
+            Tool::Osascript => "osascript",
+            Tool::Launchctl => "launchctl",
+            Tool::Xcrun => "xcrun",
+            Tool::Docker => "docker",
+            Tool::Podman => "podman",
+            Tool::Colima => "colima",
+            Tool::Xattr => "xattr",
+            Tool::Curl => "curl",
+            Tool::Md5 => "md5",
+            Tool::Sips => "sips",
+            Tool::Fdesetup => "fdesetup",
+            Tool::Brew => "brew",
+            Tool::Npm => "npm",
+            Tool::Pip3 => "pip3",
+            Tool::Lipo => "lipo",
+            Tool::Sqlite3 => "sqlite3",
+            Tool::Netstat => "netstat",
+            Tool::Ping => "ping",
+            Tool::Nettop => "nettop",
+            Tool::Git => "git",
+            Tool::Mdfind => "mdfind",
+            Tool::Mdutil => "mdutil",
+        }
+    }
+
+    /// Checks that are skipped while the tool is missing.
+    pub fn affects(self) -> &'static [&'static str] {
+        match self {
+            Tool::Lsof => &[
+                "Open-file check before removing stale lock files",
+                "Listing active network connections",
+            ],
+            Tool::Tmutil => &[
+                "Time Machine exclusion check in safety analysis",
+                "Time Machine backup and local snapshot status",
+                "Deferring memory purges during a Time Machine backup",
+            ],
+            Tool::Brctl => &["iCloud Drive sync status"],
+            Tool::Mdls => &[
+                "Spotlight indexing check in safety analysis",
+                "Spotlight usage metadata",
+            ],
+            Tool::Plutil => &["Binary property list parsing for system snapshots"],
+            Tool::Osascript => &[
+                "Moving items to the Trash through Finder",
+                "Login item listing",
+            ],
+            Tool::Launchctl => &[
+                "Launch agent enabled and running state",
+                "Enabling, disabling and unloading launch agents",
+            ],
+            Tool::Xcrun => &[
+                "Unavailable simulator detection in developer scans",
+                "Deleting unavailable simulators through simctl",
+            ],
+            Tool::Docker => &["Docker image, container, volume and build cache cleanup"],
+            Tool::Podman => &["Podman image, container and volume cleanup"],
+            Tool::Colima => &["Detecting a stopped Colima VM behind the Docker CLI"],
+            Tool::Xattr => &["Reading where old installers were downloaded from"],
+            Tool::Curl => &["Checking whether old installers can be downloaded again"],
+            Tool::Md5 => &["Comparing old installers with the server's checksum"],
+            Tool::Sips => &["Finding similar images in duplicate scans"],
+            Tool::Fdesetup => &["Deferring memory purges while FileVault encrypts the disk"],
+            Tool::Brew => &["Clearing the Homebrew cache with brew cleanup"],
+            Tool::Npm => &["Clearing the npm cache with npm cache clean"],
+            Tool::Pip3 => &["Clearing the pip cache with pip cache purge"],
+            Tool::Lipo => &["Removing unused architectures from app bundles"],
+            Tool::Sqlite3 => &["Grouping Messages attachments by conversation"],
+            Tool::Netstat => &["Per-interface network statistics"],
+            Tool::Ping => &["Connectivity and latency test"],
+            Tool::Nettop => &["Per-process network bandwidth"],
+            Tool::Git => &["Uncommitted changes and last commit date in project artifact scans"],
+            Tool::Mdfind => &["Spotlight discovery of old installers and large stale files"],
+            Tool::Mdutil => &["Checking that Spotlight indexes a folder before searching it"],
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ToolStatus {
+    pub available: bool,
+    pub path: Option<String>,
+    pub affects: Vec<String>,
+}
+
+/// Feature-availability map returned by `get_capabilities`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Capabilities {
+    pub probed_at: i64,
+    pub tools: BTreeMap<Tool, ToolStatus>,
+}
+
+impl Capabilities {
+    /// Probe `PATH` plus the standard system binary directories.
+    pub fn probe() -> Self {
+        let mut dirs: Vec<PathBuf> = env::var_os("PATH")
+            .map(|path| env::split_paths(&path).collect())
+            .unwrap_or_default();
+        for dir in SYSTEM_BIN_DIRS {
+            let dir = PathBuf::from(dir);
+            if !dirs.contains(&dir) {
+                dirs.push(dir);
+            }
+        }
+        Self::probe_in(&dirs)
+    }
+
+    pub fn probe_in(dirs: &[PathBuf]) -> Self {
+        let tools = Tool::ALL
+            .iter()
+            .map(|&tool| {
+                let path = dirs
+                    .iter()
+                    .map(|dir| dir.join(tool.command()))
+                    .find(|candidate| is_executable(candidate));
+                let status = ToolStatus {
+                    available: path.is_some(),
+                    path: path.map(|p| p.to_string_lossy().to_string()),
+                    affects: tool.affects().iter().map(|s| s.to_string()).collect(),
+                };
+                (tool, status)
+            })
+            .collect();
+        Self {
+            probed_at: Utc::now().timestamp(),
+            tools,
+        }
+    }
+
+    pub fn is_available(&self, tool: Tool) -> bool {
+        self.tools.get(&tool).is_some_and(|status| status.available)
+    }
+
+    pub fn missing(&self) -> Vec<Tool> {
+        Tool::ALL
+            .into_iter()
+            .filter(|&tool| !self.is_available(tool))
+            .collect()
+    }
+}
+
+lazy_static! {
+    static ref CAPABILITIES: RwLock<Option<Capabilities>> = RwLock::new(None);
+}
+
+/// The probed capabilities, probing on first use.
+pub fn capabilities() -> Capabilities {
+    if let Some(current) = CAPABILITIES.read().ok().and_then(|guard| guard.clone()) {
+        return current;
+    }
+    refresh_capabilities()
+}
+
+/// Probe again, e.g. after the user installs the Xcode command-line tools.
+pub fn refresh_capabilities() -> Capabilities {
+    let probed = Capabilities::probe();
+    let missing = probed.missing();
+    if !missing.is_empty() {
+        log::warn!(
+            "Optional tools unavailable, related checks will be skipped: {:?}",
+            missing
+        );
+    }
+    if let Ok(mut guard) = CAPABILITIES.write() {
+        *guard = Some(probed.clone());
+    }
+    probed
+}
+
+pub fn tool_available(tool: Tool) -> bool {
+    capabilities().is_available(tool)
+}
+
+/// Where the tool was found, for running it without relying on `PATH`.
+pub fn tool_path(tool: Tool) -> Option<PathBuf> {
+    capabilities()
+        .tools
+        .get(&tool)
+        .and_then(|status| status.path.clone())
+        .map(PathBuf::from)
+}
+
+fn is_executable(path: &Path) -> bool {
+    let Ok(metadata) = path.metadata() else {
+        return false;
+    };
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        metadata.is_file() && metadata.permissions().mode() & 0o111 != 0
+    }
+    #[cfg(not(unix))]
+    {
+        metadata.is_file()
+    }
+}
@@ -0,0 +1,30 @@
+use super::{Capabilities, Tool};
+use std::fs;
+use std::os::unix::fs::PermissionsExt;
+use tempfile::TempDir;
+
+#[test]
+fn probe_reports_only_executable_tools() {
+    let bin = TempDir::new().unwrap();
+    let lsof = bin.path().join("lsof");
+    fs::write(&lsof, b"#!/bin/sh\n").unwrap();
+    fs::set_permissions(&lsof, fs::Permissions::from_mode(0o755)).unwrap();
+    let mdls = bin.path().join("mdls");
+    fs::write(&mdls, b"").unwrap();
+    fs::set_permissions(&mdls, fs::Permissions::from_mode(0o644)).unwrap();
+
+    let caps = Capabilities::probe_in(&[bin.path().join("missing"), bin.path().to_path_buf()]);
+    assert!(caps.is_available(Tool::Lsof));
+    assert_eq!(
+        caps.tools[&Tool::Lsof].path.as_deref(),
+        Some(lsof.to_string_lossy().as_ref())
+    );
+    assert!(!caps.is_available(Tool::Mdls));
+    assert!(caps.tools[&Tool::Mdls].path.is_none());
+    assert!(!caps.tools[&Tool::Mdls].affects.is_empty());
+    assert_eq!(caps.missing().len(), Tool::ALL.len() - 1);
+
+    let json = serde_json::to_value(&caps).unwrap();
+    assert_eq!(json["tools"]["lsof"]["available"], true);
+    assert_eq!(json["tools"]["tmutil"]["available"], false);
+}
@@ -0,0 +1,114 @@
+// src/cli.rs
+
+//! Command line of the `macos-optimizer-cli` companion binary.
+//!
+//! Only the parsing lives here so it can be tested without a Mac; the
+//! binary in `src/bin/cli.rs` runs the commands on the same engine the app
+//! uses.
+
+#[cfg(test)]
+mod tests;
+
+pub const CLI_USAGE: &str = "\
+usage: macos-optimizer-cli [--json] <command>
+
+commands:
+  scan                                 list what can be cleaned, by category
+  clean --category <name>... [--dry-run]
+                                       move auto-selected items of the
+                                       categories to the Trash
+  mem optimize [--admin]               free memory; --admin prompts for the
+                                       deep clean
+  report                               system, memory and volume summary
+
+--json prints the result as JSON instead of text.";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CliCommand {
+    Scan,
+    Clean {
+        categories: Vec<String>,
+        dry_run: bool,
+    },
+    MemOptimize {
+        admin: bool,
+    },
+    Report,
+    Help,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CliInvocation {
+    pub command: CliCommand,
+    pub json: bool,
+}
+
+/// Parse the arguments after the program name. `--json` may appear
+/// anywhere.
+pub fn parse_cli_args<I>(args: I) -> Result<CliInvocation, String>
+where
+    I: IntoIterator,
+    I::Item: Into<String>,
+{
+    let mut json = false;
+    let mut words = Vec::new();
+    for arg in args.into_iter().map(Into::into) {
+        match arg.as_str() {
+            "--json" => json = true,
+            "-h" | "--help" => {
+                return Ok(CliInvocation {
+                    command: CliCommand::Help,
+                    json,
+                })
+            }
+            _ => words.push(arg),
+        }
+    }
+    let mut words = words.into_iter();
+    let command = match words.next().as_deref() {
+        None | Some("help") => CliCommand::Help,
+        Some("scan") => CliCommand::Scan,
+        Some("report") => CliCommand::Report,
+        Some("clean") => {
+            let mut categories = Vec::new();
+            let mut dry_run = false;
+            while let Some(word) = words.next() {
+                match word.as_str() {
+                    "--category" | "-c" => {
+                        categories.push(words.next().ok_or("--category needs a category name")?)
+                    }
+                    "--dry-run" => dry_run = true,
+                    other => return Err(format!("Unknown clean option: {}", other)),
+                }
+            }
+            if categories.is_empty() {
+                return Err("clean needs at least one --category".into());
+            }
+            return Ok(CliInvocation {
+                command: CliCommand::Clean {
+                    categories,
+                    dry_run,
+                },
+                json,
+            });
+        }
+        Some("mem") => {
+            if words.next().as_deref() != Some("optimize") {
+                return Err("Expected `mem optimize`".into());
+            }
+            let mut admin = false;
+            for word in words.by_ref() {
+                match word.as_str() {
+                    "--admin" => admin = true,
+                    other => return Err(format!("Unknown mem optimize option: {}", other)),
+                }
+            }
+            CliCommand::MemOptimize { admin }
+        }
+        Some(other) => return Err(format!("Unknown command: {}", other)),
+    };
+    if let Some(extra) = words.next() {
+        return Err(format!("Unexpected argument: {}", extra));
+    }
+    Ok(CliInvocation { command, json })
+}
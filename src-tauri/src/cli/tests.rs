@@ -0,0 +1,49 @@
+use super::{parse_cli_args, CliCommand, CliInvocation};
+
+fn parse(args: &[&str]) -> Result<CliInvocation, String> {
+    parse_cli_args(args.iter().copied())
+}
+
+#[test]
+fn commands_and_their_options_are_parsed() {
+    assert_eq!(parse(&[]).unwrap().command, CliCommand::Help);
+    assert_eq!(parse(&["scan"]).unwrap().command, CliCommand::Scan);
+
+    let report = parse(&["report", "--json"]).unwrap();
+    assert_eq!(report.command, CliCommand::Report);
+    assert!(report.json);
+
+    assert_eq!(
+        parse(&[
+            "--json",
+            "clean",
+            "--category",
+            "User Cache",
+            "-c",
+            "Logs",
+            "--dry-run"
+        ])
+        .unwrap(),
+        CliInvocation {
+            command: CliCommand::Clean {
+                categories: vec!["User Cache".into(), "Logs".into()],
+                dry_run: true,
+            },
+            json: true,
+        }
+    );
+    assert_eq!(
+        parse(&["mem", "optimize", "--admin"]).unwrap().command,
+        CliCommand::MemOptimize { admin: true }
+    );
+}
+
+#[test]
+fn malformed_command_lines_are_rejected() {
+    assert!(parse(&["clean"]).is_err());
+    assert!(parse(&["clean", "--category"]).is_err());
+    assert!(parse(&["mem"]).is_err());
+    assert!(parse(&["mem", "optimize", "--fast"]).is_err());
+    assert!(parse(&["scan", "extra"]).is_err());
+    assert!(parse(&["defrag"]).is_err());
+}
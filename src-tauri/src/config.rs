@@ -1,8 +1,25 @@
 // src/config.rs
 
+use std::path::{Path, PathBuf};
+
 use lazy_static::lazy_static;
 use serde::{Deserialize, Serialize};
 
+#[cfg(feature = "app")]
+use crate::file_cleaner::disk_analyzer::{DEFAULT_TREE_DEPTH, MAX_TREE_DEPTH};
+#[cfg(feature = "app")]
+use crate::formatting::UnitSystem;
+#[cfg(feature = "app")]
+use crate::performance::PerformanceProfile;
+use crate::persistence;
+
+#[cfg(test)]
+mod tests;
+
+const AUDIT_MODE_FILE: &str = "audit_mode.json";
+#[cfg(feature = "app")]
+const SETTINGS_FILE: &str = "settings.json";
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[allow(dead_code)]
 pub struct PerformanceConfig {
@@ -47,5 +64,160 @@ lazy_static! {
     pub static ref PERFORMANCE_CONFIG: PerformanceConfig = PerformanceConfig::default();
 }
 
+/// Audit mode: inspector mode that survives restarts, for cautious users
+/// and evaluations where nothing may be changed.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct AuditModeSettings {
+    pub enabled: bool,
+    #[serde(skip)]
+    path: Option<PathBuf>,
+}
+
+impl AuditModeSettings {
+    /// Load from `<data dir>/macos-optimizer/audit_mode.json`.
+    pub fn load() -> Self {
+        match persistence::data_file(AUDIT_MODE_FILE) {
+            Some(path) => Self::load_from(&path),
+            None => Self::default(),
+        }
+    }
+
+    /// Load from an explicit file; a missing or unreadable file leaves audit
+    /// mode off.
+    pub fn load_from(path: &Path) -> Self {
+        let mut settings = persistence::read_json::<AuditModeSettings>(path).unwrap_or_default();
+        settings.path = Some(path.to_path_buf());
+        settings
+    }
+
+    pub fn set_enabled(&mut self, enabled: bool) -> Result<(), String> {
+        self.enabled = enabled;
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        persistence::write_json(path, self)
+    }
+}
+
+/// User preferences every subsystem reads, edited through `get_settings`
+/// and `update_settings`. The telemetry opt-in and the concurrency profile
+/// are owned by [`crate::usage`] and [`crate::performance`] and only shown
+/// here; the rest is kept by [`SettingsStore`].
+#[cfg(feature = "app")]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AppSettings {
+    #[serde(default)]
+    pub telemetry_opt_in: bool,
+    #[serde(default)]
+    pub concurrency_profile: PerformanceProfile,
+    #[serde(flatten)]
+    pub stored: StoredSettings,
+}
+
+/// The part of [`AppSettings`] saved in `settings.json`.
+#[cfg(feature = "app")]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct StoredSettings {
+    /// Depth of the disk usage tree when a scan does not ask for one.
+    pub scan_depth: usize,
+    /// Whether Risky Mode is on, letting items that are not known to be safe
+    /// be selected; cleans that do not say otherwise follow it.
+    pub risky_mode: bool,
+    /// Walk rule paths on several threads; off walks them on one, for
+    /// disks that slow down under parallel reads.
+    pub parallel_scan: bool,
+    /// Search rule paths that pick files by type, size or age with
+    /// Spotlight instead of walking them, where the volume is indexed.
+    pub spotlight_discovery: bool,
+    /// Whether sizes are shown in decimal units, as Finder does, or binary.
+    pub size_units: UnitSystem,
+    pub schedules: SchedulePreferences,
+}
+
+#[cfg(feature = "app")]
+impl Default for StoredSettings {
+    fn default() -> Self {
+        Self {
+            scan_depth: DEFAULT_TREE_DEPTH,
+            risky_mode: false,
+            parallel_scan: true,
+            spotlight_discovery: false,
+            size_units: UnitSystem::default(),
+            schedules: SchedulePreferences::default(),
+        }
+    }
+}
+
+#[cfg(feature = "app")]
+impl StoredSettings {
+    pub fn validate(&self) -> Result<(), String> {
+        if !(1..=MAX_TREE_DEPTH).contains(&self.scan_depth) {
+            return Err(format!(
+                "Scan depth must be between 1 and {}",
+                MAX_TREE_DEPTH
+            ));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "app")]
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SchedulePreferences {
+    /// Skip due schedules without deleting them.
+    pub paused: bool,
+}
+
+#[cfg(feature = "app")]
+pub struct SettingsStore {
+    path: Option<PathBuf>,
+    settings: StoredSettings,
+}
+
+#[cfg(feature = "app")]
+impl SettingsStore {
+    /// Load from `<data dir>/macos-optimizer/settings.json`.
+    pub fn load() -> Self {
+        match persistence::data_file(SETTINGS_FILE) {
+            Some(path) => Self::load_from(&path),
+            None => Self {
+                path: None,
+                settings: StoredSettings::default(),
+            },
+        }
+    }
+
+    /// Load from an explicit file; a missing or invalid file yields the
+    /// defaults, and fields it lacks take theirs.
+    pub fn load_from(path: &Path) -> Self {
+        let settings = match persistence::read_json::<StoredSettings>(path) {
+            Some(settings) if settings.validate().is_err() => {
+                log::warn!("Ignoring invalid {}", path.display());
+                StoredSettings::default()
+            }
+            settings => settings.unwrap_or_default(),
+        };
+        Self {
+            path: Some(path.to_path_buf()),
+            settings,
+        }
+    }
+
+    pub fn settings(&self) -> StoredSettings {
+        self.settings.clone()
+    }
+
+    pub fn update(&mut self, settings: StoredSettings) -> Result<(), String> {
+        settings.validate()?;
+        if let Some(path) = &self.path {
+            persistence::write_json(path, &settings)?;
+        }
+        self.settings = settings;
+        Ok(())
+    }
+}
+
 // Operation metrics tracking
 // Removed lightweight timing helpers to keep the crate warning-free.
@@ -0,0 +1,91 @@
+use super::AuditModeSettings;
+#[cfg(feature = "app")]
+use super::{AppSettings, SchedulePreferences, SettingsStore, StoredSettings};
+#[cfg(feature = "app")]
+use crate::formatting::UnitSystem;
+#[cfg(feature = "app")]
+use crate::performance::PerformanceProfile;
+
+#[test]
+fn audit_mode_persists_and_defaults_off() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("audit_mode.json");
+    let mut settings = AuditModeSettings::load_from(&path);
+    assert!(!settings.enabled);
+    settings.set_enabled(true).unwrap();
+    assert!(AuditModeSettings::load_from(&path).enabled);
+
+    std::fs::write(&path, b"not json").unwrap();
+    assert!(!AuditModeSettings::load_from(&path).enabled);
+}
+
+#[cfg(feature = "app")]
+#[test]
+fn settings_persist_and_reject_invalid_values() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("settings.json");
+    let mut store = SettingsStore::load_from(&path);
+    assert_eq!(store.settings(), StoredSettings::default());
+
+    let changed = StoredSettings {
+        scan_depth: 5,
+        risky_mode: true,
+        parallel_scan: false,
+        size_units: UnitSystem::Binary,
+        schedules: SchedulePreferences { paused: true },
+        ..StoredSettings::default()
+    };
+    store.update(changed.clone()).unwrap();
+    assert_eq!(SettingsStore::load_from(&path).settings(), changed);
+
+    assert!(store
+        .update(StoredSettings {
+            scan_depth: 0,
+            ..StoredSettings::default()
+        })
+        .is_err());
+    assert_eq!(store.settings(), changed);
+
+    // Files written before the opt-in and the profile moved out still load.
+    std::fs::write(
+        &path,
+        br#"{"scan_depth": 4, "risky_mode": true, "telemetry_opt_in": true}"#,
+    )
+    .unwrap();
+    let partial = SettingsStore::load_from(&path).settings();
+    assert_eq!(partial.scan_depth, 4);
+    assert!(partial.risky_mode);
+    assert_eq!(
+        partial.parallel_scan,
+        StoredSettings::default().parallel_scan
+    );
+}
+
+#[cfg(feature = "app")]
+#[test]
+fn app_settings_keep_one_flat_shape_and_save_only_their_own_part() {
+    let settings = AppSettings {
+        telemetry_opt_in: true,
+        concurrency_profile: PerformanceProfile::Aggressive,
+        stored: StoredSettings {
+            scan_depth: 6,
+            ..StoredSettings::default()
+        },
+    };
+    let json = serde_json::to_value(&settings).unwrap();
+    assert_eq!(json["scan_depth"], 6);
+    assert_eq!(json["telemetry_opt_in"], true);
+    assert_eq!(
+        serde_json::from_value::<AppSettings>(json).unwrap(),
+        settings
+    );
+
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("settings.json");
+    SettingsStore::load_from(&path)
+        .update(settings.stored.clone())
+        .unwrap();
+    let saved: serde_json::Value = serde_json::from_slice(&std::fs::read(&path).unwrap()).unwrap();
+    assert!(saved.get("telemetry_opt_in").is_none());
+    assert!(saved.get("concurrency_profile").is_none());
+}
@@ -0,0 +1,555 @@
+// src/container_cleaner.rs
+
+//! Docker and Podman leftovers: dangling images, stopped containers, unused
+//! volumes and builder cache.
+//!
+//! Everything goes through the engine's own CLI so the daemon stays the judge
+//! of what is still in use. Colima runs the Docker daemon inside a VM; when it
+//! is installed but stopped the Docker CLI cannot connect, and the report says
+//! so instead of showing nothing to clean.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tokio::process::Command;
+use tokio_util::sync::CancellationToken;
+
+use crate::capabilities::{self, Tool};
+
+#[cfg(test)]
+mod tests;
+
+/// Scan and prune progress callback: `(percent, message)`.
+pub type ContainerProgressFn = dyn Fn(f32, &str) + Send + Sync;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ContainerEngine {
+    Docker,
+    Podman,
+}
+
+impl ContainerEngine {
+    pub const ALL: [ContainerEngine; 2] = [ContainerEngine::Docker, ContainerEngine::Podman];
+
+    fn tool(self) -> Tool {
+        match self {
+            ContainerEngine::Docker => Tool::Docker,
+            ContainerEngine::Podman => Tool::Podman,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        self.tool().command()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ArtifactKind {
+    DanglingImages,
+    StoppedContainers,
+    UnusedVolumes,
+    BuildCache,
+}
+
+impl ArtifactKind {
+    pub const ALL: [ArtifactKind; 4] = [
+        ArtifactKind::DanglingImages,
+        ArtifactKind::StoppedContainers,
+        ArtifactKind::UnusedVolumes,
+        ArtifactKind::BuildCache,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            ArtifactKind::DanglingImages => "Dangling images",
+            ArtifactKind::StoppedContainers => "Stopped containers",
+            ArtifactKind::UnusedVolumes => "Unused volumes",
+            ArtifactKind::BuildCache => "Builder cache",
+        }
+    }
+
+    /// Row of `system df` that covers this kind.
+    fn df_type(self) -> &'static str {
+        match self {
+            ArtifactKind::DanglingImages => "Images",
+            ArtifactKind::StoppedContainers => "Containers",
+            ArtifactKind::UnusedVolumes => "Local Volumes",
+            ArtifactKind::BuildCache => "Build Cache",
+        }
+    }
+
+    fn list_args(self) -> Option<&'static [&'static str]> {
+        match self {
+            ArtifactKind::DanglingImages => Some(&[
+                "images",
+                "--filter",
+                "dangling=true",
+                "--format",
+                "{{json .}}",
+            ]),
+            ArtifactKind::StoppedContainers => Some(&[
+                "ps",
+                "--all",
+                "--filter",
+                "status=exited",
+                "--filter",
+                "status=created",
+                "--format",
+                "{{json .}}",
+            ]),
+            ArtifactKind::UnusedVolumes => Some(&[
+                "volume",
+                "ls",
+                "--filter",
+                "dangling=true",
+                "--format",
+                "{{json .}}",
+            ]),
+            // Cache records are only summarized by `system df`.
+            ArtifactKind::BuildCache => None,
+        }
+    }
+
+    /// `None` when the engine has no prune command for this kind.
+    fn prune_args(self, engine: ContainerEngine) -> Option<&'static [&'static str]> {
+        match (self, engine) {
+            (ArtifactKind::DanglingImages, _) => Some(&["image", "prune", "--force"]),
+            (ArtifactKind::StoppedContainers, _) => Some(&["container", "prune", "--force"]),
+            (ArtifactKind::UnusedVolumes, _) => Some(&["volume", "prune", "--force"]),
+            (ArtifactKind::BuildCache, ContainerEngine::Docker) => {
+                Some(&["builder", "prune", "--force"])
+            }
+            (ArtifactKind::BuildCache, ContainerEngine::Podman) => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ContainerArtifact {
+    pub id: String,
+    pub name: String,
+    /// Not reported per item for containers and volumes.
+    pub size: Option<u64>,
+    pub detail: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArtifactSummary {
+    pub kind: ArtifactKind,
+    pub count: usize,
+    pub reclaimable: u64,
+    pub items: Vec<ContainerArtifact>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EngineReport {
+    pub engine: ContainerEngine,
+    /// False when the CLI could not reach its daemon or VM.
+    pub available: bool,
+    pub error: Option<String>,
+    pub artifacts: Vec<ArtifactSummary>,
+    pub reclaimable: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ContainerScanReport {
+    /// Installed engines only.
+    pub engines: Vec<EngineReport>,
+    pub reclaimable: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PruneStep {
+    pub engine: ContainerEngine,
+    pub kind: ArtifactKind,
+    pub freed: u64,
+    pub removed: usize,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PruneResult {
+    pub steps: Vec<PruneStep>,
+    pub freed: u64,
+    pub removed: usize,
+    /// Set when inspector mode reported what would happen instead of doing it.
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+/// Report reclaimable artifacts for every installed engine.
+pub async fn scan_container_artifacts(
+    token: &CancellationToken,
+    progress: Option<&ContainerProgressFn>,
+) -> Result<ContainerScanReport, String> {
+    let engines: Vec<ContainerEngine> = ContainerEngine::ALL
+        .into_iter()
+        .filter(|engine| capabilities::tool_available(engine.tool()))
+        .collect();
+    let mut reports = Vec::new();
+    for (index, engine) in engines.iter().enumerate() {
+        if let Some(cb) = progress {
+            cb(
+                index as f32 / engines.len() as f32 * 100.0,
+                &format!("Checking {} artifacts", engine.name()),
+            );
+        }
+        reports.push(scan_engine(*engine, token).await?);
+    }
+    Ok(ContainerScanReport {
+        reclaimable: reports.iter().map(|report| report.reclaimable).sum(),
+        engines: reports,
+    })
+}
+
+async fn scan_engine(
+    engine: ContainerEngine,
+    token: &CancellationToken,
+) -> Result<EngineReport, String> {
+    let mut report = EngineReport {
+        engine,
+        available: false,
+        error: None,
+        artifacts: Vec::new(),
+        reclaimable: 0,
+    };
+    match run_cli(
+        engine,
+        &["version", "--format", "{{json .Server.Version}}"],
+        token,
+    )
+    .await
+    {
+        Ok(_) => report.available = true,
+        Err(err) if err == "cancelled" => return Err(err),
+        Err(err) => {
+            report.error = Some(unreachable_reason(engine, &err));
+            return Ok(report);
+        }
+    }
+
+    let df = match run_cli(engine, &["system", "df", "--format", "{{json .}}"], token).await {
+        Ok(output) => parse_json_lines(&output),
+        Err(err) if err == "cancelled" => return Err(err),
+        Err(err) => {
+            report.error = Some(err);
+            Vec::new()
+        }
+    };
+
+    for kind in ArtifactKind::ALL {
+        if kind.prune_args(engine).is_none() {
+            continue;
+        }
+        let items = match kind.list_args() {
+            Some(args) => match run_cli(engine, args, token).await {
+                Ok(output) => parse_json_lines(&output)
+                    .iter()
+                    .map(|row| parse_artifact(kind, row))
+                    .collect(),
+                Err(err) if err == "cancelled" => return Err(err),
+                Err(err) => {
+                    log::debug!("{} listing failed: {}", kind.label(), err);
+                    Vec::new()
+                }
+            },
+            None => Vec::new(),
+        };
+        let df_row = df
+            .iter()
+            .find(|row| text(row, &["Type"]).eq_ignore_ascii_case(kind.df_type()));
+        // The Images row counts every unused image, not just dangling ones.
+        let reclaimable = match kind {
+            ArtifactKind::DanglingImages => items.iter().filter_map(|item| item.size).sum(),
+            _ => df_row
+                .and_then(|row| parse_size(&text(row, &["Reclaimable"])))
+                .unwrap_or(0),
+        };
+        let count = match kind {
+            ArtifactKind::BuildCache => df_row
+                .and_then(|row| text(row, &["TotalCount", "Total"]).parse().ok())
+                .unwrap_or(0),
+            _ => items.len(),
+        };
+        if count == 0 && reclaimable == 0 {
+            continue;
+        }
+        report.reclaimable += reclaimable;
+        report.artifacts.push(ArtifactSummary {
+            kind,
+            count,
+            reclaimable,
+            items,
+        });
+    }
+    Ok(report)
+}
+
+fn unreachable_reason(engine: ContainerEngine, err: &str) -> String {
+    if engine == ContainerEngine::Docker && capabilities::tool_available(Tool::Colima) {
+        format!(
+            "Docker daemon is not reachable; if it runs in Colima, start it with `colima start` ({})",
+            err
+        )
+    } else {
+        format!("{} daemon is not reachable: {}", engine.name(), err)
+    }
+}
+
+fn select_engines(
+    scan: &ContainerScanReport,
+    engine: Option<ContainerEngine>,
+) -> Result<Vec<&EngineReport>, String> {
+    let engines: Vec<&EngineReport> = scan
+        .engines
+        .iter()
+        .filter(|report| report.available)
+        .filter(|report| engine.is_none_or(|wanted| wanted == report.engine))
+        .collect();
+    if engines.is_empty() {
+        return Err(match engine {
+            Some(engine) => format!("{} is not available", engine.name()),
+            None => "No container engine is available".into(),
+        });
+    }
+    Ok(engines)
+}
+
+/// What [`prune_container_artifacts`] would remove, from the scanned sizes.
+pub fn preview_container_prune(
+    scan: &ContainerScanReport,
+    engine: Option<ContainerEngine>,
+    kinds: &[ArtifactKind],
+) -> Result<PruneResult, String> {
+    if kinds.is_empty() {
+        return Err("No artifact types selected".into());
+    }
+    let mut result = PruneResult {
+        dry_run: true,
+        ..Default::default()
+    };
+    for report in select_engines(scan, engine)? {
+        for &kind in kinds {
+            let scanned = report.artifacts.iter().find(|summary| summary.kind == kind);
+            let step = PruneStep {
+                engine: report.engine,
+                kind,
+                freed: scanned.map(|summary| summary.reclaimable).unwrap_or(0),
+                removed: scanned.map(|summary| summary.count).unwrap_or(0),
+                error: kind.prune_args(report.engine).is_none().then(|| {
+                    format!(
+                        "{} cannot prune {}",
+                        report.engine.name(),
+                        kind.label().to_lowercase()
+                    )
+                }),
+            };
+            result.freed += step.freed;
+            result.removed += step.removed;
+            result.steps.push(step);
+        }
+    }
+    Ok(result)
+}
+
+/// Prune the selected kinds on one engine, or on every reachable engine.
+/// A failed step is recorded and the remaining kinds still run.
+pub async fn prune_container_artifacts(
+    engine: Option<ContainerEngine>,
+    kinds: &[ArtifactKind],
+    token: &CancellationToken,
+    progress: Option<&ContainerProgressFn>,
+) -> Result<PruneResult, String> {
+    if kinds.is_empty() {
+        return Err("No artifact types selected".into());
+    }
+    let scan = scan_container_artifacts(token, None).await?;
+    let engines = select_engines(&scan, engine)?;
+
+    let total_steps = engines.len() * kinds.len();
+    let mut result = PruneResult::default();
+    for report in engines {
+        for &kind in kinds {
+            if token.is_cancelled() {
+                return Err("cancelled".into());
+            }
+            if let Some(cb) = progress {
+                cb(
+                    result.steps.len() as f32 / total_steps as f32 * 100.0,
+                    &format!(
+                        "Pruning {} ({})",
+                        kind.label().to_lowercase(),
+                        report.engine.name()
+                    ),
+                );
+            }
+            let scanned = report.artifacts.iter().find(|summary| summary.kind == kind);
+            let mut step = PruneStep {
+                engine: report.engine,
+                kind,
+                freed: 0,
+                removed: 0,
+                error: None,
+            };
+            match kind.prune_args(report.engine) {
+                None => {
+                    step.error = Some(format!(
+                        "{} cannot prune {}",
+                        report.engine.name(),
+                        kind.label().to_lowercase()
+                    ))
+                }
+                Some(args) => match run_cli(report.engine, args, token).await {
+                    Ok(output) => {
+                        step.freed = parse_reclaimed(&output)
+                            .or(scanned.map(|summary| summary.reclaimable))
+                            .unwrap_or(0);
+                        // Docker 23+ keeps named volumes the scan listed,
+                        // so count what the prune says it removed.
+                        step.removed = if kind == ArtifactKind::UnusedVolumes {
+                            parse_pruned_volumes(&output)
+                        } else {
+                            scanned.map(|summary| summary.count).unwrap_or(0)
+                        };
+                    }
+                    Err(err) if err == "cancelled" => return Err(err),
+                    Err(err) => step.error = Some(err),
+                },
+            }
+            result.freed += step.freed;
+            result.removed += step.removed;
+            result.steps.push(step);
+        }
+    }
+    Ok(result)
+}
+
+/// Run the engine CLI; cancellation kills the child process.
+async fn run_cli(
+    engine: ContainerEngine,
+    args: &[&str],
+    token: &CancellationToken,
+) -> Result<String, String> {
+    let program = capabilities::tool_path(engine.tool())
+        .ok_or_else(|| format!("{} is not installed", engine.name()))?;
+    let mut command = Command::new(program);
+    command.args(args).kill_on_drop(true);
+    let output = tokio::select! {
+        _ = token.cancelled() => return Err("cancelled".into()),
+        output = command.output() => output
+            .map_err(|e| format!("Failed to run {}: {}", engine.name(), e))?,
+    };
+    if output.status.success() {
+        Ok(String::from_utf8_lossy(&output.stdout).to_string())
+    } else {
+        Err(format!(
+            "{} {} failed: {}",
+            engine.name(),
+            args.first().unwrap_or(&""),
+            String::from_utf8_lossy(&output.stderr).trim()
+        ))
+    }
+}
+
+/// One JSON object per line, as printed by `--format '{{json .}}'`.
+fn parse_json_lines(output: &str) -> Vec<Value> {
+    output
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .collect()
+}
+
+/// First present field among `keys`; list values (Podman's `Names`) are joined.
+fn text(row: &Value, keys: &[&str]) -> String {
+    keys.iter()
+        .find_map(|key| match row.get(*key)? {
+            Value::String(value) => Some(value.clone()),
+            Value::Number(value) => Some(value.to_string()),
+            Value::Array(values) => Some(
+                values
+                    .iter()
+                    .filter_map(Value::as_str)
+                    .collect::<Vec<_>>()
+                    .join(", "),
+            ),
+            _ => None,
+        })
+        .unwrap_or_default()
+}
+
+fn parse_artifact(kind: ArtifactKind, row: &Value) -> ContainerArtifact {
+    match kind {
+        ArtifactKind::DanglingImages => ContainerArtifact {
+            id: text(row, &["ID", "Id"]),
+            name: format!("{}:{}", text(row, &["Repository"]), text(row, &["Tag"])),
+            size: parse_size(&text(row, &["Size"])),
+            detail: text(row, &["CreatedSince", "Created"]),
+        },
+        ArtifactKind::StoppedContainers => ContainerArtifact {
+            id: text(row, &["ID", "Id"]),
+            name: text(row, &["Names", "Name"]),
+            size: None,
+            detail: format!("{} · {}", text(row, &["Image"]), text(row, &["Status"])),
+        },
+        ArtifactKind::UnusedVolumes | ArtifactKind::BuildCache => ContainerArtifact {
+            id: text(row, &["Name", "ID"]),
+            name: text(row, &["Name", "ID"]),
+            size: None,
+            detail: text(row, &["Driver"]),
+        },
+    }
+}
+
+/// Parse CLI sizes such as "1.2GB", "512kB", "3.5 MiB" or "1.2GB (40%)".
+/// Plain units are decimal, as the engines print them; "iB" units are binary.
+fn parse_size(value: &str) -> Option<u64> {
+    let value = value.split('(').next()?.trim();
+    let split = value
+        .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+        .unwrap_or(value.len());
+    let (number, unit) = value.split_at(split);
+    let number: f64 = number.parse().ok()?;
+    let multiplier: f64 = match unit.trim().to_ascii_lowercase().as_str() {
+        "" | "b" => 1.0,
+        "kb" => 1e3,
+        "mb" => 1e6,
+        "gb" => 1e9,
+        "tb" => 1e12,
+        "kib" => 1024.0,
+        "mib" => 1024.0 * 1024.0,
+        "gib" => 1024.0 * 1024.0 * 1024.0,
+        "tib" => 1024.0 * 1024.0 * 1024.0 * 1024.0,
+        _ => return None,
+    };
+    Some((number * multiplier).round() as u64)
+}
+
+/// Volumes `volume prune` removed: Docker lists them under "Deleted
+/// Volumes:", Podman prints one name per line.
+fn parse_pruned_volumes(output: &str) -> usize {
+    match output
+        .lines()
+        .position(|line| line.trim() == "Deleted Volumes:")
+    {
+        Some(header) => output
+            .lines()
+            .skip(header + 1)
+            .take_while(|line| !line.trim().is_empty())
+            .count(),
+        None => output
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with("Total reclaimed space:"))
+            .count(),
+    }
+}
+
+/// "Total reclaimed space: 1.2GB" from prune output.
+fn parse_reclaimed(output: &str) -> Option<u64> {
+    output
+        .lines()
+        .find_map(|line| line.trim().strip_prefix("Total reclaimed space:"))
+        .and_then(parse_size)
+}
@@ -0,0 +1,114 @@
+use super::{
+    parse_artifact, parse_json_lines, parse_pruned_volumes, parse_reclaimed, parse_size,
+    preview_container_prune, text, ArtifactKind, ArtifactSummary, ContainerEngine,
+    ContainerScanReport, EngineReport,
+};
+
+#[test]
+fn sizes_parse_decimal_and_binary_units() {
+    assert_eq!(parse_size("0B"), Some(0));
+    assert_eq!(parse_size("1.5kB"), Some(1_500));
+    assert_eq!(parse_size("1.2GB (40%)"), Some(1_200_000_000));
+    assert_eq!(parse_size("2 MiB"), Some(2 * 1024 * 1024));
+    assert_eq!(parse_size("unknown"), None);
+    assert_eq!(
+        parse_reclaimed("Deleted Images:\nsha256:abc\n\nTotal reclaimed space: 512MB\n"),
+        Some(512_000_000)
+    );
+    assert_eq!(parse_reclaimed("abc123\n"), None);
+}
+
+#[test]
+fn pruned_volumes_are_counted_from_the_prune_output() {
+    assert_eq!(
+        parse_pruned_volumes("Deleted Volumes:\n3f2a9c\n7be01d\n\nTotal reclaimed space: 1.2GB\n"),
+        2
+    );
+    // Named volumes Docker 23+ keeps leave nothing to list.
+    assert_eq!(parse_pruned_volumes("Total reclaimed space: 0B\n"), 0);
+    assert_eq!(parse_pruned_volumes("pgdata\ncache\n"), 2);
+}
+
+#[test]
+fn cli_rows_become_artifacts() {
+    let images = parse_json_lines(
+        "{\"ID\":\"4f1c\",\"Repository\":\"<none>\",\"Tag\":\"<none>\",\"Size\":\"1.1GB\",\"CreatedSince\":\"3 weeks ago\"}\n\
+         not json\n",
+    );
+    assert_eq!(images.len(), 1);
+    let image = parse_artifact(ArtifactKind::DanglingImages, &images[0]);
+    assert_eq!(image.id, "4f1c");
+    assert_eq!(image.size, Some(1_100_000_000));
+    assert_eq!(image.detail, "3 weeks ago");
+
+    // Podman prints container names as a list.
+    let containers = parse_json_lines(
+        "{\"Id\":\"9a2b\",\"Names\":[\"web\",\"web-old\"],\"Image\":\"nginx\",\"Status\":\"Exited (0) 2 days ago\"}",
+    );
+    let container = parse_artifact(ArtifactKind::StoppedContainers, &containers[0]);
+    assert_eq!(container.name, "web, web-old");
+    assert_eq!(container.detail, "nginx · Exited (0) 2 days ago");
+
+    let df = parse_json_lines(
+        "{\"Type\":\"Build Cache\",\"TotalCount\":\"12\",\"Reclaimable\":\"2.5GB\"}",
+    );
+    assert_eq!(text(&df[0], &["TotalCount", "Total"]), "12");
+}
+
+#[test]
+fn podman_has_no_builder_prune() {
+    assert!(ArtifactKind::BuildCache
+        .prune_args(ContainerEngine::Podman)
+        .is_none());
+    assert!(ArtifactKind::ALL
+        .iter()
+        .all(|kind| kind.prune_args(ContainerEngine::Docker).is_some()));
+}
+
+#[test]
+fn prune_preview_reports_scanned_sizes_without_running() {
+    let summary = |kind, count, reclaimable| ArtifactSummary {
+        kind,
+        count,
+        reclaimable,
+        items: Vec::new(),
+    };
+    let scan = ContainerScanReport {
+        engines: vec![
+            EngineReport {
+                engine: ContainerEngine::Docker,
+                available: true,
+                error: None,
+                artifacts: vec![summary(ArtifactKind::DanglingImages, 3, 900)],
+                reclaimable: 900,
+            },
+            EngineReport {
+                engine: ContainerEngine::Podman,
+                available: false,
+                error: Some("VM stopped".into()),
+                artifacts: Vec::new(),
+                reclaimable: 0,
+            },
+        ],
+        reclaimable: 900,
+    };
+
+    let preview = preview_container_prune(
+        &scan,
+        None,
+        &[ArtifactKind::DanglingImages, ArtifactKind::BuildCache],
+    )
+    .unwrap();
+    assert!(preview.dry_run);
+    assert_eq!(preview.freed, 900);
+    assert_eq!(preview.removed, 3);
+    assert_eq!(preview.steps.len(), 2);
+    assert!(preview.steps.iter().all(|step| step.error.is_none()));
+
+    assert!(preview_container_prune(
+        &scan,
+        Some(ContainerEngine::Podman),
+        &[ArtifactKind::DanglingImages]
+    )
+    .is_err());
+}
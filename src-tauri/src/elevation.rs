@@ -0,0 +1,106 @@
+// src/elevation.rs
+
+//! Running shell commands as root behind the system administrator prompt.
+//!
+//! Several commands share one prompt by running in a single script. Each is
+//! followed by an `OK:<key>` or `ERR:<key>` marker so callers can report
+//! them separately; a command without a marker did not run.
+
+use tokio::process::Command;
+use tokio_util::sync::CancellationToken;
+
+use crate::capabilities::{self, Tool};
+
+/// One `if …; then echo OK:key; else echo ERR:key; fi` per `(key, command)`,
+/// in order.
+pub(crate) fn marker_script<'a>(commands: impl IntoIterator<Item = (&'a str, String)>) -> String {
+    commands
+        .into_iter()
+        .map(|(key, command)| {
+            format!(
+                "if {} >/dev/null 2>&1; then echo OK:{key}; else echo ERR:{key}; fi",
+                command
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("; ")
+}
+
+/// Whether the command behind `key` succeeded, or `None` when it printed no
+/// marker. AppleScript returns shell output with `\r` line endings.
+pub(crate) fn marker_status(output: &str, key: &str) -> Option<bool> {
+    output
+        .split(['\r', '\n'])
+        .map(str::trim)
+        .find_map(|line| match line.split_once(':') {
+            Some(("OK", marker)) if marker == key => Some(true),
+            Some(("ERR", marker)) if marker == key => Some(false),
+            _ => None,
+        })
+}
+
+/// The AppleScript that runs `script` as root. Backslashes and double quotes
+/// are escaped so the script reaches the shell exactly as written.
+pub(crate) fn admin_applescript(script: &str) -> String {
+    format!(
+        "do shell script \"{}\" with administrator privileges",
+        applescript_escape(script)
+    )
+}
+
+/// [`admin_applescript`] with `prompt` shown above the password field in
+/// place of the generic request.
+pub(crate) fn admin_applescript_with_prompt(script: &str, prompt: &str) -> String {
+    format!(
+        "do shell script \"{}\" with prompt \"{}\" with administrator privileges",
+        applescript_escape(script),
+        applescript_escape(prompt)
+    )
+}
+
+fn applescript_escape(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+/// Run `script` through `osascript` with administrator privileges and return
+/// what it printed; cancellation kills the prompt.
+pub(crate) async fn run_as_admin(
+    script: &str,
+    token: &CancellationToken,
+) -> Result<String, String> {
+    run_admin_applescript(&admin_applescript(script), token).await
+}
+
+/// [`run_as_admin`] with `prompt` explaining what the password is for.
+pub(crate) async fn run_as_admin_with_prompt(
+    script: &str,
+    prompt: &str,
+    token: &CancellationToken,
+) -> Result<String, String> {
+    run_admin_applescript(&admin_applescript_with_prompt(script, prompt), token).await
+}
+
+async fn run_admin_applescript(
+    applescript: &str,
+    token: &CancellationToken,
+) -> Result<String, String> {
+    let program = capabilities::tool_path(Tool::Osascript).ok_or("osascript is not installed")?;
+    let mut command = Command::new(program);
+    command.arg("-e").arg(applescript).kill_on_drop(true);
+    let output = tokio::select! {
+        _ = token.cancelled() => return Err("cancelled".into()),
+        output = command.output() => output
+            .map_err(|e| format!("Failed to run osascript: {}", e))?,
+    };
+    if output.status.success() {
+        Ok(String::from_utf8_lossy(&output.stdout).to_string())
+    } else {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        // osascript reports a dismissed password prompt as error -128.
+        if stderr.contains("-128") {
+            Err("Administrator authorization was canceled".into())
+        } else {
+            Err(format!("Administrator command failed: {}", stderr.trim()))
+        }
+    }
+}
@@ -1,33 +1,102 @@
+#[cfg(feature = "app")]
+pub mod activity_heatmap;
 mod advanced_safety;
+pub mod age_thresholds;
+pub mod analyzer_plugins;
+#[cfg(feature = "app")]
+pub mod app_data;
+pub mod app_quit;
+#[cfg(feature = "app")]
+pub mod app_slimmer;
+pub mod app_uninstaller;
 mod auto_selection;
 mod cache;
+#[cfg(feature = "app")]
+pub mod cache_growth;
+pub mod custom_path;
+pub mod custom_rules;
 mod dependency_checker;
+pub mod developer_junk;
+#[cfg(feature = "app")]
+pub mod disk_analyzer;
+pub mod disk_usage;
+mod ds_store;
 pub mod duplicate_detector;
 mod engine;
 mod engine_utils;
 pub mod enhanced_engine;
 pub mod enhanced_rules;
+pub mod exclusions;
+#[cfg(feature = "app")]
+pub mod extension_stats;
+pub mod external_volumes;
+pub mod hashing;
+mod incremental;
+#[cfg(feature = "app")]
+pub mod installer_sources;
+#[cfg(feature = "app")]
+pub mod ios_backups;
+pub mod large_files;
+pub mod learned_exceptions;
 mod macos_integration;
+pub mod native_cleanup;
+mod native_trash;
+mod parallel_walk;
+mod path_alias;
+#[cfg(feature = "app")]
+pub mod photos_library;
 pub mod process_snapshot;
+pub mod project_artifacts;
+pub mod protection_packs;
+pub mod quarantine;
+pub mod quick_clean;
+pub mod recommendations;
+pub mod report_delta;
+#[cfg(feature = "app")]
+pub mod report_export;
+pub mod rule_packs;
+pub mod runtime_detection;
 mod safety;
+pub mod safety_policies;
+pub mod similar_images;
 pub mod smart_cache;
+pub mod spotlight;
+pub mod stale_locks;
+pub mod system_load;
 pub mod telemetry;
+pub mod trash_restore;
 pub mod types;
 mod validation;
+pub mod volumes;
 
 #[cfg(test)]
 mod tests;
 
 // Legacy exports for backward compatibility
+#[cfg(feature = "app")]
+pub(crate) use engine::is_osascript_disabled;
 pub use engine::FileCleaner;
-pub use types::{CleanableFile, CleaningReport};
+#[cfg(any(test, feature = "app"))]
+pub use types::CleanableFile;
+#[cfg(feature = "app")]
+pub use types::{
+    CleaningReport, TrashEmptyResult, TrashItem, TrashPurgeResult, TrashSummary, TrashVolume,
+};
+pub use types::{ScanProgress, TrashEmptyProgress};
 
 // Enhanced engine with all safety features - used by lib.rs
+#[cfg(any(test, feature = "app"))]
 pub use auto_selection::UserAction;
-#[cfg(feature = "cache-refresh")]
+#[cfg(all(feature = "cache-refresh", feature = "app"))]
 pub(crate) use cache::CacheRefresher;
-pub use enhanced_engine::{EnhancedCleaningReport, EnhancedDeletionProgress, EnhancedFileCleaner};
+#[cfg(feature = "app")]
+pub use cache::{persist_dir_size_cache, restore_dir_size_cache};
+#[cfg(feature = "app")]
+pub use enhanced_engine::EnhancedDeletionProgress;
+pub use enhanced_engine::{EnhancedCleaningReport, EnhancedFileCleaner};
 #[cfg(feature = "app")]
 pub use enhanced_rules::{DryRunReport, DynamicRuleEngine, RuleConflict, RuleValidator};
 #[cfg(feature = "app")]
 pub use types::load_rules_result;
+#[cfg(feature = "app")]
+pub use validation::{RestoredFile, UndoResult};
@@ -0,0 +1,502 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use chrono::DateTime;
+use serde::{Deserialize, Serialize};
+use tokio_util::sync::CancellationToken;
+use walkdir::WalkDir;
+
+/// Week columns in a heatmap, the current week last.
+pub const HEATMAP_WEEKS: usize = 26;
+/// Directories up to this many levels below the root get their own row;
+/// anything deeper is counted in its ancestor at this depth.
+const ROW_DEPTH: usize = 2;
+const DAY_SECS: i64 = 24 * 60 * 60;
+const WEEK_SECS: i64 = 7 * DAY_SECS;
+/// The Unix epoch fell on a Thursday; shifting by three days starts weeks on Monday.
+const MONDAY_OFFSET_SECS: i64 = 3 * DAY_SECS;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ActivityHeatmap {
+    pub root: String,
+    /// First day (Monday, UTC) of each week column, oldest first.
+    pub weeks: Vec<String>,
+    /// Largest directories first.
+    pub rows: Vec<ActivityRow>,
+    /// Unchanged directories were reused from the previous heatmap of this
+    /// root because FSEvents reported which ones changed since.
+    pub incremental: bool,
+    /// Files read for this heatmap; only changed directories when incremental.
+    pub files_scanned: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ActivityRow {
+    pub path: String,
+    /// Bytes of files whose last modification fell in each week of `weeks`.
+    pub bytes_per_week: Vec<u64>,
+    pub total_size: u64,
+    pub file_count: usize,
+    /// Newest modification time of any file, unix seconds.
+    pub last_modified: i64,
+    /// Holds files but none were modified during the heatmap's weeks; a
+    /// candidate for archiving.
+    pub dormant: bool,
+}
+
+#[derive(Debug, Clone, Default)]
+struct RowActivity {
+    /// Bytes by absolute week number, so rows stay valid as weeks pass.
+    bytes_by_week: BTreeMap<i64, u64>,
+    total_size: u64,
+    file_count: usize,
+    last_modified: i64,
+}
+
+struct RootSnapshot {
+    /// FSEvents id taken before the walk, or `None` without FSEvents.
+    event_id: Option<u64>,
+    rows: HashMap<PathBuf, RowActivity>,
+}
+
+/// Heatmaps built so far this session, kept so later requests for the same
+/// root only re-read the directories FSEvents reports as changed.
+#[derive(Default)]
+pub struct ActivityIndex {
+    snapshots: HashMap<PathBuf, RootSnapshot>,
+}
+
+impl ActivityIndex {
+    pub fn heatmap(
+        &mut self,
+        root: &Path,
+        now: i64,
+        token: &CancellationToken,
+    ) -> Result<ActivityHeatmap, String> {
+        let root = user_root(root)?;
+        // Taken before walking, so changes made during the walk are replayed
+        // next time.
+        let event_id = fsevents::current_event_id();
+        let changed = self
+            .snapshots
+            .get(&root)
+            .and_then(|snapshot| snapshot.event_id)
+            .and_then(|since| fsevents::changed_directories(&root, since));
+        self.heatmap_with_changes(&root, now, event_id, changed, token)
+    }
+
+    /// Build the heatmap for `root`, re-reading only the directories in
+    /// `changed` when a previous snapshot exists, or everything for `None`.
+    pub(crate) fn heatmap_with_changes(
+        &mut self,
+        root: &Path,
+        now: i64,
+        event_id: Option<u64>,
+        changed: Option<Vec<PathBuf>>,
+        token: &CancellationToken,
+    ) -> Result<ActivityHeatmap, String> {
+        let previous = self.snapshots.remove(root);
+        let incremental = previous.is_some() && changed.is_some();
+        let (mut rows, dirty) = match (previous, changed) {
+            (Some(snapshot), Some(changed)) => (snapshot.rows, dirty_rows(root, &changed)),
+            _ => (HashMap::new(), vec![root.to_path_buf()]),
+        };
+
+        // Drop the changed rows, and rows below them whose directory is gone.
+        rows.retain(|path, _| {
+            !dirty.contains(path)
+                && (path.is_dir() || !dirty.iter().any(|row| path.starts_with(row)))
+        });
+        let mut walked: Vec<&PathBuf> = Vec::new();
+        let mut files_scanned = 0;
+        for row in dirty.iter().filter(|row| row.is_dir()) {
+            // Walking an ancestor already re-read this row.
+            if walked.iter().any(|ancestor| row.starts_with(ancestor)) {
+                continue;
+            }
+            files_scanned += walk_row(root, row, &mut rows, token)?;
+            walked.push(row);
+        }
+
+        let heatmap = render(root, &rows, now, incremental, files_scanned);
+        self.snapshots
+            .insert(root.to_path_buf(), RootSnapshot { event_id, rows });
+        Ok(heatmap)
+    }
+}
+
+/// `root` resolved, required to be a directory inside the home folder.
+fn user_root(root: &Path) -> Result<PathBuf, String> {
+    let home = dirs::home_dir().ok_or("Home directory not found")?;
+    let home = home.canonicalize().unwrap_or(home);
+    let root = root
+        .canonicalize()
+        .map_err(|e| format!("Cannot open {}: {}", root.display(), e))?;
+    if !root.is_dir() {
+        return Err(format!("{} is not a directory", root.display()));
+    }
+    if !root.starts_with(&home) {
+        return Err("Activity heatmaps are limited to folders in your home directory".into());
+    }
+    Ok(root)
+}
+
+/// The row a directory's files are counted in.
+fn row_for(root: &Path, dir: &Path) -> Option<PathBuf> {
+    let relative = dir.strip_prefix(root).ok()?;
+    let mut row = root.to_path_buf();
+    for component in relative.components().take(ROW_DEPTH) {
+        row.push(component);
+    }
+    Some(row)
+}
+
+/// Rows holding the changed directories, ancestors first.
+fn dirty_rows(root: &Path, changed: &[PathBuf]) -> Vec<PathBuf> {
+    let mut rows: Vec<PathBuf> = changed
+        .iter()
+        .filter_map(|dir| row_for(root, dir))
+        .collect();
+    rows.sort();
+    rows.dedup();
+    rows
+}
+
+/// Re-read the files of `row`, and of any directory below it that has no
+/// row yet. Known rows below it are left alone. Returns the files read.
+fn walk_row(
+    root: &Path,
+    row: &Path,
+    rows: &mut HashMap<PathBuf, RowActivity>,
+    token: &CancellationToken,
+) -> Result<usize, String> {
+    let known: HashSet<PathBuf> = rows.keys().cloned().collect();
+    let mut files = 0usize;
+    let walker = WalkDir::new(row)
+        .into_iter()
+        .filter_entry(|entry| entry.depth() == 0 || !known.contains(entry.path()))
+        .filter_map(Result::ok);
+    for entry in walker {
+        if !entry.file_type().is_file() {
+            continue;
+        }
+        files += 1;
+        if files.is_multiple_of(4096) && token.is_cancelled() {
+            return Err("cancelled".into());
+        }
+        let Ok(metadata) = entry.metadata() else {
+            continue;
+        };
+        let Some(key) = entry.path().parent().and_then(|dir| row_for(root, dir)) else {
+            continue;
+        };
+        let modified = metadata
+            .modified()
+            .ok()
+            .and_then(|time| time.duration_since(SystemTime::UNIX_EPOCH).ok())
+            .map_or(0, |since| since.as_secs() as i64);
+        let activity = rows.entry(key).or_default();
+        let bytes = activity.bytes_by_week.entry(week_of(modified)).or_default();
+        *bytes = bytes.saturating_add(metadata.len());
+        activity.total_size = activity.total_size.saturating_add(metadata.len());
+        activity.file_count += 1;
+        activity.last_modified = activity.last_modified.max(modified);
+    }
+    if token.is_cancelled() {
+        return Err("cancelled".into());
+    }
+    Ok(files)
+}
+
+fn week_of(timestamp: i64) -> i64 {
+    (timestamp + MONDAY_OFFSET_SECS).div_euclid(WEEK_SECS)
+}
+
+fn render(
+    root: &Path,
+    rows: &HashMap<PathBuf, RowActivity>,
+    now: i64,
+    incremental: bool,
+    files_scanned: usize,
+) -> ActivityHeatmap {
+    let first_week = week_of(now) - (HEATMAP_WEEKS as i64 - 1);
+    let weeks = (0..HEATMAP_WEEKS as i64)
+        .map(|offset| {
+            let start = (first_week + offset) * WEEK_SECS - MONDAY_OFFSET_SECS;
+            DateTime::from_timestamp(start, 0)
+                .unwrap_or_default()
+                .format("%Y-%m-%d")
+                .to_string()
+        })
+        .collect();
+
+    let mut rows: Vec<ActivityRow> = rows
+        .iter()
+        .map(|(path, activity)| {
+            let mut bytes_per_week = vec![0u64; HEATMAP_WEEKS];
+            for (week, bytes) in activity.bytes_by_week.range(first_week..) {
+                if let Some(slot) = bytes_per_week.get_mut((week - first_week) as usize) {
+                    *slot = slot.saturating_add(*bytes);
+                }
+            }
+            ActivityRow {
+                path: path.to_string_lossy().to_string(),
+                dormant: activity.file_count > 0 && bytes_per_week.iter().all(|b| *b == 0),
+                bytes_per_week,
+                total_size: activity.total_size,
+                file_count: activity.file_count,
+                last_modified: activity.last_modified,
+            }
+        })
+        .collect();
+    rows.sort_by(|a, b| {
+        b.total_size
+            .cmp(&a.total_size)
+            .then_with(|| a.path.cmp(&b.path))
+    });
+
+    ActivityHeatmap {
+        root: root.to_string_lossy().to_string(),
+        weeks,
+        rows,
+        incremental,
+        files_scanned,
+    }
+}
+
+#[cfg(not(target_os = "macos"))]
+mod fsevents {
+    use std::path::{Path, PathBuf};
+
+    pub(super) fn current_event_id() -> Option<u64> {
+        None
+    }
+
+    pub(super) fn changed_directories(_root: &Path, _since: u64) -> Option<Vec<PathBuf>> {
+        None
+    }
+}
+
+/// Replays the FSEvents history kept by fseventsd to find the directories
+/// changed since an event id.
+#[cfg(target_os = "macos")]
+mod fsevents {
+    use std::ffi::{CStr, CString};
+    use std::os::raw::{c_char, c_void};
+    use std::path::{Path, PathBuf};
+    use std::sync::mpsc::{self, Sender};
+    use std::sync::Mutex;
+    use std::time::Duration;
+
+    type CFIndex = isize;
+    type FSEventStreamRef = *mut c_void;
+    type FSEventStreamCallback = extern "C" fn(
+        stream: FSEventStreamRef,
+        info: *mut c_void,
+        count: usize,
+        paths: *mut c_void,
+        flags: *const u32,
+        ids: *const u64,
+    );
+
+    const CF_STRING_ENCODING_UTF8: u32 = 0x0800_0100;
+    const CREATE_FLAG_NONE: u32 = 0;
+    const FLAG_MUST_SCAN_SUBDIRS: u32 = 0x01;
+    const FLAG_USER_DROPPED: u32 = 0x02;
+    const FLAG_KERNEL_DROPPED: u32 = 0x04;
+    const FLAG_EVENT_IDS_WRAPPED: u32 = 0x08;
+    const FLAG_HISTORY_DONE: u32 = 0x10;
+    const FLAG_ROOT_CHANGED: u32 = 0x20;
+    /// Any of these means the history cannot say exactly what changed.
+    const FLAGS_NEED_RESCAN: u32 = FLAG_MUST_SCAN_SUBDIRS
+        | FLAG_USER_DROPPED
+        | FLAG_KERNEL_DROPPED
+        | FLAG_EVENT_IDS_WRAPPED
+        | FLAG_ROOT_CHANGED;
+    /// Replaying a long history can take a moment; past this, rescan instead.
+    const HISTORY_TIMEOUT: Duration = Duration::from_secs(10);
+
+    /// Mirrors `FSEventStreamContext` from <CoreServices/FSEvents.h>.
+    #[repr(C)]
+    struct StreamContext {
+        version: CFIndex,
+        info: *mut c_void,
+        retain: *const c_void,
+        release: *const c_void,
+        copy_description: *const c_void,
+    }
+
+    /// Mirrors `CFArrayCallBacks`; only its address is used.
+    #[repr(C)]
+    struct CFArrayCallBacks {
+        version: CFIndex,
+        retain: *const c_void,
+        release: *const c_void,
+        copy_description: *const c_void,
+        equal: *const c_void,
+    }
+
+    #[link(name = "CoreFoundation", kind = "framework")]
+    extern "C" {
+        static kCFTypeArrayCallBacks: CFArrayCallBacks;
+        fn CFStringCreateWithCString(
+            allocator: *const c_void,
+            string: *const c_char,
+            encoding: u32,
+        ) -> *const c_void;
+        fn CFArrayCreate(
+            allocator: *const c_void,
+            values: *const *const c_void,
+            count: CFIndex,
+            callbacks: *const CFArrayCallBacks,
+        ) -> *const c_void;
+        fn CFRelease(object: *const c_void);
+    }
+
+    #[link(name = "CoreServices", kind = "framework")]
+    extern "C" {
+        fn FSEventsGetCurrentEventId() -> u64;
+        fn FSEventStreamCreate(
+            allocator: *const c_void,
+            callback: FSEventStreamCallback,
+            context: *mut StreamContext,
+            paths: *const c_void,
+            since_when: u64,
+            latency: f64,
+            flags: u32,
+        ) -> FSEventStreamRef;
+        fn FSEventStreamSetDispatchQueue(stream: FSEventStreamRef, queue: *mut c_void);
+        fn FSEventStreamStart(stream: FSEventStreamRef) -> u8;
+        fn FSEventStreamStop(stream: FSEventStreamRef);
+        fn FSEventStreamInvalidate(stream: FSEventStreamRef);
+        fn FSEventStreamRelease(stream: FSEventStreamRef);
+    }
+
+    // libdispatch is part of libSystem.
+    extern "C" {
+        fn dispatch_queue_create(label: *const c_char, attr: *const c_void) -> *mut c_void;
+        fn dispatch_release(object: *mut c_void);
+    }
+
+    struct Replay {
+        paths: Vec<PathBuf>,
+        rescan: bool,
+        done: Option<Sender<()>>,
+    }
+
+    pub(super) fn current_event_id() -> Option<u64> {
+        // SAFETY: takes no arguments and only reads the current event id.
+        Some(unsafe { FSEventsGetCurrentEventId() })
+    }
+
+    extern "C" fn on_events(
+        _stream: FSEventStreamRef,
+        info: *mut c_void,
+        count: usize,
+        paths: *mut c_void,
+        flags: *const u32,
+        _ids: *const u64,
+    ) {
+        if count == 0 || info.is_null() {
+            return;
+        }
+        // SAFETY: info is the Mutex<Replay> owned by changed_directories,
+        // which outlives the stream; paths and flags hold `count` entries.
+        let (replay, paths, flags) = unsafe {
+            (
+                &*(info as *const Mutex<Replay>),
+                std::slice::from_raw_parts(paths as *const *const c_char, count),
+                std::slice::from_raw_parts(flags, count),
+            )
+        };
+        let Ok(mut replay) = replay.lock() else {
+            return;
+        };
+        for (path, flag) in paths.iter().zip(flags) {
+            if flag & FLAGS_NEED_RESCAN != 0 {
+                replay.rescan = true;
+            }
+            if flag & FLAG_HISTORY_DONE != 0 {
+                if let Some(done) = replay.done.take() {
+                    let _ = done.send(());
+                }
+                continue;
+            }
+            // SAFETY: FSEvents passes NUL-terminated UTF-8 paths.
+            let path = unsafe { CStr::from_ptr(*path) };
+            replay
+                .paths
+                .push(PathBuf::from(path.to_string_lossy().into_owned()));
+        }
+    }
+
+    /// Directories under `root` changed since event `since`, or `None` when
+    /// the history is incomplete and everything must be read again.
+    pub(super) fn changed_directories(root: &Path, since: u64) -> Option<Vec<PathBuf>> {
+        let root_c = CString::new(root.to_string_lossy().as_bytes()).ok()?;
+        let (done_tx, done_rx) = mpsc::channel();
+        let replay = Box::new(Mutex::new(Replay {
+            paths: Vec::new(),
+            rescan: false,
+            done: Some(done_tx),
+        }));
+        let mut context = StreamContext {
+            version: 0,
+            info: &*replay as *const Mutex<Replay> as *mut c_void,
+            retain: std::ptr::null(),
+            release: std::ptr::null(),
+            copy_description: std::ptr::null(),
+        };
+
+        // SAFETY: every CF object created here is released below, and the
+        // stream is stopped and invalidated before `replay` is dropped, so
+        // the callback never sees a dangling pointer.
+        let finished = unsafe {
+            let path = CFStringCreateWithCString(
+                std::ptr::null(),
+                root_c.as_ptr(),
+                CF_STRING_ENCODING_UTF8,
+            );
+            if path.is_null() {
+                return None;
+            }
+            let paths = CFArrayCreate(std::ptr::null(), &path, 1, &kCFTypeArrayCallBacks);
+            CFRelease(path);
+            if paths.is_null() {
+                return None;
+            }
+            let stream = FSEventStreamCreate(
+                std::ptr::null(),
+                on_events,
+                &mut context,
+                paths,
+                since,
+                0.0,
+                CREATE_FLAG_NONE,
+            );
+            CFRelease(paths);
+            if stream.is_null() {
+                return None;
+            }
+            let label = c"macos-optimizer.activity-heatmap";
+            let queue = dispatch_queue_create(label.as_ptr(), std::ptr::null());
+            FSEventStreamSetDispatchQueue(stream, queue);
+            let started = FSEventStreamStart(stream) != 0;
+            let finished = started && done_rx.recv_timeout(HISTORY_TIMEOUT).is_ok();
+            if started {
+                FSEventStreamStop(stream);
+            }
+            FSEventStreamInvalidate(stream);
+            FSEventStreamRelease(stream);
+            dispatch_release(queue);
+            finished
+        };
+
+        let replay = (*replay).into_inner().ok()?;
+        if !finished || replay.rescan {
+            return None;
+        }
+        Some(replay.paths)
+    }
+}
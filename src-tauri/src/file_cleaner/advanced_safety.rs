@@ -1,9 +1,13 @@
+use super::analyzer_plugins::{run_registered_analyzers, AnalyzerInput};
 use super::process_snapshot::ProcessSnapshot;
+use crate::capabilities::{self, Capabilities, Tool};
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::fs;
 use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
 use tokio::process::Command;
 
 /// Multi-layer safety analysis system
@@ -17,15 +21,26 @@ pub struct SafetyAnalyzer {
 
 impl SafetyAnalyzer {
     pub fn new() -> Self {
+        Self::with_capabilities(capabilities::capabilities())
+    }
+
+    /// Analyzer whose system checks only use the tools `capabilities` reports.
+    pub(crate) fn with_capabilities(capabilities: Capabilities) -> Self {
         Self {
             pattern_detector: PatternBasedDetector::new(),
             usage_analyzer: FileUsageAnalyzer::new(),
             content_inspector: ContentInspector::new(),
-            system_checker: SystemIntegrationChecker::new(),
+            system_checker: SystemIntegrationChecker::new(capabilities),
             ml_predictor: None, // ML model can be loaded later
         }
     }
 
+    /// Give content inspection its full byte budget again, for a new scan.
+    pub(crate) fn reset_content_budget(&self) {
+        self.content_inspector
+            .reset_budget(CONTENT_SCAN_BYTE_BUDGET);
+    }
+
     #[cfg_attr(not(test), allow(dead_code))]
     pub async fn analyze(&self, path: &Path, category: &str) -> SafetyMetrics {
         self.analyze_with_snapshot(path, category, &ProcessSnapshot::empty())
@@ -38,15 +53,49 @@ impl SafetyAnalyzer {
         category: &str,
         process_snapshot: &ProcessSnapshot,
     ) -> SafetyMetrics {
+        self.analyze_layers(path, category, process_snapshot)
+            .await
+            .0
+    }
+
+    /// Like [`Self::analyze_with_snapshot`], also returning what each layer
+    /// contributed, in the order they ran.
+    pub async fn analyze_layers(
+        &self,
+        path: &Path,
+        category: &str,
+        process_snapshot: &ProcessSnapshot,
+    ) -> (SafetyMetrics, Vec<SafetyLayer>) {
         let mut base_score = 50u8; // Start neutral
         let mut confidence = 0.5f32;
         let mut risk_factors = Vec::new();
         let mut safety_flags = SafetyFlags::default();
+        let mut layers = Vec::new();
+        let mut record = |layer: &str,
+                          score_adjustment: i8,
+                          layer_confidence: f32,
+                          factors: &[RiskFactor],
+                          score_after: u8| {
+            layers.push(SafetyLayer {
+                layer: layer.to_string(),
+                score_adjustment,
+                score_after,
+                confidence: layer_confidence,
+                risk_factors: factors.to_vec(),
+            });
+        };
 
         // Layer 1: Pattern-based analysis
         let pattern_result = self.pattern_detector.analyze(path);
         base_score = adjust_score(base_score, pattern_result.score_adjustment);
         confidence = (confidence + pattern_result.confidence) / 2.0;
+        record(
+            "pattern",
+            pattern_result.score_adjustment,
+            pattern_result.confidence,
+            &pattern_result.risk_factors,
+            base_score,
+        );
         risk_factors.extend(pattern_result.risk_factors);
         safety_flags.merge(&pattern_result.flags);
 
@@ -54,6 +103,13 @@ impl SafetyAnalyzer {
         let usage_result = self.usage_analyzer.analyze(path, process_snapshot).await;
         base_score = adjust_score(base_score, usage_result.score_adjustment);
         confidence = (confidence + usage_result.confidence) / 2.0;
+        record(
+            "usage",
+            usage_result.score_adjustment,
+            usage_result.confidence,
+            &usage_result.risk_factors,
+            base_score,
+        );
         risk_factors.extend(usage_result.risk_factors);
         safety_flags.merge(&usage_result.flags);
 
@@ -62,6 +118,13 @@ impl SafetyAnalyzer {
             let content_result = self.content_inspector.inspect(path).await;
             base_score = adjust_score(base_score, content_result.score_adjustment);
             confidence = (confidence + content_result.confidence) / 2.0;
+            record(
+                "content",
+                content_result.score_adjustment,
+                content_result.confidence,
+                &content_result.risk_factors,
+                base_score,
+            );
             risk_factors.extend(content_result.risk_factors);
             safety_flags.merge(&content_result.flags);
         }
@@ -70,14 +133,58 @@ impl SafetyAnalyzer {
         let system_result = self.system_checker.check(path).await;
         base_score = adjust_score(base_score, system_result.score_adjustment);
         confidence = (confidence + system_result.confidence) / 2.0;
+        record(
+            "system",
+            system_result.score_adjustment,
+            system_result.confidence,
+            &system_result.risk_factors,
+            base_score,
+        );
         risk_factors.extend(system_result.risk_factors);
         safety_flags.merge(&system_result.flags);
 
-        // Layer 5: ML prediction (if available)
+        // Layer 5: Custom analyzers registered by the host application
+        let plugin_input = AnalyzerInput {
+            path,
+            category,
+            size: fs::metadata(path).ok().map(|m| m.len()),
+        };
+        for (analyzer, verdict) in run_registered_analyzers(&plugin_input) {
+            base_score = adjust_score(base_score, verdict.score_adjustment);
+            let factors: Vec<RiskFactor> = if verdict.score_adjustment < 0 {
+                verdict
+                    .reasons
+                    .into_iter()
+                    .map(|reason| RiskFactor::CustomAnalyzer {
+                        analyzer: analyzer.clone(),
+                        reason,
+                    })
+                    .collect()
+            } else {
+                Vec::new()
+            };
+            record(
+                &format!("analyzer:{}", analyzer),
+                verdict.score_adjustment,
+                confidence,
+                &factors,
+                base_score,
+            );
+            risk_factors.extend(factors);
+        }
+
+        // Layer 6: ML prediction (if available)
         if let Some(ref ml_model) = self.ml_predictor {
             let ml_result = ml_model.predict(path, &risk_factors).await;
             base_score = adjust_score(base_score, ml_result.score_adjustment);
             confidence = (confidence + ml_result.confidence) / 2.0;
+            record(
+                "ml",
+                ml_result.score_adjustment,
+                ml_result.confidence,
+                &[],
+                base_score,
+            );
         }
 
         // Determine recommendation based on final score
@@ -89,13 +196,14 @@ impl SafetyAnalyzer {
             _ => SafetyRecommendation::DoNotDelete,
         };
 
-        SafetyMetrics {
+        let metrics = SafetyMetrics {
             base_score,
             confidence,
             risk_factors,
             safety_flags,
             recommendation,
-        }
+        };
+        (metrics, layers)
     }
 }
 
@@ -236,7 +344,9 @@ impl FileUsageAnalyzer {
             }
 
             // Check if file is currently open by any process
-            if process_snapshot.command_contains_path(&path_lower) {
+            if process_snapshot.has_open_file(path)
+                || process_snapshot.command_contains_path(&path_lower)
+            {
                 score_adjustment -= 50;
                 confidence += 0.2;
                 risk_factors.push(RiskFactor::CurrentlyInUse);
@@ -254,26 +364,47 @@ impl FileUsageAnalyzer {
 }
 
 /// Inspects file content for sensitive data
+///
+/// Reading happens on the blocking pool through a memory map, so a slow
+/// disk never stalls the async runtime, and all inspections of a scan share
+/// one byte budget. Clones share the patterns and the budget.
+#[derive(Clone)]
 pub struct ContentInspector {
-    sensitive_patterns: Vec<regex::Regex>,
-    binary_signatures: HashMap<Vec<u8>, String>,
+    sensitive_patterns: Arc<Vec<regex::bytes::Regex>>,
+    binary_signatures: Arc<HashMap<Vec<u8>, String>>,
+    remaining_bytes: Arc<AtomicU64>,
 }
 
 const MAX_TEXT_SCAN_BYTES: usize = 512 * 1024; // 512KB preview window
+/// Bytes all content inspections of one scan may read together.
+pub(crate) const CONTENT_SCAN_BYTE_BUDGET: u64 = 256 * 1024 * 1024;
+/// The preview is matched in chunks of this size...
+const SCAN_CHUNK_BYTES: usize = 64 * 1024;
+/// ...overlapping by this much, more than the longest fixed pattern, so a
+/// match across a chunk boundary is still found.
+const SCAN_CHUNK_OVERLAP: usize = 64;
+/// Extensions whose files are binary, so reading them for text is wasted.
+const BINARY_EXTENSIONS: &[&str] = &[
+    "png", "jpg", "jpeg", "gif", "heic", "webp", "tiff", "ico", "icns", "mp3", "m4a", "aac", "wav",
+    "flac", "mp4", "mov", "m4v", "avi", "mkv", "dylib", "so", "o", "a", "pyc", "class", "wasm",
+    "dmg", "iso", "ttf", "otf", "woff", "woff2", "car", "nib",
+];
 
 impl ContentInspector {
     pub fn new() -> Self {
         let mut sensitive_patterns = Vec::new();
 
         // Common patterns for sensitive data
-        if let Ok(re) = regex::Regex::new(r"(?i)(api[_-]?key|secret|password|token|credential)") {
+        if let Ok(re) =
+            regex::bytes::Regex::new(r"(?i)(api[_-]?key|secret|password|token|credential)")
+        {
             sensitive_patterns.push(re);
         }
-        if let Ok(re) = regex::Regex::new(r"[A-Za-z0-9+/]{40,}={0,2}") {
+        if let Ok(re) = regex::bytes::Regex::new(r"[A-Za-z0-9+/]{40,}={0,2}") {
             // Base64 encoded data
             sensitive_patterns.push(re);
         }
-        if let Ok(re) = regex::Regex::new(r"-----BEGIN (RSA |EC |)PRIVATE KEY-----") {
+        if let Ok(re) = regex::bytes::Regex::new(r"-----BEGIN (RSA |EC |)PRIVATE KEY-----") {
             sensitive_patterns.push(re);
         }
 
@@ -285,70 +416,120 @@ impl ContentInspector {
         binary_signatures.insert(vec![0x25, 0x50, 0x44, 0x46], "PDF".to_string());
 
         Self {
-            sensitive_patterns,
-            binary_signatures,
+            sensitive_patterns: Arc::new(sensitive_patterns),
+            binary_signatures: Arc::new(binary_signatures),
+            remaining_bytes: Arc::new(AtomicU64::new(CONTENT_SCAN_BYTE_BUDGET)),
         }
     }
 
+    /// Give the inspections from now on `bytes` to read between them.
+    pub(crate) fn reset_budget(&self, bytes: u64) {
+        self.remaining_bytes.store(bytes, Ordering::Relaxed);
+    }
+
     pub async fn inspect(&self, path: &Path) -> ContentInspectionResult {
+        let inspector = self.clone();
+        let path = path.to_path_buf();
+        tokio::task::spawn_blocking(move || inspector.inspect_blocking(&path))
+            .await
+            .unwrap_or_else(|_| ContentInspectionResult {
+                score_adjustment: 0,
+                confidence: 0.3,
+                risk_factors: vec![],
+                flags: SafetyFlags::default(),
+            })
+    }
+
+    fn inspect_blocking(&self, path: &Path) -> ContentInspectionResult {
         let mut score_adjustment = 0i8;
         let mut confidence = 0.5f32;
         let mut risk_factors = Vec::new();
         let mut flags = SafetyFlags::default();
 
+        let binary_extension = path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .is_some_and(|ext| BINARY_EXTENSIONS.contains(&ext.to_lowercase().as_str()));
+        if binary_extension {
+            flags.is_binary_file = true;
+            return ContentInspectionResult {
+                score_adjustment,
+                confidence,
+                risk_factors,
+                flags,
+            };
+        }
+
+        let Ok(file) = fs::File::open(path) else {
+            return ContentInspectionResult {
+                score_adjustment,
+                confidence,
+                risk_factors,
+                flags,
+            };
+        };
         // Skip inspection for very large files
-        if let Ok(metadata) = fs::metadata(path) {
-            if metadata.len() > 100 * 1024 * 1024 {
-                // 100MB
-                return ContentInspectionResult {
-                    score_adjustment: 0,
-                    confidence: 0.3,
-                    risk_factors: vec![],
-                    flags,
-                };
-            }
+        let len = file.metadata().map(|metadata| metadata.len()).unwrap_or(0);
+        if len > 100 * 1024 * 1024 {
+            // 100MB
+            return ContentInspectionResult {
+                score_adjustment: 0,
+                confidence: 0.3,
+                risk_factors: vec![],
+                flags,
+            };
         }
+        let window = len.min(MAX_TEXT_SCAN_BYTES as u64);
+        if window == 0 {
+            return ContentInspectionResult {
+                score_adjustment,
+                confidence,
+                risk_factors,
+                flags,
+            };
+        }
+        if !self.take_budget(window) {
+            flags.reduced_confidence = true;
+            return ContentInspectionResult {
+                score_adjustment: 0,
+                confidence: 0.3,
+                risk_factors: vec![RiskFactor::ContentScanBudgetExhausted],
+                flags,
+            };
+        }
+
+        // SAFETY: the map is only read within this call; a file truncated
+        // meanwhile by another process can fault the read, which is the
+        // usual caveat of mapping files we do not own.
+        let map = unsafe { memmap2::MmapOptions::new().len(window as usize).map(&file) };
+        let Ok(map) = map else {
+            return ContentInspectionResult {
+                score_adjustment,
+                confidence,
+                risk_factors,
+                flags,
+            };
+        };
 
-        // Try to read file header for binary detection
-        if let Ok(mut file) = fs::File::open(path) {
-            use std::io::Read;
-            let mut header = vec![0u8; 16];
-            if file.read(&mut header).is_ok() {
-                // Check for binary signatures
-                for (sig, file_type) in &self.binary_signatures {
-                    if header.starts_with(sig) {
-                        flags.is_binary_file = true;
-                        if file_type == "ZIP" || file_type == "PDF" {
-                            // These might contain important data
-                            score_adjustment -= 10;
-                            risk_factors
-                                .push(RiskFactor::PotentiallyImportantFileType(file_type.clone()));
-                        }
-                        break;
-                    }
+        // Check the file header for binary signatures
+        for (sig, file_type) in self.binary_signatures.iter() {
+            if map.starts_with(sig) {
+                flags.is_binary_file = true;
+                if file_type == "ZIP" || file_type == "PDF" {
+                    // These might contain important data
+                    score_adjustment -= 10;
+                    risk_factors.push(RiskFactor::PotentiallyImportantFileType(file_type.clone()));
                 }
+                break;
             }
         }
 
         // For text files, scan for sensitive patterns
-        if !flags.is_binary_file {
-            if let Ok(file) = fs::File::open(path) {
-                use std::io::Read;
-                let mut limited = file.take(MAX_TEXT_SCAN_BYTES as u64);
-                let mut buffer = Vec::with_capacity(MAX_TEXT_SCAN_BYTES);
-                if limited.read_to_end(&mut buffer).is_ok() {
-                    let preview = String::from_utf8_lossy(&buffer);
-                    for pattern in &self.sensitive_patterns {
-                        if pattern.is_match(&preview) {
-                            score_adjustment -= 30;
-                            confidence += 0.15;
-                            risk_factors.push(RiskFactor::ContainsSensitiveContent);
-                            flags.contains_sensitive_data = true;
-                            break;
-                        }
-                    }
-                }
-            }
+        if !flags.is_binary_file && self.contains_sensitive_pattern(&map) {
+            score_adjustment -= 30;
+            confidence += 0.15;
+            risk_factors.push(RiskFactor::ContainsSensitiveContent);
+            flags.contains_sensitive_data = true;
         }
 
         ContentInspectionResult {
@@ -358,14 +539,43 @@ impl ContentInspector {
             flags,
         }
     }
+
+    fn take_budget(&self, bytes: u64) -> bool {
+        self.remaining_bytes
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |remaining| {
+                remaining.checked_sub(bytes)
+            })
+            .is_ok()
+    }
+
+    fn contains_sensitive_pattern(&self, content: &[u8]) -> bool {
+        let mut start = 0;
+        loop {
+            let end = (start + SCAN_CHUNK_BYTES).min(content.len());
+            let chunk = &content[start..end];
+            if self
+                .sensitive_patterns
+                .iter()
+                .any(|pattern| pattern.is_match(chunk))
+            {
+                return true;
+            }
+            if end == content.len() {
+                return false;
+            }
+            start = end - SCAN_CHUNK_OVERLAP;
+        }
+    }
 }
 
 /// Checks system integration and dependencies
-pub struct SystemIntegrationChecker {}
+pub struct SystemIntegrationChecker {
+    capabilities: Capabilities,
+}
 
 impl SystemIntegrationChecker {
-    pub fn new() -> Self {
-        Self {}
+    pub fn new(capabilities: Capabilities) -> Self {
+        Self { capabilities }
     }
 
     pub async fn check(&self, path: &Path) -> SystemIntegrationResult {
@@ -375,7 +585,11 @@ impl SystemIntegrationChecker {
         let mut flags = SafetyFlags::default();
 
         // Check if file is referenced in system databases
-        if self.check_spotlight_index(path).await {
+        if !self.capabilities.is_available(Tool::Mdls) {
+            confidence -= 0.1;
+            risk_factors.push(RiskFactor::CheckUnavailable(Tool::Mdls.command().into()));
+            flags.reduced_confidence = true;
+        } else if self.check_spotlight_index(path).await {
             score_adjustment -= 15;
             confidence += 0.1;
             risk_factors.push(RiskFactor::IndexedBySpotlight);
@@ -383,7 +597,11 @@ impl SystemIntegrationChecker {
         }
 
         // Check for Time Machine exclusions
-        if self.check_time_machine_status(path).await {
+        if !self.capabilities.is_available(Tool::Tmutil) {
+            confidence -= 0.1;
+            risk_factors.push(RiskFactor::CheckUnavailable(Tool::Tmutil.command().into()));
+            flags.reduced_confidence = true;
+        } else if self.check_time_machine_status(path).await {
             score_adjustment += 10; // If excluded from backup, likely safe to delete
             confidence += 0.05;
             flags.excluded_from_backup = true;
@@ -472,6 +690,18 @@ pub struct SafetyMetrics {
     pub recommendation: SafetyRecommendation,
 }
 
+/// What one analysis layer contributed to a file's safety score.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SafetyLayer {
+    /// `pattern`, `usage`, `content`, `system`, `analyzer:<name>` or `ml`.
+    pub layer: String,
+    pub score_adjustment: i8,
+    /// The score once this layer applied, starting from a neutral 50.
+    pub score_after: u8,
+    pub confidence: f32,
+    pub risk_factors: Vec<RiskFactor>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum RiskFactor {
     ContainsSensitivePattern(String),
@@ -486,6 +716,14 @@ pub enum RiskFactor {
     LargeFileSize(u64),
     UserCreatedContent,
     SafetyAnalysisDeferred,
+    /// A check was skipped because its command-line tool is missing.
+    CheckUnavailable(String),
+    /// The scan's byte budget for reading file contents ran out first.
+    ContentScanBudgetExhausted,
+    CustomAnalyzer {
+        analyzer: String,
+        reason: String,
+    },
 }
 
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
@@ -500,6 +738,8 @@ pub struct SafetyFlags {
     pub system_indexed: bool,
     pub excluded_from_backup: bool,
     pub is_system_component: bool,
+    /// Some checks were skipped, so the score rests on less evidence.
+    pub reduced_confidence: bool,
 }
 
 impl SafetyFlags {
@@ -514,6 +754,7 @@ impl SafetyFlags {
         self.system_indexed |= other.system_indexed;
         self.excluded_from_backup |= other.excluded_from_backup;
         self.is_system_component |= other.is_system_component;
+        self.reduced_confidence |= other.reduced_confidence;
     }
 }
 
@@ -565,7 +806,7 @@ pub(crate) fn adjust_score(current: u8, adjustment: i8) -> u8 {
     if adjustment >= 0 {
         current.saturating_add(adjustment as u8).min(100)
     } else {
-        current.saturating_sub(adjustment.unsigned_abs() as u8)
+        current.saturating_sub(adjustment.unsigned_abs())
     }
 }
 
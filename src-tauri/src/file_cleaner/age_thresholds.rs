@@ -0,0 +1,142 @@
+use std::collections::HashMap;
+use std::path::Path;
+#[cfg(any(test, feature = "app"))]
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+
+use super::types::CategoryRule;
+use crate::persistence;
+
+const AGE_THRESHOLDS_FILE: &str = "age_thresholds.json";
+/// Ten years; anything older is not worth a threshold.
+#[cfg(any(test, feature = "app"))]
+const MAX_AGE_DAYS: i64 = 3650;
+
+/// Categories whose threshold also decides when the matching kind of path
+/// counts as safe in the risk assessment.
+pub(crate) const SAVED_STATE_CATEGORY: &str = "Saved Application State (30d+)";
+pub(crate) const USER_LOGS_CATEGORY: &str = "User Logs (30d+)";
+pub(crate) const CRASH_REPORTS_CATEGORY: &str = "Crash Reports (30d+)";
+
+/// The user's replacement for a category's `min_age_days`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AgeThreshold {
+    pub category: String,
+    pub min_age_days: i64,
+}
+
+/// Per-category age thresholds chosen in settings.
+#[derive(Default)]
+pub struct AgeThresholdStore {
+    #[cfg(any(test, feature = "app"))]
+    path: Option<PathBuf>,
+    thresholds: Vec<AgeThreshold>,
+}
+
+impl AgeThresholdStore {
+    /// Load from `<data dir>/macos-optimizer/age_thresholds.json`.
+    pub fn load() -> Self {
+        match persistence::data_file(AGE_THRESHOLDS_FILE) {
+            Some(path) => Self::load_from(&path),
+            None => Self::default(),
+        }
+    }
+
+    /// Load from an explicit file; a missing or unreadable file yields an empty store.
+    pub fn load_from(path: &Path) -> Self {
+        let thresholds = persistence::read_json(path).unwrap_or_default();
+        Self {
+            #[cfg(any(test, feature = "app"))]
+            path: Some(path.to_path_buf()),
+            thresholds,
+        }
+    }
+
+    #[cfg(any(test, feature = "app"))]
+    pub fn list(&self) -> Vec<AgeThreshold> {
+        self.thresholds.clone()
+    }
+
+    /// Override `category`'s minimum age, or restore the rule default with `None`.
+    #[cfg(any(test, feature = "app"))]
+    pub fn set(&mut self, category: &str, min_age_days: Option<i64>) -> Result<(), String> {
+        let category = category.trim();
+        if category.is_empty() {
+            return Err("Category cannot be empty".into());
+        }
+        self.thresholds
+            .retain(|threshold| !threshold.category.eq_ignore_ascii_case(category));
+        if let Some(days) = min_age_days {
+            if !(0..=MAX_AGE_DAYS).contains(&days) {
+                return Err(format!(
+                    "Age threshold must be between 0 and {} days",
+                    MAX_AGE_DAYS
+                ));
+            }
+            self.thresholds.push(AgeThreshold {
+                category: category.to_string(),
+                min_age_days: days,
+            });
+        }
+        self.persist()
+    }
+
+    pub(crate) fn matcher(&self) -> AgeThresholds {
+        AgeThresholds {
+            days: self
+                .thresholds
+                .iter()
+                .map(|threshold| (threshold.category.to_lowercase(), threshold.min_age_days))
+                .collect(),
+        }
+    }
+
+    #[cfg(any(test, feature = "app"))]
+    fn persist(&self) -> Result<(), String> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        persistence::write_json(path, &self.thresholds)
+    }
+}
+
+/// Stored thresholds, compiled for scans.
+#[derive(Debug, Clone, Default)]
+pub struct AgeThresholds {
+    days: HashMap<String, i64>,
+}
+
+impl AgeThresholds {
+    /// The stored thresholds; none if they cannot be loaded.
+    pub(crate) fn load() -> Self {
+        AgeThresholdStore::load().matcher()
+    }
+
+    pub fn days_for(&self, category: &str) -> Option<i64> {
+        self.days.get(&category.to_lowercase()).copied()
+    }
+
+    /// Replace the `min_age_days` of every overridden rule.
+    pub(crate) fn apply(&self, rules: &mut [CategoryRule]) {
+        for rule in rules {
+            if let Some(days) = self.days_for(&rule.name) {
+                rule.min_age_days = Some(days);
+            }
+        }
+    }
+
+    /// `category` as shown to the user: an age tag such as "(90d+" follows
+    /// the override, and one is appended if the name has none.
+    pub fn label(&self, category: &str) -> Option<String> {
+        let days = self.days_for(category)?;
+        if let Some(open) = category.find('(') {
+            let tag = &category[open + 1..];
+            let digits = tag.find(|c: char| !c.is_ascii_digit()).unwrap_or(tag.len());
+            if digits > 0 && tag[digits..].starts_with("d+") {
+                return Some(format!("{}{}{}", &category[..=open], days, &tag[digits..]));
+            }
+        }
+        Some(format!("{} ({}d+)", category, days))
+    }
+}
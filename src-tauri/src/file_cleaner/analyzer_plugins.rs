@@ -0,0 +1,125 @@
+use std::path::Path;
+use std::sync::{Arc, RwLock};
+
+use lazy_static::lazy_static;
+use regex::Regex;
+
+/// Everything a custom analyzer gets to look at for one candidate.
+pub struct AnalyzerInput<'a> {
+    pub path: &'a Path,
+    pub category: &'a str,
+    pub size: Option<u64>,
+}
+
+/// What a custom analyzer contributes to the safety score.
+#[derive(Debug, Clone, Default)]
+pub struct AnalyzerVerdict {
+    /// Added to the running safety score (negative makes the file less deletable).
+    pub score_adjustment: i8,
+    pub reasons: Vec<String>,
+}
+
+/// A custom safety heuristic evaluated after the built-in layers.
+///
+/// Implementations are registered once at startup with [`register_analyzer`]
+/// and shared by every scan, so they must be cheap and side-effect free.
+pub trait AnalyzerPlugin: Send + Sync {
+    fn name(&self) -> &str;
+
+    /// Return `None` when the analyzer has no opinion on this path.
+    fn analyze(&self, input: &AnalyzerInput<'_>) -> Option<AnalyzerVerdict>;
+}
+
+lazy_static! {
+    static ref ANALYZER_REGISTRY: RwLock<Vec<Arc<dyn AnalyzerPlugin>>> = RwLock::new(Vec::new());
+}
+
+/// Add an analyzer to the process-wide registry. Registering a second analyzer
+/// with the same name replaces the first.
+pub fn register_analyzer(plugin: Arc<dyn AnalyzerPlugin>) {
+    if let Ok(mut registry) = ANALYZER_REGISTRY.write() {
+        registry.retain(|existing| existing.name() != plugin.name());
+        registry.push(plugin);
+    }
+}
+
+/// Remove a previously registered analyzer; returns whether one was removed.
+pub fn unregister_analyzer(name: &str) -> bool {
+    match ANALYZER_REGISTRY.write() {
+        Ok(mut registry) => {
+            let before = registry.len();
+            registry.retain(|existing| existing.name() != name);
+            registry.len() != before
+        }
+        Err(_) => false,
+    }
+}
+
+pub fn registered_analyzers() -> Vec<String> {
+    ANALYZER_REGISTRY
+        .read()
+        .map(|registry| registry.iter().map(|p| p.name().to_string()).collect())
+        .unwrap_or_default()
+}
+
+/// Run every registered analyzer, returning `(analyzer name, verdict)` pairs.
+pub(crate) fn run_registered_analyzers(
+    input: &AnalyzerInput<'_>,
+) -> Vec<(String, AnalyzerVerdict)> {
+    let plugins: Vec<Arc<dyn AnalyzerPlugin>> = match ANALYZER_REGISTRY.read() {
+        Ok(registry) => registry.clone(),
+        Err(_) => return Vec::new(),
+    };
+    plugins
+        .iter()
+        .filter_map(|plugin| {
+            plugin
+                .analyze(input)
+                .map(|verdict| (plugin.name().to_string(), verdict))
+        })
+        .collect()
+}
+
+/// Ready-made analyzer that penalizes paths matching any of a set of regexes,
+/// e.g. to protect an organization's internal tooling directories.
+pub struct PathPatternAnalyzer {
+    name: String,
+    patterns: Vec<Regex>,
+    score_adjustment: i8,
+    reason: String,
+}
+
+impl PathPatternAnalyzer {
+    pub fn protect(name: &str, patterns: &[&str], reason: &str) -> Result<Self, String> {
+        let patterns = patterns
+            .iter()
+            .map(|pattern| {
+                Regex::new(pattern).map_err(|e| format!("Invalid pattern {}: {}", pattern, e))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Self {
+            name: name.to_string(),
+            patterns,
+            score_adjustment: -100,
+            reason: reason.to_string(),
+        })
+    }
+}
+
+impl AnalyzerPlugin for PathPatternAnalyzer {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn analyze(&self, input: &AnalyzerInput<'_>) -> Option<AnalyzerVerdict> {
+        let path = input.path.to_string_lossy();
+        if self.patterns.iter().any(|pattern| pattern.is_match(&path)) {
+            Some(AnalyzerVerdict {
+                score_adjustment: self.score_adjustment,
+                reasons: vec![self.reason.clone()],
+            })
+        } else {
+            None
+        }
+    }
+}
@@ -0,0 +1,320 @@
+// src/file_cleaner/app_data.rs
+
+//! Itemized breakdowns of the data Mail, Messages and Photos keep on disk.
+//!
+//! The scan reports these as single "Review" buckets; here each becomes a
+//! browsable list grouped the way the user thinks about it: Mail downloads
+//! by age, Messages attachments by conversation and Photos caches by what
+//! regenerates them. Nothing is changed, only measured.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::SystemTime;
+
+use serde::{Deserialize, Serialize};
+use walkdir::WalkDir;
+
+use super::engine::FileCleaner;
+use crate::capabilities::{self, Tool};
+
+/// Each group lists at most this many of its largest items; its totals still
+/// count everything.
+const MAX_GROUP_ITEMS: usize = 200;
+const UNKNOWN_CONVERSATION: &str = "Unknown conversation";
+const MESSAGES_ATTACHMENTS: &str = "Library/Messages/Attachments";
+const MESSAGES_DATABASE: &str = "Library/Messages/chat.db";
+const MAIL_DOWNLOADS: &[&str] = &[
+    "Library/Containers/com.apple.mail/Data/Library/Mail Downloads",
+    "Library/Mail Downloads",
+];
+const PHOTOS_LIBRARY: &str = "Pictures/Photos Library.photoslibrary";
+/// Photos cache locations relative to the home folder, with the group each
+/// is listed under.
+const PHOTOS_CACHES: &[(&str, &str)] = &[
+    (
+        "Pictures/Photos Library.photoslibrary/resources/derivatives",
+        "Previews and thumbnails",
+    ),
+    (
+        "Pictures/Photos Library.photoslibrary/resources/caches",
+        "Library caches",
+    ),
+    (
+        "Pictures/Photos Library.photoslibrary/private/com.apple.photoanalysisd/caches",
+        "Analysis caches",
+    ),
+    (
+        "Library/Containers/com.apple.photoanalysisd/Data/Library/Caches",
+        "Analysis caches",
+    ),
+    (
+        "Library/Containers/com.apple.Photos/Data/Library/Caches",
+        "App caches",
+    ),
+];
+/// Upper bounds in days, oldest bucket open-ended.
+const AGE_BUCKETS: &[(u64, &str)] = &[
+    (30, "Last 30 days"),
+    (90, "1–3 months"),
+    (365, "3–12 months"),
+    (u64::MAX, "Over a year"),
+];
+/// Attachment paths and the chat each was sent in, named when the chat has
+/// a name and by its handle otherwise.
+const CONVERSATION_QUERY: &str = "SELECT a.filename, \
+     COALESCE(NULLIF(c.display_name, ''), c.chat_identifier) \
+     FROM attachment a \
+     JOIN message_attachment_join ma ON ma.attachment_id = a.ROWID \
+     JOIN chat_message_join cm ON cm.message_id = ma.message_id \
+     JOIN chat c ON c.ROWID = cm.chat_id \
+     WHERE a.filename IS NOT NULL;";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AppDataApp {
+    Mail,
+    Messages,
+    Photos,
+}
+
+impl AppDataApp {
+    /// The scan category whose contents this app's report itemizes.
+    pub fn category(self) -> Option<&'static str> {
+        match self {
+            AppDataApp::Mail => Some("Mail Downloads (Review)"),
+            AppDataApp::Messages => Some("Messages Attachments (90d+, Review)"),
+            AppDataApp::Photos => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AppDataItem {
+    pub path: String,
+    /// Allocated bytes, matching the sizes shown for cleanable files.
+    pub size: u64,
+    /// Days since the file was last modified.
+    pub age_days: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AppDataGroup {
+    pub name: String,
+    pub size: u64,
+    pub count: usize,
+    /// Largest first, at most [`MAX_GROUP_ITEMS`] of them.
+    pub items: Vec<AppDataItem>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AgeBucket {
+    pub label: String,
+    pub size: u64,
+    pub count: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AppDataReport {
+    pub app: AppDataApp,
+    pub category: Option<String>,
+    pub total_size: u64,
+    pub total_count: usize,
+    /// Largest first.
+    pub groups: Vec<AppDataGroup>,
+    /// Newest first, empty buckets included.
+    pub age_buckets: Vec<AgeBucket>,
+    /// Why parts of the breakdown are missing, e.g. no access to a folder.
+    pub notes: Vec<String>,
+}
+
+/// Itemize `app`'s data under the current user's home folder.
+pub fn analyze_app_data(app: AppDataApp) -> Result<AppDataReport, String> {
+    let home = dirs::home_dir().ok_or("Home directory not found")?;
+    let mut notes = Vec::new();
+    let conversations = match app {
+        AppDataApp::Messages => match message_conversations(&home) {
+            Ok(conversations) => conversations,
+            Err(err) => {
+                notes.push(format!(
+                    "Attachments aren't grouped by conversation: {}",
+                    err
+                ));
+                HashMap::new()
+            }
+        },
+        _ => HashMap::new(),
+    };
+    let mut report = analyze_app_data_in(app, &home, &conversations, SystemTime::now());
+    notes.append(&mut report.notes);
+    report.notes = notes;
+    Ok(report)
+}
+
+/// Itemize `app`'s data under `home`, naming Messages conversations from
+/// `conversations` (attachment path to chat name).
+pub fn analyze_app_data_in(
+    app: AppDataApp,
+    home: &Path,
+    conversations: &HashMap<PathBuf, String>,
+    now: SystemTime,
+) -> AppDataReport {
+    let mut notes = Vec::new();
+    let mut grouped: HashMap<String, Vec<AppDataItem>> = HashMap::new();
+    let mut add_files = |root: &Path, group_of: &dyn Fn(&AppDataItem) -> String| {
+        for item in files_under(root, now) {
+            let group = group_of(&item);
+            grouped.entry(group).or_default().push(item);
+        }
+    };
+    match app {
+        AppDataApp::Mail => {
+            let roots: Vec<PathBuf> = MAIL_DOWNLOADS
+                .iter()
+                .map(|rel| home.join(rel))
+                .filter(|root| root.is_dir())
+                .collect();
+            if roots.is_empty() {
+                notes.push("No Mail downloads folder found".to_string());
+            }
+            // Mail keeps one folder per message, so age is the useful grouping.
+            for root in roots {
+                add_files(&root, &|item| {
+                    AGE_BUCKETS[age_bucket(item.age_days)].1.to_string()
+                });
+            }
+        }
+        AppDataApp::Messages => {
+            let root = home.join(MESSAGES_ATTACHMENTS);
+            if !root.is_dir() {
+                notes.push("No Messages attachments folder found".to_string());
+            }
+            add_files(&root, &|item| {
+                conversations
+                    .get(Path::new(&item.path))
+                    .cloned()
+                    .unwrap_or_else(|| UNKNOWN_CONVERSATION.to_string())
+            });
+        }
+        AppDataApp::Photos => {
+            if !home.join(PHOTOS_LIBRARY).is_dir() {
+                notes.push("The Photos library isn't in the Pictures folder".to_string());
+            }
+            for (rel, group) in PHOTOS_CACHES {
+                add_files(&home.join(rel), &|_| group.to_string());
+            }
+        }
+    }
+
+    let mut age_buckets: Vec<AgeBucket> = AGE_BUCKETS
+        .iter()
+        .map(|(_, label)| AgeBucket {
+            label: label.to_string(),
+            size: 0,
+            count: 0,
+        })
+        .collect();
+    let mut groups: Vec<AppDataGroup> = grouped
+        .into_iter()
+        .map(|(name, mut items)| {
+            for item in &items {
+                let index = age_bucket(item.age_days);
+                age_buckets[index].size += item.size;
+                age_buckets[index].count += 1;
+            }
+            items.sort_by(|a, b| b.size.cmp(&a.size).then_with(|| a.path.cmp(&b.path)));
+            let size = items.iter().map(|item| item.size).sum();
+            let count = items.len();
+            items.truncate(MAX_GROUP_ITEMS);
+            AppDataGroup {
+                name,
+                size,
+                count,
+                items,
+            }
+        })
+        .collect();
+    groups.sort_by(|a, b| b.size.cmp(&a.size).then_with(|| a.name.cmp(&b.name)));
+
+    AppDataReport {
+        app,
+        category: app.category().map(str::to_string),
+        total_size: groups.iter().map(|group| group.size).sum(),
+        total_count: groups.iter().map(|group| group.count).sum(),
+        groups,
+        age_buckets,
+        notes,
+    }
+}
+
+fn files_under(root: &Path, now: SystemTime) -> Vec<AppDataItem> {
+    WalkDir::new(root)
+        .into_iter()
+        .filter_map(Result::ok)
+        .filter(|entry| entry.file_type().is_file())
+        .filter_map(|entry| {
+            let metadata = entry.metadata().ok()?;
+            let age_days = metadata
+                .modified()
+                .ok()
+                .and_then(|modified| now.duration_since(modified).ok())
+                .map(|age| age.as_secs() / 86_400)
+                .unwrap_or(0);
+            Some(AppDataItem {
+                path: entry.path().to_string_lossy().to_string(),
+                size: FileCleaner::metadata_size_bytes(&metadata),
+                age_days,
+            })
+        })
+        .collect()
+}
+
+/// Index into [`AGE_BUCKETS`] for a file `age_days` old.
+fn age_bucket(age_days: u64) -> usize {
+    AGE_BUCKETS
+        .iter()
+        .position(|(limit, _)| age_days < *limit)
+        .unwrap_or(AGE_BUCKETS.len() - 1)
+}
+
+/// Which chat each attachment belongs to, read from the Messages database.
+/// Needs `sqlite3` and Full Disk Access.
+fn message_conversations(home: &Path) -> Result<HashMap<PathBuf, String>, String> {
+    let sqlite = capabilities::tool_path(Tool::Sqlite3).ok_or("sqlite3 is not installed")?;
+    let output = Command::new(sqlite)
+        .args(["-readonly", "-separator", "\t"])
+        .arg(home.join(MESSAGES_DATABASE))
+        .arg(CONVERSATION_QUERY)
+        .output()
+        .map_err(|e| format!("Failed to run sqlite3: {}", e))?;
+    if !output.status.success() {
+        return Err(format!(
+            "the Messages database can't be read ({})",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(parse_attachment_conversations(
+        &String::from_utf8_lossy(&output.stdout),
+        home,
+    ))
+}
+
+/// Attachment paths and chat names from `sqlite3` output, one tab-separated
+/// pair per line. Messages stores paths with `~` for the home folder.
+pub fn parse_attachment_conversations(output: &str, home: &Path) -> HashMap<PathBuf, String> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let (path, chat) = line.split_once('\t')?;
+            let chat = chat.trim();
+            if chat.is_empty() {
+                return None;
+            }
+            let path = match path.strip_prefix("~/") {
+                Some(rest) => home.join(rest),
+                None => PathBuf::from(path),
+            };
+            Some((path, chat.to_string()))
+        })
+        .collect()
+}
@@ -0,0 +1,313 @@
+#[cfg(any(test, feature = "app"))]
+use std::fs;
+#[cfg(any(test, feature = "app"))]
+use std::path::{Path, PathBuf};
+
+#[cfg(any(test, feature = "app"))]
+use serde::{Deserialize, Serialize};
+#[cfg(any(test, feature = "app"))]
+use walkdir::WalkDir;
+
+use super::process_snapshot::ProcessSnapshot;
+#[cfg(any(test, feature = "app"))]
+use crate::persistence;
+
+#[cfg(feature = "app")]
+const APP_QUIT_FILE: &str = "app_quit_cleanup.json";
+
+/// A heavyweight app whose caches are only safe to clear while it is closed.
+#[derive(Debug)]
+pub struct HeavyApp {
+    pub key: &'static str,
+    #[cfg(any(test, feature = "app"))]
+    pub display_name: &'static str,
+    /// `CFBundleIdentifier`, which quit notifications name the app by.
+    #[cfg(any(test, feature = "app"))]
+    pub bundle_id: &'static str,
+    /// Any of these running means the app is still open.
+    pub process_names: &'static [&'static str],
+    /// Cache directories relative to the home directory; only their contents are removed.
+    #[cfg(any(test, feature = "app"))]
+    pub cache_dirs: &'static [&'static str],
+}
+
+pub const HEAVY_APPS: &[HeavyApp] = &[
+    HeavyApp {
+        key: "xcode",
+        #[cfg(any(test, feature = "app"))]
+        display_name: "Xcode",
+        #[cfg(any(test, feature = "app"))]
+        bundle_id: "com.apple.dt.Xcode",
+        process_names: &["Xcode", "xcodebuild", "XCBBuildService"],
+        #[cfg(any(test, feature = "app"))]
+        cache_dirs: &[
+            "Library/Developer/Xcode/DerivedData",
+            "Library/Caches/com.apple.dt.Xcode",
+        ],
+    },
+    HeavyApp {
+        key: "chrome",
+        #[cfg(any(test, feature = "app"))]
+        display_name: "Google Chrome",
+        #[cfg(any(test, feature = "app"))]
+        bundle_id: "com.google.Chrome",
+        process_names: &["Google Chrome", "Google Chrome Helper"],
+        #[cfg(any(test, feature = "app"))]
+        cache_dirs: &["Library/Caches/Google/Chrome"],
+    },
+    HeavyApp {
+        key: "slack",
+        #[cfg(any(test, feature = "app"))]
+        display_name: "Slack",
+        #[cfg(any(test, feature = "app"))]
+        bundle_id: "com.tinyspeck.slackmacgap",
+        process_names: &["Slack", "Slack Helper"],
+        #[cfg(any(test, feature = "app"))]
+        cache_dirs: &[
+            "Library/Application Support/Slack/Cache",
+            "Library/Application Support/Slack/Service Worker/CacheStorage",
+        ],
+    },
+    HeavyApp {
+        key: "spotify",
+        #[cfg(any(test, feature = "app"))]
+        display_name: "Spotify",
+        #[cfg(any(test, feature = "app"))]
+        bundle_id: "com.spotify.client",
+        process_names: &["Spotify", "Spotify Helper"],
+        #[cfg(any(test, feature = "app"))]
+        cache_dirs: &["Library/Caches/com.spotify.client"],
+    },
+];
+
+pub fn heavy_app(key: &str) -> Option<&'static HeavyApp> {
+    HEAVY_APPS.iter().find(|app| app.key == key)
+}
+
+/// The heavyweight app with bundle identifier `bundle_id`.
+#[cfg(any(test, feature = "app"))]
+pub fn heavy_app_for_bundle(bundle_id: &str) -> Option<&'static HeavyApp> {
+    HEAVY_APPS
+        .iter()
+        .find(|app| app.bundle_id.eq_ignore_ascii_case(bundle_id))
+}
+
+impl HeavyApp {
+    pub fn is_running(&self, processes: &ProcessSnapshot) -> bool {
+        self.process_names
+            .iter()
+            .any(|name| processes.has_process_named(name))
+    }
+
+    /// Existing cache directories for this app under `home`.
+    #[cfg(any(test, feature = "app"))]
+    pub fn cache_targets(&self, home: &Path) -> Vec<PathBuf> {
+        self.cache_dirs
+            .iter()
+            .map(|rel| home.join(rel))
+            .filter(|path| path.is_dir())
+            .collect()
+    }
+}
+
+/// What to do when a watched app quits.
+#[cfg(any(test, feature = "app"))]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AppQuitCleanupMode {
+    Off,
+    /// Tell the UI a cleanup is available.
+    #[default]
+    Offer,
+    /// Clean the app's caches right away.
+    Auto,
+}
+
+/// The [`AppQuitCleanupMode`] the user chose, kept across launches.
+#[cfg(any(test, feature = "app"))]
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct AppQuitSettings {
+    pub mode: AppQuitCleanupMode,
+    #[serde(skip)]
+    path: Option<PathBuf>,
+}
+
+#[cfg(any(test, feature = "app"))]
+impl AppQuitSettings {
+    /// Load from `<data dir>/macos-optimizer/app_quit_cleanup.json`.
+    #[cfg(feature = "app")]
+    pub fn load() -> Self {
+        match persistence::data_file(APP_QUIT_FILE) {
+            Some(path) => Self::load_from(&path),
+            None => Self::default(),
+        }
+    }
+
+    /// Load from an explicit file; a missing or unreadable file means
+    /// [`AppQuitCleanupMode::Offer`].
+    pub fn load_from(path: &Path) -> Self {
+        let mut settings = persistence::read_json::<AppQuitSettings>(path).unwrap_or_default();
+        settings.path = Some(path.to_path_buf());
+        settings
+    }
+
+    pub fn set_mode(&mut self, mode: AppQuitCleanupMode) -> Result<(), String> {
+        self.mode = mode;
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        persistence::write_json(path, self)
+    }
+}
+
+/// Cleanup available for an app that just quit.
+#[cfg(any(test, feature = "app"))]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AppQuitCleanupOffer {
+    pub app: String,
+    pub display_name: String,
+    pub paths: Vec<String>,
+    pub total_size: u64,
+}
+
+#[cfg(any(test, feature = "app"))]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AppCacheCleanResult {
+    pub app: String,
+    pub freed: u64,
+    pub removed: usize,
+    pub failed: usize,
+    /// Set when inspector mode reported what would happen instead of doing it.
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+/// Send every heavyweight app that quits to `quits`, from
+/// `NSWorkspaceDidTerminateApplicationNotification`. AppKit posts it on the
+/// main thread, so register from there; the observer stays for the life of
+/// the app.
+#[cfg(all(target_os = "macos", feature = "app"))]
+pub fn watch_quits(quits: tokio::sync::mpsc::UnboundedSender<&'static HeavyApp>) {
+    use std::ptr::NonNull;
+
+    use block2::RcBlock;
+    use objc2_app_kit::{
+        NSRunningApplication, NSWorkspace, NSWorkspaceApplicationKey,
+        NSWorkspaceDidTerminateApplicationNotification,
+    };
+    use objc2_foundation::NSNotification;
+
+    let on_terminate: RcBlock<dyn Fn(NonNull<NSNotification>)> =
+        RcBlock::new(move |notification: NonNull<NSNotification>| {
+            // SAFETY: the notification outlives the call it is passed to.
+            let notification = unsafe { notification.as_ref() };
+            let Some(app) = notification
+                .userInfo()
+                .and_then(|info| info.objectForKey(unsafe { NSWorkspaceApplicationKey }))
+                .and_then(|app| app.downcast::<NSRunningApplication>().ok())
+            else {
+                return;
+            };
+            let Some(bundle_id) = (unsafe { app.bundleIdentifier() }) else {
+                return;
+            };
+            if let Some(heavy_app) = heavy_app_for_bundle(&bundle_id.to_string()) {
+                let _ = quits.send(heavy_app);
+            }
+        });
+    // SAFETY: the block only touches a channel sender, which is Send.
+    let observer = unsafe {
+        NSWorkspace::sharedWorkspace()
+            .notificationCenter()
+            .addObserverForName_object_queue_usingBlock(
+                Some(NSWorkspaceDidTerminateApplicationNotification),
+                None,
+                None,
+                &on_terminate,
+            )
+    };
+    std::mem::forget(observer);
+}
+
+/// Size the cache directories an app left behind.
+#[cfg(any(test, feature = "app"))]
+pub fn cleanup_offer(app: &HeavyApp, home: &Path) -> AppQuitCleanupOffer {
+    let targets = app.cache_targets(home);
+    let total_size = targets.iter().map(|path| directory_size(path)).sum();
+    AppQuitCleanupOffer {
+        app: app.key.to_string(),
+        display_name: app.display_name.to_string(),
+        paths: targets
+            .iter()
+            .map(|path| path.to_string_lossy().to_string())
+            .collect(),
+        total_size,
+    }
+}
+
+/// Remove the contents of an app's cache directories, keeping the directories
+/// themselves so the app can recreate entries without permission surprises.
+/// Callers must confirm the app is not running first.
+#[cfg(any(test, feature = "app"))]
+pub fn clean_app_caches(app: &HeavyApp, home: &Path) -> AppCacheCleanResult {
+    sweep_app_caches(app, home, false)
+}
+
+/// What [`clean_app_caches`] would remove, leaving everything in place.
+#[cfg(feature = "app")]
+pub fn preview_app_caches(app: &HeavyApp, home: &Path) -> AppCacheCleanResult {
+    sweep_app_caches(app, home, true)
+}
+
+#[cfg(any(test, feature = "app"))]
+fn sweep_app_caches(app: &HeavyApp, home: &Path, dry_run: bool) -> AppCacheCleanResult {
+    let mut result = AppCacheCleanResult {
+        app: app.key.to_string(),
+        dry_run,
+        ..Default::default()
+    };
+    for target in app.cache_targets(home) {
+        let Ok(entries) = fs::read_dir(&target) else {
+            result.failed += 1;
+            continue;
+        };
+        for entry in entries.filter_map(Result::ok) {
+            let path = entry.path();
+            let is_dir = entry.file_type().map(|t| t.is_dir()).unwrap_or(false);
+            let size = if is_dir {
+                directory_size(&path)
+            } else {
+                entry.metadata().map(|m| m.len()).unwrap_or(0)
+            };
+            let removed = if dry_run {
+                Ok(())
+            } else if is_dir {
+                fs::remove_dir_all(&path)
+            } else {
+                fs::remove_file(&path)
+            };
+            match removed {
+                Ok(()) => {
+                    result.removed += 1;
+                    result.freed = result.freed.saturating_add(size);
+                }
+                Err(err) => {
+                    log::debug!("Failed to remove {}: {}", path.display(), err);
+                    result.failed += 1;
+                }
+            }
+        }
+    }
+    result
+}
+
+#[cfg(any(test, feature = "app"))]
+fn directory_size(path: &Path) -> u64 {
+    WalkDir::new(path)
+        .into_iter()
+        .filter_map(Result::ok)
+        .filter(|entry| entry.file_type().is_file())
+        .filter_map(|entry| entry.metadata().ok())
+        .map(|metadata| metadata.len())
+        .sum()
+}
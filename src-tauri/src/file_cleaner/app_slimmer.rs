@@ -0,0 +1,498 @@
+// src/file_cleaner/app_slimmer.rs
+
+//! Slimming app bundles: localizations in languages the user doesn't use
+//! and, on Apple Silicon, the Intel slices of universal binaries.
+//!
+//! Both live inside the bundle, which a code signature may seal. Removing
+//! sealed resources makes the signature invalid and the app may refuse to
+//! launch or lose its privacy grants, so bundles with sealed resources or an
+//! App Store receipt are only reported, never changed. Removed pieces go to
+//! the Trash, so slimming is undone by putting them back.
+
+use std::fs;
+use std::io::Read;
+#[cfg(unix)]
+use std::os::unix::fs::PermissionsExt;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use tokio::process::Command;
+use walkdir::WalkDir;
+
+use super::engine::FileCleaner;
+use super::process_snapshot::ProcessSnapshot;
+use crate::capabilities::{self, Tool};
+use crate::system_snapshot::{self, AppEntry};
+
+const BASE_LOCALIZATION: &str = "Base";
+/// Folder names older apps use instead of language codes.
+const LEGACY_LANGUAGE_NAMES: &[(&str, &str)] = &[
+    ("english", "en"),
+    ("french", "fr"),
+    ("german", "de"),
+    ("japanese", "ja"),
+    ("spanish", "es"),
+    ("italian", "it"),
+    ("dutch", "nl"),
+];
+const FAT_MAGIC: u32 = 0xcafe_babe;
+const FAT_MAGIC_64: u32 = 0xcafe_babf;
+/// Java class files share the fat magic; their version field reads as a
+/// slice count far above what any universal binary has.
+const MAX_FAT_SLICES: u32 = 20;
+const CPU_TYPE_X86: u32 = 7;
+const CPU_TYPE_X86_64: u32 = 0x0100_0007;
+const CPU_TYPE_ARM64: u32 = 0x0100_000c;
+const CPU_TYPE_POWERPC: u32 = 18;
+/// Suffix of the thinned copy while it is written next to the binary.
+const THIN_SUFFIX: &str = ".slim-tmp";
+
+/// Whether the bundle's signature would notice files going missing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BundleSignature {
+    /// No sealed resources; at most the linker's ad-hoc signature on each
+    /// binary slice, which thinning keeps.
+    Unsealed,
+    /// A `_CodeSignature` folder seals the files of the bundle, or of a
+    /// framework or helper inside it.
+    Sealed,
+    /// Installed from the App Store, which also checks the receipt.
+    AppStore,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SlimKind {
+    Localization,
+    Architecture,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SlimItem {
+    pub path: String,
+    pub kind: SlimKind,
+    /// The language of a localization, or the architectures a binary loses.
+    pub detail: String,
+    /// Bytes freed by removing it.
+    pub size: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AppSlimReport {
+    pub app: AppEntry,
+    pub signature: BundleSignature,
+    pub items: Vec<SlimItem>,
+    pub reclaimable: u64,
+    /// Why the app can't be slimmed; `items` still shows what it carries.
+    pub blocked_reason: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AppSlimResult {
+    pub app_path: String,
+    /// Localization folders and original binaries moved to the Trash.
+    pub trashed: Vec<String>,
+    pub freed: u64,
+    /// One `path: reason` line per item left in place.
+    pub failures: Vec<String>,
+    /// Set when inspector mode reported what would happen instead of doing it.
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+/// One architecture in a universal binary.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FatSlice {
+    pub architecture: String,
+    pub size: u64,
+}
+
+/// Where slimming gets its settings from the system.
+#[derive(Debug, Clone, Default)]
+pub struct SlimSettings {
+    /// Language codes to keep, e.g. `en` or `zh-Hans`.
+    pub languages: Vec<String>,
+    /// Architecture binaries are thinned to; none leaves binaries alone.
+    pub thin_to: Option<String>,
+}
+
+impl SlimSettings {
+    /// The user's preferred languages, and `arm64` on Apple Silicon when
+    /// `lipo` is installed.
+    pub async fn current() -> Self {
+        let thin_to = (cfg!(target_arch = "aarch64")
+            && capabilities::capabilities().is_available(Tool::Lipo))
+        .then(|| "arm64".to_string());
+        Self {
+            languages: preferred_languages().await,
+            thin_to,
+        }
+    }
+}
+
+/// Languages from `defaults read -g AppleLanguages`, or `LANG` without it.
+async fn preferred_languages() -> Vec<String> {
+    let output = Command::new("defaults")
+        .args(["read", "-g", "AppleLanguages"])
+        .output()
+        .await;
+    let mut languages = match output {
+        Ok(output) if output.status.success() => {
+            parse_apple_languages(&String::from_utf8_lossy(&output.stdout))
+        }
+        _ => Vec::new(),
+    };
+    if languages.is_empty() {
+        if let Ok(lang) = std::env::var("LANG") {
+            languages.push(lang.split('.').next().unwrap_or_default().to_string());
+        }
+    }
+    languages
+}
+
+/// The entries of the property-list array `defaults` prints, e.g.
+/// `("en-US", "fr-FR")` over several lines.
+pub fn parse_apple_languages(output: &str) -> Vec<String> {
+    output
+        .split(['(', ')', ',', '\n'])
+        .map(|entry| entry.trim().trim_matches('"').trim())
+        .filter(|entry| !entry.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// Base language of a code or `.lproj` name: `en` for `en_GB`, `English`
+/// or `en-GB.lproj`.
+pub fn language_code(name: &str) -> String {
+    let name = name.trim_end_matches(".lproj").to_lowercase();
+    if let Some((_, code)) = LEGACY_LANGUAGE_NAMES
+        .iter()
+        .find(|(legacy, _)| *legacy == name)
+    {
+        return code.to_string();
+    }
+    name.split(['-', '_'])
+        .next()
+        .unwrap_or_default()
+        .to_string()
+}
+
+/// The slices of a universal binary from its first bytes, or `None` for
+/// anything else.
+pub fn fat_slices(header: &[u8]) -> Option<Vec<FatSlice>> {
+    let word = |offset: usize| -> Option<u32> {
+        header
+            .get(offset..offset + 4)
+            .map(|bytes| u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    };
+    let wide = match word(0)? {
+        FAT_MAGIC => false,
+        FAT_MAGIC_64 => true,
+        _ => return None,
+    };
+    let count = word(4)?;
+    if count == 0 || count > MAX_FAT_SLICES {
+        return None;
+    }
+    let entry_size = if wide { 32 } else { 20 };
+    (0..count as usize)
+        .map(|index| {
+            let entry = 8 + index * entry_size;
+            let cpu_type = word(entry)?;
+            let size = if wide {
+                (u64::from(word(entry + 16)?) << 32) | u64::from(word(entry + 20)?)
+            } else {
+                u64::from(word(entry + 12)?)
+            };
+            Some(FatSlice {
+                architecture: architecture_name(cpu_type),
+                size,
+            })
+        })
+        .collect()
+}
+
+fn architecture_name(cpu_type: u32) -> String {
+    match cpu_type {
+        CPU_TYPE_X86 => "i386".to_string(),
+        CPU_TYPE_X86_64 => "x86_64".to_string(),
+        CPU_TYPE_ARM64 => "arm64".to_string(),
+        CPU_TYPE_POWERPC => "ppc".to_string(),
+        other => format!("cpu-{:#x}", other),
+    }
+}
+
+/// What slimming `app` would remove under `settings`.
+pub fn analyze_app(app: &Path, settings: &SlimSettings) -> AppSlimReport {
+    let entry = system_snapshot::app_entry(app);
+    let signature = bundle_signature(app);
+    let info =
+        system_snapshot::read_plist_xml(&app.join("Contents/Info.plist")).unwrap_or_default();
+    let development_region = system_snapshot::plist_string(&info, "CFBundleDevelopmentRegion");
+
+    let mut items = unused_localizations(app, &settings.languages, development_region.as_deref());
+    if let Some(native) = &settings.thin_to {
+        items.extend(foreign_architectures(app, native));
+    }
+    let blocked_reason = if entry
+        .bundle_id
+        .as_deref()
+        .is_some_and(|id| id.starts_with("com.apple."))
+    {
+        Some(format!("{} ships with macOS", entry.name))
+    } else {
+        match signature {
+            BundleSignature::Unsealed => None,
+            BundleSignature::Sealed => Some(
+                "Its code signature seals the bundle; removing files would break it".to_string(),
+            ),
+            BundleSignature::AppStore => {
+                Some("App Store apps are verified as a whole and can't be slimmed".to_string())
+            }
+        }
+    };
+    AppSlimReport {
+        reclaimable: items.iter().map(|item| item.size).sum(),
+        app: entry,
+        signature,
+        items,
+        blocked_reason,
+    }
+}
+
+/// Sealed when the bundle, or any framework or helper inside it, has sealed
+/// resources.
+fn bundle_signature(app: &Path) -> BundleSignature {
+    let contents = app.join("Contents");
+    if contents.join("_MASReceipt").exists() {
+        return BundleSignature::AppStore;
+    }
+    let sealed = WalkDir::new(&contents)
+        .into_iter()
+        .filter_map(Result::ok)
+        .any(|entry| entry.file_type().is_dir() && entry.file_name() == "_CodeSignature");
+    if sealed {
+        BundleSignature::Sealed
+    } else {
+        BundleSignature::Unsealed
+    }
+}
+
+/// `.lproj` folders in languages nobody asked for. `Base`, the preferred
+/// languages and the app's development region stay; an app with none of
+/// those keeps all its localizations so it still has one to fall back on.
+fn unused_localizations(
+    app: &Path,
+    languages: &[String],
+    development_region: Option<&str>,
+) -> Vec<SlimItem> {
+    let keep: Vec<String> = languages
+        .iter()
+        .map(String::as_str)
+        .chain(development_region)
+        .map(language_code)
+        .collect();
+    let mut folders: Vec<PathBuf> = WalkDir::new(app.join("Contents"))
+        .into_iter()
+        .filter_map(Result::ok)
+        .filter(|entry| {
+            entry.file_type().is_dir() && entry.path().extension().is_some_and(|e| e == "lproj")
+        })
+        .map(|entry| entry.into_path())
+        .collect();
+    folders.sort();
+
+    let name_of = |folder: &PathBuf| {
+        folder
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().to_string())
+            .unwrap_or_default()
+    };
+    let kept = |folder: &PathBuf| {
+        let name = name_of(folder);
+        name == BASE_LOCALIZATION || keep.contains(&language_code(&name))
+    };
+    if !folders
+        .iter()
+        .any(|folder| kept(folder) && name_of(folder) != BASE_LOCALIZATION)
+    {
+        return Vec::new();
+    }
+    folders
+        .iter()
+        .filter(|folder| !kept(folder))
+        .map(|folder| SlimItem {
+            path: folder.to_string_lossy().to_string(),
+            kind: SlimKind::Localization,
+            detail: name_of(folder),
+            size: FileCleaner::walk_directory_size(folder),
+        })
+        .collect()
+}
+
+/// Universal binaries with slices other than `native`, sized by what
+/// thinning drops. Binaries without a `native` slice are left alone.
+fn foreign_architectures(app: &Path, native: &str) -> Vec<SlimItem> {
+    WalkDir::new(app.join("Contents"))
+        .into_iter()
+        .filter_map(Result::ok)
+        .filter(|entry| entry.file_type().is_file() && is_binary_candidate(entry))
+        .filter_map(|entry| {
+            let slices = read_fat_slices(entry.path())?;
+            if !slices.iter().any(|slice| slice.architecture == native) {
+                return None;
+            }
+            let foreign: Vec<&FatSlice> = slices
+                .iter()
+                .filter(|slice| slice.architecture != native)
+                .collect();
+            if foreign.is_empty() {
+                return None;
+            }
+            Some(SlimItem {
+                path: entry.path().to_string_lossy().to_string(),
+                kind: SlimKind::Architecture,
+                detail: foreign
+                    .iter()
+                    .map(|slice| slice.architecture.as_str())
+                    .collect::<Vec<_>>()
+                    .join(", "),
+                size: foreign.iter().map(|slice| slice.size).sum(),
+            })
+        })
+        .collect()
+}
+
+fn is_binary_candidate(entry: &walkdir::DirEntry) -> bool {
+    if entry
+        .path()
+        .extension()
+        .is_some_and(|ext| ext == "dylib" || ext == "so")
+    {
+        return true;
+    }
+    #[cfg(unix)]
+    {
+        entry
+            .metadata()
+            .is_ok_and(|metadata| metadata.permissions().mode() & 0o111 != 0)
+    }
+    #[cfg(not(unix))]
+    {
+        false
+    }
+}
+
+fn read_fat_slices(path: &Path) -> Option<Vec<FatSlice>> {
+    let mut header = [0u8; 8 + 32 * MAX_FAT_SLICES as usize];
+    let mut file = fs::File::open(path).ok()?;
+    let mut read = 0;
+    while read < header.len() {
+        match file.read(&mut header[read..]) {
+            Ok(0) => break,
+            Ok(n) => read += n,
+            Err(_) => return None,
+        }
+    }
+    fat_slices(&header[..read])
+}
+
+/// Slim the app at `report.app.path` as analyzed: localizations go to the
+/// Trash, and each binary is replaced by a thinned copy with the original in
+/// the Trash. Refuses blocked reports and running apps.
+pub async fn slim_app(
+    report: &AppSlimReport,
+    settings: &SlimSettings,
+    cleaner: &FileCleaner,
+) -> Result<AppSlimResult, String> {
+    if let Some(reason) = &report.blocked_reason {
+        return Err(reason.clone());
+    }
+    let processes = ProcessSnapshot::capture().await;
+    if processes.has_process_named(&report.app.name)
+        || processes.runs_from_bundle(Path::new(&report.app.path))
+    {
+        return Err(format!(
+            "{} is running; quit it before slimming",
+            report.app.name
+        ));
+    }
+    let mut result = AppSlimResult {
+        app_path: report.app.path.clone(),
+        ..AppSlimResult::default()
+    };
+    for item in &report.items {
+        let path = Path::new(&item.path);
+        let outcome = match (item.kind, &settings.thin_to) {
+            (SlimKind::Localization, _) => cleaner.move_to_trash(path).await,
+            (SlimKind::Architecture, Some(native)) => thin_binary(path, native, cleaner).await,
+            (SlimKind::Architecture, None) => Err("Thinning is not available".to_string()),
+        };
+        match outcome {
+            Ok(()) => {
+                result.freed = result.freed.saturating_add(item.size);
+                result.trashed.push(item.path.clone());
+            }
+            Err(err) => result.failures.push(format!("{}: {}", item.path, err)),
+        }
+    }
+    FileCleaner::invalidate_scan_caches(Path::new(&report.app.path)).await;
+    Ok(result)
+}
+
+/// What [`slim_app`] would do, without changing anything.
+pub fn preview_slim(report: &AppSlimReport) -> Result<AppSlimResult, String> {
+    if let Some(reason) = &report.blocked_reason {
+        return Err(reason.clone());
+    }
+    Ok(AppSlimResult {
+        app_path: report.app.path.clone(),
+        trashed: report.items.iter().map(|item| item.path.clone()).collect(),
+        freed: report.reclaimable,
+        failures: Vec::new(),
+        dry_run: true,
+    })
+}
+
+async fn thin_binary(path: &Path, native: &str, cleaner: &FileCleaner) -> Result<(), String> {
+    let lipo = capabilities::tool_path(Tool::Lipo).ok_or("lipo is not installed")?;
+    let mut thinned = path.as_os_str().to_owned();
+    thinned.push(THIN_SUFFIX);
+    let thinned = PathBuf::from(thinned);
+    let output = Command::new(lipo)
+        .arg(path)
+        .args(["-thin", native, "-output"])
+        .arg(&thinned)
+        .output()
+        .await
+        .map_err(|e| format!("Failed to run lipo: {}", e))?;
+    if !output.status.success() {
+        let _ = fs::remove_file(&thinned);
+        return Err(format!(
+            "lipo failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    let prepared = match fs::metadata(path) {
+        Ok(metadata) => fs::set_permissions(&thinned, metadata.permissions())
+            .map_err(|e| format!("Failed to copy permissions: {}", e)),
+        Err(e) => Err(format!("Failed to read {}: {}", path.display(), e)),
+    };
+    if let Err(err) = match prepared {
+        Ok(()) => cleaner.move_to_trash(path).await,
+        Err(err) => Err(err),
+    } {
+        let _ = fs::remove_file(&thinned);
+        return Err(err);
+    }
+    // The original is in the Trash now; on failure the thinned copy stays
+    // next to where it belongs.
+    fs::rename(&thinned, path).map_err(|e| {
+        format!(
+            "Failed to install thinned copy {}: {}",
+            thinned.display(),
+            e
+        )
+    })
+}
@@ -0,0 +1,327 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use super::advanced_safety::{SafetyAnalyzer, SafetyRecommendation};
+use super::engine::FileCleaner;
+use super::process_snapshot::ProcessSnapshot;
+use crate::system_snapshot::{self, AppEntry};
+
+/// Category passed to the safety analyzer for every leftover path.
+const LEFTOVER_CATEGORY: &str = "App Leftovers";
+/// Shorter app names match too many unrelated folders.
+const MIN_NAME_MATCH_LEN: usize = 3;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LeftoverKind {
+    Container,
+    GroupContainer,
+    Cache,
+    ApplicationSupport,
+    Preferences,
+    SavedState,
+    WebData,
+    Logs,
+    LaunchAgent,
+    LaunchDaemon,
+    Receipt,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AppLeftover {
+    pub path: String,
+    pub kind: LeftoverKind,
+    pub size: u64,
+    pub safety_score: u8,
+    pub recommendation: SafetyRecommendation,
+    /// Whether the leftover is removed by default (safety analysis agrees).
+    pub selected: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AppFootprint {
+    pub app: AppEntry,
+    pub bundle_size: u64,
+    pub leftovers: Vec<AppLeftover>,
+    pub total_size: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SkippedLeftover {
+    pub path: String,
+    pub reason: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct UninstallResult {
+    pub moved_to_trash: Vec<String>,
+    pub skipped: Vec<SkippedLeftover>,
+    pub freed: u64,
+    /// Set when inspector mode reported what would happen instead of doing it.
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+/// Finds installed apps and everything they scatter across the Library
+/// folders, and moves the lot to the Trash.
+pub struct AppUninstaller {
+    home: PathBuf,
+    /// Prefix for system-wide locations (`/Applications`, `/Library`, `/var/db/receipts`).
+    system_root: PathBuf,
+    analyzer: SafetyAnalyzer,
+    cleaner: FileCleaner,
+}
+
+impl AppUninstaller {
+    pub fn new() -> Result<Self, String> {
+        let home = dirs::home_dir().ok_or("Home directory not found")?;
+        Ok(Self::with_roots(home, PathBuf::from("/")))
+    }
+
+    pub fn with_roots(home: PathBuf, system_root: PathBuf) -> Self {
+        Self {
+            home,
+            system_root,
+            analyzer: SafetyAnalyzer::new(),
+            cleaner: FileCleaner::new(),
+        }
+    }
+
+    /// Apps in /Applications and ~/Applications (one folder deep).
+    pub fn list_installed_apps(&self) -> Vec<AppEntry> {
+        system_snapshot::list_apps(&[
+            self.system_root.join("Applications"),
+            self.home.join("Applications"),
+        ])
+    }
+
+    pub async fn analyze_app_footprint(&self, app_path: &Path) -> Result<AppFootprint, String> {
+        let app = self.installed_app(app_path)?;
+        let bundle_size = self.cleaner.get_path_size_async(app_path).await?;
+
+        let process_snapshot = ProcessSnapshot::capture().await;
+        let mut leftovers = Vec::new();
+        for (path, kind) in self.find_leftovers(&app) {
+            let size = self.cleaner.get_path_size_async(&path).await.unwrap_or(0);
+            let metrics = self
+                .analyzer
+                .analyze_with_snapshot(&path, LEFTOVER_CATEGORY, &process_snapshot)
+                .await;
+            let selected = matches!(
+                metrics.recommendation,
+                SafetyRecommendation::SafeToAutoDelete
+                    | SafetyRecommendation::SafeWithUserConfirmation
+            );
+            leftovers.push(AppLeftover {
+                path: path.to_string_lossy().to_string(),
+                kind,
+                size,
+                safety_score: metrics.base_score,
+                recommendation: metrics.recommendation,
+                selected,
+            });
+        }
+        let total_size = leftovers
+            .iter()
+            .fold(bundle_size, |acc, l| acc.saturating_add(l.size));
+        Ok(AppFootprint {
+            app,
+            bundle_size,
+            leftovers,
+            total_size,
+        })
+    }
+
+    /// Move the app bundle and its leftovers to the Trash. Leftovers the
+    /// safety analysis doesn't clear are kept unless `include_low_safety`.
+    pub async fn uninstall_app(
+        &self,
+        app_path: &Path,
+        include_low_safety: bool,
+    ) -> Result<UninstallResult, String> {
+        let footprint = self.analyze_app_footprint(app_path).await?;
+        let processes = ProcessSnapshot::capture().await;
+        if processes.has_process_named(&footprint.app.name) || processes.runs_from_bundle(app_path)
+        {
+            return Err(format!(
+                "{} is running; quit it before uninstalling",
+                footprint.app.name
+            ));
+        }
+
+        let mut result = UninstallResult::default();
+        self.cleaner.move_to_trash(app_path).await?;
+        result.moved_to_trash.push(footprint.app.path.clone());
+        result.freed = footprint.bundle_size;
+        FileCleaner::invalidate_scan_caches(app_path).await;
+
+        for leftover in footprint.leftovers {
+            if !leftover.selected && !include_low_safety {
+                result.skipped.push(SkippedLeftover {
+                    path: leftover.path,
+                    reason: format!(
+                        "Safety score {} needs review; enable Risky Mode to include it",
+                        leftover.safety_score
+                    ),
+                });
+                continue;
+            }
+            let path = PathBuf::from(&leftover.path);
+            match self.cleaner.move_to_trash(&path).await {
+                Ok(()) => {
+                    FileCleaner::invalidate_scan_caches(&path).await;
+                    result.freed = result.freed.saturating_add(leftover.size);
+                    result.moved_to_trash.push(leftover.path);
+                }
+                Err(reason) => result.skipped.push(SkippedLeftover {
+                    path: leftover.path,
+                    reason,
+                }),
+            }
+        }
+        Ok(result)
+    }
+
+    /// What [`Self::uninstall_app`] would move to the Trash, without moving anything.
+    pub async fn preview_uninstall(
+        &self,
+        app_path: &Path,
+        include_low_safety: bool,
+    ) -> Result<UninstallResult, String> {
+        let footprint = self.analyze_app_footprint(app_path).await?;
+        let mut result = UninstallResult {
+            moved_to_trash: vec![footprint.app.path.clone()],
+            freed: footprint.bundle_size,
+            dry_run: true,
+            ..Default::default()
+        };
+        for leftover in footprint.leftovers {
+            if leftover.selected || include_low_safety {
+                result.freed = result.freed.saturating_add(leftover.size);
+                result.moved_to_trash.push(leftover.path);
+            } else {
+                result.skipped.push(SkippedLeftover {
+                    path: leftover.path,
+                    reason: format!(
+                        "Safety score {} needs review; enable Risky Mode to include it",
+                        leftover.safety_score
+                    ),
+                });
+            }
+        }
+        Ok(result)
+    }
+
+    /// Only apps found by [`Self::list_installed_apps`] may be analyzed or
+    /// removed, and never Apple's own.
+    fn installed_app(&self, app_path: &Path) -> Result<AppEntry, String> {
+        let wanted = app_path.to_string_lossy();
+        let app = self
+            .list_installed_apps()
+            .into_iter()
+            .find(|app| app.path == wanted)
+            .ok_or_else(|| format!("{} is not an installed application", app_path.display()))?;
+        if app
+            .bundle_id
+            .as_deref()
+            .is_some_and(|id| id.starts_with("com.apple."))
+        {
+            return Err(format!(
+                "{} ships with macOS and can't be removed",
+                app.name
+            ));
+        }
+        Ok(app)
+    }
+
+    fn find_leftovers(&self, app: &AppEntry) -> Vec<(PathBuf, LeftoverKind)> {
+        let bundle_id = app.bundle_id.as_deref().unwrap_or("");
+        let name = if app.name.len() >= MIN_NAME_MATCH_LEN {
+            app.name.as_str()
+        } else {
+            ""
+        };
+        let is_id = |entry: &str| !bundle_id.is_empty() && entry == bundle_id;
+        let is_id_or_name = |entry: &str| is_id(entry) || (!name.is_empty() && entry == name);
+        let has_id_prefix = |entry: &str| {
+            !bundle_id.is_empty()
+                && entry
+                    .strip_prefix(bundle_id)
+                    .is_some_and(|rest| rest.is_empty() || rest.starts_with('.'))
+        };
+
+        let library = self.home.join("Library");
+        let system_library = self.system_root.join("Library");
+        let mut found = Vec::new();
+        let mut collect = |dir: PathBuf, kind: LeftoverKind, matches: &dyn Fn(&str) -> bool| {
+            let Ok(entries) = fs::read_dir(&dir) else {
+                return;
+            };
+            let mut paths: Vec<PathBuf> = entries
+                .filter_map(Result::ok)
+                .filter(|entry| matches(&entry.file_name().to_string_lossy()))
+                .map(|entry| entry.path())
+                .collect();
+            paths.sort();
+            found.extend(paths.into_iter().map(|path| (path, kind)));
+        };
+
+        collect(library.join("Containers"), LeftoverKind::Container, &is_id);
+        collect(
+            library.join("Group Containers"),
+            LeftoverKind::GroupContainer,
+            &|entry| !bundle_id.is_empty() && entry.ends_with(&format!(".{}", bundle_id)),
+        );
+        collect(library.join("Caches"), LeftoverKind::Cache, &is_id_or_name);
+        collect(
+            library.join("Application Support"),
+            LeftoverKind::ApplicationSupport,
+            &is_id_or_name,
+        );
+        collect(
+            library.join("Preferences"),
+            LeftoverKind::Preferences,
+            &has_id_prefix,
+        );
+        collect(
+            library.join("Preferences/ByHost"),
+            LeftoverKind::Preferences,
+            &has_id_prefix,
+        );
+        collect(
+            library.join("Saved Application State"),
+            LeftoverKind::SavedState,
+            &|entry| entry.strip_suffix(".savedState").is_some_and(&is_id),
+        );
+        collect(
+            library.join("HTTPStorages"),
+            LeftoverKind::WebData,
+            &has_id_prefix,
+        );
+        collect(library.join("WebKit"), LeftoverKind::WebData, &is_id);
+        collect(library.join("Logs"), LeftoverKind::Logs, &is_id_or_name);
+        collect(
+            library.join("LaunchAgents"),
+            LeftoverKind::LaunchAgent,
+            &has_id_prefix,
+        );
+        collect(
+            system_library.join("LaunchAgents"),
+            LeftoverKind::LaunchAgent,
+            &has_id_prefix,
+        );
+        collect(
+            system_library.join("LaunchDaemons"),
+            LeftoverKind::LaunchDaemon,
+            &has_id_prefix,
+        );
+        collect(
+            self.system_root.join("var/db/receipts"),
+            LeftoverKind::Receipt,
+            &has_id_prefix,
+        );
+        found
+    }
+}
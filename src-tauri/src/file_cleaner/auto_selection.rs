@@ -1,10 +1,13 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::fs;
 use std::path::{Path, PathBuf};
 
 use super::advanced_safety::SafetyMetrics;
 use super::types::CleanableFile;
+use crate::capabilities::{self, Tool};
+use crate::persistence;
 
 /// Intelligent auto-selection engine with machine learning capabilities
 pub struct AutoSelectionEngine {
@@ -14,10 +17,16 @@ pub struct AutoSelectionEngine {
 }
 
 impl AutoSelectionEngine {
+    /// An engine whose learned patterns stay in memory.
+    #[cfg_attr(not(test), allow(dead_code))]
     pub fn new() -> Self {
+        Self::with_learner(UserPatternLearner::new())
+    }
+
+    pub fn with_learner(user_pattern_learner: UserPatternLearner) -> Self {
         Self {
             conservative_defaults: ConservativeDefaults::new(),
-            user_pattern_learner: UserPatternLearner::new(),
+            user_pattern_learner,
             _scoring_weights: ScoringWeights::default(),
         }
     }
@@ -75,6 +84,9 @@ impl AutoSelectionEngine {
     }
 
     async fn check_backup_status(&self, path: &Path) -> BackupStatus {
+        if !capabilities::tool_available(Tool::Tmutil) {
+            return BackupStatus::Unknown;
+        }
         // Check Time Machine status
         if let Ok(output) = tokio::process::Command::new("tmutil")
             .arg("isexcluded")
@@ -115,6 +127,10 @@ impl AutoSelectionEngine {
     pub fn update_from_user_action(&mut self, file: &CleanableFile, action: UserAction) {
         self.user_pattern_learner.record_action(file, action);
     }
+
+    pub fn reset_learning_data(&mut self) -> Result<(), String> {
+        self.user_pattern_learner.reset()
+    }
 }
 
 /// Conservative default settings for auto-selection
@@ -168,10 +184,16 @@ impl ConservativeDefaults {
     }
 }
 
+const USER_PATTERNS_FILE: &str = "user_patterns.json";
+/// Version written to `user_patterns.json`; see [`UserPatternData::migrate`].
+pub const USER_PATTERNS_VERSION: u32 = 1;
+
 /// Learns from user selection patterns
 pub struct UserPatternLearner {
     selection_history: HashMap<String, SelectionPattern>,
     category_preferences: HashMap<String, f32>,
+    /// Where patterns persist; `None` keeps them in memory only.
+    path: Option<PathBuf>,
 }
 
 impl UserPatternLearner {
@@ -179,7 +201,43 @@ impl UserPatternLearner {
         Self {
             selection_history: HashMap::new(),
             category_preferences: HashMap::new(),
+            path: None,
+        }
+    }
+
+    /// Load from `<data dir>/macos-optimizer/user_patterns.json`.
+    pub fn load() -> Self {
+        match persistence::data_file(USER_PATTERNS_FILE) {
+            Some(path) => Self::load_from(&path),
+            None => Self::new(),
+        }
+    }
+
+    /// Load from an explicit file; a missing or unreadable file yields no
+    /// patterns. A file written by a newer version is left untouched and
+    /// learning stays in memory for this run.
+    pub fn load_from(path: &Path) -> Self {
+        let mut learner = Self {
+            path: Some(path.to_path_buf()),
+            ..Self::new()
+        };
+        let Some(data) = persistence::read_json::<UserPatternData>(path) else {
+            return learner;
+        };
+        match data.migrate() {
+            Some(data) => {
+                learner.selection_history = data.selection_history;
+                learner.category_preferences = data.category_preferences;
+            }
+            None => {
+                log::warn!(
+                    "{} was written by a newer version; not persisting learned patterns",
+                    path.display()
+                );
+                learner.path = None;
+            }
         }
+        learner
     }
 
     pub fn get_user_pattern(&self, file: &CleanableFile) -> UserPreference {
@@ -237,9 +295,34 @@ impl UserPatternLearner {
         let preference = pattern.selected_count as f32 / pattern.total_count as f32;
         self.category_preferences
             .insert(file.category.clone(), preference);
+
+        if let Err(err) = self.persist() {
+            log::warn!("Failed to save learned selection patterns: {}", err);
+        }
+    }
+
+    /// Forget every learned pattern, on disk too.
+    pub fn reset(&mut self) -> Result<(), String> {
+        self.selection_history.clear();
+        self.category_preferences.clear();
+        match &self.path {
+            Some(path) if path.exists() => fs::remove_file(path)
+                .map_err(|e| format!("Failed to remove {}: {}", path.display(), e)),
+            _ => Ok(()),
+        }
     }
 
-    // Persistence APIs can be added under a feature flag when needed.
+    fn persist(&self) -> Result<(), String> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        let data = UserPatternData {
+            version: USER_PATTERNS_VERSION,
+            selection_history: self.selection_history.clone(),
+            category_preferences: self.category_preferences.clone(),
+        };
+        persistence::write_json(path, &data)
+    }
 }
 
 /// Auto-selection scoring system
@@ -392,6 +475,20 @@ impl AutoSelectScore {
         }
     }
 
+    /// Applied after [`Self::finalize`] when the path matches a learned
+    /// exception the user accepted.
+    pub fn apply_learned_exception(&mut self, deselections: u32) {
+        self.confidence = (self.confidence - 0.3).max(0.0);
+        self.can_auto_select = false;
+        self.add_constraint_reason(&format!(
+            "You deselected {} similar items",
+            crate::formatting::format_count(deselections as u64)
+        ));
+        if self.recommendation == SelectionRecommendation::AutoSelect {
+            self.recommendation = SelectionRecommendation::Recommend;
+        }
+    }
+
     pub fn apply_system_importance(&mut self, importance: SystemImportance) {
         match importance {
             SystemImportance::Critical => {
@@ -506,9 +603,41 @@ pub enum UserAction {
     Ignored,
 }
 
+/// On-disk form of [`UserPatternLearner`].
 #[derive(Debug, Clone, Serialize, Deserialize)]
-#[allow(dead_code)]
 pub struct UserPatternData {
+    /// Missing in files written before the schema was versioned.
+    #[serde(default)]
+    pub version: u32,
     pub selection_history: HashMap<String, SelectionPattern>,
+    #[serde(default)]
     pub category_preferences: HashMap<String, f32>,
 }
+
+impl UserPatternData {
+    /// Bring data of any earlier version up to [`USER_PATTERNS_VERSION`];
+    /// `None` when it comes from a newer version.
+    pub fn migrate(mut self) -> Option<Self> {
+        if self.version > USER_PATTERNS_VERSION {
+            return None;
+        }
+        if self.version == 0 {
+            // Unversioned data could hold preferences for categories without
+            // any history; rebuild them from the recorded counts.
+            self.selection_history
+                .retain(|_, pattern| pattern.total_count > 0);
+            self.category_preferences = self
+                .selection_history
+                .iter()
+                .map(|(category, pattern)| {
+                    (
+                        category.clone(),
+                        pattern.selected_count as f32 / pattern.total_count as f32,
+                    )
+                })
+                .collect();
+        }
+        self.version = USER_PATTERNS_VERSION;
+        Some(self)
+    }
+}
@@ -1,40 +1,96 @@
 // src/file_cleaner/cache.rs
 
-#[cfg(feature = "metadata-cache")]
+#[cfg(all(feature = "cache-refresh", feature = "app"))]
 use dashmap::DashMap;
 use lazy_static::lazy_static;
 use lru::LruCache;
+#[cfg(any(test, feature = "app"))]
+use serde::{Deserialize, Serialize};
 use std::fs;
 use std::num::NonZeroUsize;
 use std::path::{Path, PathBuf};
-use std::sync::Arc;
-use std::time::{Duration, Instant, SystemTime};
+use std::sync::{Arc, Mutex};
+#[cfg(all(feature = "cache-refresh", feature = "app"))]
+use std::time::Instant;
+use std::time::{Duration, SystemTime};
+#[cfg(all(feature = "cache-refresh", feature = "app"))]
 use tokio::sync::RwLock;
 
+#[cfg(all(feature = "cache-refresh", feature = "app"))]
+use super::age_thresholds::AgeThresholds;
+use super::disk_usage::DiskUsage;
+#[cfg(all(feature = "cache-refresh", feature = "app"))]
+use super::protection_packs::ProtectionPacks;
+#[cfg(any(test, feature = "app"))]
+use crate::persistence;
+
+#[cfg(feature = "app")]
+const DIR_SIZE_CACHE_FILE: &str = "dir_size_cache.json";
+/// Sizes restored from a previous session are trusted this long while the
+/// directory's mtime is unchanged. An mtime only moves when direct entries
+/// change, so changes deeper down go unnoticed until then.
+const RESTORED_MAX_AGE: Duration = Duration::from_secs(24 * 60 * 60);
+
 pub struct DirectorySizeCache {
-    cache: Arc<RwLock<LruCache<PathBuf, CachedSize>>>,
+    cache: Arc<Mutex<LruCache<PathBuf, CachedSize>>>,
     ttl: Duration,
 }
 
 #[derive(Clone)]
 struct CachedSize {
-    size: u64,
-    calculated_at: Instant,
+    usage: DiskUsage,
+    computed_at: SystemTime,
     last_modified: SystemTime,
+    /// Loaded from disk rather than computed this session.
+    restored: bool,
+}
+
+impl CachedSize {
+    fn is_fresh(&self, modified: SystemTime, ttl: Duration) -> bool {
+        let max_age = if self.restored { RESTORED_MAX_AGE } else { ttl };
+        self.last_modified == modified && self.computed_at.elapsed().is_ok_and(|age| age < max_age)
+    }
+}
+
+/// One cached size as written to disk.
+#[cfg(any(test, feature = "app"))]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct PersistedSize {
+    path: PathBuf,
+    size: u64,
+    /// Missing from caches saved before usage was tracked.
+    #[serde(default)]
+    usage: Option<DiskUsage>,
+    mtime: SystemTime,
+    computed_at: SystemTime,
 }
 
 impl DirectorySizeCache {
     pub fn new(capacity: usize, ttl_seconds: u64) -> Self {
         let cap = NonZeroUsize::new(capacity).unwrap_or_else(|| NonZeroUsize::new(1000).unwrap());
         DirectorySizeCache {
-            cache: Arc::new(RwLock::new(LruCache::new(cap))),
+            cache: Arc::new(Mutex::new(LruCache::new(cap))),
             ttl: Duration::from_secs(ttl_seconds),
         }
     }
 
-    pub async fn get_or_calculate<F>(&self, path: &Path, calculator: F) -> Result<u64, String>
+    fn entries(&self) -> std::sync::MutexGuard<'_, LruCache<PathBuf, CachedSize>> {
+        self.cache
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    pub async fn get_or_measure<F>(&self, path: &Path, measure: F) -> Result<DiskUsage, String>
     where
-        F: FnOnce(&Path) -> Result<u64, String>,
+        F: FnOnce(&Path) -> Result<DiskUsage, String>,
+    {
+        self.get_or_measure_blocking(path, measure)
+    }
+
+    /// The cached usage of `path`, or what `measure` says once it changed.
+    pub fn get_or_measure_blocking<F>(&self, path: &Path, measure: F) -> Result<DiskUsage, String>
+    where
+        F: FnOnce(&Path) -> Result<DiskUsage, String>,
     {
         // Check if path metadata changed
         let metadata = fs::metadata(path).map_err(|e| format!("Failed to get metadata: {}", e))?;
@@ -42,37 +98,33 @@ impl DirectorySizeCache {
             .modified()
             .map_err(|e| format!("Failed to get modified time: {}", e))?;
 
-        // Try to get from cache
-        {
-            let cache = self.cache.read().await;
-            if let Some(cached) = cache.peek(path) {
-                if cached.last_modified == modified && cached.calculated_at.elapsed() < self.ttl {
-                    return Ok(cached.size);
-                }
+        if let Some(cached) = self.entries().get(path) {
+            if cached.is_fresh(modified, self.ttl) {
+                return Ok(cached.usage);
             }
         }
 
-        // Calculate new size
-        let size = calculator(path)?;
-
-        // Update cache
-        {
-            let mut cache = self.cache.write().await;
-            cache.put(
-                path.to_path_buf(),
-                CachedSize {
-                    size,
-                    calculated_at: Instant::now(),
-                    last_modified: modified,
-                },
-            );
-        }
+        // Calculate outside the lock; sizing a large tree takes a while.
+        let usage = measure(path)?;
+        self.entries().put(
+            path.to_path_buf(),
+            CachedSize {
+                usage,
+                computed_at: SystemTime::now(),
+                last_modified: modified,
+                restored: false,
+            },
+        );
+        Ok(usage)
+    }
 
-        Ok(size)
+    /// The usage last measured for `path`, however old.
+    pub fn peek_usage(&self, path: &Path) -> Option<DiskUsage> {
+        self.entries().peek(path).map(|cached| cached.usage)
     }
 
     pub async fn invalidate(&self, path: &Path) {
-        let mut cache = self.cache.write().await;
+        let mut cache = self.entries();
 
         // Invalidate path and all children
         let keys_to_remove: Vec<_> = cache
@@ -85,6 +137,86 @@ impl DirectorySizeCache {
             cache.pop(&key);
         }
     }
+
+    /// Write the cached sizes to `path`, least recently used first.
+    #[cfg(any(test, feature = "app"))]
+    pub fn save_to(&self, path: &Path) -> Result<(), String> {
+        let entries: Vec<PersistedSize> = self
+            .entries()
+            .iter()
+            .rev()
+            .map(|(dir, cached)| PersistedSize {
+                path: dir.clone(),
+                size: cached.usage.allocated,
+                usage: Some(cached.usage),
+                mtime: cached.last_modified,
+                computed_at: cached.computed_at,
+            })
+            .collect();
+        let data = serde_json::to_vec(&entries)
+            .map_err(|e| format!("Failed to serialize directory sizes: {}", e))?;
+        persistence::write_atomic(path, &data)
+    }
+
+    /// Restore sizes saved by `save_to`, skipping directories that changed
+    /// or vanished since and sizes too old to trust. Returns how many were
+    /// restored.
+    #[cfg(any(test, feature = "app"))]
+    pub fn load_from(&self, path: &Path) -> usize {
+        let Some(entries) = persistence::read_json::<Vec<PersistedSize>>(path) else {
+            return 0;
+        };
+        let mut cache = self.entries();
+        let mut restored = 0;
+        for entry in entries {
+            let unchanged = fs::metadata(&entry.path)
+                .and_then(|metadata| metadata.modified())
+                .is_ok_and(|mtime| mtime == entry.mtime);
+            let recent = entry
+                .computed_at
+                .elapsed()
+                .is_ok_and(|age| age < RESTORED_MAX_AGE);
+            if !unchanged || !recent || cache.contains(&entry.path) {
+                continue;
+            }
+            cache.put(
+                entry.path,
+                CachedSize {
+                    usage: entry
+                        .usage
+                        .unwrap_or_else(|| DiskUsage::from_allocated(entry.size)),
+                    computed_at: entry.computed_at,
+                    last_modified: entry.mtime,
+                    restored: true,
+                },
+            );
+            restored += 1;
+        }
+        restored
+    }
+}
+
+#[cfg(feature = "app")]
+fn dir_size_cache_path() -> Option<PathBuf> {
+    persistence::data_file(DIR_SIZE_CACHE_FILE)
+}
+
+/// Save `DIR_SIZE_CACHE` to `<data dir>/macos-optimizer/dir_size_cache.json`
+/// for the next launch.
+#[cfg(feature = "app")]
+pub fn persist_dir_size_cache() -> Result<(), String> {
+    match dir_size_cache_path() {
+        Some(path) => DIR_SIZE_CACHE.save_to(&path),
+        None => Ok(()),
+    }
+}
+
+/// Load what the last session saved into `DIR_SIZE_CACHE`.
+#[cfg(feature = "app")]
+pub fn restore_dir_size_cache() -> usize {
+    dir_size_cache_path()
+        .map(|path| DIR_SIZE_CACHE.load_from(&path))
+        .unwrap_or(0)
 }
 
 // Global cache instance
@@ -92,13 +224,13 @@ lazy_static! {
     pub static ref DIR_SIZE_CACHE: DirectorySizeCache = DirectorySizeCache::new(1000, 300);
 }
 
-#[cfg(feature = "metadata-cache")]
+#[cfg(all(feature = "cache-refresh", feature = "app"))]
 pub struct FileMetadataCache {
     cache: Arc<DashMap<PathBuf, CachedMetadata>>,
     ttl: Duration,
 }
 
-#[cfg(feature = "metadata-cache")]
+#[cfg(all(feature = "cache-refresh", feature = "app"))]
 #[derive(Clone)]
 pub struct CachedMetadata {
     pub size: u64,
@@ -108,7 +240,7 @@ pub struct CachedMetadata {
     pub cached_at: Instant,
 }
 
-#[cfg(feature = "metadata-cache")]
+#[cfg(all(feature = "cache-refresh", feature = "app"))]
 impl FileMetadataCache {
     pub fn new(capacity: usize, ttl_seconds: u64) -> Self {
         FileMetadataCache {
@@ -127,7 +259,11 @@ impl FileMetadataCache {
 
         // Fetch metadata
         if let Ok(metadata) = fs::metadata(path) {
-            let risk = super::safety::assess_path_risk(path);
+            let risk = super::safety::assess_path_risk_with(
+                path,
+                &AgeThresholds::default(),
+                &ProtectionPacks::default(),
+            );
             let is_safe = matches!(risk.level, super::safety::RiskLevel::Safe);
             let cached = CachedMetadata {
                 size: metadata.len(),
@@ -158,23 +294,23 @@ impl FileMetadataCache {
 }
 
 // Global file metadata cache
-#[cfg(feature = "metadata-cache")]
+#[cfg(all(feature = "cache-refresh", feature = "app"))]
 lazy_static! {
     pub static ref FILE_METADATA_CACHE: FileMetadataCache = FileMetadataCache::new(5000, 300);
 }
 
 // Background cache refresh
-#[cfg(feature = "cache-refresh")]
+#[cfg(all(feature = "cache-refresh", feature = "app"))]
 use tokio::time::interval;
 
-#[cfg(feature = "cache-refresh")]
+#[cfg(all(feature = "cache-refresh", feature = "app"))]
 pub struct CacheRefresher {
     dir_cache: Arc<DirectorySizeCache>,
     file_cache: Arc<FileMetadataCache>,
     paths_to_monitor: Arc<RwLock<Vec<PathBuf>>>,
 }
 
-#[cfg(feature = "cache-refresh")]
+#[cfg(all(feature = "cache-refresh", feature = "app"))]
 impl CacheRefresher {
     pub fn new() -> Self {
         CacheRefresher {
@@ -216,7 +352,10 @@ impl CacheRefresher {
                 if path.is_dir() {
                     let _ = self
                         .dir_cache
-                        .get_or_calculate(path, |p| self.calculate_directory_size(p))
+                        .get_or_measure(path, |p| {
+                            self.calculate_directory_size(p)
+                                .map(DiskUsage::from_allocated)
+                        })
                         .await;
                 }
 
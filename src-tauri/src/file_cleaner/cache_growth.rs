@@ -0,0 +1,404 @@
+// src/file_cleaner/cache_growth.rs
+
+//! How fast each app's caches grow back.
+//!
+//! An FSEvents stream watches the high-churn cache folders. Every sweep
+//! re-measures the per-app folders something changed in and adds the
+//! difference from their last size to that app's total for the day: growth
+//! as regenerated bytes, shrinkage as removed ones. The first sweep, and
+//! any sweep after FSEvents dropped events or without FSEvents at all,
+//! measures every folder. The first size seen for a folder is only its
+//! baseline, so caches that were already there never count as growth.
+
+use std::collections::{BTreeMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use super::disk_usage;
+use super::incremental::{invalidated_subtrees, is_within, ChangeTracker};
+use super::path_alias::dedupe_key;
+use crate::persistence;
+
+const CACHE_GROWTH_FILE: &str = "cache_growth.json";
+const DAY_SECS: i64 = 24 * 60 * 60;
+/// Days of growth kept; the longest report covers the same span.
+pub const CACHE_GROWTH_RETENTION_DAYS: u32 = 30;
+pub const DEFAULT_CACHE_GROWTH_DAYS: u32 = 7;
+/// How often the changed cache folders are measured.
+pub const CACHE_GROWTH_SWEEP: Duration = Duration::from_secs(10 * 60);
+
+/// A watched folder and whose caches it holds: `owner`, or with no owner,
+/// the app each folder directly inside it is named after.
+struct CacheRoot {
+    path: PathBuf,
+    owner: Option<&'static str>,
+}
+
+fn cache_roots(home: &Path) -> Vec<CacheRoot> {
+    vec![
+        CacheRoot {
+            path: home.join("Library/Caches"),
+            owner: None,
+        },
+        CacheRoot {
+            path: home.join("Library/Developer/Xcode/DerivedData"),
+            owner: Some("Xcode"),
+        },
+    ]
+}
+
+/// Size of one cache folder, zero once it is gone.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct CacheMeasurement {
+    /// Dedupe key of the folder.
+    folder: String,
+    app: String,
+    bytes: u64,
+}
+
+/// What one sweep measured.
+#[derive(Debug, Default)]
+pub struct CacheSweep {
+    /// Dedupe keys of roots measured in full: known folders under them that
+    /// were not measured are gone.
+    complete: Vec<String>,
+    folders: Vec<CacheMeasurement>,
+}
+
+/// Watches the cache folders under a home folder between sweeps.
+pub struct CacheGrowthTracker {
+    roots: Vec<CacheRoot>,
+    changes: ChangeTracker,
+    swept: bool,
+}
+
+impl CacheGrowthTracker {
+    pub fn watch(home: &Path) -> Self {
+        let roots = cache_roots(home);
+        let paths: Vec<PathBuf> = roots.iter().map(|root| root.path.clone()).collect();
+        Self {
+            roots,
+            changes: ChangeTracker::watch(&paths),
+            swept: false,
+        }
+    }
+
+    /// A tracker fed only through `record_change`.
+    #[cfg(test)]
+    pub(crate) fn manual(home: &Path) -> Self {
+        Self {
+            roots: cache_roots(home),
+            changes: ChangeTracker::manual(),
+            swept: false,
+        }
+    }
+
+    #[cfg(test)]
+    pub(crate) fn record_change(&self, dir: &Path) {
+        self.changes.record(dir);
+    }
+
+    /// Measure the folders that may have changed since the last sweep.
+    /// Blocks while it walks them.
+    pub fn sweep(&mut self) -> CacheSweep {
+        let changed = self.changes.take().filter(|_| self.swept);
+        self.swept = true;
+        let mut sweep = CacheSweep::default();
+        for root in &self.roots {
+            let root_key = dedupe_key(&root.path.to_string_lossy());
+            let Some(changed) = &changed else {
+                sweep.sweep_root(root, root_key);
+                continue;
+            };
+            for (key, path) in invalidated_subtrees(&root.path, changed, root.owner.is_some()) {
+                match (root.owner, path) {
+                    (None, _) if key == root_key => {
+                        sweep.sweep_root(root, root_key.clone());
+                    }
+                    (owner, path) => sweep.folders.push(CacheMeasurement {
+                        app: owner
+                            .map(str::to_string)
+                            .or_else(|| folder_name(path.as_deref()?))
+                            .unwrap_or_else(|| key.rsplit('/').next().unwrap_or_default().into()),
+                        bytes: path.map_or(0, |path| disk_usage::measure(&path).allocated),
+                        folder: key,
+                    }),
+                }
+            }
+        }
+        sweep
+    }
+}
+
+impl CacheSweep {
+    fn sweep_root(&mut self, root: &CacheRoot, root_key: String) {
+        let measure = |key: String, app: String, path: &Path| CacheMeasurement {
+            folder: key,
+            app,
+            bytes: disk_usage::measure(path).allocated,
+        };
+        if let Some(owner) = root.owner {
+            if root.path.is_dir() {
+                self.folders
+                    .push(measure(root_key.clone(), owner.into(), &root.path));
+            }
+        } else if let Ok(entries) = fs::read_dir(&root.path) {
+            for entry in entries.filter_map(|entry| entry.ok()) {
+                let path = entry.path();
+                if !entry.file_type().is_ok_and(|kind| kind.is_dir()) {
+                    continue;
+                }
+                if let Some(app) = folder_name(&path) {
+                    self.folders
+                        .push(measure(dedupe_key(&path.to_string_lossy()), app, &path));
+                }
+            }
+        }
+        self.complete.push(root_key);
+    }
+}
+
+fn folder_name(path: &Path) -> Option<String> {
+    path.file_name()
+        .map(|name| name.to_string_lossy().to_string())
+}
+
+/// Bytes an app's caches gained and lost over one day.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+struct DayGrowth {
+    grown: u64,
+    removed: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct FolderSize {
+    app: String,
+    bytes: u64,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct GrowthData {
+    /// UTC date of the first sweep; earlier days have no data.
+    since: Option<String>,
+    /// Last measured size of each cache folder, by dedupe key.
+    folders: BTreeMap<String, FolderSize>,
+    /// Growth by UTC date (`YYYY-MM-DD`), then app.
+    days: BTreeMap<String, BTreeMap<String, DayGrowth>>,
+}
+
+/// One app's cache growth over a report's window.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AppCacheGrowth {
+    pub app: String,
+    /// Size of the app's cache folders at the last sweep.
+    pub current_size: u64,
+    pub grown: u64,
+    pub removed: u64,
+    pub grown_per_day: u64,
+    /// Bytes regenerated per hundred removed, when any were removed. At or
+    /// above 100 the app grew back everything cleaned in the window.
+    pub regrowth_percent: Option<u32>,
+    /// Days with any change, oldest first.
+    pub daily: Vec<DailyCacheGrowth>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DailyCacheGrowth {
+    pub date: String,
+    pub grown: u64,
+    pub removed: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CacheGrowthReport {
+    /// First and last UTC date covered.
+    pub from: String,
+    pub to: String,
+    /// Days of the window that were tracked.
+    pub days: u32,
+    pub tracking_since: Option<String>,
+    pub total_grown: u64,
+    /// Apps whose caches changed in the window, fastest growing first.
+    pub apps: Vec<AppCacheGrowth>,
+}
+
+/// Cache folder sizes and the daily growth measured between them.
+pub struct CacheGrowthLog {
+    path: Option<PathBuf>,
+    data: GrowthData,
+}
+
+impl CacheGrowthLog {
+    /// Load from `<data dir>/macos-optimizer/cache_growth.json`.
+    pub fn load() -> Self {
+        match persistence::data_file(CACHE_GROWTH_FILE) {
+            Some(path) => Self::load_from(&path),
+            None => Self {
+                path: None,
+                data: GrowthData::default(),
+            },
+        }
+    }
+
+    /// Load from an explicit file; a missing or unreadable file yields an empty log.
+    pub fn load_from(path: &Path) -> Self {
+        let data = persistence::read_json(path).unwrap_or_default();
+        Self {
+            path: Some(path.to_path_buf()),
+            data,
+        }
+    }
+
+    /// Add what changed since the previous sweep to the day of `now`.
+    pub fn record(&mut self, now: i64, sweep: CacheSweep) -> Result<(), String> {
+        let date = utc_date(now);
+        let measured: HashSet<&str> = sweep
+            .folders
+            .iter()
+            .map(|folder| folder.folder.as_str())
+            .collect();
+        let vanished: Vec<CacheMeasurement> = self
+            .data
+            .folders
+            .iter()
+            .filter(|(key, _)| {
+                !measured.contains(key.as_str())
+                    && sweep.complete.iter().any(|root| is_within(key, root))
+            })
+            .map(|(key, folder)| CacheMeasurement {
+                folder: key.clone(),
+                app: folder.app.clone(),
+                bytes: 0,
+            })
+            .collect();
+
+        let mut changed = self.data.since.is_none();
+        self.data.since.get_or_insert_with(|| date.clone());
+        for measurement in sweep.folders.into_iter().chain(vanished) {
+            let previous = match measurement.bytes {
+                0 => self.data.folders.remove(&measurement.folder),
+                bytes => self.data.folders.insert(
+                    measurement.folder,
+                    FolderSize {
+                        app: measurement.app.clone(),
+                        bytes,
+                    },
+                ),
+            };
+            let Some(previous) = previous else {
+                changed |= measurement.bytes > 0;
+                continue;
+            };
+            if previous.bytes == measurement.bytes {
+                continue;
+            }
+            let day = self
+                .data
+                .days
+                .entry(date.clone())
+                .or_default()
+                .entry(previous.app)
+                .or_default();
+            if measurement.bytes > previous.bytes {
+                day.grown += measurement.bytes - previous.bytes;
+            } else {
+                day.removed += previous.bytes - measurement.bytes;
+            }
+            changed = true;
+        }
+
+        let cutoff = utc_date(now - (CACHE_GROWTH_RETENTION_DAYS as i64 - 1) * DAY_SECS);
+        let before = self.data.days.len();
+        self.data.days.retain(|day, _| *day >= cutoff);
+        changed |= self.data.days.len() != before;
+        if changed {
+            self.persist()
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Growth per app over the `days` days ending on the day of `now`.
+    pub fn report(&self, now: i64, days: u32) -> CacheGrowthReport {
+        let days = days.clamp(1, CACHE_GROWTH_RETENTION_DAYS);
+        let to = utc_date(now);
+        let from = utc_date(now - (days as i64 - 1) * DAY_SECS);
+        let tracked_days = match &self.data.since {
+            Some(since) if *since > from => days_between(since, &to).map_or(1, |span| span + 1),
+            Some(_) => days,
+            None => 0,
+        };
+
+        let mut apps: BTreeMap<&str, AppCacheGrowth> = BTreeMap::new();
+        for (date, growth) in self.data.days.range(from.clone()..=to.clone()) {
+            for (app, day) in growth {
+                let entry = apps.entry(app).or_insert_with(|| AppCacheGrowth {
+                    app: app.clone(),
+                    current_size: 0,
+                    grown: 0,
+                    removed: 0,
+                    grown_per_day: 0,
+                    regrowth_percent: None,
+                    daily: Vec::new(),
+                });
+                entry.grown += day.grown;
+                entry.removed += day.removed;
+                entry.daily.push(DailyCacheGrowth {
+                    date: date.clone(),
+                    grown: day.grown,
+                    removed: day.removed,
+                });
+            }
+        }
+        for folder in self.data.folders.values() {
+            if let Some(app) = apps.get_mut(folder.app.as_str()) {
+                app.current_size += folder.bytes;
+            }
+        }
+
+        let mut apps: Vec<AppCacheGrowth> = apps
+            .into_values()
+            .map(|mut app| {
+                app.grown_per_day = app.grown / tracked_days.max(1) as u64;
+                app.regrowth_percent = (app.removed > 0).then(|| {
+                    (app.grown.saturating_mul(100) / app.removed).min(u32::MAX as u64) as u32
+                });
+                app
+            })
+            .collect();
+        apps.sort_by(|a, b| b.grown.cmp(&a.grown).then_with(|| a.app.cmp(&b.app)));
+
+        CacheGrowthReport {
+            from,
+            to,
+            days: tracked_days,
+            tracking_since: self.data.since.clone(),
+            total_grown: apps.iter().map(|app| app.grown).sum(),
+            apps,
+        }
+    }
+
+    fn persist(&self) -> Result<(), String> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        persistence::write_json(path, &self.data)
+    }
+}
+
+fn utc_date(timestamp: i64) -> String {
+    DateTime::<Utc>::from_timestamp(timestamp, 0)
+        .unwrap_or_default()
+        .format("%Y-%m-%d")
+        .to_string()
+}
+
+fn days_between(from: &str, to: &str) -> Option<u32> {
+    let parse = |date: &str| chrono::NaiveDate::parse_from_str(date, "%Y-%m-%d").ok();
+    let span = (parse(to)? - parse(from)?).num_days();
+    u32::try_from(span).ok()
+}
@@ -0,0 +1,164 @@
+// src/file_cleaner/custom_path.rs
+
+//! Scanning a folder the user picks, such as an external drive or a project
+//! folder, with the rules and safety analysis of the system scan.
+//!
+//! A rule whose paths lie inside the folder is walked as usual, and a rule
+//! whose path contains the folder is walked from the folder down. Rules that
+//! match by extension or path fragment (partial downloads, installers,
+//! screenshots, nested caches) are also applied to the folder itself. What
+//! those find is never preselected: outside the places the rule was written
+//! for, such files are as often kept on purpose.
+
+use std::fs;
+#[cfg(any(test, feature = "app"))]
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+
+use serde::Deserialize;
+
+use super::types::CategoryRule;
+
+pub const DEFAULT_CUSTOM_SCAN_DEPTH: usize = 8;
+/// Depth a rule walks when it does not set one, as in the system scan.
+const DEFAULT_RULE_DEPTH: usize = 10;
+/// Parts of macOS a custom scan never walks, besides the whole disk.
+#[cfg(any(test, feature = "app"))]
+const PROTECTED_ROOTS: &[&str] = &[
+    "/System",
+    "/usr",
+    "/bin",
+    "/sbin",
+    "/dev",
+    "/private/etc",
+    "/private/var/db",
+];
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct CustomScanOptions {
+    /// How deep rules applied to the folder itself walk.
+    pub max_depth: usize,
+    /// Replaces the minimum age of every rule.
+    pub min_age_days: Option<i64>,
+    /// Replaces the minimum size of every rule.
+    pub min_size_kb: Option<u64>,
+    /// Also look for duplicates among what was found.
+    pub find_duplicates: bool,
+}
+
+impl Default for CustomScanOptions {
+    fn default() -> Self {
+        Self {
+            max_depth: DEFAULT_CUSTOM_SCAN_DEPTH,
+            min_age_days: None,
+            min_size_kb: None,
+            find_duplicates: false,
+        }
+    }
+}
+
+/// One walk of a custom scan.
+#[derive(Debug, Clone)]
+pub(crate) struct CustomScanTarget {
+    pub(crate) rule: CategoryRule,
+    pub(crate) root: PathBuf,
+    /// Applied to the folder outside the rule's own paths.
+    pub(crate) rerooted: bool,
+}
+
+/// Expand `~/`, resolve links and check that `path` is a folder the scan
+/// may read.
+#[cfg(any(test, feature = "app"))]
+pub fn resolve_scan_root(path: &str, home: Option<&Path>) -> Result<PathBuf, String> {
+    let expanded = expand(path, home).ok_or("Home directory not found")?;
+    if !expanded.is_absolute() {
+        return Err(format!("Not an absolute path: {}", path));
+    }
+    let root = fs::canonicalize(&expanded).map_err(|e| match e.kind() {
+        ErrorKind::NotFound => format!("Folder not found: {}", path),
+        _ => format!("Cannot access {}: {}", path, e),
+    })?;
+    if !root.is_dir() {
+        return Err(format!("Not a folder: {}", path));
+    }
+    if root.parent().is_none()
+        || PROTECTED_ROOTS
+            .iter()
+            .any(|protected| root.starts_with(protected))
+    {
+        return Err(format!("Scanning {} is not allowed", root.display()));
+    }
+    fs::read_dir(&root).map_err(|e| match e.kind() {
+        ErrorKind::PermissionDenied => format!(
+            "No permission to read {}; grant Full Disk Access in System Settings > Privacy & Security",
+            root.display()
+        ),
+        _ => format!("Cannot read {}: {}", root.display(), e),
+    })?;
+    Ok(root)
+}
+
+/// The walks that scan `root` with `rules`.
+pub(crate) fn custom_scan_targets(
+    rules: Vec<CategoryRule>,
+    root: &Path,
+    options: &CustomScanOptions,
+    home: Option<&Path>,
+) -> Vec<CustomScanTarget> {
+    let mut targets = Vec::new();
+    for mut rule in rules {
+        if options.min_age_days.is_some() {
+            rule.min_age_days = options.min_age_days;
+        }
+        if options.min_size_kb.is_some() {
+            rule.min_size_kb = options.min_size_kb;
+        }
+        let mut covered = false;
+        for path in rule.paths.iter().filter_map(|path| expand(path, home)) {
+            let path = fs::canonicalize(&path).unwrap_or(path);
+            if path.starts_with(root) {
+                if path.exists() {
+                    targets.push(CustomScanTarget {
+                        rule: rule.clone(),
+                        root: path,
+                        rerooted: false,
+                    });
+                }
+                covered = true;
+            } else if let Ok(below) = root.strip_prefix(&path) {
+                let depth = rule
+                    .max_depth
+                    .unwrap_or(DEFAULT_RULE_DEPTH)
+                    .saturating_sub(below.components().count());
+                if depth > 0 {
+                    let mut scoped = rule.clone();
+                    scoped.max_depth = Some(depth);
+                    targets.push(CustomScanTarget {
+                        rule: scoped,
+                        root: root.to_path_buf(),
+                        rerooted: false,
+                    });
+                }
+                covered = true;
+            }
+        }
+        if !covered && (rule.extensions.is_some() || rule.require_subpaths.is_some()) {
+            rule.safe = false;
+            rule.max_depth = Some(options.max_depth);
+            targets.push(CustomScanTarget {
+                rule,
+                root: root.to_path_buf(),
+                rerooted: true,
+            });
+        }
+    }
+    targets
+}
+
+fn expand(path: &str, home: Option<&Path>) -> Option<PathBuf> {
+    match path.strip_prefix("~/") {
+        Some(rest) => home.map(|home| home.join(rest)),
+        None => Some(PathBuf::from(path)),
+    }
+}
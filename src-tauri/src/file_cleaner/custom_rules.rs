@@ -0,0 +1,213 @@
+use std::path::Path;
+#[cfg(any(test, feature = "app"))]
+use std::path::PathBuf;
+
+#[cfg(any(test, feature = "app"))]
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+
+use super::enhanced_rules::RuleValidator;
+#[cfg(any(test, feature = "app"))]
+use super::types::load_base_rules;
+use super::types::{CategoryRule, CleanerRules};
+use crate::persistence;
+
+const CUSTOM_RULES_FILE: &str = "custom_rules.json";
+
+/// Request payload for adding or replacing a custom rule. Fields mirror the
+/// entries in `cleaner_rules.json`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NewCustomRule {
+    pub name: String,
+    pub paths: Vec<String>,
+    #[serde(default)]
+    pub safe: bool,
+    #[serde(default)]
+    pub advanced: bool,
+    pub max_depth: Option<usize>,
+    pub min_age_days: Option<i64>,
+    pub min_size_kb: Option<u64>,
+    #[serde(default)]
+    pub excludes: Vec<String>,
+    #[serde(default)]
+    pub extensions: Vec<String>,
+    #[serde(default)]
+    pub require_subpaths: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CustomRule {
+    pub id: String,
+    #[serde(flatten)]
+    pub rule: NewCustomRule,
+    pub created_at: i64,
+    pub updated_at: i64,
+}
+
+impl NewCustomRule {
+    pub(crate) fn normalized(mut self) -> Self {
+        let trim_all = |values: Vec<String>| -> Vec<String> {
+            values
+                .into_iter()
+                .map(|value| value.trim().to_string())
+                .filter(|value| !value.is_empty())
+                .collect()
+        };
+        self.name = self.name.trim().to_string();
+        self.paths = trim_all(self.paths);
+        self.excludes = trim_all(self.excludes);
+        self.extensions = trim_all(self.extensions)
+            .into_iter()
+            .map(|ext| ext.trim_start_matches('.').to_lowercase())
+            .collect();
+        self.require_subpaths = trim_all(self.require_subpaths);
+        self
+    }
+
+    pub(crate) fn to_category_rule(&self) -> CategoryRule {
+        let non_empty = |values: &Vec<String>| (!values.is_empty()).then(|| values.clone());
+        CategoryRule {
+            name: self.name.clone(),
+            paths: self.paths.clone(),
+            safe: self.safe,
+            advanced: Some(self.advanced),
+            max_depth: self.max_depth,
+            min_age_days: self.min_age_days,
+            min_size_kb: self.min_size_kb,
+            excludes: non_empty(&self.excludes),
+            extensions: non_empty(&self.extensions),
+            require_subpaths: non_empty(&self.require_subpaths),
+        }
+    }
+}
+
+/// User-defined cleaning rules, scanned alongside the embedded rules.
+pub struct CustomRuleStore {
+    #[cfg(any(test, feature = "app"))]
+    path: Option<PathBuf>,
+    rules: Vec<CustomRule>,
+}
+
+impl CustomRuleStore {
+    /// Load from `<data dir>/macos-optimizer/custom_rules.json`.
+    pub fn load() -> Self {
+        match persistence::data_file(CUSTOM_RULES_FILE) {
+            Some(path) => Self::load_from(&path),
+            None => Self {
+                #[cfg(any(test, feature = "app"))]
+                path: None,
+                rules: Vec::new(),
+            },
+        }
+    }
+
+    /// Load from an explicit file; a missing or unreadable file yields an empty store.
+    pub fn load_from(path: &Path) -> Self {
+        let rules = persistence::read_json(path).unwrap_or_default();
+        Self {
+            #[cfg(any(test, feature = "app"))]
+            path: Some(path.to_path_buf()),
+            rules,
+        }
+    }
+
+    #[cfg(any(test, feature = "app"))]
+    pub fn list(&self) -> Vec<CustomRule> {
+        self.rules.clone()
+    }
+
+    #[cfg(any(test, feature = "app"))]
+    pub fn add(&mut self, request: NewCustomRule) -> Result<CustomRule, String> {
+        let request = request.normalized();
+        self.validate(&request, None)?;
+        let now = Utc::now().timestamp();
+        let rule = CustomRule {
+            id: uuid::Uuid::new_v4().to_string(),
+            rule: request,
+            created_at: now,
+            updated_at: now,
+        };
+        self.rules.push(rule.clone());
+        self.persist()?;
+        Ok(rule)
+    }
+
+    #[cfg(any(test, feature = "app"))]
+    pub fn update(&mut self, id: &str, request: NewCustomRule) -> Result<CustomRule, String> {
+        if !self.rules.iter().any(|rule| rule.id == id) {
+            return Err("Unknown custom rule".into());
+        }
+        let request = request.normalized();
+        self.validate(&request, Some(id))?;
+        let Some(rule) = self.rules.iter_mut().find(|rule| rule.id == id) else {
+            return Err("Unknown custom rule".into());
+        };
+        rule.rule = request;
+        rule.updated_at = Utc::now().timestamp();
+        let updated = rule.clone();
+        self.persist()?;
+        Ok(updated)
+    }
+
+    /// Remove a rule; returns whether it existed.
+    #[cfg(any(test, feature = "app"))]
+    pub fn remove(&mut self, id: &str) -> Result<bool, String> {
+        let before = self.rules.len();
+        self.rules.retain(|rule| rule.id != id);
+        if self.rules.len() == before {
+            return Ok(false);
+        }
+        self.persist()?;
+        Ok(true)
+    }
+
+    pub(crate) fn category_rules(&self) -> Vec<CategoryRule> {
+        self.rules
+            .iter()
+            .map(|rule| rule.rule.to_category_rule())
+            .collect()
+    }
+
+    /// Check a candidate against the embedded rules and the other custom
+    /// rules (`replacing` is excluded when updating).
+    #[cfg(any(test, feature = "app"))]
+    fn validate(&self, request: &NewCustomRule, replacing: Option<&str>) -> Result<(), String> {
+        let mut existing = load_base_rules()?;
+        existing.categories.extend(
+            self.rules
+                .iter()
+                .filter(|rule| Some(rule.id.as_str()) != replacing)
+                .map(|rule| rule.rule.to_category_rule()),
+        );
+        RuleValidator::new().validate_custom_rule(&request.to_category_rule(), &existing)
+    }
+
+    #[cfg(any(test, feature = "app"))]
+    fn persist(&self) -> Result<(), String> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        persistence::write_json(path, &self.rules)
+    }
+}
+
+/// Custom rules to merge into a scan; rules that no longer pass validation
+/// (e.g. after an embedded rule changed) are skipped.
+pub(crate) fn load_custom_category_rules(base: &CleanerRules) -> Vec<CategoryRule> {
+    let store = CustomRuleStore::load();
+    let validator = RuleValidator::new();
+    let mut accepted = CleanerRules {
+        categories: base.categories.clone(),
+    };
+    let mut rules = Vec::new();
+    for rule in store.category_rules() {
+        match validator.validate_custom_rule(&rule, &accepted) {
+            Ok(()) => {
+                accepted.categories.push(rule.clone());
+                rules.push(rule);
+            }
+            Err(err) => log::warn!("Skipping custom rule {}: {}", rule.name, err),
+        }
+    }
+    rules
+}
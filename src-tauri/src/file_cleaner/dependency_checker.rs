@@ -4,8 +4,6 @@ use std::path::{Path, PathBuf};
 use tokio::process::Command;
 use tokio::time::{timeout, Duration};
 
-use dirs;
-
 use super::types::CleanableFile;
 
 pub struct DependencyChecker;
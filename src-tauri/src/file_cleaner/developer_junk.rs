@@ -0,0 +1,553 @@
+use std::cmp::{Ordering, Reverse};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use serde::{Deserialize, Serialize};
+use tokio_util::sync::CancellationToken;
+use walkdir::WalkDir;
+
+use super::engine::FileCleaner;
+use super::exclusions::ExclusionMatcher;
+use super::types::CleanableFile;
+
+const XCODE_DIR: &str = "Library/Developer/Xcode";
+const SIMULATOR_DEVICES_DIR: &str = "Library/Developer/CoreSimulator/Devices";
+const SWIFTPM_CACHE_DIR: &str = "Library/Caches/org.swift.swiftpm";
+const MODULE_CACHE_DIR: &str = "ModuleCache.noindex";
+/// Archives older than this are reported; they hold the only dSYMs for
+/// symbolicating crashes from that build, so they are never preselected.
+const ARCHIVE_STALE_DAYS: i64 = 365;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DeveloperJunkKind {
+    DerivedData,
+    ModuleCache,
+    DeviceSupport,
+    Simulator,
+    Archive,
+}
+
+impl DeveloperJunkKind {
+    /// Scan category the item is reported and excluded under.
+    pub fn category(self) -> &'static str {
+        match self {
+            DeveloperJunkKind::DerivedData => "Xcode DerivedData",
+            DeveloperJunkKind::ModuleCache => "Developer Module Cache",
+            DeveloperJunkKind::DeviceSupport => "Xcode Device Support",
+            DeveloperJunkKind::Simulator => "Unavailable Simulators",
+            DeveloperJunkKind::Archive => "Stale Xcode Archives",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeveloperJunkItem {
+    pub path: String,
+    pub kind: DeveloperJunkKind,
+    /// Project, OS version or simulator name.
+    pub name: String,
+    pub detail: String,
+    pub size: u64,
+    pub last_modified: i64,
+    /// Safe to remove without losing anything that cannot be rebuilt or redownloaded.
+    pub recommended: bool,
+}
+
+impl DeveloperJunkItem {
+    pub(crate) fn to_cleanable(&self) -> CleanableFile {
+        CleanableFile {
+            path: self.path.clone(),
+            size: self.size,
+            category: self.kind.category().to_string(),
+            description: self.detail.clone(),
+            last_modified: self.last_modified,
+            safe_to_delete: false,
+            safety_score: 0,
+            auto_select: false,
+        }
+    }
+}
+
+/// Items belonging to one developer tool.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeveloperJunkGroup {
+    pub tool: String,
+    pub total_size: u64,
+    pub recommended_size: u64,
+    pub items: Vec<DeveloperJunkItem>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeveloperJunkReport {
+    pub groups: Vec<DeveloperJunkGroup>,
+    pub total_size: u64,
+    pub recommended_size: u64,
+    /// DerivedData is not preselected while Xcode is building.
+    pub xcode_running: bool,
+    /// False when `xcrun simctl` could not be queried, so simulators were skipped.
+    pub simulators_checked: bool,
+}
+
+/// A simulator as listed by `xcrun simctl list devices --json`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SimulatorDevice {
+    pub udid: String,
+    pub name: String,
+    /// Readable runtime, e.g. "iOS 17.0".
+    pub runtime: String,
+    pub state: String,
+    pub is_available: bool,
+    pub availability_error: Option<String>,
+    pub data_path: Option<String>,
+}
+
+/// What the developer scan needs to know about the running system.
+#[derive(Debug, Clone, Default)]
+pub struct DeveloperEnvironment {
+    pub xcode_running: bool,
+    /// `None` when simctl is unavailable.
+    pub simulators: Option<Vec<SimulatorDevice>>,
+}
+
+#[derive(Deserialize)]
+struct SimctlDevices {
+    devices: BTreeMap<String, Vec<SimctlDevice>>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SimctlDevice {
+    udid: String,
+    name: String,
+    #[serde(default)]
+    state: String,
+    #[serde(default = "default_available")]
+    is_available: bool,
+    availability_error: Option<String>,
+    data_path: Option<String>,
+}
+
+fn default_available() -> bool {
+    true
+}
+
+/// Parse `xcrun simctl list devices --json` output.
+pub fn parse_simctl_devices(json: &str) -> Result<Vec<SimulatorDevice>, String> {
+    let parsed: SimctlDevices =
+        serde_json::from_str(json).map_err(|e| format!("Invalid simctl output: {}", e))?;
+    Ok(parsed
+        .devices
+        .into_iter()
+        .flat_map(|(runtime, devices)| {
+            let runtime = runtime_display_name(&runtime);
+            devices.into_iter().map(move |device| SimulatorDevice {
+                udid: device.udid,
+                name: device.name,
+                runtime: runtime.clone(),
+                state: device.state,
+                is_available: device.is_available,
+                availability_error: device.availability_error,
+                data_path: device.data_path,
+            })
+        })
+        .collect())
+}
+
+/// "com.apple.CoreSimulator.SimRuntime.iOS-17-0" -> "iOS 17.0"
+fn runtime_display_name(identifier: &str) -> String {
+    let short = identifier.rsplit('.').next().unwrap_or(identifier);
+    match short.split_once('-') {
+        Some((platform, version)) => format!("{} {}", platform, version.replace('-', ".")),
+        None => short.to_string(),
+    }
+}
+
+/// Enumerate developer junk under `home`, grouped by tool and largest first.
+pub(crate) fn scan_developer_junk(
+    home: &Path,
+    environment: &DeveloperEnvironment,
+    exclusions: &ExclusionMatcher,
+    token: &CancellationToken,
+) -> Result<DeveloperJunkReport, String> {
+    let xcode = home.join(XCODE_DIR);
+    let mut groups: Vec<(&str, Vec<DeveloperJunkItem>)> = vec![
+        (
+            "Xcode",
+            derived_data(&xcode, environment.xcode_running, token)?,
+        ),
+        ("Device Support", device_support(&xcode, token)?),
+        ("Simulators", Vec::new()),
+        ("Swift Package Manager", swiftpm_cache(home, token)?),
+    ];
+    groups[0].1.extend(stale_archives(&xcode, token)?);
+    if let Some(simulators) = &environment.simulators {
+        groups[2].1 = unavailable_simulators(home, simulators, token)?;
+    }
+
+    let mut groups: Vec<DeveloperJunkGroup> = groups
+        .into_iter()
+        .filter_map(|(tool, items)| {
+            let mut items: Vec<DeveloperJunkItem> = items
+                .into_iter()
+                .filter(|item| !exclusions.is_excluded(&item.path, item.kind.category()))
+                .collect();
+            if items.is_empty() {
+                return None;
+            }
+            items.sort_by(|a, b| b.size.cmp(&a.size).then_with(|| a.path.cmp(&b.path)));
+            Some(DeveloperJunkGroup {
+                tool: tool.to_string(),
+                total_size: items.iter().map(|item| item.size).sum(),
+                recommended_size: recommended_size(&items),
+                items,
+            })
+        })
+        .collect();
+    groups.sort_by_key(|group| Reverse(group.total_size));
+
+    Ok(DeveloperJunkReport {
+        total_size: groups.iter().map(|group| group.total_size).sum(),
+        recommended_size: groups.iter().map(|group| group.recommended_size).sum(),
+        groups,
+        xcode_running: environment.xcode_running,
+        simulators_checked: environment.simulators.is_some(),
+    })
+}
+
+fn recommended_size(items: &[DeveloperJunkItem]) -> u64 {
+    items
+        .iter()
+        .filter(|item| item.recommended)
+        .map(|item| item.size)
+        .sum()
+}
+
+fn derived_data(
+    xcode: &Path,
+    xcode_running: bool,
+    token: &CancellationToken,
+) -> Result<Vec<DeveloperJunkItem>, String> {
+    let mut items = Vec::new();
+    for path in child_dirs(&xcode.join("DerivedData")) {
+        let file_name = file_name(&path);
+        let item = if file_name == MODULE_CACHE_DIR {
+            junk_item(
+                &path,
+                DeveloperJunkKind::ModuleCache,
+                "Clang module cache".to_string(),
+                "Precompiled modules shared by all projects; rebuilt on the next build".into(),
+                !xcode_running,
+                token,
+            )?
+        } else {
+            // Project folders are named "<Project>-<hash>".
+            let project = file_name
+                .rsplit_once('-')
+                .map(|(project, _)| project)
+                .unwrap_or(&file_name)
+                .to_string();
+            let detail = if xcode_running {
+                format!("Build products for {}; Xcode is running", project)
+            } else {
+                format!("Build products for {}; rebuilt on the next build", project)
+            };
+            junk_item(
+                &path,
+                DeveloperJunkKind::DerivedData,
+                project,
+                detail,
+                !xcode_running,
+                token,
+            )?
+        };
+        items.push(item);
+    }
+    Ok(items)
+}
+
+/// Every `<Platform> DeviceSupport` version except the newest per platform,
+/// which Xcode needs to debug devices on the current OS.
+fn device_support(
+    xcode: &Path,
+    token: &CancellationToken,
+) -> Result<Vec<DeveloperJunkItem>, String> {
+    let mut items = Vec::new();
+    for platform_dir in child_dirs(xcode) {
+        let dir_name = file_name(&platform_dir);
+        let Some(platform) = dir_name.strip_suffix(" DeviceSupport") else {
+            continue;
+        };
+        let versions: Vec<(PathBuf, Vec<u32>, String)> = child_dirs(&platform_dir)
+            .into_iter()
+            .filter_map(|path| {
+                let name = file_name(&path);
+                parse_device_support_version(&name).map(|version| (path, version, name))
+            })
+            .collect();
+        let Some(latest) = versions
+            .iter()
+            .map(|(_, version, _)| version)
+            .max()
+            .cloned()
+        else {
+            continue;
+        };
+        let latest_label = join_version(&latest);
+        for (path, version, name) in versions {
+            if compare_versions(&version, &latest) == Ordering::Equal {
+                continue;
+            }
+            // Newer Xcodes prefix the folder with the device model, e.g. "iPhone15,2 17.0 (21A329)".
+            let device = name
+                .split_whitespace()
+                .next()
+                .filter(|first| !first.starts_with(|c: char| c.is_ascii_digit()));
+            let detail = match device {
+                Some(device) => format!(
+                    "Symbols for {} {} from {}; latest {} is kept",
+                    platform,
+                    join_version(&version),
+                    device,
+                    latest_label
+                ),
+                None => format!(
+                    "Symbols for {} {}; latest {} is kept",
+                    platform,
+                    join_version(&version),
+                    latest_label
+                ),
+            };
+            items.push(junk_item(
+                &path,
+                DeveloperJunkKind::DeviceSupport,
+                format!("{} {}", platform, join_version(&version)),
+                detail,
+                true,
+                token,
+            )?);
+        }
+    }
+    Ok(items)
+}
+
+/// First whitespace-separated token that starts with a digit, as numeric parts.
+fn parse_device_support_version(name: &str) -> Option<Vec<u32>> {
+    let token = name
+        .split_whitespace()
+        .find(|part| part.starts_with(|c: char| c.is_ascii_digit()))?;
+    token
+        .split('.')
+        .map(|part| part.parse::<u32>().ok())
+        .collect()
+}
+
+fn compare_versions(a: &[u32], b: &[u32]) -> Ordering {
+    let len = a.len().max(b.len());
+    (0..len)
+        .map(|i| {
+            a.get(i)
+                .copied()
+                .unwrap_or(0)
+                .cmp(&b.get(i).copied().unwrap_or(0))
+        })
+        .find(|ordering| ordering.is_ne())
+        .unwrap_or(Ordering::Equal)
+}
+
+fn join_version(version: &[u32]) -> String {
+    version
+        .iter()
+        .map(|part| part.to_string())
+        .collect::<Vec<_>>()
+        .join(".")
+}
+
+fn unavailable_simulators(
+    home: &Path,
+    simulators: &[SimulatorDevice],
+    token: &CancellationToken,
+) -> Result<Vec<DeveloperJunkItem>, String> {
+    let devices_dir = home.join(SIMULATOR_DEVICES_DIR);
+    let mut items = Vec::new();
+    for device in simulators.iter().filter(|device| !device.is_available) {
+        let path = devices_dir.join(&device.udid);
+        if !path.is_dir() {
+            continue;
+        }
+        let booted = device.state.eq_ignore_ascii_case("booted");
+        let reason = device
+            .availability_error
+            .as_deref()
+            .unwrap_or("runtime no longer installed");
+        let detail = if booted {
+            format!(
+                "{} unavailable ({}); currently booted",
+                device.runtime, reason
+            )
+        } else {
+            format!("{} unavailable ({})", device.runtime, reason)
+        };
+        items.push(junk_item(
+            &path,
+            DeveloperJunkKind::Simulator,
+            device.name.clone(),
+            detail,
+            !booted,
+            token,
+        )?);
+    }
+    Ok(items)
+}
+
+fn stale_archives(
+    xcode: &Path,
+    token: &CancellationToken,
+) -> Result<Vec<DeveloperJunkItem>, String> {
+    let now = unix_now();
+    let mut items = Vec::new();
+    for day_dir in child_dirs(&xcode.join("Archives")) {
+        for archive in child_dirs(&day_dir) {
+            if archive.extension().and_then(|ext| ext.to_str()) != Some("xcarchive") {
+                continue;
+            }
+            let modified = modified_secs(&archive);
+            let age_days = (now - modified) / 86_400;
+            if age_days < ARCHIVE_STALE_DAYS {
+                continue;
+            }
+            let name = archive
+                .file_stem()
+                .map(|stem| stem.to_string_lossy().to_string())
+                .unwrap_or_default();
+            items.push(junk_item(
+                &archive,
+                DeveloperJunkKind::Archive,
+                name,
+                format!(
+                    "Archived {} days ago; removing it loses the dSYMs for that build",
+                    age_days
+                ),
+                false,
+                token,
+            )?);
+        }
+    }
+    Ok(items)
+}
+
+fn swiftpm_cache(home: &Path, token: &CancellationToken) -> Result<Vec<DeveloperJunkItem>, String> {
+    let path = home.join(SWIFTPM_CACHE_DIR);
+    if !path.is_dir() {
+        return Ok(Vec::new());
+    }
+    Ok(vec![junk_item(
+        &path,
+        DeveloperJunkKind::ModuleCache,
+        "Package cache".to_string(),
+        "Downloaded package checkouts; fetched again on the next resolve".into(),
+        true,
+        token,
+    )?])
+}
+
+fn junk_item(
+    path: &Path,
+    kind: DeveloperJunkKind,
+    name: String,
+    detail: String,
+    recommended: bool,
+    token: &CancellationToken,
+) -> Result<DeveloperJunkItem, String> {
+    Ok(DeveloperJunkItem {
+        path: path.to_string_lossy().to_string(),
+        kind,
+        name,
+        detail,
+        size: directory_size(path, token)?,
+        last_modified: modified_secs(path),
+        recommended,
+    })
+}
+
+fn directory_size(path: &Path, token: &CancellationToken) -> Result<u64, String> {
+    let mut total = 0u64;
+    for (index, entry) in WalkDir::new(path)
+        .into_iter()
+        .filter_map(Result::ok)
+        .enumerate()
+    {
+        if index.is_multiple_of(4096) && token.is_cancelled() {
+            return Err("cancelled".into());
+        }
+        if entry.file_type().is_file() {
+            if let Ok(metadata) = entry.metadata() {
+                total = total.saturating_add(FileCleaner::metadata_size_bytes(&metadata));
+            }
+        }
+    }
+    Ok(total)
+}
+
+fn child_dirs(dir: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut dirs: Vec<PathBuf> = entries
+        .filter_map(Result::ok)
+        .filter(|entry| entry.file_type().map(|kind| kind.is_dir()).unwrap_or(false))
+        .map(|entry| entry.path())
+        .collect();
+    dirs.sort();
+    dirs
+}
+
+fn file_name(path: &Path) -> String {
+    path.file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default()
+}
+
+fn modified_secs(path: &Path) -> i64 {
+    fs::metadata(path)
+        .and_then(|metadata| metadata.modified())
+        .ok()
+        .and_then(|time| time.duration_since(SystemTime::UNIX_EPOCH).ok())
+        .map(|since| since.as_secs() as i64)
+        .unwrap_or(0)
+}
+
+fn unix_now() -> i64 {
+    SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map(|since| since.as_secs() as i64)
+        .unwrap_or(0)
+}
+
+/// Simulators known to CoreSimulator, or `None` when simctl cannot be run.
+pub async fn list_simulators() -> Option<Vec<SimulatorDevice>> {
+    if !crate::capabilities::tool_available(crate::capabilities::Tool::Xcrun) {
+        return None;
+    }
+    let output = tokio::process::Command::new("xcrun")
+        .args(["simctl", "list", "devices", "--json"])
+        .output()
+        .await
+        .ok()?;
+    if !output.status.success() {
+        log::debug!(
+            "simctl failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+        return None;
+    }
+    match parse_simctl_devices(&String::from_utf8_lossy(&output.stdout)) {
+        Ok(devices) => Some(devices),
+        Err(err) => {
+            log::warn!("{}", err);
+            None
+        }
+    }
+}
@@ -0,0 +1,166 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use tokio_util::sync::CancellationToken;
+
+use super::cache::DIR_SIZE_CACHE;
+use super::disk_usage::UsageCounter;
+use super::engine::FileCleaner;
+
+pub const DEFAULT_TREE_DEPTH: usize = 3;
+pub const MAX_TREE_DEPTH: usize = 8;
+/// Children kept per directory; the rest are folded into one `Other` node so
+/// the treemap stays readable and the payload small.
+const MAX_CHILDREN: usize = 48;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DiskNodeKind {
+    Directory,
+    File,
+    /// Smaller siblings folded together.
+    Other,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DiskNode {
+    pub name: String,
+    pub path: String,
+    pub kind: DiskNodeKind,
+    /// Allocated bytes, matching the sizes shown for cleanable files.
+    pub size: u64,
+    /// Children sorted by size, largest first; empty at `max_depth`.
+    pub children: Vec<DiskNode>,
+    /// The directory could not be read, so its size is a lower bound.
+    pub unreadable: bool,
+}
+
+/// Build a size-annotated tree of `root` for treemap/sunburst rendering.
+///
+/// Directories are expanded `max_depth` levels deep; anything below is
+/// sized through the shared directory size cache. Symlinks are not followed.
+/// Walks the disk, so run it on the blocking pool.
+pub fn scan_directory_tree(
+    root: &Path,
+    max_depth: usize,
+    token: &CancellationToken,
+) -> Result<DiskNode, String> {
+    let metadata =
+        fs::symlink_metadata(root).map_err(|e| format!("Cannot read {}: {}", root.display(), e))?;
+    if !metadata.is_dir() {
+        return Err(format!("{} is not a directory", root.display()));
+    }
+    build_node(root.to_path_buf(), max_depth.min(MAX_TREE_DEPTH), token)
+}
+
+fn build_node(
+    path: PathBuf,
+    depth_left: usize,
+    token: &CancellationToken,
+) -> Result<DiskNode, String> {
+    if token.is_cancelled() {
+        return Err("cancelled".into());
+    }
+    let name = path
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_else(|| path.to_string_lossy().to_string());
+    let metadata = fs::symlink_metadata(&path)
+        .map_err(|e| format!("Cannot read {}: {}", path.display(), e))?;
+
+    if !metadata.is_dir() {
+        return Ok(DiskNode {
+            name,
+            path: path.to_string_lossy().to_string(),
+            kind: DiskNodeKind::File,
+            size: FileCleaner::metadata_size_bytes(&metadata),
+            children: Vec::new(),
+            unreadable: false,
+        });
+    }
+
+    if depth_left == 0 {
+        return cached_directory_node(name, path, token);
+    }
+
+    let entries = match fs::read_dir(&path) {
+        Ok(entries) => entries,
+        Err(err) => {
+            log::debug!("Skipping unreadable {}: {}", path.display(), err);
+            return Ok(DiskNode {
+                name,
+                path: path.to_string_lossy().to_string(),
+                kind: DiskNodeKind::Directory,
+                size: 0,
+                children: Vec::new(),
+                unreadable: true,
+            });
+        }
+    };
+    let mut children = Vec::new();
+    for entry in entries.filter_map(Result::ok) {
+        match build_node(entry.path(), depth_left - 1, token) {
+            Ok(child) => children.push(child),
+            Err(err) if err == "cancelled" => return Err(err),
+            Err(err) => log::debug!("Skipping {}: {}", entry.path().display(), err),
+        }
+    }
+
+    let size = children.iter().map(|c| c.size).sum();
+    let unreadable = children.iter().any(|c| c.unreadable);
+    Ok(DiskNode {
+        name,
+        path: path.to_string_lossy().to_string(),
+        kind: DiskNodeKind::Directory,
+        size,
+        children: fold_small_children(&path, children),
+        unreadable,
+    })
+}
+
+/// A leaf directory sized through DIR_SIZE_CACHE, so repeated treemap scans
+/// and the cleaner share work.
+fn cached_directory_node(
+    name: String,
+    path: PathBuf,
+    token: &CancellationToken,
+) -> Result<DiskNode, String> {
+    let size = DIR_SIZE_CACHE
+        .get_or_measure_blocking(&path, |dir| {
+            let mut counter = UsageCounter::default();
+            if !counter.add_tree(dir, || !token.is_cancelled()) {
+                return Err("cancelled".into());
+            }
+            Ok(counter.finish())
+        })?
+        .allocated;
+    Ok(DiskNode {
+        name,
+        path: path.to_string_lossy().to_string(),
+        kind: DiskNodeKind::Directory,
+        size,
+        children: Vec::new(),
+        unreadable: false,
+    })
+}
+
+fn fold_small_children(parent: &Path, mut children: Vec<DiskNode>) -> Vec<DiskNode> {
+    children.sort_by(|a, b| b.size.cmp(&a.size).then_with(|| a.name.cmp(&b.name)));
+    if children.len() <= MAX_CHILDREN {
+        return children;
+    }
+    let rest = children.split_off(MAX_CHILDREN - 1);
+    children.push(DiskNode {
+        name: format!(
+            "{} other items",
+            crate::formatting::format_count(rest.len() as u64)
+        ),
+        path: parent.to_string_lossy().to_string(),
+        kind: DiskNodeKind::Other,
+        size: rest.iter().map(|c| c.size).sum(),
+        children: Vec::new(),
+        unreadable: rest.iter().any(|c| c.unreadable),
+    });
+    children
+}
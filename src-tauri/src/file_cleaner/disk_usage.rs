@@ -0,0 +1,210 @@
+//! Sizing that knows about hard links and APFS clones.
+//!
+//! A file's apparent size is its length and its allocated size the blocks
+//! it occupies. Neither says what deleting it frees: a hard-linked inode is
+//! only freed with its last link, and an APFS clone shares its blocks with
+//! the file it was cloned from. `reclaimable` is what would be freed, as far
+//! as stat info and the file system tell.
+
+use std::collections::HashMap;
+use std::fs;
+#[cfg(unix)]
+use std::os::unix::fs::MetadataExt;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+use walkdir::WalkDir;
+
+use super::cache::DIR_SIZE_CACHE;
+
+/// Sizes of a file, a directory tree or a set of them.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DiskUsage {
+    /// Sum of file lengths.
+    pub apparent: u64,
+    /// Blocks on disk, each hard-linked inode counted once.
+    pub allocated: u64,
+    /// Allocated bytes that deleting everything measured would free.
+    pub reclaimable: u64,
+}
+
+impl DiskUsage {
+    /// Usage known only by its allocated size, e.g. from an older cache.
+    pub fn from_allocated(size: u64) -> Self {
+        Self {
+            apparent: size,
+            allocated: size,
+            reclaimable: size,
+        }
+    }
+
+    pub fn add(&mut self, other: DiskUsage) {
+        self.apparent = self.apparent.saturating_add(other.apparent);
+        self.allocated = self.allocated.saturating_add(other.allocated);
+        self.reclaimable = self.reclaimable.saturating_add(other.reclaimable);
+    }
+}
+
+/// A hard-linked inode met while counting.
+struct LinkedInode {
+    links: u64,
+    seen: u64,
+    private: u64,
+}
+
+/// Adds up files, counting each hard-linked inode once. An inode is only
+/// reclaimable if all of its links were counted.
+#[derive(Default)]
+pub(crate) struct UsageCounter {
+    usage: DiskUsage,
+    linked: HashMap<(u64, u64), LinkedInode>,
+}
+
+impl UsageCounter {
+    /// Count the regular file at `path`; anything else is ignored.
+    pub(crate) fn add_file(&mut self, path: &Path, metadata: &fs::Metadata) {
+        if !metadata.is_file() {
+            return;
+        }
+        let allocated = allocated_bytes(metadata);
+        #[cfg(unix)]
+        if metadata.nlink() > 1 {
+            let usage = &mut self.usage;
+            let inode = self
+                .linked
+                .entry((metadata.dev(), metadata.ino()))
+                .or_insert_with(|| {
+                    usage.apparent = usage.apparent.saturating_add(metadata.len());
+                    usage.allocated = usage.allocated.saturating_add(allocated);
+                    LinkedInode {
+                        links: metadata.nlink(),
+                        seen: 0,
+                        private: private_bytes(path, allocated),
+                    }
+                });
+            inode.seen += 1;
+            return;
+        }
+        self.usage.add(DiskUsage {
+            apparent: metadata.len(),
+            allocated,
+            reclaimable: private_bytes(path, allocated),
+        });
+    }
+
+    /// Count every file under `dir`, or `dir` itself if it is a file.
+    /// Stops early, returning false, once `keep_going` does.
+    pub(crate) fn add_tree(&mut self, dir: &Path, keep_going: impl Fn() -> bool) -> bool {
+        for entry in WalkDir::new(dir).into_iter().filter_map(Result::ok) {
+            if !keep_going() {
+                return false;
+            }
+            if entry.file_type().is_file() {
+                if let Ok(metadata) = entry.metadata() {
+                    self.add_file(entry.path(), &metadata);
+                }
+            }
+        }
+        true
+    }
+
+    /// Merge in usage counted elsewhere, such as a cached directory size.
+    pub(crate) fn add_usage(&mut self, usage: DiskUsage) {
+        self.usage.add(usage);
+    }
+
+    pub(crate) fn finish(self) -> DiskUsage {
+        let mut usage = self.usage;
+        for inode in self.linked.values() {
+            if inode.seen >= inode.links {
+                usage.reclaimable = usage.reclaimable.saturating_add(inode.private);
+            }
+        }
+        usage
+    }
+}
+
+/// Usage of the file or directory tree at `path`; empty if it is missing.
+pub fn measure(path: &Path) -> DiskUsage {
+    let mut counter = UsageCounter::default();
+    counter.add_tree(path, || true);
+    counter.finish()
+}
+
+/// Usage of scan results given as `(path, size)`. Files are measured, so a
+/// file hard-linked from several results counts once; directories use the
+/// usage cached when the scan sized them, or `size` without one.
+pub(crate) fn usage_of_results<'a>(results: impl IntoIterator<Item = (&'a str, u64)>) -> DiskUsage {
+    let mut counter = UsageCounter::default();
+    for (path, size) in results {
+        let path = Path::new(path);
+        match fs::symlink_metadata(path) {
+            Ok(metadata) if metadata.is_file() => counter.add_file(path, &metadata),
+            Ok(metadata) if metadata.is_dir() => counter.add_usage(
+                DIR_SIZE_CACHE
+                    .peek_usage(path)
+                    .unwrap_or_else(|| DiskUsage::from_allocated(size)),
+            ),
+            _ => counter.add_usage(DiskUsage::from_allocated(size)),
+        }
+    }
+    counter.finish()
+}
+
+/// Blocks on disk, or the length where the file system reports none.
+pub(crate) fn allocated_bytes(metadata: &fs::Metadata) -> u64 {
+    #[cfg(unix)]
+    {
+        let blocks = metadata.blocks();
+        if blocks > 0 {
+            return blocks.saturating_mul(512);
+        }
+    }
+    metadata.len()
+}
+
+/// Of `allocated`, the bytes not shared with an APFS clone.
+#[cfg(target_os = "macos")]
+fn private_bytes(path: &Path, allocated: u64) -> u64 {
+    use std::ffi::CString;
+    use std::os::unix::ffi::OsStrExt;
+
+    let Ok(c_path) = CString::new(path.as_os_str().as_bytes()) else {
+        return allocated;
+    };
+    let mut request = libc::attrlist {
+        bitmapcount: libc::ATTR_BIT_MAP_COUNT,
+        reserved: 0,
+        commonattr: libc::ATTR_CMN_RETURNED_ATTRS,
+        volattr: 0,
+        dirattr: 0,
+        fileattr: 0,
+        forkattr: libc::ATTR_CMNEXT_PRIVATESIZE,
+    };
+    // Length, the returned attribute set, then the private size
+    let mut buffer = [0u8; 4 + 20 + 8];
+    let status = unsafe {
+        libc::getattrlist(
+            c_path.as_ptr(),
+            &mut request as *mut libc::attrlist as *mut libc::c_void,
+            buffer.as_mut_ptr() as *mut libc::c_void,
+            buffer.len(),
+            libc::FSOPT_NOFOLLOW | libc::FSOPT_ATTR_CMN_EXTENDED,
+        )
+    };
+    if status != 0 {
+        return allocated;
+    }
+    let returned_fork = u32::from_ne_bytes(buffer[20..24].try_into().unwrap_or_default());
+    if returned_fork & libc::ATTR_CMNEXT_PRIVATESIZE == 0 {
+        // Not APFS: nothing is shared
+        return allocated;
+    }
+    let private = i64::from_ne_bytes(buffer[24..32].try_into().unwrap_or_default());
+    u64::try_from(private).unwrap_or(0).min(allocated)
+}
+
+#[cfg(not(target_os = "macos"))]
+fn private_bytes(_path: &Path, allocated: u64) -> u64 {
+    allocated
+}
@@ -0,0 +1,243 @@
+// src/file_cleaner/ds_store.rs
+
+//! Reader for the `.DS_Store` files Finder keeps per folder.
+//!
+//! Only what the Trash needs is decoded: each record's file name, four-byte
+//! code and, for `ustr` values, the string. The file is a buddy-allocated
+//! heap ("Bud1") whose `DSDB` entry points at a B-tree of records sorted by
+//! file name. All integers are big-endian and strings are UTF-16BE.
+
+use std::collections::HashMap;
+
+/// Finder's record of where a trashed item came from.
+pub const PUT_BACK_LOCATION: &[u8; 4] = b"ptbL";
+pub const PUT_BACK_NAME: &[u8; 4] = b"ptbN";
+
+/// Guards against corrupt files whose node pointers loop.
+const MAX_TREE_DEPTH: usize = 32;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DsValue {
+    Ustr(String),
+    /// Any other type; its bytes are skipped.
+    Other,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DsRecord {
+    pub file_name: String,
+    pub code: [u8; 4],
+    pub value: DsValue,
+}
+
+/// Where Finder would put a trashed item back.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PutBack {
+    /// Parent directory relative to the volume root, e.g. `Users/me/Desktop/`.
+    pub directory: Option<String>,
+    /// Name before trashing, when Finder renamed it to avoid a clash.
+    pub name: Option<String>,
+}
+
+/// Put-back information per item name in the folder the file belongs to.
+pub fn put_back_locations(data: &[u8]) -> Result<HashMap<String, PutBack>, String> {
+    let mut locations: HashMap<String, PutBack> = HashMap::new();
+    for record in read_records(data)? {
+        let DsValue::Ustr(value) = record.value else {
+            continue;
+        };
+        if &record.code == PUT_BACK_LOCATION {
+            locations.entry(record.file_name).or_default().directory = Some(value);
+        } else if &record.code == PUT_BACK_NAME {
+            locations.entry(record.file_name).or_default().name = Some(value);
+        }
+    }
+    Ok(locations)
+}
+
+/// Every record in the file, in tree order.
+pub fn read_records(data: &[u8]) -> Result<Vec<DsRecord>, String> {
+    let file = Reader::new(data);
+    if file.u32_at(0)? != 1 || file.bytes_at(4, 4)? != b"Bud1" {
+        return Err("Not a .DS_Store file".to_string());
+    }
+    // Offsets in the file are relative to the byte after the leading 1.
+    let heap = Reader::new(&data[4..]);
+    let root_offset = heap.u32_at(4)? as usize;
+    let root_size = heap.u32_at(8)? as usize;
+    let root = Reader::new(heap.bytes_at(root_offset, root_size)?);
+
+    let mut cursor = Cursor::new(root);
+    let address_count = cursor.u32()? as usize;
+    cursor.skip(4)?;
+    let addresses = (0..address_count)
+        .map(|_| cursor.u32())
+        .collect::<Result<Vec<_>, _>>()?;
+    // The address table is padded to a multiple of 256 entries.
+    let padded = address_count.div_ceil(256) * 256;
+    cursor.skip((padded - address_count) * 4)?;
+
+    let directory_count = cursor.u32()?;
+    let mut master = None;
+    for _ in 0..directory_count {
+        let name_len = cursor.u8()? as usize;
+        let name = cursor.bytes(name_len)?;
+        let block = cursor.u32()?;
+        if name == b"DSDB" {
+            master = Some(block);
+        }
+    }
+    let master = master.ok_or("No DSDB directory entry")?;
+
+    let block = |number: u32| -> Result<Reader, String> {
+        let address = *addresses
+            .get(number as usize)
+            .ok_or_else(|| format!("Block {} out of range", number))?;
+        let offset = (address & !0x1f) as usize;
+        let size = 1usize << (address & 0x1f);
+        heap.bytes_at(offset, size).map(Reader::new)
+    };
+
+    let root_node = Cursor::new(block(master)?).u32()?;
+    let mut records = Vec::new();
+    read_node(&block, root_node, 0, &mut records)?;
+    Ok(records)
+}
+
+fn read_node<'a>(
+    block: &impl Fn(u32) -> Result<Reader<'a>, String>,
+    number: u32,
+    depth: usize,
+    records: &mut Vec<DsRecord>,
+) -> Result<(), String> {
+    if depth > MAX_TREE_DEPTH {
+        return Err("Record tree is too deep".to_string());
+    }
+    let mut cursor = Cursor::new(block(number)?);
+    let rightmost = cursor.u32()?;
+    let count = cursor.u32()?;
+    for _ in 0..count {
+        if rightmost != 0 {
+            let child = cursor.u32()?;
+            read_node(block, child, depth + 1, records)?;
+        }
+        records.push(read_record(&mut cursor)?);
+    }
+    if rightmost != 0 {
+        read_node(block, rightmost, depth + 1, records)?;
+    }
+    Ok(())
+}
+
+fn read_record(cursor: &mut Cursor) -> Result<DsRecord, String> {
+    let name_len = cursor.u32()? as usize;
+    let file_name = cursor.utf16(name_len)?;
+    let code: [u8; 4] = cursor.array()?;
+    let kind: [u8; 4] = cursor.array()?;
+    let value = match &kind {
+        b"ustr" => {
+            let len = cursor.u32()? as usize;
+            DsValue::Ustr(cursor.utf16(len)?)
+        }
+        b"bool" => {
+            cursor.skip(1)?;
+            DsValue::Other
+        }
+        b"long" | b"shor" | b"type" => {
+            cursor.skip(4)?;
+            DsValue::Other
+        }
+        b"comp" | b"dutc" => {
+            cursor.skip(8)?;
+            DsValue::Other
+        }
+        b"blob" => {
+            let len = cursor.u32()? as usize;
+            cursor.skip(len)?;
+            DsValue::Other
+        }
+        _ => {
+            return Err(format!(
+                "Unknown record type {}",
+                String::from_utf8_lossy(&kind)
+            ))
+        }
+    };
+    Ok(DsRecord {
+        file_name,
+        code,
+        value,
+    })
+}
+
+#[derive(Clone, Copy)]
+struct Reader<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        Self { data }
+    }
+
+    fn bytes_at(&self, offset: usize, len: usize) -> Result<&'a [u8], String> {
+        offset
+            .checked_add(len)
+            .and_then(|end| self.data.get(offset..end))
+            .ok_or_else(|| "Truncated .DS_Store file".to_string())
+    }
+
+    fn u32_at(&self, offset: usize) -> Result<u32, String> {
+        let bytes = self.bytes_at(offset, 4)?;
+        Ok(u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    }
+}
+
+struct Cursor<'a> {
+    reader: Reader<'a>,
+    position: usize,
+}
+
+impl<'a> Cursor<'a> {
+    fn new(reader: Reader<'a>) -> Self {
+        Self {
+            reader,
+            position: 0,
+        }
+    }
+
+    fn bytes(&mut self, len: usize) -> Result<&'a [u8], String> {
+        let bytes = self.reader.bytes_at(self.position, len)?;
+        self.position += len;
+        Ok(bytes)
+    }
+
+    fn skip(&mut self, len: usize) -> Result<(), String> {
+        self.bytes(len).map(|_| ())
+    }
+
+    fn array(&mut self) -> Result<[u8; 4], String> {
+        let bytes = self.bytes(4)?;
+        Ok([bytes[0], bytes[1], bytes[2], bytes[3]])
+    }
+
+    fn u8(&mut self) -> Result<u8, String> {
+        Ok(self.bytes(1)?[0])
+    }
+
+    fn u32(&mut self) -> Result<u32, String> {
+        self.array().map(u32::from_be_bytes)
+    }
+
+    fn utf16(&mut self, units: usize) -> Result<String, String> {
+        let len = units
+            .checked_mul(2)
+            .ok_or_else(|| "Truncated .DS_Store file".to_string())?;
+        let units: Vec<u16> = self
+            .bytes(len)?
+            .chunks_exact(2)
+            .map(|pair| u16::from_be_bytes([pair[0], pair[1]]))
+            .collect();
+        Ok(String::from_utf16_lossy(&units))
+    }
+}
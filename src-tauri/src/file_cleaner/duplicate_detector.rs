@@ -1,110 +1,231 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
-use std::cmp;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::fs;
 use std::io::{self, Read, Seek, SeekFrom};
+#[cfg(unix)]
+use std::os::unix::fs::MetadataExt;
 use std::path::{Path, PathBuf};
-use std::time::{Duration, Instant};
+use std::time::{Duration, Instant, SystemTime};
 use tokio::fs as async_fs;
 use tokio::task;
 use tokio_util::sync::CancellationToken;
+use walkdir::WalkDir;
 
+use super::disk_usage;
+use super::exclusions::ExclusionMatcher;
+use super::hashing::{self, HashAlgorithm};
+use super::similar_images::{self, SimilarImageGroup, SimilarImageScan, MAX_SIMILAR_DISTANCE};
+use crate::capabilities::{tool_available, Tool};
+
+/// Bytes hashed from each end of a file before it is hashed whole.
 const QUICK_FINGERPRINT_BYTES: usize = 64 * 1024;
-const DEFAULT_DUPLICATE_TIME_BUDGET: Duration = Duration::from_secs(12);
+/// Files compared in one scan at most; beyond it the scan is `truncated`.
+const MAX_DUPLICATE_CANDIDATES: usize = 2_000_000;
+/// Images scaled by `sips` at once while computing perceptual hashes.
+const CONCURRENT_IMAGE_HASHES: usize = 4;
+pub(crate) const DEFAULT_DUPLICATE_TIME_BUDGET: Duration = Duration::from_secs(12);
+/// Budget for scanning folders the user picked, which they expect to wait
+/// for and can cancel; results stream in meanwhile.
+pub(crate) const FOLDER_DUPLICATE_TIME_BUDGET: Duration = Duration::from_secs(30 * 60);
+/// Category duplicates are cleaned and excluded under.
+pub const DUPLICATE_CATEGORY: &str = "Duplicate Files";
+/// Directories macOS presents as single documents; removing a file inside
+/// one corrupts the whole library or bundle.
+pub(crate) const PACKAGE_EXTENSIONS: &[&str] = &[
+    "app",
+    "bundle",
+    "framework",
+    "photoslibrary",
+    "musiclibrary",
+    "tvlibrary",
+    "imovielibrary",
+    "fcpbundle",
+    "logicx",
+    "band",
+    "pages",
+    "numbers",
+    "key",
+    "xcodeproj",
+    "xcworkspace",
+];
+
+/// Which copy of a duplicate group survives `resolve_duplicates`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "strategy", content = "path", rename_all = "snake_case")]
+pub enum KeepStrategy {
+    Newest,
+    Oldest,
+    ShortestPath,
+    SpecificPath(PathBuf),
+}
+
+impl KeepStrategy {
+    /// The file to keep, chosen among the group's files that still exist.
+    pub fn keeper(&self, files: &[PathBuf]) -> Option<PathBuf> {
+        let existing: Vec<(&PathBuf, SystemTime)> = files
+            .iter()
+            .filter_map(|path| {
+                let modified = fs::metadata(path).and_then(|m| m.modified()).ok()?;
+                Some((path, modified))
+            })
+            .collect();
+        let chosen = match self {
+            KeepStrategy::Newest => existing.iter().max_by_key(|(_, modified)| *modified),
+            KeepStrategy::Oldest => existing.iter().min_by_key(|(_, modified)| *modified),
+            KeepStrategy::ShortestPath => existing.iter().min_by(|(a, _), (b, _)| {
+                a.as_os_str().len().cmp(&b.as_os_str().len()).then(a.cmp(b))
+            }),
+            KeepStrategy::SpecificPath(keep) => existing.iter().find(|(path, _)| *path == keep),
+        };
+        chosen.map(|(path, _)| (*path).clone())
+    }
+}
 
-#[derive(Debug, Default)]
+/// Regular files under `roots` to compare, skipping hidden entries, package
+/// contents and exclusions. Symlinks are not followed.
+pub(crate) fn collect_candidates(
+    roots: &[PathBuf],
+    exclusions: &ExclusionMatcher,
+    token: &CancellationToken,
+) -> Result<Vec<PathBuf>, String> {
+    let mut files = Vec::new();
+    for root in roots {
+        let entries = WalkDir::new(root).into_iter().filter_entry(|entry| {
+            if entry.depth() == 0 {
+                return true;
+            }
+            let name = entry.file_name().to_string_lossy();
+            let is_package = entry.file_type().is_dir()
+                && Path::new(name.as_ref())
+                    .extension()
+                    .and_then(|ext| ext.to_str())
+                    .is_some_and(|ext| PACKAGE_EXTENSIONS.contains(&ext.to_lowercase().as_str()));
+            !name.starts_with('.') && !is_package
+        });
+        for entry in entries.filter_map(Result::ok) {
+            if token.is_cancelled() {
+                return Err("cancelled".into());
+            }
+            if !entry.file_type().is_file() {
+                continue;
+            }
+            let path = entry.path();
+            if !exclusions.is_excluded(&path.to_string_lossy(), DUPLICATE_CATEGORY) {
+                files.push(path.to_path_buf());
+            }
+        }
+    }
+    Ok(files)
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
 pub struct DuplicateScanResult {
     pub groups: Vec<DuplicateGroup>,
     pub analyzed_files: usize,
     pub skipped_files: usize,
     pub truncated: bool,
+    /// Visually identical images among the scanned files, for folder scans.
+    #[serde(default)]
+    pub similar_groups: Vec<SimilarImageGroup>,
+}
+
+/// Size and modification time of a file when it was hashed; a cached hash
+/// only stands while both are unchanged.
+type FileStamp = (u64, Option<SystemTime>);
+
+fn file_stamp(metadata: &fs::Metadata) -> FileStamp {
+    (metadata.len(), metadata.modified().ok())
 }
 
 pub struct DuplicateDetector {
-    hash_cache: HashMap<PathBuf, String>,
-    quick_cache: HashMap<PathBuf, (u64, String)>,
+    algorithm: HashAlgorithm,
+    hash_cache: HashMap<PathBuf, (FileStamp, String)>,
+    quick_cache: HashMap<PathBuf, (FileStamp, String)>,
+    perceptual_cache: HashMap<PathBuf, (FileStamp, u64)>,
 }
 
 impl DuplicateDetector {
     pub fn new() -> Self {
+        Self::with_algorithm(HashAlgorithm::default())
+    }
+
+    /// Falls back to the default when `algorithm` isn't available here.
+    pub fn with_algorithm(algorithm: HashAlgorithm) -> Self {
         Self {
+            algorithm: if algorithm.is_available() {
+                algorithm
+            } else {
+                HashAlgorithm::default()
+            },
             hash_cache: HashMap::new(),
             quick_cache: HashMap::new(),
+            perceptual_cache: HashMap::new(),
         }
     }
 
-    pub async fn find_duplicates(
+    /// Group `paths` by content, stopping with `truncated` once `budget` elapses.
+    pub async fn find_duplicates_within(
+        &mut self,
+        paths: &[PathBuf],
+        token: &CancellationToken,
+        budget: Duration,
+    ) -> Result<DuplicateScanResult, String> {
+        self.stream_duplicates_within(paths, token, budget, |_| {})
+            .await
+    }
+
+    /// Same as `find_duplicates_within`, calling `on_group` with each group
+    /// as soon as it is confirmed.
+    ///
+    /// Files are bucketed by size, and only files sharing a size have the
+    /// first and last 64 KB hashed; only those agreeing on that are hashed
+    /// whole. Largest sizes go first, so a scan cut short by `budget` still
+    /// reports the groups that free the most.
+    pub async fn stream_duplicates_within(
         &mut self,
         paths: &[PathBuf],
         token: &CancellationToken,
+        budget: Duration,
+        mut on_group: impl FnMut(&DuplicateGroup),
     ) -> Result<DuplicateScanResult, String> {
         let mut result = DuplicateScanResult::default();
         if paths.is_empty() {
             return Ok(result);
         }
-
-        let mut size_buckets: HashMap<u64, Vec<PathBuf>> = HashMap::new();
-        for path in paths {
-            if token.is_cancelled() {
-                return Err("cancelled".into());
-            }
-            match async_fs::metadata(path).await {
-                Ok(metadata) if metadata.is_file() && metadata.len() > 0 => {
-                    size_buckets
-                        .entry(metadata.len())
-                        .or_insert_with(Vec::new)
-                        .push(path.clone());
-                }
-                Ok(_) => {
-                    result.skipped_files += 1;
-                }
-                Err(err) => {
-                    result.skipped_files += 1;
-                    log::debug!("Skipping duplicate candidate {}: {}", path.display(), err);
-                }
-            }
-        }
-
-        let mut buckets: Vec<_> = size_buckets.into_iter().collect();
-        buckets.sort_by(|a, b| b.0.cmp(&a.0));
-
         let start = Instant::now();
+        let deadline = start + budget;
 
-        'outer: for (size, files) in buckets {
-            if files.len() < 2 {
-                continue;
-            }
-
+        let candidates = paths[..paths.len().min(MAX_DUPLICATE_CANDIDATES)].to_vec();
+        if candidates.len() < paths.len() {
+            result.truncated = true;
+            result.skipped_files += paths.len() - candidates.len();
+        }
+        let bucket_token = token.clone();
+        let (buckets, skipped) =
+            task::spawn_blocking(move || bucket_by_size(&candidates, &bucket_token))
+                .await
+                .map_err(|e| format!("Duplicate scan task failed: {}", e))??;
+        result.skipped_files += skipped;
+
+        for (size, files) in buckets {
             if token.is_cancelled() {
                 return Err("cancelled".into());
             }
-            if start.elapsed() >= DEFAULT_DUPLICATE_TIME_BUDGET {
+            if Instant::now() >= deadline {
                 result.truncated = true;
                 break;
             }
 
+            let (fingerprints, complete) = self
+                .partial_fingerprints(size, files, token, deadline)
+                .await?;
             let mut fingerprint_map: HashMap<String, Vec<PathBuf>> = HashMap::new();
-
-            for path in files {
-                if token.is_cancelled() {
-                    return Err("cancelled".into());
-                }
-                if start.elapsed() >= DEFAULT_DUPLICATE_TIME_BUDGET {
-                    result.truncated = true;
-                    break 'outer;
-                }
-
-                match self.quick_fingerprint(&path, size).await {
-                    Ok(Some(fingerprint)) => {
+            for (path, fingerprint) in fingerprints {
+                match fingerprint {
+                    Ok(fingerprint) => {
                         result.analyzed_files += 1;
-                        fingerprint_map
-                            .entry(fingerprint)
-                            .or_insert_with(Vec::new)
-                            .push(path);
-                    }
-                    Ok(None) => {
-                        result.skipped_files += 1;
+                        fingerprint_map.entry(fingerprint).or_default().push(path);
                     }
                     Err(err) => {
                         result.skipped_files += 1;
@@ -116,62 +237,41 @@ impl DuplicateDetector {
                     }
                 }
             }
+            if !complete {
+                result.truncated = true;
+                break;
+            }
 
-            for (_, candidates) in fingerprint_map.into_iter() {
+            for (fingerprint, candidates) in fingerprint_map.into_iter() {
                 if candidates.len() < 2 {
                     continue;
                 }
-
-                if token.is_cancelled() {
-                    return Err("cancelled".into());
-                }
-                if start.elapsed() >= DEFAULT_DUPLICATE_TIME_BUDGET {
-                    result.truncated = true;
-                    break 'outer;
-                }
-
-                let mut hash_groups: HashMap<String, Vec<PathBuf>> = HashMap::new();
-
-                for path in candidates {
-                    if token.is_cancelled() {
-                        return Err("cancelled".into());
-                    }
-                    if start.elapsed() >= DEFAULT_DUPLICATE_TIME_BUDGET {
-                        result.truncated = true;
-                        break 'outer;
-                    }
-
-                    match self.calculate_file_signature(&path).await {
-                        Ok(signature) => {
-                            hash_groups
-                                .entry(signature)
-                                .or_insert_with(Vec::new)
-                                .push(path);
-                        }
-                        Err(err) => {
-                            result.skipped_files += 1;
-                            log::debug!(
-                                "Unable to hash {} during duplicate detection: {}",
-                                path.display(),
-                                err
-                            );
+                // The fingerprint of a file this small covers all of it.
+                let hash_groups = if size <= 2 * QUICK_FINGERPRINT_BYTES as u64 {
+                    HashMap::from([(fingerprint, candidates)])
+                } else {
+                    match self
+                        .full_hash_groups(candidates, token, deadline, &mut result)
+                        .await?
+                    {
+                        Some(groups) => groups,
+                        None => {
+                            result.truncated = true;
+                            break;
                         }
                     }
-                }
-
+                };
                 for (signature, group_files) in hash_groups.into_iter() {
                     if group_files.len() > 1 {
-                        let total_size = self.calculate_total_size(&group_files);
-                        let recommended_to_keep = self.determine_original(&group_files);
-                        result.groups.push(DuplicateGroup {
-                            hash: signature,
-                            files: group_files,
-                            total_size,
-                            recommended_to_keep,
-                        });
+                        let group = self.build_group(signature, group_files);
+                        on_group(&group);
+                        result.groups.push(group);
                     }
                 }
             }
+            if result.truncated {
+                break;
+            }
 
             task::yield_now().await;
         }
@@ -179,98 +279,210 @@ impl DuplicateDetector {
         Ok(result)
     }
 
-    async fn quick_fingerprint(
+    /// Partial fingerprints of `files`, all `size` bytes long, hashed off the
+    /// async runtime in one go. The flag is false when `deadline` passed
+    /// before every file was fingerprinted.
+    async fn partial_fingerprints(
         &mut self,
-        path: &Path,
         size: u64,
-    ) -> Result<Option<String>, String> {
-        if size == 0 {
-            return Ok(None);
-        }
-
-        if let Some((cached_size, fingerprint)) = self.quick_cache.get(path) {
-            if *cached_size == size {
-                return Ok(Some(fingerprint.clone()));
+        files: Vec<PathBuf>,
+        token: &CancellationToken,
+        deadline: Instant,
+    ) -> Result<(Vec<(PathBuf, Result<String, String>)>, bool), String> {
+        let mut fingerprints = Vec::with_capacity(files.len());
+        let mut pending = Vec::new();
+        for path in files {
+            let stamp = fs::metadata(&path)
+                .ok()
+                .map(|metadata| file_stamp(&metadata));
+            match (self.quick_cache.get(&path), stamp) {
+                (Some((cached, fingerprint)), Some(stamp))
+                    if *cached == stamp && stamp.0 == size =>
+                {
+                    fingerprints.push((path, Ok(fingerprint.clone())));
+                }
+                _ => pending.push(path),
             }
         }
-
-        let path_clone = path.to_path_buf();
-        let chunk = cmp::min(QUICK_FINGERPRINT_BYTES as u64, size.max(1)) as usize;
-
-        let fingerprint = task::spawn_blocking(move || -> Result<Option<String>, io::Error> {
-            use sha2::{Digest, Sha256};
-
-            let mut file = fs::File::open(&path_clone)?;
-            let mut buffer = vec![0u8; chunk];
-            let bytes_read = file.read(&mut buffer)?;
-            if bytes_read == 0 {
-                return Ok(None);
+        let algorithm = self.algorithm;
+        let worker_token = token.clone();
+        let (hashed, complete) = task::spawn_blocking(move || {
+            let mut hashed = Vec::with_capacity(pending.len());
+            for path in pending {
+                if worker_token.is_cancelled() || Instant::now() >= deadline {
+                    return (hashed, false);
+                }
+                let stamp = fs::metadata(&path)
+                    .ok()
+                    .map(|metadata| file_stamp(&metadata));
+                let fingerprint =
+                    partial_fingerprint(algorithm, &path, size).map_err(|err| err.to_string());
+                hashed.push((path, stamp, fingerprint));
             }
-
-            let mut hasher = Sha256::new();
-            hasher.update(&buffer[..bytes_read]);
-            Ok(Some(format!("{:x}", hasher.finalize())))
+            (hashed, true)
         })
         .await
-        .map_err(|err| err.to_string())
-        .and_then(|res| res.map_err(|err| err.to_string()))?;
-
-        if let Some(ref fingerprint) = fingerprint {
-            self.quick_cache
-                .insert(path.to_path_buf(), (size, fingerprint.clone()));
+        .map_err(|e| format!("Duplicate scan task failed: {}", e))?;
+        if token.is_cancelled() {
+            return Err("cancelled".into());
         }
-
-        Ok(fingerprint)
+        for (path, stamp, fingerprint) in hashed {
+            if let (Ok(fingerprint), Some(stamp)) = (&fingerprint, stamp) {
+                self.quick_cache
+                    .insert(path.clone(), (stamp, fingerprint.clone()));
+            }
+            fingerprints.push((path, fingerprint));
+        }
+        Ok((fingerprints, complete))
     }
 
-    async fn calculate_file_signature(&mut self, path: &Path) -> Result<String, String> {
-        if let Some(hash) = self.hash_cache.get(path) {
-            return Ok(hash.clone());
+    /// `candidates` grouped by their whole-file hash, or `None` once
+    /// `deadline` passes.
+    async fn full_hash_groups(
+        &mut self,
+        candidates: Vec<PathBuf>,
+        token: &CancellationToken,
+        deadline: Instant,
+        result: &mut DuplicateScanResult,
+    ) -> Result<Option<HashMap<String, Vec<PathBuf>>>, String> {
+        let mut hash_groups: HashMap<String, Vec<PathBuf>> = HashMap::new();
+        for path in candidates {
+            if token.is_cancelled() {
+                return Err("cancelled".into());
+            }
+            if Instant::now() >= deadline {
+                return Ok(None);
+            }
+            match self.calculate_file_signature(&path).await {
+                Ok(signature) => {
+                    hash_groups.entry(signature).or_default().push(path);
+                }
+                Err(err) => {
+                    result.skipped_files += 1;
+                    log::debug!(
+                        "Unable to hash {} during duplicate detection: {}",
+                        path.display(),
+                        err
+                    );
+                }
+            }
         }
+        Ok(Some(hash_groups))
+    }
 
-        let path_clone = path.to_path_buf();
-        let hash = task::spawn_blocking(move || -> Result<String, io::Error> {
-            use sha2::{Digest, Sha256};
-
-            let mut file = fs::File::open(&path_clone)?;
-            let metadata = file.metadata()?;
-            let file_len = metadata.len();
-
-            let mut hasher = Sha256::new();
-            let mut buffer = vec![0u8; QUICK_FINGERPRINT_BYTES];
+    fn build_group(&self, hash: String, files: Vec<PathBuf>) -> DuplicateGroup {
+        let total_size = self.calculate_total_size(&files);
+        let recommended_to_keep = self.determine_original(&files);
+        let reclaimable_size = reclaimable_size(&files, recommended_to_keep.as_deref());
+        DuplicateGroup {
+            hash,
+            files,
+            total_size,
+            recommended_to_keep,
+            reclaimable_size,
+        }
+    }
 
-            let bytes_read = file.read(&mut buffer)?;
-            if bytes_read > 0 {
-                hasher.update(&buffer[..bytes_read]);
+    /// Group images among `paths` that look the same, such as one photo
+    /// saved at another size or quality, by the distance between their
+    /// difference hashes. Stops with `truncated` once `budget` elapses.
+    pub async fn find_similar_images_within(
+        &mut self,
+        paths: &[PathBuf],
+        token: &CancellationToken,
+        budget: Duration,
+    ) -> Result<SimilarImageScan, String> {
+        let mut result = SimilarImageScan::default();
+        let mut images = Vec::new();
+        for path in paths.iter().filter(|path| similar_images::is_image(path)) {
+            match async_fs::metadata(path).await {
+                Ok(metadata)
+                    if metadata.is_file() && metadata.len() >= similar_images::MIN_IMAGE_BYTES =>
+                {
+                    images.push((path.clone(), file_stamp(&metadata)));
+                }
+                _ => result.skipped_files += 1,
             }
+        }
+        if images.len() < 2 {
+            return Ok(result);
+        }
+        if !tool_available(Tool::Sips) {
+            result.skipped_files += images.len();
+            return Ok(result);
+        }
 
-            if file_len > (QUICK_FINGERPRINT_BYTES as u64 * 2) {
-                let mid_offset = file_len / 2;
-                let seek_offset = mid_offset.saturating_sub((QUICK_FINGERPRINT_BYTES / 2) as u64);
-                file.seek(SeekFrom::Start(seek_offset))?;
-                let bytes_read = file.read(&mut buffer)?;
-                if bytes_read > 0 {
-                    hasher.update(&buffer[..bytes_read]);
+        let start = Instant::now();
+        let mut hashes = Vec::with_capacity(images.len());
+        let mut processed = 0;
+        for chunk in images.chunks(CONCURRENT_IMAGE_HASHES) {
+            if token.is_cancelled() {
+                return Err("cancelled".into());
+            }
+            if start.elapsed() >= budget {
+                result.truncated = true;
+                result.skipped_files += images.len() - processed;
+                break;
+            }
+            let pending: Vec<_> = chunk
+                .iter()
+                .filter(|(path, stamp)| {
+                    !matches!(self.perceptual_cache.get(path), Some((cached, _)) if cached == stamp)
+                })
+                .map(|(path, stamp)| async move {
+                    (path, *stamp, similar_images::perceptual_hash(path).await)
+                })
+                .collect();
+            for (path, stamp, hash) in futures::future::join_all(pending).await {
+                match hash {
+                    Ok(hash) => {
+                        self.perceptual_cache.insert(path.clone(), (stamp, hash));
+                    }
+                    Err(err) => {
+                        log::debug!("Unable to hash image {}: {}", path.display(), err);
+                    }
                 }
             }
-
-            if file_len > QUICK_FINGERPRINT_BYTES as u64 {
-                let end_offset = file_len.saturating_sub(QUICK_FINGERPRINT_BYTES as u64);
-                file.seek(SeekFrom::Start(end_offset))?;
-                let bytes_read = file.read(&mut buffer)?;
-                if bytes_read > 0 {
-                    hasher.update(&buffer[..bytes_read]);
+            for (path, stamp) in chunk {
+                match self.perceptual_cache.get(path) {
+                    Some((cached, hash)) if cached == stamp => {
+                        result.analyzed_files += 1;
+                        hashes.push((path.clone(), stamp.0, *hash));
+                    }
+                    _ => result.skipped_files += 1,
                 }
             }
+            processed += chunk.len();
+            task::yield_now().await;
+        }
 
-            hasher.update(file_len.to_le_bytes());
-            Ok(format!("{:x}", hasher.finalize()))
-        })
-        .await
-        .map_err(|err| err.to_string())
-        .and_then(|res| res.map_err(|err| err.to_string()))?;
+        result.groups = similar_images::group_similar(hashes, MAX_SIMILAR_DISTANCE);
+        result
+            .groups
+            .sort_by_key(|group| std::cmp::Reverse(group.reclaimable));
+        Ok(result)
+    }
 
-        self.hash_cache.insert(path.to_path_buf(), hash.clone());
+    async fn calculate_file_signature(&mut self, path: &Path) -> Result<String, String> {
+        let stamp = async_fs::metadata(path)
+            .await
+            .map(|metadata| file_stamp(&metadata))
+            .map_err(|err| err.to_string())?;
+        if let Some((cached, hash)) = self.hash_cache.get(path) {
+            if *cached == stamp {
+                return Ok(hash.clone());
+            }
+        }
+
+        let path_clone = path.to_path_buf();
+        let algorithm = self.algorithm;
+        let hash = task::spawn_blocking(move || hashing::hash_file(algorithm, &path_clone))
+            .await
+            .map_err(|err| err.to_string())
+            .and_then(|res| res.map_err(|err| err.to_string()))?;
+
+        self.hash_cache
+            .insert(path.to_path_buf(), (stamp, hash.clone()));
         Ok(hash)
     }
 
@@ -313,15 +525,95 @@ impl DuplicateDetector {
             candidates.push((file.clone(), score));
         }
 
-        candidates.sort_by(|a, b| b.1.cmp(&a.1));
+        candidates.sort_by_key(|c| std::cmp::Reverse(c.1));
         candidates.first().map(|(path, _)| path.clone())
     }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DuplicateGroup {
+    /// Content hash; also identifies the group to `resolve_duplicates`.
     pub hash: String,
     pub files: Vec<PathBuf>,
     pub total_size: u64,
     pub recommended_to_keep: Option<PathBuf>,
+    /// What removing every copy but the one to keep frees; less than their
+    /// size where copies are APFS clones sharing blocks.
+    #[serde(default)]
+    pub reclaimable_size: u64,
+}
+
+/// Reclaimable size of all of `files` except `keep`, or except the first
+/// without one.
+fn reclaimable_size(files: &[PathBuf], keep: Option<&Path>) -> u64 {
+    let keep = keep.or_else(|| files.first().map(PathBuf::as_path));
+    files
+        .iter()
+        .filter(|file| Some(file.as_path()) != keep)
+        .map(|file| disk_usage::measure(file).reclaimable)
+        .sum()
+}
+
+/// Files of one size, and the size.
+type SizeBucket = (u64, Vec<PathBuf>);
+
+/// Files of `paths` sharing their size with another, bucketed by size with
+/// the largest first, and how many were skipped. Extra hard links to one
+/// file, empty files and anything but regular files are skipped.
+fn bucket_by_size(
+    paths: &[PathBuf],
+    token: &CancellationToken,
+) -> Result<(Vec<SizeBucket>, usize), String> {
+    let mut size_buckets: HashMap<u64, Vec<PathBuf>> = HashMap::new();
+    let mut inodes = HashSet::new();
+    let mut skipped = 0;
+    for path in paths {
+        if token.is_cancelled() {
+            return Err("cancelled".into());
+        }
+        match fs::metadata(path) {
+            // Another link to a file already listed: same data, nothing to free
+            #[cfg(unix)]
+            Ok(metadata)
+                if metadata.nlink() > 1 && !inodes.insert((metadata.dev(), metadata.ino())) =>
+            {
+                skipped += 1;
+            }
+            Ok(metadata) if metadata.is_file() && metadata.len() > 0 => {
+                size_buckets
+                    .entry(metadata.len())
+                    .or_default()
+                    .push(path.clone());
+            }
+            Ok(_) => skipped += 1,
+            Err(err) => {
+                skipped += 1;
+                log::debug!("Skipping duplicate candidate {}: {}", path.display(), err);
+            }
+        }
+    }
+    let mut buckets: Vec<_> = size_buckets
+        .into_iter()
+        .filter(|(_, files)| files.len() > 1)
+        .collect();
+    buckets.sort_by_key(|bucket| std::cmp::Reverse(bucket.0));
+    Ok((buckets, skipped))
+}
+
+/// Hash of the first and last [`QUICK_FINGERPRINT_BYTES`] of a `size`-byte
+/// file; for a file no bigger than both, the hash of all of it.
+fn partial_fingerprint(algorithm: HashAlgorithm, path: &Path, size: u64) -> io::Result<String> {
+    let mut file = fs::File::open(path)?;
+    let edge = QUICK_FINGERPRINT_BYTES as u64;
+    if size <= 2 * edge {
+        let mut data = Vec::with_capacity(size as usize);
+        file.read_to_end(&mut data)?;
+        return hashing::hash_bytes(algorithm, &data);
+    }
+    let mut data = vec![0u8; 2 * QUICK_FINGERPRINT_BYTES];
+    let (head, tail) = data.split_at_mut(QUICK_FINGERPRINT_BYTES);
+    file.read_exact(head)?;
+    file.seek(SeekFrom::Start(size - edge))?;
+    file.read_exact(tail)?;
+    hashing::hash_bytes(algorithm, &data)
 }
@@ -1,37 +1,137 @@
-#[cfg(feature = "parallel-scan")]
-use dashmap::DashMap;
-use rayon::prelude::*;
 use std::collections::{HashMap, HashSet};
 use std::env;
 use std::fs;
-#[cfg(not(feature = "parallel-scan"))]
-use std::io::ErrorKind;
 #[cfg(unix)]
 use std::os::unix::fs::MetadataExt;
 use std::path::{Path, PathBuf};
-#[cfg(feature = "parallel-scan")]
-use std::sync::Arc;
+use std::sync::Mutex;
+use std::time::Instant;
 use tokio::process::Command;
-use tokio::time::{sleep, Duration};
 
-use chrono::Local;
-#[cfg(not(feature = "parallel-scan"))]
-use chrono::{DateTime, Duration as ChronoDuration, Utc};
-use dirs;
-use walkdir::WalkDir;
-
-#[cfg(not(feature = "parallel-scan"))]
-use super::safety::{assess_path_risk, calculate_safety_score, RiskLevel};
+use super::age_thresholds::AgeThresholds;
+use super::protection_packs::ProtectionPacks;
 // Light build: metrics disabled to avoid unused code warnings.
 use super::cache::DIR_SIZE_CACHE;
-#[cfg(feature = "metadata-cache")]
+#[cfg(all(feature = "cache-refresh", feature = "app"))]
 use super::cache::FILE_METADATA_CACHE;
+use super::disk_usage::{self, DiskUsage};
+use super::exclusions::ExclusionMatcher;
+use super::incremental::invalidated_subtrees;
+use super::incremental::is_within;
+use super::incremental::ScanCache;
+use super::native_trash;
+use super::parallel_walk::{self, Found, Walk, WalkEvent, WalkStats};
+use super::path_alias::dedupe_key;
+use super::spotlight;
 use super::types::{
-    load_rules, load_rules_result, CategoryReport, CategoryRule, CleanableFile, CleanerRules,
-    CleaningReport,
+    load_age_thresholds, load_rules, load_rules_result, CategoryReport, CategoryRule,
+    CleanableFile, CleanerRules, CleaningReport, ScanProgress, TrashEmptyProgress,
+    TrashEmptyResult, TrashItem, TrashPurgeResult, TrashSummary, TrashVolume,
 };
+use super::volumes;
+use crate::ops::ThroughputTracker;
+#[cfg(target_os = "macos")]
+use crate::privileged_helper::{self, HelperCommand};
 use tokio_util::sync::CancellationToken;
 
+/// Number of Trash entries removed per blocking batch between progress reports.
+const TRASH_DELETE_BATCH: usize = 64;
+
+/// Baseline scan progress callback.
+pub type BaselineProgressFn<'a> = dyn Fn(ScanProgress) + Send + Sync + 'a;
+
+/// Running totals for one scan, reported as each rule path finishes.
+struct ScanTally<'a> {
+    progress: Option<&'a BaselineProgressFn<'a>>,
+    paths_total: usize,
+    started: Instant,
+    /// Paths done, entries seen, bytes found and the ETA tracker, updated
+    /// together so reports arrive in order.
+    totals: Mutex<(usize, u64, u64, ThroughputTracker)>,
+}
+
+impl<'a> ScanTally<'a> {
+    fn new(progress: Option<&'a BaselineProgressFn<'a>>, paths_total: usize) -> Self {
+        Self {
+            progress,
+            paths_total,
+            started: Instant::now(),
+            totals: Mutex::new((0, 0, 0, ThroughputTracker::default())),
+        }
+    }
+
+    fn path_done(&self, category: &str, path: &Path, walked: WalkStats) {
+        let mut totals = self
+            .totals
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        let (paths_done, entries_seen, bytes_found, tracker) = &mut *totals;
+        *paths_done += 1;
+        *entries_seen += walked.entries;
+        *bytes_found += walked.bytes;
+        let Some(cb) = self.progress else {
+            return;
+        };
+        // Paths vary wildly in size, so the ETA is rough early on.
+        let (eta_ms, _, mb_per_s) =
+            tracker.tick(*paths_done as u64, *bytes_found, self.paths_total as u64);
+        let seconds = self.started.elapsed().as_secs_f32();
+        cb(ScanProgress {
+            category: category.to_string(),
+            path: path.to_string_lossy().to_string(),
+            paths_done: *paths_done,
+            paths_total: self.paths_total,
+            entries_seen: *entries_seen,
+            bytes_found: *bytes_found,
+            eta_ms,
+            entries_per_s: (seconds > 0.0).then(|| *entries_seen as f32 / seconds),
+            mb_per_s,
+        });
+    }
+}
+
+/// `files` with the results of `category` replaced by `fresh`. A fresh item
+/// already listed, or inside a folder listed, under another category stays
+/// with that category, as the first match does in a full scan.
+pub(crate) fn replace_category_results(
+    files: Vec<CleanableFile>,
+    category: &str,
+    fresh: Vec<CleanableFile>,
+) -> Vec<CleanableFile> {
+    let kept = files
+        .into_iter()
+        .filter(|file| file.category != category)
+        .collect();
+    merge_unclaimed(kept, fresh)
+}
+
+/// `files` plus the items of `fresh` not already listed, or inside a folder
+/// listed, in `files` or earlier in `fresh`.
+pub(crate) fn merge_unclaimed(
+    files: Vec<CleanableFile>,
+    fresh: Vec<CleanableFile>,
+) -> Vec<CleanableFile> {
+    let mut merged = files;
+    let mut claimed: HashSet<String> = merged.iter().map(|file| dedupe_key(&file.path)).collect();
+    let mut claimed_dirs: Vec<String> = merged
+        .iter()
+        .filter(|file| Path::new(&file.path).is_dir())
+        .map(|file| dedupe_key(&file.path))
+        .collect();
+    for file in fresh {
+        let key = dedupe_key(&file.path);
+        if claimed.contains(&key) || claimed_dirs.iter().any(|dir| is_within(&key, dir)) {
+            continue;
+        }
+        if Path::new(&file.path).is_dir() {
+            claimed_dirs.push(key.clone());
+        }
+        claimed.insert(key);
+        merged.push(file);
+    }
+    merged
+}
+
 /// macOS file cleaner with conservative safety heuristics + user override.
 pub struct FileCleaner {
     cleanable_files: Vec<CleanableFile>,
@@ -40,6 +140,18 @@ pub struct FileCleaner {
     /// If a directory is added, we store its lowercased prefix (ending with '/')
     /// and skip any children to avoid double-counting.
     seen_dir_prefixes: Vec<String>,
+    /// The user's per-category age thresholds, refreshed at the start of each scan.
+    age_thresholds: AgeThresholds,
+    /// The user's enabled protection packs, refreshed with the age thresholds.
+    protection_packs: ProtectionPacks,
+    /// The last walk's results and the changes since, for `scan_incremental`.
+    scan_cache: Option<ScanCache>,
+}
+
+impl Default for FileCleaner {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl FileCleaner {
@@ -48,540 +160,345 @@ impl FileCleaner {
             cleanable_files: Vec::new(),
             seen_paths: HashSet::new(),
             seen_dir_prefixes: Vec::new(),
+            age_thresholds: AgeThresholds::default(),
+            protection_packs: ProtectionPacks::default(),
+            scan_cache: None,
         }
     }
 
-    // Standalone scan methods removed; use `scan_system_with_cancel` which
-    // supports both parallel and serial paths with cancellation.
+    pub(crate) fn age_thresholds(&self) -> &AgeThresholds {
+        &self.age_thresholds
+    }
+
+    pub(crate) fn protection_packs(&self) -> &ProtectionPacks {
+        &self.protection_packs
+    }
 
-    /// Cancellable scan wrapper
+    /// Cancellable scan. `progress` hears about each rule path as its walk
+    /// finishes.
     pub async fn scan_system_with_cancel(
         &mut self,
         cancel: &CancellationToken,
+        progress: Option<&BaselineProgressFn<'_>>,
     ) -> Result<CleaningReport, String> {
-        #[cfg(feature = "parallel-scan")]
-        {
-            if cancel.is_cancelled() {
-                return Err("cancelled".into());
-            }
-            // Run parallel scan but guard inner workers to early-out
-            self.cleanable_files.clear();
-            self.seen_paths.clear();
-            self.seen_dir_prefixes.clear();
-
-            let rules: CleanerRules = load_rules_result()?;
-
-            let found_files = Arc::new(DashMap::new());
-            let seen_paths = Arc::new(DashMap::new());
-            let seen_dir_prefixes = Arc::new(DashMap::new());
-
-            let categories: Vec<_> = rules.categories.into_iter().collect();
-            let (user_rules, system_rules): (Vec<_>, Vec<_>) =
-                categories.into_iter().partition(|r| {
-                    !r.paths
-                        .iter()
-                        .any(|p| p.starts_with("/System") || p.starts_with("/Library"))
-                });
-
-            let found_files_clone = found_files.clone();
-            let seen_paths_clone = seen_paths.clone();
-            let seen_dir_prefixes_clone = seen_dir_prefixes.clone();
-            let token = cancel.clone();
-            user_rules.par_iter().for_each(|rule| {
-                if token.is_cancelled() {
-                    return;
-                }
-                let paths: Vec<_> = rule
-                    .paths
-                    .iter()
-                    .filter_map(|p| Self::expand_path(p))
-                    .filter(|path| path.exists())
-                    .collect();
-                paths.par_iter().for_each(|path| {
-                    if token.is_cancelled() {
-                        return;
-                    }
-                    let _ = self.scan_path_parallel_with_cancel(
-                        path,
-                        rule,
-                        found_files_clone.clone(),
-                        seen_paths_clone.clone(),
-                        seen_dir_prefixes_clone.clone(),
-                        &token,
-                    );
-                });
-            });
-
-            let found_files_clone = found_files.clone();
-            let seen_paths_clone = seen_paths.clone();
-            let seen_dir_prefixes_clone = seen_dir_prefixes.clone();
-            let token = cancel.clone();
-            system_rules.par_iter().for_each(|rule| {
-                if token.is_cancelled() {
-                    return;
-                }
-                let paths: Vec<_> = rule
-                    .paths
-                    .iter()
-                    .filter_map(|p| Self::expand_path(p))
-                    .filter(|path| path.exists())
-                    .collect();
-                for path in paths {
-                    if token.is_cancelled() {
-                        break;
-                    }
-                    let _ = self.scan_path_parallel_with_cancel(
-                        &path,
-                        rule,
-                        found_files_clone.clone(),
-                        seen_paths_clone.clone(),
-                        seen_dir_prefixes_clone.clone(),
-                        &token,
-                    );
-                }
-            });
-
-            self.cleanable_files = Self::prune_parallel_results(&found_files, &seen_dir_prefixes);
-            self.seen_paths = self
-                .cleanable_files
-                .iter()
-                .map(|file| file.path.to_lowercase())
-                .collect();
-            self.seen_dir_prefixes = seen_dir_prefixes
-                .iter()
-                .map(|prefix| prefix.key().clone())
-                .collect();
-            if cancel.is_cancelled() {
-                return Err("cancelled".into());
-            }
-            Ok(self.generate_report())
-        }
-
-        #[cfg(not(feature = "parallel-scan"))]
-        {
-            if cancel.is_cancelled() {
-                return Err("cancelled".into());
-            }
-            self.cleanable_files.clear();
-            self.seen_paths.clear();
-            self.seen_dir_prefixes.clear();
-
-            let rules: CleanerRules = load_rules_result()?;
-            for rule in rules.categories.iter() {
-                if cancel.is_cancelled() {
-                    return Err("cancelled".into());
-                }
-                let paths_to_scan: Vec<_> = rule
-                    .paths
-                    .iter()
-                    .filter_map(|p| Self::expand_path(p))
-                    .filter(|path| path.exists())
-                    .collect();
-                if !paths_to_scan.is_empty() {
-                    for path in paths_to_scan {
-                        if cancel.is_cancelled() {
-                            return Err("cancelled".into());
-                        }
-                        if let Err(_) = self.scan_path_with_rule(&path, rule).await {
-                            continue;
-                        }
-                    }
-                    tokio::task::yield_now().await;
-                }
-            }
-            Ok(self.generate_report())
-        }
-    }
-
-    #[cfg(not(feature = "parallel-scan"))]
-    async fn scan_path_with_rule(
-        &mut self,
-        path: &Path,
-        rule: &CategoryRule,
-    ) -> Result<(), String> {
-        let mut found_files = Vec::new();
-        let mut local_seen_paths = HashSet::new();
-        let mut local_seen_dirs = Vec::new();
-
-        self.scan_path_internal(
-            path,
-            rule,
-            &mut found_files,
-            &mut local_seen_paths,
-            &mut local_seen_dirs,
-        )?;
-
-        // Merge results
-        for file in found_files {
-            self.cleanable_files.push(file);
-        }
-        for path in local_seen_paths {
-            self.seen_paths.insert(path);
+        if cancel.is_cancelled() {
+            return Err("cancelled".into());
         }
-        for dir in local_seen_dirs {
-            self.seen_dir_prefixes.push(dir);
+        self.cleanable_files.clear();
+        self.seen_paths.clear();
+        self.seen_dir_prefixes.clear();
+        self.age_thresholds = load_age_thresholds();
+        self.protection_packs = ProtectionPacks::load();
+
+        let rules: CleanerRules = load_rules_result()?;
+        let walks: Vec<Walk> = Self::rule_targets(rules)
+            .into_iter()
+            .flat_map(|(rule, paths)| {
+                paths.into_iter().map(move |root| Walk {
+                    rule: rule.clone(),
+                    root,
+                })
+            })
+            .collect();
+        let found = self.walk(walks, cancel, progress).await;
+        if cancel.is_cancelled() {
+            return Err("cancelled".into());
         }
 
-        Ok(())
+        let (files, dir_prefixes) = parallel_walk::merge(Vec::new(), Vec::new(), found);
+        self.cleanable_files = files;
+        self.seen_dir_prefixes = dir_prefixes;
+        self.seen_paths = self
+            .cleanable_files
+            .iter()
+            .map(|file| dedupe_key(&file.path))
+            .collect();
+        self.apply_exclusions(&ExclusionMatcher::load());
+        Ok(self.generate_report())
     }
 
-    #[cfg(feature = "parallel-scan")]
-    pub(crate) fn collect_rule_matches_for_path(
+    /// Everything `rule` matches under `path`, without touching the last
+    /// scan's results.
+    pub(crate) async fn collect_rule_matches_for_path(
         &self,
         path: &Path,
         rule: &CategoryRule,
         cancel: &CancellationToken,
     ) -> Result<Vec<CleanableFile>, String> {
-        let found_files = Arc::new(DashMap::new());
-        let seen_paths = Arc::new(DashMap::new());
-        let seen_dir_prefixes = Arc::new(DashMap::new());
-        self.scan_path_parallel_with_cancel(
-            path,
-            rule,
-            found_files.clone(),
-            seen_paths,
-            seen_dir_prefixes.clone(),
-            cancel,
-        )?;
-        let results = Self::prune_parallel_results(&found_files, &seen_dir_prefixes);
-        Ok(results)
-    }
-
-    #[cfg(not(feature = "parallel-scan"))]
-    pub(crate) fn collect_rule_matches_for_path(
-        &self,
-        path: &Path,
-        rule: &CategoryRule,
-        _cancel: &CancellationToken,
-    ) -> Result<Vec<CleanableFile>, String> {
-        let mut found_files = Vec::new();
-        let mut local_seen_paths = HashSet::new();
-        let mut local_seen_dirs = Vec::new();
-
-        self.scan_path_internal(
-            path,
-            rule,
-            &mut found_files,
-            &mut local_seen_paths,
-            &mut local_seen_dirs,
-        )?;
-
-        Ok(found_files)
+        let walk = Walk {
+            rule: rule.clone(),
+            root: path.to_path_buf(),
+        };
+        let found = self.walk(vec![walk], cancel, None).await;
+        Ok(parallel_walk::merge(Vec::new(), Vec::new(), found).0)
     }
 
-    // The remaining parallel implementation is the cancellable variant below.
-
-    #[cfg(feature = "parallel-scan")]
-    fn scan_path_parallel_with_cancel(
+    /// Run `walks` on the scan threads, or through Spotlight where it can
+    /// answer for them, and gather each one's matches, in the order of
+    /// `walks`.
+    async fn walk(
         &self,
-        path: &Path,
-        rule: &CategoryRule,
-        found_files: Arc<DashMap<String, CleanableFile>>,
-        seen_paths: Arc<DashMap<String, bool>>,
-        seen_dir_prefixes: Arc<DashMap<String, bool>>,
+        walks: Vec<Walk>,
         cancel: &CancellationToken,
-    ) -> Result<(), String> {
-        if !path.exists() {
-            return Ok(());
-        }
-        let token = cancel.clone();
-        WalkDir::new(path)
-            .max_depth(rule.max_depth.unwrap_or(10))
-            .into_iter()
-            .par_bridge()
-            .filter_map(|e| e.ok())
-            .for_each(|entry| {
-                if token.is_cancelled() {
-                    return;
-                }
-                let file_path = entry.path();
-                let path_str = file_path.to_string_lossy().to_string();
-                let path_lower = path_str.to_lowercase();
-                if seen_paths.contains_key(&path_lower) {
-                    return;
-                }
-                if Self::has_seen_parent_prefix(&path_lower, &seen_dir_prefixes) {
-                    return;
-                }
-                if let Some(cleanable) = self.process_entry(&entry, rule) {
-                    if entry.file_type().is_dir() {
-                        let mut dir_prefix = path_lower.clone();
-                        if !dir_prefix.ends_with('/') {
-                            dir_prefix.push('/');
-                        }
-                        seen_dir_prefixes.insert(dir_prefix, true);
-                    }
-                    found_files.insert(path_str.clone(), cleanable);
-                    seen_paths.insert(path_lower, true);
+        progress: Option<&BaselineProgressFn<'_>>,
+    ) -> Vec<Vec<Found>> {
+        let tally = ScanTally::new(progress, walks.len());
+        let mut found: Vec<Vec<Found>> = walks.iter().map(|_| Vec::new()).collect();
+        // Walks Spotlight could not answer for, and their index in `walks`.
+        let mut walked: Vec<(usize, Walk)> = Vec::new();
+        for (index, walk) in walks.into_iter().enumerate() {
+            if cancel.is_cancelled() {
+                return found;
+            }
+            match spotlight::discover(self.walker(), &walk, cancel).await {
+                Some((matches, stats)) => {
+                    found[index] = matches;
+                    tally.path_done(&walk.rule.name, &walk.root, stats);
                 }
-            });
-        Ok(())
-    }
+                None => walked.push((index, walk)),
+            }
+        }
 
-    #[cfg(feature = "parallel-scan")]
-    fn has_seen_parent_prefix(path_lower: &str, seen_dir_prefixes: &DashMap<String, bool>) -> bool {
-        seen_dir_prefixes
+        let (indices, walks): (Vec<usize>, Vec<Walk>) = walked.into_iter().unzip();
+        let labels: Vec<(String, PathBuf)> = walks
             .iter()
-            .any(|prefix| path_lower.starts_with(prefix.key()))
+            .map(|walk| (walk.rule.name.clone(), walk.root.clone()))
+            .collect();
+        let mut events = parallel_walk::spawn(self.walker(), walks, cancel.clone());
+        while let Some(event) = events.recv().await {
+            match event {
+                WalkEvent::Found(walk, matches) => found[indices[walk]].extend(matches),
+                WalkEvent::Done(walk, stats) => {
+                    let (category, root) = &labels[walk];
+                    tally.path_done(category, root, stats);
+                }
+            }
+        }
+        found
     }
 
-    #[cfg(feature = "parallel-scan")]
-    fn prune_parallel_results(
-        found_files: &DashMap<String, CleanableFile>,
-        seen_dir_prefixes: &DashMap<String, bool>,
-    ) -> Vec<CleanableFile> {
-        found_files
-            .iter()
-            .filter_map(|entry| {
-                let path_lower = entry.key().to_lowercase();
-                if Self::has_seen_parent_prefix(&path_lower, seen_dir_prefixes) {
-                    return None;
-                }
-                Some(entry.value().clone())
-            })
-            .collect()
+    /// A cleaner for the walkers, with their own copy of the thresholds and
+    /// packs, as they outlive this borrow of `self` if the scan is dropped.
+    fn walker(&self) -> FileCleaner {
+        FileCleaner {
+            age_thresholds: self.age_thresholds.clone(),
+            protection_packs: self.protection_packs.clone(),
+            ..FileCleaner::new()
+        }
     }
 
-    #[cfg(not(feature = "parallel-scan"))]
-    fn scan_path_internal(
-        &self,
-        path: &Path,
-        rule: &CategoryRule,
-        found_files: &mut Vec<CleanableFile>,
-        local_seen_paths: &mut HashSet<String>,
-        local_seen_dirs: &mut Vec<String>,
-    ) -> Result<(), String> {
-        if !path.exists() {
-            return Ok(());
+    /// Scan like `scan_system_with_cancel`, but once a scan has run only
+    /// rewalk the parts of each root FSEvents reported as changed and reuse
+    /// the previous results everywhere else.
+    pub async fn scan_incremental(
+        &mut self,
+        cancel: &CancellationToken,
+        progress: Option<&BaselineProgressFn<'_>>,
+    ) -> Result<CleaningReport, String> {
+        if cancel.is_cancelled() {
+            return Err("cancelled".into());
         }
+        self.cleanable_files.clear();
+        self.seen_paths.clear();
+        self.seen_dir_prefixes.clear();
+        self.age_thresholds = load_age_thresholds();
+        self.protection_packs = ProtectionPacks::load();
+
+        let rules: CleanerRules = load_rules_result()?;
+        let fingerprint = ScanCache::fingerprint(&(&rules, &self.protection_packs));
+        let targets = Self::rule_targets(rules);
+        let mut roots: Vec<PathBuf> = targets
+            .iter()
+            .flat_map(|(_, roots)| roots.iter().cloned())
+            .collect();
+        roots.sort();
+        roots.dedup();
 
-        let now = Utc::now();
-        let min_age = rule.min_age_days;
-        let min_size_bytes_from_rule = rule.min_size_kb.map(|kb| kb * 1024);
+        let mut kept_files = Vec::new();
+        let mut kept_dirs = Vec::new();
+        let mut walks: Vec<Walk> = Vec::new();
 
-        let excludes = rule
-            .excludes
+        // Taken, not borrowed: a cancelled scan leaves no cache, since the
+        // changes it consumed are gone.
+        let previous = self.scan_cache.take();
+        let changes = previous
             .as_ref()
-            .map(|v| v.iter().map(|s| s.to_lowercase()).collect::<Vec<_>>())
-            .unwrap_or_default();
-        let exts = rule
-            .extensions
-            .as_ref()
-            .map(|v| v.iter().map(|s| s.to_lowercase()).collect::<Vec<_>>());
-        let require_subpaths = rule
-            .require_subpaths
-            .as_ref()
-            .map(|v| v.iter().map(|s| s.to_lowercase()).collect::<Vec<_>>())
-            .unwrap_or_default();
-
-        // Build the walker - limit depth for better performance
-        let walker = WalkDir::new(path);
-        let iter = if let Some(d) = rule.max_depth {
-            walker.max_depth(d).into_iter()
-        } else {
-            walker.max_depth(10).into_iter() // Default max depth for performance
-        };
-
-        // Process entries in parallel batches for better performance
-        let entries: Vec<_> = iter.filter_map(|e| e.ok()).collect();
-
-        // Use chunks to process in batches
-        for chunk in entries.chunks(100) {
-            for entry in chunk {
-                let file_type = entry.file_type();
-                let file_path = entry.path();
-
-                // Skip symlinks to avoid unintended deletions
-                if let Ok(md) = fs::symlink_metadata(file_path) {
-                    if md.file_type().is_symlink() {
-                        continue;
-                    }
-                }
-
-                // Determine whether this is a file or directory match
-                let is_dir_based_rule = rule.name.to_lowercase().contains("folder")
-                    || rule.name.to_lowercase().contains("cache")
-                    || rule.name.to_lowercase().contains("container");
-
-                let consider_as_result = if is_dir_based_rule && file_type.is_dir() {
-                    true
-                } else if !is_dir_based_rule && file_type.is_file() {
-                    true
-                } else {
-                    false
-                };
-
-                if !consider_as_result {
-                    continue;
-                }
-
-                let path_str = file_path.to_string_lossy();
-                let key = path_str.to_string();
-                let path_lower = path_str.to_lowercase();
-
-                // Skip if already seen
-                if self.seen_paths.contains(&path_lower) || local_seen_paths.contains(&path_lower) {
-                    continue;
-                }
-
-                // Check if this path is a child of any seen directory prefix
-                if self
-                    .seen_dir_prefixes
+            .and_then(|cache| cache.changes(fingerprint, &roots));
+        let mut cache = match (previous, changes) {
+            (Some(mut cache), Some(changed)) => {
+                let cached_keys: HashSet<String> = cache
+                    .files
                     .iter()
-                    .any(|prefix| path_lower.starts_with(prefix))
-                    || local_seen_dirs
-                        .iter()
-                        .any(|prefix| path_lower.starts_with(prefix))
-                {
-                    continue;
-                }
-
-                // Apply exclude filters
-                if excludes.iter().any(|ex| path_lower.contains(ex)) {
-                    continue;
-                }
-
-                // Apply subpath requirements
-                if !require_subpaths.is_empty() {
-                    if !require_subpaths.iter().any(|req| path_lower.contains(req)) {
-                        continue;
-                    }
-                }
-
-                // Metadata checks
-                let metadata = match fs::metadata(file_path) {
-                    Ok(m) => m,
-                    Err(e) => {
-                        if e.kind() == ErrorKind::NotFound {
-                            continue;
-                        }
-                        continue;
-                    }
-                };
-
-                if file_type.is_dir() {
-                    // Directory processing
-                    let mut dir_prefix = path_lower.clone();
-                    if !dir_prefix.ends_with('/') {
-                        dir_prefix.push('/');
-                    }
-                    local_seen_dirs.push(dir_prefix);
-
-                    // Age filter
-                    if let Some(days) = min_age {
-                        if let Ok(m) = metadata.modified() {
-                            if now.signed_duration_since(DateTime::<Utc>::from(m))
-                                < ChronoDuration::days(days)
-                            {
-                                continue;
+                    .map(|file| dedupe_key(&file.path))
+                    .collect();
+                let mut stale: Vec<String> = Vec::new();
+                for (rule, rule_roots) in &targets {
+                    for root in rule_roots {
+                        let root_matched =
+                            cached_keys.contains(&dedupe_key(&root.to_string_lossy()));
+                        for (key, path) in invalidated_subtrees(root, &changed, root_matched) {
+                            if let Some(path) = path {
+                                // Sizes below a change are stale even where
+                                // the directory's own mtime is not.
+                                DIR_SIZE_CACHE.invalidate(&path).await;
+                                walks.push(Walk {
+                                    rule: Self::rule_for_subtree(rule, root, &path),
+                                    root: path,
+                                });
                             }
+                            stale.push(key);
                         }
                     }
-
-                    let dir_size = self.get_directory_size_blocking(file_path).unwrap_or(0);
-                    let min_size = min_size_bytes_from_rule.unwrap_or(0);
-                    if dir_size < min_size {
-                        continue;
+                }
+                for file in std::mem::take(&mut cache.files) {
+                    let key = dedupe_key(&file.path);
+                    if !stale.iter().any(|prefix| is_within(&key, prefix)) {
+                        kept_files.push(file);
                     }
-
-                    let risk = assess_path_risk(file_path);
-                    let path_is_safe = matches!(risk.level, RiskLevel::Safe);
-                    let is_safe = rule.safe && path_is_safe;
-                    let (safety_score, mut auto_select) =
-                        calculate_safety_score(file_path, &rule.name, &risk, rule.min_age_days);
-                    auto_select = auto_select && rule.safe && path_is_safe;
-
-                    let last_modified = metadata
-                        .modified()
-                        .map(|t| DateTime::<Utc>::from(t).timestamp())
-                        .unwrap_or(0);
-
-                    let cleanable = CleanableFile {
-                        path: key.clone(),
-                        size: dir_size,
-                        category: rule.name.clone(),
-                        description: self.get_file_description(file_path, &rule.name),
-                        last_modified,
-                        safe_to_delete: is_safe,
-                        safety_score,
-                        auto_select,
-                    };
-
-                    found_files.push(cleanable);
-                    local_seen_paths.insert(path_lower);
-                } else {
-                    // File processing
-                    if let Some(ref allowed_exts) = exts {
-                        if let Some(ext) = file_path
-                            .extension()
-                            .and_then(|e| e.to_str())
-                            .map(|s| s.to_lowercase())
-                        {
-                            if !allowed_exts.iter().any(|e| e == &ext) {
-                                continue;
-                            }
-                        } else {
-                            continue;
-                        }
+                }
+                for prefix in std::mem::take(&mut cache.dir_prefixes) {
+                    let dir = prefix.trim_end_matches('/');
+                    if !stale.iter().any(|stale| is_within(dir, stale)) {
+                        kept_dirs.push(prefix);
                     }
-
-                    // Age filter
-                    if let Some(days) = min_age {
-                        let relevant_time = (|| {
-                            let name_lower = rule.name.to_lowercase();
-                            if name_lower.contains("downloads") || name_lower.contains("desktop") {
-                                if let Ok(created) = metadata.created() {
-                                    return Some(DateTime::<Utc>::from(created));
-                                }
-                            }
-                            metadata.modified().ok().map(|t| DateTime::<Utc>::from(t))
-                        })();
-
-                        if let Some(file_time) = relevant_time {
-                            if now.signed_duration_since(file_time) < ChronoDuration::days(days) {
-                                continue;
-                            }
-                        }
+                }
+                cache
+            }
+            _ => {
+                // Watch before walking, so changes made during the walk are
+                // picked up by the next scan.
+                let cache = ScanCache::start(fingerprint, roots);
+                for (rule, rule_roots) in &targets {
+                    for root in rule_roots {
+                        walks.push(Walk {
+                            rule: rule.clone(),
+                            root: root.clone(),
+                        });
                     }
+                }
+                cache
+            }
+        };
 
-                    // Size filter
-                    let file_size_logical = metadata.len();
-                    let file_size = Self::metadata_size_bytes(&metadata);
-                    let min_size = min_size_bytes_from_rule.unwrap_or(0);
-                    if file_size_logical < min_size {
-                        continue;
-                    }
+        let found = self.walk(walks, cancel, progress).await;
+        if cancel.is_cancelled() {
+            return Err("cancelled".into());
+        }
 
-                    let last_modified = metadata
-                        .modified()
-                        .map(|t| DateTime::<Utc>::from(t).timestamp())
-                        .unwrap_or(0);
-
-                    let risk = assess_path_risk(file_path);
-                    let path_is_safe = matches!(risk.level, RiskLevel::Safe);
-                    let is_safe = rule.safe && path_is_safe;
-                    let (safety_score, mut auto_select) =
-                        calculate_safety_score(file_path, &rule.name, &risk, rule.min_age_days);
-                    auto_select = auto_select && rule.safe && path_is_safe;
-
-                    let cleanable = CleanableFile {
-                        path: key.clone(),
-                        size: file_size,
-                        category: rule.name.clone(),
-                        description: self.get_file_description(file_path, &rule.name),
-                        last_modified,
-                        safe_to_delete: is_safe,
-                        safety_score,
-                        auto_select,
-                    };
+        let (files, dir_prefixes) = parallel_walk::merge(kept_files, kept_dirs, found);
+        self.cleanable_files = files;
+        self.seen_dir_prefixes = dir_prefixes;
+        cache.files = self.cleanable_files.clone();
+        cache.dir_prefixes = self.seen_dir_prefixes.clone();
+        self.scan_cache = Some(cache);
+        self.seen_paths = self
+            .cleanable_files
+            .iter()
+            .map(|file| dedupe_key(&file.path))
+            .collect();
+        self.apply_exclusions(&ExclusionMatcher::load());
+        Ok(self.generate_report())
+    }
 
-                    found_files.push(cleanable);
-                    local_seen_paths.insert(path_lower);
-                }
+    /// Rescan the rule paths of one category and swap its results into the
+    /// last scan's, leaving the other categories as they were. Returns the
+    /// category's new results.
+    pub async fn scan_category(
+        &mut self,
+        category: &str,
+        cancel: &CancellationToken,
+    ) -> Result<Vec<CleanableFile>, String> {
+        if cancel.is_cancelled() {
+            return Err("cancelled".into());
+        }
+        let rules: CleanerRules = load_rules_result()?;
+        let Some(rule) = rules
+            .categories
+            .into_iter()
+            .find(|rule| rule.name == category)
+        else {
+            return Err(format!("Unknown category: {}", category));
+        };
+        self.age_thresholds = load_age_thresholds();
+        self.protection_packs = ProtectionPacks::load();
+
+        // Items of other categories stay claimed while walking, as in a full scan.
+        self.seen_paths = self
+            .cleanable_files
+            .iter()
+            .filter(|file| file.category != category)
+            .map(|file| dedupe_key(&file.path))
+            .collect();
+        let mut found = Vec::new();
+        for path in rule
+            .paths
+            .iter()
+            .filter_map(|p| Self::expand_path(p))
+            .filter(|path| path.exists())
+        {
+            if cancel.is_cancelled() {
+                break;
+            }
+            match self
+                .collect_rule_matches_for_path(&path, &rule, cancel)
+                .await
+            {
+                Ok(matches) => found.extend(matches),
+                Err(err) => log::warn!("Category scan error for {}: {}", path.display(), err),
             }
+            tokio::task::yield_now().await;
         }
+        // A cancelled walk leaves the previous results in place.
+        if !cancel.is_cancelled() {
+            let previous = std::mem::take(&mut self.cleanable_files);
+            self.cleanable_files = replace_category_results(previous, category, found);
+        }
+        self.seen_paths = self
+            .cleanable_files
+            .iter()
+            .map(|file| dedupe_key(&file.path))
+            .collect();
+        let seen_paths = &self.seen_paths;
+        self.seen_dir_prefixes
+            .retain(|prefix| seen_paths.contains(prefix.trim_end_matches('/')));
+        if cancel.is_cancelled() {
+            return Err("cancelled".into());
+        }
+        self.apply_exclusions(&ExclusionMatcher::load());
+        Ok(self
+            .cleanable_files
+            .iter()
+            .filter(|file| file.category == category)
+            .cloned()
+            .collect())
+    }
 
-        Ok(())
+    /// Each rule with its paths that exist on this Mac.
+    fn rule_targets(rules: CleanerRules) -> Vec<(CategoryRule, Vec<PathBuf>)> {
+        rules
+            .categories
+            .into_iter()
+            .map(|rule| {
+                let paths = rule
+                    .paths
+                    .iter()
+                    .filter_map(|p| Self::expand_path(p))
+                    .filter(|path| path.exists())
+                    .collect();
+                (rule, paths)
+            })
+            .collect()
+    }
+
+    /// `rule` for walking `subtree` of `root`, with the depth left below it.
+    fn rule_for_subtree(rule: &CategoryRule, root: &Path, subtree: &Path) -> CategoryRule {
+        let depth = subtree
+            .strip_prefix(root)
+            .map(|rest| rest.components().count())
+            .unwrap_or(0);
+        let mut rule = rule.clone();
+        rule.max_depth = Some(rule.max_depth.unwrap_or(10).saturating_sub(depth));
+        rule
     }
 
     pub(crate) fn get_file_description(&self, path: &Path, category: &str) -> String {
@@ -622,13 +539,25 @@ impl FileCleaner {
         }
     }
 
+    /// Drop results the user asked never to be suggested again.
+    fn apply_exclusions(&mut self, exclusions: &ExclusionMatcher) {
+        if exclusions.is_empty() {
+            return;
+        }
+        self.cleanable_files
+            .retain(|file| !exclusions.is_excluded(&file.path, &file.category));
+        self.seen_paths = self
+            .cleanable_files
+            .iter()
+            .map(|file| dedupe_key(&file.path))
+            .collect();
+    }
+
     fn generate_report(&self) -> CleaningReport {
         let mut categories: std::collections::HashMap<String, (u64, usize)> =
             std::collections::HashMap::new();
-        let mut total_size = 0u64;
 
         for file in &self.cleanable_files {
-            total_size += file.size;
             let entry = categories.entry(file.category.clone()).or_insert((0, 0));
             entry.0 += file.size;
             entry.1 += 1;
@@ -636,7 +565,12 @@ impl FileCleaner {
 
         let category_reports: Vec<CategoryReport> = categories
             .into_iter()
-            .map(|(name, (size, count))| CategoryReport { name, size, count })
+            .map(|(name, (size, count))| CategoryReport {
+                label: self.age_thresholds.label(&name),
+                name,
+                size,
+                count,
+            })
             .collect();
 
         // Load advanced categories from rules for UI toggling
@@ -648,14 +582,26 @@ impl FileCleaner {
             .map(|r| r.name)
             .collect();
 
+        let disk_usage = disk_usage::usage_of_results(
+            self.cleanable_files
+                .iter()
+                .map(|file| (file.path.as_str(), file.size)),
+        );
+
         CleaningReport {
-            total_size,
+            total_size: disk_usage.allocated,
             files_count: self.cleanable_files.len(),
             categories: category_reports,
             advanced_categories: advanced,
+            disk_usage,
         }
     }
 
+    /// Summary of the items the last scan found.
+    pub fn report(&self) -> CleaningReport {
+        self.generate_report()
+    }
+
     pub fn get_cleanable_files(&self) -> &Vec<CleanableFile> {
         &self.cleanable_files
     }
@@ -757,7 +703,9 @@ impl FileCleaner {
         // On macOS, retry permission-denied items once using a single admin prompt
         #[cfg(target_os = "macos")]
         if !pending_elevated.is_empty() {
-            match Self::remove_with_admin(
+            // Some items may be gone even when others failed, so check each
+            // one either way.
+            if let Err(e) = Self::remove_with_admin(
                 &pending_elevated
                     .iter()
                     .map(|p| p.path.as_str())
@@ -765,42 +713,37 @@ impl FileCleaner {
             )
             .await
             {
-                Ok(_) => {
-                    // Verify and account freed sizes
-                    for p in pending_elevated.iter() {
-                        let path = Path::new(&p.path);
-                        if !path.exists() {
-                            total_freed += p.size;
-                            items_removed += 1;
-                            Self::invalidate_scan_caches(path).await;
-                        } else {
-                            // Fallback check: try a final direct removal if elevation succeeded partially
-                            let _ = if p.is_dir {
-                                fs::remove_dir_all(path)
-                            } else {
-                                fs::remove_file(path)
-                            };
-                            if !path.exists() {
-                                total_freed += p.size;
-                                items_removed += 1;
-                                Self::invalidate_scan_caches(path).await;
-                            } else {
-                                errors.push(format!(
-                                    "Failed to remove {} even with admin rights",
-                                    p.path
-                                ));
-                            }
-                        }
+                errors.push(format!("Admin removal failed: {}", e));
+            }
+            for p in pending_elevated.iter() {
+                let path = Path::new(&p.path);
+                if !path.exists() {
+                    total_freed += p.size;
+                    items_removed += 1;
+                    Self::invalidate_scan_caches(path).await;
+                } else {
+                    // Fallback check: try a final direct removal if elevation succeeded partially
+                    let _ = if p.is_dir {
+                        fs::remove_dir_all(path)
+                    } else {
+                        fs::remove_file(path)
+                    };
+                    if !path.exists() {
+                        total_freed += p.size;
+                        items_removed += 1;
+                        Self::invalidate_scan_caches(path).await;
+                    } else {
+                        errors.push(format!(
+                            "Failed to remove {} even with admin rights",
+                            p.path
+                        ));
                     }
                 }
-                Err(e) => {
-                    errors.push(format!("Admin removal failed: {}", e));
-                }
             }
         }
 
-        if !errors.is_empty() {
-            eprintln!("Cleaning errors: {:?}", errors);
+        for error in &errors {
+            log::warn!("{}", error);
         }
 
         (total_freed, items_removed)
@@ -814,20 +757,50 @@ impl FileCleaner {
 
         // Restrict to user's home directory for safety
         let home = dirs::home_dir().ok_or_else(|| "Could not find home directory".to_string())?;
-        let home_str = home.to_string_lossy().to_string();
+        let home = home.canonicalize().unwrap_or(home);
+        let paths: Vec<String> = paths
+            .iter()
+            .filter_map(|p| admin_removable_path(p, &home))
+            .collect();
+
+        // The installed helper removes them without a prompt and reports
+        // each one; the caller checks what is left either way.
+        let commands: Vec<HelperCommand> = paths
+            .iter()
+            .map(|p| HelperCommand::Remove { path: p.clone() })
+            .collect();
+        if let Some(outcomes) =
+            privileged_helper::send(&commands, &CancellationToken::new()).await?
+        {
+            let failures: Vec<String> = paths
+                .iter()
+                .zip(outcomes)
+                .filter_map(|(path, outcome)| {
+                    (!outcome.ok).then(|| {
+                        format!(
+                            "{}: {}",
+                            path,
+                            outcome.error.unwrap_or_else(|| "failed".to_string())
+                        )
+                    })
+                })
+                .collect();
+            return if failures.is_empty() {
+                Ok(())
+            } else {
+                Err(failures.join("; "))
+            };
+        }
 
         // Build AppleScript that constructs a single shell script and runs it once with admin rights.
         // Use 'quoted form of POSIX path' for robust escaping.
         let mut script = String::from("set cmd to \"\"\n");
-        for p in paths {
-            // Only include paths within the user's home directory
-            if p.starts_with(&home_str) {
-                let escaped = p.replace("\\", "\\\\").replace("\"", "\\\"");
-                script.push_str(&format!(
-                    "set cmd to cmd & \"rm -rf \" & quoted form of POSIX path of \"{}\" & \"\n\"\n",
-                    escaped
-                ));
-            }
+        for p in &paths {
+            let escaped = p.replace("\\", "\\\\").replace("\"", "\\\"");
+            script.push_str(&format!(
+                "set cmd to cmd & \"rm -rf \" & quoted form of POSIX path of \"{}\" & \"\n\"\n",
+                escaped
+            ));
         }
 
         // If nothing eligible, bail out
@@ -855,158 +828,341 @@ impl FileCleaner {
         }
     }
 
-    /// Move a file or directory to the macOS Trash.
-    /// 1) Ask Finder (osascript) — handles per-volume trash & name collisions.
-    /// 2) Fallback: rename into ~/.Trash with a unique name.
-    async fn move_to_trash(&self, path: &Path) -> Result<(), String> {
-        // Try Finder first
-        if !is_osascript_disabled() {
-            let path_str = path.to_string_lossy();
-            let escaped = path_str.replace("\\", "\\\\").replace("\"", "\\\"");
-            let script = format!(
-                "tell application \"Finder\" to move POSIX file \"{}\" to trash",
-                escaped
-            );
-
-            match Command::new("osascript")
-                .arg("-e")
-                .arg(&script)
-                .output()
-                .await
-            {
-                Ok(output) if output.status.success() => {
-                    return Ok(());
-                }
-                Ok(output) => {
-                    log::warn!(
-                        "Finder trash command failed (status {:?}): {}",
-                        output.status.code(),
-                        String::from_utf8_lossy(&output.stderr)
-                    );
-                }
-                Err(err) => {
-                    log::warn!("Failed to execute AppleScript for trash move: {}", err);
-                }
-            }
-        }
+    /// Move a file or directory to the Trash of its volume.
+    pub(crate) async fn move_to_trash(&self, path: &Path) -> Result<(), String> {
+        native_trash::move_to_trash(path).await.map(|_| ())
+    }
 
-        // Fallback: rename into ~/.Trash with a unique name
-        let home = dirs::home_dir().ok_or_else(|| "Could not find home directory".to_string())?;
-        let trash = home.join(".Trash");
-        if !trash.exists() {
-            fs::create_dir_all(&trash)
-                .map_err(|e| format!("Failed to create trash directory: {}", e))?;
-        }
+    /// Empty Trash, returning `(freed_bytes, removed_items)`.
+    pub async fn empty_trash(&self) -> Result<(u64, usize), String> {
+        let result = self
+            .empty_trash_batched(&CancellationToken::new(), None)
+            .await?;
+        Ok((result.freed, result.removed))
+    }
 
-        let original_name = path
-            .file_name()
-            .ok_or_else(|| "Invalid filename".to_string())?;
-        let mut target = trash.join(original_name);
-
-        // Ensure unique filename
-        if target.exists() {
-            let stem = original_name.to_string_lossy().to_string();
-            let (base, ext) = split_name_ext(&stem);
-            let ts = Local::now().format("%Y%m%d-%H%M%S").to_string();
-            let mut counter = 1u32;
-            loop {
-                let candidate = if ext.is_empty() {
-                    format!("{} ({}-{})", base, ts, counter)
-                } else {
-                    format!("{} ({}-{}).{}", base, ts, counter, ext)
-                };
-                target = trash.join(candidate);
-                if !target.exists() {
-                    break;
-                }
-                counter += 1;
-            }
-        }
+    /// List the top-level Trash entries with their on-disk sizes.
+    pub async fn enumerate_trash(&self) -> Result<Vec<TrashItem>, String> {
+        let trash_dir = Self::trash_dir()?;
+        tokio::task::spawn_blocking(move || Self::read_trash_items(&trash_dir))
+            .await
+            .map_err(|e| format!("Trash enumeration task failed: {}", e))?
+    }
 
-        fs::rename(path, &target).map_err(|e| format!("Failed to move to trash: {}", e))?;
-        Ok(())
+    /// Size and item count of the Trash on every mounted volume.
+    pub async fn trash_summary(&self) -> Result<TrashSummary, String> {
+        let locations = Self::trash_locations()?;
+        tokio::task::spawn_blocking(move || {
+            let volumes: Vec<TrashVolume> = locations
+                .into_iter()
+                .map(|(volume, path)| {
+                    let items = Self::read_trash_items(&path)?;
+                    Ok(TrashVolume {
+                        volume,
+                        path: path.to_string_lossy().to_string(),
+                        size: items.iter().map(|item| item.size).sum(),
+                        items: items.len(),
+                    })
+                })
+                .collect::<Result<_, String>>()?;
+            Ok(TrashSummary {
+                total_size: volumes.iter().map(|volume| volume.size).sum(),
+                total_items: volumes.iter().map(|volume| volume.items).sum(),
+                volumes,
+            })
+        })
+        .await
+        .map_err(|e| format!("Trash enumeration task failed: {}", e))?
     }
 
-    /// Empty Trash using Finder (preferred), with safe fallbacks.
-    pub async fn empty_trash(&self) -> Result<(u64, usize), String> {
-        // Get initial trash size and count
-        let home = dirs::home_dir().ok_or_else(|| "Could not find home directory".to_string())?;
-        let trash_dir = home.join(".Trash");
+    /// Empty the home Trash; see [`Self::empty_trash_volumes`].
+    pub async fn empty_trash_batched(
+        &self,
+        cancel: &CancellationToken,
+        progress: Option<&(dyn Fn(TrashEmptyProgress) + Send + Sync)>,
+    ) -> Result<TrashEmptyResult, String> {
+        self.empty_trash_volumes(&[], cancel, progress).await
+    }
 
-        if !trash_dir.exists() {
-            return Ok((0, 0));
+    /// Empty the home Trash and the Trash of each volume in `volumes` (mount
+    /// points). Each Trash is enumerated first, then deleted in batches,
+    /// reporting progress after each batch. Cancellation stops between items
+    /// and returns the partial result with `canceled` set instead of an error.
+    /// A Trash that cannot be listed (no Full Disk Access) is emptied through
+    /// Finder instead; if that is not possible either, the listing error is
+    /// returned.
+    pub async fn empty_trash_volumes(
+        &self,
+        volumes: &[String],
+        cancel: &CancellationToken,
+        progress: Option<&(dyn Fn(TrashEmptyProgress) + Send + Sync)>,
+    ) -> Result<TrashEmptyResult, String> {
+        let locations = Self::trash_locations()?;
+        if let Some(missing) = volumes
+            .iter()
+            .find(|volume| !locations.iter().any(|(mount, _)| mount == *volume))
+        {
+            return Err(format!("No Trash found on {}", missing));
         }
+        let locations: Vec<(String, PathBuf)> = locations
+            .into_iter()
+            .enumerate()
+            .filter(|(index, (mount, path))| {
+                (*index == 0 || volumes.contains(mount)) && path.exists()
+            })
+            .map(|(_, location)| location)
+            .collect();
 
-        let size_before = self.get_directory_size_async(&trash_dir).await.unwrap_or(0);
-        let count_before = fs::read_dir(&trash_dir)
-            .map(|entries| entries.count())
-            .unwrap_or(0);
-
-        // First attempt: Use AppleScript to empty trash properly through Finder
-        let mut emptied_via_finder = false;
-        if !is_osascript_disabled() {
-            match Command::new("osascript")
-                .arg("-e")
-                .arg("tell application \"Finder\" to empty trash")
-                .output()
-                .await
-            {
-                Ok(output) if output.status.success() => {
-                    emptied_via_finder = true;
+        let enumerated = locations.clone();
+        let trashes = tokio::task::spawn_blocking(move || {
+            enumerated
+                .into_iter()
+                .map(|(volume, path)| Self::read_trash_items(&path).map(|items| (volume, items)))
+                .collect::<Result<Vec<(String, Vec<TrashItem>)>, String>>()
+        })
+        .await
+        .map_err(|e| format!("Trash enumeration task failed: {}", e))?;
+        let trashes = match trashes {
+            Ok(trashes) => trashes,
+            Err(err) if cancel.is_cancelled() => return Err(err),
+            Err(err) => {
+                log::warn!("{}; asking Finder to empty the Trash", err);
+                Self::empty_trash_with_finder()
+                    .await
+                    .map_err(|finder_err| format!("{} ({})", err, finder_err))?;
+                for (_, trash_dir) in &locations {
+                    Self::invalidate_scan_caches(trash_dir).await;
                 }
-                Ok(output) => {
-                    log::warn!(
-                        "Finder empty trash failed (status {:?}): {}",
-                        output.status.code(),
-                        String::from_utf8_lossy(&output.stderr)
-                    );
+                return Ok(TrashEmptyResult {
+                    emptied_by_finder: true,
+                    volumes: locations.into_iter().map(|(volume, _)| volume).collect(),
+                    ..TrashEmptyResult::default()
+                });
+            }
+        };
+        let mut result = TrashEmptyResult {
+            total_items: trashes.iter().map(|(_, items)| items.len()).sum(),
+            total_bytes: trashes
+                .iter()
+                .flat_map(|(_, items)| items)
+                .map(|item| item.size)
+                .sum(),
+            volumes: trashes.iter().map(|(volume, _)| volume.clone()).collect(),
+            ..TrashEmptyResult::default()
+        };
+        let mut failed_items: Vec<TrashItem> = Vec::new();
+        let mut tracker = ThroughputTracker::default();
+
+        let batches: Vec<(String, Vec<TrashItem>)> = trashes
+            .into_iter()
+            .flat_map(|(volume, items)| {
+                items
+                    .chunks(TRASH_DELETE_BATCH)
+                    .map(|batch| (volume.clone(), batch.to_vec()))
+                    .collect::<Vec<_>>()
+            })
+            .collect();
+        for (volume, batch_items) in batches {
+            if cancel.is_cancelled() {
+                result.canceled = true;
+                break;
+            }
+
+            let batch_cancel = cancel.clone();
+            let outcomes = tokio::task::spawn_blocking(move || {
+                let mut outcomes = Vec::with_capacity(batch_items.len());
+                for item in batch_items {
+                    if batch_cancel.is_cancelled() {
+                        break;
+                    }
+                    let removed = Self::remove_trash_item(&item);
+                    outcomes.push((item, removed));
                 }
-                Err(err) => {
-                    log::warn!("Failed to execute Finder empty trash AppleScript: {}", err);
+                outcomes
+            })
+            .await
+            .map_err(|e| format!("Trash deletion task failed: {}", e))?;
+
+            for (item, removed) in outcomes {
+                if removed {
+                    result.removed += 1;
+                    result.freed = result.freed.saturating_add(item.size);
+                } else {
+                    result.failed += 1;
+                    failed_items.push(item);
                 }
             }
+
+            if let Some(cb) = progress {
+                let items_done = result.removed + result.failed;
+                let (eta_ms, items_per_s, mb_per_s) =
+                    tracker.tick(items_done as u64, result.freed, result.total_items as u64);
+                cb(TrashEmptyProgress {
+                    volume,
+                    items_done,
+                    items_total: result.total_items,
+                    bytes_done: result.freed,
+                    bytes_total: result.total_bytes,
+                    eta_ms,
+                    items_per_s,
+                    mb_per_s,
+                });
+            }
+        }
+        if cancel.is_cancelled() && result.removed + result.failed < result.total_items {
+            result.canceled = true;
         }
 
-        if !emptied_via_finder {
-            // If AppleScript fails, try removing contents manually
-            if let Ok(entries) = fs::read_dir(&trash_dir) {
-                for entry in entries.flatten() {
-                    let path = entry.path();
-                    if path.is_dir() {
-                        let _ = fs::remove_dir_all(&path);
-                    } else {
-                        let _ = fs::remove_file(&path);
+        // Items we could not remove directly (locked or owned by another user)
+        // get one more chance through Finder, which handles those cases.
+        if !result.canceled && !failed_items.is_empty() && !is_osascript_disabled() {
+            match Self::empty_trash_with_finder().await {
+                Ok(()) => {
+                    for item in failed_items
+                        .iter()
+                        .filter(|item| fs::symlink_metadata(&item.path).is_err())
+                    {
+                        result.failed -= 1;
+                        result.removed += 1;
+                        result.freed = result.freed.saturating_add(item.size);
                     }
                 }
+                Err(err) => log::warn!("{}", err),
             }
+        }
 
-            // If still items remain, try shell command to clear trash contents
-            if let Some(trash_str) = trash_dir.to_str() {
-                let trash_contents = format!("{}/{}", trash_str, "*");
-                let _ = Command::new("sh")
-                    .arg("-c")
-                    .arg(format!("rm -rf {}", trash_contents))
-                    .output()
-                    .await;
-            }
+        // Ensure cached directory sizes reflect the latest state
+        for (_, trash_dir) in &locations {
+            Self::invalidate_scan_caches(trash_dir).await;
         }
 
-        // Wait a moment for operations to complete
-        sleep(Duration::from_millis(500)).await;
+        Ok(result)
+    }
+
+    /// Ask Finder to empty the Trash, which it can do for items the app
+    /// cannot list or remove itself.
+    async fn empty_trash_with_finder() -> Result<(), String> {
+        if is_osascript_disabled() {
+            return Err("Finder fallback is disabled".into());
+        }
+        let output = Command::new("osascript")
+            .arg("-e")
+            .arg("tell application \"Finder\" to empty trash")
+            .output()
+            .await
+            .map_err(|e| format!("Failed to execute Finder empty trash AppleScript: {}", e))?;
+        if output.status.success() {
+            Ok(())
+        } else {
+            Err(format!(
+                "Finder empty trash failed (status {:?}): {}",
+                output.status.code(),
+                String::from_utf8_lossy(&output.stderr)
+            ))
+        }
+    }
 
-        // Ensure cached directory sizes reflect the latest state before measuring again
+    /// Permanently delete the Trash entries moved there before `cutoff`
+    /// (unix seconds), leaving newer ones in place.
+    pub async fn purge_trash_older_than(
+        &self,
+        cutoff: i64,
+        cancel: &CancellationToken,
+    ) -> Result<TrashPurgeResult, String> {
+        let trash_dir = Self::trash_dir()?;
+        let purge_dir = trash_dir.clone();
+        let token = cancel.clone();
+        let result =
+            tokio::task::spawn_blocking(move || Self::purge_trash_dir(&purge_dir, cutoff, &token))
+                .await
+                .map_err(|e| format!("Trash purge task failed: {}", e))??;
         Self::invalidate_scan_caches(&trash_dir).await;
+        Ok(result)
+    }
 
-        // Calculate freed space
-        let size_after = self.get_directory_size_async(&trash_dir).await.unwrap_or(0);
-        let count_after = fs::read_dir(&trash_dir)
-            .map(|entries| entries.count())
-            .unwrap_or(0);
+    pub(crate) fn purge_trash_dir(
+        trash_dir: &Path,
+        cutoff: i64,
+        cancel: &CancellationToken,
+    ) -> Result<TrashPurgeResult, String> {
+        let (expired, kept): (Vec<TrashItem>, Vec<TrashItem>) = Self::read_trash_items(trash_dir)?
+            .into_iter()
+            .partition(|item| item.trashed_at < cutoff);
+        let mut result = TrashPurgeResult {
+            kept: kept.len(),
+            ..TrashPurgeResult::default()
+        };
+        for item in expired {
+            if cancel.is_cancelled() {
+                result.canceled = true;
+                break;
+            }
+            if Self::remove_trash_item(&item) {
+                result.freed = result.freed.saturating_add(item.size);
+                result.purged.push(item);
+            } else {
+                result.failed += 1;
+            }
+        }
+        Ok(result)
+    }
 
-        let freed = size_before.saturating_sub(size_after);
-        let removed = count_before.saturating_sub(count_after);
+    fn trash_dir() -> Result<PathBuf, String> {
+        let home = dirs::home_dir().ok_or_else(|| "Could not find home directory".to_string())?;
+        Ok(home.join(".Trash"))
+    }
 
-        Ok((freed, removed))
+    /// Mount point and Trash directory of every volume, the home Trash first.
+    fn trash_locations() -> Result<Vec<(String, PathBuf)>, String> {
+        let mount_points = volumes::list_volumes().into_iter().map(|volume| volume.id);
+        let uid = unsafe { libc::getuid() };
+        Ok(volumes::trash_locations(
+            mount_points,
+            &Self::trash_dir()?,
+            uid,
+        ))
+    }
+
+    fn read_trash_items(trash_dir: &Path) -> Result<Vec<TrashItem>, String> {
+        let mut items: Vec<TrashItem> = fs::read_dir(trash_dir)
+            .map_err(|e| format!("Failed to read {}: {}", trash_dir.display(), e))?
+            .flatten()
+            .filter_map(|entry| {
+                let path = entry.path();
+                let metadata = fs::symlink_metadata(&path).ok()?;
+                let is_dir = metadata.is_dir();
+                let size = if is_dir {
+                    Self::walk_directory_size(&path)
+                } else {
+                    Self::metadata_size_bytes(&metadata)
+                };
+                Some(TrashItem {
+                    path: path.to_string_lossy().to_string(),
+                    name: entry.file_name().to_string_lossy().to_string(),
+                    size,
+                    is_dir,
+                    trashed_at: metadata.ctime(),
+                })
+            })
+            .collect();
+        items.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(items)
+    }
+
+    fn remove_trash_item(item: &TrashItem) -> bool {
+        let path = Path::new(&item.path);
+        let outcome = if item.is_dir {
+            fs::remove_dir_all(path)
+        } else {
+            fs::remove_file(path)
+        };
+        match outcome {
+            Ok(()) => true,
+            Err(err) => {
+                log::debug!("Failed to remove trash item {}: {}", item.path, err);
+                false
+            }
+        }
     }
 
     // Cancellable wrappers for cleaning and trash
@@ -1024,10 +1180,7 @@ impl FileCleaner {
                 .parent()
                 .unwrap_or_else(|| Path::new("/"))
                 .to_path_buf();
-            files_by_dir
-                .entry(parent)
-                .or_insert_with(Vec::new)
-                .push(p.clone());
+            files_by_dir.entry(parent).or_default().push(p.clone());
         }
         for (dir, files) in files_by_dir.into_iter() {
             if cancel.is_cancelled() {
@@ -1047,60 +1200,52 @@ impl FileCleaner {
         if cancel.is_cancelled() {
             return Err("cancelled".into());
         }
-        let (freed, removed) = self.empty_trash().await?;
-        if cancel.is_cancelled() {
+        let result = self.empty_trash_batched(cancel, None).await?;
+        if result.canceled {
             return Err("cancelled".into());
         }
-        Ok((freed, removed))
+        Ok((result.freed, result.removed))
     }
 
     pub async fn get_directory_size_async(&self, path: &Path) -> Result<u64, String> {
         DIR_SIZE_CACHE
-            .get_or_calculate(path, |p| {
-                let mut total = 0u64;
-                for entry in WalkDir::new(p).into_iter().filter_map(|e| e.ok()) {
-                    if entry.file_type().is_file() {
-                        if let Ok(md) = fs::metadata(entry.path()) {
-                            total = total.saturating_add(Self::metadata_size_bytes(&md));
-                        }
-                    }
-                }
-                Ok(total)
-            })
+            .get_or_measure(path, |dir| Ok(disk_usage::measure(dir)))
             .await
+            .map(|usage| usage.allocated)
     }
 
     pub async fn get_path_size_async(&self, path: &Path) -> Result<u64, String> {
+        self.get_path_usage_async(path)
+            .await
+            .map(|usage| usage.allocated)
+    }
+
+    /// Apparent, allocated and reclaimable size of a file or directory.
+    pub async fn get_path_usage_async(&self, path: &Path) -> Result<DiskUsage, String> {
         let metadata = fs::metadata(path).map_err(|e| format!("Failed to get metadata: {}", e))?;
         if metadata.is_dir() {
-            self.get_directory_size_async(path).await
+            DIR_SIZE_CACHE
+                .get_or_measure(path, |dir| Ok(disk_usage::measure(dir)))
+                .await
         } else {
-            Ok(Self::metadata_size_bytes(&metadata))
+            Ok(disk_usage::measure(path))
         }
     }
 
     pub(crate) fn get_directory_size_blocking(&self, path: &Path) -> Result<u64, String> {
         // Synchronous calculation without touching the async runtime (safe in rayon/scan contexts)
-        let mut total = 0u64;
-        for entry in WalkDir::new(path).into_iter().filter_map(|e| e.ok()) {
-            if entry.file_type().is_file() {
-                if let Ok(md) = fs::metadata(entry.path()) {
-                    total = total.saturating_add(Self::metadata_size_bytes(&md));
-                }
-            }
-        }
-        Ok(total)
+        DIR_SIZE_CACHE
+            .get_or_measure_blocking(path, |dir| Ok(disk_usage::measure(dir)))
+            .map(|usage| usage.allocated)
+    }
+
+    /// Allocated size of the tree at `path`, hard links counted once.
+    pub(crate) fn walk_directory_size(path: &Path) -> u64 {
+        disk_usage::measure(path).allocated
     }
 
     pub(crate) fn metadata_size_bytes(metadata: &fs::Metadata) -> u64 {
-        #[cfg(unix)]
-        {
-            let blocks = metadata.blocks();
-            if blocks > 0 {
-                return blocks.saturating_mul(512);
-            }
-        }
-        metadata.len()
+        disk_usage::allocated_bytes(metadata)
     }
 
     pub fn get_auto_selectable_files(&self) -> Vec<CleanableFile> {
@@ -1120,9 +1265,9 @@ impl FileCleaner {
     }
 
     fn expand_path(input: &str) -> Option<PathBuf> {
-        if input.starts_with("~/") {
+        if let Some(rest) = input.strip_prefix("~/") {
             if let Some(home) = dirs::home_dir() {
-                return Some(home.join(&input[2..]));
+                return Some(home.join(rest));
             } else {
                 return None;
             }
@@ -1134,7 +1279,7 @@ impl FileCleaner {
         let mut current = Some(path.to_path_buf());
         while let Some(target) = current {
             DIR_SIZE_CACHE.invalidate(&target).await;
-            #[cfg(feature = "metadata-cache")]
+            #[cfg(all(feature = "cache-refresh", feature = "app"))]
             FILE_METADATA_CACHE.invalidate(&target).await;
 
             current = target.parent().map(|p| p.to_path_buf());
@@ -1142,17 +1287,7 @@ impl FileCleaner {
     }
 }
 
-/// Utility: split a name into (base, ext) without touching the filesystem.
-fn split_name_ext(name: &str) -> (String, String) {
-    if let Some(idx) = name.rfind('.') {
-        let (base, ext) = name.split_at(idx);
-        (base.to_string(), ext.trim_start_matches('.').to_string())
-    } else {
-        (name.to_string(), String::new())
-    }
-}
-
-fn is_osascript_disabled() -> bool {
+pub(crate) fn is_osascript_disabled() -> bool {
     env::var("MACOS_OPTIMIZER_DISABLE_OSA")
         .map(|value| {
             let lowercase = value.trim().to_ascii_lowercase();
@@ -1160,3 +1295,15 @@ fn is_osascript_disabled() -> bool {
         })
         .unwrap_or(false)
 }
+
+/// `path` with its folders resolved, or `None` unless it then lies inside
+/// `home` (resolved by the caller). The last component is kept as is, so a
+/// symlink is removed rather than what it points to.
+#[cfg(any(target_os = "macos", test))]
+pub(crate) fn admin_removable_path(path: &str, home: &Path) -> Option<String> {
+    let path = Path::new(path);
+    let parent = path.parent()?.canonicalize().ok()?;
+    let resolved = parent.join(path.file_name()?).to_string_lossy().to_string();
+    crate::privileged_helper::removable_path(&resolved, home).ok()?;
+    Some(resolved)
+}
@@ -1,15 +1,10 @@
 // src/file_cleaner/engine_utils.rs
 
-#[cfg(feature = "parallel-scan")]
-use super::safety::{assess_path_risk, calculate_safety_score, RiskLevel};
-#[cfg(feature = "parallel-scan")]
+use super::safety::{assess_path_risk_with, calculate_safety_score, RiskLevel};
 use super::types::{CategoryRule, CleanableFile};
-#[cfg(feature = "parallel-scan")]
 use chrono::{DateTime, Duration as ChronoDuration, Utc};
-#[cfg(feature = "parallel-scan")]
 use walkdir::DirEntry;
 
-#[cfg(feature = "parallel-scan")]
 impl super::engine::FileCleaner {
     pub(crate) fn process_entry(
         &self,
@@ -50,10 +45,7 @@ impl super::engine::FileCleaner {
         let min_age = rule.min_age_days;
         let min_size_bytes = rule.min_size_kb.map(|kb| kb * 1024);
 
-        let rule_name_lower = rule.name.to_lowercase();
-        let is_dir_based_rule = rule_name_lower.contains("folder")
-            || rule_name_lower.contains("cache")
-            || rule_name_lower.contains("container");
+        let is_dir_based_rule = rule.matches_folders();
 
         // Enforce directory/file expectation based on rule intent
         if metadata.is_dir() {
@@ -81,12 +73,11 @@ impl super::engine::FileCleaner {
 
         // Age filter
         if let Some(days) = min_age {
-            let relevant_time =
-                if rule_name_lower.contains("downloads") || rule_name_lower.contains("desktop") {
-                    metadata.created().ok().map(|t| DateTime::<Utc>::from(t))
-                } else {
-                    metadata.modified().ok().map(|t| DateTime::<Utc>::from(t))
-                };
+            let relevant_time = if rule.ages_by_creation() {
+                metadata.created().ok().map(DateTime::<Utc>::from)
+            } else {
+                metadata.modified().ok().map(DateTime::<Utc>::from)
+            };
 
             if let Some(file_time) = relevant_time {
                 if now.signed_duration_since(file_time) < ChronoDuration::days(days) {
@@ -118,7 +109,7 @@ impl super::engine::FileCleaner {
             .map(|t| DateTime::<Utc>::from(t).timestamp())
             .unwrap_or(0);
 
-        let risk = assess_path_risk(file_path);
+        let risk = assess_path_risk_with(file_path, self.age_thresholds(), self.protection_packs());
         let path_is_safe = matches!(risk.level, RiskLevel::Safe);
         let is_safe = rule.safe && path_is_safe;
         let (safety_score, mut auto_select) =
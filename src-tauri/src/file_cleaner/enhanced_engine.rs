@@ -1,34 +1,81 @@
-use std::collections::{HashMap, HashSet};
-use std::env;
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::fs;
 use std::path::{Path, PathBuf};
-use std::time::{Duration, Instant};
+use std::time::{Duration, Instant, SystemTime};
 
-use chrono::Local;
+use chrono::Utc;
 use serde::{Deserialize, Serialize};
 
 use super::advanced_safety::{
-    RiskFactor, SafetyAnalyzer, SafetyFlags, SafetyMetrics, SafetyRecommendation,
+    RiskFactor, SafetyAnalyzer, SafetyFlags, SafetyLayer, SafetyMetrics, SafetyRecommendation,
 };
-use super::auto_selection::{AutoSelectScore, AutoSelectionEngine, UserAction};
-use super::duplicate_detector::{DuplicateDetector, DuplicateGroup};
-use super::engine::FileCleaner;
+use super::app_quit::heavy_app;
+use super::app_uninstaller::AppFootprint;
+use super::auto_selection::{AutoSelectScore, AutoSelectionEngine, UserAction, UserPatternLearner};
+use super::custom_path::{self, CustomScanOptions};
+use super::developer_junk::{self, DeveloperEnvironment, DeveloperJunkReport};
+use super::disk_usage;
+use super::duplicate_detector::{
+    self, DuplicateDetector, DuplicateGroup, DuplicateScanResult, KeepStrategy, DUPLICATE_CATEGORY,
+    FOLDER_DUPLICATE_TIME_BUDGET,
+};
+use super::engine::{merge_unclaimed, BaselineProgressFn, FileCleaner};
 use super::enhanced_rules::DynamicRuleEngine;
+use super::exclusions::ExclusionMatcher;
+use super::external_volumes::{self, ExternalVolumeStore};
+use super::hashing::{self, HashAlgorithm};
+use super::large_files::{self, LargeFile, LargeFileQuery, LargeFileScan};
+use super::learned_exceptions::{
+    ExceptionDecision, LearnedException, LearnedExceptionStore, LearnedShapes,
+};
 use super::macos_integration::{
     BackupStatus, CloudStatus, FileAssociation, MacOSIntegration, SpotlightInfo,
 };
-use super::process_snapshot::ProcessSnapshot;
-use super::safety::policy_for_category;
+use super::native_cleanup::{self, CleanupAction, NativeCleanup};
+use super::native_trash;
+use super::path_alias::dedupe_key;
+use super::process_snapshot::{self, ProcessSnapshot};
+use super::project_artifacts::{self, ProjectArtifactQuery, ProjectArtifactReport};
+use super::protection_packs::ProtectionPacks;
+use super::quarantine::{
+    QuarantineEntry, QuarantinePurgeResult, QuarantineRestoreResult, QuarantineSettings,
+    QuarantineStore,
+};
+use super::quick_clean::{self, QuickCleanResult};
+use super::recommendations::{self, RecommendationInputs, StorageRecommendation};
+use super::report_delta::{self, ReportDelta, ReportHistory};
+use super::safety::{assess_path_risk_with, calculate_safety_score, RiskAssessment};
+use super::safety_policies::{SafetyPolicies, SafetyThresholds};
+use super::similar_images::{self, SimilarImageGroup};
 use super::smart_cache::{CacheValidation, SmartCacheDetector};
-use super::telemetry::{SafetyMetricsCollector, TelemetrySnapshot};
-use super::types::{CategoryReport, CleanableFile, CleanerRules, CleaningReport};
+use super::stale_locks::{self, STALE_LOCK_CATEGORY};
+use super::system_load::{
+    plan_heavy_work, HeavyWorkPlan, SystemLoad, DEFERRED_SAFETY_BATCH_BUDGET,
+    SAFETY_ANALYSIS_BUDGET,
+};
+use super::telemetry::{AnalysisStage, SafetyMetricsCollector, ScanTelemetry, TelemetrySnapshot};
+use super::trash_restore::{self, TrashRestoreItem, TrashRestoreResult};
+use super::types::{
+    load_age_thresholds, load_rules, load_rules_result, CategoryReport, CategoryRule,
+    CleanableFile, CleanerRules, CleaningReport, ScanProgress,
+};
 use super::validation::{
-    BlockReason, FileValidationState, PreDeletionValidator, RecoveryManager, ValidationResult,
+    BlockReason, FileValidationState, PreDeletionValidator, RecoveryManager, UndoResult,
+    ValidationResult,
 };
+use super::volumes::{self, VolumeInfo, VolumeResolver, VolumeScope, VolumeSummary};
+use crate::capabilities::{self, Tool};
 use crate::ops::ThroughputTracker;
 use dirs;
 use tokio_util::sync::CancellationToken;
 
+/// Scan progress callback: `(percent, message, stage)`.
+pub type ScanProgressFn = dyn Fn(f32, &str, &str) + Send + Sync;
+
+/// Most files one batch of deferred safety analysis takes on, which bounds
+/// the `lsof` run in front of it.
+const DEFERRED_SAFETY_BATCH_FILES: usize = 200;
+
 #[derive(Clone, Debug)]
 pub struct EnhancedDeletionProgress {
     pub progress: f32,
@@ -51,10 +98,39 @@ pub struct EnhancedFileCleaner {
     cache_detector: SmartCacheDetector,
     validator: PreDeletionValidator,
     recovery_manager: RecoveryManager,
+    /// Takes cleaned items in place of the Trash while enabled.
+    quarantine: QuarantineStore,
     auto_selector: AutoSelectionEngine,
     macos_integration: MacOSIntegration,
     duplicate_detector: DuplicateDetector,
+    /// Groups from the latest system or folder scan, for `resolve_duplicates`.
+    duplicate_groups: Vec<DuplicateGroup>,
+    /// Similar-image groups from the same scan, also resolvable by id.
+    similar_groups: Vec<SimilarImageGroup>,
+    /// When the scan that found the groups started; images modified since
+    /// are not staged for removal.
+    groups_scanned_at: Option<SystemTime>,
     telemetry: SafetyMetricsCollector,
+    system_load: Option<SystemLoad>,
+    exclusions: ExclusionMatcher,
+    /// User-tuned safety thresholds, reloaded at the start of each scan.
+    safety_policies: SafetyPolicies,
+    /// Loaded on first use; the accepted shapes are compiled into `learned_shapes`.
+    learned_exceptions: Option<LearnedExceptionStore>,
+    learned_shapes: LearnedShapes,
+    volume_scope: VolumeScope,
+    /// Mount table read lazily and refreshed at the start of each scan.
+    volume_resolver: Option<VolumeResolver>,
+    /// Categories of the current results that are never auto-selected, such
+    /// as rules a folder scan re-rooted.
+    manual_categories: HashSet<String>,
+    report_history: ReportHistory,
+}
+
+impl Default for EnhancedFileCleaner {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl EnhancedFileCleaner {
@@ -68,14 +144,55 @@ impl EnhancedFileCleaner {
             safety_analyzer: SafetyAnalyzer::new(),
             cache_detector: SmartCacheDetector::new(),
             validator: PreDeletionValidator::new(),
-            recovery_manager: RecoveryManager::new(),
-            auto_selector: AutoSelectionEngine::new(),
+            recovery_manager: RecoveryManager::load(),
+            quarantine: QuarantineStore::load(),
+            auto_selector: AutoSelectionEngine::with_learner(UserPatternLearner::load()),
             macos_integration: MacOSIntegration::new(),
             duplicate_detector: DuplicateDetector::new(),
+            duplicate_groups: Vec::new(),
+            similar_groups: Vec::new(),
+            groups_scanned_at: None,
             telemetry: SafetyMetricsCollector::new(),
+            system_load: None,
+            exclusions: ExclusionMatcher::default(),
+            safety_policies: SafetyPolicies::default(),
+            learned_exceptions: None,
+            learned_shapes: LearnedShapes::default(),
+            volume_scope: VolumeScope::default(),
+            volume_resolver: None,
+            manual_categories: HashSet::new(),
+            report_history: ReportHistory::default(),
         }
     }
 
+    /// Record the current system load so the next scan can defer or shrink
+    /// duplicate hashing and deep safety analysis when the machine is busy.
+    pub fn set_system_load(&mut self, load: Option<SystemLoad>) {
+        self.system_load = load;
+    }
+
+    /// Proposed and reviewed learned exceptions, after applying `decisions`.
+    pub fn review_learned_exceptions(
+        &mut self,
+        decisions: &[ExceptionDecision],
+    ) -> Result<Vec<LearnedException>, String> {
+        let store = self
+            .learned_exceptions
+            .get_or_insert_with(LearnedExceptionStore::load);
+        store.review(decisions)?;
+        self.learned_shapes = store.matcher(dirs::home_dir().as_deref());
+        Ok(store.list())
+    }
+
+    /// Limit system scans to the given volumes; takes effect on the next scan.
+    pub fn set_volume_scope(&mut self, scope: VolumeScope) {
+        self.volume_scope = scope;
+    }
+
+    pub fn volume_scope(&self) -> VolumeScope {
+        self.volume_scope.clone()
+    }
+
     /// Prepare deletion by filtering currently scanned files with provided paths.
     pub async fn prepare_deletion_by_paths(
         &mut self,
@@ -91,14 +208,17 @@ impl EnhancedFileCleaner {
     }
 
     fn add_enhanced_placeholder(&mut self, base: CleanableFile) {
-        let path_lower = base.path.to_lowercase();
-        if self.seen_paths.contains(&path_lower) {
+        if self.exclusions.is_excluded(&base.path, &base.category) {
+            return;
+        }
+        let key = dedupe_key(&base.path);
+        if self.seen_paths.contains(&key) {
             return;
         }
 
         if let Ok(metadata) = fs::metadata(&base.path) {
             if metadata.is_dir() {
-                let mut prefix = path_lower.clone();
+                let mut prefix = key.clone();
                 if !prefix.ends_with('/') {
                     prefix.push('/');
                 }
@@ -106,9 +226,25 @@ impl EnhancedFileCleaner {
             }
         }
 
-        self.seen_paths.insert(path_lower);
-
+        self.seen_paths.insert(key);
+
+        let volume = self
+            .volume_resolver
+            .get_or_insert_with(VolumeResolver::mounted)
+            .volume_of(&base.path);
+        let cleanup_action = dirs::home_dir()
+            .map(|home| {
+                native_cleanup::cleanup_action_for(
+                    &base.path,
+                    &base.category,
+                    &home,
+                    capabilities::tool_available,
+                )
+            })
+            .unwrap_or_default();
         let enhanced = EnhancedCleanableFile {
+            id: report_delta::stable_id(&base.path),
+            volume,
             base,
             safety_metrics: SafetyMetrics {
                 base_score: 0,
@@ -121,6 +257,7 @@ impl EnhancedFileCleaner {
             auto_select_score: AutoSelectScore::new(),
             macos_status: None,
             validation_state: None,
+            cleanup_action,
         };
 
         self.cleanable_files.push(enhanced);
@@ -130,28 +267,437 @@ impl EnhancedFileCleaner {
     pub async fn scan_system_enhanced_with_cancel(
         &mut self,
         token: &CancellationToken,
-        progress: Option<&(dyn Fn(f32, &str, &str) + Send + Sync)>,
+        progress: Option<&ScanProgressFn>,
+    ) -> Result<EnhancedCleaningReport, String> {
+        self.scan_enhanced(token, progress, false).await
+    }
+
+    /// Enhanced scan whose baseline walk only revisits what changed since
+    /// the previous one; see [`FileCleaner::scan_incremental`].
+    pub async fn scan_system_enhanced_incremental(
+        &mut self,
+        token: &CancellationToken,
+        progress: Option<&ScanProgressFn>,
+    ) -> Result<EnhancedCleaningReport, String> {
+        self.scan_enhanced(token, progress, true).await
+    }
+
+    /// Rescan one category and merge its results into the last scan's, so
+    /// toggling a category does not need a full rescan. Other categories,
+    /// and the duplicate groups of the last full scan, are left as they were.
+    pub async fn scan_category(
+        &mut self,
+        category: &str,
+        token: &CancellationToken,
+        progress: Option<&ScanProgressFn>,
+    ) -> Result<EnhancedCleaningReport, String> {
+        self.exclusions = ExclusionMatcher::load();
+        self.safety_policies = SafetyPolicies::load();
+        let learned_exceptions = LearnedExceptionStore::load();
+        self.learned_shapes = learned_exceptions.matcher(dirs::home_dir().as_deref());
+        self.learned_exceptions = Some(learned_exceptions);
+        self.volume_resolver = Some(VolumeResolver::mounted());
+        if let Some(cb) = progress {
+            cb(10.0, &format!("Scanning {}", category), "discovery");
+        }
+        let process_snapshot = ProcessSnapshot::capture().await;
+
+        let is_builtin = load_rules_result()?
+            .categories
+            .iter()
+            .any(|rule| rule.name == category);
+        let fresh = if is_builtin {
+            self.base_cleaner.scan_category(category, token).await?
+        } else {
+            let rule = self
+                .dynamic_rules(&process_snapshot)
+                .into_iter()
+                .find(|rule| rule.name == category)
+                .ok_or_else(|| format!("Unknown category: {}", category))?;
+            let mut matches = Vec::new();
+            for path in rule
+                .paths
+                .iter()
+                .filter_map(|p| Self::expand_path(p))
+                .filter(|path| path.exists())
+            {
+                if token.is_cancelled() {
+                    return Err("cancelled".into());
+                }
+                match self
+                    .base_cleaner
+                    .collect_rule_matches_for_path(&path, &rule, token)
+                    .await
+                {
+                    Ok(found) => matches.extend(found),
+                    Err(err) => {
+                        log::warn!("Dynamic rule scan error for {}: {}", path.display(), err);
+                    }
+                }
+                tokio::task::yield_now().await;
+            }
+            matches
+        };
+        if token.is_cancelled() {
+            return Err("cancelled".into());
+        }
+
+        self.safety_analyzer.reset_content_budget();
+        let previous = self.cleanable_files.clone();
+        self.cleanable_files
+            .retain(|file| file.base.category != category);
+        self.seen_paths = self
+            .cleanable_files
+            .iter()
+            .map(|file| dedupe_key(&file.base.path))
+            .collect();
+        let seen_paths = &self.seen_paths;
+        self.seen_dir_prefixes
+            .retain(|prefix| seen_paths.contains(prefix.trim_end_matches('/')));
+        let kept = self.cleanable_files.len();
+        for file in fresh {
+            self.add_enhanced_placeholder(file);
+        }
+        let mut added = self.cleanable_files.split_off(kept);
+        if !self.volume_scope.is_unrestricted() {
+            let scope = &self.volume_scope;
+            added.retain(|file| scope.allows(&file.volume));
+        }
+
+        if let Some(cb) = progress {
+            cb(40.0, "Analyzing safety", "safety");
+        }
+        let total_files = added.len();
+        let processed = match self
+            .analyze_files(
+                &mut added,
+                token,
+                &process_snapshot,
+                SAFETY_ANALYSIS_BUDGET,
+                |processed| {
+                    if let Some(cb) = progress {
+                        if processed.is_multiple_of(75) || processed == total_files {
+                            let fraction = processed as f32 / total_files as f32;
+                            cb(
+                                40.0 + fraction * 50.0,
+                                &format!(
+                                    "Analyzing safety ({} / {} files)",
+                                    processed, total_files
+                                ),
+                                "safety",
+                            );
+                        }
+                    }
+                },
+            )
+            .await
+        {
+            Ok(processed) => processed,
+            Err(err) => {
+                self.cleanable_files = previous;
+                self.seen_paths = self
+                    .cleanable_files
+                    .iter()
+                    .map(|file| dedupe_key(&file.base.path))
+                    .collect();
+                return Err(err);
+            }
+        };
+        for file in added.iter_mut().skip(processed) {
+            Self::apply_deferred_safety(file, &self.safety_policies);
+        }
+
+        if let Some(cb) = progress {
+            cb(95.0, "Scoring and summarizing", "scoring");
+        }
+        self.cleanable_files.extend(added);
+        self.cleanable_files.sort_by(|a, b| {
+            a.base
+                .category
+                .cmp(&b.base.category)
+                .then_with(|| a.base.path.cmp(&b.base.path))
+        });
+        self.report_history.record(&self.cleanable_files);
+        Ok(self.generate_enhanced_report(self.duplicate_groups.clone()))
+    }
+
+    /// Scan `root`, a folder already checked by
+    /// [`custom_path::resolve_scan_root`], in place of the system. The
+    /// results replace the last scan's, so they can be cleaned the same way.
+    pub async fn scan_custom_path(
+        &mut self,
+        root: &Path,
+        options: &CustomScanOptions,
+        token: &CancellationToken,
+        progress: Option<&ScanProgressFn>,
     ) -> Result<EnhancedCleaningReport, String> {
         self.cleanable_files.clear();
         self.seen_paths.clear();
         self.seen_dir_prefixes.clear();
+        self.exclusions = ExclusionMatcher::load();
+        self.safety_policies = SafetyPolicies::load();
+        let learned_exceptions = LearnedExceptionStore::load();
+        self.learned_shapes = learned_exceptions.matcher(dirs::home_dir().as_deref());
+        self.learned_exceptions = Some(learned_exceptions);
+        self.volume_resolver = Some(VolumeResolver::mounted());
+        let scanned_at = SystemTime::now();
         self.telemetry.start_scan();
+        self.safety_analyzer.reset_content_budget();
 
+        let process_snapshot = ProcessSnapshot::capture().await;
+        let mut rules = load_rules_result()?.categories;
+        rules.extend(self.dynamic_rules(&process_snapshot));
+        let targets =
+            custom_path::custom_scan_targets(rules, root, options, dirs::home_dir().as_deref());
+        let mut found = Vec::new();
+        let mut rerooted = HashSet::new();
+        for (index, target) in targets.iter().enumerate() {
+            if token.is_cancelled() {
+                return Err("cancelled".into());
+            }
+            if let Some(cb) = progress {
+                let fraction = index as f32 / targets.len() as f32;
+                cb(
+                    5.0 + fraction * 35.0,
+                    &format!("Scanning {}", target.rule.name),
+                    "discovery",
+                );
+            }
+            match self
+                .base_cleaner
+                .collect_rule_matches_for_path(&target.root, &target.rule, token)
+                .await
+            {
+                Ok(matches) => found.extend(matches),
+                Err(err) => {
+                    log::warn!("Custom scan error for {}: {}", target.root.display(), err);
+                }
+            }
+            if target.rerooted {
+                rerooted.insert(target.rule.name.clone());
+            }
+            tokio::task::yield_now().await;
+        }
+        for file in merge_unclaimed(Vec::new(), found) {
+            self.add_enhanced_placeholder(file);
+        }
         if token.is_cancelled() {
             return Err("cancelled".into());
         }
+
+        let work_plan = match &self.system_load {
+            Some(load) => plan_heavy_work(load, &process_snapshot),
+            None => HeavyWorkPlan::unrestricted(),
+        };
+        let duplicate_groups = if options.find_duplicates {
+            if let Some(cb) = progress {
+                cb(45.0, "Detecting duplicates", "duplicates");
+            }
+            let paths: Vec<PathBuf> = self
+                .cleanable_files
+                .iter()
+                .map(|f| PathBuf::from(&f.base.path))
+                .collect();
+            self.duplicate_detector
+                .find_duplicates_within(&paths, token, FOLDER_DUPLICATE_TIME_BUDGET)
+                .await?
+                .groups
+        } else {
+            Vec::new()
+        };
+
         if let Some(cb) = progress {
-            cb(8.0, "Scanning baseline categories", "discovery");
+            cb(65.0, "Analyzing safety", "safety");
+        }
+        let mut files = std::mem::take(&mut self.cleanable_files);
+        let total_files = files.len();
+        let analyzed = self
+            .analyze_files(
+                &mut files,
+                token,
+                &process_snapshot,
+                work_plan.safety_budget,
+                |processed| {
+                    if let Some(cb) = progress {
+                        if processed.is_multiple_of(75) || processed == total_files {
+                            let fraction = processed as f32 / total_files as f32;
+                            cb(
+                                65.0 + fraction * 25.0,
+                                &format!(
+                                    "Analyzing safety ({} / {} files)",
+                                    processed, total_files
+                                ),
+                                "safety",
+                            );
+                        }
+                    }
+                },
+            )
+            .await;
+        self.cleanable_files = files;
+        let processed = analyzed?;
+        for file in self.cleanable_files.iter_mut().skip(processed) {
+            Self::apply_deferred_safety(file, &self.safety_policies);
+        }
+        self.manual_categories = rerooted;
+        for file in self
+            .cleanable_files
+            .iter_mut()
+            .filter(|file| self.manual_categories.contains(&file.base.category))
+        {
+            file.base.auto_select = false;
+            file.auto_select_score.can_auto_select = false;
         }
 
-        self.base_cleaner.scan_system_with_cancel(token).await?;
+        if let Some(cb) = progress {
+            cb(90.0, "Scoring and summarizing", "scoring");
+        }
+        self.duplicate_groups = duplicate_groups.clone();
+        self.similar_groups = Vec::new();
+        self.groups_scanned_at = Some(scanned_at);
+        self.cleanable_files.sort_by(|a, b| {
+            a.base
+                .category
+                .cmp(&b.base.category)
+                .then_with(|| a.base.path.cmp(&b.base.path))
+        });
+        self.report_history.record(&self.cleanable_files);
+        let report = self.generate_enhanced_report(duplicate_groups);
+        self.finish_scan_telemetry();
+        Ok(report)
+    }
 
-        let baseline_files: Vec<CleanableFile> = self
-            .base_cleaner
-            .get_cleanable_files()
+    /// Files of the current results whose safety analysis a scan cut short.
+    pub fn deferred_safety_count(&self) -> usize {
+        self.cleanable_files
             .iter()
-            .cloned()
+            .filter(|file| is_safety_deferred(file))
+            .count()
+    }
+
+    /// Analyze the next batch of files whose safety analysis a scan cut
+    /// short and return them as they are now. The batch gets a smaller time
+    /// budget while the machine is busy, so running it between other work
+    /// goes unnoticed; call again until nothing remains.
+    pub async fn analyze_deferred_safety(
+        &mut self,
+        token: &CancellationToken,
+    ) -> Result<DeferredSafetyUpdate, String> {
+        let deferred: Vec<usize> = self
+            .cleanable_files
+            .iter()
+            .enumerate()
+            .filter(|(_, file)| is_safety_deferred(file))
+            .map(|(index, _)| index)
+            .take(DEFERRED_SAFETY_BATCH_FILES)
             .collect();
+        if deferred.is_empty() {
+            return Ok(DeferredSafetyUpdate::default());
+        }
+
+        self.safety_analyzer.reset_content_budget();
+        let process_snapshot = ProcessSnapshot::capture().await;
+        let work_plan = match &self.system_load {
+            Some(load) => plan_heavy_work(load, &process_snapshot),
+            None => HeavyWorkPlan::unrestricted(),
+        };
+        let budget = DEFERRED_SAFETY_BATCH_BUDGET
+            .mul_f64(work_plan.safety_budget.as_secs_f64() / SAFETY_ANALYSIS_BUDGET.as_secs_f64());
+        let mut batch: Vec<EnhancedCleanableFile> = deferred
+            .iter()
+            .map(|&index| self.cleanable_files[index].clone())
+            .collect();
+        let processed = self
+            .analyze_files(&mut batch, token, &process_snapshot, budget, |_| {})
+            .await?;
+        batch.truncate(processed);
+        for file in batch.iter_mut() {
+            if self.manual_categories.contains(&file.base.category) {
+                file.base.auto_select = false;
+                file.auto_select_score.can_auto_select = false;
+            }
+        }
+        for (&index, file) in deferred.iter().zip(&batch) {
+            self.cleanable_files[index] = file.clone();
+        }
+        if processed > 0 {
+            self.report_history.record(&self.cleanable_files);
+        }
+        Ok(DeferredSafetyUpdate {
+            files: batch,
+            remaining: self.deferred_safety_count(),
+        })
+    }
+
+    /// App-specific rules (developer caches and the like) for what is
+    /// installed, with the user's age thresholds applied.
+    fn dynamic_rules(&self, process_snapshot: &ProcessSnapshot) -> Vec<CategoryRule> {
+        let dynamic_engine = DynamicRuleEngine::new();
+        let generated = dynamic_engine.generate_app_specific_rules(process_snapshot);
+        if generated.is_empty() {
+            return Vec::new();
+        }
+        let mut adapted = dynamic_engine.adapt_rules_to_system(&CleanerRules {
+            categories: generated,
+        });
+        self.base_cleaner
+            .age_thresholds()
+            .apply(&mut adapted.categories);
+        adapted.categories
+    }
+
+    async fn scan_enhanced(
+        &mut self,
+        token: &CancellationToken,
+        progress: Option<&ScanProgressFn>,
+        incremental: bool,
+    ) -> Result<EnhancedCleaningReport, String> {
+        self.cleanable_files.clear();
+        self.seen_paths.clear();
+        self.seen_dir_prefixes.clear();
+        self.manual_categories.clear();
+        self.exclusions = ExclusionMatcher::load();
+        self.safety_policies = SafetyPolicies::load();
+        let learned_exceptions = LearnedExceptionStore::load();
+        self.learned_shapes = learned_exceptions.matcher(dirs::home_dir().as_deref());
+        self.learned_exceptions = Some(learned_exceptions);
+        self.volume_resolver = Some(VolumeResolver::mounted());
+        let scanned_at = SystemTime::now();
+        self.telemetry.start_scan();
+        self.safety_analyzer.reset_content_budget();
+
+        if token.is_cancelled() {
+            return Err("cancelled".into());
+        }
+        if let Some(cb) = progress {
+            cb(8.0, "Scanning baseline categories", "discovery");
+        }
+
+        // The baseline walk fills 8-18%, moving as each rule path finishes.
+        let baseline_progress = progress.map(|cb| {
+            move |update: ScanProgress| {
+                let fraction = update.paths_done as f32 / update.paths_total.max(1) as f32;
+                cb(
+                    8.0 + fraction * 10.0,
+                    &format!("Scanning {}", update.category),
+                    "discovery",
+                )
+            }
+        });
+        let baseline_progress = baseline_progress
+            .as_ref()
+            .map(|cb| cb as &BaselineProgressFn);
+        if incremental {
+            self.base_cleaner
+                .scan_incremental(token, baseline_progress)
+                .await?;
+        } else {
+            self.base_cleaner
+                .scan_system_with_cancel(token, baseline_progress)
+                .await?;
+        }
+
+        let baseline_files: Vec<CleanableFile> = self.base_cleaner.get_cleanable_files().to_vec();
 
         for base in baseline_files {
             if token.is_cancelled() {
@@ -160,20 +706,17 @@ impl EnhancedFileCleaner {
             self.add_enhanced_placeholder(base);
         }
 
+        // One snapshot serves the dynamic rules, stale lock and in-use checks
+        let process_snapshot = ProcessSnapshot::capture().await;
+
         // Dynamic app-aware rules (developer caches, etc.)
-        let dynamic_engine = DynamicRuleEngine::new();
-        let generated_dynamic = dynamic_engine.generate_app_specific_rules();
-        if !generated_dynamic.is_empty() {
+        let dynamic_rules = self.dynamic_rules(&process_snapshot);
+        if !dynamic_rules.is_empty() {
             if let Some(cb) = progress {
                 cb(18.0, "Scanning developer caches", "discovery");
             }
 
-            let dynamic_rules = CleanerRules {
-                categories: generated_dynamic,
-            };
-            let adapted = dynamic_engine.adapt_rules_to_system(&dynamic_rules);
-
-            for rule in adapted.categories.iter() {
+            for rule in dynamic_rules.iter() {
                 if token.is_cancelled() {
                     return Err("cancelled".into());
                 }
@@ -191,6 +734,7 @@ impl EnhancedFileCleaner {
                     match self
                         .base_cleaner
                         .collect_rule_matches_for_path(&path, rule, token)
+                        .await
                     {
                         Ok(matches) => {
                             for file in matches {
@@ -210,6 +754,47 @@ impl EnhancedFileCleaner {
             }
         }
 
+        // Relative rules on the external volumes the user opted into
+        let opted_in = ExternalVolumeStore::load().list();
+        if !opted_in.is_empty() {
+            let mounted: Vec<String> = volumes::list_volumes()
+                .into_iter()
+                .map(|volume| volume.id)
+                .collect();
+            let uid = unsafe { libc::getuid() };
+            for (index, mount) in opted_in.iter().enumerate() {
+                if token.is_cancelled() {
+                    return Err("cancelled".into());
+                }
+                if let Some(cb) = progress {
+                    cb(
+                        30.0 + 10.0 * index as f32 / opted_in.len() as f32,
+                        &format!(
+                            "Scanning volume {} ({}/{})",
+                            mount,
+                            index + 1,
+                            opted_in.len()
+                        ),
+                        "discovery",
+                    );
+                }
+                let mount = PathBuf::from(mount);
+                if let Err(reason) = external_volumes::check_volume(&mount, &mounted) {
+                    log::info!("Skipping external volume: {}", reason);
+                    continue;
+                }
+                let volume_token = token.clone();
+                let found = tokio::task::spawn_blocking(move || {
+                    external_volumes::scan_volume(&mount, uid, &volume_token)
+                })
+                .await
+                .map_err(|e| format!("Volume scan failed: {}", e))??;
+                for file in found {
+                    self.add_enhanced_placeholder(file);
+                }
+            }
+        }
+
         if token.is_cancelled() {
             return Err("cancelled".into());
         }
@@ -217,61 +802,133 @@ impl EnhancedFileCleaner {
             cb(40.0, "Enhancing metadata", "analysis");
         }
 
-        // Phase 2: Duplicate detection
-        if token.is_cancelled() {
-            return Err("cancelled".into());
-        }
+        // Lock/pid/socket files whose owner is gone, cross-checked against the snapshot
         if let Some(cb) = progress {
-            cb(45.0, "Detecting duplicates", "duplicates");
+            cb(42.0, "Checking for stale lock files", "discovery");
         }
-        let all_paths: Vec<PathBuf> = self
-            .cleanable_files
-            .iter()
-            .map(|f| PathBuf::from(&f.base.path))
-            .collect();
-        let duplicate_scan = self
-            .duplicate_detector
-            .find_duplicates(&all_paths, token)
-            .await?;
-        let duplicate_group_count = duplicate_scan.groups.len();
-        let analyzed_for_duplicates = duplicate_scan.analyzed_files;
-        let skipped_for_duplicates = duplicate_scan.skipped_files;
-        if duplicate_scan.truncated {
-            log::warn!(
-                "Duplicate detection truncated: processed {} files, skipped {}",
-                analyzed_for_duplicates,
-                skipped_for_duplicates
-            );
-        } else {
-            log::debug!(
-                "Duplicate detection completed: {} groups from {} files ({} skipped)",
-                duplicate_group_count,
-                analyzed_for_duplicates,
-                skipped_for_duplicates
-            );
+        let stale_snapshot = process_snapshot.clone();
+        let stale_token = token.clone();
+        let stale_locks = tokio::task::spawn_blocking(move || {
+            stale_locks::find_stale_lock_files(
+                &stale_locks::default_scan_roots(),
+                &stale_snapshot,
+                stale_locks::boot_time(),
+                &stale_token,
+            )
+        })
+        .await
+        .unwrap_or_default();
+        for file in stale_locks {
+            self.add_enhanced_placeholder(file);
         }
-        if let Some(cb) = progress {
-            let message = if duplicate_scan.truncated {
-                format!(
-                    "Duplicate scan partial ({} files, {} skipped)",
-                    analyzed_for_duplicates, skipped_for_duplicates
-                )
-            } else {
-                format!(
-                    "Detected {} duplicate sets across {} files",
-                    duplicate_group_count, analyzed_for_duplicates
-                )
-            };
-            let progress_value = if duplicate_scan.truncated { 52.0 } else { 55.0 };
-            cb(progress_value, &message, "duplicates");
+        let work_plan = match &self.system_load {
+            Some(load) => plan_heavy_work(load, &process_snapshot),
+            None => HeavyWorkPlan::unrestricted(),
+        };
+        if let Some(note) = work_plan.note() {
+            log::info!("System load {:?}: {}", work_plan.level, note);
+        }
+        if !self.volume_scope.is_unrestricted() {
+            let scope = &self.volume_scope;
+            self.cleanable_files
+                .retain(|file| scope.allows(&file.volume));
         }
-        let duplicate_groups = duplicate_scan.groups;
 
+        // Phase 2: Duplicate detection
         if token.is_cancelled() {
             return Err("cancelled".into());
         }
-
-        let process_snapshot = ProcessSnapshot::capture().await;
+        let duplicate_groups = match work_plan.duplicate_budget {
+            Some(budget) => {
+                if let Some(cb) = progress {
+                    let message = match work_plan.note() {
+                        Some(note) => format!("Detecting duplicates (reduced: {})", note),
+                        None => "Detecting duplicates".to_string(),
+                    };
+                    cb(45.0, &message, "duplicates");
+                }
+                let all_paths: Vec<PathBuf> = self
+                    .cleanable_files
+                    .iter()
+                    .map(|f| PathBuf::from(&f.base.path))
+                    .collect();
+                let duplicate_scan = self
+                    .duplicate_detector
+                    .find_duplicates_within(&all_paths, token, budget)
+                    .await?;
+                let duplicate_group_count = duplicate_scan.groups.len();
+                let analyzed_for_duplicates = duplicate_scan.analyzed_files;
+                let skipped_for_duplicates = duplicate_scan.skipped_files;
+                if duplicate_scan.truncated {
+                    self.telemetry.track_truncation(AnalysisStage::Duplicates);
+                    log::warn!(
+                        "Duplicate detection truncated: processed {} files, skipped {}",
+                        analyzed_for_duplicates,
+                        skipped_for_duplicates
+                    );
+                } else {
+                    log::debug!(
+                        "Duplicate detection completed: {} groups from {} files ({} skipped)",
+                        duplicate_group_count,
+                        analyzed_for_duplicates,
+                        skipped_for_duplicates
+                    );
+                }
+                if let Some(cb) = progress {
+                    let message = if duplicate_scan.truncated {
+                        format!(
+                            "Duplicate scan partial ({} files, {} skipped)",
+                            analyzed_for_duplicates, skipped_for_duplicates
+                        )
+                    } else {
+                        format!(
+                            "Detected {} duplicate sets across {} files",
+                            duplicate_group_count, analyzed_for_duplicates
+                        )
+                    };
+                    let progress_value = if duplicate_scan.truncated { 52.0 } else { 55.0 };
+                    cb(progress_value, &message, "duplicates");
+                }
+                duplicate_scan.groups
+            }
+            None => {
+                let note = work_plan.note().unwrap_or_default();
+                log::info!("Duplicate detection deferred: {}", note);
+                if let Some(cb) = progress {
+                    cb(
+                        55.0,
+                        &format!("Duplicate detection deferred: system busy ({})", note),
+                        "duplicates",
+                    );
+                }
+                Vec::new()
+            }
+        };
+        let similar_groups = match work_plan.duplicate_budget {
+            Some(budget) => {
+                let paths: Vec<PathBuf> = self
+                    .cleanable_files
+                    .iter()
+                    .map(|f| PathBuf::from(&f.base.path))
+                    .collect();
+                let images = similar_images::image_candidates(&paths, &duplicate_groups);
+                let similar_scan = self
+                    .duplicate_detector
+                    .find_similar_images_within(&images, token, budget)
+                    .await?;
+                if similar_scan.truncated {
+                    self.telemetry
+                        .track_truncation(AnalysisStage::SimilarImages);
+                    log::warn!(
+                        "Similar image detection truncated: processed {} images, skipped {}",
+                        similar_scan.analyzed_files,
+                        similar_scan.skipped_files
+                    );
+                }
+                similar_scan.groups
+            }
+            None => Vec::new(),
+        };
 
         if token.is_cancelled() {
             return Err("cancelled".into());
@@ -284,93 +941,50 @@ impl EnhancedFileCleaner {
 
         let total_files = self.cleanable_files.len();
         if let Some(cb) = progress {
-            cb(65.0, "Analyzing safety", "safety");
+            match work_plan.note() {
+                Some(note) if work_plan.safety_budget < SAFETY_ANALYSIS_BUDGET => cb(
+                    65.0,
+                    &format!("Analyzing safety (reduced: {})", note),
+                    "safety",
+                ),
+                _ => cb(65.0, "Analyzing safety", "safety"),
+            }
         }
 
         if total_files > 0 {
-            const SAFETY_ANALYSIS_BUDGET: Duration = Duration::from_secs(12);
-            let safety_start = Instant::now();
-            let mut processed = 0usize;
-            let mut truncated = false;
-
-            for file in &mut self.cleanable_files {
-                if token.is_cancelled() {
-                    return Err("cancelled".into());
-                }
-                if safety_start.elapsed() >= SAFETY_ANALYSIS_BUDGET {
-                    truncated = true;
-                    break;
-                }
-
-                let path = PathBuf::from(&file.base.path);
-
-                // Multi-layer safety analysis
-                file.safety_metrics = self
-                    .safety_analyzer
-                    .analyze_with_snapshot(&path, &file.base.category, &process_snapshot)
-                    .await;
-
-                // Cache validation if applicable
-                if file.base.category.to_lowercase().contains("cache") {
-                    file.cache_validation = Some(
-                        self.cache_detector
-                            .validate_cache_file(&path, &file.base.category, &process_snapshot)
-                            .await,
-                    );
-                }
-
-                // macOS integration checks
-                file.macos_status = Some(MacOSFileStatus {
-                    is_sip_protected: self.macos_integration.check_sip_protection(&path),
-                    spotlight_info: self
-                        .macos_integration
-                        .check_spotlight_importance(&path)
-                        .await,
-                    time_machine_status: self
-                        .macos_integration
-                        .check_time_machine_status(&path)
-                        .await,
-                    icloud_status: self.macos_integration.check_icloud_status(&path).await,
-                    file_associations: self.macos_integration.get_file_associations(&path).await,
-                });
-
-                // Auto-selection scoring
-                file.auto_select_score = self
-                    .auto_selector
-                    .calculate_auto_select_score(&file.base, &file.safety_metrics)
-                    .await;
-
-                // Update base file with enhanced safety data
-                file.base.safe_to_delete = matches!(
-                    file.safety_metrics.recommendation,
-                    SafetyRecommendation::SafeToAutoDelete
-                        | SafetyRecommendation::SafeWithUserConfirmation
-                );
-                file.base.safety_score = file.safety_metrics.base_score;
-                file.base.auto_select = file.auto_select_score.can_auto_select;
-
-                // Enforce policy gates (auto-select threshold, never-auto), without overriding hard blocks
-                let policy = policy_for_category(&file.base.category);
-                policy.enforce(&mut file.base);
-
-                processed += 1;
-
-                if let Some(cb) = progress {
-                    if processed % 75 == 0 || processed == total_files {
-                        let fraction = processed as f32 / total_files as f32;
-                        let progress_value = 65.0 + fraction * 20.0;
-                        cb(
-                            progress_value.min(88.0),
-                            &format!("Analyzing safety ({} / {} files)", processed, total_files),
-                            "safety",
-                        );
-                    }
-                }
-            }
+            let mut files = std::mem::take(&mut self.cleanable_files);
+            let analyzed = self
+                .analyze_files(
+                    &mut files,
+                    token,
+                    &process_snapshot,
+                    work_plan.safety_budget,
+                    |processed| {
+                        if let Some(cb) = progress {
+                            if processed.is_multiple_of(75) || processed == total_files {
+                                let fraction = processed as f32 / total_files as f32;
+                                let progress_value = 65.0 + fraction * 20.0;
+                                cb(
+                                    progress_value.min(88.0),
+                                    &format!(
+                                        "Analyzing safety ({} / {} files)",
+                                        processed, total_files
+                                    ),
+                                    "safety",
+                                );
+                            }
+                        }
+                    },
+                )
+                .await;
+            self.cleanable_files = files;
+            let processed = analyzed?;
+            let truncated = processed < total_files;
 
             if truncated {
+                self.telemetry.track_truncation(AnalysisStage::Safety);
                 for file in self.cleanable_files.iter_mut().skip(processed) {
-                    Self::apply_deferred_safety(file);
+                    Self::apply_deferred_safety(file, &self.safety_policies);
                 }
                 if let Some(cb) = progress {
                     cb(
@@ -402,11 +1016,127 @@ impl EnhancedFileCleaner {
             cb(90.0, "Scoring and summarizing", "scoring");
         }
         // Generate enhanced report
+        self.duplicate_groups = duplicate_groups.clone();
+        self.similar_groups = similar_groups;
+        self.groups_scanned_at = Some(scanned_at);
+        self.cleanable_files.sort_by(|a, b| {
+            a.base
+                .category
+                .cmp(&b.base.category)
+                .then_with(|| a.base.path.cmp(&b.base.path))
+        });
+        self.report_history.record(&self.cleanable_files);
         let report = self.generate_enhanced_report(duplicate_groups);
-        self.telemetry.finish_scan();
+        self.finish_scan_telemetry();
         Ok(report)
     }
 
+    /// Run the safety analysis on `files` in order until `budget` runs out
+    /// and return how many were analyzed, at least one if there are any.
+    /// `on_progress` hears the running count after each file.
+    async fn analyze_files(
+        &self,
+        files: &mut [EnhancedCleanableFile],
+        token: &CancellationToken,
+        process_snapshot: &ProcessSnapshot,
+        budget: Duration,
+        on_progress: impl Fn(usize),
+    ) -> Result<usize, String> {
+        let started = Instant::now();
+        let process_snapshot = &Self::with_open_files(process_snapshot, files).await;
+        let mut processed = 0usize;
+        for file in files.iter_mut() {
+            if token.is_cancelled() {
+                return Err("cancelled".into());
+            }
+            if processed > 0 && started.elapsed() >= budget {
+                break;
+            }
+
+            let path = PathBuf::from(&file.base.path);
+
+            // Multi-layer safety analysis
+            file.safety_metrics = self
+                .safety_analyzer
+                .analyze_with_snapshot(&path, &file.base.category, process_snapshot)
+                .await;
+
+            // Cache validation if applicable
+            if file.base.category.to_lowercase().contains("cache") {
+                file.cache_validation = Some(
+                    self.cache_detector
+                        .validate_cache_file(&path, &file.base.category, process_snapshot)
+                        .await,
+                );
+            }
+
+            // macOS integration checks
+            file.macos_status = Some(MacOSFileStatus {
+                is_sip_protected: self.macos_integration.check_sip_protection(&path),
+                spotlight_info: self
+                    .macos_integration
+                    .check_spotlight_importance(&path)
+                    .await,
+                time_machine_status: self
+                    .macos_integration
+                    .check_time_machine_status(&path)
+                    .await,
+                icloud_status: self.macos_integration.check_icloud_status(&path).await,
+                file_associations: self.macos_integration.get_file_associations(&path).await,
+                unavailable_checks: self.macos_integration.unavailable_checks(),
+            });
+
+            // Auto-selection scoring
+            file.auto_select_score = self
+                .auto_selector
+                .calculate_auto_select_score(&file.base, &file.safety_metrics)
+                .await;
+            if let Some(deselections) = self.learned_shapes.deselections_for(&file.base) {
+                file.auto_select_score.apply_learned_exception(deselections);
+            }
+
+            // Update base file with enhanced safety data
+            file.base.safe_to_delete = matches!(
+                file.safety_metrics.recommendation,
+                SafetyRecommendation::SafeToAutoDelete
+                    | SafetyRecommendation::SafeWithUserConfirmation
+            );
+            file.base.safety_score = file.safety_metrics.base_score;
+            file.base.auto_select = file.auto_select_score.can_auto_select;
+
+            // Enforce policy gates (auto-select threshold, never-auto), without overriding hard blocks
+            let policy = self.safety_policies.policy_for(&file.base.category);
+            policy.enforce(&mut file.base);
+
+            processed += 1;
+            on_progress(processed);
+        }
+        Ok(processed)
+    }
+
+    /// `process_snapshot` plus which of `files` some process holds open,
+    /// asked of `lsof` in batches; without lsof the snapshot is returned as is.
+    async fn with_open_files(
+        process_snapshot: &ProcessSnapshot,
+        files: &[EnhancedCleanableFile],
+    ) -> ProcessSnapshot {
+        let Some(lsof) = capabilities::tool_path(Tool::Lsof) else {
+            return process_snapshot.clone();
+        };
+        let paths: Vec<PathBuf> = files
+            .iter()
+            .map(|file| PathBuf::from(&file.base.path))
+            .filter(|path| fs::symlink_metadata(path).is_ok_and(|metadata| metadata.is_file()))
+            .collect();
+        match process_snapshot::open_files_among(&lsof, &paths).await {
+            Ok(open) => process_snapshot.clone().with_open_files(open),
+            Err(err) => {
+                log::warn!("Open-file check skipped: {}", err);
+                process_snapshot.clone()
+            }
+        }
+    }
+
     /// Pre-deletion validation with recovery point creation
     pub async fn validate_and_prepare_deletion(
         &mut self,
@@ -439,6 +1169,46 @@ impl EnhancedFileCleaner {
         })
     }
 
+    /// Quick clean `items` until `budget` runs out. They go through the
+    /// pre-deletion validator first, and a recovery point records where the
+    /// ones moved to the Trash ended up, so the run can be undone.
+    pub async fn quick_clean(
+        &mut self,
+        items: Vec<CleanableFile>,
+        budget: Duration,
+        token: &CancellationToken,
+    ) -> Result<QuickCleanResult, String> {
+        let deadline = Instant::now() + budget;
+        let validation = self.validator.validate_before_deletion(&items).await;
+        if token.is_cancelled() {
+            return Err("cancelled".into());
+        }
+        let (cleared, skipped) = quick_clean::cleared_items(items, &validation);
+        let recovery_point = self.recovery_manager.create_recovery_point(&cleared);
+
+        let token = token.clone();
+        let mut result = tokio::task::spawn_blocking(move || {
+            quick_clean::clean_until(
+                &cleared,
+                &ExclusionMatcher::load(),
+                deadline,
+                &token,
+                &native_trash::move_to_trash_blocking,
+            )
+        })
+        .await
+        .map_err(|e| format!("Quick clean task failed: {}", e))?;
+
+        for (original, trashed) in result.trashed.drain(..) {
+            self.recovery_manager
+                .mark_trashed(&recovery_point.id, &original, trashed);
+        }
+        self.persist_recovery_point();
+        result.failed.extend(skipped);
+        result.recovery_point_id = Some(recovery_point.id);
+        Ok(result)
+    }
+
     /// Clean selected files with enhanced safety
     pub async fn clean_files_enhanced(
         &mut self,
@@ -528,7 +1298,7 @@ impl EnhancedFileCleaner {
 
         if let Some(cb) = progress {
             cb(EnhancedDeletionProgress {
-                progress: if total_files == 0 { 20.0 } else { 20.0 },
+                progress: 20.0,
                 message: if total_files > 0 {
                     format!(
                         "Validation complete. {} file(s) cleared for deletion",
@@ -569,11 +1339,22 @@ impl EnhancedFileCleaner {
             }
         }
 
+        if self.quarantine.is_enabled() {
+            self.purge_expired_quarantine();
+        }
+        let mut quarantined_files = Vec::new();
+
         let mut tracker = ThroughputTracker::default();
         let mut processed_files = 0u64;
         let mut processed_bytes = 0u64;
         let progress_base = 20.0;
         let progress_scale = 80.0;
+        let mut mounted_volumes: Option<Vec<String>> = None;
+
+        // Caches whose tool can clear them go first, in one run per tool
+        let (native_removed, native_cleanups) = self
+            .run_native_cleanups(&eligible_files, allow_low_safety, progress)
+            .await;
 
         if let Some(cb) = progress {
             cb(EnhancedDeletionProgress {
@@ -593,20 +1374,57 @@ impl EnhancedFileCleaner {
         for file in eligible_files {
             if let Some(t) = token {
                 if t.is_cancelled() {
+                    self.persist_recovery_point();
+                    self.persist_quarantine();
                     return Err("cancelled".into());
                 }
             }
             let path = PathBuf::from(&file.base.path);
-            let measured_size = if path.exists() {
+            let measured_size = if let Some(&size) = native_removed.get(&file.base.path) {
+                size
+            } else if path.exists() {
+                // Only what the clean frees: not blocks shared with clones or
+                // with hard links left behind
                 self.base_cleaner
-                    .get_path_size_async(&path)
+                    .get_path_usage_async(&path)
                     .await
+                    .map(|usage| usage.reclaimable)
                     .unwrap_or(file.base.size)
             } else {
                 0
             };
             let base_score = file.safety_metrics.base_score;
 
+            // Lock owners can come back between scan and clean
+            if file.base.category == STALE_LOCK_CATEGORY {
+                if let Err(reason) = stale_locks::verify_still_stale(&path).await {
+                    failed_files.push(FailedDeletion {
+                        path: file.base.path.clone(),
+                        reason,
+                    });
+                    continue;
+                }
+            }
+
+            // Volumes can be ejected or swapped between scan and clean
+            if external_volumes::is_volume_category(&file.base.category) {
+                let mounted = mounted_volumes.get_or_insert_with(|| {
+                    volumes::list_volumes()
+                        .into_iter()
+                        .map(|volume| volume.id)
+                        .collect::<Vec<_>>()
+                });
+                if let Err(reason) =
+                    external_volumes::check_volume(Path::new(&file.volume), mounted)
+                {
+                    failed_files.push(FailedDeletion {
+                        path: file.base.path.clone(),
+                        reason,
+                    });
+                    continue;
+                }
+            }
+
             // Double-check safety
             if base_score < 40 && !allow_low_safety {
                 failed_files.push(FailedDeletion {
@@ -619,7 +1437,18 @@ impl EnhancedFileCleaner {
             // Attempt deletion (prefer Trash). Only direct-delete when extremely safe
             let prefer_trash_only = allow_low_safety || base_score < 80;
 
-            let deleted = if self.move_to_trash(&path).await {
+            let deleted = if native_removed.contains_key(&file.base.path) {
+                true
+            } else if let Some(staged) =
+                self.quarantine_item(&path, &file.base.category, measured_size)
+            {
+                self.recovery_manager
+                    .mark_trashed(&preparation.recovery_point_id, &path, staged);
+                quarantined_files.push(file.base.path.clone());
+                true
+            } else if let Some(trashed) = self.move_to_trash(&path).await {
+                self.recovery_manager
+                    .mark_trashed(&preparation.recovery_point_id, &path, trashed);
                 true
             } else if !prefer_trash_only && base_score >= 95 {
                 // Only attempt direct deletion for extremely safe files
@@ -666,6 +1495,9 @@ impl EnhancedFileCleaner {
             }
         }
 
+        self.persist_recovery_point();
+        self.persist_quarantine();
+
         if let Some(cb) = progress {
             cb(EnhancedDeletionProgress {
                 progress: 100.0,
@@ -684,106 +1516,244 @@ impl EnhancedFileCleaner {
             deleted_files,
             failed_files,
             recovery_point_id: preparation.recovery_point_id,
+            native_cleanups,
+            quarantined_files,
+            dry_run: false,
         })
     }
 
-    async fn move_to_trash(&self, path: &Path) -> bool {
-        // Prefer Finder deletion (moves to Trash per-volume)
-        if !Self::is_osascript_disabled() {
-            let escaped = path
-                .to_string_lossy()
-                .replace("\\", "\\\\")
-                .replace("\"", "\\\"");
-            match tokio::process::Command::new("osascript")
-                .arg("-e")
-                .arg(format!(
-                    "tell application \"Finder\" to move POSIX file \"{}\" to trash",
-                    escaped
-                ))
-                .output()
-                .await
-            {
-                Ok(output) if output.status.success() => {
-                    return true;
-                }
-                Ok(output) => {
-                    log::warn!(
-                        "Finder trash command (enhanced) failed (status {:?}): {}",
-                        output.status.code(),
-                        String::from_utf8_lossy(&output.stderr)
-                    );
-                }
-                Err(err) => {
-                    log::warn!("Enhanced cleaner AppleScript move failed: {}", err);
+    /// Run each app-native cleanup the selection calls for once. Returns the
+    /// selected paths the tools removed, with their size beforehand, and the
+    /// commands that ran; items of a failed run are left for the Trash.
+    async fn run_native_cleanups(
+        &self,
+        files: &[EnhancedCleanableFile],
+        allow_low_safety: bool,
+        progress: Option<&(dyn Fn(EnhancedDeletionProgress) + Send + Sync)>,
+    ) -> (HashMap<String, u64>, Vec<String>) {
+        let mut batches: BTreeMap<&NativeCleanup, Vec<&EnhancedCleanableFile>> = BTreeMap::new();
+        for file in files {
+            if let CleanupAction::AppNative { cleanup } = &file.cleanup_action {
+                if file.safety_metrics.base_score >= 40 || allow_low_safety {
+                    batches.entry(cleanup).or_default().push(file);
                 }
             }
         }
 
-        // Fallback: rename into ~/.Trash with unique name
-        if let Some(home) = dirs::home_dir() {
-            let trash = home.join(".Trash");
-            if trash.exists() || std::fs::create_dir_all(&trash).is_ok() {
-                if let Some(name) = path.file_name() {
-                    let mut target = trash.join(name);
-                    if target.exists() {
-                        let stem = name.to_string_lossy().to_string();
-                        let (base, ext) = Self::split_name_ext_local(&stem);
-                        let ts = Local::now().format("%Y%m%d-%H%M%S").to_string();
-                        let mut counter = 1u32;
-                        loop {
-                            let candidate = if ext.is_empty() {
-                                format!("{} ({}-{})", base, ts, counter)
-                            } else {
-                                format!("{} ({}-{}).{}", base, ts, counter, ext)
-                            };
-                            target = trash.join(candidate);
-                            if !target.exists() {
-                                break;
-                            }
-                            counter += 1;
-                        }
-                    }
-                    if std::fs::rename(path, &target).is_ok() {
-                        return true;
-                    }
+        let mut removed = HashMap::new();
+        let mut commands = Vec::new();
+        for (cleanup, files) in batches {
+            let mut sizes = Vec::with_capacity(files.len());
+            for file in &files {
+                let size = self
+                    .base_cleaner
+                    .get_path_size_async(Path::new(&file.base.path))
+                    .await
+                    .unwrap_or(file.base.size);
+                sizes.push(size);
+            }
+            if let Some(cb) = progress {
+                cb(EnhancedDeletionProgress {
+                    progress: 20.0,
+                    message: format!("Running {}", cleanup.command_line()),
+                    stage: "external_tool",
+                    eta_ms: None,
+                    files_per_s: None,
+                    mb_per_s: None,
+                });
+            }
+            if let Err(err) = cleanup.run().await {
+                log::warn!("{}; moving its items to the Trash instead", err);
+                continue;
+            }
+            commands.push(cleanup.command_line());
+            for (file, size) in files.into_iter().zip(sizes) {
+                if fs::symlink_metadata(&file.base.path).is_err() {
+                    removed.insert(file.base.path.clone(), size);
                 }
             }
         }
+        (removed, commands)
+    }
 
-        false
+    /// Put the files of recovery point `id` back where they were cleaned from.
+    pub async fn undo_clean(&mut self, id: &str) -> Result<UndoResult, String> {
+        let result = self.recovery_manager.restore(id)?;
+        // Quarantined files it put back are no longer staged
+        if let Err(err) = self.quarantine.forget_missing() {
+            log::warn!("Failed to update the quarantine manifest: {}", err);
+        }
+        for file in result.files.iter().filter(|file| file.error.is_none()) {
+            FileCleaner::invalidate_scan_caches(Path::new(&file.original_path)).await;
+        }
+        Ok(result)
     }
 
-    /// Local helper to split name and extension (avoids referencing private engine helper)
-    fn split_name_ext_local(name: &str) -> (String, String) {
-        if let Some(idx) = name.rfind('.') {
-            let (base, ext) = name.split_at(idx);
-            (base.to_string(), ext.trim_start_matches('.').to_string())
-        } else {
-            (name.to_string(), String::new())
+    /// Restore items from the user's Trash by name to where they were
+    /// trashed from; `destination` takes those with no recorded origin.
+    pub async fn restore_from_trash(
+        &mut self,
+        names: &[String],
+        destination: Option<&Path>,
+    ) -> Result<TrashRestoreResult, String> {
+        let home = dirs::home_dir().ok_or("Could not find home directory")?;
+        let trash = home.join(".Trash");
+        let result = trash_restore::restore_items(
+            &trash,
+            Path::new("/"),
+            names,
+            |trashed| self.recovery_manager.original_for(trashed),
+            destination,
+        );
+        let restored: Vec<&TrashRestoreItem> = result
+            .items
+            .iter()
+            .filter(|item| item.error.is_none())
+            .collect();
+        let trashed: Vec<PathBuf> = restored.iter().map(|item| trash.join(&item.name)).collect();
+        if let Err(err) = self.recovery_manager.forget_trashed(&trashed) {
+            log::warn!("Failed to update recovery points: {}", err);
         }
+        for target in restored.iter().filter_map(|item| item.restored_to.as_ref()) {
+            FileCleaner::invalidate_scan_caches(Path::new(target)).await;
+        }
+        Ok(result)
     }
 
-    fn is_osascript_disabled() -> bool {
-        env::var("MACOS_OPTIMIZER_DISABLE_OSA")
-            .map(|value| {
-                let lowercase = value.trim().to_ascii_lowercase();
-                lowercase == "1" || lowercase == "true" || lowercase == "yes"
-            })
-            .unwrap_or(false)
+    pub fn quarantine_settings(&self) -> QuarantineSettings {
+        self.quarantine.settings()
+    }
+
+    pub fn update_quarantine_settings(
+        &mut self,
+        settings: QuarantineSettings,
+    ) -> Result<(), String> {
+        self.quarantine.update_settings(settings)
+    }
+
+    /// Quarantined items, newest first, after purging the expired ones.
+    pub fn list_quarantine(&mut self) -> Vec<QuarantineEntry> {
+        self.purge_expired_quarantine();
+        self.quarantine.list()
+    }
+
+    /// Move quarantined items back to where they were cleaned from.
+    pub async fn restore_from_quarantine(
+        &mut self,
+        ids: &[String],
+    ) -> Result<QuarantineRestoreResult, String> {
+        let staged = self.quarantine_entries(ids);
+        let result = self.quarantine.restore(ids)?;
+        self.forget_unstaged(staged);
+        for target in result
+            .items
+            .iter()
+            .filter(|item| item.error.is_none())
+            .filter_map(|item| item.original_path.as_ref())
+        {
+            FileCleaner::invalidate_scan_caches(Path::new(target)).await;
+        }
+        Ok(result)
+    }
+
+    /// Delete quarantined items for good: `ids`, or all of them with `None`.
+    pub fn purge_quarantine(
+        &mut self,
+        ids: Option<&[String]>,
+    ) -> Result<QuarantinePurgeResult, String> {
+        let staged = match ids {
+            Some(ids) => self.quarantine_entries(ids),
+            None => self.quarantine.list(),
+        };
+        let result = self.quarantine.purge(ids);
+        self.forget_unstaged(staged);
+        result
+    }
+
+    fn purge_expired_quarantine(&mut self) {
+        let staged = self.quarantine.list();
+        match self.quarantine.purge_expired(Utc::now()) {
+            Ok(result) if result.purged > 0 => log::info!(
+                "Purged {} expired quarantined item(s), {} bytes",
+                result.purged,
+                result.freed
+            ),
+            Ok(_) => {}
+            Err(err) => log::warn!("Failed to purge expired quarantine: {}", err),
+        }
+        self.forget_unstaged(staged);
+    }
+
+    /// Move `path` into quarantine when it is enabled; `None` sends it to
+    /// the Trash instead.
+    fn quarantine_item(&mut self, path: &Path, category: &str, size: u64) -> Option<PathBuf> {
+        if !self.quarantine.is_enabled() {
+            return None;
+        }
+        self.quarantine
+            .stage(path, category, size)
+            .inspect_err(|err| log::warn!("{}; moving it to the Trash instead", err))
+            .ok()
+    }
+
+    fn quarantine_entries(&self, ids: &[String]) -> Vec<QuarantineEntry> {
+        self.quarantine
+            .list()
+            .into_iter()
+            .filter(|entry| ids.contains(&entry.id))
+            .collect()
+    }
+
+    /// Stop recovery points from pointing at `entries` that left quarantine.
+    fn forget_unstaged(&mut self, entries: Vec<QuarantineEntry>) {
+        let remaining = self.quarantine.list();
+        let gone: Vec<PathBuf> = entries
+            .into_iter()
+            .filter(|entry| !remaining.iter().any(|other| other.id == entry.id))
+            .map(|entry| PathBuf::from(entry.staged_path))
+            .collect();
+        if gone.is_empty() {
+            return;
+        }
+        if let Err(err) = self.recovery_manager.forget_trashed(&gone) {
+            log::warn!("Failed to update recovery points: {}", err);
+        }
+    }
+
+    fn persist_quarantine(&self) {
+        if let Err(err) = self.quarantine.persist_manifest() {
+            log::warn!("Failed to save the quarantine manifest: {}", err);
+        }
+    }
+
+    fn persist_recovery_point(&self) {
+        if let Err(err) = self.recovery_manager.persist() {
+            log::warn!("Failed to save recovery point: {}", err);
+        }
+    }
+
+    /// Move `path` to the Trash and return where it ended up there.
+    async fn move_to_trash(&self, path: &Path) -> Option<PathBuf> {
+        match native_trash::move_to_trash(path).await {
+            Ok(trashed) => Some(trashed),
+            Err(err) => {
+                log::warn!("{}", err);
+                None
+            }
+        }
     }
 
     fn generate_enhanced_report(
         &self,
         duplicate_groups: Vec<DuplicateGroup>,
     ) -> EnhancedCleaningReport {
-        let mut categories_map: HashMap<String, CategorySummary> = HashMap::new();
-        let mut total_size = 0u64;
+        let mut categories_map: BTreeMap<String, CategorySummary> = BTreeMap::new();
         let mut auto_selected_size = 0u64;
         let mut high_risk_count = 0;
-        let mut duplicate_size = 0u64;
+        let mut reclaimable = ReclaimableBreakdown::default();
 
         for file in &self.cleanable_files {
-            total_size += file.base.size;
+            reclaimable.add(file);
 
             if file.base.auto_select {
                 auto_selected_size += file.base.size;
@@ -832,14 +1802,10 @@ impl EnhancedFileCleaner {
         }
 
         // Calculate duplicate space before consuming duplicate_groups
-        for group in &duplicate_groups {
-            // Count all but one file in each group as duplicate space
-            if group.files.len() > 1 {
-                let duplicate_files_in_group = group.files.len() - 1;
-                let size_per_file = group.total_size / group.files.len() as u64;
-                duplicate_size += size_per_file * duplicate_files_in_group as u64;
-            }
-        }
+        let duplicate_size: u64 = duplicate_groups
+            .iter()
+            .map(|group| group.reclaimable_size)
+            .sum();
 
         // Convert to legacy category reports for compatibility
         let categories: Vec<CategoryReport> = categories_map
@@ -848,6 +1814,7 @@ impl EnhancedFileCleaner {
                 name: s.name.clone(),
                 size: s.total_size,
                 count: s.file_count,
+                label: self.base_cleaner.age_thresholds().label(&s.name),
             })
             .collect();
 
@@ -857,15 +1824,23 @@ impl EnhancedFileCleaner {
             .cloned()
             .collect();
 
+        let disk_usage = disk_usage::usage_of_results(
+            self.cleanable_files
+                .iter()
+                .map(|file| (file.base.path.as_str(), file.base.size)),
+        );
+
         EnhancedCleaningReport {
+            revision: self.report_history.revision(),
             base: CleaningReport {
-                total_size,
+                total_size: disk_usage.allocated,
                 files_count: self.cleanable_files.len(),
                 categories,
                 advanced_categories,
+                disk_usage,
             },
             enhanced_files: self.cleanable_files.clone(),
-            category_summaries: categories_map.into_iter().map(|(_, v)| v).collect(),
+            category_summaries: categories_map.into_values().collect(),
             safety_summary: SafetySummary {
                 auto_selected_size,
                 auto_selected_count: self
@@ -887,6 +1862,14 @@ impl EnhancedFileCleaner {
             },
             duplicate_groups,
             duplicate_space_recoverable: duplicate_size,
+            similar_image_groups: self.similar_groups.clone(),
+            similar_space_recoverable: self
+                .similar_groups
+                .iter()
+                .map(|group| group.reclaimable)
+                .sum(),
+            reclaimable,
+            volumes: volumes::summarize_by_volume(&self.cleanable_files),
         }
     }
 
@@ -898,6 +1881,18 @@ impl EnhancedFileCleaner {
         }
     }
 
+    /// The last scan's report as it stands now, without what was cleaned
+    /// since; `None` before the first scan.
+    pub fn latest_report(&self) -> Option<EnhancedCleaningReport> {
+        (self.report_history.revision() > 0)
+            .then(|| self.generate_enhanced_report(self.duplicate_groups.clone()))
+    }
+
+    /// Files added, changed and removed since the report with `since_revision`.
+    pub fn report_delta(&self, since_revision: u64) -> ReportDelta {
+        self.report_history.delta_since(since_revision)
+    }
+
     /// Record user feedback for machine learning
     pub fn record_user_feedback(&mut self, file_path: &str, action: UserAction) {
         if let Some(file) = self
@@ -910,14 +1905,522 @@ impl EnhancedFileCleaner {
             if matches!(action, UserAction::Deselected) {
                 self.telemetry.track_deselection();
             }
+            let store = self
+                .learned_exceptions
+                .get_or_insert_with(LearnedExceptionStore::load);
+            if let Err(err) = store.record(
+                &file.base,
+                &action,
+                file.base.auto_select,
+                dirs::home_dir().as_deref(),
+                chrono::Utc::now().timestamp(),
+            ) {
+                log::warn!("Failed to record learned exception: {}", err);
+            }
+        }
+    }
+
+    /// Forget the selection patterns learned from user feedback.
+    pub fn reset_learning_data(&mut self) -> Result<(), String> {
+        self.auto_selector.reset_learning_data()
+    }
+
+    /// Items reported by the last scan.
+    pub fn scanned_files(&self) -> Vec<CleanableFile> {
+        self.cleanable_files
+            .iter()
+            .map(|file| file.base.clone())
+            .collect()
+    }
+
+    /// Developer-mode scan of Xcode, simulator and package manager leftovers.
+    /// Found items join the current scan results with full safety analysis,
+    /// so they can be cleaned like any other selection.
+    pub async fn scan_developer_junk(
+        &mut self,
+        token: &CancellationToken,
+    ) -> Result<DeveloperJunkReport, String> {
+        let home = dirs::home_dir().ok_or("Could not determine home directory")?;
+        self.exclusions = ExclusionMatcher::load();
+        self.safety_policies = SafetyPolicies::load();
+        let process_snapshot = ProcessSnapshot::capture().await;
+        let environment = DeveloperEnvironment {
+            xcode_running: heavy_app("xcode")
+                .map(|app| app.is_running(&process_snapshot))
+                .unwrap_or(false),
+            simulators: developer_junk::list_simulators().await,
+        };
+
+        let exclusions = self.exclusions.clone();
+        let scan_token = token.clone();
+        let report = tokio::task::spawn_blocking(move || {
+            developer_junk::scan_developer_junk(&home, &environment, &exclusions, &scan_token)
+        })
+        .await
+        .map_err(|e| format!("Developer scan task failed: {}", e))??;
+
+        for item in report.groups.iter().flat_map(|group| group.items.iter()) {
+            if token.is_cancelled() {
+                return Err("cancelled".into());
+            }
+            self.add_analyzed(item.to_cleanable(), item.recommended, &process_snapshot)
+                .await;
+        }
+        Ok(report)
+    }
+
+    /// Dependency and build folders of the projects under `query.roots`,
+    /// grouped by project; only those of stale projects are recommended.
+    pub async fn scan_project_artifacts(
+        &mut self,
+        query: ProjectArtifactQuery,
+        token: &CancellationToken,
+    ) -> Result<ProjectArtifactReport, String> {
+        let home = dirs::home_dir().ok_or("Could not determine home directory")?;
+        let roots: Vec<PathBuf> = if query.roots.is_empty() {
+            project_artifacts::default_roots(&home)
+        } else {
+            query
+                .roots
+                .iter()
+                .filter_map(|root| Self::expand_path(root))
+                .collect()
+        };
+        let stale_days = query
+            .stale_days
+            .unwrap_or(project_artifacts::DEFAULT_STALE_DAYS);
+        self.exclusions = ExclusionMatcher::load();
+        self.safety_policies = SafetyPolicies::load();
+        let process_snapshot = ProcessSnapshot::capture().await;
+
+        let exclusions = self.exclusions.clone();
+        let scan_token = token.clone();
+        let report = tokio::task::spawn_blocking(move || {
+            project_artifacts::scan_project_artifacts(
+                &roots,
+                stale_days,
+                &exclusions,
+                &project_artifacts::git_state,
+                &scan_token,
+            )
+        })
+        .await
+        .map_err(|e| format!("Project artifact scan task failed: {}", e))??;
+
+        for project in &report.projects {
+            for artifact in &project.artifacts {
+                if token.is_cancelled() {
+                    return Err("cancelled".into());
+                }
+                self.add_analyzed(
+                    artifact.to_cleanable(project),
+                    artifact.recommended,
+                    &process_snapshot,
+                )
+                .await;
+            }
+        }
+        Ok(report)
+    }
+
+    /// Hunt for large, old files under `query.roots`, passing each match to
+    /// `on_match` as the walk finds it.
+    pub async fn find_large_files(
+        &mut self,
+        query: LargeFileQuery,
+        token: &CancellationToken,
+        on_match: impl FnMut(&LargeFile) + Send + 'static,
+    ) -> Result<LargeFileScan, String> {
+        self.exclusions = ExclusionMatcher::load();
+        self.safety_policies = SafetyPolicies::load();
+        let exclusions = self.exclusions.clone();
+        let walk_token = token.clone();
+        tokio::task::spawn_blocking(move || {
+            large_files::find_large_files(
+                &query,
+                &exclusions,
+                &load_age_thresholds(),
+                &ProtectionPacks::load(),
+                &walk_token,
+                on_match,
+            )
+        })
+        .await
+        .map_err(|e| format!("Large file search task failed: {}", e))?
+    }
+
+    /// Add an item found outside the main scan with full safety analysis.
+    /// `recommended` caps auto-selection; returns false if it was excluded.
+    async fn add_analyzed(
+        &mut self,
+        base: CleanableFile,
+        recommended: bool,
+        process_snapshot: &ProcessSnapshot,
+    ) -> bool {
+        let item_path = base.path.clone();
+        self.add_enhanced_placeholder(base);
+        let Some(index) = self
+            .cleanable_files
+            .iter()
+            .position(|file| file.base.path == item_path)
+        else {
+            return false;
+        };
+        let path = PathBuf::from(&item_path);
+        let category = self.cleanable_files[index].base.category.clone();
+        let safety_metrics = self
+            .safety_analyzer
+            .analyze_with_snapshot(&path, &category, process_snapshot)
+            .await;
+        let mut auto_select_score = self
+            .auto_selector
+            .calculate_auto_select_score(&self.cleanable_files[index].base, &safety_metrics)
+            .await;
+        if let Some(deselections) = self
+            .learned_shapes
+            .deselections_for(&self.cleanable_files[index].base)
+        {
+            auto_select_score.apply_learned_exception(deselections);
+        }
+
+        let file = &mut self.cleanable_files[index];
+        file.base.safe_to_delete = matches!(
+            safety_metrics.recommendation,
+            SafetyRecommendation::SafeToAutoDelete | SafetyRecommendation::SafeWithUserConfirmation
+        );
+        file.base.safety_score = safety_metrics.base_score;
+        file.base.auto_select = recommended && auto_select_score.can_auto_select;
+        file.safety_metrics = safety_metrics;
+        file.auto_select_score = auto_select_score;
+        self.safety_policies
+            .policy_for(&file.base.category)
+            .enforce(&mut file.base);
+        true
+    }
+
+    /// Find duplicates and similar images within folders the user picked,
+    /// replacing the groups available to [`Self::prepare_duplicate_resolution`].
+    /// `on_group` is called with each duplicate group as soon as it is found.
+    pub async fn scan_duplicates_in_paths(
+        &mut self,
+        roots: &[PathBuf],
+        token: &CancellationToken,
+        on_group: impl FnMut(&DuplicateGroup) + Send,
+    ) -> Result<DuplicateScanResult, String> {
+        if roots.is_empty() {
+            return Err("No folders selected".into());
+        }
+        if let Some(root) = roots
+            .iter()
+            .find(|root| !root.is_absolute() || !root.is_dir())
+        {
+            return Err(format!("Not a folder: {}", root.display()));
+        }
+        self.exclusions = ExclusionMatcher::load();
+        self.safety_policies = SafetyPolicies::load();
+        let scanned_at = SystemTime::now();
+        let exclusions = self.exclusions.clone();
+        let walk_roots = roots.to_vec();
+        let walk_token = token.clone();
+        let candidates = tokio::task::spawn_blocking(move || {
+            duplicate_detector::collect_candidates(&walk_roots, &exclusions, &walk_token)
+        })
+        .await
+        .map_err(|e| format!("Duplicate scan task failed: {}", e))??;
+
+        let started = Instant::now();
+        let mut result = self
+            .duplicate_detector
+            .stream_duplicates_within(&candidates, token, FOLDER_DUPLICATE_TIME_BUDGET, on_group)
+            .await?;
+        let images = similar_images::image_candidates(&candidates, &result.groups);
+        let remaining = FOLDER_DUPLICATE_TIME_BUDGET.saturating_sub(started.elapsed());
+        result.similar_groups = self
+            .duplicate_detector
+            .find_similar_images_within(&images, token, remaining)
+            .await?
+            .groups;
+        self.duplicate_groups = result.groups.clone();
+        self.similar_groups = result.similar_groups.clone();
+        self.groups_scanned_at = Some(scanned_at);
+        Ok(result)
+    }
+
+    /// Pick the copy to keep in a duplicate group and stage the others for
+    /// [`Self::clean_files_enhanced`], with the usual safety analysis.
+    /// The copy to keep must still be there. Exact duplicates are hashed
+    /// again in full and only copies still identical to it are staged;
+    /// similar images are staged only while their size is unchanged and
+    /// they were not modified since the scan.
+    pub async fn prepare_duplicate_resolution(
+        &mut self,
+        group_id: &str,
+        strategy: &KeepStrategy,
+    ) -> Result<DuplicateResolution, String> {
+        let (files, sizes, relation, exact) = if let Some(group) = self
+            .duplicate_groups
+            .iter()
+            .find(|group| group.hash == group_id)
+        {
+            let file_size = group.total_size / group.files.len().max(1) as u64;
+            (
+                group.files.clone(),
+                vec![file_size; group.files.len()],
+                "Duplicate of",
+                true,
+            )
+        } else if let Some(group) = self
+            .similar_groups
+            .iter()
+            .find(|group| group.id == group_id)
+        {
+            (
+                group.files(),
+                group.images.iter().map(|image| image.size).collect(),
+                "Looks the same as",
+                false,
+            )
+        } else {
+            return Err("Duplicate group not found; scan again".into());
+        };
+        let keep = strategy
+            .keeper(&files)
+            .ok_or("The copy to keep is missing or not part of this group")?;
+        let keep_name = keep
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_default();
+
+        let copies: Vec<(PathBuf, u64)> = files
+            .iter()
+            .cloned()
+            .zip(sizes)
+            .filter(|(path, _)| *path != keep)
+            .collect();
+        let scanned_at = self.groups_scanned_at;
+        let check_keep = keep.clone();
+        let unchanged = tokio::task::spawn_blocking(move || {
+            unchanged_copies(&check_keep, copies, exact, scanned_at)
+        })
+        .await
+        .map_err(|e| format!("Duplicate check task failed: {}", e))??;
+
+        let process_snapshot = ProcessSnapshot::capture().await;
+        let mut remove = Vec::new();
+        for (path, metadata) in unchanged {
+            let last_modified = metadata
+                .modified()
+                .ok()
+                .and_then(|time| time.duration_since(std::time::UNIX_EPOCH).ok())
+                .map(|since| since.as_secs() as i64)
+                .unwrap_or(0);
+            let base = CleanableFile {
+                path: path.to_string_lossy().to_string(),
+                size: metadata.len(),
+                category: DUPLICATE_CATEGORY.to_string(),
+                description: format!("{} {}", relation, keep_name),
+                last_modified,
+                safe_to_delete: false,
+                safety_score: 0,
+                auto_select: false,
+            };
+            let staged = base.path.clone();
+            if self.add_analyzed(base, false, &process_snapshot).await {
+                remove.push(staged);
+            }
+        }
+        Ok(DuplicateResolution {
+            group_id: group_id.to_string(),
+            keep: keep.to_string_lossy().to_string(),
+            remove,
+        })
+    }
+
+    /// Ranked suggestions from the last scan's files and duplicate groups,
+    /// together with analyzed app footprints and current disk usage.
+    pub fn storage_recommendations(
+        &self,
+        app_footprints: &[AppFootprint],
+        volumes: &[VolumeInfo],
+    ) -> Vec<StorageRecommendation> {
+        recommendations::recommend(&RecommendationInputs {
+            files: &self.cleanable_files,
+            duplicate_groups: &self.duplicate_groups,
+            similar_groups: &self.similar_groups,
+            app_footprints,
+            volumes,
+            age_thresholds: self.base_cleaner.age_thresholds().clone(),
+            protection_packs: self.base_cleaner.protection_packs().clone(),
+        })
+    }
+
+    /// Drop deleted copies from a group, and the group once one copy is left.
+    pub fn settle_duplicate_group(&mut self, group_id: &str, deleted: &[String]) {
+        for group in self
+            .duplicate_groups
+            .iter_mut()
+            .filter(|group| group.hash == group_id)
+        {
+            let before = group.files.len().max(1) as u64;
+            let file_size = group.total_size / before;
+            group
+                .files
+                .retain(|path| !deleted.contains(&path.to_string_lossy().to_string()));
+            group.total_size = file_size * group.files.len() as u64;
+        }
+        self.duplicate_groups.retain(|group| group.files.len() > 1);
+        for group in self
+            .similar_groups
+            .iter_mut()
+            .filter(|group| group.id == group_id)
+        {
+            group
+                .images
+                .retain(|image| !deleted.contains(&image.path.to_string_lossy().to_string()));
+            group.total_size = group.images.iter().map(|image| image.size).sum();
+            group.recommended_to_keep = group.images.first().map(|image| image.path.clone());
+            group.reclaimable =
+                group.total_size - group.images.first().map_or(0, |image| image.size);
         }
+        self.similar_groups.retain(|group| group.images.len() > 1);
     }
 
     pub fn telemetry_snapshot(&self) -> TelemetrySnapshot {
         self.telemetry.get_snapshot()
     }
 
-    fn apply_deferred_safety(file: &mut EnhancedCleanableFile) {
+    /// What the last completed scan found and which analysis it cut short.
+    pub fn last_scan_telemetry(&self) -> Option<ScanTelemetry> {
+        self.telemetry.last_scan().cloned()
+    }
+
+    fn finish_scan_telemetry(&mut self) {
+        let auto_selected = self
+            .cleanable_files
+            .iter()
+            .filter(|file| file.base.auto_select)
+            .count();
+        self.telemetry
+            .finish_scan(self.cleanable_files.len(), auto_selected);
+    }
+
+    /// Run every safety layer on `path`, a candidate from the current
+    /// results, and report what each one found and which policy gates
+    /// applied.
+    pub async fn explain_file_safety(&self, path: &str) -> Result<SafetyExplanation, String> {
+        let candidate = self
+            .cleanable_files
+            .iter()
+            .find(|file| file.base.path == path)
+            .ok_or("Not in the current scan results")?;
+        let mut base = candidate.base.clone();
+        let file_path = PathBuf::from(&base.path);
+
+        let rule_assessment = assess_path_risk_with(
+            &file_path,
+            self.base_cleaner.age_thresholds(),
+            self.base_cleaner.protection_packs(),
+        );
+        let rule_min_age = load_rules()
+            .categories
+            .into_iter()
+            .find(|rule| rule.name == base.category)
+            .and_then(|rule| rule.min_age_days);
+        let (rule_score, _) =
+            calculate_safety_score(&file_path, &base.category, &rule_assessment, rule_min_age);
+
+        let process_snapshot = ProcessSnapshot::capture().await;
+        let (safety_metrics, layers) = self
+            .safety_analyzer
+            .analyze_layers(&file_path, &base.category, &process_snapshot)
+            .await;
+        let cache_validation = if base.category.to_lowercase().contains("cache") {
+            Some(
+                self.cache_detector
+                    .validate_cache_file(&file_path, &base.category, &process_snapshot)
+                    .await,
+            )
+        } else {
+            None
+        };
+        let macos_status = MacOSFileStatus {
+            is_sip_protected: self.macos_integration.check_sip_protection(&file_path),
+            spotlight_info: self
+                .macos_integration
+                .check_spotlight_importance(&file_path)
+                .await,
+            time_machine_status: self
+                .macos_integration
+                .check_time_machine_status(&file_path)
+                .await,
+            icloud_status: self.macos_integration.check_icloud_status(&file_path).await,
+            file_associations: self
+                .macos_integration
+                .get_file_associations(&file_path)
+                .await,
+            unavailable_checks: self.macos_integration.unavailable_checks(),
+        };
+        let mut auto_select_score = self
+            .auto_selector
+            .calculate_auto_select_score(&base, &safety_metrics)
+            .await;
+        if let Some(deselections) = self.learned_shapes.deselections_for(&base) {
+            auto_select_score.apply_learned_exception(deselections);
+        }
+
+        base.safe_to_delete = matches!(
+            safety_metrics.recommendation,
+            SafetyRecommendation::SafeToAutoDelete | SafetyRecommendation::SafeWithUserConfirmation
+        );
+        base.safety_score = safety_metrics.base_score;
+        base.auto_select = auto_select_score.can_auto_select;
+        let before = base.clone();
+        self.safety_policies
+            .policy_for(&base.category)
+            .enforce(&mut base);
+        let thresholds = SafetyThresholds::from(self.safety_policies.policy_for(&base.category));
+        let mut applied = Vec::new();
+        if before.auto_select && !base.auto_select {
+            applied.push(match thresholds.auto_select_threshold {
+                None => "This category is never auto-selected".to_string(),
+                Some(threshold) if before.safety_score < threshold => format!(
+                    "Safety score {} is below the auto-select threshold of {}",
+                    before.safety_score, threshold
+                ),
+                Some(_) => format!(
+                    "Larger than the {} auto-select limit",
+                    crate::formatting::format_size(thresholds.max_auto_select_size.unwrap_or(0))
+                ),
+            });
+        }
+        if before.safe_to_delete && !base.safe_to_delete {
+            applied.push(format!(
+                "Safety score {} is below the direct delete threshold of {}",
+                before.safety_score, thresholds.direct_delete_threshold
+            ));
+        }
+
+        Ok(SafetyExplanation {
+            path: base.path.clone(),
+            category: base.category.clone(),
+            rule_assessment,
+            rule_score,
+            layers,
+            safety_metrics,
+            cache_validation,
+            macos_status,
+            auto_select_score,
+            policy: PolicyGates {
+                thresholds,
+                customized: self.safety_policies.is_customized(&base.category),
+                applied,
+            },
+            safe_to_delete: base.safe_to_delete,
+            auto_select: base.auto_select,
+        })
+    }
+
+    fn apply_deferred_safety(file: &mut EnhancedCleanableFile, policies: &SafetyPolicies) {
         file.safety_metrics = SafetyMetrics {
             base_score: 45,
             confidence: 0.2,
@@ -930,7 +2433,7 @@ impl EnhancedFileCleaner {
         file.base.safety_score = file.safety_metrics.base_score;
         file.base.auto_select = false;
 
-        let policy = policy_for_category(&file.base.category);
+        let policy = policies.policy_for(&file.base.category);
         policy.enforce(&mut file.base);
     }
 }
@@ -939,12 +2442,111 @@ impl EnhancedFileCleaner {
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct EnhancedCleanableFile {
+    /// Hash of the canonical path; the same file keeps it across scans.
+    #[serde(default)]
+    pub id: String,
     pub base: CleanableFile,
+    /// Mount point of the volume holding the file.
+    #[serde(default)]
+    pub volume: String,
     pub safety_metrics: SafetyMetrics,
     pub cache_validation: Option<CacheValidation>,
     pub auto_select_score: AutoSelectScore,
     pub macos_status: Option<MacOSFileStatus>,
     pub validation_state: Option<FileValidationState>,
+    #[serde(default)]
+    pub cleanup_action: CleanupAction,
+}
+
+/// Files whose deferred safety analysis just finished, and how many are
+/// still waiting for theirs.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct DeferredSafetyUpdate {
+    pub files: Vec<EnhancedCleanableFile>,
+    pub remaining: usize,
+}
+
+fn is_safety_deferred(file: &EnhancedCleanableFile) -> bool {
+    file.safety_metrics
+        .risk_factors
+        .iter()
+        .any(|factor| matches!(factor, RiskFactor::SafetyAnalysisDeferred))
+}
+
+/// The copies of a duplicate group that may still go beside `keep`, with
+/// their metadata: each must still be a file of its scanned size, and for
+/// an `exact` group hash to the same full BLAKE3 as `keep`, otherwise not
+/// be modified after `scanned_at`. Fails when `keep` itself is gone.
+pub(crate) fn unchanged_copies(
+    keep: &Path,
+    copies: Vec<(PathBuf, u64)>,
+    exact: bool,
+    scanned_at: Option<SystemTime>,
+) -> Result<Vec<(PathBuf, fs::Metadata)>, String> {
+    if !fs::metadata(keep).is_ok_and(|metadata| metadata.is_file()) {
+        return Err(format!(
+            "{} is gone; scan again before removing its copies",
+            keep.display()
+        ));
+    }
+    let keep_hash = if exact {
+        Some(
+            hashing::hash_file(HashAlgorithm::Blake3, keep)
+                .map_err(|e| format!("Failed to hash {}: {}", keep.display(), e))?,
+        )
+    } else {
+        None
+    };
+    Ok(copies
+        .into_iter()
+        .filter_map(|(path, size)| {
+            let metadata = fs::metadata(&path).ok()?;
+            if !metadata.is_file() || metadata.len() != size {
+                return None;
+            }
+            let unchanged = match &keep_hash {
+                Some(keep_hash) => hashing::hash_file(HashAlgorithm::Blake3, &path)
+                    .is_ok_and(|hash| hash == *keep_hash),
+                None => scanned_at.is_none_or(|scanned_at| {
+                    metadata
+                        .modified()
+                        .is_ok_and(|modified| modified <= scanned_at)
+                }),
+            };
+            unchanged.then_some((path, metadata))
+        })
+        .collect())
+}
+
+/// Why a scan candidate is or is not safe to delete and auto-select, layer
+/// by layer, as analyzed again just now.
+#[derive(Debug, Clone, Serialize)]
+pub struct SafetyExplanation {
+    pub path: String,
+    pub category: String,
+    /// The rule-level path check that made the file a candidate.
+    pub rule_assessment: RiskAssessment,
+    pub rule_score: u8,
+    /// Contributions of the pattern, usage, content, system and plugin
+    /// layers, in the order they ran.
+    pub layers: Vec<SafetyLayer>,
+    pub safety_metrics: SafetyMetrics,
+    pub cache_validation: Option<CacheValidation>,
+    pub macos_status: MacOSFileStatus,
+    pub auto_select_score: AutoSelectScore,
+    pub policy: PolicyGates,
+    pub safe_to_delete: bool,
+    pub auto_select: bool,
+}
+
+/// The category's safety policy and what it overruled.
+#[derive(Debug, Clone, Serialize)]
+pub struct PolicyGates {
+    pub thresholds: SafetyThresholds,
+    /// The thresholds come from the user's settings.
+    pub customized: bool,
+    /// Each way the policy changed the verdict; empty if it changed nothing.
+    pub applied: Vec<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -954,16 +2556,75 @@ pub struct MacOSFileStatus {
     pub time_machine_status: BackupStatus,
     pub icloud_status: CloudStatus,
     pub file_associations: Vec<FileAssociation>,
+    /// Tools that were missing, so the matching fields above are defaults.
+    pub unavailable_checks: Vec<String>,
+}
+
+/// The copy a duplicate group keeps and the staged copies to remove.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DuplicateResolution {
+    pub group_id: String,
+    pub keep: String,
+    pub remove: Vec<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct EnhancedCleaningReport {
+    /// Increases with every scan; pass it to `report_delta` to get what the
+    /// next reports changed.
+    #[serde(default)]
+    pub revision: u64,
     pub base: CleaningReport,
+    /// Sorted by category, then path.
     pub enhanced_files: Vec<EnhancedCleanableFile>,
+    /// Sorted by name, as are the categories of `base`.
     pub category_summaries: Vec<CategorySummary>,
     pub safety_summary: SafetySummary,
     pub duplicate_groups: Vec<DuplicateGroup>,
     pub duplicate_space_recoverable: u64,
+    /// Images that look the same without being byte-identical.
+    pub similar_image_groups: Vec<SimilarImageGroup>,
+    pub similar_space_recoverable: u64,
+    pub reclaimable: ReclaimableBreakdown,
+    /// Per-volume totals, largest first.
+    pub volumes: Vec<VolumeSummary>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ReclaimableBucket {
+    pub size: u64,
+    pub count: usize,
+}
+
+/// Splits the headline total by how confident the safety analysis is, so a
+/// few safe caches aren't presented alongside files that need a closer look.
+/// Files the analysis says not to delete are in none of the buckets.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ReclaimableBreakdown {
+    /// Recommended for deletion by the safety analysis.
+    pub safe_now: ReclaimableBucket,
+    /// Reclaimable once the user has reviewed them.
+    pub after_review: ReclaimableBucket,
+    /// Only shown with advanced categories enabled.
+    pub advanced_only: ReclaimableBucket,
+}
+
+impl ReclaimableBreakdown {
+    pub(crate) fn add(&mut self, file: &EnhancedCleanableFile) {
+        let bucket = match file.safety_metrics.recommendation {
+            SafetyRecommendation::DoNotDelete => return,
+            _ if file.base.category.contains("Advanced") => &mut self.advanced_only,
+            SafetyRecommendation::SafeToAutoDelete
+            | SafetyRecommendation::SafeWithUserConfirmation
+                if file.base.safe_to_delete =>
+            {
+                &mut self.safe_now
+            }
+            _ => &mut self.after_review,
+        };
+        bucket.size = bucket.size.saturating_add(file.base.size);
+        bucket.count += 1;
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -1000,6 +2661,36 @@ pub struct CleaningResult {
     pub deleted_files: Vec<String>,
     pub failed_files: Vec<FailedDeletion>,
     pub recovery_point_id: String,
+    /// Cache cleanup commands that ran in place of moving items to the Trash.
+    #[serde(default)]
+    pub native_cleanups: Vec<String>,
+    /// Deleted files that went to quarantine rather than the Trash.
+    #[serde(default)]
+    pub quarantined_files: Vec<String>,
+    /// Set when inspector mode reported what would happen instead of doing it.
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+impl CleaningResult {
+    /// Inspector-mode stand-in: the scanned items `paths` select, as if deleted.
+    pub fn preview(files: &[CleanableFile], paths: &[String]) -> Self {
+        let selected: Vec<&CleanableFile> = files
+            .iter()
+            .filter(|file| paths.contains(&file.path))
+            .collect();
+        Self {
+            deleted_count: selected.len(),
+            failed_count: 0,
+            total_freed: selected.iter().map(|file| file.size).sum(),
+            deleted_files: selected.iter().map(|file| file.path.clone()).collect(),
+            failed_files: Vec::new(),
+            recovery_point_id: String::new(),
+            native_cleanups: Vec::new(),
+            quarantined_files: Vec::new(),
+            dry_run: true,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
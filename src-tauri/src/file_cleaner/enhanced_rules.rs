@@ -1,26 +1,130 @@
 use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
 
 use serde::{Deserialize, Serialize};
 
+use super::process_snapshot::ProcessSnapshot;
+use super::runtime_detection::{self, Runtime};
 use super::smart_cache::AppActivityChecker;
 use super::types::{CategoryRule, CleanerRules};
 
+/// A language tool cache that is worth scanning once its runtime is in use.
+struct RuntimeCache {
+    runtime: Runtime,
+    name: &'static str,
+    path: &'static str,
+    advanced: bool,
+    max_depth: usize,
+    min_age_days: i64,
+    extensions: &'static [&'static str],
+}
+
+impl RuntimeCache {
+    fn to_rule(&self) -> CategoryRule {
+        CategoryRule {
+            name: self.name.to_string(),
+            paths: vec![self.path.to_string()],
+            safe: true,
+            advanced: Some(self.advanced),
+            max_depth: Some(self.max_depth),
+            min_age_days: Some(self.min_age_days),
+            min_size_kb: None,
+            excludes: None,
+            extensions: (!self.extensions.is_empty())
+                .then(|| self.extensions.iter().map(|ext| ext.to_string()).collect()),
+            require_subpaths: None,
+        }
+    }
+}
+
+const RUNTIME_CACHES: &[RuntimeCache] = &[
+    RuntimeCache {
+        runtime: Runtime::Node,
+        name: "Node Package Cache",
+        path: "~/.npm",
+        advanced: false,
+        max_depth: 5,
+        min_age_days: 7,
+        extensions: &[],
+    },
+    RuntimeCache {
+        runtime: Runtime::Python,
+        name: "Pip Cache",
+        path: "~/Library/Caches/pip",
+        advanced: false,
+        max_depth: 5,
+        min_age_days: 7,
+        extensions: &[],
+    },
+    RuntimeCache {
+        runtime: Runtime::Rust,
+        name: "Cargo Registry Cache",
+        path: "~/.cargo/registry/cache",
+        advanced: true,
+        max_depth: 6,
+        min_age_days: 14,
+        extensions: &["crate", "tar.gz", "tgz"],
+    },
+    RuntimeCache {
+        runtime: Runtime::Go,
+        name: "Go Module Cache",
+        path: "~/go/pkg/mod/cache",
+        advanced: true,
+        max_depth: 6,
+        min_age_days: 14,
+        extensions: &[],
+    },
+    RuntimeCache {
+        runtime: Runtime::CocoaPods,
+        name: "CocoaPods Cache",
+        path: "~/.cocoapods/checkouts",
+        advanced: true,
+        max_depth: 6,
+        min_age_days: 30,
+        extensions: &[],
+    },
+];
+
 /// Generates additional rules based on installed/active tools and adapts base rules to the system.
 pub struct DynamicRuleEngine {
     app_checker: AppActivityChecker,
+    /// Where project manifests are looked for to detect runtimes in use.
+    dev_roots: Vec<PathBuf>,
 }
 
 impl DynamicRuleEngine {
     pub fn new() -> Self {
+        Self::with_dev_roots(runtime_detection::default_dev_roots())
+    }
+
+    pub fn with_dev_roots(dev_roots: Vec<PathBuf>) -> Self {
         Self {
             app_checker: AppActivityChecker::new(),
+            dev_roots,
         }
     }
 
+    /// Runtimes with a project manifest under the dev roots or a running process.
+    pub fn runtimes_in_use(&self, active_tools: &HashSet<String>) -> HashSet<Runtime> {
+        let mut runtimes = runtime_detection::detect_runtimes(&self.dev_roots);
+        runtimes.extend(Runtime::ALL.into_iter().filter(|runtime| {
+            runtime
+                .process_names()
+                .iter()
+                .any(|name| active_tools.contains(*name))
+        }));
+        runtimes
+    }
+
     /// Generate app-specific rules opportunistically. These are conservative and marked safe.
-    pub fn generate_app_specific_rules(&self) -> Vec<CategoryRule> {
+    pub fn generate_app_specific_rules(
+        &self,
+        process_snapshot: &ProcessSnapshot,
+    ) -> Vec<CategoryRule> {
         let mut rules = Vec::new();
-        let active = self.app_checker.get_active_development_tools();
+        let active = self
+            .app_checker
+            .get_active_development_tools(process_snapshot);
         let lower: HashSet<String> = active.into_iter().map(|s| s.to_lowercase()).collect();
 
         // Helper to check if path exists
@@ -45,88 +149,13 @@ impl DynamicRuleEngine {
             });
         }
 
-        // Node / npm / yarn
-        if lower.contains("node") || path_exists("~/.npm") {
-            rules.push(CategoryRule {
-                name: "Node Package Cache".to_string(),
-                paths: vec!["~/.npm".to_string()],
-                safe: true,
-                advanced: Some(false),
-                max_depth: Some(5),
-                min_age_days: Some(7),
-                min_size_kb: None,
-                excludes: None,
-                extensions: None,
-                require_subpaths: None,
-            });
-        }
-
-        // Python / pip
-        if lower.contains("python") || path_exists("~/Library/Caches/pip") {
-            rules.push(CategoryRule {
-                name: "Pip Cache".to_string(),
-                paths: vec!["~/Library/Caches/pip".to_string()],
-                safe: true,
-                advanced: Some(false),
-                max_depth: Some(5),
-                min_age_days: Some(7),
-                min_size_kb: None,
-                excludes: None,
-                extensions: None,
-                require_subpaths: None,
-            });
-        }
-
-        // Rust / cargo
-        if lower.contains("cargo") || path_exists("~/.cargo/registry/cache") {
-            rules.push(CategoryRule {
-                name: "Cargo Registry Cache".to_string(),
-                paths: vec!["~/.cargo/registry/cache".to_string()],
-                safe: true,
-                advanced: Some(true),
-                max_depth: Some(6),
-                min_age_days: Some(14),
-                min_size_kb: None,
-                excludes: None,
-                extensions: Some(vec![
-                    "crate".to_string(),
-                    "tar.gz".to_string(),
-                    "tgz".to_string(),
-                ]),
-                require_subpaths: None,
-            });
-        }
-
-        // Go
-        if lower.contains("go") || path_exists("~/go/pkg/mod/cache") {
-            rules.push(CategoryRule {
-                name: "Go Module Cache".to_string(),
-                paths: vec!["~/go/pkg/mod/cache".to_string()],
-                safe: true,
-                advanced: Some(true),
-                max_depth: Some(6),
-                min_age_days: Some(14),
-                min_size_kb: None,
-                excludes: None,
-                extensions: None,
-                require_subpaths: None,
-            });
-        }
-
-        // CocoaPods
-        if path_exists("~/.cocoapods/checkouts") {
-            rules.push(CategoryRule {
-                name: "CocoaPods Cache".to_string(),
-                paths: vec!["~/.cocoapods/checkouts".to_string()],
-                safe: true,
-                advanced: Some(true),
-                max_depth: Some(6),
-                min_age_days: Some(30),
-                min_size_kb: None,
-                excludes: None,
-                extensions: None,
-                require_subpaths: None,
-            });
+        // Language runtime caches, only for runtimes that are in use and
+        // whose cache root exists
+        let runtimes = self.runtimes_in_use(&lower);
+        for cache in RUNTIME_CACHES {
+            if runtimes.contains(&cache.runtime) && path_exists(cache.path) {
+                rules.push(cache.to_rule());
+            }
         }
 
         // Docker
@@ -247,6 +276,107 @@ impl RuleValidator {
 
         DryRunReport { category_stats }
     }
+
+    /// Accept a user-defined rule only if it targets a specific location,
+    /// doesn't reuse an existing category name and doesn't contradict the
+    /// safe flag of an overlapping rule in `existing`.
+    pub fn validate_custom_rule(
+        &self,
+        rule: &CategoryRule,
+        existing: &CleanerRules,
+    ) -> Result<(), String> {
+        if rule.name.trim().is_empty() {
+            return Err("Rule name cannot be empty".into());
+        }
+        if existing
+            .categories
+            .iter()
+            .any(|other| other.name.eq_ignore_ascii_case(&rule.name))
+        {
+            return Err(format!("A rule named \"{}\" already exists", rule.name));
+        }
+        if rule.paths.is_empty() {
+            return Err("Rule needs at least one path".into());
+        }
+        for path in &rule.paths {
+            check_custom_rule_path(path)?;
+        }
+        if rule.max_depth == Some(0) {
+            return Err("Max depth must be at least 1".into());
+        }
+        if rule.min_age_days.is_some_and(|days| days < 0) {
+            return Err("Minimum age cannot be negative".into());
+        }
+
+        let mut combined = CleanerRules {
+            categories: existing.categories.clone(),
+        };
+        combined.categories.push(rule.clone());
+        let conflict = self
+            .validate_rule_consistency(&combined)
+            .into_iter()
+            .find_map(|conflict| match conflict {
+                RuleConflict::OverlappingPaths {
+                    rule_a,
+                    rule_b,
+                    message,
+                } if rule_a == rule.name || rule_b == rule.name => {
+                    let other = if rule_a == rule.name { rule_b } else { rule_a };
+                    Some(format!("Conflicts with \"{}\": {}", other, message))
+                }
+                RuleConflict::OverlappingPaths { .. } => None,
+            });
+        match conflict {
+            Some(message) => Err(message),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Locations too broad or too sensitive for a user rule to scan.
+const PROTECTED_RULE_ROOTS: &[&str] = &[
+    "/",
+    "~",
+    "/Applications",
+    "/Library",
+    "/Users",
+    "/Volumes",
+    "/private",
+    "/private/var",
+    "~/Library",
+    "~/Documents",
+    "~/Desktop",
+    "~/Pictures",
+    "~/Movies",
+    "~/Music",
+];
+
+/// System trees a user rule may not reach into at any depth.
+const FORBIDDEN_RULE_PREFIXES: &[&str] = &["/System", "/usr", "/bin", "/sbin", "/etc", "/cores"];
+
+fn check_custom_rule_path(path: &str) -> Result<(), String> {
+    if !(path == "~" || path.starts_with("~/") || path.starts_with('/')) {
+        return Err(format!("{} must be absolute or start with ~/", path));
+    }
+    if path.split('/').any(|part| part == "..") {
+        return Err(format!("{} must not contain ..", path));
+    }
+    let trimmed = path.trim_end_matches('/');
+    let trimmed = if trimmed.is_empty() { "/" } else { trimmed };
+    if PROTECTED_RULE_ROOTS
+        .iter()
+        .any(|root| root.eq_ignore_ascii_case(trimmed))
+    {
+        return Err(format!("{} is too broad for a cleaning rule", path));
+    }
+    let lower = trimmed.to_lowercase();
+    if FORBIDDEN_RULE_PREFIXES.iter().any(|prefix| {
+        let prefix = prefix.to_lowercase();
+        lower == prefix || lower.starts_with(&format!("{}/", prefix))
+    }) {
+        return Err(format!("{} is a protected system location", path));
+    }
+    Ok(())
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
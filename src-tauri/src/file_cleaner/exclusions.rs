@@ -0,0 +1,230 @@
+use std::collections::HashSet;
+use std::path::Path;
+#[cfg(any(test, feature = "app"))]
+use std::path::PathBuf;
+
+#[cfg(any(test, feature = "app"))]
+use chrono::Utc;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+use crate::persistence;
+
+const EXCLUSIONS_FILE: &str = "exclusions.json";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExclusionKind {
+    /// A file or directory; covers everything inside it, and any reported
+    /// directory that contains it.
+    Path,
+    /// A glob over full paths: `*` and `?` stay within one path component,
+    /// `**` spans several.
+    Glob,
+    /// A cleaner category name, e.g. "Browser Cache".
+    Category,
+}
+
+/// Request payload for adding an exclusion.
+#[cfg(any(test, feature = "app"))]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NewExclusion {
+    pub kind: ExclusionKind,
+    pub pattern: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Exclusion {
+    pub id: String,
+    pub kind: ExclusionKind,
+    pub pattern: String,
+    pub created_at: i64,
+}
+
+/// Paths, globs and categories the user never wants suggested again.
+pub struct ExclusionStore {
+    #[cfg(any(test, feature = "app"))]
+    path: Option<PathBuf>,
+    exclusions: Vec<Exclusion>,
+}
+
+impl ExclusionStore {
+    /// Load from `<data dir>/macos-optimizer/exclusions.json`.
+    pub fn load() -> Self {
+        match persistence::data_file(EXCLUSIONS_FILE) {
+            Some(path) => Self::load_from(&path),
+            None => Self {
+                #[cfg(any(test, feature = "app"))]
+                path: None,
+                exclusions: Vec::new(),
+            },
+        }
+    }
+
+    /// Load from an explicit file; a missing or unreadable file yields an empty store.
+    pub fn load_from(path: &Path) -> Self {
+        let exclusions = persistence::read_json(path).unwrap_or_default();
+        Self {
+            #[cfg(any(test, feature = "app"))]
+            path: Some(path.to_path_buf()),
+            exclusions,
+        }
+    }
+
+    #[cfg(feature = "app")]
+    pub fn list(&self) -> Vec<Exclusion> {
+        self.exclusions.clone()
+    }
+
+    #[cfg(any(test, feature = "app"))]
+    pub fn add(&mut self, request: NewExclusion) -> Result<Exclusion, String> {
+        let pattern = match request.pattern.trim() {
+            "/" => "/",
+            pattern => pattern.trim_end_matches('/'),
+        };
+        if pattern.is_empty() {
+            return Err("Exclusion pattern cannot be empty".into());
+        }
+        match request.kind {
+            ExclusionKind::Path | ExclusionKind::Glob
+                if !(pattern.starts_with('/') || pattern.starts_with("~/")) =>
+            {
+                return Err(format!("{} must be absolute or start with ~/", pattern));
+            }
+            ExclusionKind::Glob => {
+                glob_to_regex(pattern)?;
+            }
+            _ => {}
+        }
+        if self.exclusions.iter().any(|existing| {
+            existing.kind == request.kind && existing.pattern.eq_ignore_ascii_case(pattern)
+        }) {
+            return Err(format!("{} is already excluded", pattern));
+        }
+
+        let exclusion = Exclusion {
+            id: uuid::Uuid::new_v4().to_string(),
+            kind: request.kind,
+            pattern: pattern.to_string(),
+            created_at: Utc::now().timestamp(),
+        };
+        self.exclusions.push(exclusion.clone());
+        self.persist()?;
+        Ok(exclusion)
+    }
+
+    /// Remove an exclusion; returns whether it existed.
+    #[cfg(any(test, feature = "app"))]
+    pub fn remove(&mut self, id: &str) -> Result<bool, String> {
+        let before = self.exclusions.len();
+        self.exclusions.retain(|exclusion| exclusion.id != id);
+        if self.exclusions.len() == before {
+            return Ok(false);
+        }
+        self.persist()?;
+        Ok(true)
+    }
+
+    pub(crate) fn matcher(&self) -> ExclusionMatcher {
+        let mut matcher = ExclusionMatcher::default();
+        for exclusion in &self.exclusions {
+            match exclusion.kind {
+                ExclusionKind::Path => {
+                    let expanded = expand_home(&exclusion.pattern).to_lowercase();
+                    let trimmed = expanded.trim_end_matches('/');
+                    matcher.paths.push(if trimmed.is_empty() {
+                        "/".to_string()
+                    } else {
+                        trimmed.to_string()
+                    });
+                }
+                ExclusionKind::Glob => match glob_to_regex(&exclusion.pattern) {
+                    Ok(regex) => matcher.globs.push(regex),
+                    Err(err) => log::warn!("Skipping exclusion {}: {}", exclusion.pattern, err),
+                },
+                ExclusionKind::Category => {
+                    matcher.categories.insert(exclusion.pattern.to_lowercase());
+                }
+            }
+        }
+        matcher
+    }
+
+    #[cfg(any(test, feature = "app"))]
+    fn persist(&self) -> Result<(), String> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        persistence::write_json(path, &self.exclusions)
+    }
+}
+
+/// Compiled exclusions consulted by scans. Paths compare case-insensitively,
+/// like the scanners' own de-duplication.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct ExclusionMatcher {
+    paths: Vec<String>,
+    globs: Vec<Regex>,
+    categories: HashSet<String>,
+}
+
+impl ExclusionMatcher {
+    /// The stored exclusions; an empty matcher if none can be loaded.
+    pub(crate) fn load() -> Self {
+        ExclusionStore::load().matcher()
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.paths.is_empty() && self.globs.is_empty() && self.categories.is_empty()
+    }
+
+    pub(crate) fn is_excluded(&self, path: &str, category: &str) -> bool {
+        if self.categories.contains(&category.to_lowercase()) {
+            return true;
+        }
+        let path = path.trim_end_matches('/').to_lowercase();
+        let within = |parent: &str, child: &str| {
+            child == parent
+                || parent == "/"
+                || (child.starts_with(parent) && child.as_bytes().get(parent.len()) == Some(&b'/'))
+        };
+        // Cleaning a directory that contains an excluded path would remove it too.
+        self.paths
+            .iter()
+            .any(|excluded| within(excluded, &path) || within(&path, excluded))
+            || self.globs.iter().any(|glob| glob.is_match(&path))
+    }
+}
+
+fn expand_home(path: &str) -> String {
+    if let Some(rest) = path.strip_prefix("~/") {
+        if let Some(home) = dirs::home_dir() {
+            return home.join(rest).to_string_lossy().to_string();
+        }
+    }
+    path.to_string()
+}
+
+fn glob_to_regex(pattern: &str) -> Result<Regex, String> {
+    let expanded = expand_home(pattern);
+    let mut regex = String::from("(?i)^");
+    let mut chars = expanded.trim_end_matches('/').chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '*' if chars.peek() == Some(&'*') => {
+                chars.next();
+                if chars.peek() == Some(&'/') {
+                    chars.next();
+                    regex.push_str("(?:.*/)?");
+                } else {
+                    regex.push_str(".*");
+                }
+            }
+            '*' => regex.push_str("[^/]*"),
+            '?' => regex.push_str("[^/]"),
+            c => regex.push_str(&regex::escape(&c.to_string())),
+        }
+    }
+    regex.push('$');
+    Regex::new(&regex).map_err(|e| format!("Invalid glob {}: {}", pattern, e))
+}
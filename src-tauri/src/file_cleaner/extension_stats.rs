@@ -0,0 +1,180 @@
+use std::collections::HashMap;
+use std::path::Path;
+use std::time::SystemTime;
+
+use serde::{Deserialize, Serialize};
+use tokio_util::sync::CancellationToken;
+use walkdir::WalkDir;
+
+use super::engine::FileCleaner;
+use super::types::CleanableFile;
+
+/// Upper bound on files visited inside reported directories per request.
+const MAX_WALK_FILES: usize = 500_000;
+pub const DEFAULT_DRILL_DOWN_LIMIT: usize = 200;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExtensionStat {
+    /// Lowercased, without the dot; empty for files without an extension.
+    pub extension: String,
+    pub count: usize,
+    pub total_size: u64,
+    pub median_age_days: i64,
+    /// Scan categories the files were found under.
+    pub categories: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExtensionFile {
+    pub path: String,
+    pub size: u64,
+    pub age_days: i64,
+    pub category: String,
+}
+
+/// Aggregate scan results by file extension, largest total first. Reported
+/// directories are walked so their contents are attributed too.
+pub fn extension_stats(
+    files: &[CleanableFile],
+    token: &CancellationToken,
+) -> Result<Vec<ExtensionStat>, String> {
+    struct Bucket {
+        count: usize,
+        total_size: u64,
+        ages: Vec<i64>,
+        categories: Vec<String>,
+    }
+
+    let mut buckets: HashMap<String, Bucket> = HashMap::new();
+    visit_files(files, token, |file| {
+        let bucket = buckets
+            .entry(extension_of(Path::new(&file.path)))
+            .or_insert_with(|| Bucket {
+                count: 0,
+                total_size: 0,
+                ages: Vec::new(),
+                categories: Vec::new(),
+            });
+        bucket.count += 1;
+        bucket.total_size = bucket.total_size.saturating_add(file.size);
+        bucket.ages.push(file.age_days);
+        if !bucket.categories.contains(&file.category) {
+            bucket.categories.push(file.category);
+        }
+    })?;
+
+    let mut stats: Vec<ExtensionStat> = buckets
+        .into_iter()
+        .map(|(extension, mut bucket)| {
+            bucket.ages.sort_unstable();
+            bucket.categories.sort();
+            ExtensionStat {
+                extension,
+                count: bucket.count,
+                total_size: bucket.total_size,
+                median_age_days: median(&bucket.ages),
+                categories: bucket.categories,
+            }
+        })
+        .collect();
+    stats.sort_by(|a, b| {
+        b.total_size
+            .cmp(&a.total_size)
+            .then_with(|| a.extension.cmp(&b.extension))
+    });
+    Ok(stats)
+}
+
+/// Drill-down for one extension: the largest matching files, up to `limit`.
+pub fn files_with_extension(
+    files: &[CleanableFile],
+    extension: &str,
+    limit: usize,
+    token: &CancellationToken,
+) -> Result<Vec<ExtensionFile>, String> {
+    let wanted = extension.trim().trim_start_matches('.').to_lowercase();
+    let mut matches = Vec::new();
+    visit_files(files, token, |file| {
+        if extension_of(Path::new(&file.path)) == wanted {
+            matches.push(file);
+        }
+    })?;
+    matches.sort_by(|a, b| b.size.cmp(&a.size).then_with(|| a.path.cmp(&b.path)));
+    matches.truncate(limit);
+    Ok(matches)
+}
+
+fn visit_files(
+    files: &[CleanableFile],
+    token: &CancellationToken,
+    mut visit: impl FnMut(ExtensionFile),
+) -> Result<(), String> {
+    let now = SystemTime::now();
+    let mut walked = 0usize;
+    for file in files {
+        if token.is_cancelled() {
+            return Err("cancelled".into());
+        }
+        let root = Path::new(&file.path);
+        if !root.is_dir() {
+            visit(ExtensionFile {
+                path: file.path.clone(),
+                size: file.size,
+                age_days: age_days(now, file.last_modified),
+                category: file.category.clone(),
+            });
+            continue;
+        }
+        for entry in WalkDir::new(root).into_iter().filter_map(Result::ok) {
+            if walked >= MAX_WALK_FILES {
+                log::debug!("Extension stats stopped after {} files", walked);
+                return Ok(());
+            }
+            if !entry.file_type().is_file() {
+                continue;
+            }
+            walked += 1;
+            if walked.is_multiple_of(4096) && token.is_cancelled() {
+                return Err("cancelled".into());
+            }
+            let Ok(metadata) = entry.metadata() else {
+                continue;
+            };
+            let modified = metadata
+                .modified()
+                .ok()
+                .and_then(|time| time.duration_since(SystemTime::UNIX_EPOCH).ok())
+                .map(|since| since.as_secs() as i64)
+                .unwrap_or(file.last_modified);
+            visit(ExtensionFile {
+                path: entry.path().to_string_lossy().to_string(),
+                size: FileCleaner::metadata_size_bytes(&metadata),
+                age_days: age_days(now, modified),
+                category: file.category.clone(),
+            });
+        }
+    }
+    Ok(())
+}
+
+fn extension_of(path: &Path) -> String {
+    path.extension()
+        .map(|ext| ext.to_string_lossy().to_lowercase())
+        .unwrap_or_default()
+}
+
+fn age_days(now: SystemTime, modified_secs: i64) -> i64 {
+    let now_secs = now
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map(|since| since.as_secs() as i64)
+        .unwrap_or(0);
+    ((now_secs - modified_secs) / 86_400).max(0)
+}
+
+fn median(sorted: &[i64]) -> i64 {
+    match sorted.len() {
+        0 => 0,
+        len if len % 2 == 1 => sorted[len / 2],
+        len => (sorted[len / 2 - 1] + sorted[len / 2]) / 2,
+    }
+}
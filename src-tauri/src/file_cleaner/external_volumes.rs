@@ -0,0 +1,286 @@
+// src/file_cleaner/external_volumes.rs
+
+//! Cleanup of mounted external volumes and SD cards the user opted into.
+//!
+//! The system rules are written for the startup disk; on other volumes the
+//! junk sits at fixed places relative to the mount point instead: the
+//! Spotlight index, the FSEvents log, the Trash folders of other users and
+//! `node_modules` folders of projects kept on the drive. A volume is only
+//! scanned after it passes the checks in [`check_volume`], which run again
+//! before anything on it is cleaned, since it may have been ejected or
+//! swapped in between.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+#[cfg(feature = "app")]
+use serde::{Deserialize, Serialize};
+use tokio_util::sync::CancellationToken;
+use walkdir::WalkDir;
+
+use super::engine::FileCleaner;
+use super::types::CleanableFile;
+#[cfg(feature = "app")]
+use super::volumes::{self, VolumeInfo};
+use crate::persistence;
+
+const EXTERNAL_VOLUMES_FILE: &str = "external_volumes.json";
+
+pub const VOLUME_SPOTLIGHT_CATEGORY: &str = "Volume Spotlight Index";
+pub const VOLUME_FSEVENTS_CATEGORY: &str = "Volume FSEvents Logs";
+pub const VOLUME_TRASH_CATEGORY: &str = "Volume Trash (Other Users)";
+pub const VOLUME_NODE_MODULES_CATEGORY: &str = "Volume node_modules";
+
+/// How deep below the mount point `node_modules` folders are looked for.
+const NODE_MODULES_DEPTH: usize = 8;
+/// Files at the root of a volume that mark it as a Time Machine destination.
+const TIME_MACHINE_MARKERS: &[&str] =
+    &["Backups.backupdb", ".com.apple.TimeMachine.SnapshotHistory"];
+
+/// A mounted volume other than the startup disk, as offered in settings.
+#[cfg(feature = "app")]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExternalVolume {
+    pub volume: VolumeInfo,
+    pub opted_in: bool,
+    /// Why the volume is skipped even when opted in.
+    pub blocked: Option<String>,
+}
+
+/// Mount points of the volumes the user opted into.
+#[derive(Default)]
+pub struct ExternalVolumeStore {
+    #[cfg(any(test, feature = "app"))]
+    path: Option<PathBuf>,
+    opted_in: Vec<String>,
+}
+
+impl ExternalVolumeStore {
+    /// Load from `<data dir>/macos-optimizer/external_volumes.json`.
+    pub fn load() -> Self {
+        match persistence::data_file(EXTERNAL_VOLUMES_FILE) {
+            Some(path) => Self::load_from(&path),
+            None => Self::default(),
+        }
+    }
+
+    /// Load from an explicit file; a missing or unreadable file yields an empty store.
+    pub fn load_from(path: &Path) -> Self {
+        let opted_in = persistence::read_json(path).unwrap_or_default();
+        Self {
+            #[cfg(any(test, feature = "app"))]
+            path: Some(path.to_path_buf()),
+            opted_in,
+        }
+    }
+
+    pub fn list(&self) -> Vec<String> {
+        self.opted_in.clone()
+    }
+
+    #[cfg(feature = "app")]
+    pub fn is_opted_in(&self, mount: &str) -> bool {
+        self.opted_in.iter().any(|opted| opted == mount)
+    }
+
+    /// Include the volume mounted at `mount` in system scans, or stop doing so.
+    #[cfg(any(test, feature = "app"))]
+    pub fn set_opted_in(&mut self, mount: &str, opted_in: bool) -> Result<(), String> {
+        let mount = mount.trim_end_matches('/');
+        if mount.is_empty() {
+            return Err("The startup disk is always scanned".into());
+        }
+        self.opted_in.retain(|opted| opted != mount);
+        if opted_in {
+            self.opted_in.push(mount.to_string());
+            self.opted_in.sort();
+        }
+        self.persist()
+    }
+
+    #[cfg(any(test, feature = "app"))]
+    fn persist(&self) -> Result<(), String> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        persistence::write_json(path, &self.opted_in)
+    }
+}
+
+/// The mounted volumes other than the startup disk, marked opted in when
+/// their mount point is in `opted_in`.
+#[cfg(feature = "app")]
+pub fn list_external_volumes(opted_in: &[String]) -> Vec<ExternalVolume> {
+    let volumes: Vec<VolumeInfo> = volumes::list_volumes()
+        .into_iter()
+        .filter(|volume| volume.id != "/")
+        .collect();
+    let mounted: Vec<String> = volumes.iter().map(|volume| volume.id.clone()).collect();
+    volumes
+        .into_iter()
+        .map(|volume| ExternalVolume {
+            opted_in: opted_in.contains(&volume.id),
+            blocked: check_volume(Path::new(&volume.id), &mounted).err(),
+            volume,
+        })
+        .collect()
+}
+
+/// Whether the volume at `mount` may be scanned and cleaned: it is still
+/// one of `mounted`, is not part of the startup disk, is not a Time Machine
+/// destination and can be written to.
+pub fn check_volume(mount: &Path, mounted: &[String]) -> Result<(), String> {
+    let name = mount.display();
+    if mount.parent().is_none() || mount.starts_with("/System/Volumes") {
+        return Err(format!("{} is part of the startup disk", name));
+    }
+    if !mounted.iter().any(|id| Path::new(id) == mount) || !mount.is_dir() {
+        return Err(format!("{} is no longer mounted", name));
+    }
+    if mount.to_string_lossy().contains(".timemachine")
+        || TIME_MACHINE_MARKERS
+            .iter()
+            .any(|marker| mount.join(marker).exists())
+    {
+        return Err(format!("{} is a Time Machine backup disk", name));
+    }
+    let metadata = fs::metadata(mount).map_err(|e| format!("Cannot read {}: {}", name, e))?;
+    if metadata.permissions().readonly() {
+        return Err(format!("{} is read-only", name));
+    }
+    Ok(())
+}
+
+pub(crate) fn is_volume_category(category: &str) -> bool {
+    [
+        VOLUME_SPOTLIGHT_CATEGORY,
+        VOLUME_FSEVENTS_CATEGORY,
+        VOLUME_TRASH_CATEGORY,
+        VOLUME_NODE_MODULES_CATEGORY,
+    ]
+    .contains(&category)
+}
+
+/// Everything the volume rules find under `mount`. The Trash of `uid` is
+/// left to emptying the Trash.
+pub(crate) fn scan_volume(
+    mount: &Path,
+    uid: u32,
+    token: &CancellationToken,
+) -> Result<Vec<CleanableFile>, String> {
+    let mut found = Vec::new();
+    let spotlight = mount.join(".Spotlight-V100");
+    if spotlight.is_dir() {
+        found.push(volume_item(
+            &spotlight,
+            VOLUME_SPOTLIGHT_CATEGORY,
+            "Spotlight index; rebuilt the next time the volume is indexed",
+            false,
+            token,
+        )?);
+    }
+    let fsevents = mount.join(".fseventsd");
+    if fsevents.is_dir() {
+        found.push(volume_item(
+            &fsevents,
+            VOLUME_FSEVENTS_CATEGORY,
+            "File system event log; recreated when the volume is mounted",
+            true,
+            token,
+        )?);
+    }
+    if let Ok(entries) = fs::read_dir(mount.join(".Trashes")) {
+        let own = uid.to_string();
+        let mut trashes: Vec<PathBuf> = entries
+            .filter_map(Result::ok)
+            .filter(|entry| entry.file_name() != own.as_str())
+            .map(|entry| entry.path())
+            .collect();
+        trashes.sort();
+        for trash in trashes {
+            found.push(volume_item(
+                &trash,
+                VOLUME_TRASH_CATEGORY,
+                "Trash left on this volume by another user or Mac",
+                false,
+                token,
+            )?);
+        }
+    }
+    for node_modules in find_node_modules(mount, token)? {
+        found.push(volume_item(
+            &node_modules,
+            VOLUME_NODE_MODULES_CATEGORY,
+            "Installed packages of a project; reinstall them with its package manager",
+            false,
+            token,
+        )?);
+    }
+    Ok(found)
+}
+
+/// `node_modules` folders under `mount`, without descending into them or
+/// into hidden folders.
+fn find_node_modules(mount: &Path, token: &CancellationToken) -> Result<Vec<PathBuf>, String> {
+    let mut found = Vec::new();
+    let mut walker = WalkDir::new(mount)
+        .max_depth(NODE_MODULES_DEPTH)
+        .into_iter()
+        .filter_entry(|entry| {
+            entry.depth() == 0 || !entry.file_name().to_string_lossy().starts_with('.')
+        });
+    while let Some(entry) = walker.next() {
+        if token.is_cancelled() {
+            return Err("cancelled".into());
+        }
+        let Ok(entry) = entry else {
+            continue;
+        };
+        if entry.file_type().is_dir() && entry.file_name() == "node_modules" {
+            found.push(entry.into_path());
+            walker.skip_current_dir();
+        }
+    }
+    Ok(found)
+}
+
+fn volume_item(
+    path: &Path,
+    category: &str,
+    description: &str,
+    auto_select: bool,
+    token: &CancellationToken,
+) -> Result<CleanableFile, String> {
+    let mut size = 0u64;
+    for (index, entry) in WalkDir::new(path)
+        .into_iter()
+        .filter_map(Result::ok)
+        .enumerate()
+    {
+        if index.is_multiple_of(4096) && token.is_cancelled() {
+            return Err("cancelled".into());
+        }
+        if entry.file_type().is_file() {
+            if let Ok(metadata) = entry.metadata() {
+                size = size.saturating_add(FileCleaner::metadata_size_bytes(&metadata));
+            }
+        }
+    }
+    let last_modified = fs::metadata(path)
+        .and_then(|metadata| metadata.modified())
+        .ok()
+        .and_then(|modified| modified.duration_since(UNIX_EPOCH).ok())
+        .map(|age| age.as_secs() as i64)
+        .unwrap_or(0);
+    Ok(CleanableFile {
+        path: path.to_string_lossy().to_string(),
+        size,
+        category: category.to_string(),
+        description: description.to_string(),
+        last_modified,
+        safe_to_delete: true,
+        safety_score: if auto_select { 85 } else { 70 },
+        auto_select,
+    })
+}
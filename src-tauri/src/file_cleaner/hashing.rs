@@ -0,0 +1,263 @@
+use std::fs;
+use std::io::{self, Read};
+use std::path::Path;
+#[cfg(any(test, feature = "app"))]
+use std::time::Instant;
+
+use serde::{Deserialize, Serialize};
+
+/// Read size for streaming hashes.
+const READ_CHUNK_BYTES: usize = 1024 * 1024;
+/// Buffers at least this large are hashed across the Rayon pool; below it
+/// the thread hand-off costs more than it saves.
+const PARALLEL_CHUNK_BYTES: usize = 8 * 1024 * 1024;
+#[cfg(feature = "app")]
+pub const DEFAULT_BENCHMARK_MB: u64 = 256;
+#[cfg(any(test, feature = "app"))]
+pub const MAX_BENCHMARK_MB: u64 = 2048;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HashAlgorithm {
+    /// Portable software SHA-256.
+    Sha256,
+    /// SIMD BLAKE3, multi-threaded for large files.
+    #[default]
+    Blake3,
+    /// SHA-256 from the system CommonCrypto library, hardware-accelerated on
+    /// Apple silicon (the same implementation CryptoKit wraps). macOS only.
+    CommonCrypto,
+}
+
+impl HashAlgorithm {
+    #[cfg(any(test, feature = "app"))]
+    pub const ALL: [HashAlgorithm; 3] = [
+        HashAlgorithm::Sha256,
+        HashAlgorithm::Blake3,
+        HashAlgorithm::CommonCrypto,
+    ];
+
+    pub fn is_available(self) -> bool {
+        match self {
+            HashAlgorithm::CommonCrypto => cfg!(target_os = "macos"),
+            HashAlgorithm::Sha256 | HashAlgorithm::Blake3 => true,
+        }
+    }
+}
+
+enum Hasher {
+    Sha256(sha2::Sha256),
+    Blake3(Box<blake3::Hasher>),
+    #[cfg(target_os = "macos")]
+    CommonCrypto(common_crypto::Sha256),
+}
+
+impl Hasher {
+    fn new(algorithm: HashAlgorithm) -> io::Result<Self> {
+        use sha2::Digest;
+        match algorithm {
+            HashAlgorithm::Sha256 => Ok(Hasher::Sha256(sha2::Sha256::new())),
+            HashAlgorithm::Blake3 => Ok(Hasher::Blake3(Box::new(blake3::Hasher::new()))),
+            #[cfg(target_os = "macos")]
+            HashAlgorithm::CommonCrypto => Ok(Hasher::CommonCrypto(common_crypto::Sha256::new())),
+            #[cfg(not(target_os = "macos"))]
+            HashAlgorithm::CommonCrypto => Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                "CommonCrypto is only available on macOS",
+            )),
+        }
+    }
+
+    fn update(&mut self, data: &[u8]) {
+        use sha2::Digest;
+        match self {
+            Hasher::Sha256(hasher) => hasher.update(data),
+            Hasher::Blake3(hasher) if data.len() >= PARALLEL_CHUNK_BYTES => {
+                hasher.update_rayon(data);
+            }
+            Hasher::Blake3(hasher) => {
+                hasher.update(data);
+            }
+            #[cfg(target_os = "macos")]
+            Hasher::CommonCrypto(hasher) => hasher.update(data),
+        }
+    }
+
+    fn finalize(self) -> String {
+        use sha2::Digest;
+        match self {
+            Hasher::Sha256(hasher) => format!("{:x}", hasher.finalize()),
+            Hasher::Blake3(hasher) => hasher.finalize().to_hex().to_string(),
+            #[cfg(target_os = "macos")]
+            Hasher::CommonCrypto(hasher) => hasher.finalize(),
+        }
+    }
+}
+
+pub fn hash_bytes(algorithm: HashAlgorithm, data: &[u8]) -> io::Result<String> {
+    let mut hasher = Hasher::new(algorithm)?;
+    hasher.update(data);
+    Ok(hasher.finalize())
+}
+
+/// Hash the whole file. Large files are read in chunks big enough for
+/// BLAKE3 to spread each one across cores.
+pub fn hash_file(algorithm: HashAlgorithm, path: &Path) -> io::Result<String> {
+    let mut file = fs::File::open(path)?;
+    let len = file.metadata()?.len();
+    let chunk = if algorithm == HashAlgorithm::Blake3 && len >= PARALLEL_CHUNK_BYTES as u64 {
+        PARALLEL_CHUNK_BYTES
+    } else {
+        READ_CHUNK_BYTES
+    };
+    let mut hasher = Hasher::new(algorithm)?;
+    let mut buffer = vec![0u8; chunk];
+    loop {
+        let filled = read_full(&mut file, &mut buffer)?;
+        if filled == 0 {
+            break;
+        }
+        hasher.update(&buffer[..filled]);
+    }
+    Ok(hasher.finalize())
+}
+
+/// Fill `buffer` unless EOF comes first, so parallel hashing sees full chunks.
+fn read_full(reader: &mut impl Read, buffer: &mut [u8]) -> io::Result<usize> {
+    let mut filled = 0;
+    while filled < buffer.len() {
+        match reader.read(&mut buffer[filled..]) {
+            Ok(0) => break,
+            Ok(read) => filled += read,
+            Err(err) if err.kind() == io::ErrorKind::Interrupted => continue,
+            Err(err) => return Err(err),
+        }
+    }
+    Ok(filled)
+}
+
+#[cfg(any(test, feature = "app"))]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HashBenchmark {
+    pub algorithm: HashAlgorithm,
+    pub available: bool,
+    pub bytes: u64,
+    pub duration_ms: u64,
+    pub throughput_mb_s: f64,
+}
+
+/// Hash an in-memory buffer of `size_mb` with every algorithm so throughput
+/// reflects hashing alone, not disk speed.
+#[cfg(any(test, feature = "app"))]
+pub fn benchmark_hashing(size_mb: u64) -> Vec<HashBenchmark> {
+    let size_mb = size_mb.clamp(1, MAX_BENCHMARK_MB);
+    let data = benchmark_data((size_mb * 1024 * 1024) as usize);
+    HashAlgorithm::ALL
+        .into_iter()
+        .map(|algorithm| {
+            let start = Instant::now();
+            let available = algorithm.is_available() && hash_chunked(algorithm, &data).is_ok();
+            let elapsed = start.elapsed();
+            let seconds = elapsed.as_secs_f64();
+            HashBenchmark {
+                algorithm,
+                available,
+                bytes: data.len() as u64,
+                duration_ms: elapsed.as_millis() as u64,
+                throughput_mb_s: if available && seconds > 0.0 {
+                    size_mb as f64 / seconds
+                } else {
+                    0.0
+                },
+            }
+        })
+        .collect()
+}
+
+/// Feed `data` in the same chunk sizes `hash_file` uses.
+#[cfg(any(test, feature = "app"))]
+fn hash_chunked(algorithm: HashAlgorithm, data: &[u8]) -> io::Result<String> {
+    let chunk = if algorithm == HashAlgorithm::Blake3 {
+        PARALLEL_CHUNK_BYTES
+    } else {
+        READ_CHUNK_BYTES
+    };
+    let mut hasher = Hasher::new(algorithm)?;
+    for part in data.chunks(chunk) {
+        hasher.update(part);
+    }
+    Ok(hasher.finalize())
+}
+
+/// Incompressible filler so no algorithm benefits from trivial input.
+#[cfg(any(test, feature = "app"))]
+fn benchmark_data(len: usize) -> Vec<u8> {
+    let mut state = 0x9E37_79B9_7F4A_7C15u64;
+    let mut data = Vec::with_capacity(len);
+    while data.len() < len {
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        data.extend_from_slice(&state.to_le_bytes());
+    }
+    data.truncate(len);
+    data
+}
+
+#[cfg(target_os = "macos")]
+mod common_crypto {
+    use std::os::raw::{c_int, c_void};
+
+    const DIGEST_LEN: usize = 32;
+
+    /// Mirrors `CC_SHA256_CTX` from <CommonCrypto/CommonDigest.h>.
+    #[repr(C)]
+    struct Context {
+        count: [u32; 2],
+        hash: [u32; 8],
+        wbuf: [u32; 16],
+    }
+
+    // libSystem exports CommonCrypto, so no extra link directive is needed.
+    extern "C" {
+        fn CC_SHA256_Init(ctx: *mut Context) -> c_int;
+        fn CC_SHA256_Update(ctx: *mut Context, data: *const c_void, len: u32) -> c_int;
+        fn CC_SHA256_Final(md: *mut u8, ctx: *mut Context) -> c_int;
+    }
+
+    pub(super) struct Sha256(Box<Context>);
+
+    impl Sha256 {
+        pub(super) fn new() -> Self {
+            let mut ctx = Box::new(Context {
+                count: [0; 2],
+                hash: [0; 8],
+                wbuf: [0; 16],
+            });
+            // SAFETY: ctx points to a properly sized, owned CC_SHA256_CTX.
+            unsafe {
+                CC_SHA256_Init(&mut *ctx);
+            }
+            Self(ctx)
+        }
+
+        pub(super) fn update(&mut self, data: &[u8]) {
+            // CC_LONG is 32-bit, so large buffers go in pieces.
+            for part in data.chunks(u32::MAX as usize) {
+                // SAFETY: part is a valid slice and its length fits in u32.
+                unsafe {
+                    CC_SHA256_Update(&mut *self.0, part.as_ptr().cast(), part.len() as u32);
+                }
+            }
+        }
+
+        pub(super) fn finalize(mut self) -> String {
+            let mut digest = [0u8; DIGEST_LEN];
+            // SAFETY: digest has room for CC_SHA256_DIGEST_LENGTH bytes.
+            unsafe {
+                CC_SHA256_Final(digest.as_mut_ptr(), &mut *self.0);
+            }
+            digest.iter().map(|byte| format!("{:02x}", byte)).collect()
+        }
+    }
+}
@@ -0,0 +1,444 @@
+// src/file_cleaner/incremental.rs
+
+//! Repeat scans that rewalk only what changed.
+//!
+//! After a scan, an FSEvents stream watches the scanned roots and records
+//! every directory whose contents change. The next incremental scan drops
+//! the cached results under those directories and rewalks just the
+//! top-level folder of the root each change falls in. Without FSEvents, or
+//! when events were dropped, the rules changed or the cache is too old, it
+//! walks everything again.
+
+use std::collections::{HashMap, HashSet};
+use std::fmt::Debug;
+use std::fs;
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use super::path_alias::dedupe_key;
+use super::types::CleanableFile;
+
+/// Cached results older than this are rewalked in full: age-based rules
+/// start matching files without anything on disk changing.
+pub(crate) const MAX_CACHE_AGE: Duration = Duration::from_secs(30 * 60);
+
+#[derive(Debug, Default)]
+struct ChangeLog {
+    /// Dedupe keys of directories whose contents changed.
+    dirs: HashSet<String>,
+    /// Events were lost, so any directory may have changed.
+    overflowed: bool,
+}
+
+/// Directories changed under the watched roots since the last `take`.
+pub struct ChangeTracker {
+    log: Arc<Mutex<ChangeLog>>,
+    /// Whether anything feeds the log; without it every change is unknown.
+    live: bool,
+    #[cfg(target_os = "macos")]
+    _stream: Option<fsevents::EventStream>,
+}
+
+impl ChangeTracker {
+    /// Start watching `roots`. Where FSEvents is unavailable the tracker
+    /// never reports changes, so every scan walks everything.
+    pub fn watch(roots: &[PathBuf]) -> Self {
+        let log = Arc::new(Mutex::new(ChangeLog::default()));
+        #[cfg(target_os = "macos")]
+        {
+            let stream = fsevents::EventStream::start(roots, &log)
+                .map_err(|err| log::warn!("Incremental scans disabled: {}", err))
+                .ok();
+            Self {
+                log,
+                live: stream.is_some(),
+                _stream: stream,
+            }
+        }
+        #[cfg(not(target_os = "macos"))]
+        {
+            let _ = roots;
+            Self { log, live: false }
+        }
+    }
+
+    /// A tracker fed only through `record`.
+    #[cfg(test)]
+    pub(crate) fn manual() -> Self {
+        Self {
+            log: Arc::new(Mutex::new(ChangeLog::default())),
+            live: true,
+            #[cfg(target_os = "macos")]
+            _stream: None,
+        }
+    }
+
+    #[cfg(test)]
+    pub(crate) fn record(&self, dir: &Path) {
+        if let Ok(mut log) = self.log.lock() {
+            log.dirs.insert(dedupe_key(&dir.to_string_lossy()));
+        }
+    }
+
+    /// Changed directories since the last call, or `None` when they are
+    /// not known and everything has to be walked again.
+    pub(crate) fn take(&self) -> Option<HashSet<String>> {
+        let mut log = self.log.lock().ok()?;
+        let log = std::mem::take(&mut *log);
+        (self.live && !log.overflowed).then_some(log.dirs)
+    }
+}
+
+/// Results of the last walk, kept for the next incremental scan.
+pub(crate) struct ScanCache {
+    fingerprint: u64,
+    roots: Vec<PathBuf>,
+    /// When everything was last walked.
+    walked_at: Instant,
+    tracker: ChangeTracker,
+    /// Matches before exclusions, which are applied per scan.
+    pub(crate) files: Vec<CleanableFile>,
+    pub(crate) dir_prefixes: Vec<String>,
+}
+
+impl ScanCache {
+    /// A fresh cache for a full walk of `roots`, watching them from now on.
+    pub(crate) fn start(fingerprint: u64, roots: Vec<PathBuf>) -> Self {
+        let tracker = ChangeTracker::watch(&roots);
+        Self::with_tracker(fingerprint, roots, tracker)
+    }
+
+    pub(crate) fn with_tracker(
+        fingerprint: u64,
+        roots: Vec<PathBuf>,
+        tracker: ChangeTracker,
+    ) -> Self {
+        Self {
+            fingerprint,
+            roots,
+            walked_at: Instant::now(),
+            tracker,
+            files: Vec::new(),
+            dir_prefixes: Vec::new(),
+        }
+    }
+
+    /// Identifies the rules and settings results were produced under.
+    pub(crate) fn fingerprint(inputs: &impl Debug) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        format!("{:?}", inputs).hash(&mut hasher);
+        hasher.finish()
+    }
+
+    /// Directories changed since the cached walk, or `None` when the cache
+    /// cannot be reused for a scan of `roots` under `fingerprint`.
+    pub(crate) fn changes(&self, fingerprint: u64, roots: &[PathBuf]) -> Option<HashSet<String>> {
+        if self.fingerprint != fingerprint
+            || self.roots != roots
+            || self.walked_at.elapsed() > MAX_CACHE_AGE
+        {
+            return None;
+        }
+        self.tracker.take()
+    }
+}
+
+/// Whether dedupe key `key` is `prefix` or lies beneath it.
+pub(crate) fn is_within(key: &str, prefix: &str) -> bool {
+    key.strip_prefix(prefix)
+        .is_some_and(|rest| rest.is_empty() || rest.starts_with('/'))
+}
+
+/// Parts of `root` to rewalk for the `changed` directories, as the part's
+/// dedupe key and its path on disk, `None` once it no longer exists. A
+/// change inside a folder directly under the root invalidates that folder;
+/// a change to the root itself or above it, or anywhere when
+/// `root_matched` says the root is itself a result, invalidates the root.
+pub(crate) fn invalidated_subtrees(
+    root: &Path,
+    changed: &HashSet<String>,
+    root_matched: bool,
+) -> Vec<(String, Option<PathBuf>)> {
+    let root_key = dedupe_key(&root.to_string_lossy());
+    let whole_root = || vec![(root_key.clone(), root.exists().then(|| root.to_path_buf()))];
+    let mut children: HashSet<&str> = HashSet::new();
+    for dir in changed {
+        if is_within(&root_key, dir) {
+            return whole_root();
+        }
+        if is_within(dir, &root_key) {
+            if root_matched {
+                return whole_root();
+            }
+            if let Some(child) = dir[root_key.len()..]
+                .split('/')
+                .find(|part| !part.is_empty())
+            {
+                children.insert(child);
+            }
+        }
+    }
+    if children.is_empty() {
+        return Vec::new();
+    }
+    // Dedupe keys are lowercased; walk the folder under its real name.
+    let on_disk: HashMap<String, PathBuf> = fs::read_dir(root)
+        .map(|entries| {
+            entries
+                .filter_map(|entry| entry.ok())
+                .map(|entry| {
+                    (
+                        entry.file_name().to_string_lossy().to_lowercase(),
+                        entry.path(),
+                    )
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    let mut subtrees: Vec<(String, Option<PathBuf>)> = children
+        .into_iter()
+        .map(|child| {
+            (
+                format!("{}/{}", root_key, child),
+                on_disk.get(child).cloned(),
+            )
+        })
+        .collect();
+    subtrees.sort();
+    subtrees
+}
+
+#[cfg(target_os = "macos")]
+mod fsevents {
+    //! Just enough of the FSEvents API for one stream of directory-level
+    //! events delivered on its own dispatch queue.
+
+    use std::ffi::{c_void, CStr, CString};
+    use std::os::raw::c_char;
+    use std::os::unix::ffi::OsStrExt;
+    use std::path::PathBuf;
+    use std::ptr;
+    use std::sync::{Arc, Mutex};
+
+    use super::ChangeLog;
+    use crate::file_cleaner::path_alias::dedupe_key;
+
+    type CFIndex = isize;
+    type CFTypeRef = *const c_void;
+    type FSEventStreamRef = *mut c_void;
+    type DispatchQueue = *mut c_void;
+    type Callback =
+        extern "C" fn(FSEventStreamRef, *mut c_void, usize, *mut c_void, *const u32, *const u64);
+
+    const CF_STRING_ENCODING_UTF8: u32 = 0x0800_0100;
+    const EVENT_ID_SINCE_NOW: u64 = u64::MAX;
+    const CREATE_FLAG_NONE: u32 = 0;
+    const EVENT_FLAG_USER_DROPPED: u32 = 0x02;
+    const EVENT_FLAG_KERNEL_DROPPED: u32 = 0x04;
+    const EVENT_FLAG_ROOT_CHANGED: u32 = 0x20;
+    /// Seconds FSEvents may hold events back to coalesce them.
+    const LATENCY: f64 = 0.5;
+
+    #[repr(C)]
+    struct CFArrayCallBacks {
+        version: CFIndex,
+        retain: *const c_void,
+        release: *const c_void,
+        copy_description: *const c_void,
+        equal: *const c_void,
+    }
+
+    #[repr(C)]
+    struct FSEventStreamContext {
+        version: CFIndex,
+        info: *mut c_void,
+        retain: Option<extern "C" fn(*const c_void) -> *const c_void>,
+        release: Option<extern "C" fn(*const c_void)>,
+        copy_description: Option<extern "C" fn(*const c_void) -> CFTypeRef>,
+    }
+
+    #[link(name = "CoreFoundation", kind = "framework")]
+    extern "C" {
+        static kCFTypeArrayCallBacks: CFArrayCallBacks;
+        fn CFStringCreateWithCString(
+            allocator: CFTypeRef,
+            string: *const c_char,
+            encoding: u32,
+        ) -> CFTypeRef;
+        fn CFArrayCreate(
+            allocator: CFTypeRef,
+            values: *const CFTypeRef,
+            count: CFIndex,
+            callbacks: *const CFArrayCallBacks,
+        ) -> CFTypeRef;
+        fn CFRelease(object: CFTypeRef);
+    }
+
+    #[link(name = "CoreServices", kind = "framework")]
+    extern "C" {
+        fn FSEventStreamCreate(
+            allocator: CFTypeRef,
+            callback: Callback,
+            context: *const FSEventStreamContext,
+            paths: CFTypeRef,
+            since_when: u64,
+            latency: f64,
+            flags: u32,
+        ) -> FSEventStreamRef;
+        fn FSEventStreamSetDispatchQueue(stream: FSEventStreamRef, queue: DispatchQueue);
+        fn FSEventStreamStart(stream: FSEventStreamRef) -> u8;
+        fn FSEventStreamStop(stream: FSEventStreamRef);
+        fn FSEventStreamInvalidate(stream: FSEventStreamRef);
+        fn FSEventStreamRelease(stream: FSEventStreamRef);
+    }
+
+    extern "C" {
+        fn dispatch_queue_create(label: *const c_char, attributes: *const c_void) -> DispatchQueue;
+        fn dispatch_release(object: *mut c_void);
+    }
+
+    /// A started stream; dropping it stops the events.
+    pub(super) struct EventStream {
+        stream: FSEventStreamRef,
+        queue: DispatchQueue,
+    }
+
+    // SAFETY: the stream and queue are only used through FSEvents and
+    // dispatch calls, which may be made from any thread.
+    unsafe impl Send for EventStream {}
+    unsafe impl Sync for EventStream {}
+
+    impl EventStream {
+        pub(super) fn start(
+            roots: &[PathBuf],
+            log: &Arc<Mutex<ChangeLog>>,
+        ) -> Result<Self, String> {
+            let paths: Vec<CString> = roots
+                .iter()
+                .filter_map(|root| CString::new(root.as_os_str().as_bytes()).ok())
+                .collect();
+            if paths.is_empty() {
+                return Err("no directories to watch".into());
+            }
+            // SAFETY: every CoreFoundation object created here is released
+            // once the stream holds its own reference, and the stream
+            // retains the change log for as long as it can call back.
+            unsafe {
+                let strings: Vec<CFTypeRef> = paths
+                    .iter()
+                    .map(|path| {
+                        CFStringCreateWithCString(
+                            ptr::null(),
+                            path.as_ptr(),
+                            CF_STRING_ENCODING_UTF8,
+                        )
+                    })
+                    .filter(|string| !string.is_null())
+                    .collect();
+                let array = CFArrayCreate(
+                    ptr::null(),
+                    strings.as_ptr(),
+                    strings.len() as CFIndex,
+                    &kCFTypeArrayCallBacks,
+                );
+                for string in strings {
+                    CFRelease(string);
+                }
+                if array.is_null() {
+                    return Err("could not build the watched path list".into());
+                }
+                let context = FSEventStreamContext {
+                    version: 0,
+                    info: Arc::as_ptr(log) as *mut c_void,
+                    retain: Some(retain_log),
+                    release: Some(release_log),
+                    copy_description: None,
+                };
+                let stream = FSEventStreamCreate(
+                    ptr::null(),
+                    on_events,
+                    &context,
+                    array,
+                    EVENT_ID_SINCE_NOW,
+                    LATENCY,
+                    CREATE_FLAG_NONE,
+                );
+                CFRelease(array);
+                if stream.is_null() {
+                    return Err("FSEventStreamCreate failed".into());
+                }
+                let queue =
+                    dispatch_queue_create(c"macos-optimizer.fsevents".as_ptr(), ptr::null());
+                FSEventStreamSetDispatchQueue(stream, queue);
+                let started = Self { stream, queue };
+                if FSEventStreamStart(stream) == 0 {
+                    return Err("FSEventStreamStart failed".into());
+                }
+                Ok(started)
+            }
+        }
+    }
+
+    impl Drop for EventStream {
+        fn drop(&mut self) {
+            // SAFETY: `stream` and `queue` were created in `start` and are
+            // released exactly once; stopping an unstarted stream is a no-op.
+            unsafe {
+                FSEventStreamStop(self.stream);
+                FSEventStreamInvalidate(self.stream);
+                FSEventStreamRelease(self.stream);
+                dispatch_release(self.queue);
+            }
+        }
+    }
+
+    extern "C" fn retain_log(info: *const c_void) -> *const c_void {
+        // SAFETY: `info` came from `Arc::as_ptr` on a live change log.
+        unsafe { Arc::increment_strong_count(info as *const Mutex<ChangeLog>) };
+        info
+    }
+
+    extern "C" fn release_log(info: *const c_void) {
+        // SAFETY: balances the reference taken in `retain_log`.
+        unsafe { Arc::decrement_strong_count(info as *const Mutex<ChangeLog>) };
+    }
+
+    extern "C" fn on_events(
+        _stream: FSEventStreamRef,
+        info: *mut c_void,
+        count: usize,
+        paths: *mut c_void,
+        flags: *const u32,
+        _ids: *const u64,
+    ) {
+        // SAFETY: `info` is the change log the stream retains; without
+        // `kFSEventStreamCreateFlagUseCFTypes`, `paths` is `count` C strings
+        // and `flags` has one entry per path.
+        let (log, paths, flags) = unsafe {
+            (
+                &*(info as *const Mutex<ChangeLog>),
+                std::slice::from_raw_parts(paths as *const *const c_char, count),
+                std::slice::from_raw_parts(flags, count),
+            )
+        };
+        let Ok(mut log) = log.lock() else {
+            return;
+        };
+        for (&path, &flag) in paths.iter().zip(flags) {
+            if flag
+                & (EVENT_FLAG_USER_DROPPED | EVENT_FLAG_KERNEL_DROPPED | EVENT_FLAG_ROOT_CHANGED)
+                != 0
+            {
+                log.overflowed = true;
+                continue;
+            }
+            // SAFETY: FSEvents passes NUL-terminated paths.
+            let path = unsafe { CStr::from_ptr(path) }.to_string_lossy();
+            // A "must scan subdirectories" event names the folder to
+            // rescan, which is how subtrees are invalidated anyway.
+            log.dirs.insert(dedupe_key(&path));
+        }
+    }
+}
@@ -0,0 +1,368 @@
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::time::SystemTime;
+
+use futures::stream::{self, StreamExt};
+use serde::{Deserialize, Serialize};
+use tokio::io::AsyncWriteExt;
+use tokio::process::Command;
+use tokio_util::sync::CancellationToken;
+use walkdir::WalkDir;
+
+use super::exclusions::ExclusionMatcher;
+use super::types::load_age_thresholds;
+use crate::capabilities::{tool_available, Tool};
+
+/// Same scope as the "Old Installers (30d+)" cleaning rule.
+pub const INSTALLER_CATEGORY: &str = "Old Installers (30d+)";
+const INSTALLER_EXTENSIONS: &[&str] = &["dmg", "pkg", "zip"];
+const INSTALLER_MIN_AGE_DAYS: i64 = 30;
+const DOWNLOADS_MAX_DEPTH: usize = 2;
+/// Extended attribute Safari, Chrome and Firefox set on downloads: a binary
+/// plist array of the download URL followed by the referring page.
+const WHERE_FROMS_XATTR: &str = "com.apple.metadata:kMDItemWhereFroms";
+const HEAD_TIMEOUT_SECS: &str = "10";
+const CONCURRENT_CHECKS: usize = 4;
+
+/// Outcome of checking an installer against its download URL.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SourceCheck {
+    /// No download URL was recorded for the file.
+    NoSource,
+    /// The tools needed for the check are missing.
+    Unchecked,
+    /// The URL no longer answers with a success status.
+    Unreachable,
+    /// The server now serves a different file.
+    Changed,
+    /// The server reports the same length but no comparable hash.
+    SizeMatch,
+    /// The server's MD5 ETag matches the local file.
+    HashMatch,
+}
+
+impl SourceCheck {
+    pub fn redownloadable(self) -> bool {
+        matches!(self, SourceCheck::SizeMatch | SourceCheck::HashMatch)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InstallerSource {
+    pub path: String,
+    pub name: String,
+    pub size: u64,
+    pub last_modified: i64,
+    pub source_url: Option<String>,
+    /// Page the download was started from.
+    pub referrer_url: Option<String>,
+    pub check: SourceCheck,
+    /// The same file can be fetched again from `source_url`.
+    pub redownloadable: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InstallerSourceReport {
+    pub installers: Vec<InstallerSource>,
+    pub total_size: u64,
+    pub redownloadable_size: u64,
+}
+
+/// Headers of the final response of a `HEAD` request, after redirects.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct HeadResponse {
+    pub status: u16,
+    pub content_length: Option<u64>,
+    pub etag: Option<String>,
+}
+
+/// Old installers in `~/Downloads` with where they were downloaded from and
+/// whether that URL still serves the same file.
+pub async fn scan_installer_sources(
+    token: &CancellationToken,
+) -> Result<InstallerSourceReport, String> {
+    let home = dirs::home_dir().ok_or("Could not determine home directory")?;
+    let exclusions = ExclusionMatcher::load();
+    let min_age_days = load_age_thresholds()
+        .days_for(INSTALLER_CATEGORY)
+        .unwrap_or(INSTALLER_MIN_AGE_DAYS);
+    let now = chrono::Utc::now().timestamp();
+    let scan_token = token.clone();
+    let candidates = tokio::task::spawn_blocking(move || {
+        find_installers(
+            &home.join("Downloads"),
+            now,
+            min_age_days,
+            &exclusions,
+            &scan_token,
+        )
+    })
+    .await
+    .map_err(|e| format!("Installer scan task failed: {}", e))??;
+
+    let can_read_sources = tool_available(Tool::Xattr) && tool_available(Tool::Plutil);
+    let can_check = tool_available(Tool::Curl);
+    let mut installers: Vec<InstallerSource> = stream::iter(candidates)
+        .map(|installer| check_installer(installer, can_read_sources, can_check, token))
+        .buffer_unordered(CONCURRENT_CHECKS)
+        .collect()
+        .await;
+    if token.is_cancelled() {
+        return Err("cancelled".into());
+    }
+    installers.sort_by(|a, b| b.size.cmp(&a.size).then_with(|| a.path.cmp(&b.path)));
+    Ok(InstallerSourceReport {
+        total_size: installers.iter().map(|installer| installer.size).sum(),
+        redownloadable_size: installers
+            .iter()
+            .filter(|installer| installer.redownloadable)
+            .map(|installer| installer.size)
+            .sum(),
+        installers,
+    })
+}
+
+/// Installers under `downloads` last modified more than `min_age_days`
+/// before `now`.
+pub(crate) fn find_installers(
+    downloads: &Path,
+    now: i64,
+    min_age_days: i64,
+    exclusions: &ExclusionMatcher,
+    token: &CancellationToken,
+) -> Result<Vec<InstallerSource>, String> {
+    let cutoff = now - min_age_days * 24 * 60 * 60;
+    let mut found = Vec::new();
+    for entry in WalkDir::new(downloads)
+        .max_depth(DOWNLOADS_MAX_DEPTH)
+        .into_iter()
+        .filter_map(Result::ok)
+        .filter(|entry| entry.file_type().is_file())
+    {
+        if token.is_cancelled() {
+            return Err("cancelled".into());
+        }
+        let is_installer = entry
+            .path()
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .is_some_and(|ext| INSTALLER_EXTENSIONS.contains(&ext.to_lowercase().as_str()));
+        let Ok(metadata) = entry.metadata() else {
+            continue;
+        };
+        let last_modified = metadata
+            .modified()
+            .ok()
+            .and_then(|time| time.duration_since(SystemTime::UNIX_EPOCH).ok())
+            .map_or(now, |since| since.as_secs() as i64);
+        let path = entry.path().to_string_lossy().to_string();
+        if !is_installer
+            || last_modified > cutoff
+            || exclusions.is_excluded(&path, INSTALLER_CATEGORY)
+        {
+            continue;
+        }
+        found.push(InstallerSource {
+            name: entry.file_name().to_string_lossy().to_string(),
+            path,
+            size: metadata.len(),
+            last_modified,
+            source_url: None,
+            referrer_url: None,
+            check: SourceCheck::NoSource,
+            redownloadable: false,
+        });
+    }
+    Ok(found)
+}
+
+async fn check_installer(
+    mut installer: InstallerSource,
+    can_read_sources: bool,
+    can_check: bool,
+    token: &CancellationToken,
+) -> InstallerSource {
+    if token.is_cancelled() || !can_read_sources {
+        installer.check = SourceCheck::Unchecked;
+        return installer;
+    }
+    let path = PathBuf::from(&installer.path);
+    let mut urls = read_where_froms(&path).await.into_iter();
+    installer.source_url = urls.next().filter(|url| is_http_url(url));
+    installer.referrer_url = urls.next();
+    let Some(url) = installer.source_url.clone() else {
+        return installer;
+    };
+    if !can_check {
+        installer.check = SourceCheck::Unchecked;
+        return installer;
+    }
+
+    installer.check = match head(&url, token).await {
+        None => SourceCheck::Unreachable,
+        Some(response) => {
+            let local_md5 = if needs_hash(installer.size, &response) && tool_available(Tool::Md5) {
+                file_md5(&path).await
+            } else {
+                None
+            };
+            classify(installer.size, &response, local_md5.as_deref())
+        }
+    };
+    installer.redownloadable = installer.check.redownloadable();
+    installer
+}
+
+/// Compare the local file with the server's answer. `local_md5` is only
+/// consulted when the response carries an MD5-style ETag.
+pub fn classify(size: u64, response: &HeadResponse, local_md5: Option<&str>) -> SourceCheck {
+    if !(200..300).contains(&response.status) {
+        return SourceCheck::Unreachable;
+    }
+    if response.content_length.is_some_and(|length| length != size) {
+        return SourceCheck::Changed;
+    }
+    if let (Some(etag), Some(local)) = (response.etag.as_deref().and_then(md5_etag), local_md5) {
+        return if etag.eq_ignore_ascii_case(local) {
+            SourceCheck::HashMatch
+        } else {
+            SourceCheck::Changed
+        };
+    }
+    if response.content_length == Some(size) {
+        SourceCheck::SizeMatch
+    } else {
+        SourceCheck::Unchecked
+    }
+}
+
+fn needs_hash(size: u64, response: &HeadResponse) -> bool {
+    response.etag.as_deref().and_then(md5_etag).is_some()
+        && response.content_length.is_none_or(|length| length == size)
+}
+
+/// The digest from a strong ETag that is a plain MD5, as S3 and most CDNs
+/// send for single-part uploads. Multipart (`"…-3"`) and weak ETags are not
+/// content hashes.
+pub fn md5_etag(etag: &str) -> Option<&str> {
+    let etag = etag.trim();
+    if etag.starts_with("W/") {
+        return None;
+    }
+    let digest = etag.trim_matches('"');
+    (digest.len() == 32 && digest.chars().all(|c| c.is_ascii_hexdigit())).then_some(digest)
+}
+
+/// Parse `curl -sIL` output, keeping the last response of a redirect chain.
+pub fn parse_head_response(output: &str) -> Option<HeadResponse> {
+    let mut last = None;
+    for line in output.lines() {
+        let line = line.trim_end_matches('\r');
+        if line.starts_with("HTTP/") {
+            let status = line.split_whitespace().nth(1)?.parse().ok()?;
+            last = Some(HeadResponse {
+                status,
+                ..Default::default()
+            });
+            continue;
+        }
+        let (Some(response), Some((name, value))) = (last.as_mut(), line.split_once(':')) else {
+            continue;
+        };
+        let value = value.trim();
+        match name.trim().to_ascii_lowercase().as_str() {
+            "content-length" => response.content_length = value.parse().ok(),
+            "etag" => response.etag = Some(value.to_string()),
+            _ => {}
+        }
+    }
+    last
+}
+
+/// Decode the hex dump `xattr -px` prints.
+pub fn parse_hex_dump(dump: &str) -> Option<Vec<u8>> {
+    dump.split_whitespace()
+        .map(|byte| u8::from_str_radix(byte, 16).ok())
+        .collect()
+}
+
+fn is_http_url(url: &str) -> bool {
+    url.starts_with("https://") || url.starts_with("http://")
+}
+
+async fn read_where_froms(path: &Path) -> Vec<String> {
+    let Ok(output) = Command::new("xattr")
+        .arg("-px")
+        .arg(WHERE_FROMS_XATTR)
+        .arg(path)
+        .output()
+        .await
+    else {
+        return Vec::new();
+    };
+    if !output.status.success() {
+        return Vec::new();
+    }
+    let Some(plist) = parse_hex_dump(&String::from_utf8_lossy(&output.stdout)) else {
+        return Vec::new();
+    };
+    plist_to_json(&plist)
+        .await
+        .and_then(|json| serde_json::from_str::<Vec<String>>(&json).ok())
+        .unwrap_or_default()
+}
+
+async fn plist_to_json(plist: &[u8]) -> Option<String> {
+    let mut child = Command::new("plutil")
+        .args(["-convert", "json", "-o", "-", "-"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .ok()?;
+    let mut stdin = child.stdin.take()?;
+    stdin.write_all(plist).await.ok()?;
+    drop(stdin);
+    let output = child.wait_with_output().await.ok()?;
+    output
+        .status
+        .success()
+        .then(|| String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+async fn head(url: &str, token: &CancellationToken) -> Option<HeadResponse> {
+    let request = Command::new("curl")
+        .args([
+            "-sSIL",
+            "--proto",
+            "=http,https",
+            "--max-time",
+            HEAD_TIMEOUT_SECS,
+            "--",
+        ])
+        .arg(url)
+        .kill_on_drop(true)
+        .output();
+    let output = tokio::select! {
+        _ = token.cancelled() => return None,
+        output = request => output.ok()?,
+    };
+    if !output.status.success() {
+        return None;
+    }
+    parse_head_response(&String::from_utf8_lossy(&output.stdout))
+}
+
+async fn file_md5(path: &Path) -> Option<String> {
+    let output = Command::new("md5")
+        .arg("-q")
+        .arg(path)
+        .output()
+        .await
+        .ok()?;
+    output
+        .status
+        .success()
+        .then(|| String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
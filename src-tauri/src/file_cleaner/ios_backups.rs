@@ -0,0 +1,160 @@
+// src/file_cleaner/ios_backups.rs
+
+//! The iPhone and iPad backups Finder keeps, one folder per device.
+//!
+//! The scan shows them as a single advanced category; this lists each
+//! device's backup with what its `Info.plist` and `Manifest.plist` say
+//! about it, so the backup of a device that is gone can be removed
+//! without touching the others.
+
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+use chrono::{DateTime, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+use walkdir::WalkDir;
+
+use super::engine::FileCleaner;
+use crate::system_snapshot::{plist_string, read_plist_xml};
+
+const BACKUP_ROOT: &str = "Library/Application Support/MobileSync/Backup";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IosBackup {
+    /// The device identifier, which is also the backup's folder name.
+    pub udid: String,
+    pub device_name: String,
+    /// Model identifier such as `iPhone15,2`.
+    pub product_type: Option<String>,
+    pub ios_version: Option<String>,
+    pub last_backup: Option<DateTime<Utc>>,
+    pub size: u64,
+    pub encrypted: bool,
+    pub path: String,
+    /// Set when audit mode reported what would be moved instead of moving it.
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+/// `~/Library/Application Support/MobileSync/Backup`.
+pub fn default_backup_root() -> Option<PathBuf> {
+    dirs::home_dir().map(|home| home.join(BACKUP_ROOT))
+}
+
+/// Every backup under `root`, most recent first.
+pub fn list_backups(root: &Path) -> Vec<IosBackup> {
+    let Ok(entries) = std::fs::read_dir(root) else {
+        return Vec::new();
+    };
+    let mut backups: Vec<IosBackup> = entries
+        .filter_map(Result::ok)
+        .filter(|entry| entry.file_type().map(|kind| kind.is_dir()).unwrap_or(false))
+        .filter_map(|entry| read_backup(&entry.path()))
+        .collect();
+    backups.sort_by(|a, b| {
+        b.last_backup
+            .cmp(&a.last_backup)
+            .then_with(|| a.device_name.cmp(&b.device_name))
+    });
+    backups
+}
+
+/// Describe the backup in `dir`, or `None` if it is not one.
+pub fn read_backup(dir: &Path) -> Option<IosBackup> {
+    let udid = dir.file_name()?.to_string_lossy().to_string();
+    let info = read_plist_xml(&dir.join("Info.plist"));
+    let manifest = read_plist_xml(&dir.join("Manifest.plist"));
+    if info.is_none() && manifest.is_none() {
+        return None;
+    }
+    let info = info.unwrap_or_default();
+    let manifest = manifest.unwrap_or_default();
+    let device_name = plist_string(&info, "Device Name")
+        .or_else(|| plist_string(&info, "Display Name"))
+        .or_else(|| plist_string(&manifest, "DeviceName"))
+        .unwrap_or_else(|| udid.clone());
+    let last_backup = plist_date(&info, "Last Backup Date")
+        .or_else(|| plist_date(&manifest, "Date"))
+        .or_else(|| modified(dir));
+    Some(IosBackup {
+        product_type: plist_string(&info, "Product Type")
+            .or_else(|| plist_string(&manifest, "ProductType")),
+        ios_version: plist_string(&info, "Product Version")
+            .or_else(|| plist_string(&manifest, "ProductVersion")),
+        encrypted: plist_true(&manifest, "IsEncrypted"),
+        size: backup_size(dir),
+        path: dir.to_string_lossy().to_string(),
+        udid,
+        device_name,
+        last_backup,
+        dry_run: false,
+    })
+}
+
+/// The folder of the backup of device `udid` under `root`.
+pub fn backup_path(root: &Path, udid: &str) -> Result<PathBuf, String> {
+    if udid.is_empty() || !udid.chars().all(|c| c.is_ascii_alphanumeric() || c == '-') {
+        return Err(format!("Invalid device identifier: {}", udid));
+    }
+    let path = root.join(udid);
+    if !path.is_dir() {
+        return Err(format!("No backup found for device {}", udid));
+    }
+    Ok(path)
+}
+
+/// Move the backup of device `udid` to the Trash and return what it was.
+pub async fn delete_backup(root: &Path, udid: &str) -> Result<IosBackup, String> {
+    let path = backup_path(root, udid)?;
+    let backup = read_backup(&path).ok_or_else(|| format!("{} is not a backup", path.display()))?;
+    FileCleaner::new().move_to_trash(&path).await?;
+    Ok(backup)
+}
+
+/// The backup [`delete_backup`] would move, flagged as a dry run.
+pub fn preview_delete(root: &Path, udid: &str) -> Result<IosBackup, String> {
+    let path = backup_path(root, udid)?;
+    let backup = read_backup(&path).ok_or_else(|| format!("{} is not a backup", path.display()))?;
+    Ok(IosBackup {
+        dry_run: true,
+        ..backup
+    })
+}
+
+fn plist_date(plist: &str, key: &str) -> Option<DateTime<Utc>> {
+    let after_key = &plist[plist.find(&format!("<key>{}</key>", key))?..];
+    let start = after_key.find("<date>")? + "<date>".len();
+    let end = after_key[start..].find("</date>")?;
+    DateTime::parse_from_rfc3339(after_key[start..start + end].trim())
+        .ok()
+        .map(|date| date.with_timezone(&Utc))
+}
+
+fn plist_true(plist: &str, key: &str) -> bool {
+    let marker = format!("<key>{}</key>", key);
+    plist.find(&marker).is_some_and(|start| {
+        plist[start + marker.len()..]
+            .trim_start()
+            .starts_with("<true/>")
+    })
+}
+
+fn modified(path: &Path) -> Option<DateTime<Utc>> {
+    let secs = std::fs::metadata(path)
+        .and_then(|metadata| metadata.modified())
+        .ok()?
+        .duration_since(UNIX_EPOCH)
+        .ok()?
+        .as_secs();
+    Utc.timestamp_opt(secs as i64, 0).single()
+}
+
+fn backup_size(dir: &Path) -> u64 {
+    WalkDir::new(dir)
+        .into_iter()
+        .filter_map(Result::ok)
+        .filter(|entry| entry.file_type().is_file())
+        .filter_map(|entry| entry.metadata().ok())
+        .map(|metadata| FileCleaner::metadata_size_bytes(&metadata))
+        .sum()
+}
@@ -0,0 +1,165 @@
+// src/file_cleaner/large_files.rs
+
+//! Hunting for big, old files anywhere the user points at, not just the
+//! locations the cleaning rules know about.
+//!
+//! Matches are reported as the walk finds them so a long hunt shows results
+//! early; each carries the same path risk assessment the scan uses, since a
+//! large file outside the rule paths is as often a keeper as it is junk.
+
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use serde::{Deserialize, Serialize};
+use tokio_util::sync::CancellationToken;
+use walkdir::WalkDir;
+
+use super::age_thresholds::AgeThresholds;
+use super::duplicate_detector::PACKAGE_EXTENSIONS;
+use super::engine::FileCleaner;
+use super::exclusions::ExclusionMatcher;
+use super::protection_packs::ProtectionPacks;
+use super::safety::{assess_path_risk_with, RiskLevel};
+
+pub const LARGE_FILES_CATEGORY: &str = "Large Files";
+/// The hunt stops reporting after this many matches; `truncated` says so.
+const MAX_MATCHES: usize = 1000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LargeFileRisk {
+    Safe,
+    Review,
+    Risky,
+}
+
+impl From<RiskLevel> for LargeFileRisk {
+    fn from(level: RiskLevel) -> Self {
+        match level {
+            RiskLevel::Safe => LargeFileRisk::Safe,
+            RiskLevel::Review => LargeFileRisk::Review,
+            RiskLevel::Risky => LargeFileRisk::Risky,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LargeFile {
+    pub path: String,
+    /// Allocated bytes, matching the sizes shown for cleanable files.
+    pub size: u64,
+    /// Days since the file was last modified.
+    pub age_days: u64,
+    pub risk: LargeFileRisk,
+    pub confidence: u8,
+    pub reasons: Vec<String>,
+}
+
+/// What to look for and where.
+#[derive(Debug, Clone)]
+pub struct LargeFileQuery {
+    pub roots: Vec<PathBuf>,
+    pub min_size: u64,
+    pub min_age_days: u64,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct LargeFileScan {
+    /// Largest first.
+    pub files: Vec<LargeFile>,
+    pub total_size: u64,
+    /// Regular files looked at, matching or not.
+    pub scanned_files: usize,
+    pub truncated: bool,
+}
+
+/// Where the hunt looks when no folders are given: the home folder.
+#[cfg(feature = "app")]
+pub fn default_roots() -> Vec<PathBuf> {
+    dirs::home_dir().into_iter().collect()
+}
+
+/// Walk `query.roots` for files of at least `min_size` bytes untouched for
+/// `min_age_days`, calling `on_match` for each as it is found. Hidden
+/// folders, app bundles and media libraries are not entered, and symlinks are not
+/// followed.
+pub(crate) fn find_large_files(
+    query: &LargeFileQuery,
+    exclusions: &ExclusionMatcher,
+    thresholds: &AgeThresholds,
+    packs: &ProtectionPacks,
+    token: &CancellationToken,
+    mut on_match: impl FnMut(&LargeFile),
+) -> Result<LargeFileScan, String> {
+    if let Some(root) = query
+        .roots
+        .iter()
+        .find(|root| !root.is_absolute() || !root.is_dir())
+    {
+        return Err(format!("Not a folder: {}", root.display()));
+    }
+    let now = SystemTime::now();
+    let mut scan = LargeFileScan::default();
+    for root in &query.roots {
+        let entries = WalkDir::new(root).into_iter().filter_entry(|entry| {
+            entry.depth() == 0 || !(is_hidden(entry.file_name()) || is_package(entry))
+        });
+        for entry in entries.filter_map(Result::ok) {
+            if token.is_cancelled() {
+                return Err("cancelled".into());
+            }
+            if !entry.file_type().is_file() {
+                continue;
+            }
+            scan.scanned_files += 1;
+            let Ok(metadata) = entry.metadata() else {
+                continue;
+            };
+            let size = FileCleaner::metadata_size_bytes(&metadata);
+            let age_days = metadata
+                .modified()
+                .ok()
+                .and_then(|modified| now.duration_since(modified).ok())
+                .map(|age| age.as_secs() / 86_400)
+                .unwrap_or(0);
+            let path = entry.path();
+            if size < query.min_size
+                || age_days < query.min_age_days
+                || exclusions.is_excluded(&path.to_string_lossy(), LARGE_FILES_CATEGORY)
+            {
+                continue;
+            }
+            if scan.files.len() == MAX_MATCHES {
+                scan.truncated = true;
+                continue;
+            }
+            let risk = assess_path_risk_with(path, thresholds, packs);
+            let file = LargeFile {
+                path: path.to_string_lossy().to_string(),
+                size,
+                age_days,
+                risk: risk.level.into(),
+                confidence: risk.confidence,
+                reasons: risk.reasons,
+            };
+            on_match(&file);
+            scan.total_size += size;
+            scan.files.push(file);
+        }
+    }
+    scan.files
+        .sort_by(|a, b| b.size.cmp(&a.size).then_with(|| a.path.cmp(&b.path)));
+    Ok(scan)
+}
+
+fn is_hidden(name: &std::ffi::OsStr) -> bool {
+    name.to_string_lossy().starts_with('.')
+}
+
+fn is_package(entry: &walkdir::DirEntry) -> bool {
+    entry.file_type().is_dir()
+        && Path::new(entry.file_name())
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .is_some_and(|ext| PACKAGE_EXTENSIONS.contains(&ext.to_lowercase().as_str()))
+}
@@ -0,0 +1,310 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use super::auto_selection::UserAction;
+use super::types::CleanableFile;
+use crate::persistence;
+
+const LEARNED_EXCEPTIONS_FILE: &str = "learned_exceptions.json";
+/// Deselections of auto-selected items before a shape is offered for review.
+pub const DESELECTIONS_TO_PROPOSE: u32 = 3;
+/// Same bar as the category learner: fewer than one in five kept selected.
+const MAX_SELECTION_RATE: f32 = 0.2;
+/// Shapes kept on disk; the least recently seen candidates go first.
+const MAX_TRACKED_SHAPES: usize = 500;
+/// Bytes of SHA-256 kept per path segment.
+const SEGMENT_HASH_BYTES: usize = 5;
+const HOME_MARKER: &str = "~";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExceptionStatus {
+    /// Still being observed, or waiting for review.
+    Candidate,
+    /// Lowers auto-select confidence for matching paths.
+    Accepted,
+    /// Declined by the user; no longer proposed.
+    Rejected,
+}
+
+/// A path shape the user keeps deselecting when it is auto-selected.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LearnedException {
+    pub id: String,
+    /// Anonymized path shape, see [`path_shape`].
+    pub signature: String,
+    pub category: String,
+    pub deselections: u32,
+    pub selections: u32,
+    pub first_seen: i64,
+    pub last_seen: i64,
+    pub status: ExceptionStatus,
+}
+
+impl LearnedException {
+    /// Deselected consistently enough to be worth reviewing.
+    pub fn is_proposed(&self) -> bool {
+        let total = self.deselections + self.selections;
+        self.deselections >= DESELECTIONS_TO_PROPOSE
+            && (self.selections as f32 / total as f32) < MAX_SELECTION_RATE
+    }
+}
+
+/// The user's verdict on a proposed exception.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExceptionDecision {
+    pub id: String,
+    pub accept: bool,
+}
+
+/// Anonymized shape of `path`. The home directory becomes `~`, digit runs
+/// become `#` and hex runs such as UUIDs become `*`, then every segment is
+/// replaced by a truncated SHA-256 of itself. Sibling paths that differ only
+/// in dates, build numbers or identifiers share a shape; names are never
+/// stored.
+pub fn path_shape(path: &str, home: Option<&Path>) -> String {
+    let lower = path.trim_end_matches('/').to_lowercase();
+    let home = home.map(|home| home.to_string_lossy().trim_end_matches('/').to_lowercase());
+    let (mut shape, rest) = match home.as_deref() {
+        Some(home)
+            if !home.is_empty()
+                && lower.starts_with(home)
+                && matches!(lower.as_bytes().get(home.len()), None | Some(b'/')) =>
+        {
+            (HOME_MARKER.to_string(), &lower[home.len()..])
+        }
+        _ => (String::new(), lower.as_str()),
+    };
+    for segment in rest.split('/').filter(|segment| !segment.is_empty()) {
+        shape.push('/');
+        shape.push_str(&hash_segment(&normalize_segment(segment)));
+    }
+    shape
+}
+
+fn normalize_segment(segment: &str) -> String {
+    let mut normalized = String::with_capacity(segment.len());
+    let mut token = String::new();
+    let flush = |token: &mut String, normalized: &mut String| {
+        if token.is_empty() {
+            return;
+        }
+        let all_digits = token.chars().all(|c| c.is_ascii_digit());
+        let hex_id = token.len() >= 4
+            && token.chars().all(|c| c.is_ascii_hexdigit())
+            && token.chars().any(|c| c.is_ascii_digit());
+        if all_digits {
+            normalized.push('#');
+        } else if hex_id {
+            normalized.push('*');
+        } else {
+            normalized.push_str(token);
+        }
+        token.clear();
+    };
+    for c in segment.chars() {
+        if c.is_alphanumeric() {
+            token.push(c);
+        } else {
+            flush(&mut token, &mut normalized);
+            normalized.push(c);
+        }
+    }
+    flush(&mut token, &mut normalized);
+    normalized
+}
+
+fn hash_segment(segment: &str) -> String {
+    Sha256::digest(segment.as_bytes())
+        .iter()
+        .take(SEGMENT_HASH_BYTES)
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+/// Shapes learned from the user's deselections, persisted locally.
+#[derive(Default)]
+pub struct LearnedExceptionStore {
+    path: Option<PathBuf>,
+    exceptions: Vec<LearnedException>,
+}
+
+impl LearnedExceptionStore {
+    /// Load from `<data dir>/macos-optimizer/learned_exceptions.json`.
+    pub fn load() -> Self {
+        match persistence::data_file(LEARNED_EXCEPTIONS_FILE) {
+            Some(path) => Self::load_from(&path),
+            None => Self::default(),
+        }
+    }
+
+    /// Load from an explicit file; a missing or unreadable file yields an empty store.
+    pub fn load_from(path: &Path) -> Self {
+        let exceptions = persistence::read_json(path).unwrap_or_default();
+        Self {
+            path: Some(path.to_path_buf()),
+            exceptions,
+        }
+    }
+
+    /// Proposed and reviewed exceptions, most deselected first.
+    pub fn list(&self) -> Vec<LearnedException> {
+        let mut listed: Vec<LearnedException> = self
+            .exceptions
+            .iter()
+            .filter(|exception| {
+                exception.status != ExceptionStatus::Candidate || exception.is_proposed()
+            })
+            .cloned()
+            .collect();
+        listed.sort_by(|a, b| {
+            b.deselections
+                .cmp(&a.deselections)
+                .then_with(|| b.last_seen.cmp(&a.last_seen))
+        });
+        listed
+    }
+
+    /// Count a user action on a scanned item. Only deselections of items that
+    /// were auto-selected start tracking a shape; selections count against
+    /// shapes already tracked. Returns whether anything changed.
+    pub fn record(
+        &mut self,
+        file: &CleanableFile,
+        action: &UserAction,
+        was_auto_selected: bool,
+        home: Option<&Path>,
+        now: i64,
+    ) -> Result<bool, String> {
+        let deselected = match action {
+            UserAction::Deselected if was_auto_selected => true,
+            UserAction::Selected => false,
+            UserAction::Deselected | UserAction::Ignored => return Ok(false),
+        };
+        let signature = path_shape(&file.path, home);
+        let index = match self.position(&signature, &file.category) {
+            Some(index) => index,
+            None if deselected => {
+                self.exceptions.push(LearnedException {
+                    id: uuid::Uuid::new_v4().to_string(),
+                    signature,
+                    category: file.category.clone(),
+                    deselections: 0,
+                    selections: 0,
+                    first_seen: now,
+                    last_seen: now,
+                    status: ExceptionStatus::Candidate,
+                });
+                self.exceptions.len() - 1
+            }
+            None => return Ok(false),
+        };
+        let exception = &mut self.exceptions[index];
+        if exception.status == ExceptionStatus::Rejected {
+            return Ok(false);
+        }
+        if deselected {
+            exception.deselections = exception.deselections.saturating_add(1);
+        } else {
+            exception.selections = exception.selections.saturating_add(1);
+        }
+        exception.last_seen = now;
+        self.evict();
+        self.persist()?;
+        Ok(true)
+    }
+
+    /// Apply the user's review of proposed exceptions.
+    pub fn review(&mut self, decisions: &[ExceptionDecision]) -> Result<(), String> {
+        for decision in decisions {
+            let exception = self
+                .exceptions
+                .iter_mut()
+                .find(|exception| exception.id == decision.id)
+                .ok_or_else(|| format!("Unknown learned exception {}", decision.id))?;
+            exception.status = if decision.accept {
+                ExceptionStatus::Accepted
+            } else {
+                ExceptionStatus::Rejected
+            };
+        }
+        if decisions.is_empty() {
+            return Ok(());
+        }
+        self.persist()
+    }
+
+    pub(crate) fn matcher(&self, home: Option<&Path>) -> LearnedShapes {
+        LearnedShapes {
+            home: home.map(Path::to_path_buf),
+            accepted: self
+                .exceptions
+                .iter()
+                .filter(|exception| exception.status == ExceptionStatus::Accepted)
+                .map(|exception| {
+                    (
+                        (
+                            exception.category.to_lowercase(),
+                            exception.signature.clone(),
+                        ),
+                        exception.deselections,
+                    )
+                })
+                .collect(),
+        }
+    }
+
+    fn position(&self, signature: &str, category: &str) -> Option<usize> {
+        self.exceptions.iter().position(|exception| {
+            exception.signature == signature && exception.category.eq_ignore_ascii_case(category)
+        })
+    }
+
+    fn evict(&mut self) {
+        while self.exceptions.len() > MAX_TRACKED_SHAPES {
+            let oldest = self
+                .exceptions
+                .iter()
+                .enumerate()
+                .filter(|(_, exception)| exception.status == ExceptionStatus::Candidate)
+                .min_by_key(|(_, exception)| exception.last_seen)
+                .map(|(index, _)| index);
+            match oldest {
+                Some(index) => {
+                    self.exceptions.remove(index);
+                }
+                None => break,
+            }
+        }
+    }
+
+    fn persist(&self) -> Result<(), String> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        persistence::write_json(path, &self.exceptions)
+    }
+}
+
+/// Accepted exceptions, compiled for scoring scans.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct LearnedShapes {
+    home: Option<PathBuf>,
+    accepted: HashMap<(String, String), u32>,
+}
+
+impl LearnedShapes {
+    /// Deselections behind the accepted exception matching `file`, if any.
+    pub(crate) fn deselections_for(&self, file: &CleanableFile) -> Option<u32> {
+        if self.accepted.is_empty() {
+            return None;
+        }
+        let signature = path_shape(&file.path, self.home.as_deref());
+        self.accepted
+            .get(&(file.category.to_lowercase(), signature))
+            .copied()
+    }
+}
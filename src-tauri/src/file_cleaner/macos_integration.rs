@@ -1,3 +1,4 @@
+use crate::capabilities::{self, Tool};
 use serde::{Deserialize, Serialize};
 use std::path::{Path, PathBuf};
 use std::process::Command;
@@ -45,6 +46,15 @@ impl MacOSIntegration {
         self.icloud_checker.get_sync_status(path).await
     }
 
+    /// Tools behind the checks above that are missing on this machine.
+    pub fn unavailable_checks(&self) -> Vec<String> {
+        [Tool::Mdls, Tool::Tmutil, Tool::Brctl]
+            .into_iter()
+            .filter(|&tool| !capabilities::tool_available(tool))
+            .map(|tool| tool.command().to_string())
+            .collect()
+    }
+
     pub async fn get_file_associations(&self, path: &Path) -> Vec<FileAssociation> {
         let mut associations = Vec::new();
 
@@ -147,6 +157,9 @@ impl SpotlightIntegration {
             use_count: 0,
             tags: Vec::new(),
         };
+        if !capabilities::tool_available(Tool::Mdls) {
+            return info;
+        }
 
         // Use mdls to get Spotlight metadata
         if let Ok(output) = TokioCommand::new("mdls")
@@ -211,7 +224,7 @@ impl LaunchServicesChecker {
 
     pub async fn is_registered(&self, path: &Path) -> bool {
         // Check if file is registered with Launch Services
-        if path.extension().map_or(false, |ext| ext == "app") {
+        if path.extension().is_some_and(|ext| ext == "app") {
             // Check if app is registered
             if let Ok(output) = TokioCommand::new("lsregister").arg("-dump").output().await {
                 let stdout = String::from_utf8_lossy(&output.stdout);
@@ -315,6 +328,9 @@ impl TimeMachineIntegration {
     }
 
     async fn is_enabled(&self) -> bool {
+        if !capabilities::tool_available(Tool::Tmutil) {
+            return false;
+        }
         if let Ok(output) = TokioCommand::new("tmutil")
             .arg("destinationinfo")
             .output()
@@ -426,6 +442,10 @@ impl ICloudChecker {
         if !path_str.contains("Library/Mobile Documents") && !path_str.contains("iCloud Drive") {
             return status;
         }
+        if !capabilities::tool_available(Tool::Brctl) {
+            status.sync_error = Some("Sync status unknown: brctl is unavailable".to_string());
+            return status;
+        }
 
         // Use brctl to check iCloud status
         if let Ok(output) = TokioCommand::new("brctl")
@@ -0,0 +1,125 @@
+// src/file_cleaner/native_cleanup.rs
+
+//! Clearing caches through the CLI of the tool that owns them.
+//!
+//! Package managers and simulators keep indexes next to their caches, so
+//! removing files behind their back can leave them inconsistent. Where the
+//! tool has its own cleanup command and is installed, scan items under its
+//! cache get an [`CleanupAction::AppNative`] action and cleaning runs the
+//! command once for all of them instead of moving each file to the Trash.
+
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+use tokio::process::Command;
+
+use super::developer_junk::DeveloperJunkKind;
+use crate::capabilities::{self, Tool};
+
+const HOMEBREW_CACHE_DIR: &str = "Library/Caches/Homebrew";
+const NPM_CACHE_DIR: &str = ".npm";
+const PIP_CACHE_DIR: &str = "Library/Caches/pip";
+const SIMULATOR_DEVICES_DIR: &str = "Library/Developer/CoreSimulator/Devices";
+
+/// A cleanup command offered by the tool that owns a cache.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(tag = "tool", rename_all = "snake_case")]
+pub enum NativeCleanup {
+    /// `brew cleanup -s`: scrubs the download cache and outdated versions.
+    Homebrew,
+    /// `npm cache clean --force`.
+    Npm,
+    /// `pip3 cache purge`.
+    Pip,
+    /// `xcrun simctl delete <udid>`, which also unregisters the device.
+    Simulator { udid: String },
+}
+
+impl NativeCleanup {
+    pub fn tool(&self) -> Tool {
+        match self {
+            NativeCleanup::Homebrew => Tool::Brew,
+            NativeCleanup::Npm => Tool::Npm,
+            NativeCleanup::Pip => Tool::Pip3,
+            NativeCleanup::Simulator { .. } => Tool::Xcrun,
+        }
+    }
+
+    pub fn args(&self) -> Vec<&str> {
+        match self {
+            NativeCleanup::Homebrew => vec!["cleanup", "-s"],
+            NativeCleanup::Npm => vec!["cache", "clean", "--force"],
+            NativeCleanup::Pip => vec!["cache", "purge"],
+            NativeCleanup::Simulator { udid } => vec!["simctl", "delete", udid],
+        }
+    }
+
+    /// The command line, for results and logs.
+    pub fn command_line(&self) -> String {
+        std::iter::once(self.tool().command())
+            .chain(self.args())
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    /// Run the command; a missing tool or non-zero exit is an error.
+    pub async fn run(&self) -> Result<(), String> {
+        let program = capabilities::tool_path(self.tool())
+            .ok_or_else(|| format!("{} is not installed", self.tool().command()))?;
+        let output = Command::new(program)
+            .args(self.args())
+            .output()
+            .await
+            .map_err(|e| format!("Failed to run {}: {}", self.command_line(), e))?;
+        if output.status.success() {
+            Ok(())
+        } else {
+            Err(format!(
+                "{} failed: {}",
+                self.command_line(),
+                String::from_utf8_lossy(&output.stderr).trim()
+            ))
+        }
+    }
+}
+
+/// How cleaning removes a scan item.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum CleanupAction {
+    #[default]
+    MoveToTrash,
+    AppNative {
+        cleanup: NativeCleanup,
+    },
+}
+
+/// The action for an item at `path` in `category`, preferring the owning
+/// tool's command when `available` says it is installed.
+pub fn cleanup_action_for(
+    path: &str,
+    category: &str,
+    home: &Path,
+    available: impl Fn(Tool) -> bool,
+) -> CleanupAction {
+    let path = Path::new(path);
+    let native = if path.starts_with(home.join(HOMEBREW_CACHE_DIR)) {
+        Some(NativeCleanup::Homebrew)
+    } else if path.starts_with(home.join(NPM_CACHE_DIR)) {
+        Some(NativeCleanup::Npm)
+    } else if path.starts_with(home.join(PIP_CACHE_DIR)) {
+        Some(NativeCleanup::Pip)
+    } else if category == DeveloperJunkKind::Simulator.category()
+        && path.parent() == Some(home.join(SIMULATOR_DEVICES_DIR).as_path())
+    {
+        path.file_name().map(|udid| NativeCleanup::Simulator {
+            udid: udid.to_string_lossy().to_string(),
+        })
+    } else {
+        None
+    };
+    match native {
+        Some(cleanup) if available(cleanup.tool()) => CleanupAction::AppNative { cleanup },
+        _ => CleanupAction::MoveToTrash,
+    }
+}
@@ -0,0 +1,127 @@
+// src/file_cleaner/native_trash.rs
+
+//! Moving items to the Trash with `NSFileManager`.
+//!
+//! `trashItemAtURL:resultingItemURL:error:` picks the Trash of the item's
+//! volume, resolves name collisions the way Finder does and reports where
+//! the item ended up, without the Automation prompt and the per-item
+//! process a Finder AppleScript needs. Renaming into `~/.Trash` remains for
+//! when the API is unavailable: off macOS, or turned off with
+//! `MACOS_OPTIMIZER_DISABLE_NATIVE_TRASH` for runs against a fake home
+//! folder, which `NSFileManager` would ignore.
+
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use chrono::Local;
+
+/// Move `path` to the Trash and return where it ended up there.
+pub(crate) async fn move_to_trash(path: &Path) -> Result<PathBuf, String> {
+    let path = path.to_path_buf();
+    tokio::task::spawn_blocking(move || move_to_trash_blocking(&path))
+        .await
+        .map_err(|e| format!("Trash task failed: {}", e))?
+}
+
+/// [`move_to_trash`] for callers already on a blocking thread.
+pub(crate) fn move_to_trash_blocking(path: &Path) -> Result<PathBuf, String> {
+    if native_trash_available() {
+        trash_item(path)
+    } else {
+        rename_into_trash(path)
+    }
+}
+
+fn native_trash_available() -> bool {
+    cfg!(target_os = "macos")
+        && !env::var("MACOS_OPTIMIZER_DISABLE_NATIVE_TRASH")
+            .map(|value| {
+                let lowercase = value.trim().to_ascii_lowercase();
+                lowercase == "1" || lowercase == "true" || lowercase == "yes"
+            })
+            .unwrap_or(false)
+}
+
+#[cfg(target_os = "macos")]
+fn trash_item(path: &Path) -> Result<PathBuf, String> {
+    use objc2::rc::autoreleasepool;
+    use objc2_foundation::{NSFileManager, NSURL};
+
+    autoreleasepool(|_| {
+        let url = NSURL::from_path(path, path.is_dir(), None)
+            .ok_or_else(|| format!("Invalid path: {}", path.display()))?;
+        let mut resulting = None;
+        NSFileManager::defaultManager()
+            .trashItemAtURL_resultingItemURL_error(&url, Some(&mut resulting))
+            .map_err(|error| {
+                format!(
+                    "Failed to move {} to the Trash: {}",
+                    path.display(),
+                    error.localizedDescription()
+                )
+            })?;
+        // The item is in the Trash even if its new location went unreported.
+        Ok(resulting
+            .and_then(|url| url.to_file_path())
+            .or_else(|| {
+                dirs::home_dir()
+                    .zip(path.file_name())
+                    .map(|(home, name)| home.join(".Trash").join(name))
+            })
+            .unwrap_or_default())
+    })
+}
+
+#[cfg(not(target_os = "macos"))]
+fn trash_item(path: &Path) -> Result<PathBuf, String> {
+    rename_into_trash(path)
+}
+
+/// Rename `path` into `~/.Trash`, under a unique name if its own is taken.
+fn rename_into_trash(path: &Path) -> Result<PathBuf, String> {
+    let home = dirs::home_dir().ok_or_else(|| "Could not find home directory".to_string())?;
+    let trash = home.join(".Trash");
+    if !trash.exists() {
+        fs::create_dir_all(&trash)
+            .map_err(|e| format!("Failed to create trash directory: {}", e))?;
+    }
+
+    let original_name = path
+        .file_name()
+        .ok_or_else(|| "Invalid filename".to_string())?;
+    let mut target = trash.join(original_name);
+
+    // Ensure unique filename
+    if target.exists() {
+        let stem = original_name.to_string_lossy().to_string();
+        let (base, ext) = split_name_ext(&stem);
+        let ts = Local::now().format("%Y%m%d-%H%M%S").to_string();
+        let mut counter = 1u32;
+        loop {
+            let candidate = if ext.is_empty() {
+                format!("{} ({}-{})", base, ts, counter)
+            } else {
+                format!("{} ({}-{}).{}", base, ts, counter, ext)
+            };
+            target = trash.join(candidate);
+            if !target.exists() {
+                break;
+            }
+            counter += 1;
+        }
+    }
+
+    fs::rename(path, &target).map_err(|e| format!("Failed to move to trash: {}", e))?;
+    Ok(target)
+}
+
+/// Utility: split a name into (base, ext) without touching the filesystem.
+fn split_name_ext(name: &str) -> (String, String) {
+    if let Some(idx) = name.rfind('.') {
+        let (base, ext) = name.split_at(idx);
+        (base.to_string(), ext.trim_start_matches('.').to_string())
+    } else {
+        (name.to_string(), String::new())
+    }
+}
@@ -0,0 +1,296 @@
+// src/file_cleaner/parallel_walk.rs
+
+//! The file system walk behind baseline scans.
+//!
+//! Each rule path is one walk, broken into jobs of one directory listing
+//! each. Jobs wait in one queue per rule; a fixed set of scoped worker
+//! threads, as many as the performance profile allows, each works through
+//! its own rule's queue first and steals from the other rules' queues once
+//! that runs dry, so one rule with a deep tree does not leave the other
+//! workers idle. Matches travel back over a bounded channel, so the workers
+//! never run far ahead of the scan consuming them, and are merged in one
+//! step at the end, in walk order: an item two walks find goes to the
+//! earlier one no matter which finished first.
+
+use std::collections::{HashSet, VecDeque};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use tokio::sync::mpsc;
+use tokio_util::sync::CancellationToken;
+use walkdir::{DirEntry, WalkDir};
+
+use super::engine::FileCleaner;
+use super::path_alias::dedupe_key;
+use super::types::{CategoryRule, CleanableFile};
+use crate::performance;
+
+/// How long an idle worker waits before looking for work again.
+const IDLE_WAIT: Duration = Duration::from_millis(2);
+/// Finished batches each worker may have waiting in the channel.
+const QUEUED_BATCHES_PER_WORKER: usize = 4;
+
+/// One rule path to walk.
+#[derive(Debug, Clone)]
+pub(crate) struct Walk {
+    pub rule: CategoryRule,
+    pub root: PathBuf,
+}
+
+/// What walking one rule path found.
+#[derive(Debug, Default, Clone, Copy)]
+pub(crate) struct WalkStats {
+    pub entries: u64,
+    pub bytes: u64,
+}
+
+/// A match, and whether it is a folder that claims everything inside it.
+#[derive(Debug, Clone)]
+pub(crate) struct Found {
+    pub file: CleanableFile,
+    pub is_dir: bool,
+}
+
+pub(crate) enum WalkEvent {
+    /// Matches of the walk at this index.
+    Found(usize, Vec<Found>),
+    /// The walk at this index has finished.
+    Done(usize, WalkStats),
+}
+
+/// A directory of a walk to list; `depth` 0 is the walk's root, which is
+/// matched itself before its contents.
+struct Job {
+    walk: usize,
+    dir: PathBuf,
+    depth: usize,
+}
+
+struct Shared {
+    walks: Vec<Walk>,
+    /// The queue of each walk's rule.
+    queue_of: Vec<usize>,
+    queues: Vec<Mutex<VecDeque<Job>>>,
+    /// Jobs queued or running, per walk and in total.
+    pending: Vec<AtomicUsize>,
+    outstanding: AtomicUsize,
+    entries: Vec<AtomicU64>,
+    bytes: Vec<AtomicU64>,
+    cleaner: FileCleaner,
+    cancel: CancellationToken,
+}
+
+/// Walk `walks` with `cleaner`'s rules of thumb on the scan threads and
+/// return the channel their matches and completions arrive on. It closes
+/// once every walk is done or `cancel` fires.
+pub(crate) fn spawn(
+    cleaner: FileCleaner,
+    walks: Vec<Walk>,
+    cancel: CancellationToken,
+) -> mpsc::Receiver<WalkEvent> {
+    let workers = performance::scan_threads().max(1);
+    let (sender, receiver) = mpsc::channel(workers * QUEUED_BATCHES_PER_WORKER);
+    if walks.is_empty() {
+        return receiver;
+    }
+
+    let mut rules: Vec<&str> = Vec::new();
+    let queue_of: Vec<usize> = walks
+        .iter()
+        .map(
+            |walk| match rules.iter().position(|name| *name == walk.rule.name) {
+                Some(queue) => queue,
+                None => {
+                    rules.push(&walk.rule.name);
+                    rules.len() - 1
+                }
+            },
+        )
+        .collect();
+    let queues: Vec<Mutex<VecDeque<Job>>> = (0..rules.len())
+        .map(|_| Mutex::new(VecDeque::new()))
+        .collect();
+    for (walk, item) in walks.iter().enumerate() {
+        lock(&queues[queue_of[walk]]).push_back(Job {
+            walk,
+            dir: item.root.clone(),
+            depth: 0,
+        });
+    }
+    let shared = Arc::new(Shared {
+        pending: (0..walks.len()).map(|_| AtomicUsize::new(1)).collect(),
+        outstanding: AtomicUsize::new(walks.len()),
+        entries: (0..walks.len()).map(|_| AtomicU64::new(0)).collect(),
+        bytes: (0..walks.len()).map(|_| AtomicU64::new(0)).collect(),
+        walks,
+        queue_of,
+        queues,
+        cleaner,
+        cancel,
+    });
+
+    tokio::task::spawn_blocking(move || {
+        std::thread::scope(|scope| {
+            for home in 0..workers {
+                let shared = &shared;
+                let sender = sender.clone();
+                scope.spawn(move || shared.work(home % shared.queues.len(), sender));
+            }
+            drop(sender);
+        });
+    });
+    receiver
+}
+
+impl Shared {
+    fn work(&self, home: usize, events: mpsc::Sender<WalkEvent>) {
+        performance::scan_thread_started();
+        while !self.cancel.is_cancelled() {
+            let Some(job) = self.next_job(home) else {
+                if self.outstanding.load(Ordering::Acquire) == 0 || events.is_closed() {
+                    break;
+                }
+                std::thread::sleep(IDLE_WAIT);
+                continue;
+            };
+            let walk = job.walk;
+            if !self.run(job, &events) {
+                break;
+            }
+            if self.pending[walk].fetch_sub(1, Ordering::AcqRel) == 1 {
+                let stats = WalkStats {
+                    entries: self.entries[walk].load(Ordering::Relaxed),
+                    bytes: self.bytes[walk].load(Ordering::Relaxed),
+                };
+                if events.blocking_send(WalkEvent::Done(walk, stats)).is_err() {
+                    break;
+                }
+            }
+            self.outstanding.fetch_sub(1, Ordering::AcqRel);
+        }
+    }
+
+    /// The newest job of the home queue, or else the oldest of another.
+    fn next_job(&self, home: usize) -> Option<Job> {
+        if let Some(job) = lock(&self.queues[home]).pop_back() {
+            return Some(job);
+        }
+        (1..self.queues.len())
+            .map(|offset| (home + offset) % self.queues.len())
+            .find_map(|queue| lock(&self.queues[queue]).pop_front())
+    }
+
+    /// Match the entries of `job`'s directory and queue the folders below
+    /// it that nothing claimed. False once the scan stopped listening.
+    fn run(&self, job: Job, events: &mpsc::Sender<WalkEvent>) -> bool {
+        let rule = &self.walks[job.walk].rule;
+        let max_depth = rule.max_depth.unwrap_or(10);
+        let batch_size = performance::walk_batch_size().max(1);
+        let mut found = Vec::new();
+        if job.depth == 0 {
+            let root = WalkDir::new(&job.dir).max_depth(0).into_iter().next();
+            let Some(Ok(root)) = root else {
+                return true;
+            };
+            if self.visit(&root, job.walk, &mut found) || !root.file_type().is_dir() {
+                return self.flush(job.walk, found, events);
+            }
+        }
+        if job.depth >= max_depth {
+            return self.flush(job.walk, found, events);
+        }
+
+        let children = WalkDir::new(&job.dir)
+            .min_depth(1)
+            .max_depth(1)
+            .into_iter()
+            .filter_map(Result::ok);
+        for entry in children {
+            if self.cancel.is_cancelled() {
+                break;
+            }
+            let claimed = self.visit(&entry, job.walk, &mut found);
+            if !claimed && entry.file_type().is_dir() && job.depth + 1 < max_depth {
+                self.pending[job.walk].fetch_add(1, Ordering::AcqRel);
+                self.outstanding.fetch_add(1, Ordering::AcqRel);
+                lock(&self.queues[self.queue_of[job.walk]]).push_back(Job {
+                    walk: job.walk,
+                    dir: entry.into_path(),
+                    depth: job.depth + 1,
+                });
+            }
+            if found.len() >= batch_size
+                && !self.flush(job.walk, std::mem::take(&mut found), events)
+            {
+                return false;
+            }
+        }
+        self.flush(job.walk, found, events)
+    }
+
+    /// Match `entry` against the walk's rule; true if it claimed a folder.
+    fn visit(&self, entry: &DirEntry, walk: usize, found: &mut Vec<Found>) -> bool {
+        self.entries[walk].fetch_add(1, Ordering::Relaxed);
+        let Some(file) = self.cleaner.process_entry(entry, &self.walks[walk].rule) else {
+            return false;
+        };
+        self.bytes[walk].fetch_add(file.size, Ordering::Relaxed);
+        let is_dir = entry.file_type().is_dir();
+        found.push(Found { file, is_dir });
+        is_dir
+    }
+
+    fn flush(&self, walk: usize, found: Vec<Found>, events: &mpsc::Sender<WalkEvent>) -> bool {
+        found.is_empty() || events.blocking_send(WalkEvent::Found(walk, found)).is_ok()
+    }
+}
+
+fn lock(queue: &Mutex<VecDeque<Job>>) -> std::sync::MutexGuard<'_, VecDeque<Job>> {
+    queue
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+/// `seed`, with the folders in `seed_dirs` claimed, plus the matches of
+/// each walk in walk order that are not already listed or inside a claimed
+/// folder. Returns the items and the claimed folders' prefixes, each ending
+/// in `/`.
+pub(crate) fn merge(
+    seed: Vec<CleanableFile>,
+    seed_dirs: Vec<String>,
+    found: Vec<Vec<Found>>,
+) -> (Vec<CleanableFile>, Vec<String>) {
+    let mut claimed: HashSet<String> = seed.iter().map(|file| dedupe_key(&file.path)).collect();
+    let mut dirs: HashSet<String> = seed_dirs.into_iter().collect();
+    let mut files = seed;
+    for mut matches in found {
+        matches.sort_by(|a, b| a.file.path.cmp(&b.file.path));
+        for Found { file, is_dir } in matches {
+            let key = dedupe_key(&file.path);
+            if claimed.contains(&key) || inside_claimed(&key, &dirs) {
+                continue;
+            }
+            if is_dir {
+                let mut prefix = key.clone();
+                if !prefix.ends_with('/') {
+                    prefix.push('/');
+                }
+                dirs.insert(prefix);
+            }
+            claimed.insert(key);
+            files.push(file);
+        }
+    }
+    // A folder found later still claims seeded items inside it.
+    files.retain(|file| !inside_claimed(&dedupe_key(&file.path), &dirs));
+    let mut dirs: Vec<String> = dirs.into_iter().collect();
+    dirs.sort();
+    (files, dirs)
+}
+
+fn inside_claimed(key: &str, dirs: &HashSet<String>) -> bool {
+    key.char_indices()
+        .any(|(index, c)| c == '/' && index + 1 < key.len() && dirs.contains(&key[..=index]))
+}
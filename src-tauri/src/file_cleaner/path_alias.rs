@@ -0,0 +1,49 @@
+// src/file_cleaner/path_alias.rs
+
+//! One spelling per file for scan deduplication.
+//!
+//! macOS reaches the same files under several prefixes: the data volume is
+//! firmlinked onto `/` from `/System/Volumes/Data`, and `/var`, `/tmp` and
+//! `/etc` are symlinks into `/private`. Rules that name different spellings
+//! would otherwise report, and total, one file twice.
+
+const DATA_VOLUME: &str = "/system/volumes/data/";
+
+/// Symlinked top-level folders and where they point.
+const PRIVATE_ALIASES: &[(&str, &str)] = &[
+    ("/var/", "/private/var/"),
+    ("/tmp/", "/private/tmp/"),
+    ("/etc/", "/private/etc/"),
+];
+
+/// Lowercased absolute path with alias prefixes resolved, repeated and
+/// trailing separators dropped and `.` components removed. Relative paths
+/// are only lowercased.
+pub(crate) fn dedupe_key(path: &str) -> String {
+    if !path.starts_with('/') {
+        return path.to_lowercase();
+    }
+    let mut key = String::with_capacity(path.len() + 1);
+    for component in path
+        .split('/')
+        .filter(|component| !component.is_empty() && *component != ".")
+    {
+        key.push('/');
+        key.push_str(&component.to_lowercase());
+    }
+    // A trailing separator lets whole-component prefixes match the bare folder too.
+    key.push('/');
+    if let Some(rest) = key.strip_prefix(DATA_VOLUME) {
+        key = format!("/{}", rest);
+    }
+    if let Some((alias, target)) = PRIVATE_ALIASES
+        .iter()
+        .find(|(alias, _)| key.starts_with(alias))
+    {
+        key = format!("{}{}", target, &key[alias.len()..]);
+    }
+    if key.len() > 1 {
+        key.pop();
+    }
+    key
+}
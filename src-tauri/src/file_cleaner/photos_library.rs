@@ -0,0 +1,326 @@
+// src/file_cleaner/photos_library.rs
+
+//! Duplicate originals and large videos inside a Photos library.
+//!
+//! Runs only when asked for. The library's catalog is read with `sqlite3`
+//! in read-only mode and originals are only ever read to compare them;
+//! removing anything from the library would corrupt it, so candidates are
+//! reported with a link that shows them in Photos, where the user deletes
+//! them.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use chrono::{DateTime, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+
+use super::hashing::{self, HashAlgorithm};
+use crate::capabilities::{self, Tool};
+
+const DEFAULT_LIBRARY: &str = "Pictures/Photos Library.photoslibrary";
+const CATALOG: &str = "database/Photos.sqlite";
+const ORIGINALS: &str = "originals";
+/// Videos at least this big are listed.
+pub const LARGE_VIDEO_BYTES: u64 = 500 * 1024 * 1024;
+/// Lists stop after this many entries; their totals still count everything.
+const MAX_LISTED: usize = 200;
+/// Core Data stores dates as seconds since 2001-01-01.
+const CORE_DATA_EPOCH: i64 = 978_307_200;
+const ZKIND_VIDEO: u8 = 1;
+/// Assets not in the Recently Deleted album, with their original's size,
+/// name and dimensions.
+const ASSET_QUERY: &str = "SELECT a.ZUUID, a.ZDIRECTORY, a.ZFILENAME, a.ZKIND, \
+     COALESCE(a.ZDURATION, 0), COALESCE(aa.ZORIGINALFILESIZE, 0), \
+     COALESCE(aa.ZORIGINALFILENAME, a.ZFILENAME), COALESCE(a.ZWIDTH, 0), \
+     COALESCE(a.ZHEIGHT, 0), COALESCE(a.ZDATECREATED, 0) \
+     FROM ZASSET a \
+     JOIN ZADDITIONALASSETATTRIBUTES aa ON aa.ZASSET = a.Z_PK \
+     WHERE a.ZTRASHEDSTATE = 0;";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PhotosAssetKind {
+    Photo,
+    Video,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PhotosAsset {
+    pub uuid: String,
+    /// The name the file was imported with.
+    pub filename: String,
+    pub kind: PhotosAssetKind,
+    /// Size of the original.
+    pub size: u64,
+    pub duration_secs: Option<f64>,
+    pub created: Option<DateTime<Utc>>,
+    /// Whether the original is on this Mac rather than only in iCloud.
+    pub local: bool,
+    /// Opens Photos on this asset.
+    pub photos_url: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PhotosDuplicateGroup {
+    pub assets: Vec<PhotosAsset>,
+    /// Size of each copy.
+    pub size: u64,
+    /// What deleting all copies but one would free.
+    pub reclaimable: u64,
+    /// The originals were compared byte for byte; otherwise the copies only
+    /// share their size, dimensions and capture time because some are not
+    /// downloaded.
+    pub verified: bool,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PhotosLibraryReport {
+    pub library: String,
+    pub asset_count: usize,
+    /// Most reclaimable first.
+    pub duplicate_groups: Vec<PhotosDuplicateGroup>,
+    /// What deleting the extra copies in verified groups would free.
+    pub duplicate_reclaimable: u64,
+    /// The same for groups that could not be compared byte for byte, which
+    /// may not be duplicates at all.
+    pub duplicate_unverified: u64,
+    /// Largest first, at most [`MAX_LISTED`] of them.
+    pub large_videos: Vec<PhotosAsset>,
+    pub large_video_count: usize,
+    pub large_video_size: u64,
+    /// Why parts of the report are missing or less certain.
+    pub notes: Vec<String>,
+}
+
+/// One row of [`ASSET_QUERY`].
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct CatalogAsset {
+    pub uuid: String,
+    pub directory: String,
+    pub filename: String,
+    pub kind: u8,
+    pub duration: f64,
+    pub original_size: u64,
+    pub original_filename: String,
+    pub width: u64,
+    pub height: u64,
+    pub created: f64,
+}
+
+/// The library at `library`, or the system Photos library in the home
+/// folder without one.
+pub fn library_path(library: Option<&str>) -> Result<PathBuf, String> {
+    let path = match library {
+        Some(library) => PathBuf::from(library),
+        None => dirs::home_dir()
+            .ok_or("Home directory not found")?
+            .join(DEFAULT_LIBRARY),
+    };
+    let is_library = path
+        .extension()
+        .is_some_and(|extension| extension == "photoslibrary");
+    if !path.is_absolute() || !is_library || !path.is_dir() {
+        return Err(format!("Not a Photos library: {}", path.display()));
+    }
+    Ok(path)
+}
+
+/// Find duplicate originals and large videos in `library` without changing it.
+pub fn analyze_library(library: &Path) -> Result<PhotosLibraryReport, String> {
+    let assets = parse_assets(&read_catalog(library)?);
+    Ok(build_report(library, &assets))
+}
+
+/// Originals present under `library` are hashed to confirm duplicates.
+pub(crate) fn build_report(library: &Path, assets: &[CatalogAsset]) -> PhotosLibraryReport {
+    let mut report = PhotosLibraryReport {
+        library: library.to_string_lossy().to_string(),
+        asset_count: assets.len(),
+        ..PhotosLibraryReport::default()
+    };
+
+    let mut videos: Vec<&CatalogAsset> = assets
+        .iter()
+        .filter(|asset| asset.kind == ZKIND_VIDEO && asset.original_size >= LARGE_VIDEO_BYTES)
+        .collect();
+    videos.sort_by_key(|asset| std::cmp::Reverse(asset.original_size));
+    report.large_video_count = videos.len();
+    report.large_video_size = videos.iter().map(|asset| asset.original_size).sum();
+    report.large_videos = videos
+        .iter()
+        .take(MAX_LISTED)
+        .map(|asset| photos_asset(library, asset))
+        .collect();
+
+    let mut unverified = false;
+    for candidates in duplicate_candidates(assets) {
+        let paths: Vec<PathBuf> = candidates
+            .iter()
+            .map(|asset| original_path(library, asset))
+            .collect();
+        let groups = if paths.iter().all(|path| path.is_file()) {
+            let mut by_hash: HashMap<String, Vec<&CatalogAsset>> = HashMap::new();
+            for (asset, path) in candidates.iter().zip(&paths) {
+                match hashing::hash_file(HashAlgorithm::default(), path) {
+                    Ok(hash) => by_hash.entry(hash).or_default().push(asset),
+                    Err(err) => log::debug!("Unable to hash {}: {}", path.display(), err),
+                }
+            }
+            by_hash
+                .into_values()
+                .filter(|group| group.len() > 1)
+                .map(|group| (group, true))
+                .collect()
+        } else {
+            unverified = true;
+            vec![(candidates, false)]
+        };
+        for (group, verified) in groups {
+            let size = group[0].original_size;
+            let reclaimable = size * (group.len() as u64 - 1);
+            if verified {
+                report.duplicate_reclaimable += reclaimable;
+            } else {
+                report.duplicate_unverified += reclaimable;
+            }
+            report.duplicate_groups.push(PhotosDuplicateGroup {
+                assets: group
+                    .iter()
+                    .map(|asset| photos_asset(library, asset))
+                    .collect(),
+                size,
+                reclaimable,
+                verified,
+            });
+        }
+    }
+    report
+        .duplicate_groups
+        .sort_by_key(|group| std::cmp::Reverse(group.reclaimable));
+    if unverified {
+        report.notes.push(
+            "Some duplicates could not be compared byte for byte because their originals \
+             are only in iCloud; they match in size, dimensions and capture time"
+                .to_string(),
+        );
+    }
+    report
+}
+
+/// Assets that share an original size, kind, dimensions and capture time.
+pub(crate) fn duplicate_candidates(assets: &[CatalogAsset]) -> Vec<Vec<&CatalogAsset>> {
+    let mut groups: HashMap<(u64, u8, u64, u64, i64), Vec<&CatalogAsset>> = HashMap::new();
+    for asset in assets.iter().filter(|asset| asset.original_size > 0) {
+        let key = (
+            asset.original_size,
+            asset.kind,
+            asset.width,
+            asset.height,
+            asset.created.round() as i64,
+        );
+        groups.entry(key).or_default().push(asset);
+    }
+    let mut groups: Vec<Vec<&CatalogAsset>> = groups
+        .into_values()
+        .filter(|group| group.len() > 1)
+        .collect();
+    groups.sort_by_key(|group| std::cmp::Reverse(group[0].original_size));
+    groups
+}
+
+/// Rows of [`ASSET_QUERY`] as `sqlite3` prints them, tab separated.
+pub(crate) fn parse_assets(output: &str) -> Vec<CatalogAsset> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split('\t').collect();
+            let [uuid, directory, filename, kind, duration, size, original_filename, width, height, created] =
+                fields[..]
+            else {
+                return None;
+            };
+            Some(CatalogAsset {
+                uuid: uuid.to_string(),
+                directory: directory.to_string(),
+                filename: filename.to_string(),
+                kind: kind.parse().ok()?,
+                duration: duration.parse().unwrap_or(0.0),
+                original_size: size.parse().unwrap_or(0),
+                original_filename: original_filename.to_string(),
+                width: width.parse().unwrap_or(0),
+                height: height.parse().unwrap_or(0),
+                created: created.parse().unwrap_or(0.0),
+            })
+        })
+        .collect()
+}
+
+/// The `photos-navigation` link that shows the asset `uuid` in Photos.
+pub fn photos_url(uuid: &str) -> String {
+    format!("photos-navigation://asset?uuid={}", uuid)
+}
+
+/// Show the asset `uuid` in Photos, where the user can delete it.
+pub fn open_in_photos(uuid: &str) -> Result<(), String> {
+    let is_uuid = uuid.len() == 36 && uuid.chars().all(|c| c.is_ascii_hexdigit() || c == '-');
+    if !is_uuid {
+        return Err(format!("Invalid Photos asset: {}", uuid));
+    }
+    let status = Command::new("open")
+        .arg(photos_url(uuid))
+        .status()
+        .map_err(|e| format!("Failed to open Photos: {}", e))?;
+    if !status.success() {
+        return Err(format!("Failed to open Photos ({})", status));
+    }
+    Ok(())
+}
+
+fn photos_asset(library: &Path, asset: &CatalogAsset) -> PhotosAsset {
+    let kind = if asset.kind == ZKIND_VIDEO {
+        PhotosAssetKind::Video
+    } else {
+        PhotosAssetKind::Photo
+    };
+    PhotosAsset {
+        uuid: asset.uuid.clone(),
+        filename: asset.original_filename.clone(),
+        kind,
+        size: asset.original_size,
+        duration_secs: (kind == PhotosAssetKind::Video).then_some(asset.duration),
+        created: (asset.created > 0.0)
+            .then(|| {
+                Utc.timestamp_opt(CORE_DATA_EPOCH + asset.created as i64, 0)
+                    .single()
+            })
+            .flatten(),
+        local: original_path(library, asset).is_file(),
+        photos_url: photos_url(&asset.uuid),
+    }
+}
+
+fn original_path(library: &Path, asset: &CatalogAsset) -> PathBuf {
+    library
+        .join(ORIGINALS)
+        .join(&asset.directory)
+        .join(&asset.filename)
+}
+
+/// Needs `sqlite3` and Full Disk Access; the catalog is opened read-only.
+fn read_catalog(library: &Path) -> Result<String, String> {
+    let sqlite = capabilities::tool_path(Tool::Sqlite3).ok_or("sqlite3 is not installed")?;
+    let output = Command::new(sqlite)
+        .args(["-readonly", "-separator", "\t"])
+        .arg(library.join(CATALOG))
+        .arg(ASSET_QUERY)
+        .output()
+        .map_err(|e| format!("Failed to run sqlite3: {}", e))?;
+    if !output.status.success() {
+        return Err(format!(
+            "The Photos library can't be read ({})",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
@@ -1,14 +1,28 @@
 use std::collections::HashSet;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 
 use log::debug;
 use sysinfo::System;
+use tokio::process::Command;
 use tokio::task;
+use tokio::time::{timeout, Duration};
 
+/// Paths passed to one `lsof` run; keeps the command line well under ARG_MAX.
+const LSOF_BATCH: usize = 256;
+const LSOF_TIMEOUT: Duration = Duration::from_secs(10);
+/// Per path, when a batch has to be checked one path at a time.
+const LSOF_FILE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// The processes running when a scan started, captured once and shared by
+/// every in-use check of that scan.
 #[derive(Clone, Default)]
 pub struct ProcessSnapshot {
     process_names: Arc<HashSet<String>>,
     command_paths: Arc<HashSet<String>>,
+    pids: Arc<HashSet<u32>>,
+    /// Scan results some process holds open, from [`open_files_among`].
+    open_files: Arc<HashSet<PathBuf>>,
 }
 
 impl ProcessSnapshot {
@@ -16,11 +30,58 @@ impl ProcessSnapshot {
         Self::default()
     }
 
+    #[cfg(test)]
+    pub(crate) fn with_process_names(names: &[&str]) -> Self {
+        Self {
+            process_names: Arc::new(names.iter().map(|name| name.to_lowercase()).collect()),
+            command_paths: Arc::default(),
+            pids: Arc::default(),
+            open_files: Arc::default(),
+        }
+    }
+
+    #[cfg(test)]
+    pub(crate) fn with_command_paths(paths: &[&str]) -> Self {
+        Self {
+            command_paths: Arc::new(paths.iter().map(|path| path.to_lowercase()).collect()),
+            ..Self::default()
+        }
+    }
+
+    /// The same snapshot, with `open_files` as the files held open.
+    pub fn with_open_files(mut self, open_files: HashSet<PathBuf>) -> Self {
+        self.open_files = Arc::new(open_files);
+        self
+    }
+
+    pub fn has_open_file(&self, path: &Path) -> bool {
+        self.open_files.contains(path)
+    }
+
     pub fn has_process_named(&self, name: &str) -> bool {
         let target = name.to_lowercase();
         self.process_names.contains(&target)
     }
 
+    /// Whether some process was started from inside the app bundle at
+    /// `bundle`, whatever its executable is called.
+    pub fn runs_from_bundle(&self, bundle: &Path) -> bool {
+        let prefix = format!(
+            "{}/",
+            bundle
+                .to_string_lossy()
+                .trim_end_matches('/')
+                .to_lowercase()
+        );
+        self.command_paths
+            .iter()
+            .any(|cmd| cmd.starts_with(&prefix))
+    }
+
+    pub fn has_pid(&self, pid: u32) -> bool {
+        self.pids.contains(&pid)
+    }
+
     pub fn command_contains_path(&self, path: &str) -> bool {
         if self.command_paths.is_empty() {
             return false;
@@ -46,8 +107,10 @@ fn capture_snapshot() -> ProcessSnapshot {
 
     let mut names = HashSet::new();
     let mut command_paths = HashSet::new();
+    let mut pids = HashSet::new();
 
-    for process in system.processes().values() {
+    for (pid, process) in system.processes() {
+        pids.insert(pid.as_u32());
         let name = process.name().to_lowercase();
         names.insert(name);
 
@@ -61,5 +124,88 @@ fn capture_snapshot() -> ProcessSnapshot {
     ProcessSnapshot {
         process_names: Arc::new(names),
         command_paths: Arc::new(command_paths),
+        pids: Arc::new(pids),
+        open_files: Arc::default(),
+    }
+}
+
+/// The files among `paths` that some process has open, asking `lsof` about
+/// a batch of paths per run instead of once per file. A batch whose output
+/// cannot be parsed is checked again one path at a time.
+pub async fn open_files_among(
+    lsof_program: &Path,
+    paths: &[PathBuf],
+) -> Result<HashSet<PathBuf>, String> {
+    let mut open = HashSet::new();
+    for batch in paths.chunks(LSOF_BATCH) {
+        let mut args = vec!["-F".as_ref(), "n".as_ref(), "--".as_ref()];
+        args.extend(batch.iter().map(|path| path.as_os_str()));
+        let output = run_lsof(lsof_program, &args, LSOF_TIMEOUT).await?;
+        let Some(names) = parse_lsof_names(&output) else {
+            log::debug!(
+                "Unexpected lsof output; checking {} files one by one",
+                batch.len()
+            );
+            for path in batch {
+                let args = ["--".as_ref(), path.as_os_str()];
+                if !run_lsof(lsof_program, &args, LSOF_FILE_TIMEOUT)
+                    .await?
+                    .trim()
+                    .is_empty()
+                {
+                    open.insert(path.clone());
+                }
+            }
+            continue;
+        };
+        // lsof names files by their resolved path, e.g. /private/tmp for /tmp.
+        open.extend(
+            batch
+                .iter()
+                .filter(|path| {
+                    names.contains(*path)
+                        || std::fs::canonicalize(path).is_ok_and(|real| names.contains(&real))
+                })
+                .cloned(),
+        );
+    }
+    Ok(open)
+}
+
+/// Standard output of `lsof` run with `args`. lsof exits 1 when any of
+/// the paths is not open, so the exit status is ignored.
+async fn run_lsof(
+    lsof_program: &Path,
+    args: &[&std::ffi::OsStr],
+    limit: Duration,
+) -> Result<String, String> {
+    let mut command = Command::new(lsof_program);
+    command.args(args).kill_on_drop(true);
+    match timeout(limit, command.output()).await {
+        Ok(Ok(output)) => Ok(String::from_utf8_lossy(&output.stdout).to_string()),
+        Ok(Err(err)) => Err(format!(
+            "lsof check failed using {}: {}",
+            lsof_program.display(),
+            err
+        )),
+        Err(_) => Err(format!("lsof timed out using {}", lsof_program.display())),
+    }
+}
+
+/// File names from `lsof -F n` output, whose lines are tagged by their
+/// first character: `p` for the process, `f` for the descriptor and `n`
+/// for the name. `None` if any line is not one of those.
+pub(crate) fn parse_lsof_names(output: &str) -> Option<HashSet<PathBuf>> {
+    let mut names = HashSet::new();
+    for line in output.lines().filter(|line| !line.is_empty()) {
+        let tag_len = line.chars().next().map_or(0, char::len_utf8);
+        match line.split_at(tag_len) {
+            ("n", name) => {
+                names.insert(PathBuf::from(name));
+            }
+            ("p", rest) | ("f", rest) if !rest.contains(' ') => {}
+            _ => return None,
+        }
     }
+    Some(names)
 }
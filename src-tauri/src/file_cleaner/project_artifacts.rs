@@ -0,0 +1,361 @@
+// src/file_cleaner/project_artifacts.rs
+
+//! Dependency and build folders of Node, Python and Rust projects under the
+//! development roots the user picks.
+//!
+//! Everything found can be recreated with the project's own tooling, but
+//! only projects nobody has touched for a while are worth the rebuild: a
+//! project counts as stale when neither its sources nor its last commit
+//! changed within the threshold and `git status` shows no uncommitted work.
+//! Only artifacts of stale projects are recommended.
+
+use std::cmp::Reverse;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::SystemTime;
+
+use serde::{Deserialize, Serialize};
+use tokio_util::sync::CancellationToken;
+use walkdir::WalkDir;
+
+use super::engine::FileCleaner;
+use super::exclusions::ExclusionMatcher;
+use super::types::CleanableFile;
+use crate::capabilities::{self, Tool};
+
+pub const PROJECT_ARTIFACTS_CATEGORY: &str = "Project Build Artifacts";
+pub const DEFAULT_STALE_DAYS: i64 = 90;
+
+/// Folders searched when the user has not picked any, relative to home.
+const DEFAULT_ROOTS: &[&str] = &[
+    "Developer",
+    "Projects",
+    "Code",
+    "code",
+    "src",
+    "dev",
+    "git",
+    "workspace",
+];
+/// How deep below a root projects are looked for.
+const MAX_PROJECT_DEPTH: usize = 5;
+/// How deep into a project its sources are checked for recent changes.
+const ACTIVITY_DEPTH: usize = 3;
+/// Entries looked at per project when dating its sources.
+const MAX_ACTIVITY_ENTRIES: usize = 2000;
+/// Never descended into while looking for projects or dating sources.
+const SKIPPED_DIRS: &[&str] = &["node_modules", "target", "build", "venv", "__pycache__"];
+const VENV_NAMES: &[&str] = &[".venv", "venv", "env", ".env"];
+/// Manifests of the build systems that write to `build/`.
+const BUILD_MANIFESTS: &[&str] = &[
+    "package.json",
+    "setup.py",
+    "pyproject.toml",
+    "build.gradle",
+    "build.gradle.kts",
+    "CMakeLists.txt",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProjectArtifactKind {
+    NodeModules,
+    PythonVenv,
+    Tox,
+    RustTarget,
+    Build,
+}
+
+impl ProjectArtifactKind {
+    pub fn label(self) -> &'static str {
+        match self {
+            ProjectArtifactKind::NodeModules => "node_modules",
+            ProjectArtifactKind::PythonVenv => "Python virtual environment",
+            ProjectArtifactKind::Tox => "tox environments",
+            ProjectArtifactKind::RustTarget => "Cargo target",
+            ProjectArtifactKind::Build => "build output",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProjectArtifact {
+    pub path: String,
+    pub kind: ProjectArtifactKind,
+    pub size: u64,
+    pub last_modified: i64,
+    /// The project is stale, so the folder is worth rebuilding later.
+    pub recommended: bool,
+}
+
+/// What `git` says about a project.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GitState {
+    pub uncommitted_changes: usize,
+    pub last_commit: Option<i64>,
+}
+
+/// One project and the artifacts found in it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProjectArtifactGroup {
+    pub project: String,
+    pub name: String,
+    /// Newest of the sources' modification times and the last commit.
+    pub last_activity: i64,
+    /// `None` when the project is not a git repository or git is missing.
+    pub git: Option<GitState>,
+    pub stale: bool,
+    pub total_size: u64,
+    pub artifacts: Vec<ProjectArtifact>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProjectArtifactReport {
+    pub roots: Vec<String>,
+    pub projects: Vec<ProjectArtifactGroup>,
+    pub total_size: u64,
+    pub recommended_size: u64,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ProjectArtifactQuery {
+    /// Development roots; the usual ones under home when empty.
+    pub roots: Vec<String>,
+    /// Days without changes after which a project counts as stale.
+    pub stale_days: Option<i64>,
+}
+
+/// The usual development folders under `home` that exist.
+pub fn default_roots(home: &Path) -> Vec<PathBuf> {
+    DEFAULT_ROOTS
+        .iter()
+        .map(|name| home.join(name))
+        .filter(|root| root.is_dir())
+        .collect()
+}
+
+impl ProjectArtifact {
+    pub(crate) fn to_cleanable(&self, project: &ProjectArtifactGroup) -> CleanableFile {
+        let idle_days = (unix_now() - project.last_activity).max(0) / 86_400;
+        CleanableFile {
+            path: self.path.clone(),
+            size: self.size,
+            category: PROJECT_ARTIFACTS_CATEGORY.to_string(),
+            description: format!(
+                "{} of {} (no changes in {} days)",
+                self.kind.label(),
+                project.name,
+                idle_days
+            ),
+            last_modified: self.last_modified,
+            safe_to_delete: false,
+            safety_score: 0,
+            auto_select: false,
+        }
+    }
+}
+
+/// Find the artifacts of every project under `roots`, grouped by project
+/// and largest first. `git` reports on a project folder.
+pub(crate) fn scan_project_artifacts(
+    roots: &[PathBuf],
+    stale_days: i64,
+    exclusions: &ExclusionMatcher,
+    git: &dyn Fn(&Path) -> Option<GitState>,
+    token: &CancellationToken,
+) -> Result<ProjectArtifactReport, String> {
+    let stale_before = unix_now() - stale_days.max(0) * 86_400;
+    let mut projects: Vec<ProjectArtifactGroup> = Vec::new();
+    for root in roots {
+        let walker = WalkDir::new(root)
+            .max_depth(MAX_PROJECT_DEPTH)
+            .into_iter()
+            .filter_entry(|entry| entry.depth() == 0 || !is_skipped(entry.file_name()));
+        for entry in walker {
+            if token.is_cancelled() {
+                return Err("cancelled".into());
+            }
+            let Ok(entry) = entry else {
+                continue;
+            };
+            if !entry.file_type().is_dir() {
+                continue;
+            }
+            let dir = entry.path();
+            // Nested roots reach the same projects twice
+            if projects
+                .iter()
+                .any(|project| Path::new(&project.project) == dir)
+            {
+                continue;
+            }
+            let found: Vec<(PathBuf, ProjectArtifactKind)> = artifacts_in(dir)
+                .into_iter()
+                .filter(|(path, _)| {
+                    !exclusions.is_excluded(&path.to_string_lossy(), PROJECT_ARTIFACTS_CATEGORY)
+                })
+                .collect();
+            if found.is_empty() {
+                continue;
+            }
+            let git_state = git(dir);
+            let last_activity = source_activity(dir)
+                .max(git_state.and_then(|state| state.last_commit).unwrap_or(0));
+            let stale = last_activity < stale_before
+                && git_state.is_none_or(|state| state.uncommitted_changes == 0);
+            let mut artifacts = Vec::new();
+            for (path, kind) in found {
+                artifacts.push(ProjectArtifact {
+                    size: directory_size(&path, token)?,
+                    last_modified: modified_secs(&path),
+                    path: path.to_string_lossy().to_string(),
+                    kind,
+                    recommended: stale,
+                });
+            }
+            artifacts.sort_by_key(|artifact| Reverse(artifact.size));
+            projects.push(ProjectArtifactGroup {
+                project: dir.to_string_lossy().to_string(),
+                name: dir
+                    .file_name()
+                    .map(|name| name.to_string_lossy().to_string())
+                    .unwrap_or_default(),
+                last_activity,
+                git: git_state,
+                stale,
+                total_size: artifacts.iter().map(|artifact| artifact.size).sum(),
+                artifacts,
+            });
+        }
+    }
+    projects.sort_by(|a, b| {
+        b.total_size
+            .cmp(&a.total_size)
+            .then_with(|| a.project.cmp(&b.project))
+    });
+    Ok(ProjectArtifactReport {
+        roots: roots
+            .iter()
+            .map(|root| root.to_string_lossy().to_string())
+            .collect(),
+        total_size: projects.iter().map(|project| project.total_size).sum(),
+        recommended_size: projects
+            .iter()
+            .filter(|project| project.stale)
+            .map(|project| project.total_size)
+            .sum(),
+        projects,
+    })
+}
+
+/// Uncommitted changes and last commit time of the repository at
+/// `project`, or `None` if it is not one or git is missing.
+pub(crate) fn git_state(project: &Path) -> Option<GitState> {
+    if !project.join(".git").exists() || !capabilities::tool_available(Tool::Git) {
+        return None;
+    }
+    let git = |args: &[&str]| {
+        Command::new("git")
+            .arg("-C")
+            .arg(project)
+            .args(args)
+            .output()
+            .ok()
+            .filter(|output| output.status.success())
+            .map(|output| String::from_utf8_lossy(&output.stdout).to_string())
+    };
+    let status = git(&["status", "--porcelain"])?;
+    Some(GitState {
+        uncommitted_changes: status
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .count(),
+        last_commit: git(&["log", "-1", "--format=%ct"])
+            .and_then(|output| output.trim().parse().ok()),
+    })
+}
+
+/// The artifact folders directly inside `dir`, judged by the manifests
+/// next to them.
+fn artifacts_in(dir: &Path) -> Vec<(PathBuf, ProjectArtifactKind)> {
+    let has = |name: &str| dir.join(name).exists();
+    let mut found = Vec::new();
+    if has("package.json") && dir.join("node_modules").is_dir() {
+        found.push((dir.join("node_modules"), ProjectArtifactKind::NodeModules));
+    }
+    for name in VENV_NAMES {
+        let venv = dir.join(name);
+        if venv.join("pyvenv.cfg").is_file() {
+            found.push((venv, ProjectArtifactKind::PythonVenv));
+        }
+    }
+    if (has("tox.ini") || has("pyproject.toml") || has("setup.py")) && dir.join(".tox").is_dir() {
+        found.push((dir.join(".tox"), ProjectArtifactKind::Tox));
+    }
+    if has("Cargo.toml") && dir.join("target").is_dir() {
+        found.push((dir.join("target"), ProjectArtifactKind::RustTarget));
+    }
+    if BUILD_MANIFESTS.iter().any(|manifest| has(manifest)) && dir.join("build").is_dir() {
+        found.push((dir.join("build"), ProjectArtifactKind::Build));
+    }
+    found
+}
+
+/// Newest modification time among the project's own files.
+fn source_activity(project: &Path) -> i64 {
+    WalkDir::new(project)
+        .max_depth(ACTIVITY_DEPTH)
+        .into_iter()
+        .filter_entry(|entry| entry.depth() == 0 || !is_skipped(entry.file_name()))
+        .filter_map(Result::ok)
+        .take(MAX_ACTIVITY_ENTRIES)
+        .filter(|entry| entry.file_type().is_file())
+        .filter_map(|entry| entry.metadata().ok())
+        .filter_map(|metadata| metadata.modified().ok())
+        .filter_map(|time| time.duration_since(SystemTime::UNIX_EPOCH).ok())
+        .map(|since| since.as_secs() as i64)
+        .max()
+        .unwrap_or(0)
+}
+
+fn is_skipped(name: &std::ffi::OsStr) -> bool {
+    let name = name.to_string_lossy();
+    name.starts_with('.') || SKIPPED_DIRS.contains(&name.as_ref())
+}
+
+fn directory_size(path: &Path, token: &CancellationToken) -> Result<u64, String> {
+    let mut total = 0u64;
+    for (index, entry) in WalkDir::new(path)
+        .into_iter()
+        .filter_map(Result::ok)
+        .enumerate()
+    {
+        if index.is_multiple_of(4096) && token.is_cancelled() {
+            return Err("cancelled".into());
+        }
+        if entry.file_type().is_file() {
+            if let Ok(metadata) = entry.metadata() {
+                total = total.saturating_add(FileCleaner::metadata_size_bytes(&metadata));
+            }
+        }
+    }
+    Ok(total)
+}
+
+fn modified_secs(path: &Path) -> i64 {
+    fs::metadata(path)
+        .and_then(|metadata| metadata.modified())
+        .ok()
+        .and_then(|time| time.duration_since(SystemTime::UNIX_EPOCH).ok())
+        .map(|since| since.as_secs() as i64)
+        .unwrap_or(0)
+}
+
+fn unix_now() -> i64 {
+    SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map(|since| since.as_secs() as i64)
+        .unwrap_or(0)
+}
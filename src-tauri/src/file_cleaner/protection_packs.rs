@@ -0,0 +1,215 @@
+use std::path::Path;
+#[cfg(any(test, feature = "app"))]
+use std::path::PathBuf;
+
+#[cfg(any(test, feature = "app"))]
+use serde::{Deserialize, Serialize};
+
+use crate::persistence;
+
+const PROTECTION_PACKS_FILE: &str = "protection_packs.json";
+
+/// Extra protected locations for one kind of work, matched like the risk
+/// module's built-in ones: keywords against the lowercased path, sequences
+/// against its lowercased segments.
+#[derive(Debug)]
+struct PackDefinition {
+    id: &'static str,
+    name: &'static str,
+    #[cfg(any(test, feature = "app"))]
+    description: &'static str,
+    keywords: &'static [&'static str],
+    sequences: &'static [&'static [&'static str]],
+}
+
+const PACKS: &[PackDefinition] = &[
+    PackDefinition {
+        id: "photographer",
+        name: "Photographer",
+        #[cfg(any(test, feature = "app"))]
+        description: "Lightroom and Capture One catalogs, photo libraries and RAW files",
+        keywords: &[
+            ".lrcat",
+            ".lrlibrary",
+            ".cocatalog",
+            ".cosessiondb",
+            ".photoslibrary",
+            ".xmp",
+            ".dng",
+            ".cr2",
+            ".cr3",
+            ".nef",
+            ".arw",
+            ".raf",
+            ".orf",
+            ".rw2",
+        ],
+        sequences: &[
+            &["lightroom"],
+            &["capture one"],
+            &["library", "application support", "adobe", "lightroom"],
+        ],
+    },
+    PackDefinition {
+        id: "developer",
+        name: "Developer",
+        #[cfg(any(test, feature = "app"))]
+        description: "Xcode projects and archives, signing profiles and Sketch design files",
+        keywords: &[
+            ".xcodeproj",
+            ".xcworkspace",
+            ".xcarchive",
+            ".mobileprovision",
+            ".sketch",
+        ],
+        sequences: &[
+            &["library", "developer", "xcode", "archives"],
+            &["library", "mobiledevice", "provisioning profiles"],
+        ],
+    },
+    PackDefinition {
+        id: "music-producer",
+        name: "Music producer",
+        #[cfg(any(test, feature = "app"))]
+        description: "Ableton, Logic and other DAW projects, recordings and sound libraries",
+        keywords: &[
+            ".als", ".alc", ".logicx", ".band", ".ptx", ".flp", ".cpr", ".rpp", ".nki", ".exs",
+        ],
+        sequences: &[
+            &["ableton project info"],
+            &["samples", "recorded"],
+            &["samples", "processed"],
+            &["library", "application support", "ableton"],
+            &["library", "application support", "logic"],
+            &["library", "audio", "apple loops"],
+            &["library", "audio", "presets"],
+        ],
+    },
+];
+
+/// A protection pack as shown in settings.
+#[cfg(any(test, feature = "app"))]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProtectionPack {
+    pub id: String,
+    pub name: String,
+    pub description: String,
+    pub enabled: bool,
+}
+
+/// The protection packs the user opted into.
+#[derive(Default)]
+pub struct ProtectionPackStore {
+    #[cfg(any(test, feature = "app"))]
+    path: Option<PathBuf>,
+    enabled: Vec<String>,
+}
+
+impl ProtectionPackStore {
+    /// Load from `<data dir>/macos-optimizer/protection_packs.json`.
+    pub fn load() -> Self {
+        match persistence::data_file(PROTECTION_PACKS_FILE) {
+            Some(path) => Self::load_from(&path),
+            None => Self::default(),
+        }
+    }
+
+    /// Load from an explicit file; a missing or unreadable file enables no packs.
+    pub fn load_from(path: &Path) -> Self {
+        let enabled = persistence::read_json(path).unwrap_or_default();
+        Self {
+            #[cfg(any(test, feature = "app"))]
+            path: Some(path.to_path_buf()),
+            enabled,
+        }
+    }
+
+    /// Every pack, enabled or not.
+    #[cfg(any(test, feature = "app"))]
+    pub fn list(&self) -> Vec<ProtectionPack> {
+        PACKS
+            .iter()
+            .map(|pack| ProtectionPack {
+                id: pack.id.to_string(),
+                name: pack.name.to_string(),
+                description: pack.description.to_string(),
+                enabled: self.enabled.iter().any(|id| id == pack.id),
+            })
+            .collect()
+    }
+
+    #[cfg(any(test, feature = "app"))]
+    pub fn enable(&mut self, id: &str) -> Result<(), String> {
+        let pack = definition(id)?;
+        if !self.enabled.iter().any(|enabled| enabled == pack.id) {
+            self.enabled.push(pack.id.to_string());
+        }
+        self.persist()
+    }
+
+    #[cfg(any(test, feature = "app"))]
+    pub fn disable(&mut self, id: &str) -> Result<(), String> {
+        let pack = definition(id)?;
+        self.enabled.retain(|enabled| enabled != pack.id);
+        self.persist()
+    }
+
+    pub(crate) fn matcher(&self) -> ProtectionPacks {
+        ProtectionPacks {
+            packs: PACKS
+                .iter()
+                .filter(|pack| self.enabled.iter().any(|id| id == pack.id))
+                .collect(),
+        }
+    }
+
+    #[cfg(any(test, feature = "app"))]
+    fn persist(&self) -> Result<(), String> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        persistence::write_json(path, &self.enabled)
+    }
+}
+
+#[cfg(any(test, feature = "app"))]
+fn definition(id: &str) -> Result<&'static PackDefinition, String> {
+    PACKS
+        .iter()
+        .find(|pack| pack.id.eq_ignore_ascii_case(id.trim()))
+        .ok_or_else(|| format!("Unknown protection pack: {}", id))
+}
+
+/// Enabled packs, compiled for scans.
+#[derive(Debug, Clone, Default)]
+pub struct ProtectionPacks {
+    packs: Vec<&'static PackDefinition>,
+}
+
+impl ProtectionPacks {
+    /// The enabled packs; none if they cannot be loaded.
+    pub(crate) fn load() -> Self {
+        ProtectionPackStore::load().matcher()
+    }
+
+    /// Name of the first enabled pack protecting a path, given whether a
+    /// keyword or segment sequence of it is present.
+    pub(crate) fn protecting(
+        &self,
+        contains_keyword: impl Fn(&str) -> bool,
+        contains_sequence: impl Fn(&[&str]) -> bool,
+    ) -> Option<&'static str> {
+        self.packs
+            .iter()
+            .find(|pack| {
+                pack.keywords
+                    .iter()
+                    .any(|keyword| contains_keyword(keyword))
+                    || pack
+                        .sequences
+                        .iter()
+                        .any(|sequence| contains_sequence(sequence))
+            })
+            .map(|pack| pack.name)
+    }
+}
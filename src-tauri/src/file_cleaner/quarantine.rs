@@ -0,0 +1,285 @@
+//! Quarantine: an optional staging area between a clean and the Trash.
+//!
+//! While enabled, cleaned items are moved into
+//! `<data dir>/macos-optimizer/quarantine/<id>/` instead of the Trash and
+//! recorded in the manifest next to them. They can be put back from there
+//! until they are purged, by hand or once they are older than the retention
+//! period.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+
+use super::trash_restore;
+use crate::persistence;
+
+const QUARANTINE_DIR: &str = "quarantine";
+const MANIFEST_FILE: &str = "manifest.json";
+const SETTINGS_FILE: &str = "quarantine.json";
+const DEFAULT_RETENTION_DAYS: u32 = 7;
+const MAX_RETENTION_DAYS: u32 = 90;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct QuarantineSettings {
+    pub enabled: bool,
+    /// Days an item stays quarantined before it is purged.
+    pub retention_days: u32,
+}
+
+impl Default for QuarantineSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            retention_days: DEFAULT_RETENTION_DAYS,
+        }
+    }
+}
+
+/// One quarantined item, as recorded in the manifest.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct QuarantineEntry {
+    pub id: String,
+    pub original_path: String,
+    pub staged_path: String,
+    pub category: String,
+    pub size: u64,
+    pub quarantined_at: DateTime<Utc>,
+    /// When the item is purged under the current retention period.
+    pub expires_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QuarantineRestoreItem {
+    pub id: String,
+    pub original_path: Option<String>,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QuarantineRestoreResult {
+    pub restored: usize,
+    pub failed: usize,
+    pub items: Vec<QuarantineRestoreItem>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct QuarantinePurgeResult {
+    pub purged: usize,
+    pub freed: u64,
+    /// `original path: reason` for each item that could not be removed.
+    pub failed: Vec<String>,
+}
+
+/// The staging directory, its manifest and the quarantine settings.
+#[derive(Default)]
+pub struct QuarantineStore {
+    dir: Option<PathBuf>,
+    settings_path: Option<PathBuf>,
+    settings: QuarantineSettings,
+    entries: Vec<QuarantineEntry>,
+}
+
+impl QuarantineStore {
+    /// Load from `<data dir>/macos-optimizer/`.
+    pub fn load() -> Self {
+        match persistence::data_dir() {
+            Some(app_dir) => {
+                Self::load_from(&app_dir.join(QUARANTINE_DIR), &app_dir.join(SETTINGS_FILE))
+            }
+            None => Self::default(),
+        }
+    }
+
+    /// Load the manifest in `dir` and the settings from `settings_path`.
+    /// Entries whose staged item has gone missing are dropped.
+    pub fn load_from(dir: &Path, settings_path: &Path) -> Self {
+        let settings = persistence::read_json(settings_path).unwrap_or_default();
+        let mut entries: Vec<QuarantineEntry> =
+            persistence::read_json(&dir.join(MANIFEST_FILE)).unwrap_or_default();
+        entries.retain(|entry| Path::new(&entry.staged_path).symlink_metadata().is_ok());
+        Self {
+            dir: Some(dir.to_path_buf()),
+            settings_path: Some(settings_path.to_path_buf()),
+            settings,
+            entries,
+        }
+    }
+
+    pub fn settings(&self) -> QuarantineSettings {
+        self.settings
+    }
+
+    /// Whether cleans should quarantine instead of using the Trash.
+    pub fn is_enabled(&self) -> bool {
+        self.settings.enabled && self.dir.is_some()
+    }
+
+    /// Change the settings; a new retention period applies to items already
+    /// quarantined too.
+    pub fn update_settings(&mut self, settings: QuarantineSettings) -> Result<(), String> {
+        if !(1..=MAX_RETENTION_DAYS).contains(&settings.retention_days) {
+            return Err(format!(
+                "Retention must be between 1 and {} days",
+                MAX_RETENTION_DAYS
+            ));
+        }
+        self.settings = settings;
+        let retention = self.retention();
+        for entry in &mut self.entries {
+            entry.expires_at = entry.quarantined_at + retention;
+        }
+        self.persist_manifest()?;
+        let Some(path) = &self.settings_path else {
+            return Ok(());
+        };
+        persistence::write_json(path, &self.settings)
+    }
+
+    /// Newest first.
+    pub fn list(&self) -> Vec<QuarantineEntry> {
+        let mut entries = self.entries.clone();
+        entries.sort_by_key(|entry| std::cmp::Reverse(entry.quarantined_at));
+        entries
+    }
+
+    /// Move `path` into the staging directory and return where it went.
+    /// Fails, leaving `path` alone, when it is on another volume.
+    pub fn stage(&mut self, path: &Path, category: &str, size: u64) -> Result<PathBuf, String> {
+        let dir = self.dir.as_ref().ok_or("Quarantine is not available")?;
+        let name = path.file_name().ok_or("Cannot quarantine a volume root")?;
+        let id = uuid::Uuid::new_v4().to_string();
+        let item_dir = dir.join(&id);
+        fs::create_dir_all(&item_dir)
+            .map_err(|e| format!("Failed to create {}: {}", item_dir.display(), e))?;
+        let staged = item_dir.join(name);
+        if let Err(err) = fs::rename(path, &staged) {
+            let _ = fs::remove_dir(&item_dir);
+            return Err(format!("Failed to quarantine {}: {}", path.display(), err));
+        }
+        let quarantined_at = Utc::now();
+        self.entries.push(QuarantineEntry {
+            id,
+            original_path: path.to_string_lossy().to_string(),
+            staged_path: staged.to_string_lossy().to_string(),
+            category: category.to_string(),
+            size,
+            quarantined_at,
+            expires_at: quarantined_at + self.retention(),
+        });
+        Ok(staged)
+    }
+
+    /// Drop entries whose staged item is gone, e.g. after an undo moved it
+    /// back.
+    pub fn forget_missing(&mut self) -> Result<(), String> {
+        let before = self.entries.len();
+        self.entries
+            .retain(|entry| Path::new(&entry.staged_path).symlink_metadata().is_ok());
+        if self.entries.len() == before {
+            return Ok(());
+        }
+        self.persist_manifest()
+    }
+
+    /// Save the manifest after a batch of `stage` calls.
+    pub fn persist_manifest(&self) -> Result<(), String> {
+        let Some(dir) = &self.dir else {
+            return Ok(());
+        };
+        persistence::write_json(&dir.join(MANIFEST_FILE), &self.entries)
+    }
+
+    /// Move the items `ids` back to where they were cleaned from. Nothing is
+    /// overwritten: an item whose original location is taken stays put.
+    pub fn restore(&mut self, ids: &[String]) -> Result<QuarantineRestoreResult, String> {
+        let mut items = Vec::with_capacity(ids.len());
+        for id in ids {
+            let Some(index) = self.entries.iter().position(|entry| &entry.id == id) else {
+                items.push(QuarantineRestoreItem {
+                    id: id.clone(),
+                    original_path: None,
+                    error: Some("Not in quarantine".to_string()),
+                });
+                continue;
+            };
+            let entry = &self.entries[index];
+            let staged = PathBuf::from(&entry.staged_path);
+            let outcome = if staged.symlink_metadata().is_err() {
+                Err("No longer in quarantine".to_string())
+            } else {
+                trash_restore::move_back(&staged, Path::new(&entry.original_path))
+            };
+            let original_path = Some(entry.original_path.clone());
+            if outcome.is_ok() || staged.symlink_metadata().is_err() {
+                if let Some(item_dir) = staged.parent() {
+                    let _ = fs::remove_dir(item_dir);
+                }
+                self.entries.remove(index);
+            }
+            items.push(QuarantineRestoreItem {
+                id: id.clone(),
+                original_path,
+                error: outcome.err(),
+            });
+        }
+        self.persist_manifest()?;
+        let restored = items.iter().filter(|item| item.error.is_none()).count();
+        Ok(QuarantineRestoreResult {
+            restored,
+            failed: items.len() - restored,
+            items,
+        })
+    }
+
+    /// Delete the items `ids` for good, or every item with `None`.
+    pub fn purge(&mut self, ids: Option<&[String]>) -> Result<QuarantinePurgeResult, String> {
+        self.purge_where(|entry| ids.is_none_or(|ids| ids.contains(&entry.id)))
+    }
+
+    /// Delete the items that outlived the retention period as of `now`.
+    pub fn purge_expired(&mut self, now: DateTime<Utc>) -> Result<QuarantinePurgeResult, String> {
+        self.purge_where(|entry| entry.expires_at <= now)
+    }
+
+    fn purge_where(
+        &mut self,
+        selected: impl Fn(&QuarantineEntry) -> bool,
+    ) -> Result<QuarantinePurgeResult, String> {
+        let mut result = QuarantinePurgeResult::default();
+        let before = self.entries.len();
+        let mut kept = Vec::with_capacity(self.entries.len());
+        for entry in self.entries.drain(..) {
+            if !selected(&entry) {
+                kept.push(entry);
+                continue;
+            }
+            let staged = Path::new(&entry.staged_path);
+            let item_dir = staged.parent().unwrap_or(staged);
+            match fs::remove_dir_all(item_dir) {
+                Ok(()) => {
+                    result.purged += 1;
+                    result.freed = result.freed.saturating_add(entry.size);
+                }
+                Err(_) if staged.symlink_metadata().is_err() => {}
+                Err(err) => {
+                    result
+                        .failed
+                        .push(format!("{}: {}", entry.original_path, err));
+                    kept.push(entry);
+                }
+            }
+        }
+        let changed = kept.len() != before;
+        self.entries = kept;
+        if changed {
+            self.persist_manifest()?;
+        }
+        Ok(result)
+    }
+
+    fn retention(&self) -> Duration {
+        Duration::days(i64::from(self.settings.retention_days))
+    }
+}
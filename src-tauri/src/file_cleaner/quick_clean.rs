@@ -0,0 +1,233 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+#[cfg(feature = "app")]
+use std::time::Duration;
+use std::time::Instant;
+
+use serde::{Deserialize, Serialize};
+use tokio_util::sync::CancellationToken;
+use walkdir::WalkDir;
+
+#[cfg(any(test, feature = "app"))]
+use super::advanced_safety::SafetyRecommendation;
+#[cfg(any(test, feature = "app"))]
+use super::enhanced_engine::EnhancedCleanableFile;
+use super::enhanced_engine::FailedDeletion;
+use super::exclusions::ExclusionMatcher;
+use super::types::CleanableFile;
+use super::validation::{FileValidationState, ValidationResult, WarningType};
+
+/// Wall-clock limit for a quick clean; what is left waits for a full clean.
+#[cfg(feature = "app")]
+pub const QUICK_CLEAN_BUDGET: Duration = Duration::from_secs(30);
+/// Trash items younger than this stay restorable.
+#[cfg(any(test, feature = "app"))]
+const TRASH_MIN_AGE_DAYS: i64 = 30;
+const TRASH_CATEGORY: &str = "Trash";
+/// Caches macOS regenerates on demand.
+#[cfg(any(test, feature = "app"))]
+const REGENERABLE_CATEGORIES: &[&str] = &["Temporary Files", "QuickLook Cache"];
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct QuickCleanResult {
+    pub removed: Vec<String>,
+    pub freed: u64,
+    pub failed: Vec<FailedDeletion>,
+    /// Items skipped because they changed or were excluded since the scan.
+    pub changed: usize,
+    /// Items not reached before the budget ran out.
+    pub remaining: usize,
+    pub timed_out: bool,
+    /// Set when the run was canceled; what was done before stays done.
+    #[serde(default)]
+    pub canceled: bool,
+    /// Recovery point holding where the trashed items went, for
+    /// `undo_last_clean`.
+    #[serde(default)]
+    pub recovery_point_id: Option<String>,
+    /// Set when inspector mode reported what would happen instead of doing it.
+    #[serde(default)]
+    pub dry_run: bool,
+    /// Original and Trash location of each item moved to the Trash.
+    #[serde(skip)]
+    pub(crate) trashed: Vec<(PathBuf, PathBuf)>,
+}
+
+impl QuickCleanResult {
+    /// Inspector-mode stand-in: every candidate, as if removed.
+    pub fn preview(items: &[CleanableFile]) -> Self {
+        Self {
+            removed: items.iter().map(|item| item.path.clone()).collect(),
+            freed: items.iter().map(|item| item.size).sum(),
+            dry_run: true,
+            ..Default::default()
+        }
+    }
+}
+
+/// Scanned items quick clean may remove without review: cleared for
+/// automatic deletion by the safety analysis, and either Trash items older
+/// than 30 days, temporary files or QuickLook thumbnails. Largest first, so
+/// a run cut short by the budget still frees the most.
+#[cfg(any(test, feature = "app"))]
+pub fn quick_clean_candidates(files: &[EnhancedCleanableFile], now: i64) -> Vec<CleanableFile> {
+    let trash_cutoff = now - TRASH_MIN_AGE_DAYS * 24 * 60 * 60;
+    let mut candidates: Vec<CleanableFile> = files
+        .iter()
+        .filter(|file| {
+            let category = file.base.category.as_str();
+            file.safety_metrics.recommendation == SafetyRecommendation::SafeToAutoDelete
+                && file.base.safe_to_delete
+                && (REGENERABLE_CATEGORIES.contains(&category)
+                    || (category == TRASH_CATEGORY && file.base.last_modified <= trash_cutoff))
+        })
+        .map(|file| file.base.clone())
+        .collect();
+    candidates.sort_by_key(|file| std::cmp::Reverse(file.size));
+    candidates
+}
+
+/// Split `items` into the ones the pre-deletion validator cleared for an
+/// unattended run and the rest, with why. Quick clean never asks, so items
+/// in use, depended on, or not checked for open handles are left alone; a
+/// missing backup is fine, as cleared items go to the Trash.
+pub(crate) fn cleared_items(
+    items: Vec<CleanableFile>,
+    validation: &ValidationResult,
+) -> (Vec<CleanableFile>, Vec<FailedDeletion>) {
+    let lock_check_degraded = validation
+        .warnings
+        .iter()
+        .any(|warning| matches!(warning.warning_type, WarningType::LockCheckUnavailable));
+    let mut cleared = Vec::new();
+    let mut skipped = Vec::new();
+    for item in items {
+        let path = PathBuf::from(&item.path);
+        let has_dependencies = validation.warnings.iter().any(|warning| {
+            matches!(warning.warning_type, WarningType::HasDependencies)
+                && warning.file_path == path
+        });
+        let reason = match validation.file_states.get(&path) {
+            Some(FileValidationState::Blocked(_)) => Some(
+                validation
+                    .errors
+                    .iter()
+                    .find(|error| error.file_path == path)
+                    .map(|error| error.message.clone())
+                    .unwrap_or_else(|| "Blocked by pre-deletion validation".to_string()),
+            ),
+            _ if lock_check_degraded => {
+                Some("Could not check whether the item is in use".to_string())
+            }
+            _ if has_dependencies => Some("Other items depend on it".to_string()),
+            _ => None,
+        };
+        match reason {
+            Some(reason) => skipped.push(FailedDeletion {
+                path: item.path,
+                reason,
+            }),
+            None => cleared.push(item),
+        }
+    }
+    (cleared, skipped)
+}
+
+/// Work through `items` until `deadline`, re-checking each against the
+/// exclusions and the size the scan recorded first. Trash items are deleted,
+/// being in the Trash already; everything else goes there through `trash`.
+pub(crate) fn clean_until(
+    items: &[CleanableFile],
+    exclusions: &ExclusionMatcher,
+    deadline: Instant,
+    token: &CancellationToken,
+    trash: &dyn Fn(&Path) -> Result<PathBuf, String>,
+) -> QuickCleanResult {
+    let mut result = QuickCleanResult::default();
+    for (index, item) in items.iter().enumerate() {
+        if token.is_cancelled() {
+            result.canceled = true;
+            result.remaining = items.len() - index;
+            break;
+        }
+        if Instant::now() >= deadline {
+            result.timed_out = true;
+            result.remaining = items.len() - index;
+            break;
+        }
+        let path = Path::new(&item.path);
+        if exclusions.is_excluded(&item.path, &item.category) || !unchanged(item, path) {
+            result.changed += 1;
+            continue;
+        }
+        if item.category != TRASH_CATEGORY {
+            match trash(path) {
+                Ok(trashed) => {
+                    result.freed = result.freed.saturating_add(item.size);
+                    result.removed.push(item.path.clone());
+                    result.trashed.push((path.to_path_buf(), trashed));
+                }
+                Err(reason) => result.failed.push(FailedDeletion {
+                    path: item.path.clone(),
+                    reason,
+                }),
+            }
+            continue;
+        }
+        match remove_until(path, deadline) {
+            Ok((freed, true)) => {
+                result.freed = result.freed.saturating_add(freed);
+                result.removed.push(item.path.clone());
+            }
+            Ok((freed, false)) => {
+                result.freed = result.freed.saturating_add(freed);
+                result.timed_out = true;
+                result.remaining = items.len() - index;
+                break;
+            }
+            Err(reason) => result.failed.push(FailedDeletion {
+                path: item.path.clone(),
+                reason,
+            }),
+        }
+    }
+    result
+}
+
+/// Files must keep their scanned size; directories must still be directories.
+fn unchanged(item: &CleanableFile, path: &Path) -> bool {
+    match fs::symlink_metadata(path) {
+        Ok(metadata) if metadata.is_dir() => true,
+        Ok(metadata) if metadata.is_file() => metadata.len() == item.size,
+        _ => false,
+    }
+}
+
+/// Delete `path` bottom-up, stopping at `deadline`. Returns the bytes freed
+/// and whether the whole tree is gone. Symlinks are removed, not followed.
+fn remove_until(path: &Path, deadline: Instant) -> Result<(u64, bool), String> {
+    let metadata = fs::symlink_metadata(path)
+        .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    if !metadata.is_dir() {
+        fs::remove_file(path).map_err(|e| format!("Failed to remove {}: {}", path.display(), e))?;
+        return Ok((metadata.len(), true));
+    }
+    let mut freed = 0u64;
+    for entry in WalkDir::new(path).contents_first(true) {
+        if Instant::now() >= deadline {
+            return Ok((freed, false));
+        }
+        let entry = entry.map_err(|e| e.to_string())?;
+        let entry_path = entry.path();
+        if entry.file_type().is_dir() {
+            fs::remove_dir(entry_path)
+                .map_err(|e| format!("Failed to remove {}: {}", entry_path.display(), e))?;
+        } else {
+            let size = entry.metadata().map(|m| m.len()).unwrap_or(0);
+            fs::remove_file(entry_path)
+                .map_err(|e| format!("Failed to remove {}: {}", entry_path.display(), e))?;
+            freed = freed.saturating_add(size);
+        }
+    }
+    Ok((freed, true))
+}
@@ -0,0 +1,335 @@
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use super::advanced_safety::SafetyRecommendation;
+use super::age_thresholds::AgeThresholds;
+use super::app_uninstaller::AppFootprint;
+use super::duplicate_detector::DuplicateGroup;
+use super::enhanced_engine::EnhancedCleanableFile;
+use super::protection_packs::ProtectionPacks;
+use super::safety::{assess_path_risk_with, RiskLevel};
+use super::similar_images::SimilarImageGroup;
+use super::volumes::VolumeInfo;
+use crate::formatting::{format_count, format_size};
+
+/// Suggestions worth less than this are noise next to a full disk.
+const MIN_SAVINGS: u64 = 50 * 1024 * 1024;
+/// Apps at least this large are suggested for removal.
+const LARGE_APP_BYTES: u64 = 1024 * 1024 * 1024;
+/// Largest files of a category whose risk reasons are quoted.
+const SAMPLED_FILES: usize = 3;
+/// A volume with less free space than this share is running out.
+const LOW_SPACE_RATIO: f64 = 0.1;
+/// Ranking boost for suggestions that free space on such a volume.
+const LOW_SPACE_URGENCY: f64 = 1.5;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RecommendationKind {
+    /// Clean the scanned files of one category.
+    CleanCategory,
+    /// Move data off the Mac before removing it.
+    Offload,
+    /// Resolve exact duplicate groups.
+    RemoveDuplicates,
+    /// Review groups of similar images.
+    ReviewSimilarImages,
+    /// Uninstall an app, or remove what a removed app left behind.
+    ReviewApp,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StorageRecommendation {
+    /// Stable across scans, for dismissing a suggestion in the UI.
+    pub id: String,
+    pub kind: RecommendationKind,
+    pub title: String,
+    pub detail: String,
+    pub estimated_savings: u64,
+    /// From 0 to 1: how safe acting on the suggestion is.
+    pub confidence: f32,
+    /// Risk assessment reasons behind the confidence.
+    pub reasons: Vec<String>,
+    /// Files or app bundles the suggestion acts on.
+    pub paths: Vec<String>,
+    /// Groups to pass to `resolve_duplicates`.
+    pub group_ids: Vec<String>,
+    /// Set when the savings land on a volume that is nearly full.
+    pub low_space_volume: Option<String>,
+}
+
+/// What the recommendations are drawn from; any part may be empty.
+#[derive(Default)]
+pub struct RecommendationInputs<'a> {
+    pub files: &'a [EnhancedCleanableFile],
+    pub duplicate_groups: &'a [DuplicateGroup],
+    pub similar_groups: &'a [SimilarImageGroup],
+    pub app_footprints: &'a [AppFootprint],
+    pub volumes: &'a [VolumeInfo],
+    /// The user's age thresholds, so descriptions match what was scanned.
+    pub age_thresholds: AgeThresholds,
+    /// The user's enabled protection packs, so their locations count as risky.
+    pub protection_packs: ProtectionPacks,
+}
+
+/// Ranked suggestions, the most savings per unit of risk first.
+pub fn recommend(inputs: &RecommendationInputs<'_>) -> Vec<StorageRecommendation> {
+    let low_space: Vec<&str> = inputs
+        .volumes
+        .iter()
+        .filter(|volume| {
+            volume.total_space > 0
+                && (volume.available_space as f64) < volume.total_space as f64 * LOW_SPACE_RATIO
+        })
+        .map(|volume| volume.id.as_str())
+        .collect();
+
+    let mut recommendations = category_recommendations(inputs, &low_space);
+    recommendations.extend(duplicate_recommendation(inputs.duplicate_groups));
+    recommendations.extend(similar_image_recommendation(inputs.similar_groups));
+    recommendations.extend(inputs.app_footprints.iter().map(app_recommendation));
+    recommendations.retain(|recommendation| recommendation.estimated_savings >= MIN_SAVINGS);
+
+    let rank = |recommendation: &StorageRecommendation| {
+        let urgency = if recommendation.low_space_volume.is_some() {
+            LOW_SPACE_URGENCY
+        } else {
+            1.0
+        };
+        recommendation.estimated_savings as f64 * recommendation.confidence as f64 * urgency
+    };
+    recommendations.sort_by(|a, b| rank(b).total_cmp(&rank(a)).then_with(|| a.id.cmp(&b.id)));
+    recommendations
+}
+
+fn category_recommendations<'a>(
+    inputs: &RecommendationInputs<'a>,
+    low_space: &[&str],
+) -> Vec<StorageRecommendation> {
+    let age_thresholds = &inputs.age_thresholds;
+    let mut by_category: BTreeMap<&str, Vec<&EnhancedCleanableFile>> = BTreeMap::new();
+    for file in inputs
+        .files
+        .iter()
+        .filter(|file| file.safety_metrics.recommendation != SafetyRecommendation::DoNotDelete)
+    {
+        by_category
+            .entry(file.base.category.as_str())
+            .or_default()
+            .push(file);
+    }
+
+    by_category
+        .into_iter()
+        .map(|(category, mut files)| {
+            files.sort_by_key(|file| std::cmp::Reverse(file.base.size));
+            let savings: u64 = files.iter().map(|file| file.base.size).sum();
+            let weighted: f64 = files
+                .iter()
+                .map(|file| file.safety_metrics.confidence as f64 * file.base.size as f64)
+                .sum();
+            let mut confidence = if savings > 0 {
+                (weighted / savings as f64) as f32
+            } else {
+                0.0
+            };
+
+            let mut reasons: Vec<String> = Vec::new();
+            let mut risky = false;
+            for file in files.iter().take(SAMPLED_FILES) {
+                let assessment = assess_path_risk_with(
+                    Path::new(&file.base.path),
+                    age_thresholds,
+                    &inputs.protection_packs,
+                );
+                risky |= assessment.level == RiskLevel::Risky;
+                for reason in assessment.reasons {
+                    if !reasons.contains(&reason) {
+                        reasons.push(reason);
+                    }
+                }
+            }
+
+            if risky {
+                confidence *= 0.5;
+            }
+
+            let (kind, title, detail) =
+                describe_category(category, savings, files.len(), age_thresholds);
+            let low_space_volume = files
+                .iter()
+                .find(|file| low_space.contains(&file.volume.as_str()))
+                .map(|file| file.volume.clone());
+            StorageRecommendation {
+                id: format!("category:{}", category),
+                kind,
+                title,
+                detail,
+                estimated_savings: savings,
+                confidence: confidence.clamp(0.0, 1.0),
+                reasons,
+                paths: files.iter().map(|file| file.base.path.clone()).collect(),
+                group_ids: Vec::new(),
+                low_space_volume,
+            }
+        })
+        .collect()
+}
+
+fn describe_category(
+    category: &str,
+    size: u64,
+    count: usize,
+    age_thresholds: &AgeThresholds,
+) -> (RecommendationKind, String, String) {
+    let size = format_size(size);
+    let items = format_count(count as u64);
+    let days = |default: i64| age_thresholds.days_for(category).unwrap_or(default);
+    match category {
+        "iOS Backups (Advanced)" => (
+            RecommendationKind::Offload,
+            format!("Offload {} of iOS backups", size),
+            "Copy device backups to an external drive or rely on iCloud Backup, then remove them here.".to_string(),
+        ),
+        "Old Installers (30d+)" => (
+            RecommendationKind::CleanCategory,
+            format!("You have {} of old installers", size),
+            format!(
+                "{} disk images and packages older than {} days in Downloads.",
+                items,
+                days(30)
+            ),
+        ),
+        "Old Downloads (90d+)" => (
+            RecommendationKind::CleanCategory,
+            format!("{} of downloads untouched for {} days", size, days(90)),
+            format!("{} items in Downloads you have not opened recently.", items),
+        ),
+        "Trash" => (
+            RecommendationKind::CleanCategory,
+            format!("Empty {} from the Trash", size),
+            "Items in the Trash still take up space until it is emptied.".to_string(),
+        ),
+        "Xcode Archives (Old)" | "iOS Device Support (Old)" => (
+            RecommendationKind::CleanCategory,
+            format!("Reclaim {} of old Xcode data", size),
+            format!("{} archives and device support files Xcode can recreate.", items),
+        ),
+        _ => (
+            RecommendationKind::CleanCategory,
+            format!(
+                "Clean {} of {}",
+                size,
+                age_thresholds
+                    .label(category)
+                    .as_deref()
+                    .unwrap_or(category)
+            ),
+            format!("{} items found by the last scan.", items),
+        ),
+    }
+}
+
+fn duplicate_recommendation(groups: &[DuplicateGroup]) -> Option<StorageRecommendation> {
+    let groups: Vec<&DuplicateGroup> = groups
+        .iter()
+        .filter(|group| group.files.len() > 1)
+        .collect();
+    if groups.is_empty() {
+        return None;
+    }
+    let (copies, savings) = groups
+        .iter()
+        .fold((0u64, 0u64), |(copies, savings), group| {
+            let extra = group.files.len() as u64 - 1;
+            let file_size = group.total_size / group.files.len() as u64;
+            (copies + extra, savings + file_size * extra)
+        });
+    Some(StorageRecommendation {
+        id: "duplicates".to_string(),
+        kind: RecommendationKind::RemoveDuplicates,
+        title: format!("Remove {} duplicate copies", format_count(copies)),
+        detail: format!(
+            "{} byte-identical files in {} groups; one copy of each is kept.",
+            format_count(copies + groups.len() as u64),
+            format_count(groups.len() as u64)
+        ),
+        estimated_savings: savings,
+        confidence: 0.9,
+        reasons: vec!["Contents are identical; one copy is kept".to_string()],
+        paths: Vec::new(),
+        group_ids: groups.iter().map(|group| group.hash.clone()).collect(),
+        low_space_volume: None,
+    })
+}
+
+fn similar_image_recommendation(groups: &[SimilarImageGroup]) -> Option<StorageRecommendation> {
+    if groups.is_empty() {
+        return None;
+    }
+    let savings = groups.iter().map(|group| group.reclaimable).sum();
+    let similarity = groups.iter().map(|group| group.similarity).sum::<f32>() / groups.len() as f32;
+    Some(StorageRecommendation {
+        id: "similar_images".to_string(),
+        kind: RecommendationKind::ReviewSimilarImages,
+        title: format!(
+            "Review {} sets of similar photos",
+            format_count(groups.len() as u64)
+        ),
+        detail:
+            "The same pictures saved at different sizes or qualities; the largest of each is kept."
+                .to_string(),
+        estimated_savings: savings,
+        // Similar is not identical: always worth a look before removing.
+        confidence: similarity * 0.7,
+        reasons: vec!["Images look alike but are not byte-identical".to_string()],
+        paths: Vec::new(),
+        group_ids: groups.iter().map(|group| group.id.clone()).collect(),
+        low_space_volume: None,
+    })
+}
+
+fn app_recommendation(footprint: &AppFootprint) -> StorageRecommendation {
+    let leftovers: u64 = footprint
+        .leftovers
+        .iter()
+        .filter(|leftover| leftover.selected)
+        .map(|leftover| leftover.size)
+        .sum();
+    let name = &footprint.app.name;
+    let (title, detail, savings, confidence, reasons) = if footprint.total_size >= LARGE_APP_BYTES {
+        (
+            format!(
+                "Uninstall {} to free {}",
+                name,
+                format_size(footprint.total_size)
+            ),
+            "Only if you no longer use it; it can be reinstalled later.".to_string(),
+            footprint.total_size,
+            0.4,
+            vec!["Large app; removing it depends on whether you still use it".to_string()],
+        )
+    } else {
+        (
+            format!("Remove {} of {} support data", format_size(leftovers), name),
+            "Caches and support files the safety analysis cleared.".to_string(),
+            leftovers,
+            0.7,
+            vec!["Support files rebuilt by the app when needed".to_string()],
+        )
+    };
+    StorageRecommendation {
+        id: format!("app:{}", footprint.app.path),
+        kind: RecommendationKind::ReviewApp,
+        title,
+        detail,
+        estimated_savings: savings,
+        confidence,
+        reasons,
+        paths: vec![footprint.app.path.clone()],
+        group_ids: Vec::new(),
+        low_space_volume: None,
+    }
+}
@@ -0,0 +1,108 @@
+use std::collections::{HashMap, VecDeque};
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use super::enhanced_engine::EnhancedCleanableFile;
+
+/// Older revisions are answered with the full report.
+const RETAINED_REVISIONS: usize = 8;
+/// Hex digits of the path hash kept in an id.
+const ID_LENGTH: usize = 16;
+
+/// Id of the file at `path`, stable across scans: a hash of its canonical
+/// path, or of `path` itself once the file is gone.
+pub(crate) fn stable_id(path: &str) -> String {
+    let canonical = Path::new(path)
+        .canonicalize()
+        .map(|canonical| canonical.to_string_lossy().into_owned())
+        .unwrap_or_else(|_| path.to_string());
+    let mut id = blake3::hash(canonical.as_bytes()).to_hex().to_string();
+    id.truncate(ID_LENGTH);
+    id
+}
+
+/// What changed in the enhanced report since an earlier revision.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReportDelta {
+    pub revision: u64,
+    pub since_revision: u64,
+    /// `since_revision` is unknown or too old, so `added` holds every file
+    /// of the current report and the caller should replace its copy.
+    pub full: bool,
+    pub added: Vec<EnhancedCleanableFile>,
+    pub changed: Vec<EnhancedCleanableFile>,
+    /// Ids of files no longer in the report.
+    pub removed: Vec<String>,
+}
+
+/// Fingerprints of the last few reports, for [`ReportDelta`]s.
+#[derive(Default)]
+pub struct ReportHistory {
+    revision: u64,
+    /// Oldest first; each maps file id to a hash of the file's entry.
+    snapshots: VecDeque<(u64, HashMap<String, blake3::Hash>)>,
+    latest: Vec<EnhancedCleanableFile>,
+}
+
+impl ReportHistory {
+    /// Remember `files` as the next revision and return its number.
+    pub(crate) fn record(&mut self, files: &[EnhancedCleanableFile]) -> u64 {
+        self.revision += 1;
+        let fingerprints = files
+            .iter()
+            .map(|file| (file.id.clone(), fingerprint(file)))
+            .collect();
+        if self.snapshots.len() == RETAINED_REVISIONS {
+            self.snapshots.pop_front();
+        }
+        self.snapshots.push_back((self.revision, fingerprints));
+        self.latest = files.to_vec();
+        self.revision
+    }
+
+    pub fn revision(&self) -> u64 {
+        self.revision
+    }
+
+    pub fn delta_since(&self, since_revision: u64) -> ReportDelta {
+        let mut delta = ReportDelta {
+            revision: self.revision,
+            since_revision,
+            full: false,
+            added: Vec::new(),
+            changed: Vec::new(),
+            removed: Vec::new(),
+        };
+        let Some((_, before)) = self
+            .snapshots
+            .iter()
+            .find(|(revision, _)| *revision == since_revision)
+        else {
+            delta.full = true;
+            delta.added = self.latest.clone();
+            return delta;
+        };
+        let Some((_, now)) = self.snapshots.back() else {
+            return delta;
+        };
+        for file in &self.latest {
+            match before.get(&file.id) {
+                None => delta.added.push(file.clone()),
+                Some(hash) if *hash != now[&file.id] => delta.changed.push(file.clone()),
+                Some(_) => {}
+            }
+        }
+        delta.removed = before
+            .keys()
+            .filter(|id| !now.contains_key(*id))
+            .cloned()
+            .collect();
+        delta.removed.sort();
+        delta
+    }
+}
+
+fn fingerprint(file: &EnhancedCleanableFile) -> blake3::Hash {
+    blake3::hash(&serde_json::to_vec(file).unwrap_or_default())
+}
@@ -0,0 +1,193 @@
+// src/file_cleaner/report_export.rs
+
+//! Scan reports written to a file, to archive or diff scans over time and
+//! to attach to support tickets.
+//!
+//! JSON keeps the whole report, safety metrics included, under a small
+//! header. CSV has one row per file with its safety verdict and the reasons
+//! behind it; rows follow the report's category-then-path order so exports
+//! of two scans diff cleanly.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use super::enhanced_engine::EnhancedCleaningReport;
+use super::types::{CleanableFile, CleaningReport};
+
+/// Bumped when either format changes incompatibly.
+pub const REPORT_EXPORT_VERSION: u32 = 1;
+const CSV_HEADER: [&str; 11] = [
+    "path",
+    "category",
+    "size",
+    "safe_to_delete",
+    "safety_score",
+    "auto_select",
+    "recommendation",
+    "confidence",
+    "selection",
+    "risk_factors",
+    "reasons",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReportFormat {
+    Json,
+    Csv,
+}
+
+impl ReportFormat {
+    pub fn extension(self) -> &'static str {
+        match self {
+            ReportFormat::Json => "json",
+            ReportFormat::Csv => "csv",
+        }
+    }
+}
+
+/// A report to export: from the basic scan, or the enhanced one with its
+/// safety analysis.
+#[derive(Debug, Clone, Copy)]
+pub enum ScanReport<'a> {
+    Basic {
+        report: &'a CleaningReport,
+        files: &'a [CleanableFile],
+    },
+    Enhanced(&'a EnhancedCleaningReport),
+}
+
+#[derive(Serialize)]
+struct JsonExport<'a> {
+    version: u32,
+    exported_at: DateTime<Utc>,
+    kind: &'static str,
+    report: &'a CleaningReport,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    files: Option<&'a [CleanableFile]>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    enhanced: Option<&'a EnhancedCleaningReport>,
+}
+
+/// Where an export goes unless the user picks a file: their Downloads folder.
+pub fn default_export_path(format: ReportFormat, now: DateTime<Utc>) -> Option<PathBuf> {
+    dirs::download_dir().or_else(dirs::home_dir).map(|dir| {
+        dir.join(format!(
+            "macos-optimizer-scan-{}.{}",
+            now.format("%Y-%m-%d-%H%M%S"),
+            format.extension()
+        ))
+    })
+}
+
+/// Write `report` to `destination`, creating its folder.
+pub fn export_report(
+    report: ScanReport<'_>,
+    format: ReportFormat,
+    destination: &Path,
+    now: DateTime<Utc>,
+) -> Result<(), String> {
+    let text = render(report, format, now)?;
+    if let Some(parent) = destination.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+    }
+    fs::write(destination, text)
+        .map_err(|e| format!("Failed to write {}: {}", destination.display(), e))
+}
+
+pub fn render(
+    report: ScanReport<'_>,
+    format: ReportFormat,
+    now: DateTime<Utc>,
+) -> Result<String, String> {
+    match format {
+        ReportFormat::Json => {
+            let export = match report {
+                ScanReport::Basic { report, files } => JsonExport {
+                    version: REPORT_EXPORT_VERSION,
+                    exported_at: now,
+                    kind: "basic",
+                    report,
+                    files: Some(files),
+                    enhanced: None,
+                },
+                ScanReport::Enhanced(enhanced) => JsonExport {
+                    version: REPORT_EXPORT_VERSION,
+                    exported_at: now,
+                    kind: "enhanced",
+                    report: &enhanced.base,
+                    files: None,
+                    enhanced: Some(enhanced),
+                },
+            };
+            serde_json::to_string_pretty(&export)
+                .map_err(|e| format!("Failed to serialize report: {}", e))
+        }
+        ReportFormat::Csv => Ok(render_csv(report)),
+    }
+}
+
+fn render_csv(report: ScanReport<'_>) -> String {
+    let mut rows = vec![CSV_HEADER.map(String::from).to_vec()];
+    let base_row = |file: &CleanableFile| {
+        vec![
+            file.path.clone(),
+            file.category.clone(),
+            file.size.to_string(),
+            file.safe_to_delete.to_string(),
+            file.safety_score.to_string(),
+            file.auto_select.to_string(),
+        ]
+    };
+    match report {
+        ScanReport::Basic { files, .. } => {
+            for file in files {
+                let mut row = base_row(file);
+                row.resize(CSV_HEADER.len(), String::new());
+                rows.push(row);
+            }
+        }
+        ScanReport::Enhanced(report) => {
+            for file in &report.enhanced_files {
+                let mut row = base_row(&file.base);
+                let metrics = &file.safety_metrics;
+                let selection = &file.auto_select_score;
+                row.extend([
+                    format!("{:?}", metrics.recommendation),
+                    format!("{:.2}", metrics.confidence),
+                    format!("{:?}", selection.recommendation),
+                    metrics
+                        .risk_factors
+                        .iter()
+                        .map(|factor| format!("{:?}", factor))
+                        .collect::<Vec<_>>()
+                        .join("; "),
+                    selection.constraint_reasons.join("; "),
+                ]);
+                rows.push(row);
+            }
+        }
+    }
+    rows.iter()
+        .map(|row| {
+            row.iter()
+                .map(|field| csv_field(field))
+                .collect::<Vec<_>>()
+                .join(",")
+                + "\n"
+        })
+        .collect()
+}
+
+/// Quoted when it holds a separator, quote or line break, as RFC 4180 has it.
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
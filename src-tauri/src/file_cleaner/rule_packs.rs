@@ -0,0 +1,321 @@
+//! Rule packs: extra cleaning rules dropped into
+//! `<data dir>/macos-optimizer/rules/*.toml`, so community-maintained
+//! definitions can be added without a new build.
+//!
+//! A pack is a TOML file with optional `name`, `version` and `description`
+//! keys and one `[[rules]]` table per rule, using the same fields as a
+//! custom rule. Packs start disabled; each rule of an enabled pack is
+//! checked like a custom rule, against the embedded rules, the custom rules
+//! and the rules of enabled packs before it, and skipped if it conflicts.
+
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "app")]
+use super::custom_rules::load_custom_category_rules;
+use super::custom_rules::NewCustomRule;
+use super::enhanced_rules::RuleValidator;
+#[cfg(feature = "app")]
+use super::types::load_base_rules;
+use super::types::{CategoryRule, CleanerRules};
+use crate::persistence;
+
+const RULE_PACKS_DIR: &str = "rules";
+const RULE_PACK_SETTINGS_FILE: &str = "rule_packs.json";
+const RULE_PACK_EXTENSION: &str = "toml";
+
+/// What a pack file may contain; unknown keys are schema errors.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RulePackFile {
+    name: Option<String>,
+    version: Option<String>,
+    description: Option<String>,
+    #[serde(default)]
+    rules: Vec<PackRule>,
+}
+
+/// A `[[rules]]` table: the fields of a custom rule, without unknown keys.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct PackRule {
+    name: String,
+    paths: Vec<String>,
+    #[serde(default)]
+    safe: bool,
+    #[serde(default)]
+    advanced: bool,
+    max_depth: Option<usize>,
+    min_age_days: Option<i64>,
+    min_size_kb: Option<u64>,
+    #[serde(default)]
+    excludes: Vec<String>,
+    #[serde(default)]
+    extensions: Vec<String>,
+    #[serde(default)]
+    require_subpaths: Vec<String>,
+}
+
+impl From<PackRule> for NewCustomRule {
+    fn from(rule: PackRule) -> Self {
+        NewCustomRule {
+            name: rule.name,
+            paths: rule.paths,
+            safe: rule.safe,
+            advanced: rule.advanced,
+            max_depth: rule.max_depth,
+            min_age_days: rule.min_age_days,
+            min_size_kb: rule.min_size_kb,
+            excludes: rule.excludes,
+            extensions: rule.extensions,
+            require_subpaths: rule.require_subpaths,
+        }
+    }
+}
+
+/// A rule of a pack that will not be scanned, and why.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RejectedPackRule {
+    pub rule: String,
+    pub reason: String,
+}
+
+/// One pack file as shown in settings.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RulePack {
+    /// The file name without `.toml`.
+    pub id: String,
+    pub name: String,
+    pub version: Option<String>,
+    pub description: Option<String>,
+    pub path: String,
+    pub enabled: bool,
+    /// Rules that take part in scans while the pack is enabled.
+    pub rules: Vec<String>,
+    pub rejected: Vec<RejectedPackRule>,
+    /// Set when the file could not be read or does not match the schema;
+    /// such a pack contributes no rules.
+    pub error: Option<String>,
+}
+
+/// Which packs are enabled, persisted next to the packs directory.
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct RulePackSettings {
+    enabled: Vec<String>,
+}
+
+/// The packs found in the rules directory, with each rule already checked.
+#[derive(Default)]
+pub struct RulePackStore {
+    dir: Option<PathBuf>,
+    #[cfg(any(test, feature = "app"))]
+    settings_path: Option<PathBuf>,
+    settings: RulePackSettings,
+    packs: Vec<RulePack>,
+    rules: Vec<CategoryRule>,
+}
+
+impl RulePackStore {
+    /// Load packs from `<data dir>/macos-optimizer/rules/`, checked against
+    /// the embedded and custom rules.
+    #[cfg(feature = "app")]
+    pub fn load() -> Self {
+        match default_locations() {
+            Some((dir, settings_path)) => {
+                Self::load_from(&dir, &settings_path, &rules_before_packs())
+            }
+            None => Self::default(),
+        }
+    }
+
+    /// Load packs from `dir` and the enabled set from `settings_path`;
+    /// pack rules must not clash with `existing`.
+    pub(crate) fn load_from(dir: &Path, settings_path: &Path, existing: &CleanerRules) -> Self {
+        let settings = persistence::read_json(settings_path).unwrap_or_default();
+        let mut store = Self {
+            dir: Some(dir.to_path_buf()),
+            #[cfg(any(test, feature = "app"))]
+            settings_path: Some(settings_path.to_path_buf()),
+            settings,
+            packs: Vec::new(),
+            rules: Vec::new(),
+        };
+        store.reload_against(existing);
+        store
+    }
+
+    /// Read the packs directory again, e.g. after a pack was added or edited.
+    #[cfg(feature = "app")]
+    pub fn reload(&mut self) {
+        self.reload_against(&rules_before_packs());
+    }
+
+    fn reload_against(&mut self, existing: &CleanerRules) {
+        self.packs.clear();
+        self.rules.clear();
+        let Some(dir) = &self.dir else {
+            return;
+        };
+        let mut files: Vec<PathBuf> = match fs::read_dir(dir) {
+            Ok(entries) => entries
+                .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+                .filter(|path| {
+                    path.is_file()
+                        && path
+                            .extension()
+                            .is_some_and(|ext| ext.eq_ignore_ascii_case(RULE_PACK_EXTENSION))
+                })
+                .collect(),
+            Err(_) => Vec::new(),
+        };
+        // Earlier packs win a conflict, so the order must not depend on the
+        // file system.
+        files.sort();
+
+        let validator = RuleValidator::new();
+        let mut accepted = CleanerRules {
+            categories: existing.categories.clone(),
+        };
+        for path in files {
+            let id = path
+                .file_stem()
+                .map(|stem| stem.to_string_lossy().to_string())
+                .unwrap_or_default();
+            let enabled = self.settings.enabled.contains(&id);
+            let mut pack = RulePack {
+                id: id.clone(),
+                name: id.clone(),
+                version: None,
+                description: None,
+                path: path.to_string_lossy().to_string(),
+                enabled,
+                rules: Vec::new(),
+                rejected: Vec::new(),
+                error: None,
+            };
+            let file = fs::read_to_string(&path)
+                .map_err(|e| format!("Failed to read {}: {}", path.display(), e))
+                .and_then(|text| parse_pack(&text));
+            let file = match file {
+                Ok(file) => file,
+                Err(err) => {
+                    pack.error = Some(err);
+                    self.packs.push(pack);
+                    continue;
+                }
+            };
+            if let Some(name) = file.name.filter(|name| !name.trim().is_empty()) {
+                pack.name = name.trim().to_string();
+            }
+            pack.version = file.version;
+            pack.description = file.description;
+
+            // A disabled pack is checked against what is scanned, but its
+            // rules are not added, so it cannot block a later pack.
+            let mut candidates = accepted.categories.clone();
+            for rule in file.rules {
+                let rule = NewCustomRule::from(rule).normalized().to_category_rule();
+                let against = CleanerRules {
+                    categories: candidates.clone(),
+                };
+                match validator.validate_custom_rule(&rule, &against) {
+                    Ok(()) => {
+                        pack.rules.push(rule.name.clone());
+                        candidates.push(rule);
+                    }
+                    Err(reason) => pack.rejected.push(RejectedPackRule {
+                        rule: rule.name,
+                        reason,
+                    }),
+                }
+            }
+            if enabled {
+                let added = candidates.split_off(accepted.categories.len());
+                self.rules.extend(added.iter().cloned());
+                accepted.categories.extend(added);
+            }
+            self.packs.push(pack);
+        }
+    }
+
+    #[cfg(any(test, feature = "app"))]
+    pub fn list(&self) -> Vec<RulePack> {
+        self.packs.clone()
+    }
+
+    /// Turn a pack on or off. It takes effect with the next reload.
+    #[cfg(any(test, feature = "app"))]
+    pub fn set_enabled(&mut self, id: &str, enabled: bool) -> Result<(), String> {
+        if !self.packs.iter().any(|pack| pack.id == id) {
+            return Err(format!("Unknown rule pack: {}", id));
+        }
+        self.settings.enabled.retain(|other| other != id);
+        if enabled {
+            self.settings.enabled.push(id.to_string());
+        }
+        self.persist()
+    }
+
+    /// Rules of the enabled packs that passed validation.
+    pub(crate) fn category_rules(&self) -> Vec<CategoryRule> {
+        self.rules.clone()
+    }
+
+    #[cfg(any(test, feature = "app"))]
+    fn persist(&self) -> Result<(), String> {
+        let Some(path) = &self.settings_path else {
+            return Ok(());
+        };
+        persistence::write_json(path, &self.settings)
+    }
+}
+
+fn parse_pack(text: &str) -> Result<RulePackFile, String> {
+    let file: RulePackFile =
+        toml::from_str(text).map_err(|e| format!("Invalid rule pack: {}", e))?;
+    if file.rules.is_empty() {
+        return Err("Rule pack has no [[rules]]".into());
+    }
+    Ok(file)
+}
+
+fn default_locations() -> Option<(PathBuf, PathBuf)> {
+    let app_dir = persistence::data_dir()?;
+    Some((
+        app_dir.join(RULE_PACKS_DIR),
+        app_dir.join(RULE_PACK_SETTINGS_FILE),
+    ))
+}
+
+/// The embedded and custom rules, which pack rules must not clash with.
+#[cfg(feature = "app")]
+fn rules_before_packs() -> CleanerRules {
+    match load_base_rules() {
+        Ok(mut rules) => {
+            let custom = load_custom_category_rules(&rules);
+            rules.categories.extend(custom);
+            rules
+        }
+        Err(err) => {
+            log::warn!("Checking rule packs without the embedded rules: {}", err);
+            CleanerRules { categories: vec![] }
+        }
+    }
+}
+
+/// Rules of the enabled packs to merge into a scan, checked against `base`
+/// (the embedded and custom rules). None under a rules override.
+pub(crate) fn load_rule_pack_rules(base: &CleanerRules) -> Vec<CategoryRule> {
+    if env::var_os("MACOS_OPTIMIZER_RULES_OVERRIDE").is_some() {
+        return Vec::new();
+    }
+    match default_locations() {
+        Some((dir, settings_path)) => {
+            RulePackStore::load_from(&dir, &settings_path, base).category_rules()
+        }
+        None => Vec::new(),
+    }
+}
@@ -0,0 +1,130 @@
+use std::collections::HashSet;
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+use walkdir::WalkDir;
+
+/// Projects are rarely nested deeper than `~/Developer/org/repo/package`.
+const MAX_MANIFEST_DEPTH: usize = 4;
+/// Dependency and build output folders hold other projects' manifests;
+/// hidden folders are skipped as well.
+const SKIPPED_DIRS: &[&str] = &[
+    "node_modules",
+    "target",
+    "vendor",
+    "Pods",
+    "build",
+    "dist",
+    "venv",
+    "__pycache__",
+];
+
+/// Language runtimes whose tool caches get dedicated cleanup rules.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Runtime {
+    Node,
+    Python,
+    Rust,
+    Go,
+    CocoaPods,
+}
+
+impl Runtime {
+    pub const ALL: [Runtime; 5] = [
+        Runtime::Node,
+        Runtime::Python,
+        Runtime::Rust,
+        Runtime::Go,
+        Runtime::CocoaPods,
+    ];
+
+    /// Files whose presence in a project means the runtime is in use.
+    pub fn manifests(self) -> &'static [&'static str] {
+        match self {
+            Runtime::Node => &["package.json"],
+            Runtime::Python => &["requirements.txt", "pyproject.toml", "Pipfile", "setup.py"],
+            Runtime::Rust => &["Cargo.toml"],
+            Runtime::Go => &["go.mod"],
+            Runtime::CocoaPods => &["Podfile"],
+        }
+    }
+
+    /// Names reported by `AppActivityChecker::get_active_development_tools`, lowercased.
+    pub fn process_names(self) -> &'static [&'static str] {
+        match self {
+            Runtime::Node => &["node", "npm", "yarn", "pnpm"],
+            Runtime::Python => &["python", "python3", "pip", "pip3"],
+            Runtime::Rust => &["cargo", "rustc"],
+            Runtime::Go => &["go", "golang"],
+            Runtime::CocoaPods => &[],
+        }
+    }
+
+    fn from_manifest(file_name: &str) -> Option<Runtime> {
+        Runtime::ALL
+            .into_iter()
+            .find(|runtime| runtime.manifests().contains(&file_name))
+    }
+}
+
+/// Usual homes for source checkouts.
+pub fn default_dev_roots() -> Vec<PathBuf> {
+    let Some(home) = dirs::home_dir() else {
+        return Vec::new();
+    };
+    let mut roots: Vec<PathBuf> = Vec::new();
+    for rel in [
+        "Developer",
+        "Projects",
+        "projects",
+        "Code",
+        "code",
+        "src",
+        "dev",
+        "workspace",
+        "repos",
+        "git",
+        "Documents/GitHub",
+    ] {
+        let path = home.join(rel);
+        // Case-insensitive volumes report `Code` and `code` as the same folder.
+        let Ok(canonical) = path.canonicalize() else {
+            continue;
+        };
+        if canonical.is_dir() && !roots.contains(&canonical) {
+            roots.push(canonical);
+        }
+    }
+    roots
+}
+
+/// Runtimes with at least one project manifest under `roots`. Stops walking
+/// once every runtime has been seen.
+pub fn detect_runtimes(roots: &[PathBuf]) -> HashSet<Runtime> {
+    let mut found = HashSet::new();
+    for root in roots {
+        let walker = WalkDir::new(root)
+            .max_depth(MAX_MANIFEST_DEPTH)
+            .follow_links(false)
+            .into_iter()
+            .filter_entry(|entry| {
+                let name = entry.file_name().to_string_lossy();
+                !(entry.file_type().is_dir()
+                    && entry.depth() > 0
+                    && (name.starts_with('.') || SKIPPED_DIRS.contains(&name.as_ref())))
+            });
+        for entry in walker.filter_map(Result::ok) {
+            if !entry.file_type().is_file() {
+                continue;
+            }
+            if let Some(runtime) = Runtime::from_manifest(&entry.file_name().to_string_lossy()) {
+                found.insert(runtime);
+                if found.len() == Runtime::ALL.len() {
+                    return found;
+                }
+            }
+        }
+    }
+    found
+}
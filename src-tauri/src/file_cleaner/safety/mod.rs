@@ -2,5 +2,7 @@ mod context;
 mod policy;
 mod risk;
 
-pub(crate) use policy::{calculate_safety_score, policy_for_category};
-pub(crate) use risk::{assess_path_risk, RiskLevel};
+pub(crate) use policy::{calculate_safety_score, policy_for_category, SafetyPolicy};
+#[cfg(test)]
+pub(crate) use risk::assess_path_risk;
+pub(crate) use risk::{assess_path_risk_with, RiskAssessment, RiskLevel};
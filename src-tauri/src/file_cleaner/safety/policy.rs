@@ -67,6 +67,13 @@ pub(crate) fn policy_for_category(category: &str) -> SafetyPolicy {
             max_auto_select_size: None,
         };
     }
+    if c == "stale lock files" {
+        return SafetyPolicy {
+            auto_select_threshold: 90,
+            direct_delete_threshold: 95,
+            max_auto_select_size: None,
+        };
+    }
     if c.contains("saved application state") {
         return SafetyPolicy {
             auto_select_threshold: 90,
@@ -138,7 +145,7 @@ pub(crate) fn calculate_safety_score(
         }
         "Saved Application State (30d+)" => {
             score = score.max(88);
-            if rule_min_age.unwrap_or(0) >= 30 {
+            if rule_min_age.unwrap_or(0) >= risk.safe_age_days.unwrap_or(30) {
                 auto_select = allow_auto;
             }
         }
@@ -151,7 +158,7 @@ pub(crate) fn calculate_safety_score(
         | "Crash Reports (30d+)"
         | "System Crash Reports (30d+, Advanced)" => {
             score = score.max(78);
-            if rule_min_age.unwrap_or(0) >= 30 {
+            if rule_min_age.unwrap_or(0) >= risk.safe_age_days.unwrap_or(30) {
                 auto_select = allow_auto;
             }
         }
@@ -1,21 +1,31 @@
 use std::path::Path;
 
+use serde::Serialize;
+
 use super::context::PathContext;
+use crate::file_cleaner::age_thresholds::{
+    AgeThresholds, CRASH_REPORTS_CATEGORY, SAVED_STATE_CATEGORY, USER_LOGS_CATEGORY,
+};
+use crate::file_cleaner::protection_packs::ProtectionPacks;
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
 pub enum RiskLevel {
     Safe,
     Review,
     Risky,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct RiskAssessment {
     pub level: RiskLevel,
     pub confidence: u8,
     pub reasons: Vec<String>,
     pub age_modified_days: Option<i64>,
     pub age_created_days: Option<i64>,
+    /// Age at which the age-gated check that matched this path (saved state,
+    /// logs or crash reports) starts to count it as safe.
+    pub safe_age_days: Option<i64>,
 }
 
 #[derive(Debug, Clone)]
@@ -73,10 +83,23 @@ fn new_assessment(ctx: &PathContext<'_>) -> RiskAssessment {
         reasons: Vec::new(),
         age_modified_days: ctx.age_days_modified(),
         age_created_days: ctx.age_days_created(),
+        safe_age_days: None,
     }
 }
 
+#[cfg(test)]
 pub(crate) fn assess_path_risk(path: &Path) -> RiskAssessment {
+    assess_path_risk_with(path, &AgeThresholds::default(), &ProtectionPacks::default())
+}
+
+/// How risky deleting `path` is, with the user's age thresholds in place of
+/// the defaults for saved state (7 days), logs and crash reports (30 days), and
+/// the locations of their enabled protection packs treated as sensitive.
+pub(crate) fn assess_path_risk_with(
+    path: &Path,
+    thresholds: &AgeThresholds,
+    packs: &ProtectionPacks,
+) -> RiskAssessment {
     let ctx = PathContext::new(path);
     let mut assessment = new_assessment(&ctx);
 
@@ -139,6 +162,18 @@ pub(crate) fn assess_path_risk(path: &Path) -> RiskAssessment {
         return assessment;
     }
 
+    if let Some(pack) = packs.protecting(
+        |keyword| ctx.contains_keyword(keyword),
+        |sequence| ctx.contains_sequence(sequence),
+    ) {
+        assessment.level = RiskLevel::Risky;
+        assessment.confidence = 98;
+        assessment
+            .reasons
+            .push(format!("Protected by the {} pack", pack));
+        return assessment;
+    }
+
     if ctx.contains_sequence(&["system", "library"]) && ctx.contains_keyword("/frameworks/") {
         push_signal(&mut risky_signals, 95, "System framework or component");
     }
@@ -302,20 +337,35 @@ pub(crate) fn assess_path_risk(path: &Path) -> RiskAssessment {
     }
 
     if ctx.contains_sequence(&["library", "saved application state"]) {
-        if relevant_age.unwrap_or(0) >= 7 {
+        let safe_age = thresholds.days_for(SAVED_STATE_CATEGORY).unwrap_or(7);
+        assessment.safe_age_days = Some(safe_age);
+        if relevant_age.unwrap_or(0) >= safe_age {
             push_signal(
                 &mut safe_signals,
                 86,
-                "Saved application state (unused >7d)",
+                format!("Saved application state (unused >{}d)", safe_age),
             );
         } else {
             push_signal(&mut review_signals, 65, "Saved application state");
         }
     }
 
+    let in_diagnostic_reports = ctx.contains_sequence(&["library", "logs", "diagnosticreports"]);
+    let crash_safe_age = thresholds.days_for(CRASH_REPORTS_CATEGORY).unwrap_or(30);
+
     if ctx.contains_sequence(&["library", "logs"]) {
-        if relevant_age.unwrap_or(0) >= 30 {
-            push_signal(&mut safe_signals, 84, "Log files (>30d old)");
+        let safe_age = if in_diagnostic_reports {
+            crash_safe_age
+        } else {
+            thresholds.days_for(USER_LOGS_CATEGORY).unwrap_or(30)
+        };
+        assessment.safe_age_days = Some(safe_age);
+        if relevant_age.unwrap_or(0) >= safe_age {
+            push_signal(
+                &mut safe_signals,
+                84,
+                format!("Log files (>{}d old)", safe_age),
+            );
         } else {
             push_signal(&mut review_signals, 60, "Recent log files");
         }
@@ -326,8 +376,13 @@ pub(crate) fn assess_path_risk(path: &Path) -> RiskAssessment {
             ext,
             "log" | "trace" | "crash" | "ips" | "ips1" | "ips2" | "diag" | "dmp"
         ) {
-            if relevant_age.unwrap_or(0) >= 30 {
-                push_signal(&mut safe_signals, 82, "Crash/log report (>30d old)");
+            let safe_age = *assessment.safe_age_days.get_or_insert(crash_safe_age);
+            if relevant_age.unwrap_or(0) >= safe_age {
+                push_signal(
+                    &mut safe_signals,
+                    82,
+                    format!("Crash/log report (>{}d old)", safe_age),
+                );
             } else {
                 push_signal(&mut review_signals, 60, "Recent crash/log report");
             }
@@ -0,0 +1,241 @@
+use std::collections::HashMap;
+use std::env;
+use std::path::Path;
+#[cfg(any(test, feature = "app"))]
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+
+use super::safety::{policy_for_category, SafetyPolicy};
+use crate::persistence;
+
+const SAFETY_POLICIES_FILE: &str = "safety_policies.json";
+/// Lowest auto-select threshold a user may pick, unless the built-in one is
+/// already lower (the Trash auto-selects everything).
+const MIN_AUTO_SELECT_THRESHOLD: u8 = 70;
+/// Lowest score at which a file may still be deleted without review.
+const MIN_DIRECT_DELETE_THRESHOLD: u8 = 80;
+const MIN_AUTO_SELECT_SIZE: u64 = 1024 * 1024;
+const MAX_AUTO_SELECT_SIZE: u64 = 100 * 1024 * 1024 * 1024;
+
+/// When files of a category are auto-selected or deleted without review.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SafetyThresholds {
+    /// Minimum safety score to auto-select; `None` never auto-selects.
+    pub auto_select_threshold: Option<u8>,
+    /// Minimum safety score to stay deletable without review.
+    pub direct_delete_threshold: u8,
+    /// Larger files are never auto-selected.
+    pub max_auto_select_size: Option<u64>,
+}
+
+impl From<SafetyPolicy> for SafetyThresholds {
+    fn from(policy: SafetyPolicy) -> Self {
+        Self {
+            auto_select_threshold: (policy.auto_select_threshold < 255)
+                .then_some(policy.auto_select_threshold),
+            direct_delete_threshold: policy.direct_delete_threshold,
+            max_auto_select_size: policy.max_auto_select_size,
+        }
+    }
+}
+
+impl From<SafetyThresholds> for SafetyPolicy {
+    fn from(thresholds: SafetyThresholds) -> Self {
+        Self {
+            auto_select_threshold: thresholds.auto_select_threshold.unwrap_or(255),
+            direct_delete_threshold: thresholds.direct_delete_threshold,
+            max_auto_select_size: thresholds.max_auto_select_size,
+        }
+    }
+}
+
+/// The user's replacement for a category's built-in thresholds.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SafetyPolicyOverride {
+    pub category: String,
+    pub thresholds: SafetyThresholds,
+}
+
+/// A category's thresholds as shown in settings.
+#[cfg(any(test, feature = "app"))]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CategorySafetyPolicy {
+    pub category: String,
+    /// What scans apply: the override if there is one, else the default.
+    pub thresholds: SafetyThresholds,
+    pub defaults: SafetyThresholds,
+    pub customized: bool,
+}
+
+/// Per-category safety thresholds chosen in settings.
+#[derive(Default)]
+pub struct SafetyPolicyStore {
+    #[cfg(any(test, feature = "app"))]
+    path: Option<PathBuf>,
+    overrides: Vec<SafetyPolicyOverride>,
+}
+
+impl SafetyPolicyStore {
+    /// Load from `<data dir>/macos-optimizer/safety_policies.json`.
+    pub fn load() -> Self {
+        match persistence::data_file(SAFETY_POLICIES_FILE) {
+            Some(path) => Self::load_from(&path),
+            None => Self::default(),
+        }
+    }
+
+    /// Load from an explicit file; a missing or unreadable file yields an
+    /// empty store. Overrides that fall outside the bounds, say from an
+    /// edited file, are dropped.
+    pub fn load_from(path: &Path) -> Self {
+        let overrides: Vec<SafetyPolicyOverride> = persistence::read_json(path).unwrap_or_default();
+        let overrides = overrides
+            .into_iter()
+            .filter(|entry| match validate(&entry.category, &entry.thresholds) {
+                Ok(()) => true,
+                Err(err) => {
+                    log::warn!("Ignoring safety policy for {}: {}", entry.category, err);
+                    false
+                }
+            })
+            .collect();
+        Self {
+            #[cfg(any(test, feature = "app"))]
+            path: Some(path.to_path_buf()),
+            overrides,
+        }
+    }
+
+    /// Thresholds for each of `categories`, followed by any overridden
+    /// category not among them.
+    #[cfg(any(test, feature = "app"))]
+    pub fn list(&self, categories: &[String]) -> Vec<CategorySafetyPolicy> {
+        let mut names: Vec<&str> = categories.iter().map(String::as_str).collect();
+        for entry in &self.overrides {
+            if !names
+                .iter()
+                .any(|name| name.eq_ignore_ascii_case(&entry.category))
+            {
+                names.push(&entry.category);
+            }
+        }
+        let policies = self.matcher();
+        names
+            .into_iter()
+            .map(|category| {
+                let defaults = SafetyThresholds::from(policy_for_category(category));
+                let custom = policies.thresholds_for(category);
+                CategorySafetyPolicy {
+                    category: category.to_string(),
+                    thresholds: custom.unwrap_or(defaults),
+                    defaults,
+                    customized: custom.is_some(),
+                }
+            })
+            .collect()
+    }
+
+    /// Override `category`'s thresholds, or restore the defaults with `None`.
+    #[cfg(any(test, feature = "app"))]
+    pub fn update(
+        &mut self,
+        category: &str,
+        thresholds: Option<SafetyThresholds>,
+    ) -> Result<(), String> {
+        let category = category.trim();
+        if category.is_empty() {
+            return Err("Category cannot be empty".into());
+        }
+        if let Some(thresholds) = &thresholds {
+            validate(category, thresholds)?;
+        }
+        self.overrides
+            .retain(|entry| !entry.category.eq_ignore_ascii_case(category));
+        if let Some(thresholds) = thresholds {
+            self.overrides.push(SafetyPolicyOverride {
+                category: category.to_string(),
+                thresholds,
+            });
+        }
+        self.persist()
+    }
+
+    pub(crate) fn matcher(&self) -> SafetyPolicies {
+        SafetyPolicies {
+            overrides: self
+                .overrides
+                .iter()
+                .map(|entry| (entry.category.to_lowercase(), entry.thresholds))
+                .collect(),
+        }
+    }
+
+    #[cfg(any(test, feature = "app"))]
+    fn persist(&self) -> Result<(), String> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        persistence::write_json(path, &self.overrides)
+    }
+}
+
+fn validate(category: &str, thresholds: &SafetyThresholds) -> Result<(), String> {
+    if let Some(threshold) = thresholds.auto_select_threshold {
+        let floor = SafetyThresholds::from(policy_for_category(category))
+            .auto_select_threshold
+            .map_or(MIN_AUTO_SELECT_THRESHOLD, |default| {
+                default.min(MIN_AUTO_SELECT_THRESHOLD)
+            });
+        if !(floor..=100).contains(&threshold) {
+            return Err(format!(
+                "Auto-select threshold must be between {} and 100",
+                floor
+            ));
+        }
+    }
+    if !(MIN_DIRECT_DELETE_THRESHOLD..=100).contains(&thresholds.direct_delete_threshold) {
+        return Err(format!(
+            "Direct delete threshold must be between {} and 100",
+            MIN_DIRECT_DELETE_THRESHOLD
+        ));
+    }
+    if let Some(size) = thresholds.max_auto_select_size {
+        if !(MIN_AUTO_SELECT_SIZE..=MAX_AUTO_SELECT_SIZE).contains(&size) {
+            return Err("Maximum auto-select size must be between 1 MB and 100 GB".into());
+        }
+    }
+    Ok(())
+}
+
+/// Stored thresholds, compiled for scans.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct SafetyPolicies {
+    overrides: HashMap<String, SafetyThresholds>,
+}
+
+impl SafetyPolicies {
+    /// The stored thresholds; none under a rules override, so test scans do
+    /// not pick up the user's settings, or if they cannot be loaded.
+    pub(crate) fn load() -> Self {
+        if env::var_os("MACOS_OPTIMIZER_RULES_OVERRIDE").is_some() {
+            return Self::default();
+        }
+        SafetyPolicyStore::load().matcher()
+    }
+
+    fn thresholds_for(&self, category: &str) -> Option<SafetyThresholds> {
+        self.overrides.get(&category.to_lowercase()).copied()
+    }
+
+    pub(crate) fn is_customized(&self, category: &str) -> bool {
+        self.thresholds_for(category).is_some()
+    }
+
+    /// The user's thresholds for `category`, else the built-in ones.
+    pub(crate) fn policy_for(&self, category: &str) -> SafetyPolicy {
+        self.thresholds_for(category)
+            .map(SafetyPolicy::from)
+            .unwrap_or_else(|| policy_for_category(category))
+    }
+}
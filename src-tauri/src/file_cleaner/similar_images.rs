@@ -0,0 +1,209 @@
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use tokio::process::Command;
+
+use super::duplicate_detector::DuplicateGroup;
+
+/// Formats `sips` can decode.
+const IMAGE_EXTENSIONS: &[&str] = &[
+    "jpg", "jpeg", "png", "heic", "heif", "tif", "tiff", "gif", "bmp", "webp",
+];
+/// Smaller images are icons and thumbnails rather than photos.
+pub(crate) const MIN_IMAGE_BYTES: u64 = 16 * 1024;
+/// dHash grid: one extra column so each row yields eight left/right comparisons.
+const HASH_WIDTH: usize = 9;
+const HASH_HEIGHT: usize = 8;
+/// Differing bits, out of 64, still treated as the same picture. Re-encodes
+/// and resizes usually land within a few bits; unrelated photos near 32.
+pub const MAX_SIMILAR_DISTANCE: u32 = 6;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SimilarImage {
+    pub path: PathBuf,
+    pub size: u64,
+    /// Similarity to the group's first image, from 0 to 1.
+    pub similarity: f32,
+}
+
+/// Images that look the same but are not byte-identical, such as one photo
+/// exported at several sizes or qualities.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SimilarImageGroup {
+    /// Identifies the group to `resolve_duplicates`.
+    pub id: String,
+    /// Largest first.
+    pub images: Vec<SimilarImage>,
+    /// Lowest similarity of any member to the first image.
+    pub similarity: f32,
+    pub total_size: u64,
+    /// The largest file, usually the highest-quality encode.
+    pub recommended_to_keep: Option<PathBuf>,
+    /// Space freed by keeping only the recommended image.
+    pub reclaimable: u64,
+}
+
+impl SimilarImageGroup {
+    pub fn files(&self) -> Vec<PathBuf> {
+        self.images.iter().map(|image| image.path.clone()).collect()
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct SimilarImageScan {
+    pub groups: Vec<SimilarImageGroup>,
+    pub analyzed_files: usize,
+    pub skipped_files: usize,
+    pub truncated: bool,
+}
+
+pub fn is_image(path: &Path) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| IMAGE_EXTENSIONS.contains(&ext.to_lowercase().as_str()))
+}
+
+/// Images among `paths` worth comparing. Only the kept copy of each exact
+/// duplicate group takes part, so byte-identical files are not reported twice.
+pub(crate) fn image_candidates(paths: &[PathBuf], exact: &[DuplicateGroup]) -> Vec<PathBuf> {
+    let redundant: HashSet<&PathBuf> = exact
+        .iter()
+        .flat_map(|group| {
+            let keep = group.recommended_to_keep.as_ref().or(group.files.first());
+            group.files.iter().filter(move |path| Some(*path) != keep)
+        })
+        .collect();
+    paths
+        .iter()
+        .filter(|path| is_image(path) && !redundant.contains(path))
+        .cloned()
+        .collect()
+}
+
+/// Difference hash of a `HASH_WIDTH` x `HASH_HEIGHT` grayscale grid: one bit
+/// per pixel, set when it is brighter than its right-hand neighbour.
+pub fn dhash(luma: &[u8], width: usize, height: usize) -> Option<u64> {
+    if width != HASH_WIDTH || height != HASH_HEIGHT || luma.len() != width * height {
+        return None;
+    }
+    let mut hash = 0u64;
+    for row in luma.chunks(width) {
+        for pair in row.windows(2) {
+            hash = (hash << 1) | u64::from(pair[0] > pair[1]);
+        }
+    }
+    Some(hash)
+}
+
+pub fn similarity(distance: u32) -> f32 {
+    1.0 - distance.min(64) as f32 / 64.0
+}
+
+/// Decode an uncompressed 24- or 32-bit BMP, as written by `sips`, into
+/// top-down grayscale rows.
+pub fn parse_bmp_luma(data: &[u8]) -> Option<(usize, usize, Vec<u8>)> {
+    let u16_at = |at: usize| Some(u16::from_le_bytes(data.get(at..at + 2)?.try_into().ok()?));
+    let u32_at = |at: usize| Some(u32::from_le_bytes(data.get(at..at + 4)?.try_into().ok()?));
+    if data.get(..2)? != b"BM" {
+        return None;
+    }
+    let offset = u32_at(10)? as usize;
+    let width = u32_at(18)? as i32;
+    let height = u32_at(22)? as i32;
+    let bytes_per_pixel = match (u16_at(28)?, u32_at(30)?) {
+        (24, 0) => 3,
+        (32, 0 | 3) => 4,
+        _ => return None,
+    };
+    if width <= 0 || height == 0 {
+        return None;
+    }
+    let (width, rows) = (width as usize, height.unsigned_abs() as usize);
+    let stride = (width * bytes_per_pixel).div_ceil(4) * 4;
+    let mut luma = Vec::with_capacity(width * rows);
+    for y in 0..rows {
+        // Positive heights are stored bottom-up.
+        let source_row = if height > 0 { rows - 1 - y } else { y };
+        let start = offset + source_row * stride;
+        let row = data.get(start..start + width * bytes_per_pixel)?;
+        for pixel in row.chunks(bytes_per_pixel) {
+            let (b, g, r) = (pixel[0] as u32, pixel[1] as u32, pixel[2] as u32);
+            luma.push(((r * 299 + g * 587 + b * 114) / 1000) as u8);
+        }
+    }
+    Some((width, rows, luma))
+}
+
+/// Cluster `(path, size, hash)` entries around the largest image of each
+/// cluster; groups with a single image are dropped.
+pub fn group_similar(
+    mut hashes: Vec<(PathBuf, u64, u64)>,
+    max_distance: u32,
+) -> Vec<SimilarImageGroup> {
+    hashes.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    let mut clusters: Vec<(u64, Vec<SimilarImage>)> = Vec::new();
+    for (path, size, hash) in hashes {
+        let nearest = clusters
+            .iter_mut()
+            .map(|cluster| ((cluster.0 ^ hash).count_ones(), cluster))
+            .filter(|(distance, _)| *distance <= max_distance)
+            .min_by_key(|(distance, _)| *distance);
+        match nearest {
+            Some((distance, cluster)) => cluster.1.push(SimilarImage {
+                path,
+                size,
+                similarity: similarity(distance),
+            }),
+            None => clusters.push((
+                hash,
+                vec![SimilarImage {
+                    path,
+                    size,
+                    similarity: 1.0,
+                }],
+            )),
+        }
+    }
+    clusters
+        .into_iter()
+        .filter(|(_, images)| images.len() > 1)
+        .map(|(hash, images)| {
+            let total_size: u64 = images.iter().map(|image| image.size).sum();
+            SimilarImageGroup {
+                id: format!("similar-{:016x}", hash),
+                similarity: images
+                    .iter()
+                    .map(|image| image.similarity)
+                    .fold(1.0, f32::min),
+                recommended_to_keep: images.first().map(|image| image.path.clone()),
+                reclaimable: total_size - images[0].size,
+                total_size,
+                images,
+            }
+        })
+        .collect()
+}
+
+/// Scale the image down to the hash grid with `sips` and hash the result.
+pub(crate) async fn perceptual_hash(path: &Path) -> Result<u64, String> {
+    let scaled = std::env::temp_dir().join(format!("dhash-{}.bmp", uuid::Uuid::new_v4()));
+    let output = Command::new("sips")
+        .args(["-s", "format", "bmp", "-z"])
+        .arg(HASH_HEIGHT.to_string())
+        .arg(HASH_WIDTH.to_string())
+        .arg(path)
+        .arg("--out")
+        .arg(&scaled)
+        .output()
+        .await
+        .map_err(|e| format!("Failed to run sips: {}", e))?;
+    let data = tokio::fs::read(&scaled).await;
+    let _ = tokio::fs::remove_file(&scaled).await;
+    if !output.status.success() {
+        return Err(String::from_utf8_lossy(&output.stderr).trim().to_string());
+    }
+    let data = data.map_err(|e| format!("Failed to read scaled image: {}", e))?;
+    let (width, height, luma) = parse_bmp_luma(&data).ok_or("Unsupported scaled image")?;
+    dhash(&luma, width, height).ok_or_else(|| "Unexpected scaled image size".to_string())
+}
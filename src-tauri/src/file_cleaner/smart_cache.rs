@@ -4,7 +4,6 @@ use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::fs;
 use std::path::Path;
-use sysinfo::System;
 
 /// Smart cache detection with validation
 pub struct SmartCacheDetector {
@@ -261,23 +260,20 @@ impl AppActivityChecker {
         let path_str = path.to_string_lossy().to_lowercase();
 
         for (app_key, process_names) in &self.app_process_map {
-            if path_str.contains(app_key) {
-                if process_names
+            if path_str.contains(app_key)
+                && process_names
                     .iter()
                     .any(|name| snapshot.has_process_named(name))
-                {
-                    return true;
-                }
+            {
+                return true;
             }
         }
 
         false
     }
 
-    pub fn get_active_development_tools(&self) -> Vec<String> {
+    pub fn get_active_development_tools(&self, snapshot: &ProcessSnapshot) -> Vec<String> {
         let mut active_tools = Vec::new();
-        let mut system = System::new_all();
-        system.refresh_processes();
 
         let dev_tools = vec![
             "Xcode",
@@ -303,10 +299,8 @@ impl AppActivityChecker {
         ];
 
         for tool in &dev_tools {
-            if system.processes_by_name(tool).count() > 0 {
-                if !active_tools.contains(&tool.to_string()) {
-                    active_tools.push(tool.to_string());
-                }
+            if snapshot.has_process_named(tool) && !active_tools.contains(&tool.to_string()) {
+                active_tools.push(tool.to_string());
             }
         }
 
@@ -0,0 +1,180 @@
+// src/file_cleaner/spotlight.rs
+
+//! Spotlight-backed discovery for rules that pick files by metadata.
+//!
+//! A rule such as Old Installers or Large Stale Files only matches files of
+//! certain types, sizes or ages, which Spotlight already indexes. With
+//! discovery turned on, such a rule path is searched with `mdfind` instead
+//! of walked, and only the handful of files it returns are checked against
+//! the rule. The query is looser than the rule, never stricter, so every
+//! candidate still goes through the same matching a walk would do.
+//!
+//! Anything Spotlight cannot answer falls back to the walk: `mdfind` or
+//! `mdutil` missing, indexing turned off for the volume, or a search that
+//! fails.
+
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use tokio::process::Command;
+use tokio_util::sync::CancellationToken;
+use walkdir::WalkDir;
+
+use super::engine::FileCleaner;
+use super::parallel_walk::{Found, Walk, WalkStats};
+use super::types::CategoryRule;
+use crate::capabilities::{self, Tool};
+
+static SPOTLIGHT_DISCOVERY: AtomicBool = AtomicBool::new(false);
+
+/// Search rule paths with Spotlight where the rule allows it; off walks
+/// every rule path.
+#[cfg(feature = "app")]
+pub fn set_spotlight_discovery(enabled: bool) {
+    SPOTLIGHT_DISCOVERY.store(enabled, Ordering::Relaxed);
+}
+
+pub(crate) fn spotlight_discovery() -> bool {
+    SPOTLIGHT_DISCOVERY.load(Ordering::Relaxed)
+}
+
+/// The `mdfind` query for the files `rule` could match, or `None` when the
+/// rule matches folders, filters on path substrings, or has neither an
+/// extension nor a size to narrow the search by.
+pub(crate) fn query_for(rule: &CategoryRule, now: i64) -> Option<String> {
+    if rule.matches_folders()
+        || rule
+            .require_subpaths
+            .as_ref()
+            .is_some_and(|s| !s.is_empty())
+    {
+        return None;
+    }
+    let extensions: Vec<&String> = rule
+        .extensions
+        .iter()
+        .flatten()
+        .filter(|ext| !ext.is_empty() && ext.chars().all(|c| c.is_ascii_alphanumeric()))
+        .collect();
+    if extensions.is_empty() && rule.min_size_kb.is_none() {
+        return None;
+    }
+
+    let mut clauses = Vec::new();
+    if !extensions.is_empty() {
+        let names: Vec<String> = extensions
+            .iter()
+            .map(|ext| format!("kMDItemFSName == \"*.{}\"c", ext))
+            .collect();
+        clauses.push(format!("({})", names.join(" || ")));
+    }
+    if let Some(kb) = rule.min_size_kb {
+        clauses.push(format!("kMDItemFSSize >= {}", kb * 1024));
+    }
+    if let Some(days) = rule.min_age_days.filter(|days| *days > 0) {
+        let attribute = if rule.ages_by_creation() {
+            "kMDItemFSCreationDate"
+        } else {
+            "kMDItemFSContentChangeDate"
+        };
+        let cutoff = chrono::DateTime::from_timestamp(now - days * 86_400, 0)?;
+        clauses.push(format!(
+            "{} <= $time.iso({})",
+            attribute,
+            cutoff.format("%Y-%m-%dT%H:%M:%SZ")
+        ));
+    }
+    Some(clauses.join(" && "))
+}
+
+/// The paths in `mdfind -0` output no deeper below `root` than a walk
+/// limited to `max_depth` would reach.
+pub(crate) fn candidates(output: &[u8], root: &Path, max_depth: usize) -> Vec<PathBuf> {
+    let mut paths: Vec<PathBuf> = output
+        .split(|byte| *byte == 0)
+        .filter(|raw| !raw.is_empty())
+        .map(|raw| PathBuf::from(String::from_utf8_lossy(raw).trim_end_matches('\n')))
+        .filter(|path| {
+            path.strip_prefix(root)
+                .is_ok_and(|relative| relative.components().count() <= max_depth)
+        })
+        .collect();
+    paths.sort();
+    paths.dedup();
+    paths
+}
+
+/// Find what `walk` would, through Spotlight. `None` when the walk has to
+/// run instead.
+pub(crate) async fn discover(
+    cleaner: FileCleaner,
+    walk: &Walk,
+    cancel: &CancellationToken,
+) -> Option<(Vec<Found>, WalkStats)> {
+    if !spotlight_discovery() {
+        return None;
+    }
+    let query = query_for(&walk.rule, chrono::Utc::now().timestamp())?;
+    if !indexed(&walk.root).await {
+        return None;
+    }
+    let output = Command::new(capabilities::tool_path(Tool::Mdfind)?)
+        .arg("-0")
+        .arg("-onlyin")
+        .arg(&walk.root)
+        .arg(&query)
+        .output()
+        .await
+        .ok()?;
+    if !output.status.success() {
+        log::debug!(
+            "mdfind failed for {}, walking it instead",
+            walk.root.display()
+        );
+        return None;
+    }
+    let paths = candidates(
+        &output.stdout,
+        &walk.root,
+        walk.rule.max_depth.unwrap_or(10),
+    );
+    let rule = walk.rule.clone();
+    let cancel = cancel.clone();
+    tokio::task::spawn_blocking(move || {
+        let mut stats = WalkStats::default();
+        let mut found = Vec::new();
+        for path in paths {
+            if cancel.is_cancelled() {
+                break;
+            }
+            let Some(Ok(entry)) = WalkDir::new(&path).max_depth(0).into_iter().next() else {
+                continue;
+            };
+            stats.entries += 1;
+            if let Some(file) = cleaner.process_entry(&entry, &rule) {
+                stats.bytes += file.size;
+                let is_dir = entry.file_type().is_dir();
+                found.push(Found { file, is_dir });
+            }
+        }
+        (found, stats)
+    })
+    .await
+    .ok()
+}
+
+/// Whether Spotlight indexes the volume holding `root`.
+async fn indexed(root: &Path) -> bool {
+    let Some(mdutil) = capabilities::tool_path(Tool::Mdutil) else {
+        return false;
+    };
+    if !capabilities::tool_available(Tool::Mdfind) {
+        return false;
+    }
+    match Command::new(mdutil).arg("-s").arg(root).output().await {
+        Ok(output) if output.status.success() => {
+            String::from_utf8_lossy(&output.stdout).contains("Indexing enabled")
+        }
+        _ => false,
+    }
+}
@@ -0,0 +1,256 @@
+use std::fs::{self, Metadata};
+#[cfg(unix)]
+use std::os::unix::fs::FileTypeExt;
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+use tokio::process::Command;
+use tokio_util::sync::CancellationToken;
+use walkdir::WalkDir;
+
+use super::process_snapshot::ProcessSnapshot;
+use super::types::CleanableFile;
+use crate::capabilities::{self, Tool};
+
+pub const STALE_LOCK_CATEGORY: &str = "Stale Lock Files";
+
+const MAX_SCAN_DEPTH: usize = 4;
+/// Lock and pid files are tiny; anything larger is probably data with an unlucky name.
+const MAX_LOCK_FILE_SIZE: u64 = 16 * 1024;
+const MAX_CANDIDATES: usize = 500;
+const LOCK_EXTENSIONS: &[&str] = &["lock", "lck", "pid", "sock", "socket"];
+const LOCK_FILE_NAMES: &[&str] = &["SingletonLock", "SingletonSocket", "SingletonCookie"];
+/// Package manager lockfiles pin dependency versions and are never stale.
+const DEPENDENCY_LOCKFILES: &[&str] = &[
+    "cargo.lock",
+    "yarn.lock",
+    "gemfile.lock",
+    "podfile.lock",
+    "poetry.lock",
+    "pipfile.lock",
+    "uv.lock",
+    "composer.lock",
+    "pdm.lock",
+    "flake.lock",
+    "mix.lock",
+    "pubspec.lock",
+    "bun.lock",
+];
+/// Directories under ~/Library that sync or hold credentials; never scanned.
+const SKIPPED_DIRS: &[&str] = &["Mobile Documents", "CloudStorage", "Keychains", "Mail"];
+
+/// Who is supposed to hold a lock file, as far as the file itself tells us.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LockOwner {
+    Pid(u32),
+    Unknown,
+}
+
+/// Locations searched for stale lock files: ~/Library, /tmp and the per-user temp dir.
+pub fn default_scan_roots() -> Vec<PathBuf> {
+    let mut roots = Vec::new();
+    if let Some(home) = dirs::home_dir() {
+        roots.push(home.join("Library"));
+    }
+    roots.push(PathBuf::from("/private/tmp"));
+    if let Ok(tmpdir) = std::env::var("TMPDIR") {
+        let tmpdir = PathBuf::from(tmpdir);
+        if !roots.iter().any(|root| tmpdir.starts_with(root)) {
+            roots.push(tmpdir);
+        }
+    }
+    roots
+}
+
+/// Seconds since the epoch at which the system last booted.
+pub fn boot_time() -> u64 {
+    sysinfo::System::boot_time()
+}
+
+/// Find lock, pid and socket files whose owner is gone.
+///
+/// A file with a recorded pid is stale when that pid is neither in the
+/// process snapshot nor alive right now. A file without one is only stale
+/// when it predates the last boot and no running command references it.
+pub fn find_stale_lock_files(
+    roots: &[PathBuf],
+    processes: &ProcessSnapshot,
+    boot_time: u64,
+    token: &CancellationToken,
+) -> Vec<CleanableFile> {
+    let mut found = Vec::new();
+    let can_check_open_files = capabilities::tool_available(Tool::Lsof);
+    for root in roots {
+        let walker = WalkDir::new(root)
+            .max_depth(MAX_SCAN_DEPTH)
+            .follow_links(false)
+            .into_iter()
+            .filter_entry(|entry| {
+                !(entry.file_type().is_dir()
+                    && SKIPPED_DIRS.contains(&entry.file_name().to_string_lossy().as_ref()))
+            });
+        for entry in walker.filter_map(Result::ok) {
+            if token.is_cancelled() || found.len() >= MAX_CANDIDATES {
+                return found;
+            }
+            let path = entry.path();
+            let Ok(metadata) = fs::symlink_metadata(path) else {
+                continue;
+            };
+            if !is_lock_candidate(path, &metadata) {
+                continue;
+            }
+            let owner = lock_owner(path, &metadata);
+            if !owner_is_gone(path, owner, &metadata, processes, boot_time) {
+                continue;
+            }
+            // Without lsof, ownerless locks can't be confirmed closed before
+            // deletion; keep them but ask for review.
+            let unverified = owner == LockOwner::Unknown && !can_check_open_files;
+            let mut description = describe(owner);
+            if unverified {
+                description.push_str(" (open-file check unavailable)");
+            }
+            found.push(CleanableFile {
+                path: path.to_string_lossy().to_string(),
+                size: metadata.len(),
+                category: STALE_LOCK_CATEGORY.to_string(),
+                description,
+                last_modified: modified_secs(&metadata) as i64,
+                safe_to_delete: !unverified,
+                safety_score: if unverified { 70 } else { 90 },
+                auto_select: false,
+            });
+        }
+    }
+    found
+}
+
+/// Re-check liveness immediately before deletion; `Err` explains why the
+/// file must be kept.
+pub async fn verify_still_stale(path: &Path) -> Result<(), String> {
+    let metadata =
+        fs::symlink_metadata(path).map_err(|e| format!("Lock file no longer readable: {}", e))?;
+    match lock_owner(path, &metadata) {
+        LockOwner::Pid(pid) => {
+            if pid_alive(pid) {
+                return Err(format!("Owning process {} is running again", pid));
+            }
+        }
+        LockOwner::Unknown => {
+            if modified_secs(&metadata) >= boot_time() {
+                return Err("Lock file was touched since the last restart".into());
+            }
+            if is_held_open(path).await {
+                return Err("Lock file is held open by a running process".into());
+            }
+        }
+    }
+    Ok(())
+}
+
+pub fn is_lock_candidate(path: &Path, metadata: &Metadata) -> bool {
+    let Some(name) = path.file_name().map(|n| n.to_string_lossy().to_string()) else {
+        return false;
+    };
+    if DEPENDENCY_LOCKFILES.contains(&name.to_lowercase().as_str()) {
+        return false;
+    }
+    let file_type = metadata.file_type();
+    #[cfg(unix)]
+    if file_type.is_socket() {
+        return true;
+    }
+    if file_type.is_dir() {
+        return false;
+    }
+    if LOCK_FILE_NAMES.contains(&name.as_str()) {
+        return true;
+    }
+    let has_lock_extension = path
+        .extension()
+        .map(|ext| LOCK_EXTENSIONS.contains(&ext.to_string_lossy().to_lowercase().as_str()))
+        .unwrap_or(false);
+    has_lock_extension && (file_type.is_symlink() || metadata.len() <= MAX_LOCK_FILE_SIZE)
+}
+
+/// Read the owning pid from the file contents (`12345`) or, for Chromium-style
+/// symlink locks, from the link target (`hostname-12345`).
+pub fn lock_owner(path: &Path, metadata: &Metadata) -> LockOwner {
+    let pid = if metadata.file_type().is_symlink() {
+        fs::read_link(path).ok().and_then(|target| {
+            target
+                .to_string_lossy()
+                .rsplit('-')
+                .next()
+                .and_then(parse_pid)
+        })
+    } else if metadata.is_file() && metadata.len() <= MAX_LOCK_FILE_SIZE {
+        fs::read_to_string(path)
+            .ok()
+            .and_then(|contents| contents.lines().next().and_then(parse_pid))
+    } else {
+        None
+    };
+    pid.map(LockOwner::Pid).unwrap_or(LockOwner::Unknown)
+}
+
+fn parse_pid(text: &str) -> Option<u32> {
+    let pid: u32 = text.trim().parse().ok()?;
+    // 0 would address our own process group, and pids never exceed i32::MAX.
+    (pid > 0 && pid <= i32::MAX as u32).then_some(pid)
+}
+
+fn owner_is_gone(
+    path: &Path,
+    owner: LockOwner,
+    metadata: &Metadata,
+    processes: &ProcessSnapshot,
+    boot_time: u64,
+) -> bool {
+    match owner {
+        LockOwner::Pid(pid) => !processes.has_pid(pid) && !pid_alive(pid),
+        LockOwner::Unknown => {
+            modified_secs(metadata) < boot_time
+                && !processes.command_contains_path(&path.to_string_lossy())
+        }
+    }
+}
+
+fn pid_alive(pid: u32) -> bool {
+    // Signal 0 only checks for existence; EPERM means it exists but isn't ours.
+    let rc = unsafe { libc::kill(pid as libc::pid_t, 0) };
+    rc == 0 || std::io::Error::last_os_error().raw_os_error() == Some(libc::EPERM)
+}
+
+async fn is_held_open(path: &Path) -> bool {
+    if !capabilities::tool_available(Tool::Lsof) {
+        return false;
+    }
+    match Command::new("lsof").arg("-t").arg(path).output().await {
+        // lsof prints holder pids and exits 0 when the file is open, 1 otherwise.
+        Ok(output) => !output.stdout.is_empty(),
+        Err(err) => {
+            log::debug!("lsof unavailable for {}: {}", path.display(), err);
+            false
+        }
+    }
+}
+
+fn modified_secs(metadata: &Metadata) -> u64 {
+    metadata
+        .modified()
+        .ok()
+        .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+        .map(|duration| duration.as_secs())
+        .unwrap_or(u64::MAX)
+}
+
+fn describe(owner: LockOwner) -> String {
+    match owner {
+        LockOwner::Pid(pid) => {
+            format!("Left behind by process {}, which is no longer running", pid)
+        }
+        LockOwner::Unknown => "Created before the last restart; no running process uses it".into(),
+    }
+}
@@ -0,0 +1,157 @@
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use super::duplicate_detector::DEFAULT_DUPLICATE_TIME_BUDGET as DUPLICATE_BUDGET;
+use super::process_snapshot::ProcessSnapshot;
+
+/// Time budget for deep safety analysis on an idle system.
+pub(crate) const SAFETY_ANALYSIS_BUDGET: Duration = Duration::from_secs(12);
+/// Time budget for one background batch of the analysis a scan deferred,
+/// on an idle system.
+pub(crate) const DEFERRED_SAFETY_BATCH_BUDGET: Duration = Duration::from_secs(2);
+
+const BUSY_CPU_PERCENT: f32 = 85.0;
+const MODERATE_CPU_PERCENT: f32 = 60.0;
+const BUSY_MEMORY_PRESSURE_PERCENT: f32 = 90.0;
+/// Combined read and write throughput at which hashing would compete with
+/// whatever is already using the disk.
+const BUSY_DISK_BYTES_PER_SEC: f64 = 150_000_000.0;
+const MODERATE_DISK_BYTES_PER_SEC: f64 = 40_000_000.0;
+
+/// Video-call apps where a hashing burst would be noticed as audio/video glitches.
+const MEETING_PROCESSES: &[&str] = &[
+    "zoom.us",
+    "microsoft teams",
+    "msteams",
+    "facetime",
+    "webex",
+    "cisco webex meetings",
+];
+
+/// Compilers and build drivers that already saturate CPU and disk.
+const BUILD_PROCESSES: &[&str] = &[
+    "xcodebuild",
+    "swift-frontend",
+    "clang",
+    "cc1",
+    "rustc",
+    "ld",
+];
+
+/// Point-in-time system load, as reported by the metrics sampler.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+pub struct SystemLoad {
+    pub cpu_percent: Option<f32>,
+    pub memory_pressure_percent: Option<f32>,
+    #[serde(default)]
+    pub disk_read_bytes_per_sec: Option<f64>,
+    #[serde(default)]
+    pub disk_write_bytes_per_sec: Option<f64>,
+}
+
+impl SystemLoad {
+    /// Reads plus writes, when the sampler measured either.
+    fn disk_bytes_per_sec(&self) -> Option<f64> {
+        match (self.disk_read_bytes_per_sec, self.disk_write_bytes_per_sec) {
+            (None, None) => None,
+            (read, write) => Some(read.unwrap_or(0.0) + write.unwrap_or(0.0)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LoadLevel {
+    Idle,
+    Moderate,
+    Busy,
+}
+
+/// How heavy scan phases should run given the current load.
+#[derive(Debug, Clone)]
+pub struct HeavyWorkPlan {
+    pub level: LoadLevel,
+    /// `None` defers duplicate hashing to a later scan.
+    pub duplicate_budget: Option<Duration>,
+    pub safety_budget: Duration,
+    pub reasons: Vec<String>,
+}
+
+impl HeavyWorkPlan {
+    pub fn unrestricted() -> Self {
+        Self {
+            level: LoadLevel::Idle,
+            duplicate_budget: Some(DUPLICATE_BUDGET),
+            safety_budget: SAFETY_ANALYSIS_BUDGET,
+            reasons: Vec::new(),
+        }
+    }
+
+    /// Short human-readable note for progress messages, if anything was throttled.
+    pub fn note(&self) -> Option<String> {
+        if self.level == LoadLevel::Idle || self.reasons.is_empty() {
+            return None;
+        }
+        Some(self.reasons.join(", "))
+    }
+}
+
+/// Decide whether to defer or shrink duplicate hashing and deep safety analysis.
+pub fn plan_heavy_work(load: &SystemLoad, processes: &ProcessSnapshot) -> HeavyWorkPlan {
+    let mut busy = Vec::new();
+    let mut moderate = Vec::new();
+
+    if let Some(cpu) = load.cpu_percent {
+        if cpu >= BUSY_CPU_PERCENT {
+            busy.push(format!("CPU at {:.0}%", cpu));
+        } else if cpu >= MODERATE_CPU_PERCENT {
+            moderate.push(format!("CPU at {:.0}%", cpu));
+        }
+    }
+    if let Some(pressure) = load.memory_pressure_percent {
+        if pressure >= BUSY_MEMORY_PRESSURE_PERCENT {
+            busy.push(format!("memory pressure {:.0}%", pressure));
+        }
+    }
+    if let Some(disk) = load.disk_bytes_per_sec() {
+        let reason = format!("disk busy ({:.0} MB/s)", disk / 1_000_000.0);
+        if disk >= BUSY_DISK_BYTES_PER_SEC {
+            busy.push(reason);
+        } else if disk >= MODERATE_DISK_BYTES_PER_SEC {
+            moderate.push(reason);
+        }
+    }
+    if let Some(name) = first_running(processes, MEETING_PROCESSES) {
+        busy.push(format!("{} call in progress", name));
+    }
+    if let Some(name) = first_running(processes, BUILD_PROCESSES) {
+        moderate.push(format!("{} running", name));
+    }
+
+    if !busy.is_empty() {
+        busy.extend(moderate);
+        return HeavyWorkPlan {
+            level: LoadLevel::Busy,
+            duplicate_budget: None,
+            safety_budget: SAFETY_ANALYSIS_BUDGET / 2,
+            reasons: busy,
+        };
+    }
+    if !moderate.is_empty() {
+        return HeavyWorkPlan {
+            level: LoadLevel::Moderate,
+            duplicate_budget: Some(DUPLICATE_BUDGET / 2),
+            safety_budget: SAFETY_ANALYSIS_BUDGET,
+            reasons: moderate,
+        };
+    }
+    HeavyWorkPlan::unrestricted()
+}
+
+fn first_running(processes: &ProcessSnapshot, names: &[&'static str]) -> Option<&'static str> {
+    names
+        .iter()
+        .copied()
+        .find(|name| processes.has_process_named(name))
+}
@@ -1,17 +1,79 @@
+//! Scan metrics for this session, and the local history kept of them once
+//! the user opts in.
+//!
+//! [`SafetyMetricsCollector`] lives in the enhanced cleaner and describes the
+//! session and the last scan. [`TelemetryStore`] adds each scan, deselection
+//! and clean to a per-day tally on disk while the telemetry opt-in in the
+//! settings is on, and aggregates the tallies for the insights screen. It
+//! keeps counts and byte totals only, never paths or file names, and the
+//! names of custom rules are folded into "Other". Opting out discards it.
+
 use serde::{Deserialize, Serialize};
-use std::fs;
+#[cfg(any(test, feature = "app"))]
+use std::collections::{BTreeMap, HashSet};
+#[cfg(any(test, feature = "app"))]
+use std::path::{Path, PathBuf};
 use std::time::Instant;
 
+#[cfg(any(test, feature = "app"))]
+use chrono::{DateTime, Datelike, NaiveDate, Utc, Weekday};
+
+#[cfg(any(test, feature = "app"))]
+use super::types::builtin_category_names;
+#[cfg(feature = "app")]
+use crate::lazy_init::InitTiming;
+#[cfg(any(test, feature = "app"))]
+use crate::persistence;
+
+#[cfg(feature = "app")]
+const TELEMETRY_FILE: &str = "telemetry.json";
+#[cfg(any(test, feature = "app"))]
+const DAY_SECS: i64 = 24 * 60 * 60;
+/// Days of history kept; older days are dropped as new ones are recorded.
+#[cfg(any(test, feature = "app"))]
+pub const TELEMETRY_RETENTION_DAYS: u32 = 366;
+/// Stands in for every category that is not one of the embedded rules.
+#[cfg(any(test, feature = "app"))]
+const OTHER_CATEGORY: &str = "Other";
+
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct TelemetrySnapshot {
     pub total_scans: u64,
     pub last_scan_ms: u64,
     pub total_deselections: u64,
+    /// Scans this session that cut at least one analysis stage short.
+    #[serde(default)]
+    pub total_truncated_scans: u64,
+    /// Subsystems constructed this session and how long each took; filled
+    /// in by the app.
+    #[cfg(feature = "app")]
+    #[serde(default)]
+    pub subsystem_init: Vec<InitTiming>,
+}
+
+/// Analysis a scan stops early once its time budget runs out.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AnalysisStage {
+    Duplicates,
+    SimilarImages,
+    Safety,
+}
+
+/// What one completed scan found and where it was cut short.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ScanTelemetry {
+    pub duration_ms: u64,
+    pub items_found: u64,
+    pub auto_selected: u64,
+    pub truncated: Vec<AnalysisStage>,
 }
 
 pub struct SafetyMetricsCollector {
     snapshot: TelemetrySnapshot,
     scan_timer: Option<Instant>,
+    truncated: Vec<AnalysisStage>,
+    last_scan: Option<ScanTelemetry>,
 }
 
 impl SafetyMetricsCollector {
@@ -19,38 +81,349 @@ impl SafetyMetricsCollector {
         Self {
             snapshot: TelemetrySnapshot::default(),
             scan_timer: None,
+            truncated: Vec::new(),
+            last_scan: None,
         }
     }
 
     pub fn start_scan(&mut self) {
         self.scan_timer = Some(Instant::now());
+        self.truncated.clear();
+    }
+
+    pub fn track_truncation(&mut self, stage: AnalysisStage) {
+        if !self.truncated.contains(&stage) {
+            self.truncated.push(stage);
+        }
     }
 
-    pub fn finish_scan(&mut self) {
+    pub fn finish_scan(&mut self, items_found: usize, auto_selected: usize) {
         self.snapshot.total_scans = self.snapshot.total_scans.saturating_add(1);
         if let Some(t0) = self.scan_timer.take() {
             self.snapshot.last_scan_ms = t0.elapsed().as_millis() as u64;
         }
-        let _ = self.persist();
+        let truncated = std::mem::take(&mut self.truncated);
+        if !truncated.is_empty() {
+            self.snapshot.total_truncated_scans =
+                self.snapshot.total_truncated_scans.saturating_add(1);
+        }
+        self.last_scan = Some(ScanTelemetry {
+            duration_ms: self.snapshot.last_scan_ms,
+            items_found: items_found as u64,
+            auto_selected: auto_selected as u64,
+            truncated,
+        });
     }
 
     pub fn track_deselection(&mut self) {
         self.snapshot.total_deselections = self.snapshot.total_deselections.saturating_add(1);
-        let _ = self.persist();
     }
 
     pub fn get_snapshot(&self) -> TelemetrySnapshot {
         self.snapshot.clone()
     }
 
-    fn persist(&self) -> std::io::Result<()> {
-        if let Some(mut path) = dirs::data_dir() {
-            path.push("macos-optimizer");
-            let _ = fs::create_dir_all(&path);
-            path.push("telemetry.json");
-            let data = serde_json::to_vec_pretty(&self.snapshot).unwrap_or_else(|_| b"{}".to_vec());
-            fs::write(path, data)?;
+    /// The most recent completed scan this session.
+    pub fn last_scan(&self) -> Option<&ScanTelemetry> {
+        self.last_scan.as_ref()
+    }
+}
+
+/// Tallies for one UTC day.
+#[cfg(any(test, feature = "app"))]
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+struct DayTelemetry {
+    scans: u64,
+    scan_ms: u64,
+    truncated_scans: u64,
+    items_found: u64,
+    auto_selected: u64,
+    deselections: u64,
+    cleans: u64,
+    bytes_freed: BTreeMap<String, u64>,
+    truncations: BTreeMap<AnalysisStage, u64>,
+}
+
+#[cfg(any(test, feature = "app"))]
+impl DayTelemetry {
+    fn add(&mut self, other: &DayTelemetry) {
+        self.scans = self.scans.saturating_add(other.scans);
+        self.scan_ms = self.scan_ms.saturating_add(other.scan_ms);
+        self.truncated_scans = self.truncated_scans.saturating_add(other.truncated_scans);
+        self.items_found = self.items_found.saturating_add(other.items_found);
+        self.auto_selected = self.auto_selected.saturating_add(other.auto_selected);
+        self.deselections = self.deselections.saturating_add(other.deselections);
+        self.cleans = self.cleans.saturating_add(other.cleans);
+        for (category, bytes) in &other.bytes_freed {
+            let total = self.bytes_freed.entry(category.clone()).or_default();
+            *total = total.saturating_add(*bytes);
+        }
+        for (stage, count) in &other.truncations {
+            let total = self.truncations.entry(*stage).or_default();
+            *total = total.saturating_add(*count);
+        }
+    }
+}
+
+#[cfg(any(test, feature = "app"))]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+struct TelemetryData {
+    opted_in: bool,
+    since: i64,
+    /// By UTC date, `YYYY-MM-DD`.
+    days: BTreeMap<String, DayTelemetry>,
+}
+
+/// How the days of an insights query are grouped.
+#[cfg(any(test, feature = "app"))]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TelemetryBucket {
+    #[default]
+    Day,
+    /// Weeks starting on Monday.
+    Week,
+    Month,
+    /// One period covering the whole window.
+    All,
+}
+
+#[cfg(any(test, feature = "app"))]
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TelemetryQuery {
+    /// Days ending today to cover; everything kept when absent.
+    pub days: Option<u32>,
+    pub bucket: TelemetryBucket,
+}
+
+/// Aggregated tallies for one period of an insights query.
+#[cfg(any(test, feature = "app"))]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TelemetryPeriod {
+    /// First UTC date of the period.
+    pub start: String,
+    pub scans: u64,
+    pub average_scan_ms: Option<u64>,
+    pub items_found: u64,
+    pub auto_selected: u64,
+    pub deselections: u64,
+    /// Deselections per auto-selected item, when any were auto-selected.
+    pub deselection_rate: Option<f64>,
+    pub cleans: u64,
+    pub bytes_freed: u64,
+    /// Built-in cleaning category, or "Other" for custom rules, to bytes.
+    pub bytes_freed_by_category: BTreeMap<String, u64>,
+    pub truncated_scans: u64,
+    /// Share of scans that cut at least one analysis stage short.
+    pub truncation_rate: Option<f64>,
+    pub truncations: BTreeMap<AnalysisStage, u64>,
+}
+
+#[cfg(any(test, feature = "app"))]
+impl TelemetryPeriod {
+    fn new(start: String, day: &DayTelemetry) -> Self {
+        let rate = |count: u64, of: u64| (of > 0).then(|| count as f64 / of as f64);
+        Self {
+            start,
+            scans: day.scans,
+            average_scan_ms: (day.scans > 0).then(|| day.scan_ms / day.scans),
+            items_found: day.items_found,
+            auto_selected: day.auto_selected,
+            deselections: day.deselections,
+            deselection_rate: rate(day.deselections, day.auto_selected),
+            cleans: day.cleans,
+            bytes_freed: day
+                .bytes_freed
+                .values()
+                .fold(0u64, |total, bytes| total.saturating_add(*bytes)),
+            bytes_freed_by_category: day.bytes_freed.clone(),
+            truncated_scans: day.truncated_scans,
+            truncation_rate: rate(day.truncated_scans, day.scans),
+            truncations: day.truncations.clone(),
+        }
+    }
+}
+
+#[cfg(any(test, feature = "app"))]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TelemetryInsights {
+    pub collecting_since: String,
+    /// First and last UTC date covered.
+    pub from: String,
+    pub to: String,
+    pub bucket: TelemetryBucket,
+    /// Periods with any activity, oldest first.
+    pub periods: Vec<TelemetryPeriod>,
+    /// The whole window as one period starting at `from`.
+    pub totals: TelemetryPeriod,
+}
+
+/// Local scan and cleaning history, kept only while the user has opted in.
+#[cfg(any(test, feature = "app"))]
+#[derive(Default)]
+pub struct TelemetryStore {
+    path: Option<PathBuf>,
+    data: TelemetryData,
+}
+
+#[cfg(any(test, feature = "app"))]
+impl TelemetryStore {
+    /// Load from `<data dir>/macos-optimizer/telemetry.json`.
+    #[cfg(feature = "app")]
+    pub fn load() -> Self {
+        match persistence::data_file(TELEMETRY_FILE) {
+            Some(path) => Self::load_from(&path),
+            None => Self::default(),
+        }
+    }
+
+    /// Load from an explicit file; a missing or unreadable file yields an
+    /// empty store that has not been opted in.
+    pub fn load_from(path: &Path) -> Self {
+        let data = persistence::read_json(path).unwrap_or_default();
+        Self {
+            path: Some(path.to_path_buf()),
+            data,
+        }
+    }
+
+    #[cfg(feature = "app")]
+    pub fn opted_in(&self) -> bool {
+        self.data.opted_in
+    }
+
+    /// Start or stop collecting. Opting out discards everything collected.
+    pub fn set_opted_in(&mut self, opted_in: bool, now: i64) -> Result<(), String> {
+        if opted_in == self.data.opted_in {
+            return Ok(());
         }
-        Ok(())
+        self.data = TelemetryData {
+            opted_in,
+            since: if opted_in { now } else { 0 },
+            ..Default::default()
+        };
+        self.persist()
     }
+
+    /// Add a completed scan; a no-op unless opted in.
+    pub fn record_scan(&mut self, now: i64, scan: &ScanTelemetry) -> Result<(), String> {
+        self.update(now, |day| {
+            day.scans += 1;
+            day.scan_ms = day.scan_ms.saturating_add(scan.duration_ms);
+            day.items_found = day.items_found.saturating_add(scan.items_found);
+            day.auto_selected = day.auto_selected.saturating_add(scan.auto_selected);
+            if !scan.truncated.is_empty() {
+                day.truncated_scans += 1;
+            }
+            for stage in &scan.truncated {
+                *day.truncations.entry(*stage).or_default() += 1;
+            }
+        })
+    }
+
+    /// Count an item the user took out of a scan's selection; a no-op unless
+    /// opted in.
+    pub fn record_deselection(&mut self, now: i64) -> Result<(), String> {
+        self.update(now, |day| day.deselections += 1)
+    }
+
+    /// Count a cleaning run and the bytes it freed per category; a no-op
+    /// unless opted in.
+    pub fn record_cleaned<'a>(
+        &mut self,
+        now: i64,
+        cleaned: impl IntoIterator<Item = (&'a str, u64)>,
+    ) -> Result<(), String> {
+        if !self.data.opted_in {
+            return Ok(());
+        }
+        let builtin: HashSet<String> = builtin_category_names().into_iter().collect();
+        self.update(now, |day| {
+            day.cleans += 1;
+            for (category, bytes) in cleaned {
+                let name = if builtin.contains(category) {
+                    category
+                } else {
+                    OTHER_CATEGORY
+                };
+                let total = day.bytes_freed.entry(name.to_string()).or_default();
+                *total = total.saturating_add(bytes);
+            }
+        })
+    }
+
+    /// Tallies of the days `query` covers, grouped into its periods.
+    pub fn insights(&self, now: i64, query: &TelemetryQuery) -> Result<TelemetryInsights, String> {
+        if !self.data.opted_in {
+            return Err("Telemetry is off; opt in to see insights".into());
+        }
+        let to = utc_date(now);
+        let since = utc_date(self.data.since);
+        let from = match query.days {
+            Some(days) => utc_date(now - (days.max(1) as i64 - 1) * DAY_SECS).max(since.clone()),
+            None => since.clone(),
+        };
+
+        let mut periods: BTreeMap<String, DayTelemetry> = BTreeMap::new();
+        let mut totals = DayTelemetry::default();
+        for (date, day) in self.data.days.range(from.clone()..=to.clone()) {
+            let start = period_start(date, query.bucket).unwrap_or_else(|| from.clone());
+            periods.entry(start).or_default().add(day);
+            totals.add(day);
+        }
+
+        Ok(TelemetryInsights {
+            collecting_since: since,
+            bucket: query.bucket,
+            periods: periods
+                .iter()
+                .map(|(start, day)| TelemetryPeriod::new(start.clone(), day))
+                .collect(),
+            totals: TelemetryPeriod::new(from.clone(), &totals),
+            from,
+            to,
+        })
+    }
+
+    fn update(&mut self, now: i64, tally: impl FnOnce(&mut DayTelemetry)) -> Result<(), String> {
+        if !self.data.opted_in {
+            return Ok(());
+        }
+        tally(self.data.days.entry(utc_date(now)).or_default());
+        let cutoff = utc_date(now - (TELEMETRY_RETENTION_DAYS as i64 - 1) * DAY_SECS);
+        self.data.days.retain(|date, _| *date >= cutoff);
+        self.persist()
+    }
+
+    fn persist(&self) -> Result<(), String> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        persistence::write_json(path, &self.data)
+    }
+}
+
+#[cfg(any(test, feature = "app"))]
+fn utc_date(timestamp: i64) -> String {
+    DateTime::<Utc>::from_timestamp(timestamp, 0)
+        .unwrap_or_default()
+        .format("%Y-%m-%d")
+        .to_string()
+}
+
+/// First date of the `bucket` period `date` falls in; `None` for `All`,
+/// whose one period starts with the window.
+#[cfg(any(test, feature = "app"))]
+fn period_start(date: &str, bucket: TelemetryBucket) -> Option<String> {
+    let day = NaiveDate::parse_from_str(date, "%Y-%m-%d").ok()?;
+    let start = match bucket {
+        TelemetryBucket::Day => day,
+        TelemetryBucket::Week => day.week(Weekday::Mon).first_day(),
+        TelemetryBucket::Month => day.with_day(1)?,
+        TelemetryBucket::All => return None,
+    };
+    Some(start.format("%Y-%m-%d").to_string())
 }
@@ -0,0 +1,281 @@
+// src/file_cleaner/trash_restore.rs
+
+//! Putting items in the Trash back where they came from.
+//!
+//! The original location comes from this app's recovery points for items it
+//! trashed itself, and otherwise from the put-back records Finder keeps in
+//! the Trash's `.DS_Store`. Only items with neither go to a destination the
+//! user picks.
+
+use std::collections::HashMap;
+use std::ffi::CString;
+use std::fs;
+use std::io;
+use std::os::unix::ffi::OsStrExt;
+use std::path::{Component, Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use super::ds_store::{self, PutBack};
+
+const NO_PUT_BACK: &str = "No record of where this item came from; choose a destination";
+
+/// Where the destination of a restored item came from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RestoreSource {
+    RecoveryPoint,
+    FinderPutBack,
+    ChosenDestination,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TrashRestoreItem {
+    /// Name of the item in the Trash.
+    pub name: String,
+    pub restored_to: Option<String>,
+    pub source: Option<RestoreSource>,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TrashRestoreResult {
+    pub restored: usize,
+    pub failed: usize,
+    /// Items left in the Trash only because nothing recorded their origin;
+    /// restoring them again with a destination moves them there.
+    pub needs_destination: Vec<String>,
+    pub items: Vec<TrashRestoreItem>,
+}
+
+/// Restore the items called `names` in `trash`. Put-back directories are
+/// resolved against `volume_root`; `recorded` maps a trashed path to the
+/// original one this app remembered; `destination` receives items without
+/// either.
+pub fn restore_items(
+    trash: &Path,
+    volume_root: &Path,
+    names: &[String],
+    recorded: impl Fn(&Path) -> Option<PathBuf>,
+    destination: Option<&Path>,
+) -> TrashRestoreResult {
+    let put_backs = read_put_backs(trash);
+    let mut needs_destination = Vec::new();
+    let items: Vec<TrashRestoreItem> = names
+        .iter()
+        .map(|name| {
+            let outcome = restore_item(
+                trash,
+                volume_root,
+                name,
+                &recorded,
+                put_backs.get(name),
+                destination,
+            );
+            if matches!(&outcome, Err(err) if err == NO_PUT_BACK) {
+                needs_destination.push(name.clone());
+            }
+            match outcome {
+                Ok((target, source)) => TrashRestoreItem {
+                    name: name.clone(),
+                    restored_to: Some(target.to_string_lossy().to_string()),
+                    source: Some(source),
+                    error: None,
+                },
+                Err(err) => TrashRestoreItem {
+                    name: name.clone(),
+                    restored_to: None,
+                    source: None,
+                    error: Some(err),
+                },
+            }
+        })
+        .collect();
+    let restored = items.iter().filter(|item| item.error.is_none()).count();
+    TrashRestoreResult {
+        restored,
+        failed: items.len() - restored,
+        needs_destination,
+        items,
+    }
+}
+
+fn restore_item(
+    trash: &Path,
+    volume_root: &Path,
+    name: &str,
+    recorded: &impl Fn(&Path) -> Option<PathBuf>,
+    put_back: Option<&PutBack>,
+    destination: Option<&Path>,
+) -> Result<(PathBuf, RestoreSource), String> {
+    if name.is_empty() || name.contains('/') || name == ".." || name == "." {
+        return Err("Not an item in the Trash".to_string());
+    }
+    let source = trash.join(name);
+    if source.symlink_metadata().is_err() {
+        return Err("No longer in the Trash".to_string());
+    }
+    let original = recorded(&source)
+        .map(|path| (path, RestoreSource::RecoveryPoint))
+        .map(Ok)
+        .or_else(|| {
+            let target = put_back_target(volume_root, put_back?, name)?;
+            Some(target.map(|target| (target, RestoreSource::FinderPutBack)))
+        })
+        .transpose()?;
+    if let Some((target, origin)) = original {
+        let target = move_back_beside(&source, &target)?;
+        return Ok((target, origin));
+    }
+    let destination = destination.ok_or(NO_PUT_BACK)?;
+    let target = move_back_beside(&source, &destination.join(name))?;
+    Ok((target, RestoreSource::ChosenDestination))
+}
+
+/// Where Finder's put-back record for `name` points, under `volume_root`.
+/// `None` without a record; an error for records that climb out of the
+/// volume with `..` or through a symlink.
+fn put_back_target(
+    volume_root: &Path,
+    put_back: &PutBack,
+    name: &str,
+) -> Option<Result<PathBuf, String>> {
+    let directory = put_back.directory.as_deref()?;
+    let original_name = put_back.name.as_deref().unwrap_or(name);
+    let escapes = Path::new(directory)
+        .components()
+        .any(|component| component == Component::ParentDir)
+        || original_name.is_empty()
+        || original_name.contains('/')
+        || original_name == ".."
+        || original_name == ".";
+    let target = volume_root
+        .join(directory.trim_start_matches('/'))
+        .join(original_name);
+    if escapes || !within_volume(volume_root, &target) {
+        return Some(Err(format!(
+            "Ignoring put-back location outside {}",
+            volume_root.display()
+        )));
+    }
+    Some(Ok(target))
+}
+
+/// Whether the deepest existing folder above `target` resolves to a place
+/// inside `volume_root`.
+fn within_volume(volume_root: &Path, target: &Path) -> bool {
+    let Ok(root) = volume_root.canonicalize() else {
+        return false;
+    };
+    target
+        .ancestors()
+        .skip(1)
+        .find_map(|ancestor| ancestor.canonicalize().ok())
+        .is_some_and(|ancestor| ancestor.starts_with(&root))
+}
+
+fn read_put_backs(trash: &Path) -> HashMap<String, PutBack> {
+    let path = trash.join(".DS_Store");
+    let Ok(data) = fs::read(&path) else {
+        return HashMap::new();
+    };
+    ds_store::put_back_locations(&data).unwrap_or_else(|err| {
+        log::warn!("Ignoring unreadable {}: {}", path.display(), err);
+        HashMap::new()
+    })
+}
+
+/// Move `trashed` to `original`, recreating its parent folders; never
+/// overwrites.
+pub(crate) fn move_back(trashed: &Path, original: &Path) -> Result<(), String> {
+    if try_move_back(trashed, original)? {
+        Ok(())
+    } else {
+        Err("Something else now exists at the original location".to_string())
+    }
+}
+
+/// Move `trashed` to `target`, or next to it as `name (restored-N).ext`
+/// when something already exists there. Returns where it went.
+fn move_back_beside(trashed: &Path, target: &Path) -> Result<PathBuf, String> {
+    let (Some(dir), Some(name)) = (target.parent(), target.file_name()) else {
+        return Err(format!("Invalid restore location: {}", target.display()));
+    };
+    let name = name.to_string_lossy();
+    let mut target = target.to_path_buf();
+    while !try_move_back(trashed, &target)? {
+        target = unique_destination(dir, &name);
+    }
+    Ok(target)
+}
+
+/// Move `trashed` to `target`, recreating its parent folders. `Ok(false)`
+/// when something exists at `target`: the rename itself refuses to replace
+/// it, so an item that appears after any check is never overwritten.
+fn try_move_back(trashed: &Path, target: &Path) -> Result<bool, String> {
+    if trashed.symlink_metadata().is_err() {
+        return Err("No longer in the Trash".to_string());
+    }
+    if let Some(parent) = target.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to recreate {}: {}", parent.display(), e))?;
+    }
+    match rename_exclusive(trashed, target) {
+        Ok(()) => Ok(true),
+        Err(err) if err.kind() == io::ErrorKind::AlreadyExists => Ok(false),
+        Err(err) => Err(format!("Failed to move back: {}", err)),
+    }
+}
+
+/// `rename` that fails with `AlreadyExists` instead of replacing `to`.
+#[cfg(target_os = "macos")]
+fn rename_exclusive(from: &Path, to: &Path) -> io::Result<()> {
+    let from = CString::new(from.as_os_str().as_bytes())?;
+    let to = CString::new(to.as_os_str().as_bytes())?;
+    let status = unsafe { libc::renamex_np(from.as_ptr(), to.as_ptr(), libc::RENAME_EXCL) };
+    if status == 0 {
+        Ok(())
+    } else {
+        Err(io::Error::last_os_error())
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn rename_exclusive(from: &Path, to: &Path) -> io::Result<()> {
+    let from = CString::new(from.as_os_str().as_bytes())?;
+    let to = CString::new(to.as_os_str().as_bytes())?;
+    let status = unsafe {
+        libc::renameat2(
+            libc::AT_FDCWD,
+            from.as_ptr(),
+            libc::AT_FDCWD,
+            to.as_ptr(),
+            libc::RENAME_NOREPLACE,
+        )
+    };
+    if status == 0 {
+        Ok(())
+    } else {
+        Err(io::Error::last_os_error())
+    }
+}
+
+/// `dir/name`, or `dir/name (restored-N).ext` when that is taken.
+fn unique_destination(dir: &Path, name: &str) -> PathBuf {
+    let (base, ext) = match name.rfind('.') {
+        Some(idx) if idx > 0 => (&name[..idx], &name[idx + 1..]),
+        _ => (name, ""),
+    };
+    let mut target = dir.join(name);
+    let mut counter = 1u32;
+    while target.symlink_metadata().is_ok() {
+        let candidate = if ext.is_empty() {
+            format!("{} (restored-{})", base, counter)
+        } else {
+            format!("{} (restored-{}).{}", base, counter, ext)
+        };
+        target = dir.join(candidate);
+        counter += 1;
+    }
+    target
+}
@@ -3,6 +3,11 @@ use serde_json;
 use std::env;
 use std::fs;
 
+use super::age_thresholds::AgeThresholds;
+use super::custom_rules::load_custom_category_rules;
+use super::disk_usage::DiskUsage;
+use super::rule_packs::load_rule_pack_rules;
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct CleanableFile {
     pub path: String,
@@ -17,10 +22,15 @@ pub struct CleanableFile {
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CleaningReport {
+    /// Allocated size of all results, each hard-linked file counted once.
     pub total_size: u64,
     pub files_count: usize,
     pub categories: Vec<CategoryReport>,
     pub advanced_categories: Vec<String>,
+    /// Apparent and allocated size of the results, and what deleting all
+    /// of them would free given hard links and APFS clones.
+    #[serde(default)]
+    pub disk_usage: DiskUsage,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -28,6 +38,105 @@ pub struct CategoryReport {
     pub name: String,
     pub size: u64,
     pub count: usize,
+    /// `name` with its age tag following the user's threshold, when one is set.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+}
+
+/// A top-level entry in the user's Trash, sized before deletion.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TrashItem {
+    pub path: String,
+    pub name: String,
+    pub size: u64,
+    pub is_dir: bool,
+    /// When the entry was moved to the Trash: its inode change time, which
+    /// the move sets.
+    #[serde(default)]
+    pub trashed_at: i64,
+}
+
+/// The Trash on one mounted volume.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TrashVolume {
+    /// Mount point; `/` for the startup disk, whose Trash is `~/.Trash`.
+    pub volume: String,
+    pub path: String,
+    pub size: u64,
+    pub items: usize,
+}
+
+/// Size and item count of the Trash on every mounted volume.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TrashSummary {
+    pub volumes: Vec<TrashVolume>,
+    pub total_size: u64,
+    pub total_items: usize,
+}
+
+/// Per-batch progress while emptying the Trash.
+#[derive(Debug, Clone, Serialize)]
+pub struct TrashEmptyProgress {
+    /// Mount point of the volume whose Trash the batch came from.
+    pub volume: String,
+    pub items_done: usize,
+    pub items_total: usize,
+    pub bytes_done: u64,
+    pub bytes_total: u64,
+    pub eta_ms: Option<u32>,
+    pub items_per_s: Option<f32>,
+    pub mb_per_s: Option<f32>,
+}
+
+/// Baseline scan progress, reported as each rule path finishes walking.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScanProgress {
+    /// Category of the path that just finished.
+    pub category: String,
+    pub path: String,
+    pub paths_done: usize,
+    pub paths_total: usize,
+    /// Directory entries examined so far.
+    pub entries_seen: u64,
+    /// Combined size of the matches found so far.
+    pub bytes_found: u64,
+    pub eta_ms: Option<u32>,
+    pub entries_per_s: Option<f32>,
+    pub mb_per_s: Option<f32>,
+}
+
+/// Outcome of emptying the Trash. When `canceled` is set the counters
+/// describe the work completed before cancellation was observed.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TrashEmptyResult {
+    pub freed: u64,
+    pub removed: usize,
+    pub failed: usize,
+    pub total_items: usize,
+    pub total_bytes: u64,
+    pub canceled: bool,
+    /// Set when inspector mode reported what would happen instead of doing it.
+    #[serde(default)]
+    pub dry_run: bool,
+    /// Mount points of the volumes whose Trash was included.
+    #[serde(default)]
+    pub volumes: Vec<String>,
+    /// Set when the Trash could not be listed and Finder emptied it instead,
+    /// so the item and byte counts are unknown.
+    #[serde(default)]
+    pub emptied_by_finder: bool,
+}
+
+/// Outcome of permanently deleting the Trash entries past an age limit.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TrashPurgeResult {
+    /// Entries that were deleted.
+    pub purged: Vec<TrashItem>,
+    pub freed: u64,
+    pub failed: usize,
+    /// Entries left in place because they are newer than the limit.
+    pub kept: usize,
+    pub canceled: bool,
 }
 
 // -------- Rule Engine Types & Helpers --------
@@ -37,7 +146,7 @@ pub(crate) struct CleanerRules {
     pub(crate) categories: Vec<CategoryRule>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Deserialize)]
 pub(crate) struct CategoryRule {
     pub(crate) name: String,
     pub(crate) paths: Vec<String>,
@@ -52,10 +161,49 @@ pub(crate) struct CategoryRule {
     pub(crate) require_subpaths: Option<Vec<String>>,
 }
 
+impl CategoryRule {
+    /// Whether the rule matches folders rather than files, going by its name.
+    pub(crate) fn matches_folders(&self) -> bool {
+        let name = self.name.to_lowercase();
+        name.contains("folder") || name.contains("cache") || name.contains("container")
+    }
+
+    /// Whether `min_age_days` counts from when an item was created rather
+    /// than last modified: downloads keep the modification date the server
+    /// gave them.
+    pub(crate) fn ages_by_creation(&self) -> bool {
+        let name = self.name.to_lowercase();
+        name.contains("downloads") || name.contains("desktop")
+    }
+}
+
 const DEFAULT_RULES_JSON: &str = include_str!("../../rules/cleaner_rules.json");
 
-// Load rules with error propagation (for scan_system)
+// Load rules with error propagation (for scan_system). Custom rules, enabled
+// rule packs and the user's age thresholds apply unless an override file replaces the rule set
+// entirely.
 pub fn load_rules_result() -> Result<CleanerRules, String> {
+    let mut rules = load_base_rules()?;
+    if env::var_os("MACOS_OPTIMIZER_RULES_OVERRIDE").is_none() {
+        let custom = load_custom_category_rules(&rules);
+        rules.categories.extend(custom);
+        let packs = load_rule_pack_rules(&rules);
+        rules.categories.extend(packs);
+        AgeThresholds::load().apply(&mut rules.categories);
+    }
+    Ok(rules)
+}
+
+// The user's age thresholds, or none while an override file is in use.
+pub(crate) fn load_age_thresholds() -> AgeThresholds {
+    if env::var_os("MACOS_OPTIMIZER_RULES_OVERRIDE").is_some() {
+        return AgeThresholds::default();
+    }
+    AgeThresholds::load()
+}
+
+// Embedded (or override) rules without custom rules
+pub(crate) fn load_base_rules() -> Result<CleanerRules, String> {
     if let Ok(path) = env::var("MACOS_OPTIMIZER_RULES_OVERRIDE") {
         let data = fs::read_to_string(&path)
             .map_err(|e| format!("Failed to read cleaner rules override ({}): {}", path, e))?;
@@ -71,3 +219,12 @@ pub fn load_rules_result() -> Result<CleanerRules, String> {
 pub(crate) fn load_rules() -> CleanerRules {
     load_rules_result().unwrap_or(CleanerRules { categories: vec![] })
 }
+
+/// Names of the embedded cleaning rules; custom rule names are user-chosen
+/// and may say something about the user, so they are not included.
+#[cfg(any(test, feature = "app"))]
+pub(crate) fn builtin_category_names() -> Vec<String> {
+    serde_json::from_str::<CleanerRules>(DEFAULT_RULES_JSON)
+        .map(|rules| rules.categories.into_iter().map(|rule| rule.name).collect())
+        .unwrap_or_default()
+}
@@ -4,10 +4,13 @@ use std::collections::HashMap;
 use std::fs;
 use std::path::{Path, PathBuf};
 use tokio::process::Command;
-use tokio::time::{timeout, Duration};
 
 use super::dependency_checker::DependencyChecker;
+use super::process_snapshot;
+use super::trash_restore;
 use super::types::CleanableFile;
+use crate::capabilities::{self, Tool};
+use crate::persistence;
 
 /// Pre-deletion validation system
 pub struct PreDeletionValidator {
@@ -215,74 +218,37 @@ impl FileLockChecker {
     }
 
     pub async fn check_open_files(&self, files: &[CleanableFile]) -> OpenFileCheckReport {
-        let mut open_files = Vec::new();
-        let mut degraded_reason = None;
+        let Some(lsof_program) = &self.lsof_program else {
+            return OpenFileCheckReport {
+                open_files: Vec::new(),
+                degraded_reason: Some(
+                    "lsof command is unavailable on this system; open-file checks are fail-safe"
+                        .to_string(),
+                ),
+            };
+        };
 
-        if let Some(lsof_program) = &self.lsof_program {
-            for file in files {
-                let path = PathBuf::from(&file.path);
-                match fs::metadata(&path) {
-                    Ok(metadata) => {
-                        if metadata.is_dir() {
-                            continue;
-                        }
-                    }
-                    Err(err) => {
-                        if err.kind() == std::io::ErrorKind::NotFound {
-                            continue;
-                        }
-                        continue;
-                    }
-                }
-
-                match self.is_file_open(lsof_program, &path).await {
-                    Ok(true) => open_files.push(path),
-                    Ok(false) => {}
-                    Err(err) => {
-                        degraded_reason = Some(err);
-                        break;
-                    }
+        // Folders and files that are already gone need no check.
+        let paths: Vec<PathBuf> = files
+            .iter()
+            .map(|file| PathBuf::from(&file.path))
+            .filter(|path| fs::metadata(path).is_ok_and(|metadata| !metadata.is_dir()))
+            .collect();
+        match process_snapshot::open_files_among(lsof_program, &paths).await {
+            Ok(open) => OpenFileCheckReport {
+                open_files: paths
+                    .into_iter()
+                    .filter(|path| open.contains(path))
+                    .collect(),
+                degraded_reason: None,
+            },
+            Err(err) => {
+                log::warn!("{}", err);
+                OpenFileCheckReport {
+                    open_files: Vec::new(),
+                    degraded_reason: Some(err),
                 }
             }
-        } else {
-            degraded_reason = Some(
-                "lsof command is unavailable on this system; open-file checks are fail-safe"
-                    .to_string(),
-            );
-        }
-
-        OpenFileCheckReport {
-            open_files,
-            degraded_reason,
-        }
-    }
-
-    async fn is_file_open(&self, lsof_program: &Path, path: &Path) -> Result<bool, String> {
-        let mut command = Command::new(lsof_program);
-        command.arg(path);
-        command.kill_on_drop(true);
-
-        match timeout(Duration::from_secs(5), command.output()).await {
-            Ok(Ok(output)) => Ok(!output.stdout.is_empty()),
-            Ok(Err(err)) => {
-                let message = format!(
-                    "lsof check failed for {} using {}: {}",
-                    path.display(),
-                    lsof_program.display(),
-                    err
-                );
-                log::warn!("{}", message);
-                Err(message)
-            }
-            Err(_) => {
-                let message = format!(
-                    "lsof timed out for {} using {}",
-                    path.display(),
-                    lsof_program.display()
-                );
-                log::warn!("{}", message);
-                Err(message)
-            }
         }
     }
 }
@@ -303,6 +269,9 @@ impl BackupVerifier {
     }
 
     fn check_time_machine_status() -> bool {
+        if !capabilities::tool_available(Tool::Tmutil) {
+            return false;
+        }
         let destination_info = std::process::Command::new("tmutil")
             .arg("destinationinfo")
             .output();
@@ -371,15 +340,38 @@ impl BackupVerifier {
     }
 }
 
+const RECOVERY_FILE: &str = "recovery_points.json";
+/// Older points are dropped when a new one is created.
+const MAX_RECOVERY_POINTS: usize = 20;
+
 /// Recovery manager for deleted files
+#[derive(Default)]
 pub struct RecoveryManager {
     recovery_points: Vec<RecoveryPoint>,
+    path: Option<PathBuf>,
 }
 
 impl RecoveryManager {
-    pub fn new() -> Self {
+    /// In-memory only; nothing is persisted.
+    #[cfg(test)]
+    pub(crate) fn new() -> Self {
+        Self::default()
+    }
+
+    /// Load from `<data dir>/macos-optimizer/recovery_points.json`.
+    pub fn load() -> Self {
+        match persistence::data_file(RECOVERY_FILE) {
+            Some(path) => Self::load_from(&path),
+            None => Self::default(),
+        }
+    }
+
+    /// Load from an explicit file; a missing or unreadable file yields no points.
+    pub fn load_from(path: &Path) -> Self {
+        let recovery_points = persistence::read_json(path).unwrap_or_default();
         Self {
-            recovery_points: Vec::new(),
+            recovery_points,
+            path: Some(path.to_path_buf()),
         }
     }
 
@@ -394,15 +386,111 @@ impl RecoveryManager {
                     size: f.size,
                     category: f.category.clone(),
                     metadata: self.capture_metadata(&PathBuf::from(&f.path)),
+                    trashed_path: None,
                 })
                 .collect(),
             recovery_method: self.determine_recovery_method(files),
         };
 
         self.recovery_points.push(recovery_point.clone());
+        let excess = self
+            .recovery_points
+            .len()
+            .saturating_sub(MAX_RECOVERY_POINTS);
+        self.recovery_points.drain(..excess);
         recovery_point
     }
 
+    /// Note where `original` ended up in the Trash; kept in memory until `persist`.
+    pub fn mark_trashed(&mut self, id: &str, original: &Path, trashed: PathBuf) {
+        if let Some(file) = self
+            .recovery_points
+            .iter_mut()
+            .find(|point| point.id == id)
+            .and_then(|point| point.files.iter_mut().find(|f| f.original_path == original))
+        {
+            file.trashed_path = Some(trashed);
+        }
+    }
+
+    /// Move every trashed file of point `id` back to where it was.
+    ///
+    /// Files are never overwritten: one whose original location is taken
+    /// again stays in the Trash and is reported as failed.
+    pub fn restore(&mut self, id: &str) -> Result<UndoResult, String> {
+        let point = self
+            .recovery_points
+            .iter_mut()
+            .find(|point| point.id == id)
+            .ok_or("The recovery point for this clean is no longer available")?;
+        let mut files = Vec::new();
+        for file in point.files.iter_mut() {
+            let Some(trashed) = file.trashed_path.clone() else {
+                continue;
+            };
+            let outcome = trash_restore::move_back(&trashed, &file.original_path);
+            if outcome.is_ok() {
+                file.trashed_path = None;
+            }
+            files.push(RestoredFile {
+                original_path: file.original_path.to_string_lossy().to_string(),
+                error: outcome.err(),
+            });
+        }
+        if files.is_empty() {
+            return Err("Nothing from this clean is left to restore".to_string());
+        }
+        self.persist()?;
+        let restored = files.iter().filter(|file| file.error.is_none()).count();
+        Ok(UndoResult {
+            recovery_point_id: id.to_string(),
+            restored,
+            failed: files.len() - restored,
+            files,
+        })
+    }
+
+    /// Where the newest point that trashed `trashed` found it.
+    pub fn original_for(&self, trashed: &Path) -> Option<PathBuf> {
+        self.recovery_points
+            .iter()
+            .rev()
+            .flat_map(|point| &point.files)
+            .find(|file| file.trashed_path.as_deref() == Some(trashed))
+            .map(|file| file.original_path.clone())
+    }
+
+    /// Drop the Trash locations of items that were restored some other way.
+    pub fn forget_trashed(&mut self, trashed: &[PathBuf]) -> Result<(), String> {
+        let mut changed = false;
+        for file in self
+            .recovery_points
+            .iter_mut()
+            .flat_map(|point| point.files.iter_mut())
+        {
+            if file
+                .trashed_path
+                .as_ref()
+                .is_some_and(|path| trashed.contains(path))
+            {
+                file.trashed_path = None;
+                changed = true;
+            }
+        }
+        if changed {
+            self.persist()
+        } else {
+            Ok(())
+        }
+    }
+
+    pub fn persist(&self) -> Result<(), String> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        persistence::write_json(path, &self.recovery_points)
+    }
+
     fn capture_metadata(&self, path: &Path) -> FileMetadata {
         let mut metadata = FileMetadata {
             permissions: None,
@@ -446,10 +534,6 @@ impl RecoveryManager {
             RecoveryMethod::Regenerate
         }
     }
-
-    // Recovery restore API can be introduced as a Tauri command when UI flow is ready.
-
-    // Placeholder helpers for future recovery modes are intentionally omitted to keep the crate warning-free.
 }
 
 // Data structures for validation and recovery
@@ -523,6 +607,25 @@ pub struct RecoveryFile {
     pub size: u64,
     pub category: String,
     pub metadata: FileMetadata,
+    /// Where the file went in the Trash, once it was moved there.
+    #[serde(default)]
+    pub trashed_path: Option<PathBuf>,
+}
+
+/// What `undo_last_clean` did with each file it tried to put back.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UndoResult {
+    pub recovery_point_id: String,
+    pub restored: usize,
+    pub failed: usize,
+    pub files: Vec<RestoredFile>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RestoredFile {
+    pub original_path: String,
+    /// Why the file stayed in the Trash; `None` once it is back.
+    pub error: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
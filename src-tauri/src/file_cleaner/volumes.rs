@@ -0,0 +1,187 @@
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use sysinfo::Disks;
+
+use super::enhanced_engine::{EnhancedCleanableFile, ReclaimableBreakdown};
+
+/// Mounts under this prefix are APFS companions of the boot volume (Data,
+/// Preboot, VM); the user sees them as part of "/".
+const SYSTEM_VOLUMES_PREFIX: &str = "/System/Volumes/";
+const ROOT_VOLUME: &str = "/";
+
+/// A mounted volume, identified by its mount point.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VolumeInfo {
+    pub id: String,
+    pub name: String,
+    pub total_space: u64,
+    pub available_space: u64,
+    pub is_removable: bool,
+}
+
+/// The mounted volumes a scan can be scoped to.
+pub fn list_volumes() -> Vec<VolumeInfo> {
+    let disks = Disks::new_with_refreshed_list();
+    let mut volumes: Vec<VolumeInfo> = disks
+        .list()
+        .iter()
+        .filter_map(|disk| {
+            let id = disk.mount_point().to_string_lossy().to_string();
+            if id.starts_with(SYSTEM_VOLUMES_PREFIX) {
+                return None;
+            }
+            Some(VolumeInfo {
+                name: volume_name(&id, &disk.name().to_string_lossy()),
+                id,
+                total_space: disk.total_space(),
+                available_space: disk.available_space(),
+                is_removable: disk.is_removable(),
+            })
+        })
+        .collect();
+    volumes.sort_by(|a, b| a.id.cmp(&b.id));
+    volumes.dedup_by(|a, b| a.id == b.id);
+    volumes
+}
+
+fn volume_name(id: &str, disk_name: &str) -> String {
+    if !disk_name.is_empty() {
+        return disk_name.to_string();
+    }
+    id.rsplit('/')
+        .find(|part| !part.is_empty())
+        .unwrap_or(ROOT_VOLUME)
+        .to_string()
+}
+
+/// The Trash of user `uid` on each volume that has one: `home_trash` for
+/// the startup disk and `.Trashes/<uid>` at the root of the others.
+pub(crate) fn trash_locations(
+    mount_points: impl IntoIterator<Item = String>,
+    home_trash: &Path,
+    uid: u32,
+) -> Vec<(String, PathBuf)> {
+    let mut locations = vec![(ROOT_VOLUME.to_string(), home_trash.to_path_buf())];
+    for mount in mount_points {
+        if mount == ROOT_VOLUME || mount.starts_with(SYSTEM_VOLUMES_PREFIX) {
+            continue;
+        }
+        let trash = Path::new(&mount).join(".Trashes").join(uid.to_string());
+        if trash.is_dir() && !locations.iter().any(|(volume, _)| *volume == mount) {
+            locations.push((mount, trash));
+        }
+    }
+    locations
+}
+
+/// Which volumes a scan may report files on. An empty `include` list means
+/// every volume not in `exclude`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VolumeScope {
+    #[serde(default)]
+    pub include: Vec<String>,
+    #[serde(default)]
+    pub exclude: Vec<String>,
+}
+
+impl VolumeScope {
+    pub fn allows(&self, volume: &str) -> bool {
+        (self.include.is_empty() || self.include.iter().any(|id| id == volume))
+            && !self.exclude.iter().any(|id| id == volume)
+    }
+
+    pub fn is_unrestricted(&self) -> bool {
+        self.include.is_empty() && self.exclude.is_empty()
+    }
+}
+
+/// Maps paths to the mount point of the volume holding them.
+#[derive(Debug, Clone)]
+pub(crate) struct VolumeResolver {
+    /// Longest first, so nested mounts win over their parents.
+    mount_points: Vec<String>,
+}
+
+impl VolumeResolver {
+    pub(crate) fn new(mount_points: impl IntoIterator<Item = String>) -> Self {
+        let mut mount_points: Vec<String> = mount_points
+            .into_iter()
+            .filter(|mount| !mount.starts_with(SYSTEM_VOLUMES_PREFIX))
+            .map(|mount| {
+                let trimmed = mount.trim_end_matches('/');
+                if trimmed.is_empty() {
+                    ROOT_VOLUME.to_string()
+                } else {
+                    trimmed.to_string()
+                }
+            })
+            .collect();
+        mount_points.sort_by_key(|mount| std::cmp::Reverse(mount.len()));
+        mount_points.dedup();
+        Self { mount_points }
+    }
+
+    pub(crate) fn mounted() -> Self {
+        Self::new(list_volumes().into_iter().map(|volume| volume.id))
+    }
+
+    pub(crate) fn volume_of(&self, path: &str) -> String {
+        self.mount_points
+            .iter()
+            .find(|mount| {
+                mount.as_str() == ROOT_VOLUME
+                    || path == mount.as_str()
+                    || (path.starts_with(mount.as_str())
+                        && path.as_bytes().get(mount.len()) == Some(&b'/'))
+            })
+            .cloned()
+            .unwrap_or_else(|| ROOT_VOLUME.to_string())
+    }
+}
+
+/// Scan totals for one volume.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VolumeSummary {
+    pub volume: String,
+    pub total_size: u64,
+    pub file_count: usize,
+    pub auto_selected_size: u64,
+    pub reclaimable: ReclaimableBreakdown,
+}
+
+/// Group scan results by their volume tag, largest volume first.
+pub(crate) fn summarize_by_volume(files: &[EnhancedCleanableFile]) -> Vec<VolumeSummary> {
+    let mut summaries: Vec<VolumeSummary> = Vec::new();
+    for file in files {
+        let index = match summaries
+            .iter()
+            .position(|summary| summary.volume == file.volume)
+        {
+            Some(index) => index,
+            None => {
+                summaries.push(VolumeSummary {
+                    volume: file.volume.clone(),
+                    total_size: 0,
+                    file_count: 0,
+                    auto_selected_size: 0,
+                    reclaimable: ReclaimableBreakdown::default(),
+                });
+                summaries.len() - 1
+            }
+        };
+        let summary = &mut summaries[index];
+        summary.total_size += file.base.size;
+        summary.file_count += 1;
+        if file.base.auto_select {
+            summary.auto_selected_size += file.base.size;
+        }
+        summary.reclaimable.add(file);
+    }
+    summaries.sort_by(|a, b| {
+        b.total_size
+            .cmp(&a.total_size)
+            .then_with(|| a.volume.cmp(&b.volume))
+    });
+    summaries
+}
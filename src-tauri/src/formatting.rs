@@ -0,0 +1,239 @@
+// src/formatting.rs
+
+//! Locale-aware formatting for sizes, rates, counts and ETAs.
+//!
+//! Every user-facing string that carries a number (progress messages,
+//! completion events, notifications, exports, logs) goes through here so the
+//! same value reads the same everywhere, e.g. "1.2 GB" for `en_US` and
+//! "1,2 Go" for `fr_FR`.
+
+use std::env;
+use std::sync::RwLock;
+
+use lazy_static::lazy_static;
+use serde::{Deserialize, Serialize};
+
+#[cfg(test)]
+mod tests;
+
+/// Which multiplier sizes are shown in. Finder uses decimal units.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UnitSystem {
+    #[default]
+    Decimal,
+    Binary,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FormatPreferences {
+    /// POSIX-style locale identifier such as `en_US` or `fr_FR.UTF-8`.
+    pub locale: String,
+    pub units: UnitSystem,
+}
+
+impl FormatPreferences {
+    /// Preferences for the user's locale: `LC_ALL`, `LC_NUMERIC` or `LANG`
+    /// when set, as from a terminal, otherwise the system locale, since apps
+    /// opened from Finder get none of them.
+    pub fn detect() -> Self {
+        let locale = ["LC_ALL", "LC_NUMERIC", "LANG"]
+            .iter()
+            .filter_map(|key| env::var(key).ok())
+            .find(|value| !value.trim().is_empty() && value != "C" && value != "POSIX")
+            .or_else(system_locale)
+            .unwrap_or_else(|| "en_US".to_string());
+        Self {
+            locale,
+            units: UnitSystem::default(),
+        }
+    }
+}
+
+/// The locale chosen in System Settings, e.g. `fr_FR`.
+#[cfg(target_os = "macos")]
+fn system_locale() -> Option<String> {
+    use objc2_foundation::NSLocale;
+
+    let identifier = NSLocale::currentLocale().localeIdentifier().to_string();
+    (!identifier.is_empty()).then_some(identifier)
+}
+
+#[cfg(not(target_os = "macos"))]
+fn system_locale() -> Option<String> {
+    None
+}
+
+lazy_static! {
+    static ref FORMAT_PREFERENCES: RwLock<FormatPreferences> =
+        RwLock::new(FormatPreferences::detect());
+}
+
+pub fn preferences() -> FormatPreferences {
+    FORMAT_PREFERENCES
+        .read()
+        .map(|prefs| prefs.clone())
+        .unwrap_or_else(|_| FormatPreferences::detect())
+}
+
+#[cfg(feature = "app")]
+pub fn set_preferences(prefs: FormatPreferences) {
+    if let Ok(mut current) = FORMAT_PREFERENCES.write() {
+        *current = prefs;
+    }
+}
+
+/// Show sizes in `units` from now on, keeping the locale.
+#[cfg(feature = "app")]
+pub fn set_units(units: UnitSystem) {
+    if let Ok(mut current) = FORMAT_PREFERENCES.write() {
+        current.units = units;
+    }
+}
+
+/// Formatter for the currently configured preferences.
+pub fn current() -> Formatter {
+    Formatter::new(&preferences())
+}
+
+pub fn format_size(bytes: u64) -> String {
+    current().size(bytes)
+}
+
+#[cfg(feature = "app")]
+pub fn format_rate(bytes_per_s: f64) -> String {
+    current().rate(bytes_per_s)
+}
+
+#[cfg(feature = "app")]
+pub fn format_eta(ms: u64) -> String {
+    current().eta(ms)
+}
+
+pub fn format_count(value: u64) -> String {
+    current().count(value)
+}
+
+#[derive(Debug, Clone)]
+pub struct Formatter {
+    decimal_separator: char,
+    group_separator: char,
+    byte_symbol: char,
+    units: UnitSystem,
+}
+
+impl Formatter {
+    pub fn new(prefs: &FormatPreferences) -> Self {
+        let language = prefs
+            .locale
+            .split(['_', '-', '.', '@'])
+            .next()
+            .unwrap_or("en")
+            .to_ascii_lowercase();
+
+        let (decimal_separator, group_separator) = match language.as_str() {
+            "de" | "es" | "it" | "nl" | "pt" | "id" | "tr" | "da" | "el" | "ro" | "hr" => {
+                (',', '.')
+            }
+            "fr" | "ru" | "pl" | "cs" | "sk" | "sv" | "nb" | "no" | "fi" | "uk" | "hu" | "bg" => {
+                (',', '\u{a0}')
+            }
+            _ => ('.', ','),
+        };
+        // French-speaking locales write sizes in octets ("Go", "Mo").
+        let byte_symbol = if language == "fr" { 'o' } else { 'B' };
+
+        Self {
+            decimal_separator,
+            group_separator,
+            byte_symbol,
+            units: prefs.units,
+        }
+    }
+
+    /// Size with one decimal above the base unit, e.g. `1.2 GB` / `1,2 Go` / `1.1 GiB`.
+    pub fn size(&self, bytes: u64) -> String {
+        let (base, prefixes): (f64, [&str; 6]) = match self.units {
+            UnitSystem::Decimal => (1000.0, ["k", "M", "G", "T", "P", "E"]),
+            UnitSystem::Binary => (1024.0, ["Ki", "Mi", "Gi", "Ti", "Pi", "Ei"]),
+        };
+        if (bytes as f64) < base {
+            return format!("{} {}", self.count(bytes), self.byte_symbol);
+        }
+        let mut value = bytes as f64;
+        let mut index = 0usize;
+        while value >= base && index < prefixes.len() {
+            value /= base;
+            index += 1;
+        }
+        // Rounding can push 999.95 up to "1000.0"; step up a unit instead.
+        if (value * 10.0).round() / 10.0 >= base && index < prefixes.len() {
+            value /= base;
+            index += 1;
+        }
+        format!(
+            "{} {}{}",
+            self.decimal(value, 1),
+            prefixes[index - 1],
+            self.byte_symbol
+        )
+    }
+
+    #[cfg(any(test, feature = "app"))]
+    pub fn rate(&self, bytes_per_s: f64) -> String {
+        let bytes = if bytes_per_s.is_finite() && bytes_per_s > 0.0 {
+            bytes_per_s.round() as u64
+        } else {
+            0
+        };
+        format!("{}/s", self.size(bytes))
+    }
+
+    /// Compact duration: `45 s`, `3 min 20 s`, `1 h 05 min`.
+    #[cfg(any(test, feature = "app"))]
+    pub fn eta(&self, ms: u64) -> String {
+        let total_seconds = ms.div_ceil(1000);
+        let hours = total_seconds / 3600;
+        let minutes = (total_seconds % 3600) / 60;
+        let seconds = total_seconds % 60;
+        if hours > 0 {
+            format!("{} h {:02} min", self.count(hours), minutes)
+        } else if minutes > 0 {
+            format!("{} min {:02} s", minutes, seconds)
+        } else {
+            format!("{} s", seconds)
+        }
+    }
+
+    /// Integer with locale digit grouping, e.g. `12,345` / `12.345` / `12 345`.
+    pub fn count(&self, value: u64) -> String {
+        let digits = value.to_string();
+        let mut grouped = String::with_capacity(digits.len() + digits.len() / 3);
+        for (idx, ch) in digits.chars().enumerate() {
+            if idx > 0 && (digits.len() - idx).is_multiple_of(3) {
+                grouped.push(self.group_separator);
+            }
+            grouped.push(ch);
+        }
+        grouped
+    }
+
+    /// Fixed-point number with locale separators.
+    pub fn decimal(&self, value: f64, places: usize) -> String {
+        let rendered = format!("{:.*}", places, value.abs());
+        let (whole, fraction) = match rendered.split_once('.') {
+            Some((whole, fraction)) => (whole, Some(fraction)),
+            None => (rendered.as_str(), None),
+        };
+        let mut out = String::new();
+        if value.is_sign_negative() && value != 0.0 {
+            out.push('-');
+        }
+        out.push_str(&self.count(whole.parse().unwrap_or(0)));
+        if let Some(fraction) = fraction {
+            out.push(self.decimal_separator);
+            out.push_str(fraction);
+        }
+        out
+    }
+}
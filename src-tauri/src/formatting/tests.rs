@@ -0,0 +1,51 @@
+use super::{FormatPreferences, Formatter, UnitSystem};
+
+fn formatter(locale: &str, units: UnitSystem) -> Formatter {
+    Formatter::new(&FormatPreferences {
+        locale: locale.to_string(),
+        units,
+    })
+}
+
+#[test]
+fn sizes_follow_locale_and_unit_system() {
+    let en = formatter("en_US.UTF-8", UnitSystem::Decimal);
+    assert_eq!(en.size(512), "512 B");
+    assert_eq!(en.size(1_200_000_000), "1.2 GB");
+    assert_eq!(en.size(999_960), "1.0 MB");
+
+    let fr = formatter("fr_FR.UTF-8", UnitSystem::Decimal);
+    assert_eq!(fr.size(1_200_000_000), "1,2 Go");
+    assert_eq!(fr.size(2_500), "2,5 ko");
+
+    let binary = formatter("en_GB", UnitSystem::Binary);
+    assert_eq!(binary.size(1024 * 1024 * 3 / 2), "1.5 MiB");
+    assert_eq!(binary.rate(2048.0), "2.0 KiB/s");
+
+    let de = formatter("de_DE", UnitSystem::Decimal);
+    assert_eq!(de.rate(1_500_000.0), "1,5 MB/s");
+}
+
+#[test]
+fn counts_and_etas_are_grouped_and_compact() {
+    assert_eq!(
+        formatter("en_US", UnitSystem::Decimal).count(1_234_567),
+        "1,234,567"
+    );
+    assert_eq!(
+        formatter("de_DE", UnitSystem::Decimal).count(12_345),
+        "12.345"
+    );
+    assert_eq!(
+        formatter("fr_FR", UnitSystem::Decimal).count(12_345),
+        "12\u{a0}345"
+    );
+    assert_eq!(formatter("C", UnitSystem::Decimal).count(999), "999");
+
+    let en = formatter("en_US", UnitSystem::Decimal);
+    assert_eq!(en.eta(0), "0 s");
+    assert_eq!(en.eta(44_100), "45 s");
+    assert_eq!(en.eta(200_000), "3 min 20 s");
+    assert_eq!(en.eta(3_900_000), "1 h 05 min");
+    assert_eq!(en.decimal(-1234.56, 1), "-1,234.6");
+}
@@ -0,0 +1,408 @@
+// src/health_score.rs
+
+//! A single 0-100 system health score and the weekly summary built around it.
+//!
+//! The score is a weighted average of five factors: free space on the system
+//! volume, memory pressure and whether it is climbing, the number of enabled
+//! login items, how fast caches grow between scans, and the share of
+//! operations that failed. A factor without data (no scans yet, no metrics
+//! history) is left out and the others are reweighted.
+//!
+//! Cache sizes are not sampled anywhere else, so each completed scan appends
+//! one to a [`CacheSizeLog`].
+
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::file_cleaner::types::CleanableFile;
+use crate::formatting::{format_count, format_size};
+use crate::persistence;
+use crate::session::WeeklyDigest;
+
+#[cfg(test)]
+mod tests;
+
+const CACHE_SIZES_FILE: &str = "cache_sizes.json";
+const DAY_SECS: i64 = 24 * 60 * 60;
+const WEEK_SECS: i64 = 7 * DAY_SECS;
+/// Samples older than this are dropped when a new one is recorded.
+const CACHE_RETENTION_SECS: i64 = 8 * WEEK_SECS;
+/// Growth is only measured across samples at least this far apart.
+const MIN_GROWTH_SPAN_SECS: i64 = 12 * 60 * 60;
+/// Failures listed in the weekly summary; the counts include all of them.
+const MAX_LISTED_FAILURES: usize = 5;
+
+// Factor weights, summing to 100.
+const DISK_WEIGHT: u8 = 30;
+const MEMORY_WEIGHT: u8 = 25;
+const LOGIN_ITEMS_WEIGHT: u8 = 15;
+const CACHE_GROWTH_WEIGHT: u8 = 15;
+const FAILURES_WEIGHT: u8 = 15;
+
+// Each factor scores 100 at or below its first bound and 0 at or beyond its
+// second, linearly in between.
+const DISK_FREE_PERCENT: (f64, f64) = (25.0, 5.0);
+const MEMORY_PRESSURE_PERCENT: (f64, f64) = (50.0, 90.0);
+/// Points lost per point of pressure the second half of the window is above
+/// the first, up to `MAX_RISING_PENALTY`.
+const RISING_PENALTY_PER_POINT: f64 = 1.0;
+const MAX_RISING_PENALTY: f64 = 20.0;
+const LOGIN_ITEMS: (f64, f64) = (10.0, 40.0);
+const CACHE_GROWTH_PER_DAY: (f64, f64) = (100_000_000.0, 2_000_000_000.0);
+
+const GOOD_SCORE: u8 = 80;
+const FAIR_SCORE: u8 = 50;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HealthFactorKind {
+    DiskSpace,
+    MemoryPressure,
+    LoginItems,
+    CacheGrowth,
+    FailedOperations,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HealthFactor {
+    pub kind: HealthFactorKind,
+    pub score: u8,
+    /// Share of the total score, before factors without data are left out.
+    pub weight: u8,
+    /// What the score is based on, for display.
+    pub detail: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HealthGrade {
+    Good,
+    Fair,
+    Poor,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HealthScore {
+    pub score: u8,
+    pub grade: HealthGrade,
+    pub factors: Vec<HealthFactor>,
+}
+
+/// Cache bytes found by one scan.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CacheSample {
+    pub at: i64,
+    pub bytes: u64,
+}
+
+/// What the score is computed from, gathered by the caller over one window.
+#[derive(Debug, Clone, Default)]
+pub struct HealthInputs {
+    pub disk_free_percent: Option<f64>,
+    /// Memory pressure samples, oldest first.
+    pub memory_pressure: Vec<f32>,
+    pub login_items: Option<usize>,
+    /// Oldest first.
+    pub cache_samples: Vec<CacheSample>,
+    pub operations: OperationTally,
+    /// Newest first.
+    pub recent_failures: Vec<FailedOperation>,
+}
+
+/// Total size of the cache categories among scanned files.
+pub fn cache_size<'a>(files: impl IntoIterator<Item = &'a CleanableFile>) -> u64 {
+    files
+        .into_iter()
+        .filter(|file| file.category.contains("Cache"))
+        .map(|file| file.size)
+        .sum()
+}
+
+pub fn compute_health_score(inputs: &HealthInputs) -> HealthScore {
+    let factors: Vec<HealthFactor> = [
+        disk_factor(inputs),
+        memory_factor(inputs),
+        login_items_factor(inputs),
+        cache_growth_factor(inputs),
+        failures_factor(inputs),
+    ]
+    .into_iter()
+    .flatten()
+    .collect();
+    let weights: u32 = factors.iter().map(|factor| factor.weight as u32).sum();
+    let score = if weights == 0 {
+        100
+    } else {
+        let weighted: u32 = factors
+            .iter()
+            .map(|factor| factor.score as u32 * factor.weight as u32)
+            .sum();
+        ((weighted as f64 / weights as f64).round() as u8).min(100)
+    };
+    let grade = if score >= GOOD_SCORE {
+        HealthGrade::Good
+    } else if score >= FAIR_SCORE {
+        HealthGrade::Fair
+    } else {
+        HealthGrade::Poor
+    };
+    HealthScore {
+        score,
+        grade,
+        factors,
+    }
+}
+
+/// 100 at or below `good`, 0 at or beyond `bad`.
+fn scale(value: f64, (good, bad): (f64, f64)) -> f64 {
+    let position = (value - good) / (bad - good);
+    100.0 * (1.0 - position.clamp(0.0, 1.0))
+}
+
+fn factor(kind: HealthFactorKind, score: f64, weight: u8, detail: String) -> HealthFactor {
+    HealthFactor {
+        kind,
+        score: score.round().clamp(0.0, 100.0) as u8,
+        weight,
+        detail,
+    }
+}
+
+fn disk_factor(inputs: &HealthInputs) -> Option<HealthFactor> {
+    let free = inputs.disk_free_percent?;
+    Some(factor(
+        HealthFactorKind::DiskSpace,
+        scale(free, DISK_FREE_PERCENT),
+        DISK_WEIGHT,
+        format!("{:.0}% of the system volume is free", free),
+    ))
+}
+
+fn memory_factor(inputs: &HealthInputs) -> Option<HealthFactor> {
+    let samples = &inputs.memory_pressure;
+    let average = mean(samples)?;
+    let trend = pressure_trend(samples).unwrap_or(0.0);
+    let penalty = (trend * RISING_PENALTY_PER_POINT).clamp(0.0, MAX_RISING_PENALTY);
+    let mut detail = format!("Memory pressure averaged {:.0}%", average);
+    if trend >= 1.0 {
+        detail.push_str(&format!(", rising by {:.0} points", trend));
+    }
+    Some(factor(
+        HealthFactorKind::MemoryPressure,
+        scale(average, MEMORY_PRESSURE_PERCENT) - penalty,
+        MEMORY_WEIGHT,
+        detail,
+    ))
+}
+
+fn login_items_factor(inputs: &HealthInputs) -> Option<HealthFactor> {
+    let count = inputs.login_items?;
+    Some(factor(
+        HealthFactorKind::LoginItems,
+        scale(count as f64, LOGIN_ITEMS),
+        LOGIN_ITEMS_WEIGHT,
+        format!(
+            "{} login items start with the Mac",
+            format_count(count as u64)
+        ),
+    ))
+}
+
+fn cache_growth_factor(inputs: &HealthInputs) -> Option<HealthFactor> {
+    let per_day = cache_growth_per_day(&inputs.cache_samples)?;
+    let detail = if per_day > 0 {
+        format!("Caches grow by {} a day", format_size(per_day as u64))
+    } else {
+        "Caches are not growing".to_string()
+    };
+    Some(factor(
+        HealthFactorKind::CacheGrowth,
+        scale(per_day as f64, CACHE_GROWTH_PER_DAY),
+        CACHE_GROWTH_WEIGHT,
+        detail,
+    ))
+}
+
+fn failures_factor(inputs: &HealthInputs) -> Option<HealthFactor> {
+    let tally = inputs.operations;
+    let finished = tally.completed + tally.failed;
+    let (score, detail) = if finished == 0 {
+        (100.0, "No operations finished".to_string())
+    } else {
+        (
+            100.0 * (1.0 - tally.failed as f64 / finished as f64),
+            format!(
+                "{} of {} operations failed",
+                format_count(tally.failed as u64),
+                format_count(finished as u64)
+            ),
+        )
+    };
+    Some(factor(
+        HealthFactorKind::FailedOperations,
+        score,
+        FAILURES_WEIGHT,
+        detail,
+    ))
+}
+
+fn mean(samples: &[f32]) -> Option<f64> {
+    if samples.is_empty() {
+        return None;
+    }
+    Some(samples.iter().map(|&value| value as f64).sum::<f64>() / samples.len() as f64)
+}
+
+/// How many points the second half of the samples averages above the first.
+fn pressure_trend(samples: &[f32]) -> Option<f64> {
+    if samples.len() < 2 {
+        return None;
+    }
+    let (earlier, later) = samples.split_at(samples.len() / 2);
+    Some(mean(later)? - mean(earlier)?)
+}
+
+/// Bytes a day between the oldest and newest sample; `None` until they are
+/// far enough apart to tell.
+pub fn cache_growth_per_day(samples: &[CacheSample]) -> Option<i64> {
+    let (first, last) = (samples.first()?, samples.last()?);
+    let span = last.at - first.at;
+    if span < MIN_GROWTH_SPAN_SECS {
+        return None;
+    }
+    let growth = last.bytes as i128 - first.bytes as i128;
+    Some((growth * DAY_SECS as i128 / span as i128) as i64)
+}
+
+/// Operations that reached an end state in a window, dashboard refreshes
+/// aside.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OperationTally {
+    pub completed: usize,
+    pub failed: usize,
+    pub canceled: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FailedOperation {
+    /// Label of the operation kind, e.g. "Cleanup".
+    pub operation: String,
+    /// Unix milliseconds.
+    pub started_at_ms: i64,
+    pub error: Option<String>,
+}
+
+/// The week in one structure for the UI: health, what was cleaned, what
+/// went wrong and how the machine trended.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WeeklySummary {
+    pub from: i64,
+    pub to: i64,
+    pub health: HealthScore,
+    pub activity: WeeklyDigest,
+    pub operations: OperationTally,
+    /// Newest first.
+    pub recent_failures: Vec<FailedOperation>,
+    pub memory_pressure_average: Option<f32>,
+    pub memory_pressure_peak: Option<f32>,
+    pub disk_free_percent: Option<f64>,
+    pub cache_growth_per_day: Option<i64>,
+    /// Short sentences on what stood out, most important first.
+    pub highlights: Vec<String>,
+}
+
+/// Aggregate the week ending at `now`; `inputs` should cover the same week.
+pub fn generate_weekly_summary(
+    now: i64,
+    inputs: &HealthInputs,
+    activity: WeeklyDigest,
+) -> WeeklySummary {
+    let health = compute_health_score(inputs);
+    let operations = inputs.operations;
+    let memory_pressure_peak = inputs.memory_pressure.iter().copied().reduce(f32::max);
+
+    let mut highlights = vec![activity.headline.clone()];
+    for factor in &health.factors {
+        if factor.score < FAIR_SCORE {
+            highlights.push(factor.detail.clone());
+        }
+    }
+    if operations.failed > 0 {
+        highlights.push(format!(
+            "{} operation{} failed",
+            format_count(operations.failed as u64),
+            if operations.failed == 1 { "" } else { "s" }
+        ));
+    }
+
+    WeeklySummary {
+        from: now - WEEK_SECS,
+        to: now,
+        health,
+        activity,
+        operations,
+        recent_failures: inputs
+            .recent_failures
+            .iter()
+            .take(MAX_LISTED_FAILURES)
+            .cloned()
+            .collect(),
+        memory_pressure_average: mean(&inputs.memory_pressure).map(|value| value as f32),
+        memory_pressure_peak,
+        disk_free_percent: inputs.disk_free_percent,
+        cache_growth_per_day: cache_growth_per_day(&inputs.cache_samples),
+        highlights,
+    }
+}
+
+/// Cache size after each scan, oldest first.
+pub struct CacheSizeLog {
+    path: Option<PathBuf>,
+    samples: Vec<CacheSample>,
+}
+
+impl CacheSizeLog {
+    /// Load from `<data dir>/macos-optimizer/cache_sizes.json`.
+    pub fn load() -> Self {
+        match persistence::data_file(CACHE_SIZES_FILE) {
+            Some(path) => Self::load_from(&path),
+            None => Self {
+                path: None,
+                samples: Vec::new(),
+            },
+        }
+    }
+
+    /// Load from an explicit file; a missing or unreadable file yields an empty log.
+    pub fn load_from(path: &Path) -> Self {
+        let samples = persistence::read_json(path).unwrap_or_default();
+        Self {
+            path: Some(path.to_path_buf()),
+            samples,
+        }
+    }
+
+    pub fn record(&mut self, sample: CacheSample) -> Result<(), String> {
+        let cutoff = sample.at - CACHE_RETENTION_SECS;
+        self.samples.retain(|existing| existing.at >= cutoff);
+        self.samples.push(sample);
+        self.persist()
+    }
+
+    /// Samples taken after `from`.
+    pub fn since(&self, from: i64) -> Vec<CacheSample> {
+        self.samples
+            .iter()
+            .filter(|sample| sample.at > from)
+            .copied()
+            .collect()
+    }
+
+    fn persist(&self) -> Result<(), String> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        persistence::write_json(path, &self.samples)
+    }
+}
@@ -0,0 +1,184 @@
+use super::{
+    cache_growth_per_day, compute_health_score, generate_weekly_summary, CacheSample, CacheSizeLog,
+    FailedOperation, HealthFactorKind, HealthGrade, HealthInputs, OperationTally, WEEK_SECS,
+};
+use crate::session::SessionStore;
+use tempfile::TempDir;
+
+const GB: u64 = 1_000_000_000;
+
+fn factor_score(inputs: &HealthInputs, kind: HealthFactorKind) -> Option<u8> {
+    compute_health_score(inputs)
+        .factors
+        .iter()
+        .find(|factor| factor.kind == kind)
+        .map(|factor| factor.score)
+}
+
+#[test]
+fn healthy_machine_scores_high_and_missing_data_is_left_out() {
+    let healthy = HealthInputs {
+        disk_free_percent: Some(40.0),
+        memory_pressure: vec![30.0; 10],
+        login_items: Some(6),
+        cache_samples: vec![
+            CacheSample { at: 0, bytes: GB },
+            CacheSample {
+                at: WEEK_SECS,
+                bytes: GB,
+            },
+        ],
+        operations: OperationTally {
+            completed: 12,
+            ..Default::default()
+        },
+        recent_failures: vec![],
+    };
+    let score = compute_health_score(&healthy);
+    assert_eq!(score.score, 100);
+    assert_eq!(score.grade, HealthGrade::Good);
+    assert_eq!(score.factors.len(), 5);
+
+    let disk_only = HealthInputs {
+        disk_free_percent: Some(15.0),
+        ..Default::default()
+    };
+    let score = compute_health_score(&disk_only);
+    let kinds: Vec<_> = score.factors.iter().map(|factor| factor.kind).collect();
+    assert_eq!(
+        kinds,
+        vec![
+            HealthFactorKind::DiskSpace,
+            HealthFactorKind::FailedOperations
+        ]
+    );
+    // 50 for disk (weight 30) and 100 for operations (weight 15).
+    assert_eq!(score.score, 67);
+    assert_eq!(score.grade, HealthGrade::Fair);
+}
+
+#[test]
+fn rising_memory_pressure_costs_more_than_a_steady_one() {
+    let steady = HealthInputs {
+        memory_pressure: vec![60.0; 8],
+        ..Default::default()
+    };
+    let rising = HealthInputs {
+        memory_pressure: vec![50.0, 50.0, 50.0, 50.0, 70.0, 70.0, 70.0, 70.0],
+        ..Default::default()
+    };
+    assert_eq!(
+        factor_score(&steady, HealthFactorKind::MemoryPressure),
+        Some(75)
+    );
+    assert_eq!(
+        factor_score(&rising, HealthFactorKind::MemoryPressure),
+        Some(55)
+    );
+    let detail = &compute_health_score(&rising).factors[0].detail;
+    assert_eq!(detail, "Memory pressure averaged 60%, rising by 20 points");
+}
+
+#[test]
+fn cache_growth_needs_samples_half_a_day_apart() {
+    let day = 24 * 60 * 60;
+    assert_eq!(cache_growth_per_day(&[]), None);
+    assert_eq!(
+        cache_growth_per_day(&[
+            CacheSample { at: 0, bytes: GB },
+            CacheSample {
+                at: 3_600,
+                bytes: 2 * GB
+            },
+        ]),
+        None
+    );
+    assert_eq!(
+        cache_growth_per_day(&[
+            CacheSample { at: 0, bytes: GB },
+            CacheSample {
+                at: 2 * day,
+                bytes: 3 * GB
+            },
+        ]),
+        Some(GB as i64)
+    );
+    assert_eq!(
+        cache_growth_per_day(&[
+            CacheSample {
+                at: 0,
+                bytes: 3 * GB
+            },
+            CacheSample { at: day, bytes: GB },
+        ]),
+        Some(-2 * GB as i64)
+    );
+}
+
+#[test]
+fn cache_size_log_persists_and_filters_by_time() {
+    let temp_dir = TempDir::new().unwrap();
+    let path = temp_dir.path().join("cache_sizes.json");
+    let now = 20 * WEEK_SECS;
+
+    let mut log = CacheSizeLog::load_from(&path);
+    log.record(CacheSample {
+        at: now - 10 * WEEK_SECS,
+        bytes: 1,
+    })
+    .unwrap();
+    log.record(CacheSample {
+        at: now - 2 * WEEK_SECS,
+        bytes: 2,
+    })
+    .unwrap();
+    log.record(CacheSample { at: now, bytes: 3 }).unwrap();
+
+    let reloaded = CacheSizeLog::load_from(&path);
+    assert_eq!(reloaded.since(0).len(), 2);
+    assert_eq!(
+        reloaded.since(now - WEEK_SECS),
+        vec![CacheSample { at: now, bytes: 3 }]
+    );
+}
+
+#[test]
+fn weekly_summary_collects_activity_failures_and_trends() {
+    let temp_dir = TempDir::new().unwrap();
+    let now = 10 * WEEK_SECS;
+    let activity =
+        SessionStore::load_from(&temp_dir.path().join("sessions.json")).weekly_digest(now, None);
+    let inputs = HealthInputs {
+        disk_free_percent: Some(4.0),
+        memory_pressure: vec![60.0, 100.0],
+        login_items: Some(3),
+        cache_samples: vec![],
+        operations: OperationTally {
+            completed: 3,
+            failed: 1,
+            canceled: 2,
+        },
+        recent_failures: vec![FailedOperation {
+            operation: "Cleanup".to_string(),
+            started_at_ms: (now - 60) * 1000,
+            error: Some("Permission denied".to_string()),
+        }],
+    };
+
+    let summary = generate_weekly_summary(now, &inputs, activity);
+    assert_eq!(summary.from, now - WEEK_SECS);
+    assert_eq!(summary.operations.failed, 1);
+    assert_eq!(summary.recent_failures.len(), 1);
+    assert_eq!(summary.memory_pressure_average, Some(80.0));
+    assert_eq!(summary.memory_pressure_peak, Some(100.0));
+    assert_eq!(summary.cache_growth_per_day, None);
+    assert_eq!(
+        summary.highlights,
+        vec![
+            "No cleanups this week".to_string(),
+            "4% of the system volume is free".to_string(),
+            "Memory pressure averaged 80%, rising by 40 points".to_string(),
+            "1 operation failed".to_string(),
+        ]
+    );
+}
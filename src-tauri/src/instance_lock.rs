@@ -0,0 +1,171 @@
+// src/instance_lock.rs
+
+//! Cross-process guard for destructive operations.
+//!
+//! Two app instances, or the app and a command-line companion, must not clean
+//! the same files at once. Each destructive operation takes an exclusive
+//! `flock` on a well-known file in the data directory for its duration; the
+//! holder writes its pid and operation into the file so a contender can say
+//! who is busy. The kernel drops the lock when the holder exits, so a crashed
+//! instance never leaves a stale lock behind.
+//!
+//! `flock` belongs to an open file, not a process, so a second open in the
+//! same process would be refused as if another instance held it. The file
+//! is therefore opened and locked once per process and shared by every
+//! operation of this instance, then unlocked when the last one finishes.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::os::unix::io::AsRawFd;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use chrono::Utc;
+use lazy_static::lazy_static;
+use serde::{Deserialize, Serialize};
+
+use crate::persistence;
+
+#[cfg(test)]
+mod tests;
+
+const LOCK_FILE: &str = "destructive.lock";
+
+lazy_static! {
+    /// Lock files this process holds, with how many operations hold each.
+    static ref HELD: Mutex<HashMap<PathBuf, (File, usize)>> = Mutex::new(HashMap::new());
+}
+
+/// Who holds the lock, as recorded in the lock file.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LockHolder {
+    pub pid: u32,
+    pub operation: String,
+    pub since: i64,
+}
+
+#[derive(Debug)]
+pub enum InstanceLockError {
+    /// Another process is running a destructive operation.
+    Busy(Option<LockHolder>),
+    Io(PathBuf, io::Error),
+}
+
+impl fmt::Display for InstanceLockError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InstanceLockError::Busy(Some(holder)) => write!(
+                f,
+                "Another instance is cleaning ({} in process {}); try again when it finishes",
+                holder.operation, holder.pid
+            ),
+            InstanceLockError::Busy(None) => {
+                f.write_str("Another instance is cleaning; try again when it finishes")
+            }
+            InstanceLockError::Io(path, err) => {
+                write!(f, "Failed to lock {}: {}", path.display(), err)
+            }
+        }
+    }
+}
+
+impl std::error::Error for InstanceLockError {}
+
+impl From<InstanceLockError> for String {
+    fn from(err: InstanceLockError) -> Self {
+        err.to_string()
+    }
+}
+
+/// Held for the duration of a destructive operation; dropping the last one
+/// in this process unlocks.
+#[derive(Debug)]
+pub struct DestructiveLock {
+    path: PathBuf,
+}
+
+impl Drop for DestructiveLock {
+    fn drop(&mut self) {
+        let mut held = HELD.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        let Some((_, holders)) = held.get_mut(&self.path) else {
+            return;
+        };
+        *holders -= 1;
+        if *holders > 0 {
+            return;
+        }
+        if let Some((file, _)) = held.remove(&self.path) {
+            // Clear the holder record first so a contender never reads a
+            // stale one.
+            let _ = file.set_len(0);
+            // SAFETY: the descriptor is owned by `file` and still open.
+            unsafe {
+                libc::flock(file.as_raw_fd(), libc::LOCK_UN);
+            }
+        }
+    }
+}
+
+/// Take the lock in `<data dir>/macos-optimizer/`, failing fast when another
+/// process holds it.
+pub fn acquire_destructive_lock(operation: &str) -> Result<DestructiveLock, InstanceLockError> {
+    let dir =
+        persistence::data_dir().unwrap_or_else(|| std::env::temp_dir().join("macos-optimizer"));
+    acquire_at(&dir.join(LOCK_FILE), operation)
+}
+
+/// Take the lock at `path`; operations of this process share it.
+pub fn acquire_at(path: &Path, operation: &str) -> Result<DestructiveLock, InstanceLockError> {
+    let io_err = |err| InstanceLockError::Io(path.to_path_buf(), err);
+    let mut held = HELD.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    if let Some((_, holders)) = held.get_mut(path) {
+        *holders += 1;
+        return Ok(DestructiveLock {
+            path: path.to_path_buf(),
+        });
+    }
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(io_err)?;
+    }
+    let mut file = OpenOptions::new()
+        .read(true)
+        .write(true)
+        .create(true)
+        .truncate(false)
+        .open(path)
+        .map_err(io_err)?;
+
+    // SAFETY: the descriptor belongs to `file`, which outlives the call.
+    let locked = unsafe { libc::flock(file.as_raw_fd(), libc::LOCK_EX | libc::LOCK_NB) };
+    if locked != 0 {
+        let err = io::Error::last_os_error();
+        if err.raw_os_error() == Some(libc::EWOULDBLOCK) {
+            return Err(InstanceLockError::Busy(read_holder(&mut file)));
+        }
+        return Err(io_err(err));
+    }
+
+    let holder = LockHolder {
+        pid: std::process::id(),
+        operation: operation.to_string(),
+        since: Utc::now().timestamp(),
+    };
+    let record = serde_json::to_vec(&holder).unwrap_or_default();
+    file.set_len(0)
+        .and_then(|_| file.seek(SeekFrom::Start(0)))
+        .and_then(|_| file.write_all(&record))
+        .map_err(io_err)?;
+    held.insert(path.to_path_buf(), (file, 1));
+    Ok(DestructiveLock {
+        path: path.to_path_buf(),
+    })
+}
+
+fn read_holder(file: &mut File) -> Option<LockHolder> {
+    let mut data = Vec::new();
+    file.seek(SeekFrom::Start(0)).ok()?;
+    file.read_to_end(&mut data).ok()?;
+    serde_json::from_slice(&data).ok()
+}
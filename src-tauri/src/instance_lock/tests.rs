@@ -0,0 +1,58 @@
+use super::{acquire_at, InstanceLockError};
+use std::path::Path;
+use std::process::Command;
+use tempfile::TempDir;
+
+/// Lock file a child test process should try to take.
+const CHILD_LOCK_ENV: &str = "MACOS_OPTIMIZER_TEST_CHILD_LOCK";
+
+/// Run in a child process by the test below: exits successfully only when
+/// the lock is refused as held by the parent's `empty_trash`.
+#[test]
+fn child_process_contends_for_the_lock() {
+    let Ok(path) = std::env::var(CHILD_LOCK_ENV) else {
+        return;
+    };
+    match acquire_at(Path::new(&path), "clean_files") {
+        Err(InstanceLockError::Busy(Some(holder))) => {
+            assert_eq!(holder.pid, std::os::unix::process::parent_id());
+            assert_eq!(holder.operation, "empty_trash");
+            let message = String::from(InstanceLockError::Busy(Some(holder)));
+            assert!(message.starts_with("Another instance is cleaning (empty_trash"));
+        }
+        other => panic!("expected Busy, got {:?}", other),
+    }
+}
+
+fn child_takes_lock(path: &Path) -> bool {
+    Command::new(std::env::current_exe().unwrap())
+        .args([
+            "--exact",
+            "instance_lock::tests::child_process_contends_for_the_lock",
+            "--test-threads=1",
+        ])
+        .env(CHILD_LOCK_ENV, path)
+        .output()
+        .unwrap()
+        .status
+        .success()
+}
+
+#[test]
+fn other_processes_are_refused_while_this_one_shares_the_lock() {
+    let temp_dir = TempDir::new().unwrap();
+    let path = temp_dir.path().join("locks").join("destructive.lock");
+
+    let first = acquire_at(&path, "empty_trash").unwrap();
+    // Operations of this process are not held up by each other.
+    let second = acquire_at(&path, "clean_files").unwrap();
+    assert!(child_takes_lock(&path));
+
+    // Still held by the operation that remains.
+    drop(first);
+    assert!(child_takes_lock(&path));
+
+    drop(second);
+    assert!(!child_takes_lock(&path));
+    assert!(acquire_at(&path, "clean_files").is_ok());
+}
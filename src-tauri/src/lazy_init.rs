@@ -0,0 +1,90 @@
+// src/lazy_init.rs
+
+//! Subsystems built on first use instead of at launch.
+//!
+//! A [`LazySubsystem`] runs its constructor on a blocking thread the first
+//! time it is needed, so the window can paint before expensive engines
+//! exist. Concurrent first uses wait for the same construction. Every
+//! construction is timed into the shared [`InitTimings`].
+
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+
+use serde::{Deserialize, Serialize};
+use tokio::sync::{OnceCell, RwLock};
+
+/// How long a subsystem took to construct.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InitTiming {
+    pub subsystem: String,
+    pub duration_ms: u64,
+    /// Unix seconds.
+    pub initialized_at: i64,
+}
+
+/// Constructions timed so far, in completion order.
+#[derive(Debug, Default)]
+pub struct InitTimings {
+    timings: Mutex<Vec<InitTiming>>,
+}
+
+impl InitTimings {
+    pub fn list(&self) -> Vec<InitTiming> {
+        self.timings
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .clone()
+    }
+
+    fn record(&self, timing: InitTiming) {
+        self.timings
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .push(timing);
+    }
+}
+
+/// A `RwLock<T>` whose value is constructed on first access.
+pub struct LazySubsystem<T> {
+    name: &'static str,
+    init: fn() -> T,
+    cell: OnceCell<RwLock<T>>,
+    timings: Arc<InitTimings>,
+}
+
+impl<T: Send + 'static> LazySubsystem<T> {
+    pub fn new(name: &'static str, init: fn() -> T, timings: Arc<InitTimings>) -> Self {
+        Self {
+            name,
+            init,
+            cell: OnceCell::new(),
+            timings,
+        }
+    }
+
+    /// The subsystem, constructing it first if nothing has yet.
+    pub async fn get(&self) -> &RwLock<T> {
+        self.cell
+            .get_or_init(|| async {
+                let start = Instant::now();
+                let value = match tokio::task::spawn_blocking(self.init).await {
+                    Ok(value) => value,
+                    Err(err) if err.is_panic() => std::panic::resume_unwind(err.into_panic()),
+                    // The runtime is shutting down; finish here instead.
+                    Err(_) => (self.init)(),
+                };
+                let duration_ms = start.elapsed().as_millis() as u64;
+                log::info!("Initialized {} in {} ms", self.name, duration_ms);
+                self.timings.record(InitTiming {
+                    subsystem: self.name.to_string(),
+                    duration_ms,
+                    initialized_at: chrono::Utc::now().timestamp(),
+                });
+                RwLock::new(value)
+            })
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,30 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+use super::{InitTimings, LazySubsystem};
+
+static CONSTRUCTIONS: AtomicUsize = AtomicUsize::new(0);
+
+fn construct() -> Vec<u32> {
+    CONSTRUCTIONS.fetch_add(1, Ordering::SeqCst);
+    std::thread::sleep(std::time::Duration::from_millis(20));
+    vec![1, 2, 3]
+}
+
+#[tokio::test]
+async fn subsystem_is_constructed_once_on_first_use() {
+    let timings = Arc::new(InitTimings::default());
+    let subsystem = LazySubsystem::new("numbers", construct, timings.clone());
+    assert!(timings.list().is_empty());
+
+    let (first, second) = tokio::join!(subsystem.get(), subsystem.get());
+    assert_eq!(*first.read().await, vec![1, 2, 3]);
+    second.write().await.push(4);
+    assert_eq!(subsystem.get().await.read().await.len(), 4);
+
+    assert_eq!(CONSTRUCTIONS.load(Ordering::SeqCst), 1);
+    let recorded = timings.list();
+    assert_eq!(recorded.len(), 1);
+    assert_eq!(recorded[0].subsystem, "numbers");
+    assert!(recorded[0].duration_ms >= 20);
+}
@@ -1,19 +1,73 @@
-#[cfg(feature = "app")]
+mod capabilities;
+mod cli;
+#[cfg(feature = "engine")]
 mod config;
+#[cfg(feature = "app")]
+mod container_cleaner;
+#[cfg(feature = "app")]
+mod elevation;
 mod file_cleaner;
+mod formatting;
 #[cfg(feature = "app")]
-mod memory_optimizer;
+mod health_score;
+mod instance_lock;
 #[cfg(feature = "app")]
+mod lazy_init;
+#[cfg(feature = "app")]
+mod logging;
+#[cfg(feature = "engine")]
+mod memory_optimizer;
+#[cfg(feature = "engine")]
 mod metrics;
+#[cfg(feature = "app")]
+mod network_tools;
+#[cfg(feature = "app")]
+mod notifications;
 mod ops;
+mod performance;
+#[cfg(feature = "app")]
+mod permissions;
+mod persistence;
+mod privileged_helper;
+#[cfg(feature = "app")]
+mod scheduler;
+#[cfg(feature = "app")]
+mod session;
+mod simulation;
 #[cfg(feature = "app")]
+mod startup_manager;
+#[cfg(feature = "engine")]
 mod system_info;
+mod system_maintenance;
+mod system_snapshot;
+#[cfg(feature = "app")]
+mod tray;
+#[cfg(feature = "app")]
+mod usage;
 
+pub use cli::{parse_cli_args, CliCommand, CLI_USAGE};
+#[cfg(feature = "engine")]
+pub use config::AuditModeSettings;
+pub use file_cleaner::analyzer_plugins::{
+    register_analyzer, registered_analyzers, unregister_analyzer, AnalyzerInput, AnalyzerPlugin,
+    AnalyzerVerdict, PathPatternAnalyzer,
+};
+pub use file_cleaner::app_uninstaller::{AppUninstaller, LeftoverKind};
+pub use file_cleaner::volumes::{list_volumes, VolumeInfo};
 pub use file_cleaner::{
-    CleanableFile as StorageCleanableFile, CleaningReport as StorageCleaningReport,
-    EnhancedCleaningReport, EnhancedDeletionProgress, EnhancedFileCleaner,
-    FileCleaner as StorageFileCleaner, UserAction as StorageUserAction,
+    EnhancedCleaningReport, EnhancedFileCleaner, FileCleaner as StorageFileCleaner,
+    ScanProgress as StorageScanProgress, TrashEmptyProgress,
 };
+pub use formatting::format_size;
+pub use instance_lock::acquire_destructive_lock;
+#[cfg(feature = "engine")]
+pub use memory_optimizer::MemoryOptimizer;
+#[cfg(feature = "engine")]
+pub use metrics::MemoryStats;
+pub use privileged_helper::run_privileged_helper;
+pub use simulation::{run_simulation, FixtureSpec};
+#[cfg(feature = "engine")]
+pub use system_info::{ProcessInfo, SystemInfo, SystemMonitor};
 
 #[cfg(feature = "app")]
 mod app;
@@ -0,0 +1,257 @@
+// src/logging.rs
+
+//! Structured logs in `~/Library/Logs/macos-optimizer`, one JSON object per
+//! line.
+//!
+//! [`init`] installs the logger behind the `log` macros. Each entry carries
+//! the operation it was written for: the operation registry binds every
+//! operation to the task that registers it until it finishes, and work moved
+//! to a blocking thread can take its operation along with [`in_operation`].
+//! The file rotates once it reaches [`MAX_LOG_BYTES`], keeping
+//! [`ROTATED_LOGS`] older ones, and [`recent_logs`] reads them back newest
+//! first, for one operation or for all of them.
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use lazy_static::lazy_static;
+use log::{LevelFilter, Log, Metadata, Record};
+use serde::{Deserialize, Serialize};
+
+#[cfg(test)]
+mod tests;
+
+const LOG_FILE: &str = "macos-optimizer.log";
+/// Size at which the log is rotated.
+pub const MAX_LOG_BYTES: u64 = 5 * 1024 * 1024;
+/// Rotated logs kept next to the current one, `.1` the newest.
+pub const ROTATED_LOGS: usize = 4;
+/// Overrides the level logged, e.g. `debug`; `info` otherwise.
+const LEVEL_ENV: &str = "MACOS_OPTIMIZER_LOG_LEVEL";
+
+lazy_static! {
+    /// Operations by the task that registered them.
+    static ref TASK_OPERATIONS: Mutex<HashMap<tokio::task::Id, String>> =
+        Mutex::new(HashMap::new());
+}
+
+thread_local! {
+    static THREAD_OPERATION: RefCell<Option<String>> = const { RefCell::new(None) };
+}
+
+/// One line of the log.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LogEntry {
+    /// Milliseconds since the Unix epoch.
+    pub at_ms: i64,
+    pub level: String,
+    /// Module that wrote the entry.
+    pub target: String,
+    pub message: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub operation_id: Option<String>,
+}
+
+impl LogEntry {
+    fn from_record(record: &Record) -> Self {
+        Self {
+            at_ms: chrono::Utc::now().timestamp_millis(),
+            level: record.level().to_string(),
+            target: record.target().to_string(),
+            message: record.args().to_string(),
+            operation_id: current_operation(),
+        }
+    }
+}
+
+/// `~/Library/Logs/macos-optimizer`.
+pub fn log_dir() -> Option<PathBuf> {
+    dirs::home_dir().map(|home| home.join("Library/Logs/macos-optimizer"))
+}
+
+/// Send the `log` macros to the log file. Fails if the folder cannot be
+/// created or a logger is already installed.
+pub fn init() -> Result<(), String> {
+    let dir = log_dir().ok_or("Could not find the home directory")?;
+    let level = std::env::var(LEVEL_ENV)
+        .ok()
+        .and_then(|level| level.parse().ok())
+        .unwrap_or(LevelFilter::Info);
+    let logger = JsonLogger {
+        file: LogFile::open(&dir, MAX_LOG_BYTES)?,
+        level,
+    };
+    log::set_boxed_logger(Box::new(logger)).map_err(|e| format!("Logger not installed: {}", e))?;
+    log::set_max_level(level);
+    Ok(())
+}
+
+/// Up to `limit` entries from the log folder, newest first, only those of
+/// `operation_id` when given.
+pub fn recent_logs(operation_id: Option<&str>, limit: usize) -> Result<Vec<LogEntry>, String> {
+    let dir = log_dir().ok_or("Could not find the home directory")?;
+    Ok(read_recent(&dir, operation_id, limit))
+}
+
+/// Tag what the current task logs with `operation_id` until
+/// [`unbind_operation`]; outside a Tokio task this does nothing.
+pub fn bind_operation(operation_id: &str) {
+    if let Some(task) = tokio::task::try_id() {
+        if let Ok(mut tasks) = TASK_OPERATIONS.lock() {
+            tasks.insert(task, operation_id.to_string());
+        }
+    }
+}
+
+pub fn unbind_operation(operation_id: &str) {
+    if let Ok(mut tasks) = TASK_OPERATIONS.lock() {
+        tasks.retain(|_, bound| bound != operation_id);
+    }
+}
+
+/// Run `f`, tagging what it logs on this thread with `operation_id`.
+pub fn in_operation<T>(operation_id: Option<String>, f: impl FnOnce() -> T) -> T {
+    let previous = THREAD_OPERATION.with(|current| current.replace(operation_id));
+    let result = f();
+    THREAD_OPERATION.with(|current| *current.borrow_mut() = previous);
+    result
+}
+
+/// The operation what is logged here and now belongs to, if any.
+pub fn current_operation() -> Option<String> {
+    THREAD_OPERATION
+        .with(|current| current.borrow().clone())
+        .or_else(|| {
+            let task = tokio::task::try_id()?;
+            TASK_OPERATIONS.lock().ok()?.get(&task).cloned()
+        })
+}
+
+struct JsonLogger {
+    file: LogFile,
+    level: LevelFilter,
+}
+
+impl Log for JsonLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= self.level
+    }
+
+    fn log(&self, record: &Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+        let entry = LogEntry::from_record(record);
+        if cfg!(debug_assertions) {
+            eprintln!("[{}] {}: {}", entry.level, entry.target, entry.message);
+        }
+        // Nowhere left to report a failure to write the log.
+        let _ = self.file.append(&entry);
+    }
+
+    fn flush(&self) {
+        if let Ok(mut writer) = self.file.writer.lock() {
+            if let Some((file, _)) = writer.as_mut() {
+                let _ = file.flush();
+            }
+        }
+    }
+}
+
+/// The current log file, rotated as it fills up.
+pub(crate) struct LogFile {
+    dir: PathBuf,
+    max_bytes: u64,
+    /// Open file and its size; reopened after rotating.
+    writer: Mutex<Option<(File, u64)>>,
+}
+
+impl LogFile {
+    pub(crate) fn open(dir: &Path, max_bytes: u64) -> Result<Self, String> {
+        fs::create_dir_all(dir)
+            .map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+        Ok(Self {
+            dir: dir.to_path_buf(),
+            max_bytes,
+            writer: Mutex::new(None),
+        })
+    }
+
+    pub(crate) fn append(&self, entry: &LogEntry) -> Result<(), String> {
+        let mut line =
+            serde_json::to_vec(entry).map_err(|e| format!("Failed to serialize log: {}", e))?;
+        line.push(b'\n');
+        let mut writer = self
+            .writer
+            .lock()
+            .map_err(|_| "Log file lock is poisoned".to_string())?;
+        if writer
+            .as_ref()
+            .is_some_and(|(_, size)| *size + line.len() as u64 > self.max_bytes)
+        {
+            *writer = None;
+            self.rotate();
+        }
+        if writer.is_none() {
+            let path = self.dir.join(LOG_FILE);
+            let file = OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(&path)
+                .map_err(|e| format!("Failed to open {}: {}", path.display(), e))?;
+            let size = file.metadata().map(|meta| meta.len()).unwrap_or(0);
+            *writer = Some((file, size));
+        }
+        let Some((file, size)) = writer.as_mut() else {
+            return Ok(());
+        };
+        file.write_all(&line)
+            .map_err(|e| format!("Failed to write log: {}", e))?;
+        *size += line.len() as u64;
+        Ok(())
+    }
+
+    /// Shift `.1` to `.2` and so on, dropping the oldest, and the current
+    /// file to `.1`.
+    fn rotate(&self) {
+        for index in (1..ROTATED_LOGS).rev() {
+            let _ = fs::rename(log_path(&self.dir, index), log_path(&self.dir, index + 1));
+        }
+        let _ = fs::rename(log_path(&self.dir, 0), log_path(&self.dir, 1));
+    }
+}
+
+/// The current log for `index` 0, rotated ones after it.
+fn log_path(dir: &Path, index: usize) -> PathBuf {
+    match index {
+        0 => dir.join(LOG_FILE),
+        index => dir.join(format!("{}.{}", LOG_FILE, index)),
+    }
+}
+
+/// Entries of the logs in `dir`, newest first; lines that do not parse,
+/// such as one cut off by a crash, are skipped.
+pub(crate) fn read_recent(dir: &Path, operation_id: Option<&str>, limit: usize) -> Vec<LogEntry> {
+    let mut recent = Vec::new();
+    for index in 0..=ROTATED_LOGS {
+        let Ok(file) = File::open(log_path(dir, index)) else {
+            continue;
+        };
+        let mut entries: Vec<LogEntry> = BufReader::new(file)
+            .lines()
+            .map_while(Result::ok)
+            .filter_map(|line| serde_json::from_str::<LogEntry>(&line).ok())
+            .filter(|entry| operation_id.is_none_or(|id| entry.operation_id.as_deref() == Some(id)))
+            .collect();
+        entries.reverse();
+        recent.extend(entries.into_iter().take(limit - recent.len()));
+        if recent.len() >= limit {
+            break;
+        }
+    }
+    recent
+}
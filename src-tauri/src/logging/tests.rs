@@ -0,0 +1,86 @@
+use super::{
+    bind_operation, current_operation, in_operation, log_path, read_recent, unbind_operation,
+    LogEntry, LogFile, ROTATED_LOGS,
+};
+use std::fs;
+use tempfile::TempDir;
+
+fn entry(at_ms: i64, operation_id: Option<&str>) -> LogEntry {
+    LogEntry {
+        at_ms,
+        level: "WARN".into(),
+        target: "macos_optimizer_lib::file_cleaner".into(),
+        message: format!("Admin removal failed: attempt {}", at_ms),
+        operation_id: operation_id.map(str::to_string),
+    }
+}
+
+#[test]
+fn logs_rotate_and_read_back_newest_first_per_operation() {
+    let dir = TempDir::new().unwrap();
+    let line_len = serde_json::to_vec(&entry(10, Some("clean-1")))
+        .unwrap()
+        .len() as u64
+        + 1;
+    // Three lines per file.
+    let file = LogFile::open(dir.path(), line_len * 3).unwrap();
+    for at in 10..40 {
+        let operation = if at % 2 == 0 { "clean-1" } else { "scan-2" };
+        file.append(&entry(at, Some(operation))).unwrap();
+    }
+
+    assert!(log_path(dir.path(), ROTATED_LOGS).exists());
+    assert!(!log_path(dir.path(), ROTATED_LOGS + 1).exists());
+    let kept = (0..=ROTATED_LOGS)
+        .map(|index| fs::read_to_string(log_path(dir.path(), index)).unwrap())
+        .map(|log| log.lines().count())
+        .sum::<usize>();
+    assert_eq!(kept, 3 * ROTATED_LOGS + 3);
+
+    let all: Vec<i64> = read_recent(dir.path(), None, 5)
+        .iter()
+        .map(|entry| entry.at_ms)
+        .collect();
+    assert_eq!(all, vec![39, 38, 37, 36, 35]);
+    let clean: Vec<i64> = read_recent(dir.path(), Some("clean-1"), 100)
+        .iter()
+        .map(|entry| entry.at_ms)
+        .collect();
+    assert_eq!(clean.first(), Some(&38));
+    assert!(clean.windows(2).all(|pair| pair[0] > pair[1]));
+    assert!(read_recent(dir.path(), Some("unknown"), 10).is_empty());
+
+    // A line cut off mid-write is skipped.
+    let current = log_path(dir.path(), 0);
+    let mut log = fs::read_to_string(&current).unwrap();
+    log.push_str("{\"at_ms\":40,\"lev");
+    fs::write(&current, log).unwrap();
+    assert_eq!(read_recent(dir.path(), None, 1)[0].at_ms, 39);
+}
+
+#[tokio::test]
+async fn entries_are_tagged_with_the_operation_of_their_task_or_thread() {
+    // Commands run as spawned tasks; `block_on` itself has no task id.
+    tokio::spawn(async {
+        assert_eq!(current_operation(), None);
+        bind_operation("op-1");
+        assert_eq!(current_operation().as_deref(), Some("op-1"));
+        let other_task = tokio::spawn(async { current_operation() }).await.unwrap();
+        assert_eq!(other_task, None);
+
+        let blocking =
+            tokio::task::spawn_blocking(|| in_operation(Some("op-1".into()), current_operation))
+                .await
+                .unwrap();
+        assert_eq!(blocking.as_deref(), Some("op-1"));
+        assert_eq!(
+            in_operation(Some("op-2".into()), current_operation).as_deref(),
+            Some("op-2")
+        );
+
+        unbind_operation("op-1");
+        assert_eq!(current_operation(), None);
+    })
+    .await
+    .unwrap();
+}
@@ -6,22 +6,48 @@ use serde::{Deserialize, Serialize};
 // Internal modules backing this facade.
 // Keep this file as the stable entry point that others import.
 mod admin;
+mod blockers;
 mod non_admin;
+mod plan;
 mod stats;
 mod utils;
+mod verification;
+#[cfg(feature = "app")]
+mod watchdog;
 use tokio_util::sync::CancellationToken;
 
+use crate::formatting;
 use crate::metrics::MemoryStats;
 
+pub use blockers::BlockingCondition;
+pub use plan::{MemoryOptimizationPlan, MemoryPlanResult, MemoryStrategy};
+pub use verification::{MemoryDiff, MemoryOptimizedEvent, StrategyMeasurement};
+#[cfg(feature = "app")]
+pub use watchdog::{MemoryWatchdog, PressureLevel, WatchdogConfig, WatchdogStatus};
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct MemoryOptimizationResult {
     pub memory_before: MemoryStats,
     pub memory_after: MemoryStats,
+    /// Change in available memory over the whole optimization, as measured;
+    /// negative when other processes took more than was freed.
     pub freed_memory: i64,
+    /// What the steps were expected to free, at most.
+    #[serde(default)]
+    pub freed_estimate: u64,
+    /// Memory sampled around each step, in the order they ran.
+    #[serde(default)]
+    pub strategies: Vec<StrategyMeasurement>,
     pub optimization_type: String,
     pub success: bool,
     pub message: String,
     pub optimizations_performed: Vec<String>,
+    /// Set when inspector mode reported what would happen instead of doing it.
+    #[serde(default)]
+    pub dry_run: bool,
+    /// Why the deep clean was deferred, or would be in inspector mode.
+    #[serde(default)]
+    pub blocked_by: Vec<BlockingCondition>,
 }
 
 pub struct MemoryOptimizer;
@@ -41,23 +67,61 @@ impl MemoryOptimizer {
         cancel: &CancellationToken,
     ) -> Result<MemoryOptimizationResult, String> {
         let memory_before = Self::get_memory_stats()?;
-        let (
-            inactive_result,
-            cache_result,
-            app_cache_result,
-            compression_result,
-            network_result,
-            gc_result,
-            temp_result,
-        ) = tokio::join!(
+        let app_cache_bytes = tokio::task::spawn_blocking(non_admin::app_cache_size)
+            .await
+            .unwrap_or(0);
+        // One step at a time, so each one's effect can be told apart.
+        let mut strategies = Vec::new();
+        let (inactive_result, measured) = verification::measure(
+            "Clear inactive memory",
+            plan::estimate(&memory_before, MemoryStrategy::InactiveMemory, 0),
             non_admin::clear_inactive_memory_adaptive_with_cancel(Some(cancel)),
+        )
+        .await?;
+        strategies.push(measured);
+        let (cache_result, measured) = verification::measure(
+            "Optimize file system caches",
+            0,
             non_admin::optimize_file_caches(),
+        )
+        .await?;
+        strategies.push(measured);
+        let (app_cache_result, measured) = verification::measure(
+            "Clear application caches",
+            plan::estimate(
+                &memory_before,
+                MemoryStrategy::AppCacheTrim,
+                app_cache_bytes,
+            ),
             non_admin::clear_app_caches(),
+        )
+        .await?;
+        strategies.push(measured);
+        let (compression_result, measured) = verification::measure(
+            "Optimize memory compression",
+            0,
             non_admin::optimize_memory_compression(),
+        )
+        .await?;
+        strategies.push(measured);
+        let (network_result, measured) = verification::measure(
+            "Clear network caches",
+            0,
             non_admin::clear_network_caches_safe(),
-            non_admin::trigger_app_gc(),
-            non_admin::clear_temp_allocations()
-        );
+        )
+        .await?;
+        strategies.push(measured);
+        let (gc_result, measured) =
+            verification::measure("Trigger garbage collection", 0, non_admin::trigger_app_gc())
+                .await?;
+        strategies.push(measured);
+        let (temp_result, measured) = verification::measure(
+            "Clear temporary allocations",
+            0,
+            non_admin::clear_temp_allocations(),
+        )
+        .await?;
+        strategies.push(measured);
         if cancel.is_cancelled() {
             return Err("cancelled".into());
         }
@@ -67,12 +131,12 @@ impl MemoryOptimizer {
         if let Ok(freed) = inactive_result {
             if freed > 0 {
                 optimizations_performed.push(format!(
-                    "Cleared {} MB of inactive memory",
-                    freed / (1024 * 1024)
+                    "Cleared {} of inactive memory",
+                    formatting::format_size(freed)
                 ));
                 message.push_str(&format!(
-                    "Freed {} MB from inactive memory\n",
-                    freed / (1024 * 1024)
+                    "Freed {} from inactive memory\n",
+                    formatting::format_size(freed)
                 ));
             }
         }
@@ -113,11 +177,15 @@ impl MemoryOptimizer {
         Ok(MemoryOptimizationResult {
             memory_before,
             memory_after,
-            freed_memory: freed_memory.abs(),
-            optimization_type: "Parallel Optimization Mode".to_string(),
+            freed_memory,
+            freed_estimate: strategies.iter().map(|s| s.freed_estimate).sum(),
+            strategies,
+            optimization_type: "Standard Optimization Mode".to_string(),
             success,
             message: message.trim().to_string(),
             optimizations_performed,
+            dry_run: false,
+            blocked_by: Vec::new(),
         })
     }
 
@@ -125,12 +193,24 @@ impl MemoryOptimizer {
         &self,
         cancel: &CancellationToken,
     ) -> Result<MemoryOptimizationResult, String> {
+        let blocked_by = blockers::detect_blocking_conditions().await;
+        if !blocked_by.is_empty() {
+            return self.defer_deep_clean(blocked_by, cancel).await;
+        }
         let memory_before = Self::get_memory_stats()?;
         let mut success = true;
         let mut message = String::new();
         let mut optimizations_performed = Vec::new();
 
-        let outcome = admin::run_deep_clean_with_cancel(cancel).await;
+        // The script runs as one step; of what it does, only the purge frees
+        // memory worth estimating.
+        let (outcome, measured) = verification::measure(
+            "Deep clean (admin)",
+            plan::estimate(&memory_before, MemoryStrategy::Purge, 0),
+            admin::run_deep_clean_with_cancel(cancel),
+        )
+        .await?;
+        let mut strategies = vec![measured];
         if outcome.success {
             let stdout = outcome.stdout;
             let mapping = vec![
@@ -161,6 +241,7 @@ impl MemoryOptimizer {
             if !cancel.is_cancelled() {
                 if let Ok(regular_result) = self.optimize_memory_with_cancel(cancel).await {
                     optimizations_performed.extend(regular_result.optimizations_performed);
+                    strategies.extend(regular_result.strategies);
                     message.push_str(&format!(
                         "\nPerformed standard optimizations instead:\n{}\n",
                         regular_result.message
@@ -173,11 +254,15 @@ impl MemoryOptimizer {
             return Ok(MemoryOptimizationResult {
                 memory_before,
                 memory_after,
-                freed_memory: freed_memory.abs(),
+                freed_memory,
+                freed_estimate: strategies.iter().map(|s| s.freed_estimate).sum(),
+                strategies,
                 optimization_type: "Standard Optimization (Admin Canceled)".to_string(),
                 success,
                 message: message.trim().to_string(),
                 optimizations_performed,
+                dry_run: false,
+                blocked_by: Vec::new(),
             });
         } else {
             message.push_str(&format!(
@@ -192,6 +277,7 @@ impl MemoryOptimizer {
         }
         if let Ok(regular_result) = self.optimize_memory_with_cancel(cancel).await {
             optimizations_performed.extend(regular_result.optimizations_performed);
+            strategies.extend(regular_result.strategies);
             message.push_str(&format!(
                 "\nAlso performed standard optimizations:\n{}\n",
                 regular_result.message
@@ -208,14 +294,46 @@ impl MemoryOptimizer {
         Ok(MemoryOptimizationResult {
             memory_before,
             memory_after,
-            freed_memory: freed_memory.abs(),
+            freed_memory,
+            freed_estimate: strategies.iter().map(|s| s.freed_estimate).sum(),
+            strategies,
             optimization_type: "Deep Clean with Admin Privileges".to_string(),
             success,
             message: message.trim().to_string(),
             optimizations_performed,
+            dry_run: false,
+            blocked_by: Vec::new(),
         })
     }
 
+    /// Run only the standard optimizations while a backup or FileVault
+    /// conversion is under way; the deep clean's purge would slow it down.
+    async fn defer_deep_clean(
+        &self,
+        blocked_by: Vec<BlockingCondition>,
+        cancel: &CancellationToken,
+    ) -> Result<MemoryOptimizationResult, String> {
+        let mut result = self.optimize_memory_with_cancel(cancel).await?;
+        result.optimization_type = "Standard Optimization (Deep Clean Deferred)".to_string();
+        result.message = format!(
+            "Deep clean deferred: {}\n\nPerformed standard optimizations instead:\n{}",
+            blockers::describe(&blocked_by),
+            result.message
+        );
+        result.blocked_by = blocked_by;
+        Ok(result)
+    }
+
+    /// Run, or with `plan.dry_run` only estimate, the chosen strategies and
+    /// report what each reclaimed.
+    pub async fn run_plan_with_cancel(
+        &self,
+        plan: &MemoryOptimizationPlan,
+        cancel: &CancellationToken,
+    ) -> Result<MemoryPlanResult, String> {
+        plan::run_plan(plan, cancel).await
+    }
+
     // Keep thin wrappers to preserve the public API exactly.
     pub async fn clear_inactive_memory(&self) -> Result<u64, String> {
         non_admin::clear_inactive_memory_safe().await
@@ -237,7 +355,62 @@ impl MemoryOptimizer {
     pub async fn kill_memory_intensive_processes(
         &self,
         threshold_mb: u64,
+        dry_run: bool,
     ) -> Result<Vec<String>, String> {
-        non_admin::kill_memory_intensive_processes(threshold_mb).await
+        non_admin::kill_memory_intensive_processes(threshold_mb, dry_run).await
+    }
+
+    /// The steps an optimization would run, for inspector mode; memory is untouched.
+    pub async fn preview_optimization(admin: bool) -> Result<MemoryOptimizationResult, String> {
+        let stats = Self::get_memory_stats()?;
+        let blocked_by = if admin {
+            blockers::detect_blocking_conditions().await
+        } else {
+            Vec::new()
+        };
+        let mut steps = vec![
+            "Clear inactive memory",
+            "Optimize file system caches",
+            "Clear application caches",
+            "Optimize memory compression",
+            "Clear network caches",
+            "Trigger garbage collection in apps",
+            "Clear temporary allocations",
+        ];
+        if admin && blocked_by.is_empty() {
+            steps.splice(
+                0..0,
+                [
+                    "Purge memory and disk cache (admin)",
+                    "Flush DNS cache (admin)",
+                    "Clear system caches and swap files (admin)",
+                    "Run maintenance scripts (admin)",
+                ],
+            );
+        }
+        Ok(MemoryOptimizationResult {
+            memory_before: stats.clone(),
+            memory_after: stats,
+            freed_memory: 0,
+            freed_estimate: 0,
+            strategies: Vec::new(),
+            optimization_type: "Inspector Mode Preview".to_string(),
+            success: true,
+            message: if blocked_by.is_empty() {
+                format!("Inspector mode: {} steps would run", steps.len())
+            } else {
+                format!(
+                    "Inspector mode: {} steps would run; the deep clean would be deferred: {}",
+                    steps.len(),
+                    blockers::describe(&blocked_by)
+                )
+            },
+            optimizations_performed: steps.into_iter().map(String::from).collect(),
+            dry_run: true,
+            blocked_by,
+        })
     }
 }
+
+#[cfg(test)]
+mod tests;
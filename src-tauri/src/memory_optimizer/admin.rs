@@ -6,6 +6,9 @@ use tokio::process::Command;
 use tokio::select;
 use tokio_util::sync::CancellationToken;
 
+use super::plan::MemoryStrategy;
+use crate::privileged_helper::{self, HelperCommand};
+
 pub(crate) struct AdminScriptOutcome {
     pub success: bool,
     pub stdout: String,
@@ -47,8 +50,8 @@ run RESTART_cfprefsd killall cfprefsd
 "#
 }
 
-async fn write_script(script_path: &str) {
-    let _ = fs::write(script_path, deep_clean_script());
+async fn write_script(script_path: &str, script: &str) {
+    let _ = fs::write(script_path, script);
     if let Ok(meta) = fs::metadata(script_path) {
         let mut perms = meta.permissions();
         perms.set_mode(0o755);
@@ -56,11 +59,80 @@ async fn write_script(script_path: &str) {
     }
 }
 
+/// Script running only the admin steps of a memory optimization plan.
+fn plan_script(steps: &[MemoryStrategy]) -> String {
+    let mut script = String::from("#!/bin/bash\nset -u\n");
+    for step in steps {
+        match step {
+            MemoryStrategy::Purge => script.push_str("purge\n"),
+            MemoryStrategy::DnsFlush => {
+                script.push_str("dscacheutil -flushcache\nkillall -HUP mDNSResponder\n")
+            }
+            _ => {}
+        }
+    }
+    script
+}
+
 // "run_deep_clean" (non-cancellable) removed to avoid unused code; use the cancellable variant instead.
 
 pub(crate) async fn run_deep_clean_with_cancel(cancel: &CancellationToken) -> AdminScriptOutcome {
     let script_path = "/tmp/macos_optimizer_deep_clean.sh";
-    write_script(script_path).await;
+    write_script(script_path, deep_clean_script()).await;
+    run_script_with_cancel(script_path, cancel).await
+}
+
+/// Run the admin steps of a memory optimization plan through the installed
+/// helper, or else behind one prompt.
+pub(crate) async fn run_admin_steps_with_cancel(
+    steps: &[MemoryStrategy],
+    cancel: &CancellationToken,
+) -> AdminScriptOutcome {
+    let commands: Vec<HelperCommand> = steps
+        .iter()
+        .filter_map(|step| match step {
+            MemoryStrategy::Purge => Some(HelperCommand::Purge),
+            MemoryStrategy::DnsFlush => Some(HelperCommand::FlushDns),
+            _ => None,
+        })
+        .collect();
+    match privileged_helper::send(&commands, cancel).await {
+        Ok(Some(outcomes)) => {
+            let errors: Vec<String> = outcomes.into_iter().filter_map(|o| o.error).collect();
+            return AdminScriptOutcome {
+                success: errors.is_empty(),
+                stdout: String::new(),
+                stderr: errors.join("; "),
+                cancelled: false,
+            };
+        }
+        Ok(None) => {}
+        Err(e) if e == "cancelled" => {
+            return AdminScriptOutcome {
+                success: false,
+                stdout: String::new(),
+                stderr: String::new(),
+                cancelled: true,
+            };
+        }
+        Err(e) => {
+            return AdminScriptOutcome {
+                success: false,
+                stdout: String::new(),
+                stderr: e,
+                cancelled: false,
+            };
+        }
+    }
+    let script_path = "/tmp/macos_optimizer_memory_plan.sh";
+    write_script(script_path, &plan_script(steps)).await;
+    run_script_with_cancel(script_path, cancel).await
+}
+
+async fn run_script_with_cancel(
+    script_path: &str,
+    cancel: &CancellationToken,
+) -> AdminScriptOutcome {
     let applescript = format!(
         r#"with timeout of 1200 seconds
   do shell script "{}" with administrator privileges
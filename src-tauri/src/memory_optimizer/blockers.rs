@@ -0,0 +1,117 @@
+// src/memory_optimizer/blockers.rs
+
+use serde::{Deserialize, Serialize};
+use tokio::process::Command;
+
+use crate::capabilities::{self, Tool};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BlockingKind {
+    TimeMachineBackup,
+    FileVaultConversion,
+}
+
+/// Disk-heavy work that a purge would slow down, and that would refill the
+/// purged caches right away.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BlockingCondition {
+    pub kind: BlockingKind,
+    /// Completion between 0 and 100, when reported.
+    pub percent: Option<f32>,
+    pub message: String,
+}
+
+/// A running backup from `tmutil status`, which prints an old-style plist
+/// with `Running = 1;` while one is in progress.
+pub(crate) fn parse_tmutil_status(output: &str) -> Option<BlockingCondition> {
+    let field = |name: &str| {
+        output.lines().find_map(|line| {
+            let (key, value) = line.trim().split_once('=')?;
+            (key.trim() == name)
+                .then(|| value.trim().trim_end_matches(';').trim_matches('"').trim())
+        })
+    };
+    if field("Running") != Some("1") {
+        return None;
+    }
+    let percent = field("Percent")
+        .and_then(|value| value.parse::<f32>().ok())
+        .filter(|fraction| (0.0..=1.0).contains(fraction))
+        .map(|fraction| fraction * 100.0);
+    let message = match (field("BackupPhase"), percent) {
+        (Some(phase), Some(percent)) => format!(
+            "Time Machine backup in progress ({}, {:.0}%)",
+            phase, percent
+        ),
+        (Some(phase), None) => format!("Time Machine backup in progress ({})", phase),
+        (None, _) => "Time Machine backup in progress".to_string(),
+    };
+    Some(BlockingCondition {
+        kind: BlockingKind::TimeMachineBackup,
+        percent,
+        message,
+    })
+}
+
+/// Encryption or decryption from `fdesetup status`, e.g.
+/// `Encryption in progress: Percent completed = 42.19`.
+pub(crate) fn parse_fdesetup_status(output: &str) -> Option<BlockingCondition> {
+    let line = output.lines().map(str::trim).find(|line| {
+        line.starts_with("Encryption in progress") || line.starts_with("Decryption in progress")
+    })?;
+    let percent = line
+        .rsplit_once('=')
+        .and_then(|(_, value)| value.trim().parse::<f32>().ok());
+    let action = if line.starts_with("Encryption") {
+        "encrypting"
+    } else {
+        "decrypting"
+    };
+    let message = match percent {
+        Some(percent) => format!("FileVault is {} the disk ({:.0}%)", action, percent),
+        None => format!("FileVault is {} the disk", action),
+    };
+    Some(BlockingCondition {
+        kind: BlockingKind::FileVaultConversion,
+        percent,
+        message,
+    })
+}
+
+/// Conditions under which purging should wait; a missing tool or failed
+/// check counts as no condition.
+pub(crate) async fn detect_blocking_conditions() -> Vec<BlockingCondition> {
+    let (backup, filevault) = tokio::join!(
+        run_status(Tool::Tmutil, parse_tmutil_status),
+        run_status(Tool::Fdesetup, parse_fdesetup_status)
+    );
+    backup.into_iter().chain(filevault).collect()
+}
+
+async fn run_status(
+    tool: Tool,
+    parse: fn(&str) -> Option<BlockingCondition>,
+) -> Option<BlockingCondition> {
+    if !capabilities::tool_available(tool) {
+        return None;
+    }
+    let output = Command::new(tool.command())
+        .arg("status")
+        .output()
+        .await
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    parse(&String::from_utf8_lossy(&output.stdout))
+}
+
+/// One line naming every condition, for result messages.
+pub(crate) fn describe(conditions: &[BlockingCondition]) -> String {
+    conditions
+        .iter()
+        .map(|condition| condition.message.as_str())
+        .collect::<Vec<_>>()
+        .join("; ")
+}
@@ -6,6 +6,7 @@ use tokio_util::sync::CancellationToken;
 
 use super::stats;
 use super::utils::{calculate_adaptive_chunk_size, MEMORY_POOL};
+use crate::formatting;
 
 pub(crate) async fn clear_inactive_memory_safe() -> Result<u64, String> {
     clear_inactive_memory_adaptive_with_cancel(None).await
@@ -76,34 +77,46 @@ pub(crate) async fn optimize_file_caches() -> Result<(), String> {
     Ok(())
 }
 
-pub(crate) async fn clear_app_caches() -> Result<usize, String> {
-    let mut cleared = 0;
+/// Caches under the home folder that `clear_app_caches` deletes: Safari,
+/// Chrome's memory cache and Xcode.
+const APP_CACHE_PATHS: &[&str] = &[
+    "Library/Caches/com.apple.Safari/Cache.db",
+    "Library/Caches/Google/Chrome/Default/Cache",
+    "Library/Caches/com.apple.dt.Xcode/Cache",
+];
+
+fn app_cache_paths() -> Vec<std::path::PathBuf> {
+    dirs::home_dir()
+        .map(|home| APP_CACHE_PATHS.iter().map(|path| home.join(path)).collect())
+        .unwrap_or_default()
+}
 
-    // Clear Safari cache
-    if let Ok(_) = TokioCommand::new("rm")
-        .args(&["-rf", "~/Library/Caches/com.apple.Safari/Cache.db"])
-        .output()
-        .await
-    {
-        cleared += 1;
-    }
+/// Bytes on disk of the caches `clear_app_caches` would delete.
+pub(crate) fn app_cache_size() -> u64 {
+    app_cache_paths()
+        .iter()
+        .filter(|path| path.exists())
+        .map(|path| {
+            walkdir::WalkDir::new(path)
+                .into_iter()
+                .filter_map(Result::ok)
+                .filter_map(|entry| entry.metadata().ok())
+                .filter(|metadata| metadata.is_file())
+                .map(|metadata| metadata.len())
+                .sum::<u64>()
+        })
+        .sum()
+}
 
-    // Clear Chrome memory cache
-    if let Ok(_) = TokioCommand::new("rm")
-        .args(&["-rf", "~/Library/Caches/Google/Chrome/Default/Cache"])
-        .output()
-        .await
-    {
-        cleared += 1;
-    }
+pub(crate) async fn clear_app_caches() -> Result<usize, String> {
+    let mut cleared = 0;
 
-    // Clear system app caches
-    if let Ok(_) = TokioCommand::new("rm")
-        .args(&["-rf", "~/Library/Caches/com.apple.dt.Xcode/Cache"])
-        .output()
-        .await
-    {
-        cleared += 1;
+    for path in app_cache_paths().into_iter().filter(|path| path.exists()) {
+        if let Ok(output) = TokioCommand::new("rm").arg("-rf").arg(&path).output().await {
+            if output.status.success() {
+                cleared += 1;
+            }
+        }
     }
 
     Ok(cleared)
@@ -192,13 +205,15 @@ pub(crate) async fn optimize_swap() -> Result<String, String> {
     let _ = clear_inactive_memory_safe().await?;
 
     Ok(format!(
-        "Swap optimization attempted. Current swap usage: {} MB",
-        stats.swap_used / (1024 * 1024)
+        "Swap optimization attempted. Current swap usage: {}",
+        formatting::format_size(stats.swap_used)
     ))
 }
 
+/// With `dry_run` the matching processes are listed but not signalled.
 pub(crate) async fn kill_memory_intensive_processes(
     threshold_mb: u64,
+    dry_run: bool,
 ) -> Result<Vec<String>, String> {
     let mut killed_processes = Vec::new();
 
@@ -223,6 +238,10 @@ pub(crate) async fn kill_memory_intensive_processes(
 
                     // Skip critical system processes
                     if !is_critical_process(process_name) {
+                        if dry_run {
+                            killed_processes.push(format!("{} (PID: {})", process_name, pid));
+                            continue;
+                        }
                         // Try to kill the process
                         if let Ok(_) = TokioCommand::new("kill")
                             .arg("-TERM")
@@ -0,0 +1,257 @@
+// src/memory_optimizer/plan.rs
+
+use std::collections::HashSet;
+
+use serde::{Deserialize, Serialize};
+use tokio_util::sync::CancellationToken;
+
+use super::blockers::{self, BlockingCondition};
+use super::{admin, non_admin, stats};
+use crate::formatting;
+use crate::metrics::MemoryStats;
+
+/// One step the caller can toggle on its own.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MemoryStrategy {
+    /// `purge`: drops the file-backed page cache. Needs admin rights.
+    Purge,
+    /// Flush the directory service and mDNSResponder caches. Needs admin rights.
+    DnsFlush,
+    /// Apply memory pressure until a tenth of memory is available.
+    InactiveMemory,
+    /// Delete the browser and Xcode caches that apps keep mapped.
+    AppCacheTrim,
+    /// Relieve swap by freeing inactive memory; nothing to do without swap.
+    SwapTuning,
+}
+
+impl MemoryStrategy {
+    pub fn requires_admin(self) -> bool {
+        matches!(self, MemoryStrategy::Purge | MemoryStrategy::DnsFlush)
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            MemoryStrategy::Purge => "Purge disk cache",
+            MemoryStrategy::DnsFlush => "Flush DNS cache",
+            MemoryStrategy::InactiveMemory => "Clear inactive memory",
+            MemoryStrategy::AppCacheTrim => "Trim application caches",
+            MemoryStrategy::SwapTuning => "Relieve swap",
+        }
+    }
+}
+
+/// Strategies to run, in the order given; duplicates run once.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MemoryOptimizationPlan {
+    pub strategies: Vec<MemoryStrategy>,
+    /// Estimate every strategy without running any.
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StrategyOutcome {
+    pub strategy: MemoryStrategy,
+    pub requires_admin: bool,
+    /// Memory the strategy can be expected to make available, at most.
+    pub estimated_bytes: u64,
+    /// Change in available memory while the strategy ran; `None` in a dry run
+    /// or when it did not run.
+    pub actual_bytes: Option<i64>,
+    pub success: bool,
+    pub message: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MemoryPlanResult {
+    pub memory_before: MemoryStats,
+    pub memory_after: MemoryStats,
+    pub dry_run: bool,
+    pub estimated_total: u64,
+    /// Change in available memory over the whole plan.
+    pub freed_memory: i64,
+    pub outcomes: Vec<StrategyOutcome>,
+    /// Why the purge was deferred, or would be in a dry run.
+    #[serde(default)]
+    pub blocked_by: Vec<BlockingCondition>,
+}
+
+/// What each strategy can be expected to reclaim from `stats`.
+/// `app_cache_bytes` is the size of the caches [`MemoryStrategy::AppCacheTrim`] deletes.
+pub(crate) fn estimate(stats: &MemoryStats, strategy: MemoryStrategy, app_cache_bytes: u64) -> u64 {
+    // The inactive memory pass stops once a tenth of memory is available.
+    let inactive = (stats.total / 10).saturating_sub(stats.available);
+    match strategy {
+        MemoryStrategy::Purge => stats.cache_files,
+        MemoryStrategy::DnsFlush => 0,
+        MemoryStrategy::InactiveMemory => inactive,
+        MemoryStrategy::AppCacheTrim => app_cache_bytes.min(stats.cache_files),
+        MemoryStrategy::SwapTuning if stats.swap_used > 0 => inactive.min(stats.swap_used),
+        MemoryStrategy::SwapTuning => 0,
+    }
+}
+
+/// `plan.strategies` without repeats, keeping their order.
+pub(crate) fn unique_strategies(plan: &MemoryOptimizationPlan) -> Vec<MemoryStrategy> {
+    let mut seen = HashSet::new();
+    plan.strategies
+        .iter()
+        .copied()
+        .filter(|strategy| seen.insert(*strategy))
+        .collect()
+}
+
+pub(crate) async fn run_plan(
+    plan: &MemoryOptimizationPlan,
+    cancel: &CancellationToken,
+) -> Result<MemoryPlanResult, String> {
+    let strategies = unique_strategies(plan);
+    if strategies.is_empty() {
+        return Err("Select at least one strategy".into());
+    }
+    let memory_before = stats::get_memory_stats()?;
+    let app_cache_bytes = if strategies.contains(&MemoryStrategy::AppCacheTrim) {
+        tokio::task::spawn_blocking(non_admin::app_cache_size)
+            .await
+            .unwrap_or(0)
+    } else {
+        0
+    };
+    let mut outcomes: Vec<StrategyOutcome> = strategies
+        .iter()
+        .map(|strategy| StrategyOutcome {
+            strategy: *strategy,
+            requires_admin: strategy.requires_admin(),
+            estimated_bytes: estimate(&memory_before, *strategy, app_cache_bytes),
+            actual_bytes: None,
+            success: true,
+            message: format!("Would {}", strategy.label().to_lowercase()),
+        })
+        .collect();
+
+    // Purging mid-backup or mid-encryption slows both down and the caches
+    // refill right away, so the purge waits for a quieter moment.
+    let blocked_by = if strategies.contains(&MemoryStrategy::Purge) {
+        blockers::detect_blocking_conditions().await
+    } else {
+        Vec::new()
+    };
+    if !blocked_by.is_empty() {
+        let reason = blockers::describe(&blocked_by);
+        for result in outcomes
+            .iter_mut()
+            .filter(|o| o.strategy == MemoryStrategy::Purge)
+        {
+            result.success = false;
+            result.message = if plan.dry_run {
+                format!("Would be deferred: {}", reason)
+            } else {
+                format!("Deferred: {}", reason)
+            };
+        }
+    }
+
+    if !plan.dry_run {
+        // Admin steps share one authorization prompt, so they run together first.
+        let admin_steps: Vec<MemoryStrategy> = strategies
+            .iter()
+            .copied()
+            .filter(|strategy| strategy.requires_admin())
+            .filter(|strategy| blocked_by.is_empty() || *strategy != MemoryStrategy::Purge)
+            .collect();
+        if !admin_steps.is_empty() {
+            let before = stats::get_memory_stats()?.available;
+            let outcome = admin::run_admin_steps_with_cancel(&admin_steps, cancel).await;
+            if outcome.cancelled && cancel.is_cancelled() {
+                return Err("cancelled".into());
+            }
+            let freed = stats::get_memory_stats()?.available as i64 - before as i64;
+            let message = if outcome.success {
+                "Done".to_string()
+            } else if outcome.cancelled {
+                "Administrator authorization was canceled".to_string()
+            } else {
+                format!("Failed: {}", outcome.stderr.trim())
+            };
+            // The steps ran as one script; a DNS flush frees no memory, so
+            // the change is credited to the purge when there is one.
+            let credited = if admin_steps.contains(&MemoryStrategy::Purge) {
+                MemoryStrategy::Purge
+            } else {
+                admin_steps[0]
+            };
+            for result in outcomes
+                .iter_mut()
+                .filter(|o| admin_steps.contains(&o.strategy))
+            {
+                result.success = outcome.success;
+                result.message = message.clone();
+                result.actual_bytes = Some(if result.strategy == credited {
+                    freed
+                } else {
+                    0
+                });
+            }
+        }
+
+        for result in outcomes.iter_mut().filter(|o| !o.requires_admin) {
+            if cancel.is_cancelled() {
+                return Err("cancelled".into());
+            }
+            let before = stats::get_memory_stats()?.available;
+            let ran = run_strategy(result.strategy, cancel).await;
+            if cancel.is_cancelled() {
+                return Err("cancelled".into());
+            }
+            let after = stats::get_memory_stats()?.available;
+            result.actual_bytes = Some(after as i64 - before as i64);
+            match ran {
+                Ok(message) => result.message = message,
+                Err(err) => {
+                    result.success = false;
+                    result.message = format!("Failed: {}", err);
+                }
+            }
+        }
+    }
+
+    let memory_after = if plan.dry_run {
+        memory_before.clone()
+    } else {
+        stats::get_memory_stats()?
+    };
+    Ok(MemoryPlanResult {
+        freed_memory: memory_after.available as i64 - memory_before.available as i64,
+        estimated_total: outcomes.iter().map(|o| o.estimated_bytes).sum(),
+        memory_before,
+        memory_after,
+        dry_run: plan.dry_run,
+        outcomes,
+        blocked_by,
+    })
+}
+
+async fn run_strategy(
+    strategy: MemoryStrategy,
+    cancel: &CancellationToken,
+) -> Result<String, String> {
+    match strategy {
+        MemoryStrategy::InactiveMemory => {
+            let freed = non_admin::clear_inactive_memory_adaptive_with_cancel(Some(cancel)).await?;
+            Ok(format!(
+                "Cleared {} of inactive memory",
+                formatting::format_size(freed)
+            ))
+        }
+        MemoryStrategy::AppCacheTrim => {
+            let cleared = non_admin::clear_app_caches().await?;
+            Ok(format!("Cleared {} application caches", cleared))
+        }
+        MemoryStrategy::SwapTuning => non_admin::optimize_swap().await,
+        MemoryStrategy::Purge | MemoryStrategy::DnsFlush => {
+            Err(format!("{} needs administrator rights", strategy.label()))
+        }
+    }
+}
@@ -0,0 +1,212 @@
+use super::blockers::{parse_fdesetup_status, parse_tmutil_status, BlockingKind};
+use super::plan::{estimate, unique_strategies, MemoryOptimizationPlan, MemoryStrategy};
+use super::verification::{measurement, MemoryDiff};
+#[cfg(feature = "app")]
+use super::watchdog::{MemoryWatchdog, PressureLevel, WatchdogConfig};
+use crate::metrics::MemoryStats;
+
+const GB: u64 = 1024 * 1024 * 1024;
+
+fn stats(available: u64, cache_files: u64, swap_used: u64) -> MemoryStats {
+    MemoryStats {
+        total: 16 * GB,
+        used: 16 * GB - available,
+        available,
+        wired: 2 * GB,
+        compressed: GB,
+        swap_total: 2 * GB,
+        swap_used,
+        swap_free: 2 * GB - swap_used,
+        app_memory: 8 * GB,
+        cache_files,
+        pressure_percent: 80.0,
+        pressure_state: MemoryStats::pressure_state(80.0),
+        speculative: 0,
+        compressor_stored: 2 * GB,
+        compressor_occupancy: 6.25,
+        pressure_level: Default::default(),
+        activity: Default::default(),
+        activity_rates: None,
+    }
+}
+
+#[test]
+fn estimates_follow_what_each_strategy_can_reach() {
+    let tight = stats(GB, 3 * GB, GB / 2);
+    // A tenth of 16 GB is 1.6 GB, so the inactive pass aims for 0.6 GB more.
+    let inactive = 16 * GB / 10 - GB;
+    assert_eq!(estimate(&tight, MemoryStrategy::Purge, 0), 3 * GB);
+    assert_eq!(estimate(&tight, MemoryStrategy::DnsFlush, 0), 0);
+    assert_eq!(
+        estimate(&tight, MemoryStrategy::InactiveMemory, 0),
+        inactive
+    );
+    assert_eq!(estimate(&tight, MemoryStrategy::SwapTuning, 0), GB / 2);
+    assert_eq!(
+        estimate(&tight, MemoryStrategy::AppCacheTrim, 5 * GB),
+        3 * GB
+    );
+    assert_eq!(estimate(&tight, MemoryStrategy::AppCacheTrim, GB), GB);
+
+    let roomy = stats(8 * GB, GB, 0);
+    assert_eq!(estimate(&roomy, MemoryStrategy::InactiveMemory, 0), 0);
+    assert_eq!(estimate(&roomy, MemoryStrategy::SwapTuning, 0), 0);
+}
+
+#[test]
+fn plans_parse_from_snake_case_and_drop_repeats() {
+    let plan: MemoryOptimizationPlan = serde_json::from_str(
+        r#"{"strategies": ["dns_flush", "inactive_memory", "dns_flush", "app_cache_trim"]}"#,
+    )
+    .unwrap();
+    assert!(!plan.dry_run);
+    assert_eq!(
+        unique_strategies(&plan),
+        vec![
+            MemoryStrategy::DnsFlush,
+            MemoryStrategy::InactiveMemory,
+            MemoryStrategy::AppCacheTrim
+        ]
+    );
+    assert!(MemoryStrategy::DnsFlush.requires_admin());
+    assert!(!MemoryStrategy::SwapTuning.requires_admin());
+}
+
+#[cfg(feature = "app")]
+fn watchdog(auto_optimize: bool) -> MemoryWatchdog {
+    let mut watchdog = MemoryWatchdog::default();
+    watchdog
+        .configure(WatchdogConfig {
+            enabled: true,
+            auto_optimize,
+            cooldown_secs: 600,
+            ..WatchdogConfig::default()
+        })
+        .unwrap();
+    watchdog
+}
+
+#[cfg(feature = "app")]
+#[test]
+fn watchdog_reports_level_changes_and_repeats_after_cooldown() {
+    let mut watchdog = watchdog(false);
+    assert_eq!(watchdog.observe(50.0, 0), None);
+
+    let warning = watchdog.observe(80.0, 10).unwrap();
+    assert_eq!(warning.level, PressureLevel::Warning);
+    assert!(!warning.auto_optimizing);
+    assert_eq!(watchdog.observe(82.0, 100), None);
+
+    assert_eq!(
+        watchdog.observe(95.0, 120).unwrap().level,
+        PressureLevel::Critical
+    );
+    assert_eq!(watchdog.observe(95.0, 600), None);
+    assert!(watchdog.observe(95.0, 720).is_some());
+
+    assert_eq!(
+        watchdog.observe(40.0, 730).unwrap().level,
+        PressureLevel::Normal
+    );
+    assert_eq!(watchdog.observe(40.0, 2000), None);
+    assert_eq!(watchdog.status().pressure_percent, Some(40.0));
+}
+
+#[cfg(feature = "app")]
+#[test]
+fn watchdog_optimizes_on_critical_pressure_once_per_cooldown() {
+    let mut watchdog = watchdog(true);
+    assert!(!watchdog.observe(80.0, 0).unwrap().auto_optimizing);
+    assert!(watchdog.observe(92.0, 10).unwrap().auto_optimizing);
+    watchdog.record_optimization("Freed 1 GB".into());
+
+    // Dropping and returning within the cooldown notifies without optimizing.
+    watchdog.observe(50.0, 20);
+    assert!(!watchdog.observe(92.0, 30).unwrap().auto_optimizing);
+    assert!(watchdog.observe(92.0, 610).unwrap().auto_optimizing);
+
+    let status = watchdog.status();
+    assert_eq!(status.optimizations_run, 1);
+    assert_eq!(status.last_optimization_at, Some(610));
+}
+
+#[cfg(feature = "app")]
+#[test]
+fn watchdog_rejects_invalid_settings_and_persists_valid_ones() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("memory_watchdog.json");
+    let mut watchdog = MemoryWatchdog::load_from(&path);
+    assert_eq!(watchdog.config(), &WatchdogConfig::default());
+
+    let inverted = WatchdogConfig {
+        warning_percent: 95.0,
+        critical_percent: 90.0,
+        ..WatchdogConfig::default()
+    };
+    assert!(watchdog.configure(inverted).is_err());
+    let hasty = WatchdogConfig {
+        cooldown_secs: 5,
+        ..WatchdogConfig::default()
+    };
+    assert!(watchdog.configure(hasty).is_err());
+
+    let config = WatchdogConfig {
+        enabled: true,
+        auto_optimize: true,
+        ..WatchdogConfig::default()
+    };
+    watchdog.configure(config.clone()).unwrap();
+    assert_eq!(MemoryWatchdog::load_from(&path).config(), &config);
+}
+
+#[test]
+fn running_backups_and_filevault_conversions_block_purges() {
+    let backup = parse_tmutil_status(
+        "Backup session status:\n{\n    BackupPhase = Copying;\n    ClientID = \"com.apple.backupd\";\n    Percent = \"0.4226\";\n    Progress =     {\n        Percent = \"0.4226\";\n    };\n    Running = 1;\n    Stopping = 0;\n}\n",
+    )
+    .unwrap();
+    assert_eq!(backup.kind, BlockingKind::TimeMachineBackup);
+    assert_eq!(backup.percent.map(f32::round), Some(42.0));
+    assert_eq!(
+        backup.message,
+        "Time Machine backup in progress (Copying, 42%)"
+    );
+    assert_eq!(
+        parse_tmutil_status(
+            "Backup session status:\n{\n    ClientID = \"com.apple.backupd\";\n    Percent = \"-1\";\n    Running = 0;\n}\n"
+        ),
+        None
+    );
+
+    let encrypting = parse_fdesetup_status(
+        "FileVault is On.\nEncryption in progress: Percent completed = 42.19\n",
+    )
+    .unwrap();
+    assert_eq!(encrypting.kind, BlockingKind::FileVaultConversion);
+    assert_eq!(encrypting.percent, Some(42.19));
+    assert_eq!(encrypting.message, "FileVault is encrypting the disk (42%)");
+    assert!(parse_fdesetup_status(
+        "FileVault is Off.\nDecryption in progress: Percent completed = 7\n"
+    )
+    .is_some_and(|condition| condition.message.contains("decrypting")));
+    assert_eq!(parse_fdesetup_status("FileVault is On.\n"), None);
+}
+
+#[test]
+fn measurements_report_the_change_in_available_memory() {
+    let before = stats(GB, 3 * GB, GB / 2);
+    let after = stats(2 * GB, 2 * GB, GB / 2);
+    let measured = measurement("Purge disk cache", 3 * GB, before.clone(), after.clone());
+    assert_eq!(measured.measured_delta, GB as i64);
+    assert_eq!(measured.freed_estimate, 3 * GB);
+
+    let diff = MemoryDiff::between(&before, &after);
+    assert_eq!(diff.available, GB as i64);
+    assert_eq!(diff.used, -(GB as i64));
+    assert_eq!(diff.cache_files, -(GB as i64));
+    assert_eq!(diff.swap_used, 0);
+
+    // Memory taken by others while a step ran shows up as a negative delta.
+    let worse = measurement("Clear temporary allocations", 0, after, before);
+    assert_eq!(worse.measured_delta, -(GB as i64));
+}
@@ -1,7 +1,6 @@
 // src/memory_optimizer/utils.rs
 
 use lazy_static::lazy_static;
-use std::process::Command;
 use std::sync::Arc;
 use tokio::sync::Mutex;
 
@@ -50,43 +49,3 @@ pub fn calculate_adaptive_chunk_size(memory_pressure: f32) -> usize {
         _ => base_chunk_size,                 // 50MB when low
     }
 }
-
-// Helper functions for memory stats parsing
-pub fn get_page_size() -> u64 {
-    // Get macOS page size (usually 4096)
-    let output = Command::new("pagesize").output().unwrap_or_else(|_| {
-        Command::new("getconf")
-            .arg("PAGESIZE")
-            .output()
-            .expect("Failed to get page size")
-    });
-
-    String::from_utf8_lossy(&output.stdout)
-        .trim()
-        .parse::<u64>()
-        .unwrap_or(4096)
-}
-
-pub fn extract_number(line: &str) -> Option<u64> {
-    // Extract number from vm_stat output line
-    let parts: Vec<&str> = line.split_whitespace().collect();
-    for part in parts.iter().rev() {
-        if let Ok(num) = part.trim_end_matches('.').parse::<u64>() {
-            return Some(num);
-        }
-    }
-    None
-}
-
-pub fn extract_sysctl_value(key: &str) -> Option<u64> {
-    let output = Command::new("sysctl").arg("-n").arg(key).output().ok()?;
-
-    if output.status.success() {
-        String::from_utf8_lossy(&output.stdout)
-            .trim()
-            .parse::<u64>()
-            .ok()
-    } else {
-        None
-    }
-}
@@ -0,0 +1,104 @@
+// src/memory_optimizer/verification.rs
+
+use std::future::Future;
+
+use serde::{Deserialize, Serialize};
+
+use super::stats;
+use super::MemoryOptimizationResult;
+use crate::metrics::MemoryStats;
+
+/// Change in each memory figure from one sample to the next; positive means
+/// it grew.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MemoryDiff {
+    pub available: i64,
+    pub used: i64,
+    pub wired: i64,
+    pub compressed: i64,
+    pub app_memory: i64,
+    pub cache_files: i64,
+    pub swap_used: i64,
+}
+
+impl MemoryDiff {
+    pub fn between(before: &MemoryStats, after: &MemoryStats) -> Self {
+        let delta = |before: u64, after: u64| after as i64 - before as i64;
+        Self {
+            available: delta(before.available, after.available),
+            used: delta(before.used, after.used),
+            wired: delta(before.wired, after.wired),
+            compressed: delta(before.compressed, after.compressed),
+            app_memory: delta(before.app_memory, after.app_memory),
+            cache_files: delta(before.cache_files, after.cache_files),
+            swap_used: delta(before.swap_used, after.swap_used),
+        }
+    }
+}
+
+/// Memory sampled right before and after one step of an optimization.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StrategyMeasurement {
+    pub name: String,
+    /// Memory the step can be expected to make available, at most.
+    pub freed_estimate: u64,
+    pub before: MemoryStats,
+    pub after: MemoryStats,
+    /// Change in available memory while the step ran; negative when other
+    /// processes took more than it freed.
+    pub measured_delta: i64,
+}
+
+/// Payload of the `memory:optimized` event: what an optimization was
+/// expected to free next to what was measured.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MemoryOptimizedEvent {
+    pub optimization_type: String,
+    pub memory_before: MemoryStats,
+    pub memory_after: MemoryStats,
+    pub diff: MemoryDiff,
+    pub freed_estimate: u64,
+    pub freed_memory: i64,
+    pub strategies: Vec<StrategyMeasurement>,
+}
+
+impl From<&MemoryOptimizationResult> for MemoryOptimizedEvent {
+    fn from(result: &MemoryOptimizationResult) -> Self {
+        Self {
+            optimization_type: result.optimization_type.clone(),
+            memory_before: result.memory_before.clone(),
+            memory_after: result.memory_after.clone(),
+            diff: MemoryDiff::between(&result.memory_before, &result.memory_after),
+            freed_estimate: result.freed_estimate,
+            freed_memory: result.freed_memory,
+            strategies: result.strategies.clone(),
+        }
+    }
+}
+
+/// Run `step`, sampling memory immediately before and after it.
+pub(crate) async fn measure<T>(
+    name: &str,
+    freed_estimate: u64,
+    step: impl Future<Output = T>,
+) -> Result<(T, StrategyMeasurement), String> {
+    let before = stats::get_memory_stats()?;
+    let output = step.await;
+    let after = stats::get_memory_stats()?;
+    Ok((output, measurement(name, freed_estimate, before, after)))
+}
+
+pub(crate) fn measurement(
+    name: &str,
+    freed_estimate: u64,
+    before: MemoryStats,
+    after: MemoryStats,
+) -> StrategyMeasurement {
+    StrategyMeasurement {
+        name: name.to_string(),
+        freed_estimate,
+        measured_delta: after.available as i64 - before.available as i64,
+        before,
+        after,
+    }
+}
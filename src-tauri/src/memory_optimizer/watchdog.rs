@@ -0,0 +1,223 @@
+// src/memory_optimizer/watchdog.rs
+
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::persistence;
+
+const WATCHDOG_FILE: &str = "memory_watchdog.json";
+const MIN_COOLDOWN_SECS: u64 = 60;
+const MIN_POLL_SECS: u64 = 5;
+const MAX_POLL_SECS: u64 = 3600;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WatchdogConfig {
+    pub enabled: bool,
+    /// Memory pressure, in percent, at which the watchdog warns.
+    pub warning_percent: f32,
+    pub critical_percent: f32,
+    /// Minimum time between repeated events for the same level, and between
+    /// automatic optimizations.
+    pub cooldown_secs: u64,
+    /// Clear inactive memory when pressure turns critical.
+    pub auto_optimize: bool,
+    pub poll_secs: u64,
+}
+
+impl Default for WatchdogConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            warning_percent: 75.0,
+            critical_percent: 90.0,
+            cooldown_secs: 15 * 60,
+            auto_optimize: false,
+            poll_secs: 30,
+        }
+    }
+}
+
+impl WatchdogConfig {
+    fn validate(&self) -> Result<(), String> {
+        if !(0.0 < self.warning_percent
+            && self.warning_percent < self.critical_percent
+            && self.critical_percent <= 100.0)
+        {
+            return Err("Thresholds must satisfy 0 < warning < critical <= 100".into());
+        }
+        if self.cooldown_secs < MIN_COOLDOWN_SECS {
+            return Err(format!(
+                "Cooldown must be at least {} seconds",
+                MIN_COOLDOWN_SECS
+            ));
+        }
+        if !(MIN_POLL_SECS..=MAX_POLL_SECS).contains(&self.poll_secs) {
+            return Err(format!(
+                "Poll interval must be between {} and {} seconds",
+                MIN_POLL_SECS, MAX_POLL_SECS
+            ));
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PressureLevel {
+    #[default]
+    Normal,
+    Warning,
+    Critical,
+}
+
+impl PressureLevel {
+    pub fn name(&self) -> &'static str {
+        match self {
+            PressureLevel::Normal => "normal",
+            PressureLevel::Warning => "warning",
+            PressureLevel::Critical => "critical",
+        }
+    }
+}
+
+/// Payload of the `memory:pressure` event.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PressureEvent {
+    pub level: PressureLevel,
+    pub pressure_percent: f32,
+    /// Unix seconds.
+    pub at: i64,
+    /// An automatic optimization starts right after this event.
+    pub auto_optimizing: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WatchdogStatus {
+    pub config: WatchdogConfig,
+    pub level: PressureLevel,
+    pub pressure_percent: Option<f32>,
+    pub last_checked_at: Option<i64>,
+    pub last_event_at: Option<i64>,
+    pub last_optimization_at: Option<i64>,
+    pub optimizations_run: u32,
+    /// Summary or error of the last automatic optimization.
+    pub last_optimization_result: Option<String>,
+}
+
+/// Memory pressure levels over time, deciding when to notify and when to
+/// optimize.
+pub struct MemoryWatchdog {
+    path: Option<PathBuf>,
+    status: WatchdogStatus,
+}
+
+impl Default for MemoryWatchdog {
+    fn default() -> Self {
+        Self {
+            path: None,
+            status: WatchdogStatus {
+                config: WatchdogConfig::default(),
+                level: PressureLevel::Normal,
+                pressure_percent: None,
+                last_checked_at: None,
+                last_event_at: None,
+                last_optimization_at: None,
+                optimizations_run: 0,
+                last_optimization_result: None,
+            },
+        }
+    }
+}
+
+impl MemoryWatchdog {
+    /// Load the configuration from `<data dir>/macos-optimizer/memory_watchdog.json`.
+    pub fn load() -> Self {
+        match persistence::data_file(WATCHDOG_FILE) {
+            Some(path) => Self::load_from(&path),
+            None => Self::default(),
+        }
+    }
+
+    /// Load from an explicit file; a missing or invalid file yields the defaults.
+    pub fn load_from(path: &Path) -> Self {
+        let config = match persistence::read_json::<WatchdogConfig>(path) {
+            Some(config) if config.validate().is_err() => {
+                log::warn!("Ignoring invalid {}", path.display());
+                WatchdogConfig::default()
+            }
+            config => config.unwrap_or_default(),
+        };
+        let mut watchdog = Self {
+            path: Some(path.to_path_buf()),
+            ..Self::default()
+        };
+        watchdog.status.config = config;
+        watchdog
+    }
+
+    pub fn config(&self) -> &WatchdogConfig {
+        &self.status.config
+    }
+
+    pub fn status(&self) -> WatchdogStatus {
+        self.status.clone()
+    }
+
+    pub fn configure(&mut self, config: WatchdogConfig) -> Result<(), String> {
+        config.validate()?;
+        self.status.config = config;
+        self.persist()
+    }
+
+    /// Record a pressure reading; returns the event to emit, if any. Events
+    /// fire when the level changes and repeat once per cooldown while it
+    /// stays elevated.
+    pub fn observe(&mut self, pressure_percent: f32, now: i64) -> Option<PressureEvent> {
+        let config = &self.status.config;
+        let level = if pressure_percent >= config.critical_percent {
+            PressureLevel::Critical
+        } else if pressure_percent >= config.warning_percent {
+            PressureLevel::Warning
+        } else {
+            PressureLevel::Normal
+        };
+        let cooldown = config.cooldown_secs as i64;
+        let cooled_down = |since: Option<i64>| since.is_none_or(|at| now - at >= cooldown);
+
+        let changed = level != self.status.level;
+        let repeat = level != PressureLevel::Normal && cooled_down(self.status.last_event_at);
+        let auto_optimizing = config.auto_optimize
+            && level == PressureLevel::Critical
+            && cooled_down(self.status.last_optimization_at);
+
+        self.status.level = level;
+        self.status.pressure_percent = Some(pressure_percent);
+        self.status.last_checked_at = Some(now);
+        if !(changed || repeat || auto_optimizing) {
+            return None;
+        }
+        self.status.last_event_at = Some(now);
+        if auto_optimizing {
+            self.status.last_optimization_at = Some(now);
+        }
+        Some(PressureEvent {
+            level,
+            pressure_percent,
+            at: now,
+            auto_optimizing,
+        })
+    }
+
+    pub fn record_optimization(&mut self, result: String) {
+        self.status.optimizations_run += 1;
+        self.status.last_optimization_result = Some(result);
+    }
+
+    fn persist(&self) -> Result<(), String> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        persistence::write_json(path, &self.status.config)
+    }
+}
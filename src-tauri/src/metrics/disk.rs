@@ -2,9 +2,24 @@ use std::path::Path;
 use std::time::{Duration, Instant};
 
 use chrono::Utc;
-use sysinfo::{DiskKind, Disks};
+use sysinfo::{DiskKind, Disks, ProcessRefreshKind, System};
 
-use super::types::{DiskSnapshot, SampleEnvelope};
+use super::types::{DiskIoSnapshot, DiskSnapshot, SampleEnvelope};
+
+/// When the per-process I/O counters were last refreshed; sysinfo reports
+/// the bytes moved since then.
+pub struct DiskIoSamplerState {
+    last_refresh: Instant,
+}
+
+impl DiskIoSamplerState {
+    /// Call right after the processes were refreshed with disk usage.
+    pub fn new() -> Self {
+        DiskIoSamplerState {
+            last_refresh: Instant::now(),
+        }
+    }
+}
 
 pub fn collect_disk_sample() -> SampleEnvelope<Vec<DiskSnapshot>> {
     let started = Instant::now();
@@ -64,3 +79,47 @@ pub fn collect_disk_sample() -> SampleEnvelope<Vec<DiskSnapshot>> {
         source,
     )
 }
+
+/// Disk throughput since the previous call, summed over every process.
+pub fn collect_disk_io_sample(
+    system: &mut System,
+    state: &mut DiskIoSamplerState,
+) -> SampleEnvelope<DiskIoSnapshot> {
+    let started = Instant::now();
+    let source = "sysinfo::process_disk_usage";
+
+    system.refresh_processes_specifics(ProcessRefreshKind::new().with_disk_usage());
+    let elapsed = started.duration_since(state.last_refresh);
+    state.last_refresh = started;
+    let secs = elapsed.as_secs_f64();
+    if secs <= 0.0 {
+        let now = Utc::now();
+        return SampleEnvelope::errored(
+            now,
+            Duration::from_millis(5_000),
+            started.elapsed(),
+            source,
+            "no time passed since the previous sample".to_string(),
+        );
+    }
+
+    let (read, written) = system
+        .processes()
+        .values()
+        .map(|process| process.disk_usage())
+        .fold((0u64, 0u64), |(read, written), usage| {
+            (
+                read.saturating_add(usage.read_bytes),
+                written.saturating_add(usage.written_bytes),
+            )
+        });
+    let snapshot = DiskIoSnapshot {
+        interval_ms: elapsed.as_millis().min(u64::MAX as u128) as u64,
+        read_bytes_per_sec: read as f64 / secs,
+        write_bytes_per_sec: written as f64 / secs,
+    };
+
+    let now = Utc::now();
+    let latency = started.elapsed();
+    SampleEnvelope::fresh(snapshot, now, Duration::from_millis(5_000), latency, source)
+}
@@ -0,0 +1,293 @@
+use std::collections::VecDeque;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Duration as ChronoDuration, NaiveDate, Utc};
+use log::warn;
+use serde::{Deserialize, Serialize};
+
+use super::types::MetricsSnapshot;
+use crate::persistence;
+
+/// How often a point is added to the history.
+pub const RECORD_INTERVAL_SECS: u64 = 60;
+/// Points kept in memory: one day at the record interval.
+const RING_CAPACITY: usize = 24 * 60 * 60 / RECORD_INTERVAL_SECS as usize;
+/// Day segments older than this are deleted.
+const RETENTION_DAYS: i64 = 30;
+const SEGMENT_PREFIX: &str = "metrics-";
+const SEGMENT_SUFFIX: &str = ".jsonl";
+
+/// One recorded sample. Fields are `None` when that sampler had no value.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct HistoryPoint {
+    pub at: i64,
+    pub memory_pressure: Option<f32>,
+    pub memory_used: Option<u64>,
+    pub swap_used: Option<u64>,
+    pub cpu_usage: Option<f32>,
+    /// Used space on the system volume.
+    pub disk_used: Option<u64>,
+    pub disk_total: Option<u64>,
+}
+
+impl HistoryPoint {
+    pub fn from_snapshot(snapshot: &MetricsSnapshot) -> Self {
+        let memory = snapshot.memory.value.as_ref();
+        let disk = snapshot.disks.value.as_ref().and_then(|disks| {
+            disks
+                .iter()
+                .find(|disk| disk.is_system)
+                .or_else(|| disks.first())
+        });
+        Self {
+            at: snapshot.captured_at.timestamp(),
+            memory_pressure: memory.map(|stats| stats.pressure_percent),
+            memory_used: memory.map(|stats| stats.used),
+            swap_used: memory.map(|stats| stats.swap_used),
+            cpu_usage: snapshot.cpu.value.as_ref().map(|cpu| cpu.total_usage),
+            disk_used: disk.map(|disk| disk.used_space),
+            disk_total: disk.map(|disk| disk.total_space),
+        }
+    }
+}
+
+/// Span of history to chart.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HistoryRange {
+    Hour,
+    SixHours,
+    Day,
+    Week,
+    Month,
+}
+
+impl HistoryRange {
+    pub fn secs(self) -> i64 {
+        match self {
+            HistoryRange::Hour => 60 * 60,
+            HistoryRange::SixHours => 6 * 60 * 60,
+            HistoryRange::Day => 24 * 60 * 60,
+            HistoryRange::Week => 7 * 24 * 60 * 60,
+            HistoryRange::Month => RETENTION_DAYS * 24 * 60 * 60,
+        }
+    }
+
+    /// Bucket width that keeps a chart to a few hundred points.
+    pub fn default_resolution(self) -> u64 {
+        match self {
+            HistoryRange::Hour => 60,
+            HistoryRange::SixHours => 5 * 60,
+            HistoryRange::Day => 10 * 60,
+            HistoryRange::Week => 60 * 60,
+            HistoryRange::Month => 4 * 60 * 60,
+        }
+    }
+}
+
+/// Recent samples in a ring buffer, backed by one JSON-lines segment per day.
+pub struct MetricsHistory {
+    dir: Option<PathBuf>,
+    recent: VecDeque<HistoryPoint>,
+}
+
+impl MetricsHistory {
+    /// Open `<data dir>/macos-optimizer/metrics/`.
+    pub fn load() -> Self {
+        match persistence::data_dir() {
+            Some(dir) => Self::load_from(&dir.join("metrics")),
+            None => Self {
+                dir: None,
+                recent: VecDeque::new(),
+            },
+        }
+    }
+
+    /// Open an explicit segment directory, warming the ring buffer from the
+    /// last day of segments and dropping expired ones.
+    pub fn load_from(dir: &Path) -> Self {
+        let mut history = Self {
+            dir: Some(dir.to_path_buf()),
+            recent: VecDeque::with_capacity(RING_CAPACITY),
+        };
+        let now = Utc::now().timestamp();
+        history.prune_segments(now);
+        for point in history.read_segments(now - HistoryRange::Day.secs(), now) {
+            history.push_recent(point);
+        }
+        history
+    }
+
+    pub fn record(&mut self, point: HistoryPoint) -> Result<(), String> {
+        let new_day = self
+            .recent
+            .back()
+            .is_none_or(|last| day_of(last.at) != day_of(point.at));
+        self.push_recent(point.clone());
+        let Some(dir) = &self.dir else {
+            return Ok(());
+        };
+        fs::create_dir_all(dir)
+            .map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+        let path = segment_path(dir, day_of(point.at));
+        let mut line = serde_json::to_vec(&point)
+            .map_err(|e| format!("Failed to serialize metrics sample: {}", e))?;
+        line.push(b'\n');
+        OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .and_then(|mut file| file.write_all(&line))
+            .map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+        if new_day {
+            self.prune_segments(point.at);
+        }
+        Ok(())
+    }
+
+    /// Points in `range` before `now`, averaged into buckets of
+    /// `resolution_secs` (never finer than the record interval).
+    pub fn query(
+        &self,
+        range: HistoryRange,
+        resolution_secs: Option<u64>,
+        now: i64,
+    ) -> Vec<HistoryPoint> {
+        let from = now - range.secs();
+        let resolution = resolution_secs
+            .unwrap_or_else(|| range.default_resolution())
+            .max(RECORD_INTERVAL_SECS) as i64;
+        let covered_by_ring = self.recent.front().is_some_and(|first| first.at <= from);
+        let points: Vec<HistoryPoint> = if covered_by_ring {
+            self.recent
+                .iter()
+                .filter(|point| point.at > from && point.at <= now)
+                .cloned()
+                .collect()
+        } else {
+            self.read_segments(from, now)
+        };
+        downsample(&points, resolution)
+    }
+
+    fn push_recent(&mut self, point: HistoryPoint) {
+        if self.recent.len() == RING_CAPACITY {
+            self.recent.pop_front();
+        }
+        self.recent.push_back(point);
+    }
+
+    fn read_segments(&self, from: i64, to: i64) -> Vec<HistoryPoint> {
+        let Some(dir) = &self.dir else {
+            return Vec::new();
+        };
+        let mut points = Vec::new();
+        let (Some(mut day), Some(last)) = (day_of(from), day_of(to)) else {
+            return points;
+        };
+        while day <= last {
+            let path = segment_path(dir, Some(day));
+            if let Ok(data) = fs::read_to_string(&path) {
+                let mut malformed = 0;
+                for line in data.lines().filter(|line| !line.trim().is_empty()) {
+                    match serde_json::from_str::<HistoryPoint>(line) {
+                        Ok(point) if point.at > from && point.at <= to => points.push(point),
+                        Ok(_) => {}
+                        Err(_) => malformed += 1,
+                    }
+                }
+                if malformed > 0 {
+                    warn!(
+                        "Skipped {} malformed samples in {}",
+                        malformed,
+                        path.display()
+                    );
+                }
+            }
+            day += ChronoDuration::days(1);
+        }
+        points.sort_by_key(|point| point.at);
+        points
+    }
+
+    fn prune_segments(&self, now: i64) {
+        let (Some(dir), Some(today)) = (&self.dir, day_of(now)) else {
+            return;
+        };
+        let Ok(entries) = fs::read_dir(dir) else {
+            return;
+        };
+        let cutoff = today - ChronoDuration::days(RETENTION_DAYS);
+        for entry in entries.filter_map(Result::ok) {
+            let name = entry.file_name().to_string_lossy().to_string();
+            let expired = name
+                .strip_prefix(SEGMENT_PREFIX)
+                .and_then(|rest| rest.strip_suffix(SEGMENT_SUFFIX))
+                .and_then(|date| NaiveDate::parse_from_str(date, "%Y-%m-%d").ok())
+                .is_some_and(|date| date < cutoff);
+            if expired {
+                if let Err(err) = fs::remove_file(entry.path()) {
+                    warn!("Failed to remove {}: {}", entry.path().display(), err);
+                }
+            }
+        }
+    }
+}
+
+fn day_of(at: i64) -> Option<NaiveDate> {
+    DateTime::<Utc>::from_timestamp(at, 0).map(|time| time.date_naive())
+}
+
+fn segment_path(dir: &Path, day: Option<NaiveDate>) -> PathBuf {
+    let day = day.unwrap_or_default();
+    dir.join(format!(
+        "{}{}{}",
+        SEGMENT_PREFIX,
+        day.format("%Y-%m-%d"),
+        SEGMENT_SUFFIX
+    ))
+}
+
+/// Average consecutive points into buckets aligned to multiples of
+/// `resolution` seconds; each bucket is stamped with its start.
+pub fn downsample(points: &[HistoryPoint], resolution: i64) -> Vec<HistoryPoint> {
+    let mut buckets: Vec<Vec<&HistoryPoint>> = Vec::new();
+    for point in points {
+        let start = point.at - point.at.rem_euclid(resolution);
+        match buckets.last_mut() {
+            Some(bucket) if bucket[0].at - bucket[0].at.rem_euclid(resolution) == start => {
+                bucket.push(point)
+            }
+            _ => buckets.push(vec![point]),
+        }
+    }
+    buckets
+        .into_iter()
+        .map(|bucket| {
+            let first = bucket[0].at;
+            HistoryPoint {
+                at: first - first.rem_euclid(resolution),
+                memory_pressure: mean_f32(bucket.iter().filter_map(|p| p.memory_pressure)),
+                memory_used: mean_u64(bucket.iter().filter_map(|p| p.memory_used)),
+                swap_used: mean_u64(bucket.iter().filter_map(|p| p.swap_used)),
+                cpu_usage: mean_f32(bucket.iter().filter_map(|p| p.cpu_usage)),
+                disk_used: mean_u64(bucket.iter().filter_map(|p| p.disk_used)),
+                disk_total: mean_u64(bucket.iter().filter_map(|p| p.disk_total)),
+            }
+        })
+        .collect()
+}
+
+fn mean_f32(values: impl Iterator<Item = f32>) -> Option<f32> {
+    let (sum, count) = values.fold((0.0, 0u32), |(sum, count), v| (sum + v, count + 1));
+    (count > 0).then(|| sum / count as f32)
+}
+
+fn mean_u64(values: impl Iterator<Item = u64>) -> Option<u64> {
+    let (sum, count) = values.fold((0u128, 0u128), |(sum, count), v| {
+        (sum + v as u128, count + 1)
+    });
+    (count > 0).then(|| (sum / count) as u64)
+}
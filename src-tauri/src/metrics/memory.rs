@@ -10,7 +10,7 @@ use libc::{
     HOST_VM_INFO64_COUNT,
 };
 
-use super::types::{MemoryStats, SampleEnvelope};
+use super::types::{MemoryPressureLevel, MemoryStats, SampleEnvelope, VmActivity, VmActivityRates};
 
 #[derive(Debug)]
 pub enum MemorySampleError {
@@ -33,23 +33,142 @@ impl fmt::Display for MemorySampleError {
 
 impl std::error::Error for MemorySampleError {}
 
+/// `struct xsw_usage`, as `vm.swapusage` returns it.
 #[repr(C)]
-struct XswUsage {
+#[derive(Debug, Clone, Copy, Default)]
+pub(crate) struct XswUsage {
+    pub(crate) total: u64,
+    pub(crate) avail: u64,
+    pub(crate) used: u64,
+    pub(crate) pagesize: i32,
+    pub(crate) encrypted: i32,
+}
+
+/// The `vm_statistics64` page counts a sample is built from.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub(crate) struct PageCounts {
+    pub(crate) free: u64,
+    pub(crate) active: u64,
+    pub(crate) inactive: u64,
+    pub(crate) speculative: u64,
+    pub(crate) purgeable: u64,
+    pub(crate) wired: u64,
+    /// Pages the compressor occupies.
+    pub(crate) compressor: u64,
+    /// Pages stored in the compressor, before compression.
+    pub(crate) uncompressed_in_compressor: u64,
+    /// File-backed pages.
+    pub(crate) external: u64,
+    pub(crate) pageins: u64,
+    pub(crate) pageouts: u64,
+    pub(crate) swapins: u64,
+    pub(crate) swapouts: u64,
+    pub(crate) compressions: u64,
+    pub(crate) decompressions: u64,
+}
+
+impl PageCounts {
+    fn from_vm_statistics(stats: &vm_statistics64) -> Self {
+        Self {
+            free: stats.free_count as u64,
+            active: stats.active_count as u64,
+            inactive: stats.inactive_count as u64,
+            speculative: stats.speculative_count as u64,
+            purgeable: stats.purgeable_count as u64,
+            wired: stats.wire_count as u64,
+            compressor: stats.compressor_page_count as u64,
+            uncompressed_in_compressor: stats.total_uncompressed_pages_in_compressor,
+            external: stats.external_page_count as u64,
+            pageins: stats.pageins,
+            pageouts: stats.pageouts,
+            swapins: stats.swapins,
+            swapouts: stats.swapouts,
+            compressions: stats.compressions,
+            decompressions: stats.decompressions,
+        }
+    }
+}
+
+/// Turn raw kernel counters into a [`MemoryStats`].
+pub(crate) fn memory_stats_from(
+    pages: &PageCounts,
+    page_size: u64,
     total: u64,
-    avail: u64,
-    used: u64,
-    pagesize: i32,
-    encrypted: i32,
+    swap: &XswUsage,
+    pressure_level: MemoryPressureLevel,
+) -> MemoryStats {
+    let available = (pages.free + pages.inactive + pages.speculative + pages.purgeable) * page_size;
+    let used = total.saturating_sub(available);
+    let compressed = pages.compressor * page_size;
+    let percent_of_total = |bytes: u64| {
+        if total > 0 {
+            (bytes as f64 / total as f64 * 100.0) as f32
+        } else {
+            0.0
+        }
+    };
+    let pressure_percent = percent_of_total(used);
+    MemoryStats {
+        total,
+        used,
+        available,
+        wired: pages.wired * page_size,
+        compressed,
+        swap_total: swap.total,
+        swap_used: swap.used,
+        swap_free: swap.avail,
+        app_memory: pages.active * page_size,
+        cache_files: pages.external * page_size,
+        pressure_percent,
+        pressure_state: MemoryStats::pressure_state(pressure_percent),
+        speculative: pages.speculative * page_size,
+        compressor_stored: pages.uncompressed_in_compressor * page_size,
+        compressor_occupancy: percent_of_total(compressed),
+        pressure_level,
+        activity: VmActivity {
+            pageins: pages.pageins * page_size,
+            pageouts: pages.pageouts * page_size,
+            swapins: pages.swapins * page_size,
+            swapouts: pages.swapouts * page_size,
+            compressions: pages.compressions * page_size,
+            decompressions: pages.decompressions * page_size,
+        },
+        activity_rates: None,
+    }
+}
+
+/// Fill in `sample`'s activity rates from the sample taken before it.
+#[cfg(feature = "app")]
+pub(crate) fn with_activity_rates(
+    mut sample: SampleEnvelope<MemoryStats>,
+    previous: &SampleEnvelope<MemoryStats>,
+) -> SampleEnvelope<MemoryStats> {
+    let (Some(stats), Some(earlier)) = (sample.value.as_mut(), previous.value.as_ref()) else {
+        return sample;
+    };
+    let elapsed = (sample.collected_at - previous.collected_at)
+        .to_std()
+        .unwrap_or_default();
+    stats.activity_rates = VmActivityRates::between(&earlier.activity, &stats.activity, elapsed);
+    sample
 }
 
 pub fn collect_memory_sample() -> SampleEnvelope<MemoryStats> {
     let started = Instant::now();
     let source = "mach::host_statistics64";
+    let errored = |error: String| {
+        SampleEnvelope::errored(
+            Utc::now(),
+            Duration::from_millis(5_000),
+            started.elapsed(),
+            source,
+            error,
+        )
+    };
 
-    unsafe {
+    let stats = unsafe {
         #[allow(deprecated)]
         let host = libc::mach_host_self();
-
         let mut stats = MaybeUninit::<vm_statistics64>::uninit();
         let mut count: mach_msg_type_number_t = HOST_VM_INFO64_COUNT;
         let result = host_statistics64(
@@ -59,91 +178,46 @@ pub fn collect_memory_sample() -> SampleEnvelope<MemoryStats> {
             &mut count,
         );
         if result != 0 {
-            let now = Utc::now();
-            let latency = started.elapsed();
-            return SampleEnvelope::errored(
-                now,
-                Duration::from_millis(5_000),
-                latency,
-                source,
+            return errored(
                 MemorySampleError::MachCallFailed("host_statistics64", result).to_string(),
             );
         }
-        let stats = stats.assume_init();
+        stats.assume_init()
+    };
 
-        let page_size_raw = libc::sysconf(libc::_SC_PAGESIZE);
-        let page_size = if page_size_raw > 0 {
-            page_size_raw as u64
-        } else {
-            4096
-        };
-
-        let total = match read_sysctl_u64("hw.memsize") {
-            Ok(value) => value,
-            Err(err) => {
-                let now = Utc::now();
-                let latency = started.elapsed();
-                return SampleEnvelope::errored(
-                    now,
-                    Duration::from_millis(5_000),
-                    latency,
-                    source,
-                    err.to_string(),
-                );
-            }
-        };
-
-        let swap = match read_swap_usage() {
-            Ok(value) => value,
-            Err(err) => {
-                let now = Utc::now();
-                let latency = started.elapsed();
-                return SampleEnvelope::errored(
-                    now,
-                    Duration::from_millis(5_000),
-                    latency,
-                    source,
-                    err.to_string(),
-                );
-            }
-        };
-
-        let free = stats.free_count as u64 * page_size;
-        let inactive = stats.inactive_count as u64 * page_size;
-        let speculative = stats.speculative_count as u64 * page_size;
-        let purgeable = stats.purgeable_count as u64 * page_size;
-        let wired = stats.wire_count as u64 * page_size;
-        let active = stats.active_count as u64 * page_size;
-        let compressed = stats.compressor_page_count as u64 * page_size;
-        let external = stats.external_page_count as u64 * page_size;
-
-        let available = free + inactive + speculative + purgeable;
-        let used = total.saturating_sub(available);
-        let pressure_percent = if total > 0 {
-            (used as f64 / total as f64 * 100.0) as f32
-        } else {
-            0.0
-        };
-
-        let snapshot = MemoryStats {
-            total,
-            used,
-            available,
-            wired,
-            compressed,
-            swap_total: swap.total,
-            swap_used: swap.used,
-            swap_free: swap.avail,
-            app_memory: active,
-            cache_files: external,
-            pressure_percent,
-            pressure_state: MemoryStats::pressure_state(pressure_percent),
-        };
-
-        let now = Utc::now();
-        let latency = started.elapsed();
-        SampleEnvelope::fresh(snapshot, now, Duration::from_millis(5_000), latency, source)
-    }
+    let page_size_raw = unsafe { libc::sysconf(libc::_SC_PAGESIZE) };
+    let page_size = if page_size_raw > 0 {
+        page_size_raw as u64
+    } else {
+        4096
+    };
+    let total = match read_sysctl_u64("hw.memsize") {
+        Ok(value) => value,
+        Err(err) => return errored(err.to_string()),
+    };
+    let swap = match read_swap_usage() {
+        Ok(value) => value,
+        Err(err) => return errored(err.to_string()),
+    };
+    // Missing on older systems; the percentage still stands on its own.
+    let pressure_level = read_sysctl_i32("kern.memorystatus_vm_pressure_level")
+        .map(MemoryPressureLevel::from_kernel)
+        .unwrap_or_default();
+
+    let snapshot = memory_stats_from(
+        &PageCounts::from_vm_statistics(&stats),
+        page_size,
+        total,
+        &swap,
+        pressure_level,
+    );
+    SampleEnvelope::fresh(
+        snapshot,
+        Utc::now(),
+        Duration::from_millis(5_000),
+        started.elapsed(),
+        source,
+    )
 }
 
 fn read_sysctl_u64(name: &str) -> Result<u64, MemorySampleError> {
@@ -168,6 +242,28 @@ fn read_sysctl_u64(name: &str) -> Result<u64, MemorySampleError> {
     Ok(value)
 }
 
+fn read_sysctl_i32(name: &str) -> Result<i32, MemorySampleError> {
+    let c_name = CString::new(name).expect("sysctl name");
+    let mut size: libc::size_t = std::mem::size_of::<i32>() as libc::size_t;
+    let mut value: i32 = 0;
+    let result = unsafe {
+        libc::sysctlbyname(
+            c_name.as_ptr(),
+            &mut value as *mut i32 as *mut c_void,
+            &mut size as *mut libc::size_t,
+            ptr::null_mut(),
+            0,
+        )
+    };
+    if result != 0 {
+        return Err(MemorySampleError::SysctlError(
+            name.to_string(),
+            std::io::Error::last_os_error(),
+        ));
+    }
+    Ok(value)
+}
+
 fn read_swap_usage() -> Result<XswUsage, MemorySampleError> {
     let c_name = CString::new("vm.swapusage").expect("sysctl name");
     let mut usage = MaybeUninit::<XswUsage>::uninit();
@@ -1,12 +1,23 @@
+#[cfg(feature = "app")]
 mod cpu;
+#[cfg(feature = "app")]
 mod disk;
+#[cfg(feature = "app")]
+mod history;
 mod memory;
+#[cfg(feature = "app")]
 mod sampler;
 #[cfg(test)]
 mod tests;
 mod types;
+#[cfg(feature = "app")]
 mod uptime;
 
+#[cfg(feature = "app")]
+pub use history::{HistoryPoint, HistoryRange, MetricsHistory, RECORD_INTERVAL_SECS};
 pub use memory::collect_memory_sample;
-pub use sampler::MetricsSampler;
-pub use types::{CpuSnapshot, DiskSnapshot, MemoryStats, MetricsSnapshot, SampleEnvelope};
+#[cfg(feature = "app")]
+pub use sampler::{stream_interval, MetricsSampler};
+#[cfg(feature = "app")]
+pub use types::{CpuSnapshot, DiskSnapshot, MemoryPressureBreakdown, MetricsSnapshot};
+pub use types::{MemoryStats, SampleEnvelope};
@@ -6,23 +6,29 @@ use chrono::Utc;
 use log::warn;
 use sysinfo::System;
 use tokio::select;
-use tokio::sync::{Notify, RwLock};
+use tokio::sync::{watch, Notify, RwLock};
 use tokio::time::{interval, MissedTickBehavior};
 use tokio_util::sync::CancellationToken;
 
 use super::cpu::{collect_cpu_sample, CpuSamplerState};
-use super::disk::collect_disk_sample;
-use super::memory::collect_memory_sample;
+use super::disk::{collect_disk_io_sample, collect_disk_sample, DiskIoSamplerState};
+use super::memory::{collect_memory_sample, with_activity_rates};
 use super::types::MetricsSnapshot;
 use super::uptime::collect_uptime_sample;
 
 const CPU_PERIOD: Duration = Duration::from_secs(1);
 const MEMORY_PERIOD: Duration = Duration::from_secs(5);
 const DISK_PERIOD: Duration = Duration::from_secs(30);
+const DISK_IO_PERIOD: Duration = Duration::from_secs(5);
 const UPTIME_PERIOD: Duration = Duration::from_secs(1);
+/// Bounds for the push interval of `metrics:sample` streams.
+const MIN_STREAM_INTERVAL: Duration = Duration::from_millis(250);
+const MAX_STREAM_INTERVAL: Duration = Duration::from_secs(60);
+const DEFAULT_STREAM_INTERVAL: Duration = Duration::from_secs(1);
 
 pub struct MetricsSamplerHandle {
     snapshot: Arc<RwLock<MetricsSnapshot>>,
+    updates: watch::Sender<MetricsSnapshot>,
     ready: Arc<AtomicBool>,
     notify_ready: Arc<Notify>,
     cancel: CancellationToken,
@@ -35,9 +41,11 @@ impl MetricsSamplerHandle {
         let ready = Arc::new(AtomicBool::new(false));
         let notify_ready = Arc::new(Notify::new());
         let cancel = CancellationToken::new();
+        let (updates, _) = watch::channel(MetricsSnapshot::stale());
 
         let inner = Arc::new(MetricsSamplerInner {
             snapshot: Arc::clone(&snapshot),
+            updates: updates.clone(),
             ready: Arc::clone(&ready),
             notify_ready: Arc::clone(&notify_ready),
             cancel: cancel.clone(),
@@ -64,6 +72,7 @@ impl MetricsSamplerHandle {
 
         MetricsSamplerHandle {
             snapshot,
+            updates,
             ready,
             notify_ready,
             cancel,
@@ -75,6 +84,12 @@ impl MetricsSamplerHandle {
         self.snapshot.read().await.clone()
     }
 
+    /// Receiver that sees every stored snapshot without taking the snapshot
+    /// lock; used to push samples to the UI.
+    pub fn subscribe(&self) -> watch::Receiver<MetricsSnapshot> {
+        self.updates.subscribe()
+    }
+
     pub async fn wait_until_ready(&self) {
         if self.ready.load(Ordering::SeqCst) {
             return;
@@ -85,6 +100,7 @@ impl MetricsSamplerHandle {
 
 struct MetricsSamplerInner {
     snapshot: Arc<RwLock<MetricsSnapshot>>,
+    updates: watch::Sender<MetricsSnapshot>,
     ready: Arc<AtomicBool>,
     notify_ready: Arc<Notify>,
     cancel: CancellationToken,
@@ -95,6 +111,7 @@ impl MetricsSamplerInner {
         let mut system = System::new_all();
         system.refresh_all();
         let mut cpu_state = CpuSamplerState::new(12);
+        let mut disk_io_state = DiskIoSamplerState::new();
         let mut current = MetricsSnapshot::stale();
 
         current.cpu = collect_cpu_sample(&mut system, &mut cpu_state).await;
@@ -118,6 +135,10 @@ impl MetricsSamplerInner {
         disk_interval.set_missed_tick_behavior(MissedTickBehavior::Skip);
         disk_interval.tick().await;
 
+        let mut disk_io_interval = interval(DISK_IO_PERIOD);
+        disk_io_interval.set_missed_tick_behavior(MissedTickBehavior::Skip);
+        disk_io_interval.tick().await;
+
         let mut uptime_interval = interval(UPTIME_PERIOD);
         uptime_interval.set_missed_tick_behavior(MissedTickBehavior::Skip);
         uptime_interval.tick().await;
@@ -136,7 +157,7 @@ impl MetricsSamplerInner {
                     self.store_snapshot(&current).await;
                 }
                 _ = memory_interval.tick() => {
-                    current.memory = collect_memory_sample();
+                    current.memory = with_activity_rates(collect_memory_sample(), &current.memory);
                     if let Some(err) = current.memory.error.as_ref() {
                         warn!("memory sampler error: {}", err);
                     }
@@ -151,6 +172,14 @@ impl MetricsSamplerInner {
                     current.captured_at = Utc::now();
                     self.store_snapshot(&current).await;
                 }
+                _ = disk_io_interval.tick() => {
+                    current.disk_io = collect_disk_io_sample(&mut system, &mut disk_io_state);
+                    if let Some(err) = current.disk_io.error.as_ref() {
+                        warn!("disk io sampler error: {}", err);
+                    }
+                    current.captured_at = Utc::now();
+                    self.store_snapshot(&current).await;
+                }
                 _ = uptime_interval.tick() => {
                     current.uptime = collect_uptime_sample(&mut system);
                     if let Some(err) = current.uptime.error.as_ref() {
@@ -166,11 +195,21 @@ impl MetricsSamplerInner {
     async fn store_snapshot(&self, snapshot: &MetricsSnapshot) {
         let mut guard = self.snapshot.write().await;
         *guard = snapshot.clone();
+        self.updates.send_replace(snapshot.clone());
     }
 }
 
 pub type MetricsSampler = MetricsSamplerHandle;
 
+/// Push interval for a metrics stream, defaulting to one second and clamped
+/// so a stream can neither spin nor go silent.
+pub fn stream_interval(interval_ms: Option<u64>) -> Duration {
+    interval_ms
+        .map(Duration::from_millis)
+        .unwrap_or(DEFAULT_STREAM_INTERVAL)
+        .clamp(MIN_STREAM_INTERVAL, MAX_STREAM_INTERVAL)
+}
+
 impl Drop for MetricsSamplerHandle {
     fn drop(&mut self) {
         self.cancel.cancel();
@@ -1,6 +1,14 @@
 #![cfg(test)]
 
-use super::{MetricsSampler, SampleEnvelope};
+#[cfg(feature = "app")]
+use super::history::downsample;
+use super::memory::{memory_stats_from, PageCounts, XswUsage};
+use super::types::{
+    MemoryPressureBreakdown, MemoryPressureLevel, MemorySegmentKind, VmActivity, VmActivityRates,
+};
+use super::SampleEnvelope;
+#[cfg(feature = "app")]
+use super::{stream_interval, HistoryPoint, HistoryRange, MetricsHistory, MetricsSampler};
 use chrono::Utc;
 use std::time::Duration;
 
@@ -21,6 +29,7 @@ fn envelope_fresh_preserves_metadata() {
     assert!(envelope.error.is_none());
 }
 
+#[cfg(feature = "app")]
 #[tokio::test]
 async fn sampler_emits_recent_snapshot() {
     let sampler = MetricsSampler::spawn();
@@ -62,3 +71,216 @@ async fn sampler_emits_recent_snapshot() {
 
     drop(sampler);
 }
+
+#[cfg(feature = "app")]
+#[tokio::test]
+async fn subscribers_see_stored_snapshots() {
+    let sampler = MetricsSampler::spawn();
+    let mut updates = sampler.subscribe();
+    sampler.wait_until_ready().await;
+
+    tokio::time::timeout(Duration::from_secs(5), updates.changed())
+        .await
+        .expect("sampler should publish a snapshot")
+        .unwrap();
+    assert_ne!(updates.borrow().cpu.source, "uninitialized");
+}
+
+#[cfg(feature = "app")]
+#[test]
+fn stream_interval_is_clamped() {
+    assert_eq!(stream_interval(None), Duration::from_secs(1));
+    assert_eq!(stream_interval(Some(10)), Duration::from_millis(250));
+    assert_eq!(stream_interval(Some(2_000)), Duration::from_secs(2));
+    assert_eq!(stream_interval(Some(u64::MAX)), Duration::from_secs(60));
+}
+
+#[cfg(feature = "app")]
+#[test]
+fn history_downsamples_into_aligned_buckets() {
+    let point = |at, cpu| HistoryPoint {
+        at,
+        cpu_usage: Some(cpu),
+        memory_used: if at % 120 == 0 { Some(1_000) } else { None },
+        ..Default::default()
+    };
+    let points = vec![point(600, 10.0), point(660, 30.0), point(1_200, 50.0)];
+    let buckets = downsample(&points, 600);
+    assert_eq!(buckets.len(), 2);
+    assert_eq!(buckets[0].at, 600);
+    assert_eq!(buckets[0].cpu_usage, Some(20.0));
+    assert_eq!(buckets[0].memory_used, Some(1_000));
+    assert_eq!(buckets[1].at, 1_200);
+    assert_eq!(buckets[1].swap_used, None);
+}
+
+#[cfg(feature = "app")]
+#[test]
+fn history_survives_reopening() {
+    let dir = tempfile::TempDir::new().unwrap();
+    let now = chrono::Utc::now().timestamp();
+    let mut history = MetricsHistory::load_from(dir.path());
+    for minutes in (1..=3).rev() {
+        history
+            .record(HistoryPoint {
+                at: now - minutes * 60,
+                cpu_usage: Some(minutes as f32),
+                ..Default::default()
+            })
+            .unwrap();
+    }
+
+    let reopened = MetricsHistory::load_from(dir.path());
+    let points = reopened.query(HistoryRange::Hour, Some(60), now);
+    assert_eq!(points.len(), 3);
+    assert_eq!(points[0].cpu_usage, Some(3.0));
+    assert!(reopened
+        .query(HistoryRange::Hour, None, now - 3_600)
+        .is_empty());
+}
+
+#[test]
+fn memory_stats_follow_recorded_kernel_counters() {
+    // host_statistics64, hw.memsize and vm.swapusage from a 16 GB Apple
+    // silicon Mac under load; pages are 16 KiB.
+    const PAGE: u64 = 16_384;
+    let pages = PageCounts {
+        free: 12_345,
+        active: 301_234,
+        inactive: 289_876,
+        speculative: 6_543,
+        purgeable: 2_112,
+        wired: 180_456,
+        compressor: 98_765,
+        uncompressed_in_compressor: 312_345,
+        external: 270_001,
+        pageins: 4_567_890,
+        pageouts: 12_345,
+        swapins: 65_432,
+        swapouts: 98_765,
+        compressions: 3_456_789,
+        decompressions: 2_345_678,
+    };
+    let swap = XswUsage {
+        total: 2_147_483_648,
+        avail: 1_073_741_824,
+        used: 1_073_741_824,
+        pagesize: PAGE as i32,
+        encrypted: 1,
+    };
+    let stats = memory_stats_from(
+        &pages,
+        PAGE,
+        17_179_869_184,
+        &swap,
+        MemoryPressureLevel::from_kernel(2),
+    );
+
+    assert_eq!(stats.available, 310_876 * PAGE);
+    assert_eq!(stats.used, 17_179_869_184 - 310_876 * PAGE);
+    assert_eq!(stats.wired, 180_456 * PAGE);
+    assert_eq!(stats.app_memory, 301_234 * PAGE);
+    assert_eq!(stats.cache_files, 270_001 * PAGE);
+    assert_eq!(stats.speculative, 6_543 * PAGE);
+    assert_eq!(stats.compressed, 98_765 * PAGE);
+    assert_eq!(stats.compressor_stored, 312_345 * PAGE);
+    assert!((stats.compressor_occupancy - 9.42).abs() < 0.01);
+    assert!((stats.pressure_percent - 70.35).abs() < 0.01);
+    assert_eq!(stats.pressure_state, "elevated");
+    assert_eq!(stats.pressure_level, MemoryPressureLevel::Warning);
+    assert_eq!(stats.swap_used, 1_073_741_824);
+    assert_eq!(stats.swap_free, 1_073_741_824);
+    assert_eq!(stats.activity.swapins, 65_432 * PAGE);
+    assert_eq!(stats.activity.swapouts, 98_765 * PAGE);
+    assert_eq!(stats.activity.compressions, 3_456_789 * PAGE);
+    assert_eq!(stats.activity.decompressions, 2_345_678 * PAGE);
+    assert!(stats.activity_rates.is_none());
+}
+
+#[test]
+fn activity_rates_cover_the_interval_between_samples() {
+    let earlier = VmActivity {
+        pageins: 1_000,
+        pageouts: 0,
+        swapins: 4_000,
+        swapouts: 8_000,
+        compressions: 20_000,
+        decompressions: 10_000,
+    };
+    let later = VmActivity {
+        swapins: 6_000,
+        swapouts: 16_000,
+        compressions: 30_000,
+        ..earlier
+    };
+    let rates = VmActivityRates::between(&earlier, &later, Duration::from_secs(2)).unwrap();
+    assert_eq!(rates.interval_ms, 2_000);
+    assert_eq!(rates.deltas.swapins, 2_000);
+    assert_eq!(rates.deltas.decompressions, 0);
+    assert_eq!(rates.swapins_per_sec, 1_000.0);
+    assert_eq!(rates.swapouts_per_sec, 4_000.0);
+    assert_eq!(rates.compressions_per_sec, 5_000.0);
+    assert_eq!(rates.pageins_per_sec, 0.0);
+    assert!(VmActivityRates::between(&earlier, &later, Duration::ZERO).is_none());
+}
+
+#[test]
+fn pressure_breakdown_splits_memory_like_activity_monitor() {
+    const GB: u64 = 1024 * 1024 * 1024;
+    let pages = PageCounts {
+        free: GB / 4096,
+        active: 6 * GB / 4096,
+        wired: 3 * GB / 4096,
+        compressor: 2 * GB / 4096,
+        external: 4 * GB / 4096,
+        ..PageCounts::default()
+    };
+    let stats = memory_stats_from(
+        &pages,
+        4096,
+        16 * GB,
+        &XswUsage::default(),
+        MemoryPressureLevel::Normal,
+    );
+    let breakdown = MemoryPressureBreakdown::from_stats(&stats);
+    assert_eq!(breakdown.memory_used, 11 * GB);
+    let segments: Vec<(MemorySegmentKind, u64)> = breakdown
+        .segments
+        .iter()
+        .map(|segment| (segment.kind, segment.bytes))
+        .collect();
+    assert_eq!(
+        segments,
+        vec![
+            (MemorySegmentKind::App, 6 * GB),
+            (MemorySegmentKind::Wired, 3 * GB),
+            (MemorySegmentKind::Compressed, 2 * GB),
+            (MemorySegmentKind::CachedFiles, 4 * GB),
+            (MemorySegmentKind::Free, GB),
+        ]
+    );
+    assert_eq!(breakdown.segments[0].percent, 37.5);
+
+    // Counters that overlap never add up to more than total memory.
+    let mut crowded = stats.clone();
+    crowded.cache_files = 10 * GB;
+    let breakdown = MemoryPressureBreakdown::from_stats(&crowded);
+    assert_eq!(breakdown.cached_files, 5 * GB);
+    assert_eq!(breakdown.segments[4].bytes, 0);
+}
+
+#[test]
+fn kernel_pressure_levels_map_to_named_levels() {
+    assert_eq!(
+        MemoryPressureLevel::from_kernel(1),
+        MemoryPressureLevel::Normal
+    );
+    assert_eq!(
+        MemoryPressureLevel::from_kernel(4),
+        MemoryPressureLevel::Critical
+    );
+    assert_eq!(
+        MemoryPressureLevel::from_kernel(0),
+        MemoryPressureLevel::Unknown
+    );
+}
@@ -62,6 +62,206 @@ pub struct MemoryStats {
     pub cache_files: u64,
     pub pressure_percent: f32,
     pub pressure_state: String,
+    /// Read-ahead pages, counted as available.
+    #[serde(default)]
+    pub speculative: u64,
+    /// Uncompressed size of what the compressor holds; `compressed` is the
+    /// memory it occupies to do so.
+    #[serde(default)]
+    pub compressor_stored: u64,
+    /// `compressed` as a percentage of total memory.
+    #[serde(default)]
+    pub compressor_occupancy: f32,
+    /// The kernel's own verdict, which the percentage can disagree with.
+    #[serde(default)]
+    pub pressure_level: MemoryPressureLevel,
+    /// Paging, swap and compressor traffic since boot.
+    #[serde(default)]
+    pub activity: VmActivity,
+    /// The change in `activity` since the sampler's previous sample; `None`
+    /// for a one-off sample.
+    #[serde(default)]
+    pub activity_rates: Option<VmActivityRates>,
+}
+
+/// Bytes moved by the VM system since boot, from `host_statistics64`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VmActivity {
+    pub pageins: u64,
+    pub pageouts: u64,
+    pub swapins: u64,
+    pub swapouts: u64,
+    /// Bytes handed to the compressor.
+    pub compressions: u64,
+    /// Bytes taken back out of it.
+    pub decompressions: u64,
+}
+
+impl VmActivity {
+    /// What happened from `earlier` to `self`.
+    pub fn since(&self, earlier: &VmActivity) -> VmActivity {
+        VmActivity {
+            pageins: self.pageins.saturating_sub(earlier.pageins),
+            pageouts: self.pageouts.saturating_sub(earlier.pageouts),
+            swapins: self.swapins.saturating_sub(earlier.swapins),
+            swapouts: self.swapouts.saturating_sub(earlier.swapouts),
+            compressions: self.compressions.saturating_sub(earlier.compressions),
+            decompressions: self.decompressions.saturating_sub(earlier.decompressions),
+        }
+    }
+}
+
+/// VM activity over one sampling interval, as totals and per second.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct VmActivityRates {
+    pub interval_ms: u64,
+    pub deltas: VmActivity,
+    pub pageins_per_sec: f64,
+    pub pageouts_per_sec: f64,
+    pub swapins_per_sec: f64,
+    pub swapouts_per_sec: f64,
+    pub compressions_per_sec: f64,
+    pub decompressions_per_sec: f64,
+}
+
+impl VmActivityRates {
+    /// `None` when no time passed between the samples.
+    pub fn between(earlier: &VmActivity, later: &VmActivity, elapsed: Duration) -> Option<Self> {
+        let secs = elapsed.as_secs_f64();
+        if secs <= 0.0 {
+            return None;
+        }
+        let deltas = later.since(earlier);
+        let rate = |bytes: u64| bytes as f64 / secs;
+        Some(VmActivityRates {
+            interval_ms: elapsed.as_millis().min(u64::MAX as u128) as u64,
+            pageins_per_sec: rate(deltas.pageins),
+            pageouts_per_sec: rate(deltas.pageouts),
+            swapins_per_sec: rate(deltas.swapins),
+            swapouts_per_sec: rate(deltas.swapouts),
+            compressions_per_sec: rate(deltas.compressions),
+            decompressions_per_sec: rate(deltas.decompressions),
+            deltas,
+        })
+    }
+}
+
+/// Where memory goes, split like Activity Monitor's memory tab.
+#[cfg(any(test, feature = "app"))]
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct MemoryPressureBreakdown {
+    pub total: u64,
+    /// App memory, wired memory and compressed memory together.
+    pub memory_used: u64,
+    pub app_memory: u64,
+    /// Kernel memory that can never be compressed or swapped out.
+    pub wired: u64,
+    pub compressed: u64,
+    /// File-backed memory the system drops first when it needs room.
+    pub cached_files: u64,
+    pub swap_used: u64,
+    pub pressure_percent: f32,
+    pub pressure_level: MemoryPressureLevel,
+    /// App, wired, compressed, cached files and free, in that order, adding
+    /// up to `total`.
+    pub segments: Vec<MemorySegment>,
+    pub activity_rates: Option<VmActivityRates>,
+}
+
+#[cfg(any(test, feature = "app"))]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MemorySegmentKind {
+    App,
+    Wired,
+    Compressed,
+    CachedFiles,
+    Free,
+}
+
+#[cfg(any(test, feature = "app"))]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct MemorySegment {
+    pub kind: MemorySegmentKind,
+    pub bytes: u64,
+    /// Share of total memory.
+    pub percent: f32,
+}
+
+#[cfg(any(test, feature = "app"))]
+impl MemoryPressureBreakdown {
+    pub fn from_stats(stats: &MemoryStats) -> Self {
+        // The kernel's counters are sampled apart and can overlap a little;
+        // each segment is capped by what the earlier ones left.
+        let mut remaining = stats.total;
+        let mut take = |bytes: u64| {
+            let taken = bytes.min(remaining);
+            remaining -= taken;
+            taken
+        };
+        let app_memory = take(stats.app_memory);
+        let wired = take(stats.wired);
+        let compressed = take(stats.compressed);
+        let cached_files = take(stats.cache_files);
+        let free = remaining;
+        let percent = |bytes: u64| {
+            if stats.total > 0 {
+                (bytes as f64 / stats.total as f64 * 100.0) as f32
+            } else {
+                0.0
+            }
+        };
+        let segments = [
+            (MemorySegmentKind::App, app_memory),
+            (MemorySegmentKind::Wired, wired),
+            (MemorySegmentKind::Compressed, compressed),
+            (MemorySegmentKind::CachedFiles, cached_files),
+            (MemorySegmentKind::Free, free),
+        ]
+        .into_iter()
+        .map(|(kind, bytes)| MemorySegment {
+            kind,
+            bytes,
+            percent: percent(bytes),
+        })
+        .collect();
+        MemoryPressureBreakdown {
+            total: stats.total,
+            memory_used: app_memory + wired + compressed,
+            app_memory,
+            wired,
+            compressed,
+            cached_files,
+            swap_used: stats.swap_used,
+            pressure_percent: stats.pressure_percent,
+            pressure_level: stats.pressure_level,
+            segments,
+            activity_rates: stats.activity_rates.clone(),
+        }
+    }
+}
+
+/// `kern.memorystatus_vm_pressure_level`, the level the Activity Monitor
+/// memory pressure graph is colored by.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MemoryPressureLevel {
+    #[default]
+    Unknown,
+    Normal,
+    Warning,
+    Critical,
+}
+
+impl MemoryPressureLevel {
+    pub fn from_kernel(level: i32) -> Self {
+        match level {
+            1 => MemoryPressureLevel::Normal,
+            2 => MemoryPressureLevel::Warning,
+            4 => MemoryPressureLevel::Critical,
+            _ => MemoryPressureLevel::Unknown,
+        }
+    }
 }
 
 impl MemoryStats {
@@ -78,6 +278,7 @@ impl MemoryStats {
     }
 }
 
+#[cfg(feature = "app")]
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct CpuSnapshot {
     pub total_usage: f32,
@@ -90,6 +291,7 @@ pub struct CpuSnapshot {
     pub rolling_max: f32,
 }
 
+#[cfg(feature = "app")]
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct DiskSnapshot {
     pub name: String,
@@ -104,21 +306,35 @@ pub struct DiskSnapshot {
     pub is_system: bool,
 }
 
+/// Bytes read from and written to disk by all processes, per second, over
+/// one sampling interval.
+#[cfg(feature = "app")]
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct DiskIoSnapshot {
+    pub interval_ms: u64,
+    pub read_bytes_per_sec: f64,
+    pub write_bytes_per_sec: f64,
+}
+
+#[cfg(feature = "app")]
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct UptimeSnapshot {
     pub uptime_seconds: u64,
     pub boot_time_seconds: u64,
 }
 
+#[cfg(feature = "app")]
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct MetricsSnapshot {
     pub captured_at: DateTime<Utc>,
     pub cpu: SampleEnvelope<CpuSnapshot>,
     pub memory: SampleEnvelope<MemoryStats>,
     pub disks: SampleEnvelope<Vec<DiskSnapshot>>,
+    pub disk_io: SampleEnvelope<DiskIoSnapshot>,
     pub uptime: SampleEnvelope<UptimeSnapshot>,
 }
 
+#[cfg(feature = "app")]
 impl MetricsSnapshot {
     pub fn stale() -> Self {
         let now = Utc::now();
@@ -148,6 +364,14 @@ impl MetricsSnapshot {
                 latency_ms: 0,
                 error: Some("metrics sampler not yet initialised".to_string()),
             },
+            disk_io: SampleEnvelope {
+                value: None,
+                collected_at: now,
+                valid_for_ms: 5000,
+                source: "uninitialized".to_string(),
+                latency_ms: 0,
+                error: Some("metrics sampler not yet initialised".to_string()),
+            },
             uptime: SampleEnvelope {
                 value: None,
                 collected_at: now,
@@ -0,0 +1,514 @@
+// src/network_tools.rs
+
+//! DNS and network maintenance, per-interface statistics, active connections
+//! and a quick connectivity check.
+//!
+//! Flushing DNS, renewing the DHCP lease and restarting mDNSResponder need
+//! root, so the selected actions run together behind one administrator
+//! prompt and each reports on its own. Everything else only reads what
+//! `netstat`, `lsof` and `ping` print.
+
+use std::time::Instant;
+
+use serde::{Deserialize, Serialize};
+use tokio::process::Command;
+use tokio_util::sync::CancellationToken;
+
+use crate::capabilities::{self, Tool};
+use crate::elevation;
+use crate::privileged_helper::{self, is_valid_interface, HelperCommand};
+
+#[cfg(test)]
+mod tests;
+
+/// Maintenance and connectivity progress callback: `(percent, message)`.
+pub type NetworkProgressFn = dyn Fn(f32, &str) + Send + Sync;
+
+/// Hosts the connectivity test pings: two anycast resolvers, reachable by
+/// address even when DNS is broken, and one name that needs DNS.
+const CONNECTIVITY_TARGETS: &[&str] = &["1.1.1.1", "8.8.8.8", "apple.com"];
+/// Name resolved to time DNS lookups.
+const DNS_PROBE_HOST: &str = "apple.com";
+const PING_COUNT: &str = "4";
+/// Seconds each `ping` may take before it gives up.
+const PING_TIMEOUT_SECS: &str = "5";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NetworkAction {
+    FlushDns,
+    RenewDhcp,
+    ResetMdns,
+}
+
+impl NetworkAction {
+    pub const ALL: [NetworkAction; 3] = [
+        NetworkAction::FlushDns,
+        NetworkAction::RenewDhcp,
+        NetworkAction::ResetMdns,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            NetworkAction::FlushDns => "Flush DNS cache",
+            NetworkAction::RenewDhcp => "Renew DHCP lease",
+            NetworkAction::ResetMdns => "Restart mDNSResponder",
+        }
+    }
+
+    /// Marker the admin script echoes after the action's command.
+    fn key(self) -> &'static str {
+        match self {
+            NetworkAction::FlushDns => "flush_dns",
+            NetworkAction::RenewDhcp => "renew_dhcp",
+            NetworkAction::ResetMdns => "reset_mdns",
+        }
+    }
+
+    /// Shell command run as root; `interface` is already validated.
+    fn command(self, interface: Option<&str>) -> Option<String> {
+        match self {
+            NetworkAction::FlushDns => {
+                Some("dscacheutil -flushcache && killall -HUP mDNSResponder".to_string())
+            }
+            NetworkAction::RenewDhcp => interface.map(|name| format!("ipconfig set {} DHCP", name)),
+            // launchd starts it again right away.
+            NetworkAction::ResetMdns => Some("killall mDNSResponder".to_string()),
+        }
+    }
+
+    /// The same action for the privileged helper; `interface` is already
+    /// validated and only missing when no DHCP renewal was asked for.
+    fn helper_command(self, interface: Option<&str>) -> HelperCommand {
+        match self {
+            NetworkAction::FlushDns => HelperCommand::FlushDns,
+            NetworkAction::RenewDhcp => HelperCommand::RenewDhcp {
+                interface: interface.unwrap_or_default().to_string(),
+            },
+            NetworkAction::ResetMdns => HelperCommand::RestartMdns,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NetworkActionStep {
+    pub action: NetworkAction,
+    pub success: bool,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct NetworkMaintenanceResult {
+    pub steps: Vec<NetworkActionStep>,
+    /// Interface whose DHCP lease was renewed.
+    pub interface: Option<String>,
+    /// Set when inspector mode reported what would happen instead of doing it.
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+/// Totals since boot for one interface, from `netstat -ibn`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InterfaceStats {
+    pub name: String,
+    /// False for interfaces `netstat` marks as down.
+    pub up: bool,
+    pub mtu: u32,
+    /// Hardware address; none for loopback and tunnels.
+    pub address: Option<String>,
+    pub packets_in: u64,
+    pub errors_in: u64,
+    pub bytes_in: u64,
+    pub packets_out: u64,
+    pub errors_out: u64,
+    pub bytes_out: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NetworkConnection {
+    pub process: String,
+    pub pid: u32,
+    /// `TCP` or `UDP`.
+    pub protocol: String,
+    pub local: String,
+    /// None for listening and unconnected sockets.
+    pub remote: Option<String>,
+    /// TCP state such as `ESTABLISHED` or `LISTEN`.
+    pub state: Option<String>,
+}
+
+/// The summary `ping -q` prints for one host.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct PingSummary {
+    pub sent: u32,
+    pub received: u32,
+    pub loss_percent: f32,
+    pub min_ms: Option<f32>,
+    pub avg_ms: Option<f32>,
+    pub max_ms: Option<f32>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LatencyResult {
+    pub host: String,
+    /// Missing when `ping` could not run or printed no summary.
+    pub summary: Option<PingSummary>,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConnectivityReport {
+    /// Time to resolve a well-known name.
+    pub dns_ms: Option<u64>,
+    pub dns_error: Option<String>,
+    pub targets: Vec<LatencyResult>,
+    /// At least one target answered.
+    pub online: bool,
+}
+
+/// What [`run_network_maintenance`] would do, without asking for a password.
+pub async fn preview_network_maintenance(
+    actions: &[NetworkAction],
+    interface: Option<String>,
+) -> Result<NetworkMaintenanceResult, String> {
+    let interface = resolve_interface(actions, interface).await?;
+    Ok(NetworkMaintenanceResult {
+        steps: actions
+            .iter()
+            .map(|&action| NetworkActionStep {
+                action,
+                success: true,
+                error: None,
+            })
+            .collect(),
+        interface,
+        dry_run: true,
+    })
+}
+
+/// Run the selected actions as root, through the installed helper or
+/// behind one administrator prompt. A failed action is recorded and the
+/// others still run.
+pub async fn run_network_maintenance(
+    actions: &[NetworkAction],
+    interface: Option<String>,
+    token: &CancellationToken,
+    progress: Option<&NetworkProgressFn>,
+) -> Result<NetworkMaintenanceResult, String> {
+    let interface = resolve_interface(actions, interface).await?;
+    let commands: Vec<HelperCommand> = actions
+        .iter()
+        .map(|&action| action.helper_command(interface.as_deref()))
+        .collect();
+    if let Some(outcomes) = privileged_helper::send(&commands, token).await? {
+        return Ok(NetworkMaintenanceResult {
+            steps: actions
+                .iter()
+                .zip(outcomes)
+                .map(|(&action, outcome)| NetworkActionStep {
+                    action,
+                    success: outcome.ok,
+                    error: outcome.error,
+                })
+                .collect(),
+            interface,
+            dry_run: false,
+        });
+    }
+    if let Some(cb) = progress {
+        cb(10.0, "Requesting administrator privileges");
+    }
+    let script = maintenance_script(actions, interface.as_deref());
+    let output = elevation::run_as_admin(&script, token).await?;
+    if let Some(cb) = progress {
+        cb(90.0, "Checking results");
+    }
+    Ok(NetworkMaintenanceResult {
+        steps: parse_step_markers(&output, actions),
+        interface,
+        dry_run: false,
+    })
+}
+
+/// Validate the actions and, for a DHCP renewal, settle on an interface:
+/// the given one, or the one carrying the default route.
+async fn resolve_interface(
+    actions: &[NetworkAction],
+    interface: Option<String>,
+) -> Result<Option<String>, String> {
+    if actions.is_empty() {
+        return Err("No network actions selected".into());
+    }
+    if !actions.contains(&NetworkAction::RenewDhcp) {
+        return Ok(None);
+    }
+    let interface = match interface {
+        Some(name) => name,
+        None => default_interface()
+            .await
+            .ok_or("No network interface carries the default route")?,
+    };
+    if !is_valid_interface(&interface) {
+        return Err(format!("Not a network interface name: {}", interface));
+    }
+    Ok(Some(interface))
+}
+
+/// The admin script for `actions`, in order.
+fn maintenance_script(actions: &[NetworkAction], interface: Option<&str>) -> String {
+    elevation::marker_script(
+        actions
+            .iter()
+            .filter_map(|&action| Some((action.key(), action.command(interface)?))),
+    )
+}
+
+/// One step per action from the script's markers.
+pub fn parse_step_markers(output: &str, actions: &[NetworkAction]) -> Vec<NetworkActionStep> {
+    actions
+        .iter()
+        .map(|&action| {
+            let error = match elevation::marker_status(output, action.key()) {
+                Some(true) => None,
+                Some(false) => Some(format!("{} failed", action.label())),
+                None => Some(format!("{} did not run", action.label())),
+            };
+            NetworkActionStep {
+                action,
+                success: error.is_none(),
+                error,
+            }
+        })
+        .collect()
+}
+
+/// The interface carrying the default route, e.g. `en0`.
+pub async fn default_interface() -> Option<String> {
+    let output = Command::new("route")
+        .args(["-n", "get", "default"])
+        .output()
+        .await
+        .ok()?;
+    output
+        .status
+        .success()
+        .then(|| parse_default_interface(&String::from_utf8_lossy(&output.stdout)))
+        .flatten()
+}
+
+/// The `interface:` line of `route -n get default`.
+pub fn parse_default_interface(output: &str) -> Option<String> {
+    output.lines().find_map(|line| {
+        line.trim()
+            .strip_prefix("interface:")
+            .map(|name| name.trim().to_string())
+            .filter(|name| !name.is_empty())
+    })
+}
+
+/// Per-interface totals since boot.
+pub async fn interface_statistics() -> Result<Vec<InterfaceStats>, String> {
+    let output = run_tool(Tool::Netstat, &["-ibn"]).await?;
+    Ok(parse_netstat_interfaces(&output))
+}
+
+/// The `<Link#n>` rows of `netstat -ibn`, which carry each interface's
+/// totals; address rows repeat them per protocol. The columns end with
+/// `Ipkts Ierrs Ibytes Opkts Oerrs Obytes Coll`; the hardware address
+/// column is blank for interfaces without one.
+pub fn parse_netstat_interfaces(output: &str) -> Vec<InterfaceStats> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            if fields.len() < 10 || !fields[2].starts_with("<Link#") {
+                return None;
+            }
+            let counter = |from_end: usize| -> Option<u64> {
+                fields.get(fields.len() - from_end)?.parse().ok()
+            };
+            let name = fields[0];
+            Some(InterfaceStats {
+                name: name.trim_end_matches('*').to_string(),
+                up: !name.ends_with('*'),
+                mtu: fields[1].parse().ok()?,
+                address: (fields.len() >= 11).then(|| fields[3].to_string()),
+                packets_in: counter(7)?,
+                errors_in: counter(6)?,
+                bytes_in: counter(5)?,
+                packets_out: counter(4)?,
+                errors_out: counter(3)?,
+                bytes_out: counter(2)?,
+            })
+        })
+        .collect()
+}
+
+/// Open TCP and UDP sockets of the user's processes, like `netstat -an`
+/// with the owning process.
+pub async fn active_connections() -> Result<Vec<NetworkConnection>, String> {
+    let output = run_tool(Tool::Lsof, &["-nP", "+c0", "-iTCP", "-iUDP"]).await?;
+    Ok(parse_lsof_connections(&output))
+}
+
+/// Rows of `lsof -nP -i`: `COMMAND PID USER FD TYPE DEVICE SIZE/OFF NODE
+/// NAME [(STATE)]`. Sockets shared by several descriptors are listed once.
+pub fn parse_lsof_connections(output: &str) -> Vec<NetworkConnection> {
+    let mut connections: Vec<NetworkConnection> = Vec::new();
+    for line in output.lines().skip(1) {
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        if fields.len() < 9 {
+            continue;
+        }
+        let Ok(pid) = fields[1].parse() else {
+            continue;
+        };
+        let (local, remote) = match fields[8].split_once("->") {
+            Some((local, remote)) => (local.to_string(), Some(remote.to_string())),
+            None => (fields[8].to_string(), None),
+        };
+        let connection = NetworkConnection {
+            process: fields[0].replace("\\x20", " "),
+            pid,
+            protocol: fields[7].to_string(),
+            local,
+            remote,
+            state: fields
+                .get(9)
+                .map(|state| state.trim_matches(['(', ')']).to_string()),
+        };
+        if !connections.contains(&connection) {
+            connections.push(connection);
+        }
+    }
+    connections
+}
+
+/// Time a DNS lookup and ping each target, reporting progress per host.
+pub async fn connectivity_test(
+    token: &CancellationToken,
+    progress: Option<&NetworkProgressFn>,
+) -> Result<ConnectivityReport, String> {
+    let steps = CONNECTIVITY_TARGETS.len() + 1;
+    if let Some(cb) = progress {
+        cb(0.0, "Resolving a host name");
+    }
+    let started = Instant::now();
+    let lookup = tokio::select! {
+        _ = token.cancelled() => return Err("cancelled".into()),
+        lookup = tokio::net::lookup_host((DNS_PROBE_HOST, 443)) => lookup,
+    };
+    let elapsed = started.elapsed().as_millis() as u64;
+    let (dns_ms, dns_error) = match lookup.map(|mut addresses| addresses.next().is_some()) {
+        Ok(true) => (Some(elapsed), None),
+        Ok(false) => (None, Some(format!("{} has no addresses", DNS_PROBE_HOST))),
+        Err(e) => (None, Some(format!("DNS lookup failed: {}", e))),
+    };
+
+    let mut targets = Vec::new();
+    for (index, host) in CONNECTIVITY_TARGETS.iter().enumerate() {
+        if let Some(cb) = progress {
+            cb(
+                (index + 1) as f32 / steps as f32 * 100.0,
+                &format!("Pinging {}", host),
+            );
+        }
+        let result = tokio::select! {
+            _ = token.cancelled() => return Err("cancelled".into()),
+            result = ping(host) => result,
+        };
+        let (summary, error) = match result {
+            Ok(Some(summary)) if summary.received == 0 => {
+                (Some(summary), Some("No replies".to_string()))
+            }
+            Ok(Some(summary)) => (Some(summary), None),
+            Ok(None) => (None, Some("ping printed no summary".to_string())),
+            Err(err) => (None, Some(err)),
+        };
+        targets.push(LatencyResult {
+            host: host.to_string(),
+            summary,
+            error,
+        });
+    }
+    Ok(ConnectivityReport {
+        dns_ms,
+        dns_error,
+        online: targets
+            .iter()
+            .any(|target| target.summary.as_ref().is_some_and(|s| s.received > 0)),
+        targets,
+    })
+}
+
+/// The statistics lines of `ping -q`, as printed by macOS
+/// (`4 packets received … round-trip min/avg/max/stddev = …`) or Linux
+/// (`4 received … rtt min/avg/max/mdev = …`).
+pub fn parse_ping_summary(output: &str) -> Option<PingSummary> {
+    let counts = output
+        .lines()
+        .find(|line| line.contains("packets transmitted"))?;
+    let numbers: Vec<&str> = counts.split(',').map(str::trim).collect();
+    let leading =
+        |field: Option<&&str>| -> Option<u32> { field?.split_whitespace().next()?.parse().ok() };
+    let mut summary = PingSummary {
+        sent: leading(numbers.first())?,
+        received: leading(numbers.get(1))?,
+        loss_percent: numbers
+            .iter()
+            .find(|field| field.ends_with("packet loss"))
+            .and_then(|field| field.split('%').next())
+            .and_then(|loss| loss.trim().parse().ok())
+            .unwrap_or(0.0),
+        ..PingSummary::default()
+    };
+    if let Some(times) = output
+        .lines()
+        .find(|line| line.contains("min/avg/max"))
+        .and_then(|line| line.split_once('=').map(|(_, times)| times))
+    {
+        let times: Vec<f32> = times
+            .trim()
+            .trim_end_matches("ms")
+            .split('/')
+            .filter_map(|time| time.trim().parse().ok())
+            .collect();
+        summary.min_ms = times.first().copied();
+        summary.avg_ms = times.get(1).copied();
+        summary.max_ms = times.get(2).copied();
+    }
+    Some(summary)
+}
+
+/// Ping `host` and read the summary. `ping` exits non-zero when nothing
+/// answered but still prints one.
+async fn ping(host: &str) -> Result<Option<PingSummary>, String> {
+    let program = capabilities::tool_path(Tool::Ping).ok_or("ping is not installed")?;
+    let output = Command::new(program)
+        .args(["-c", PING_COUNT, "-q", "-t", PING_TIMEOUT_SECS, host])
+        .kill_on_drop(true)
+        .output()
+        .await
+        .map_err(|e| format!("Failed to run ping: {}", e))?;
+    Ok(parse_ping_summary(&String::from_utf8_lossy(&output.stdout)))
+}
+
+async fn run_tool(tool: Tool, args: &[&str]) -> Result<String, String> {
+    let program = capabilities::tool_path(tool)
+        .ok_or_else(|| format!("{} is not installed", tool.command()))?;
+    let output = Command::new(program)
+        .args(args)
+        .kill_on_drop(true)
+        .output()
+        .await
+        .map_err(|e| format!("Failed to run {}: {}", tool.command(), e))?;
+    if output.status.success() {
+        Ok(String::from_utf8_lossy(&output.stdout).to_string())
+    } else {
+        Err(format!(
+            "{} failed: {}",
+            tool.command(),
+            String::from_utf8_lossy(&output.stderr).trim()
+        ))
+    }
+}
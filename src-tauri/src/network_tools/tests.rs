@@ -0,0 +1,101 @@
+use super::{
+    is_valid_interface, maintenance_script, parse_default_interface, parse_lsof_connections,
+    parse_netstat_interfaces, parse_ping_summary, parse_step_markers, NetworkAction,
+};
+
+#[test]
+fn netstat_link_rows_become_interface_totals() {
+    let output = "\
+Name       Mtu   Network       Address            Ipkts Ierrs     Ibytes    Opkts Oerrs     Obytes  Coll
+lo0        16384 <Link#1>                         120034     0   50331648   120034     0   50331648     0
+lo0        16384 127           127.0.0.1          120034     -   50331648   120034     -   50331648     -
+en0        1500  <Link#6>    a4:83:e7:12:34:56  8812345     3 9876543210  4412345     0  512345678     0
+en0        1500  192.168.1     192.168.1.20     8812345     - 9876543210  4412345     -  512345678     -
+gif0*      1280  <Link#2>                              0     0          0        0     0          0     0
+";
+    let stats = parse_netstat_interfaces(output);
+    assert_eq!(stats.len(), 3);
+    assert_eq!(stats[0].name, "lo0");
+    assert!(stats[0].address.is_none());
+    assert_eq!(stats[1].address.as_deref(), Some("a4:83:e7:12:34:56"));
+    assert_eq!(stats[1].bytes_in, 9_876_543_210);
+    assert_eq!(stats[1].errors_in, 3);
+    assert_eq!(stats[1].packets_out, 4_412_345);
+    assert_eq!(stats[1].bytes_out, 512_345_678);
+    assert_eq!(stats[2].name, "gif0");
+    assert!(!stats[2].up);
+}
+
+#[test]
+fn lsof_rows_become_connections() {
+    let output = "\
+COMMAND      PID USER   FD   TYPE             DEVICE SIZE/OFF NODE NAME
+Google\\x20Chrome 812 me 31u IPv4 0x1234 0t0 TCP 192.168.1.20:51234->17.57.146.52:443 (ESTABLISHED)
+Google\\x20Chrome 812 me 32u IPv4 0x1234 0t0 TCP 192.168.1.20:51234->17.57.146.52:443 (ESTABLISHED)
+rapportd     401 me    4u  IPv6 0x5678      0t0  TCP *:49152 (LISTEN)
+mDNSResponder 233 me   9u  IPv4 0x9abc      0t0  UDP *:5353
+";
+    let connections = parse_lsof_connections(output);
+    assert_eq!(connections.len(), 3);
+    assert_eq!(connections[0].process, "Google Chrome");
+    assert_eq!(connections[0].pid, 812);
+    assert_eq!(connections[0].remote.as_deref(), Some("17.57.146.52:443"));
+    assert_eq!(connections[0].state.as_deref(), Some("ESTABLISHED"));
+    assert_eq!(connections[1].state.as_deref(), Some("LISTEN"));
+    assert!(connections[1].remote.is_none());
+    assert_eq!(connections[2].protocol, "UDP");
+    assert!(connections[2].state.is_none());
+}
+
+#[test]
+fn ping_summaries_parse_on_macos_and_linux() {
+    let macos = "--- 1.1.1.1 ping statistics ---\n\
+                 4 packets transmitted, 4 packets received, 0.0% packet loss\n\
+                 round-trip min/avg/max/stddev = 10.123/12.456/15.789/2.001 ms\n";
+    let summary = parse_ping_summary(macos).unwrap();
+    assert_eq!((summary.sent, summary.received), (4, 4));
+    assert_eq!(summary.loss_percent, 0.0);
+    assert_eq!(summary.avg_ms, Some(12.456));
+    assert_eq!(summary.max_ms, Some(15.789));
+
+    let linux = "4 packets transmitted, 3 received, 25% packet loss, time 3004ms\n\
+                 rtt min/avg/max/mdev = 9.1/9.5/10.2/0.4 ms\n";
+    let summary = parse_ping_summary(linux).unwrap();
+    assert_eq!(summary.received, 3);
+    assert_eq!(summary.loss_percent, 25.0);
+    assert_eq!(summary.min_ms, Some(9.1));
+
+    let unreachable = "4 packets transmitted, 0 packets received, 100.0% packet loss\n";
+    let summary = parse_ping_summary(unreachable).unwrap();
+    assert_eq!(summary.received, 0);
+    assert!(summary.avg_ms.is_none());
+    assert!(parse_ping_summary("ping: cannot resolve apple.com").is_none());
+}
+
+#[test]
+fn maintenance_script_reports_each_action() {
+    assert!(is_valid_interface("en0"));
+    assert!(!is_valid_interface("en0; rm -rf /"));
+    assert!(!is_valid_interface(""));
+    assert_eq!(
+        parse_default_interface("   route to: default\n  gateway: 192.168.1.1\n  interface: en0\n"),
+        Some("en0".to_string())
+    );
+
+    let actions = [NetworkAction::FlushDns, NetworkAction::RenewDhcp];
+    let script = maintenance_script(&actions, Some("en0"));
+    assert!(script.contains("ipconfig set en0 DHCP"));
+    assert!(script.contains("echo OK:flush_dns"));
+    assert!(!script.contains('"'));
+
+    // AppleScript hands back shell output with carriage returns.
+    let steps = parse_step_markers("OK:flush_dns\rERR:renew_dhcp", &actions);
+    assert!(steps[0].success);
+    assert!(!steps[1].success);
+    assert_eq!(steps[1].error.as_deref(), Some("Renew DHCP lease failed"));
+    let steps = parse_step_markers("", &[NetworkAction::ResetMdns]);
+    assert_eq!(
+        steps[0].error.as_deref(),
+        Some("Restart mDNSResponder did not run")
+    );
+}
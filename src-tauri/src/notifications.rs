@@ -0,0 +1,294 @@
+// src/notifications.rs
+
+//! macOS user notifications for events worth interrupting the user for:
+//! a long operation finishing, a volume running out of space and the memory
+//! watchdog stepping in.
+//!
+//! [`Notifier`] decides whether an event is notified from the per-event
+//! toggles in [`NotificationSettings`], then hands it to every registered
+//! [`NotificationSink`]. The default sink posts through `osascript`; other
+//! destinations are added with [`Notifier::add_sink`].
+
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::formatting;
+use crate::persistence;
+
+#[cfg(test)]
+mod tests;
+
+const NOTIFICATIONS_FILE: &str = "notifications.json";
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NotificationSettings {
+    /// Turns every notification off without losing the toggles below.
+    pub enabled: bool,
+    pub operation_finished: bool,
+    /// Operations that finish sooner are not notified; the user is most
+    /// likely still watching.
+    pub min_operation_secs: u64,
+    pub low_disk_space: bool,
+    /// Free space, in percent of a volume, below which it is reported.
+    pub low_disk_percent: f32,
+    pub memory_pressure: bool,
+}
+
+impl Default for NotificationSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            operation_finished: true,
+            min_operation_secs: 30,
+            low_disk_space: true,
+            low_disk_percent: 10.0,
+            memory_pressure: true,
+        }
+    }
+}
+
+impl NotificationSettings {
+    fn validate(&self) -> Result<(), String> {
+        if !(self.low_disk_percent > 0.0 && self.low_disk_percent < 100.0) {
+            return Err("Low disk space threshold must be between 0 and 100 percent".into());
+        }
+        Ok(())
+    }
+
+    fn allows(&self, kind: NotificationKind) -> bool {
+        self.enabled
+            && match kind {
+                NotificationKind::OperationFinished => self.operation_finished,
+                NotificationKind::LowDiskSpace => self.low_disk_space,
+                NotificationKind::MemoryPressure => self.memory_pressure,
+            }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NotificationKind {
+    OperationFinished,
+    LowDiskSpace,
+    MemoryPressure,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Notification {
+    pub kind: NotificationKind,
+    pub title: String,
+    pub body: String,
+}
+
+/// Somewhere notifications are delivered.
+pub trait NotificationSink: Send + Sync {
+    fn deliver(&self, notification: &Notification) -> Result<(), String>;
+}
+
+/// Posts to Notification Center with AppleScript's `display notification`.
+pub struct OsascriptSink;
+
+impl NotificationSink for OsascriptSink {
+    fn deliver(&self, notification: &Notification) -> Result<(), String> {
+        let script = format!(
+            "display notification {} with title {}",
+            applescript_string(&notification.body),
+            applescript_string(&notification.title)
+        );
+        let output = Command::new("osascript")
+            .args(["-e", &script])
+            .output()
+            .map_err(|e| format!("Failed to execute osascript: {}", e))?;
+        if !output.status.success() {
+            return Err(format!(
+                "osascript failed: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            ));
+        }
+        Ok(())
+    }
+}
+
+struct NotifierState {
+    path: Option<PathBuf>,
+    settings: NotificationSettings,
+    /// Volumes already reported as low on space; a volume is reported again
+    /// only after it has recovered.
+    low_volumes: HashSet<String>,
+}
+
+/// Shared handle deciding which events become notifications and delivering
+/// them off the calling thread.
+#[derive(Clone)]
+pub struct Notifier {
+    state: Arc<Mutex<NotifierState>>,
+    sinks: Arc<Mutex<Vec<Arc<dyn NotificationSink>>>>,
+}
+
+impl Notifier {
+    /// Load the settings from `<data dir>/macos-optimizer/notifications.json`
+    /// and deliver to Notification Center.
+    pub fn load() -> Self {
+        let notifier = match persistence::data_file(NOTIFICATIONS_FILE) {
+            Some(path) => Self::load_from(&path),
+            None => Self::with_settings(None, NotificationSettings::default()),
+        };
+        notifier.add_sink(Arc::new(OsascriptSink));
+        notifier
+    }
+
+    /// Load from an explicit file, without any sink; a missing or invalid
+    /// file yields the defaults.
+    pub fn load_from(path: &Path) -> Self {
+        let settings = match persistence::read_json::<NotificationSettings>(path) {
+            Some(settings) if settings.validate().is_err() => {
+                log::warn!("Ignoring invalid {}", path.display());
+                NotificationSettings::default()
+            }
+            settings => settings.unwrap_or_default(),
+        };
+        Self::with_settings(Some(path.to_path_buf()), settings)
+    }
+
+    fn with_settings(path: Option<PathBuf>, settings: NotificationSettings) -> Self {
+        Self {
+            state: Arc::new(Mutex::new(NotifierState {
+                path,
+                settings,
+                low_volumes: HashSet::new(),
+            })),
+            sinks: Arc::new(Mutex::new(Vec::new())),
+        }
+    }
+
+    pub fn add_sink(&self, sink: Arc<dyn NotificationSink>) {
+        self.sinks
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .push(sink);
+    }
+
+    pub fn settings(&self) -> NotificationSettings {
+        self.state().settings.clone()
+    }
+
+    pub fn configure(&self, settings: NotificationSettings) -> Result<(), String> {
+        settings.validate()?;
+        let mut state = self.state();
+        if let Some(path) = &state.path {
+            persistence::write_json(path, &settings)?;
+        }
+        state.settings = settings;
+        Ok(())
+    }
+
+    /// `operation` finished with `outcome`, such as "completed" or "failed";
+    /// `detail` becomes the body. Quick operations are not notified.
+    pub fn operation_finished(
+        &self,
+        operation: &str,
+        outcome: &str,
+        detail: Option<&str>,
+        duration: Duration,
+    ) -> Option<Notification> {
+        if duration.as_secs() < self.state().settings.min_operation_secs {
+            return None;
+        }
+        let took = format!(
+            "Took {}",
+            formatting::format_eta(duration.as_millis() as u64)
+        );
+        self.send(Notification {
+            kind: NotificationKind::OperationFinished,
+            title: format!("{} {}", operation, outcome),
+            body: match detail {
+                Some(detail) => format!("{}. {}", detail, took),
+                None => took,
+            },
+        })
+    }
+
+    /// A reading of the free space on `volume`. Notifies once when it drops
+    /// below the threshold, and again only after it has been above it.
+    pub fn disk_space(&self, volume: &str, available: u64, total: u64) -> Option<Notification> {
+        if total == 0 {
+            return None;
+        }
+        let free_percent = available as f64 / total as f64 * 100.0;
+        {
+            let mut state = self.state();
+            if free_percent >= state.settings.low_disk_percent as f64 {
+                state.low_volumes.remove(volume);
+                return None;
+            }
+            if !state.low_volumes.insert(volume.to_string()) {
+                return None;
+            }
+        }
+        self.send(Notification {
+            kind: NotificationKind::LowDiskSpace,
+            title: format!("{} is almost full", volume),
+            body: format!(
+                "{} free ({:.0}%)",
+                formatting::format_size(available),
+                free_percent
+            ),
+        })
+    }
+
+    /// The memory watchdog reported `level`, such as "warning" or "critical".
+    pub fn memory_pressure(
+        &self,
+        level: &str,
+        pressure_percent: f32,
+        auto_optimizing: bool,
+    ) -> Option<Notification> {
+        let mut body = format!("Memory pressure is at {:.0}%", pressure_percent);
+        if auto_optimizing {
+            body.push_str("; clearing inactive memory");
+        }
+        self.send(Notification {
+            kind: NotificationKind::MemoryPressure,
+            title: format!("Memory pressure is {}", level),
+            body,
+        })
+    }
+
+    /// Deliver to every sink in the background if the settings allow `kind`.
+    fn send(&self, notification: Notification) -> Option<Notification> {
+        if !self.state().settings.allows(notification.kind) {
+            return None;
+        }
+        let sinks = self
+            .sinks
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .clone();
+        let delivered = notification.clone();
+        std::thread::spawn(move || {
+            for sink in sinks {
+                if let Err(err) = sink.deliver(&delivered) {
+                    log::warn!("Failed to deliver notification: {}", err);
+                }
+            }
+        });
+        Some(notification)
+    }
+
+    fn state(&self) -> MutexGuard<'_, NotifierState> {
+        self.state
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+/// `text` as a double-quoted AppleScript string literal.
+fn applescript_string(text: &str) -> String {
+    format!("\"{}\"", text.replace('\\', "\\\\").replace('"', "\\\""))
+}
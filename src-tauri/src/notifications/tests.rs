@@ -0,0 +1,120 @@
+use std::sync::mpsc::{self, Sender};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use super::{
+    applescript_string, Notification, NotificationKind, NotificationSettings, NotificationSink,
+    Notifier,
+};
+use tempfile::TempDir;
+
+struct ChannelSink(Mutex<Sender<Notification>>);
+
+impl NotificationSink for ChannelSink {
+    fn deliver(&self, notification: &Notification) -> Result<(), String> {
+        self.0
+            .lock()
+            .unwrap()
+            .send(notification.clone())
+            .map_err(|e| e.to_string())
+    }
+}
+
+#[test]
+fn events_reach_every_sink_unless_toggled_off() {
+    let temp_dir = TempDir::new().unwrap();
+    let path = temp_dir.path().join("notifications.json");
+    let notifier = Notifier::load_from(&path);
+    let (sender, receiver) = mpsc::channel();
+    notifier.add_sink(Arc::new(ChannelSink(Mutex::new(sender))));
+
+    let sent = notifier
+        .memory_pressure("critical", 93.4, true)
+        .expect("memory pressure is on by default");
+    let delivered = receiver.recv_timeout(Duration::from_secs(5)).unwrap();
+    assert_eq!(delivered, sent);
+    assert_eq!(delivered.kind, NotificationKind::MemoryPressure);
+    assert_eq!(
+        delivered.body,
+        "Memory pressure is at 93%; clearing inactive memory"
+    );
+
+    notifier
+        .configure(NotificationSettings {
+            memory_pressure: false,
+            ..NotificationSettings::default()
+        })
+        .unwrap();
+    assert!(notifier.memory_pressure("critical", 95.0, false).is_none());
+    assert!(!Notifier::load_from(&path).settings().memory_pressure);
+
+    notifier
+        .configure(NotificationSettings {
+            enabled: false,
+            ..NotificationSettings::default()
+        })
+        .unwrap();
+    assert!(notifier.memory_pressure("warning", 80.0, false).is_none());
+    assert!(notifier
+        .configure(NotificationSettings {
+            low_disk_percent: 0.0,
+            ..NotificationSettings::default()
+        })
+        .is_err());
+}
+
+#[test]
+fn only_long_operations_are_notified() {
+    let temp_dir = TempDir::new().unwrap();
+    let notifier = Notifier::load_from(&temp_dir.path().join("notifications.json"));
+
+    assert!(notifier
+        .operation_finished("Cleanup", "completed", None, Duration::from_secs(5))
+        .is_none());
+    let notification = notifier
+        .operation_finished(
+            "Cleanup",
+            "completed",
+            Some("Freed 2 GB"),
+            Duration::from_secs(90),
+        )
+        .unwrap();
+    assert_eq!(notification.title, "Cleanup completed");
+    assert!(notification.body.starts_with("Freed 2 GB. Took "));
+}
+
+#[test]
+fn low_disk_space_is_reported_once_until_the_volume_recovers() {
+    let temp_dir = TempDir::new().unwrap();
+    let notifier = Notifier::load_from(&temp_dir.path().join("notifications.json"));
+    let total = 1_000_000_000;
+
+    assert!(notifier
+        .disk_space("Macintosh HD", 500_000_000, total)
+        .is_none());
+    let low = notifier
+        .disk_space("Macintosh HD", 50_000_000, total)
+        .unwrap();
+    assert_eq!(low.kind, NotificationKind::LowDiskSpace);
+    assert_eq!(low.title, "Macintosh HD is almost full");
+    assert!(low.body.ends_with("free (5%)"), "{}", low.body);
+    assert!(notifier
+        .disk_space("Macintosh HD", 40_000_000, total)
+        .is_none());
+    assert!(notifier.disk_space("Backup", 10_000_000, total).is_some());
+
+    assert!(notifier
+        .disk_space("Macintosh HD", 200_000_000, total)
+        .is_none());
+    assert!(notifier
+        .disk_space("Macintosh HD", 50_000_000, total)
+        .is_some());
+}
+
+#[test]
+fn applescript_strings_are_escaped() {
+    assert_eq!(
+        applescript_string(r#"say "hi" \ bye"#),
+        r#""say \"hi\" \\ bye""#
+    );
+}
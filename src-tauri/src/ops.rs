@@ -1,15 +1,25 @@
+#[cfg(feature = "app")]
+pub mod history;
+#[cfg(all(test, feature = "app"))]
+mod tests;
+
 #[cfg(feature = "app")]
 use dashmap::DashMap;
 #[cfg(feature = "app")]
 use serde::{Deserialize, Serialize};
 #[cfg(feature = "app")]
-use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+#[cfg(feature = "app")]
+use std::sync::{Arc, Mutex};
 use std::time::Instant;
 #[cfg(feature = "app")]
 use tokio::sync::Semaphore;
 #[cfg(feature = "app")]
 use tokio_util::sync::CancellationToken;
 
+#[cfg(feature = "app")]
+use history::{OperationHistory, OperationOutcome, OperationRecord};
+
 #[cfg(feature = "app")]
 pub type OperationId = String;
 
@@ -33,6 +43,116 @@ pub enum OperationKind {
     MemOptimize,
     MemOptimizeAdmin,
     DashboardRefresh,
+    /// Time-boxed clean of pre-verified items; has its own semaphore so it
+    /// can run next to a scan.
+    QuickClean,
+    /// Moving the files of a finished clean back out of the Trash.
+    UndoClean,
+    /// DNS flush, DHCP renewal and mDNSResponder restart as root.
+    NetworkMaintenance,
+    /// Connectivity and latency test; changes nothing.
+    NetworkDiagnostics,
+    /// Rebuilding font caches, Launch Services and the like.
+    SystemMaintenance,
+}
+
+#[cfg(feature = "app")]
+impl OperationKind {
+    /// Kinds that change the system; refused while inspector mode is on.
+    pub fn is_destructive(&self) -> bool {
+        matches!(
+            self,
+            OperationKind::FileClean
+                | OperationKind::EmptyTrash
+                | OperationKind::MemOptimize
+                | OperationKind::MemOptimizeAdmin
+                | OperationKind::QuickClean
+                | OperationKind::UndoClean
+                | OperationKind::NetworkMaintenance
+                | OperationKind::SystemMaintenance
+        )
+    }
+
+    /// Name of the operation for notifications.
+    pub fn label(&self) -> &'static str {
+        match self {
+            OperationKind::FileScan => "Scan",
+            OperationKind::FileClean => "Cleanup",
+            OperationKind::EmptyTrash => "Emptying the Trash",
+            OperationKind::MemOptimize | OperationKind::MemOptimizeAdmin => "Memory optimization",
+            OperationKind::DashboardRefresh => "Dashboard refresh",
+            OperationKind::QuickClean => "Quick clean",
+            OperationKind::UndoClean => "Undo",
+            OperationKind::NetworkMaintenance => "Network maintenance",
+            OperationKind::NetworkDiagnostics => "Network diagnostics",
+            OperationKind::SystemMaintenance => "System maintenance",
+        }
+    }
+}
+
+/// Called with the history record of every operation as it finishes.
+#[cfg(feature = "app")]
+pub type FinishHook = Arc<dyn Fn(&OperationRecord) + Send + Sync>;
+
+/// Error returned when a destructive operation is attempted in inspector mode.
+#[cfg(feature = "app")]
+pub const INSPECTOR_MODE_REFUSAL: &str = "Inspector mode is on; nothing was changed";
+
+/// Where an operation spends its time, told apart by its progress stages.
+#[cfg(feature = "app")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Phase {
+    Discovery,
+    Analysis,
+    Validation,
+    Deletion,
+    /// Waiting on `purge`, `brew`, `docker` and the like, or an admin prompt.
+    ExternalTool,
+}
+
+#[cfg(feature = "app")]
+impl Phase {
+    /// The phase a progress stage belongs to; setup and completion stages
+    /// belong to none.
+    pub fn from_stage(stage: &str) -> Option<Self> {
+        match stage {
+            "discovery" | "scanning" | "caches" | "temp_files" => Some(Phase::Discovery),
+            "analysis" | "safety" | "scoring" | "duplicates" => Some(Phase::Analysis),
+            "validation" => Some(Phase::Validation),
+            "deleting" | "volume_trash" | "finalizing" => Some(Phase::Deletion),
+            "external_tool" | "auth" | "cache_clear" | "disk_cache" | "network_cache"
+            | "memory_compression" | "containers" | "network" | "maintenance" => {
+                Some(Phase::ExternalTool)
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Milliseconds spent per phase; time in no phase is not counted.
+#[cfg(feature = "app")]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PhaseTimings {
+    pub discovery_ms: u64,
+    pub analysis_ms: u64,
+    pub validation_ms: u64,
+    pub deletion_ms: u64,
+    pub external_tool_ms: u64,
+}
+
+#[cfg(feature = "app")]
+impl PhaseTimings {
+    pub fn add(&mut self, phase: Phase, ms: u64) {
+        let slot = match phase {
+            Phase::Discovery => &mut self.discovery_ms,
+            Phase::Analysis => &mut self.analysis_ms,
+            Phase::Validation => &mut self.validation_ms,
+            Phase::Deletion => &mut self.deletion_ms,
+            Phase::ExternalTool => &mut self.external_tool_ms,
+        };
+        *slot = slot.saturating_add(ms);
+    }
 }
 
 #[cfg(feature = "app")]
@@ -47,23 +167,68 @@ pub struct OpState {
     pub details: Option<String>,
     pub cancellable: bool,
     pub status: OperationStatus,
+    /// Closed phases so far; the open one is added when it ends.
+    #[serde(default)]
+    pub phase_timings: PhaseTimings,
+    #[serde(skip)]
+    open_phase: Option<(Phase, Instant)>,
+}
+
+#[cfg(feature = "app")]
+impl OpState {
+    /// Close the open phase if `stage` belongs to another one, and open that.
+    fn checkpoint(&mut self, stage: &str, now: Instant) {
+        let next = Phase::from_stage(stage);
+        if self.open_phase.map(|(phase, _)| phase) == next {
+            return;
+        }
+        self.close_phase(now);
+        self.open_phase = next.map(|phase| (phase, now));
+    }
+
+    fn close_phase(&mut self, now: Instant) {
+        if let Some((phase, since)) = self.open_phase.take() {
+            self.phase_timings
+                .add(phase, now.duration_since(since).as_millis() as u64);
+        }
+    }
+
+    /// Timings with the open phase counted up to `now`.
+    fn timings_at(&self, now: Instant) -> PhaseTimings {
+        let mut timings = self.phase_timings;
+        if let Some((phase, since)) = self.open_phase {
+            timings.add(phase, now.duration_since(since).as_millis() as u64);
+        }
+        timings
+    }
 }
 
 #[cfg(feature = "app")]
 #[derive(Debug)]
 pub struct OpHandle {
     pub token: CancellationToken,
-    pub _started_at: Instant,
+    pub started_at: Instant,
 }
 
 #[cfg(feature = "app")]
 #[derive(Clone)]
 pub struct OperationRegistry {
     inner: Arc<DashMap<OperationId, (OpState, Arc<OpHandle>)>>,
+    outcomes: Arc<DashMap<OperationId, OperationOutcome>>,
+    /// Finished operations; in memory only unless set with `with_history`.
+    history: Arc<Mutex<OperationHistory>>,
     // Global concurrency guards
     pub scan_sem: Arc<Semaphore>,
     pub clean_sem: Arc<Semaphore>,
     pub opt_sem: Arc<Semaphore>,
+    pub quick_sem: Arc<Semaphore>,
+    /// Current sizes of the scan, clean and optimization semaphores.
+    permits: Arc<Mutex<[usize; 3]>>,
+    /// Read-only mode: destructive kinds cannot be registered.
+    inspector: Arc<AtomicBool>,
+    /// Persisted audit mode, which holds inspector mode on while set.
+    audit: Arc<AtomicBool>,
+    on_finish: Option<FinishHook>,
 }
 
 #[cfg(feature = "app")]
@@ -71,17 +236,112 @@ impl OperationRegistry {
     pub fn new(scan_permits: usize, clean_permits: usize, opt_permits: usize) -> Self {
         Self {
             inner: Arc::new(DashMap::new()),
+            outcomes: Arc::new(DashMap::new()),
+            history: Arc::new(Mutex::new(OperationHistory::default())),
             scan_sem: Arc::new(Semaphore::new(scan_permits.max(1))),
             clean_sem: Arc::new(Semaphore::new(clean_permits.max(1))),
             opt_sem: Arc::new(Semaphore::new(opt_permits.max(1))),
+            quick_sem: Arc::new(Semaphore::new(1)),
+            permits: Arc::new(Mutex::new([
+                scan_permits.max(1),
+                clean_permits.max(1),
+                opt_permits.max(1),
+            ])),
+            inspector: Arc::new(AtomicBool::new(false)),
+            audit: Arc::new(AtomicBool::new(false)),
+            on_finish: None,
+        }
+    }
+
+    /// Record finished operations in `history` instead of in memory.
+    pub fn with_history(mut self, history: OperationHistory) -> Self {
+        self.history = Arc::new(Mutex::new(history));
+        self
+    }
+
+    /// Call `hook` whenever an operation finishes.
+    pub fn with_finish_hook(mut self, hook: FinishHook) -> Self {
+        self.on_finish = Some(hook);
+        self
+    }
+
+    /// Change how many scans, cleanups and optimizations may run at once.
+    /// Operations holding a permit keep it; a smaller limit takes effect as
+    /// they finish.
+    pub fn resize_permits(&self, scan_permits: usize, clean_permits: usize, opt_permits: usize) {
+        let mut current = self
+            .permits
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        let targets = [
+            scan_permits.max(1),
+            clean_permits.max(1),
+            opt_permits.max(1),
+        ];
+        let semaphores = [&self.scan_sem, &self.clean_sem, &self.opt_sem];
+        for ((semaphore, size), target) in
+            semaphores.into_iter().zip(current.iter_mut()).zip(targets)
+        {
+            if target > *size {
+                semaphore.add_permits(target - *size);
+            } else if target < *size {
+                let excess = *size - target;
+                let pending = excess - semaphore.forget_permits(excess);
+                if pending > 0 {
+                    // Retire the rest as the operations holding them finish.
+                    let semaphore = Arc::clone(semaphore);
+                    tokio::spawn(async move {
+                        if let Ok(permits) = semaphore.acquire_many_owned(pending as u32).await {
+                            permits.forget();
+                        }
+                    });
+                }
+            }
+            *size = target;
         }
     }
 
+    pub fn history(&self) -> std::sync::MutexGuard<'_, OperationHistory> {
+        self.history
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// Whether inspector mode is in effect, turned on directly or held on
+    /// by audit mode.
+    pub fn inspector_mode(&self) -> bool {
+        self.inspector.load(Ordering::SeqCst) || self.audit.load(Ordering::SeqCst)
+    }
+
+    pub fn set_inspector_mode(&self, enabled: bool) {
+        self.inspector.store(enabled, Ordering::SeqCst);
+    }
+
+    /// Set audit mode; inspector mode turned on directly stays on when it
+    /// is turned off.
+    pub fn set_audit_mode(&self, enabled: bool) {
+        self.audit.store(enabled, Ordering::SeqCst);
+    }
+
+    /// Refuse a change that runs without registering an operation, such as
+    /// signalling a process, while inspector mode is on.
+    pub fn refuse_in_inspector_mode(&self) -> Result<(), String> {
+        if self.inspector_mode() {
+            return Err(INSPECTOR_MODE_REFUSAL.to_string());
+        }
+        Ok(())
+    }
+
+    /// Track a new operation. Destructive kinds are refused in inspector
+    /// mode, so callers must take their dry-run path before registering.
     pub fn register(
         &self,
         kind: OperationKind,
         cancellable: bool,
-    ) -> (OperationId, CancellationToken) {
+    ) -> Result<(OperationId, CancellationToken), String> {
+        if kind.is_destructive() && self.inspector_mode() {
+            return Err(INSPECTOR_MODE_REFUSAL.to_string());
+        }
         let id = uuid::Uuid::new_v4().to_string();
         let token = CancellationToken::new();
         let state = OpState {
@@ -94,21 +354,42 @@ impl OperationRegistry {
             details: None,
             cancellable,
             status: OperationStatus::Pending,
+            phase_timings: PhaseTimings::default(),
+            open_phase: None,
         };
         let handle = Arc::new(OpHandle {
             token: token.clone(),
-            _started_at: Instant::now(),
+            started_at: Instant::now(),
         });
         self.inner.insert(id.clone(), (state, handle));
-        (id, token)
+        // What the registering task logs from here on belongs to this operation.
+        crate::logging::bind_operation(&id);
+        log::info!("Started {}", kind.label());
+        Ok((id, token))
     }
 
+    /// Change the operation's state; a new stage is a phase checkpoint.
     pub fn update(&self, id: &str, mut f: impl FnMut(&mut OpState)) {
         if let Some(mut entry) = self.inner.get_mut(id) {
-            f(&mut entry.0);
+            let state = &mut entry.0;
+            f(state);
+            let stage = state.stage.clone();
+            state.checkpoint(&stage, Instant::now());
         }
     }
 
+    /// Record that the operation reached `stage`, for its phase timings.
+    pub fn checkpoint(&self, id: &str, stage: &str) {
+        self.update(id, |state| state.stage = stage.to_string());
+    }
+
+    /// Time spent per phase so far, for completion events.
+    pub fn phase_timings(&self, id: &str) -> Option<PhaseTimings> {
+        self.inner
+            .get(id)
+            .map(|entry| entry.0.timings_at(Instant::now()))
+    }
+
     pub fn get(&self, id: &str) -> Option<OpState> {
         self.inner.get(id).map(|e| e.0.clone())
     }
@@ -122,29 +403,57 @@ impl OperationRegistry {
         }
     }
 
+    /// Attach what the operation changed to its history record.
+    pub fn record_outcome(&self, id: &str, outcome: OperationOutcome) {
+        self.outcomes.insert(id.to_string(), outcome);
+    }
+
     pub fn finish_success(&self, id: &str) {
         self.update(id, |s| {
-            s.status = OperationStatus::Completed;
             s.progress = 100.0;
             s.stage = "complete".into();
         });
-        // keep short history; remove after completion to avoid growth
-        // callers can query final state right after completion event
-        let _ = self.inner.remove(id);
+        self.finish(id, OperationStatus::Completed, None);
     }
 
     pub fn finish_canceled(&self, id: &str) {
-        self.update(id, |s| {
-            s.status = OperationStatus::Canceled;
-        });
-        let _ = self.inner.remove(id);
+        self.finish(id, OperationStatus::Canceled, None);
     }
 
-    pub fn finish_failed(&self, id: &str, _msg: &str) {
-        self.update(id, |s| {
-            s.status = OperationStatus::Failed;
+    pub fn finish_failed(&self, id: &str, msg: &str) {
+        self.finish(id, OperationStatus::Failed, Some(msg.to_string()));
+    }
+
+    /// Stop tracking the operation and log it to the history.
+    fn finish(&self, id: &str, status: OperationStatus, error: Option<String>) {
+        let outcome = self.outcomes.remove(id).map(|(_, outcome)| outcome);
+        let Some((_, (mut state, handle))) = self.inner.remove(id) else {
+            return;
+        };
+        state.close_phase(Instant::now());
+        crate::logging::in_operation(Some(id.to_string()), || match (&status, &error) {
+            (OperationStatus::Failed, Some(error)) => {
+                log::error!("{} failed: {}", state.kind.label(), error)
+            }
+            (status, _) => log::info!("{} finished: {:?}", state.kind.label(), status),
         });
-        let _ = self.inner.remove(id);
+        crate::logging::unbind_operation(id);
+        let record = OperationRecord {
+            id: state.id,
+            kind: state.kind,
+            status,
+            started_at_ms: state.started_at_ms as i64,
+            duration_ms: handle.started_at.elapsed().as_millis() as u64,
+            error,
+            outcome: outcome.unwrap_or_default(),
+            phase_timings: state.phase_timings,
+        };
+        if let Some(hook) = &self.on_finish {
+            hook(&record);
+        }
+        if let Err(err) = self.history().append(record) {
+            log::warn!("Failed to record operation history: {}", err);
+        }
     }
 }
 
@@ -0,0 +1,227 @@
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use super::{OperationKind, OperationStatus, PhaseTimings};
+use crate::persistence;
+
+const HISTORY_FILE: &str = "operation_history.json";
+/// Older records are dropped when a new one is logged.
+const MAX_RECORDS: usize = 500;
+/// Cleaned paths kept per record; `items` still counts all of them.
+const MAX_RECORDED_PATHS: usize = 100;
+const DEFAULT_PAGE_SIZE: usize = 50;
+const MAX_PAGE_SIZE: usize = 500;
+
+/// What a finished operation changed, attached before it finishes.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct OperationOutcome {
+    pub items: u64,
+    pub bytes_freed: u64,
+    pub cleaned: Vec<String>,
+    /// One `path: reason` line per item that could not be handled.
+    pub failures: Vec<String>,
+    pub recovery_point_id: Option<String>,
+}
+
+impl OperationOutcome {
+    pub fn new(items: u64, bytes_freed: u64) -> Self {
+        Self {
+            items,
+            bytes_freed,
+            ..Self::default()
+        }
+    }
+
+    pub fn with_cleaned(mut self, paths: impl IntoIterator<Item = String>) -> Self {
+        self.cleaned = paths.into_iter().take(MAX_RECORDED_PATHS).collect();
+        self
+    }
+
+    pub fn with_failures(mut self, failures: impl IntoIterator<Item = String>) -> Self {
+        self.failures = failures.into_iter().take(MAX_RECORDED_PATHS).collect();
+        self
+    }
+}
+
+/// One finished operation.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OperationRecord {
+    pub id: String,
+    pub kind: OperationKind,
+    pub status: OperationStatus,
+    /// Unix milliseconds.
+    pub started_at_ms: i64,
+    pub duration_ms: u64,
+    pub error: Option<String>,
+    #[serde(default)]
+    pub outcome: OperationOutcome,
+    #[serde(default)]
+    pub phase_timings: PhaseTimings,
+}
+
+/// Which records `get_operation_history` returns; unset fields match all.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct HistoryFilter {
+    pub kind: Option<OperationKind>,
+    pub status: Option<OperationStatus>,
+    pub since_ms: Option<i64>,
+    pub until_ms: Option<i64>,
+    /// Case-insensitive match against cleaned paths, failures and the error.
+    pub search: Option<String>,
+}
+
+impl HistoryFilter {
+    fn matches(&self, record: &OperationRecord) -> bool {
+        if self.kind.as_ref().is_some_and(|kind| *kind != record.kind)
+            || self
+                .status
+                .as_ref()
+                .is_some_and(|status| *status != record.status)
+            || self
+                .since_ms
+                .is_some_and(|since| record.started_at_ms < since)
+            || self
+                .until_ms
+                .is_some_and(|until| record.started_at_ms > until)
+        {
+            return false;
+        }
+        let Some(search) = self
+            .search
+            .as_deref()
+            .map(str::trim)
+            .filter(|s| !s.is_empty())
+        else {
+            return true;
+        };
+        let needle = search.to_lowercase();
+        let outcome = &record.outcome;
+        outcome
+            .cleaned
+            .iter()
+            .chain(&outcome.failures)
+            .chain(&record.error)
+            .any(|text| text.to_lowercase().contains(&needle))
+    }
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+pub struct Pagination {
+    #[serde(default)]
+    pub offset: usize,
+    #[serde(default = "default_page_size")]
+    pub limit: usize,
+}
+
+impl Default for Pagination {
+    fn default() -> Self {
+        Self {
+            offset: 0,
+            limit: DEFAULT_PAGE_SIZE,
+        }
+    }
+}
+
+fn default_page_size() -> usize {
+    DEFAULT_PAGE_SIZE
+}
+
+/// A page of matching records, newest first.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HistoryPage {
+    /// Matching records across all pages.
+    pub total: usize,
+    pub offset: usize,
+    pub records: Vec<OperationRecord>,
+}
+
+/// Every finished operation, oldest first.
+#[derive(Default)]
+pub struct OperationHistory {
+    path: Option<PathBuf>,
+    records: Vec<OperationRecord>,
+}
+
+impl OperationHistory {
+    /// Load from `<data dir>/macos-optimizer/operation_history.json`.
+    pub fn load() -> Self {
+        match persistence::data_file(HISTORY_FILE) {
+            Some(path) => Self::load_from(&path),
+            None => Self::default(),
+        }
+    }
+
+    /// Load from an explicit file; a missing or unreadable file yields an empty history.
+    pub fn load_from(path: &Path) -> Self {
+        let records = persistence::read_json(path).unwrap_or_default();
+        Self {
+            path: Some(path.to_path_buf()),
+            records,
+        }
+    }
+
+    pub fn append(&mut self, record: OperationRecord) -> Result<(), String> {
+        self.records.push(record);
+        let excess = self.records.len().saturating_sub(MAX_RECORDS);
+        self.records.drain(..excess);
+        self.persist()
+    }
+
+    pub fn query(&self, filter: &HistoryFilter, page: Pagination) -> HistoryPage {
+        let matching: Vec<&OperationRecord> = self
+            .records
+            .iter()
+            .rev()
+            .filter(|record| filter.matches(record))
+            .collect();
+        HistoryPage {
+            total: matching.len(),
+            offset: page.offset,
+            records: matching
+                .into_iter()
+                .skip(page.offset)
+                .take(page.limit.clamp(1, MAX_PAGE_SIZE))
+                .cloned()
+                .collect(),
+        }
+    }
+
+    /// Recovery point of the newest completed operation that created one.
+    pub fn last_recovery_point_id(&self) -> Option<&str> {
+        self.records
+            .iter()
+            .rev()
+            .filter(|record| record.status == OperationStatus::Completed)
+            .find_map(|record| {
+                record
+                    .outcome
+                    .recovery_point_id
+                    .as_deref()
+                    .filter(|id| !id.is_empty())
+            })
+    }
+
+    /// Write every record to `path` as JSON.
+    pub fn export_to(&self, path: &Path) -> Result<(), String> {
+        persistence::write_json(path, &self.records)
+    }
+
+    fn persist(&self) -> Result<(), String> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        self.export_to(path)
+    }
+}
+
+/// Where an export goes unless the user picks a file: their Downloads folder.
+pub fn default_export_path(now: i64) -> Option<PathBuf> {
+    let date = DateTime::<Utc>::from_timestamp(now, 0)
+        .unwrap_or_default()
+        .format("%Y-%m-%d");
+    dirs::download_dir()
+        .or_else(dirs::home_dir)
+        .map(|dir| dir.join(format!("macos-optimizer-history-{}.json", date)))
+}
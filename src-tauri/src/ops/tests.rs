@@ -0,0 +1,163 @@
+use super::history::{HistoryFilter, OperationHistory, OperationOutcome, Pagination};
+use super::{OperationKind, OperationRegistry, OperationStatus, Phase, INSPECTOR_MODE_REFUSAL};
+
+#[test]
+fn finished_operations_are_logged_with_their_outcome() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("operation_history.json");
+    let ops = OperationRegistry::new(1, 1, 1).with_history(OperationHistory::load_from(&path));
+
+    let (clean, _) = ops.register(OperationKind::FileClean, true).unwrap();
+    ops.record_outcome(
+        &clean,
+        OperationOutcome {
+            recovery_point_id: Some("rp-1".into()),
+            ..OperationOutcome::new(2, 4096)
+                .with_cleaned(["/tmp/a.log".to_string(), "/tmp/b.log".to_string()])
+        },
+    );
+    ops.finish_success(&clean);
+    let (scan, _) = ops.register(OperationKind::FileScan, true).unwrap();
+    ops.finish_failed(&scan, "Permission denied");
+    assert!(ops.get(&clean).is_none());
+
+    let history = OperationHistory::load_from(&path);
+    let page = history.query(&HistoryFilter::default(), Pagination::default());
+    assert_eq!(page.total, 2);
+    // Newest first.
+    assert_eq!(page.records[0].id, scan);
+    assert_eq!(page.records[0].status, OperationStatus::Failed);
+    assert_eq!(page.records[0].error.as_deref(), Some("Permission denied"));
+    let cleaned = &page.records[1];
+    assert_eq!(cleaned.status, OperationStatus::Completed);
+    assert_eq!(cleaned.outcome.bytes_freed, 4096);
+    assert_eq!(cleaned.outcome.recovery_point_id.as_deref(), Some("rp-1"));
+    assert_eq!(history.last_recovery_point_id(), Some("rp-1"));
+}
+
+#[test]
+fn inspector_mode_refuses_process_control_and_destructive_operations() {
+    let ops = OperationRegistry::new(1, 1, 1);
+    assert!(ops.refuse_in_inspector_mode().is_ok());
+
+    ops.set_inspector_mode(true);
+    // Kill, suspend, resume and priority changes check this before
+    // touching the process.
+    assert_eq!(
+        ops.refuse_in_inspector_mode().unwrap_err(),
+        INSPECTOR_MODE_REFUSAL
+    );
+    assert_eq!(
+        ops.register(OperationKind::FileClean, true).unwrap_err(),
+        INSPECTOR_MODE_REFUSAL
+    );
+    assert!(ops.register(OperationKind::FileScan, true).is_ok());
+
+    ops.set_inspector_mode(false);
+    assert!(ops.refuse_in_inspector_mode().is_ok());
+}
+
+#[test]
+fn audit_mode_holds_inspector_mode_without_overriding_it() {
+    let ops = OperationRegistry::new(1, 1, 1);
+    ops.set_audit_mode(true);
+    assert!(ops.inspector_mode());
+    ops.set_audit_mode(false);
+    assert!(!ops.inspector_mode());
+
+    // Inspector mode turned on by the user outlives audit mode.
+    ops.set_inspector_mode(true);
+    ops.set_audit_mode(true);
+    ops.set_audit_mode(false);
+    assert!(ops.inspector_mode());
+}
+
+#[test]
+fn history_queries_filter_and_paginate() {
+    let ops = OperationRegistry::new(1, 1, 1);
+    for index in 0..5 {
+        let (id, _) = ops.register(OperationKind::EmptyTrash, true).unwrap();
+        ops.record_outcome(
+            &id,
+            OperationOutcome::new(1, 10).with_cleaned([format!("/Users/me/.Trash/item-{}", index)]),
+        );
+        ops.finish_success(&id);
+    }
+    let (canceled, _) = ops.register(OperationKind::FileScan, true).unwrap();
+    ops.finish_canceled(&canceled);
+
+    let history = ops.history();
+    let trash_only = HistoryFilter {
+        kind: Some(OperationKind::EmptyTrash),
+        ..HistoryFilter::default()
+    };
+    let page = history.query(
+        &trash_only,
+        Pagination {
+            offset: 1,
+            limit: 2,
+        },
+    );
+    assert_eq!(page.total, 5);
+    assert_eq!(page.records.len(), 2);
+    assert_eq!(page.records[0].outcome.cleaned, ["/Users/me/.Trash/item-3"]);
+
+    let search = HistoryFilter {
+        search: Some("ITEM-0".into()),
+        ..HistoryFilter::default()
+    };
+    assert_eq!(history.query(&search, Pagination::default()).total, 1);
+    let canceled_only = HistoryFilter {
+        status: Some(OperationStatus::Canceled),
+        ..HistoryFilter::default()
+    };
+    let page = history.query(&canceled_only, Pagination::default());
+    assert_eq!(page.records.len(), 1);
+    assert_eq!(page.records[0].id, canceled);
+}
+
+#[test]
+fn stage_changes_split_time_into_phases() {
+    assert_eq!(Phase::from_stage("safety"), Some(Phase::Analysis));
+    assert_eq!(Phase::from_stage("cache_clear"), Some(Phase::ExternalTool));
+    assert_eq!(Phase::from_stage("complete"), None);
+
+    let ops = OperationRegistry::new(1, 1, 1);
+    let (id, _) = ops.register(OperationKind::FileClean, true).unwrap();
+    ops.checkpoint(&id, "validation");
+    std::thread::sleep(std::time::Duration::from_millis(20));
+    ops.checkpoint(&id, "deleting");
+    std::thread::sleep(std::time::Duration::from_millis(20));
+    // Same phase: the deletion clock keeps running.
+    ops.checkpoint(&id, "finalizing");
+    let running = ops.phase_timings(&id).unwrap();
+    assert!(running.validation_ms >= 20);
+    assert!(running.deletion_ms >= 20);
+    assert_eq!(running.discovery_ms, 0);
+
+    ops.finish_success(&id);
+    assert!(ops.phase_timings(&id).is_none());
+    let history = ops.history();
+    let page = history.query(&HistoryFilter::default(), Pagination::default());
+    let recorded = page.records[0].phase_timings;
+    assert_eq!(recorded.validation_ms, running.validation_ms);
+    assert!(recorded.deletion_ms >= running.deletion_ms);
+}
+
+#[tokio::test]
+async fn resized_permits_wait_for_running_operations() {
+    let ops = OperationRegistry::new(1, 2, 1);
+    ops.resize_permits(2, 4, 1);
+    assert_eq!(ops.scan_sem.available_permits(), 2);
+    assert_eq!(ops.clean_sem.available_permits(), 4);
+
+    let running = ops.clean_sem.clone().acquire_many_owned(3).await.unwrap();
+    ops.resize_permits(2, 1, 1);
+    // The idle permit goes at once, the rest once the cleanups finish.
+    assert_eq!(ops.clean_sem.available_permits(), 0);
+    drop(running);
+    for _ in 0..10 {
+        tokio::task::yield_now().await;
+    }
+    assert_eq!(ops.clean_sem.available_permits(), 1);
+}
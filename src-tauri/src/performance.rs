@@ -0,0 +1,198 @@
+// src/performance.rs
+
+//! How hard scans and cleanups may push the machine.
+//!
+//! A profile sets how many scans, cleanups and optimizations may run at
+//! once, how many threads walk the file system during a scan, how many
+//! directory entries are walked per batch and, on the
+//! lowest setting, whether scan threads run at utility QoS so the rest of
+//! the system stays responsive. Walking on several threads at all is a
+//! separate switch, for disks that slow down under parallel reads.
+
+#[cfg(feature = "app")]
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+
+#[cfg(feature = "app")]
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "app")]
+use crate::persistence;
+
+#[cfg(all(test, feature = "app"))]
+mod tests;
+
+#[cfg(feature = "app")]
+const PERFORMANCE_PROFILE_FILE: &str = "performance_profile.json";
+/// Entries per batch when no profile has been applied.
+const DEFAULT_WALK_BATCH_SIZE: usize = 100;
+
+#[cfg(feature = "app")]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PerformanceProfile {
+    /// Background-friendly: one operation of each kind, a quarter of the
+    /// cores for scanning, scan threads at utility QoS.
+    Low,
+    #[default]
+    Balanced,
+    /// Scans and cleanups may overlap and scanning oversubscribes the
+    /// cores, since walking is mostly waiting on the disk.
+    Aggressive,
+}
+
+/// What a profile resolves to on this machine.
+#[cfg(feature = "app")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PerformanceSettings {
+    pub profile: PerformanceProfile,
+    pub scan_permits: usize,
+    pub clean_permits: usize,
+    pub opt_permits: usize,
+    pub scan_threads: usize,
+    pub walk_batch_size: usize,
+    /// Scan threads run at utility QoS (macOS only).
+    pub background_qos: bool,
+}
+
+#[cfg(feature = "app")]
+impl PerformanceProfile {
+    pub fn settings(self) -> PerformanceSettings {
+        self.settings_for(num_cpus::get())
+    }
+
+    pub(crate) fn settings_for(self, cpus: usize) -> PerformanceSettings {
+        let cpus = cpus.max(1);
+        match self {
+            PerformanceProfile::Low => PerformanceSettings {
+                profile: self,
+                scan_permits: 1,
+                clean_permits: 1,
+                opt_permits: 1,
+                scan_threads: (cpus / 4).max(1),
+                walk_batch_size: 50,
+                background_qos: true,
+            },
+            PerformanceProfile::Balanced => PerformanceSettings {
+                profile: self,
+                scan_permits: 1,
+                clean_permits: 2,
+                opt_permits: 1,
+                scan_threads: cpus,
+                walk_batch_size: DEFAULT_WALK_BATCH_SIZE,
+                background_qos: false,
+            },
+            PerformanceProfile::Aggressive => PerformanceSettings {
+                profile: self,
+                scan_permits: 2,
+                clean_permits: 4,
+                opt_permits: 1,
+                scan_threads: cpus * 2,
+                walk_batch_size: 500,
+                background_qos: false,
+            },
+        }
+    }
+}
+
+/// The chosen profile, persisted across launches.
+#[cfg(feature = "app")]
+#[derive(Default)]
+pub struct PerformanceProfileStore {
+    path: Option<PathBuf>,
+    profile: PerformanceProfile,
+}
+
+#[cfg(feature = "app")]
+impl PerformanceProfileStore {
+    /// Load from `<data dir>/macos-optimizer/performance_profile.json`.
+    pub fn load() -> Self {
+        match persistence::data_file(PERFORMANCE_PROFILE_FILE) {
+            Some(path) => Self::load_from(&path),
+            None => Self::default(),
+        }
+    }
+
+    /// Load from an explicit file; a missing or unreadable file yields the
+    /// balanced profile.
+    pub fn load_from(path: &Path) -> Self {
+        let profile = persistence::read_json(path).unwrap_or_default();
+        Self {
+            path: Some(path.to_path_buf()),
+            profile,
+        }
+    }
+
+    pub fn profile(&self) -> PerformanceProfile {
+        self.profile
+    }
+
+    pub fn set(&mut self, profile: PerformanceProfile) -> Result<(), String> {
+        self.profile = profile;
+        self.persist()
+    }
+
+    fn persist(&self) -> Result<(), String> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        persistence::write_json(path, &self.profile)
+    }
+}
+
+static WALK_BATCH_SIZE: AtomicUsize = AtomicUsize::new(DEFAULT_WALK_BATCH_SIZE);
+/// Scan threads; 0 until a profile is applied, meaning one per core.
+static SCAN_THREADS: AtomicUsize = AtomicUsize::new(0);
+static BACKGROUND_QOS: AtomicBool = AtomicBool::new(false);
+static PARALLEL_SCAN: AtomicBool = AtomicBool::new(true);
+
+/// Use `settings`' scan threads and batching from the next scan on.
+#[cfg(feature = "app")]
+pub fn apply(settings: &PerformanceSettings) {
+    SCAN_THREADS.store(settings.scan_threads.max(1), Ordering::Relaxed);
+    BACKGROUND_QOS.store(settings.background_qos, Ordering::Relaxed);
+    WALK_BATCH_SIZE.store(settings.walk_batch_size.max(1), Ordering::Relaxed);
+}
+
+/// Walk on the profile's scan threads, or on a single one when `enabled`
+/// is off, from the next scan on.
+#[cfg(any(test, feature = "app"))]
+pub fn set_parallel_scan(enabled: bool) {
+    PARALLEL_SCAN.store(enabled, Ordering::Relaxed);
+}
+
+/// Threads the next scan walks the file system with.
+pub(crate) fn scan_threads() -> usize {
+    if !PARALLEL_SCAN.load(Ordering::Relaxed) {
+        return 1;
+    }
+    match SCAN_THREADS.load(Ordering::Relaxed) {
+        0 => num_cpus::get(),
+        threads => threads,
+    }
+}
+
+/// Called by each scan thread as it starts, to apply the profile's QoS.
+pub(crate) fn scan_thread_started() {
+    if BACKGROUND_QOS.load(Ordering::Relaxed) {
+        lower_thread_qos();
+    }
+}
+
+/// Directory entries a scan walks per batch.
+pub(crate) fn walk_batch_size() -> usize {
+    WALK_BATCH_SIZE.load(Ordering::Relaxed)
+}
+
+#[cfg(target_os = "macos")]
+fn lower_thread_qos() {
+    // SAFETY: only changes the scheduling class of the calling thread.
+    let result =
+        unsafe { libc::pthread_set_qos_class_self_np(libc::qos_class_t::QOS_CLASS_UTILITY, 0) };
+    if result != 0 {
+        log::debug!("Could not lower scan thread QoS: {}", result);
+    }
+}
+
+#[cfg(not(target_os = "macos"))]
+fn lower_thread_qos() {}
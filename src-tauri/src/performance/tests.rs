@@ -0,0 +1,28 @@
+use super::{PerformanceProfile, PerformanceProfileStore};
+
+#[test]
+fn profiles_scale_with_the_machine_and_persist() {
+    let low = PerformanceProfile::Low.settings_for(2);
+    assert_eq!(low.scan_threads, 1);
+    assert!(low.background_qos);
+    let balanced = PerformanceProfile::Balanced.settings_for(8);
+    assert_eq!((balanced.scan_permits, balanced.clean_permits), (1, 2));
+    assert_eq!(balanced.scan_threads, 8);
+    let aggressive = PerformanceProfile::Aggressive.settings_for(8);
+    assert_eq!(aggressive.scan_threads, 16);
+    assert!(aggressive.walk_batch_size > balanced.walk_batch_size);
+
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("performance_profile.json");
+    assert_eq!(
+        PerformanceProfileStore::load_from(&path).profile(),
+        PerformanceProfile::Balanced
+    );
+    PerformanceProfileStore::load_from(&path)
+        .set(PerformanceProfile::Low)
+        .unwrap();
+    assert_eq!(
+        PerformanceProfileStore::load_from(&path).profile(),
+        PerformanceProfile::Low
+    );
+}
@@ -0,0 +1,168 @@
+// src/permissions.rs
+
+//! Whether the app has Full Disk Access, judged by reading a few locations
+//! macOS only opens to apps that have it.
+//!
+//! Without it, walks of protected folders come back empty instead of
+//! failing, so scans quietly miss what is in them. The report lists each
+//! probed location with what it is needed for, so onboarding can explain
+//! what granting access changes before sending the user to System Settings.
+
+use std::fs;
+use std::io::ErrorKind;
+use std::path::Path;
+use std::process::Command;
+
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+
+#[cfg(test)]
+mod tests;
+
+/// The Full Disk Access pane of System Settings.
+const FULL_DISK_ACCESS_PANE: &str =
+    "x-apple.systempreferences:com.apple.preference.security?Privacy_AllFiles";
+
+/// A protected location to probe: its name, its path (under the home folder
+/// when relative) and what reading it is needed for.
+pub(crate) struct ProtectedLocation {
+    pub name: &'static str,
+    pub path: &'static str,
+    pub affects: &'static [&'static str],
+}
+
+pub(crate) const PROTECTED_LOCATIONS: &[ProtectedLocation] = &[
+    ProtectedLocation {
+        name: "Mail",
+        path: "Library/Mail",
+        affects: &["Mail downloads and attachment cleanup"],
+    },
+    ProtectedLocation {
+        name: "Messages",
+        path: "Library/Messages",
+        affects: &["Messages attachments by conversation"],
+    },
+    ProtectedLocation {
+        name: "Safari",
+        path: "Library/Safari",
+        affects: &["Safari caches and website data"],
+    },
+    ProtectedLocation {
+        name: "Time Machine preferences",
+        path: "/Library/Preferences/com.apple.TimeMachine.plist",
+        affects: &["Time Machine exclusion and backup status checks"],
+    },
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LocationAccess {
+    Granted,
+    Denied,
+    /// The location does not exist, so it says nothing about access.
+    Missing,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FullDiskAccess {
+    Granted,
+    Denied,
+    /// None of the probed locations exist.
+    Unknown,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PermissionProbe {
+    pub name: String,
+    pub path: String,
+    pub access: LocationAccess,
+    /// What scans miss while the location cannot be read.
+    pub affects: Vec<String>,
+}
+
+/// Result of `check_permissions`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PermissionReport {
+    pub checked_at: i64,
+    pub full_disk_access: FullDiskAccess,
+    pub probes: Vec<PermissionProbe>,
+}
+
+impl PermissionReport {
+    /// Denied as soon as one location cannot be read, granted when at least
+    /// one can and none is denied.
+    pub fn from_probes(probes: Vec<PermissionProbe>) -> Self {
+        let full_disk_access = if probes
+            .iter()
+            .any(|probe| probe.access == LocationAccess::Denied)
+        {
+            FullDiskAccess::Denied
+        } else if probes
+            .iter()
+            .any(|probe| probe.access == LocationAccess::Granted)
+        {
+            FullDiskAccess::Granted
+        } else {
+            FullDiskAccess::Unknown
+        };
+        Self {
+            checked_at: Utc::now().timestamp(),
+            full_disk_access,
+            probes,
+        }
+    }
+}
+
+/// Probe the protected locations of the current user.
+pub fn check_permissions() -> PermissionReport {
+    let home = dirs::home_dir().unwrap_or_default();
+    check_permissions_in(&home, PROTECTED_LOCATIONS)
+}
+
+pub(crate) fn check_permissions_in(
+    home: &Path,
+    locations: &[ProtectedLocation],
+) -> PermissionReport {
+    let probes = locations
+        .iter()
+        .map(|location| {
+            let path = home.join(location.path);
+            PermissionProbe {
+                name: location.name.to_string(),
+                access: probe(&path),
+                path: path.to_string_lossy().to_string(),
+                affects: location.affects.iter().map(|s| s.to_string()).collect(),
+            }
+        })
+        .collect();
+    PermissionReport::from_probes(probes)
+}
+
+/// Open the Full Disk Access pane of System Settings, where the user can
+/// add the app.
+pub fn request_full_disk_access() -> Result<(), String> {
+    let status = Command::new("open")
+        .arg(FULL_DISK_ACCESS_PANE)
+        .status()
+        .map_err(|e| format!("Failed to open System Settings: {}", e))?;
+    if !status.success() {
+        return Err(format!("Failed to open System Settings ({})", status));
+    }
+    Ok(())
+}
+
+/// Reading a folder's listing or a file's first bytes is what privacy
+/// protection refuses without Full Disk Access; `stat` alone succeeds.
+fn probe(path: &Path) -> LocationAccess {
+    let read = if path.is_dir() {
+        fs::read_dir(path).map(|_| ())
+    } else {
+        fs::File::open(path).map(|_| ())
+    };
+    match read {
+        Ok(()) => LocationAccess::Granted,
+        Err(e) if e.kind() == ErrorKind::NotFound => LocationAccess::Missing,
+        Err(_) => LocationAccess::Denied,
+    }
+}
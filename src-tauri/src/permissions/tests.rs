@@ -0,0 +1,69 @@
+use super::{
+    check_permissions_in, FullDiskAccess, LocationAccess, PermissionProbe, PermissionReport,
+    ProtectedLocation,
+};
+use std::fs;
+use tempfile::TempDir;
+
+fn probe(access: LocationAccess) -> PermissionProbe {
+    PermissionProbe {
+        name: "Test".into(),
+        path: "/test".into(),
+        access,
+        affects: Vec::new(),
+    }
+}
+
+#[test]
+fn readable_and_missing_locations_are_told_apart() {
+    let home = TempDir::new().unwrap();
+    fs::create_dir_all(home.path().join("Library/Mail/V10")).unwrap();
+    fs::write(home.path().join("tm.plist"), b"<plist/>").unwrap();
+    let locations = [
+        ProtectedLocation {
+            name: "Mail",
+            path: "Library/Mail",
+            affects: &["Mail downloads"],
+        },
+        ProtectedLocation {
+            name: "Messages",
+            path: "Library/Messages",
+            affects: &[],
+        },
+        ProtectedLocation {
+            name: "Time Machine preferences",
+            path: "tm.plist",
+            affects: &[],
+        },
+    ];
+
+    let report = check_permissions_in(home.path(), &locations);
+    let access: Vec<LocationAccess> = report.probes.iter().map(|p| p.access).collect();
+    assert_eq!(
+        access,
+        vec![
+            LocationAccess::Granted,
+            LocationAccess::Missing,
+            LocationAccess::Granted
+        ]
+    );
+    assert_eq!(report.full_disk_access, FullDiskAccess::Granted);
+    assert_eq!(report.probes[0].affects, vec!["Mail downloads".to_string()]);
+    assert!(report.probes[0].path.ends_with("Library/Mail"));
+
+    let json = serde_json::to_value(&report).unwrap();
+    assert_eq!(json["full_disk_access"], "granted");
+    assert_eq!(json["probes"][1]["access"], "missing");
+}
+
+#[test]
+fn one_denied_location_means_no_full_disk_access() {
+    let report = PermissionReport::from_probes(vec![
+        probe(LocationAccess::Granted),
+        probe(LocationAccess::Denied),
+    ]);
+    assert_eq!(report.full_disk_access, FullDiskAccess::Denied);
+
+    let report = PermissionReport::from_probes(vec![probe(LocationAccess::Missing)]);
+    assert_eq!(report.full_disk_access, FullDiskAccess::Unknown);
+}
@@ -0,0 +1,82 @@
+// src/persistence.rs
+
+//! Where the app keeps its state and how that state is read and written.
+//!
+//! Every store lives as one JSON file under `<data dir>/macos-optimizer`.
+//! Files are replaced atomically, through a temporary file renamed over the
+//! old one, so a crash or a full disk mid-write leaves the previous version
+//! instead of a truncated file that would load as defaults.
+
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+#[cfg(test)]
+mod tests;
+
+const APP_DIR: &str = "macos-optimizer";
+
+/// `<data dir>/macos-optimizer`, or `None` where there is no data directory.
+pub fn data_dir() -> Option<PathBuf> {
+    dirs::data_dir().map(|dir| dir.join(APP_DIR))
+}
+
+/// `<data dir>/macos-optimizer/<name>`.
+pub fn data_file(name: &str) -> Option<PathBuf> {
+    data_dir().map(|dir| dir.join(name))
+}
+
+/// The JSON value stored at `path`; `None` if the file is missing or
+/// malformed, which is logged.
+pub fn read_json<T: DeserializeOwned>(path: &Path) -> Option<T> {
+    let data = fs::read(path).ok()?;
+    match serde_json::from_slice(&data) {
+        Ok(value) => Some(value),
+        Err(err) => {
+            log::warn!("Ignoring malformed {}: {}", path.display(), err);
+            None
+        }
+    }
+}
+
+/// Store `value` at `path` as pretty-printed JSON, atomically.
+pub fn write_json<T: Serialize + ?Sized>(path: &Path, value: &T) -> Result<(), String> {
+    let data = serde_json::to_vec_pretty(value)
+        .map_err(|e| format!("Failed to serialize {}: {}", path.display(), e))?;
+    write_atomic(path, &data)
+}
+
+/// Replace `path` with `data`, creating its directory first. The data is
+/// written and synced to a temporary file next to `path` and renamed over
+/// it, so readers see either the old contents or the new ones.
+pub fn write_atomic(path: &Path, data: &[u8]) -> Result<(), String> {
+    static NEXT_TEMP: AtomicU64 = AtomicU64::new(0);
+
+    let name = path
+        .file_name()
+        .ok_or_else(|| format!("Invalid file path: {}", path.display()))?;
+    let parent = path.parent().unwrap_or_else(|| Path::new("."));
+    fs::create_dir_all(parent)
+        .map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+    let temp = parent.join(format!(
+        ".{}.{}-{}.tmp",
+        name.to_string_lossy(),
+        std::process::id(),
+        NEXT_TEMP.fetch_add(1, Ordering::Relaxed)
+    ));
+    let written = fs::File::create(&temp)
+        .and_then(|mut file| {
+            file.write_all(data)?;
+            file.sync_all()
+        })
+        .and_then(|()| fs::rename(&temp, path));
+    if let Err(err) = written {
+        let _ = fs::remove_file(&temp);
+        return Err(format!("Failed to write {}: {}", path.display(), err));
+    }
+    Ok(())
+}
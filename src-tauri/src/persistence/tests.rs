@@ -0,0 +1,46 @@
+use std::collections::BTreeMap;
+use std::fs;
+
+use super::{read_json, write_atomic, write_json};
+use tempfile::TempDir;
+
+#[test]
+fn json_round_trips_through_an_atomic_replace() {
+    let temp_dir = TempDir::new().unwrap();
+    let path = temp_dir.path().join("nested/store.json");
+    let value: BTreeMap<String, u32> = [("a".to_string(), 1), ("b".to_string(), 2)].into();
+
+    write_json(&path, &value).unwrap();
+    assert_eq!(read_json::<BTreeMap<String, u32>>(&path), Some(value));
+
+    write_json(&path, &vec![3, 4]).unwrap();
+    assert_eq!(read_json::<Vec<u32>>(&path), Some(vec![3, 4]));
+    // No temporary files are left next to the store.
+    let names: Vec<_> = fs::read_dir(path.parent().unwrap())
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name())
+        .collect();
+    assert_eq!(names, ["store.json"]);
+}
+
+#[test]
+fn missing_or_malformed_files_read_as_none() {
+    let temp_dir = TempDir::new().unwrap();
+    let path = temp_dir.path().join("store.json");
+    assert_eq!(read_json::<Vec<u32>>(&path), None);
+
+    fs::write(&path, b"{ truncated").unwrap();
+    assert_eq!(read_json::<Vec<u32>>(&path), None);
+}
+
+#[test]
+fn a_failed_replace_leaves_the_target_and_no_temporary_file() {
+    let temp_dir = TempDir::new().unwrap();
+    // A non-empty directory in the way makes the rename fail.
+    let path = temp_dir.path().join("store.json");
+    fs::create_dir_all(path.join("child")).unwrap();
+
+    assert!(write_atomic(&path, b"[1]").is_err());
+    assert!(path.join("child").exists());
+    assert_eq!(fs::read_dir(temp_dir.path()).unwrap().count(), 1);
+}
@@ -0,0 +1,775 @@
+// src/privileged_helper.rs
+
+//! A root helper that runs a fixed set of elevated operations for the app,
+//! so the administrator password is asked for once, at install, instead of
+//! on every elevated delete, purge or maintenance run.
+//!
+//! The helper is this crate's `macos-optimizer-helper` binary, installed as
+//! a launch daemon. It listens on a Unix socket and serves one request per
+//! connection: a JSON line listing [`HelperCommand`]s, answered by a JSON
+//! line with one outcome per command. It only takes requests from the user
+//! who installed it, only removes paths inside that user's home folder
+//! without following symlinks, runs every command without a shell, and
+//! appends each one with its outcome to an audit log.
+//!
+//! The trust boundary is the user's uid, not this app: the socket checks
+//! the peer's uid and nothing else, so any process running as that user can
+//! ask the helper for what the app can. [`INSTALL_PROMPT`] says so before
+//! the password is given.
+//!
+//! Callers try the helper first and fall back to the administrator prompt
+//! when it is not installed or not running.
+
+use std::ffi::{CStr, CString, OsStr, OsString};
+use std::fs;
+use std::io::{Read, Write};
+use std::os::unix::ffi::OsStrExt;
+use std::os::unix::fs::PermissionsExt;
+use std::os::unix::io::{AsRawFd, FromRawFd, OwnedFd};
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::{Component, Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+#[cfg(any(target_os = "macos", feature = "engine", test))]
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+#[cfg(any(target_os = "macos", feature = "engine", test))]
+use tokio_util::sync::CancellationToken;
+
+#[cfg(feature = "app")]
+use crate::elevation;
+use crate::system_maintenance::MaintenanceTask;
+
+#[cfg(test)]
+mod tests;
+
+#[cfg(feature = "app")]
+pub const HELPER_LABEL: &str = "com.macos-optimizer.helper";
+/// Shown in the administrator prompt that installs the helper.
+#[cfg(feature = "app")]
+pub(crate) const INSTALL_PROMPT: &str = "macOS Optimizer wants to install a helper that runs \
+     as root so deleting items in your home folder and maintenance tasks stop asking for your \
+     password. Any app running as you will be able to ask the helper for the same operations.";
+/// Name of the helper binary next to the app's executable.
+#[cfg(feature = "app")]
+pub const HELPER_BINARY_NAME: &str = "macos-optimizer-helper";
+#[cfg(feature = "app")]
+const HELPER_PATH: &str = "/Library/PrivilegedHelperTools/com.macos-optimizer.helper";
+#[cfg(feature = "app")]
+const LAUNCHD_PLIST_PATH: &str = "/Library/LaunchDaemons/com.macos-optimizer.helper.plist";
+const SOCKET_PATH: &str = "/var/run/com.macos-optimizer.helper.sock";
+const AUDIT_LOG_PATH: &str = "/Library/Logs/macos-optimizer-helper.log";
+const HELPER_VERSION: &str = env!("CARGO_PKG_VERSION");
+/// Requests are a few paths at most; anything longer is not from the app.
+const MAX_REQUEST_BYTES: usize = 4 * 1024 * 1024;
+/// How long a connection has to send its request.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(5);
+/// BSD interface names are at most 15 characters.
+const MAX_INTERFACE_NAME: usize = 15;
+
+const PURGE: &str = "/usr/sbin/purge";
+const DSCACHEUTIL: &str = "/usr/bin/dscacheutil";
+const KILLALL: &str = "/usr/bin/killall";
+const IPCONFIG: &str = "/usr/sbin/ipconfig";
+
+/// An operation the helper runs as root.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum HelperCommand {
+    /// Answer with the helper's version.
+    Ping,
+    /// Remove a file or folder inside the requesting user's home folder.
+    Remove {
+        path: String,
+    },
+    Purge,
+    FlushDns,
+    RestartMdns,
+    RenewDhcp {
+        interface: String,
+    },
+    /// A maintenance task that needs root.
+    Maintenance {
+        task: MaintenanceTask,
+    },
+}
+
+impl HelperCommand {
+    /// The program and arguments behind a command that runs one; `Ping` and
+    /// `Remove` run none.
+    fn programs(&self) -> Result<Vec<(&'static str, Vec<String>)>, String> {
+        let args = |args: &[&str]| args.iter().map(|arg| arg.to_string()).collect();
+        Ok(match self {
+            HelperCommand::Ping | HelperCommand::Remove { .. } => Vec::new(),
+            HelperCommand::Purge => vec![(PURGE, Vec::new())],
+            HelperCommand::FlushDns => vec![
+                (DSCACHEUTIL, args(&["-flushcache"])),
+                (KILLALL, args(&["-HUP", "mDNSResponder"])),
+            ],
+            HelperCommand::RestartMdns => vec![(KILLALL, args(&["mDNSResponder"]))],
+            HelperCommand::RenewDhcp { interface } => {
+                if !is_valid_interface(interface) {
+                    return Err(format!("Not a network interface name: {}", interface));
+                }
+                vec![(IPCONFIG, args(&["set", interface, "DHCP"]))]
+            }
+            HelperCommand::Maintenance { task } => {
+                if !task.needs_admin() {
+                    return Err(format!("{} does not run as root", task.label()));
+                }
+                vec![(task.program(), args(task.args()))]
+            }
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HelperOutcome {
+    pub ok: bool,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct HelperRequest {
+    commands: Vec<HelperCommand>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct HelperReply {
+    version: String,
+    /// One per command, in order; empty when the request was refused.
+    outcomes: Vec<HelperOutcome>,
+    /// Why the request as a whole was refused.
+    error: Option<String>,
+}
+
+/// One line of the audit log.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HelperAuditEntry {
+    pub at: i64,
+    pub uid: u32,
+    pub command: HelperCommand,
+    pub ok: bool,
+    pub error: Option<String>,
+}
+
+/// Result of `get_privileged_helper_status`.
+#[cfg(feature = "app")]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HelperStatus {
+    pub installed: bool,
+    pub running: bool,
+    /// Version the running helper reported.
+    pub version: Option<String>,
+    /// Set when the running helper is not the app's version and should be
+    /// installed again.
+    pub outdated: bool,
+    /// The most recent audit log entries, newest first.
+    pub recent: Vec<HelperAuditEntry>,
+}
+
+// -------- Client --------
+
+/// Run `commands` through the helper. `Ok(None)` when the helper is not
+/// reachable, in which case nothing ran and the caller should fall back to
+/// the administrator prompt. Cancelling stops waiting; a command already
+/// running finishes.
+#[cfg(any(target_os = "macos", feature = "engine"))]
+pub async fn send(
+    commands: &[HelperCommand],
+    token: &CancellationToken,
+) -> Result<Option<Vec<HelperOutcome>>, String> {
+    send_to(Path::new(SOCKET_PATH), commands, token).await
+}
+
+#[cfg(any(target_os = "macos", feature = "engine", test))]
+pub(crate) async fn send_to(
+    socket: &Path,
+    commands: &[HelperCommand],
+    token: &CancellationToken,
+) -> Result<Option<Vec<HelperOutcome>>, String> {
+    let Ok(mut stream) = tokio::net::UnixStream::connect(socket).await else {
+        return Ok(None);
+    };
+    let mut request = serde_json::to_vec(&HelperRequest {
+        commands: commands.to_vec(),
+    })
+    .map_err(|e| format!("Failed to encode helper request: {}", e))?;
+    request.push(b'\n');
+    stream
+        .write_all(&request)
+        .await
+        .map_err(|e| format!("Failed to send helper request: {}", e))?;
+    let mut reply = Vec::new();
+    tokio::select! {
+        _ = token.cancelled() => return Err("cancelled".into()),
+        read = stream.read_to_end(&mut reply) => {
+            read.map_err(|e| format!("Failed to read helper reply: {}", e))?;
+        }
+    }
+    let reply: HelperReply =
+        serde_json::from_slice(&reply).map_err(|e| format!("Unreadable helper reply: {}", e))?;
+    if let Some(error) = reply.error {
+        return Err(format!("Helper refused the request: {}", error));
+    }
+    if reply.outcomes.len() != commands.len() {
+        return Err("Helper reply does not match the request".into());
+    }
+    Ok(Some(reply.outcomes))
+}
+
+/// Whether the helper is installed and answering, its version, and its most
+/// recent audit entries.
+#[cfg(feature = "app")]
+pub async fn status(recent: usize) -> HelperStatus {
+    let version = ping(Path::new(SOCKET_PATH)).await;
+    HelperStatus {
+        installed: Path::new(HELPER_PATH).exists() && Path::new(LAUNCHD_PLIST_PATH).exists(),
+        running: version.is_some(),
+        outdated: version.as_deref().is_some_and(|v| v != HELPER_VERSION),
+        version,
+        recent: read_audit_log(Path::new(AUDIT_LOG_PATH), recent),
+    }
+}
+
+#[cfg(feature = "app")]
+async fn ping(socket: &Path) -> Option<String> {
+    let mut stream = tokio::net::UnixStream::connect(socket).await.ok()?;
+    let mut request = serde_json::to_vec(&HelperRequest {
+        commands: vec![HelperCommand::Ping],
+    })
+    .ok()?;
+    request.push(b'\n');
+    stream.write_all(&request).await.ok()?;
+    let mut reply = Vec::new();
+    stream.read_to_end(&mut reply).await.ok()?;
+    let reply: HelperReply = serde_json::from_slice(&reply).ok()?;
+    reply.error.is_none().then_some(reply.version)
+}
+
+/// The last `limit` entries of the audit log at `path`, newest first.
+#[cfg(any(test, feature = "app"))]
+pub(crate) fn read_audit_log(path: &Path, limit: usize) -> Vec<HelperAuditEntry> {
+    let Ok(data) = fs::read_to_string(path) else {
+        return Vec::new();
+    };
+    data.lines()
+        .rev()
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .take(limit)
+        .collect()
+}
+
+/// Install `binary` as the helper for the current user behind one
+/// administrator prompt, replacing any earlier install.
+#[cfg(feature = "app")]
+pub async fn install(binary: &Path, token: &CancellationToken) -> Result<(), String> {
+    if !binary.is_file() {
+        return Err(format!("Helper binary not found at {}", binary.display()));
+    }
+    let uid = unsafe { libc::getuid() };
+    let staged = std::env::temp_dir().join(format!("{}.plist", HELPER_LABEL));
+    fs::write(&staged, launchd_plist(HELPER_PATH, uid))
+        .map_err(|e| format!("Failed to write {}: {}", staged.display(), e))?;
+    let steps = install_steps(binary, &staged);
+    let output = elevation::run_as_admin_with_prompt(
+        &elevation::marker_script(steps.iter().map(|(key, command)| (*key, command.clone()))),
+        INSTALL_PROMPT,
+        token,
+    )
+    .await;
+    let _ = fs::remove_file(&staged);
+    check_markers(&output?, &steps)
+}
+
+/// Stop and remove the helper behind one administrator prompt.
+#[cfg(feature = "app")]
+pub async fn uninstall(token: &CancellationToken) -> Result<(), String> {
+    let steps = vec![
+        (
+            "unload",
+            format!("launchctl bootout system/{} || true", HELPER_LABEL),
+        ),
+        (
+            "remove",
+            format!(
+                "rm -f {} {} {}",
+                LAUNCHD_PLIST_PATH, HELPER_PATH, SOCKET_PATH
+            ),
+        ),
+    ];
+    let script =
+        elevation::marker_script(steps.iter().map(|(key, command)| (*key, command.clone())));
+    check_markers(&elevation::run_as_admin(&script, token).await?, &steps)
+}
+
+#[cfg(feature = "app")]
+pub(crate) fn install_steps(binary: &Path, staged_plist: &Path) -> Vec<(&'static str, String)> {
+    vec![
+        (
+            "unload",
+            format!("launchctl bootout system/{} || true", HELPER_LABEL),
+        ),
+        (
+            "binary",
+            format!(
+                "mkdir -p /Library/PrivilegedHelperTools && cp {} {} && chown root:wheel {} && chmod 755 {}",
+                shell_quote(&binary.to_string_lossy()),
+                HELPER_PATH,
+                HELPER_PATH,
+                HELPER_PATH
+            ),
+        ),
+        (
+            "plist",
+            format!(
+                "cp {} {} && chown root:wheel {} && chmod 644 {}",
+                shell_quote(&staged_plist.to_string_lossy()),
+                LAUNCHD_PLIST_PATH,
+                LAUNCHD_PLIST_PATH,
+                LAUNCHD_PLIST_PATH
+            ),
+        ),
+        (
+            "load",
+            format!("launchctl bootstrap system {}", LAUNCHD_PLIST_PATH),
+        ),
+    ]
+}
+
+#[cfg(feature = "app")]
+fn check_markers(output: &str, steps: &[(&str, String)]) -> Result<(), String> {
+    for (key, _) in steps {
+        match elevation::marker_status(output, key) {
+            Some(true) => {}
+            Some(false) => return Err(format!("Helper {} step failed", key)),
+            None => return Err(format!("Helper {} step did not run", key)),
+        }
+    }
+    Ok(())
+}
+
+/// `value` in single quotes for the shell; the admin prompt escapes the
+/// whole script for AppleScript.
+#[cfg(feature = "app")]
+fn shell_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', r"'\''"))
+}
+
+/// The launch daemon that keeps the helper running for user `uid`.
+#[cfg(feature = "app")]
+pub(crate) fn launchd_plist(program: &str, uid: u32) -> String {
+    format!(
+        r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE plist PUBLIC "-//Apple//DTD PLIST 1.0//EN" "http://www.apple.com/DTDs/PropertyList-1.0.dtd">
+<plist version="1.0">
+<dict>
+  <key>Label</key>
+  <string>{label}</string>
+  <key>ProgramArguments</key>
+  <array>
+    <string>{program}</string>
+    <string>--allowed-uid</string>
+    <string>{uid}</string>
+  </array>
+  <key>RunAtLoad</key>
+  <true/>
+  <key>KeepAlive</key>
+  <true/>
+</dict>
+</plist>
+"#,
+        label = HELPER_LABEL,
+        program = program,
+        uid = uid
+    )
+}
+
+// -------- Helper --------
+
+/// The helper's side: who may use it and where it keeps its log.
+pub(crate) struct HelperServer {
+    pub allowed_uid: u32,
+    /// Home folder of `allowed_uid`; removals stay inside it.
+    pub home: PathBuf,
+    pub audit_log: PathBuf,
+    /// How long a connection has to send its request.
+    pub request_timeout: Duration,
+}
+
+/// Entry point of the helper binary: serve requests from `allowed_uid`
+/// until killed.
+pub fn run_privileged_helper(allowed_uid: u32) -> Result<(), String> {
+    let home =
+        home_of(allowed_uid).ok_or_else(|| format!("No home folder for uid {}", allowed_uid))?;
+    let server = HelperServer {
+        allowed_uid,
+        home,
+        audit_log: PathBuf::from(AUDIT_LOG_PATH),
+        request_timeout: REQUEST_TIMEOUT,
+    };
+    let _ = fs::remove_file(SOCKET_PATH);
+    let listener = UnixListener::bind(SOCKET_PATH)
+        .map_err(|e| format!("Failed to listen on {}: {}", SOCKET_PATH, e))?;
+    // Only the allowed user may connect; requests are still checked against
+    // the peer's uid.
+    std::os::unix::fs::chown(SOCKET_PATH, Some(allowed_uid), None)
+        .and_then(|_| fs::set_permissions(SOCKET_PATH, fs::Permissions::from_mode(0o600)))
+        .map_err(|e| format!("Failed to restrict {}: {}", SOCKET_PATH, e))?;
+    for stream in listener.incoming().flatten() {
+        server.handle(stream);
+    }
+    Ok(())
+}
+
+impl HelperServer {
+    /// Serve the one request on `stream`.
+    pub(crate) fn handle(&self, stream: UnixStream) {
+        let reply = match self.read_request(&stream) {
+            Ok((uid, request)) => HelperReply {
+                version: HELPER_VERSION.to_string(),
+                outcomes: request
+                    .commands
+                    .into_iter()
+                    .map(|command| self.execute(uid, command))
+                    .collect(),
+                error: None,
+            },
+            Err(error) => HelperReply {
+                version: HELPER_VERSION.to_string(),
+                outcomes: Vec::new(),
+                error: Some(error),
+            },
+        };
+        if let Ok(mut data) = serde_json::to_vec(&reply) {
+            data.push(b'\n');
+            let _ = (&stream).write_all(&data);
+        }
+    }
+
+    fn read_request(&self, stream: &UnixStream) -> Result<(u32, HelperRequest), String> {
+        // Who is asking is settled before anything is read. The request is
+        // still read, within the deadline, before refusing it, so the reply
+        // is not lost to a reset when the connection closes with data
+        // unread.
+        let uid = peer_uid(stream).ok_or("Could not identify the requesting process")?;
+        let line = read_line(stream, Instant::now() + self.request_timeout);
+        if uid != self.allowed_uid {
+            return Err(format!("uid {} may not use this helper", uid));
+        }
+        let request =
+            serde_json::from_slice(&line?).map_err(|e| format!("Malformed request: {}", e))?;
+        Ok((uid, request))
+    }
+
+    fn execute(&self, uid: u32, command: HelperCommand) -> HelperOutcome {
+        let result = match &command {
+            HelperCommand::Ping => Ok(()),
+            HelperCommand::Remove { path } => removable_path(path, &self.home)
+                .and_then(|relative| remove_in_home(&self.home, self.allowed_uid, &relative)),
+            _ => command.programs().and_then(|programs| {
+                programs
+                    .into_iter()
+                    .try_for_each(|(program, args)| run(program, &args))
+            }),
+        };
+        let outcome = HelperOutcome {
+            ok: result.is_ok(),
+            error: result.err(),
+        };
+        if command != HelperCommand::Ping {
+            self.audit(uid, command, &outcome);
+        }
+        outcome
+    }
+
+    fn audit(&self, uid: u32, command: HelperCommand, outcome: &HelperOutcome) {
+        let entry = HelperAuditEntry {
+            at: Utc::now().timestamp(),
+            uid,
+            command,
+            ok: outcome.ok,
+            error: outcome.error.clone(),
+        };
+        let Ok(mut line) = serde_json::to_string(&entry) else {
+            return;
+        };
+        line.push('\n');
+        let appended = fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.audit_log)
+            .and_then(|mut log| log.write_all(line.as_bytes()));
+        if let Err(e) = appended {
+            log::warn!("Failed to write helper audit log: {}", e);
+        }
+    }
+}
+
+/// Whether `name` can be a BSD interface name such as `en0`, so it is safe
+/// to pass to `ipconfig`.
+pub(crate) fn is_valid_interface(name: &str) -> bool {
+    !name.is_empty()
+        && name.len() <= MAX_INTERFACE_NAME
+        && name.chars().all(|c| c.is_ascii_alphanumeric())
+}
+
+/// `path` relative to `home` if the helper may remove it for a user whose
+/// home that is: an absolute path strictly inside the home folder, with no
+/// `..`. Symlinks along the way are refused when removing, by
+/// [`remove_in_home`].
+pub(crate) fn removable_path(path: &str, home: &Path) -> Result<PathBuf, String> {
+    let candidate = PathBuf::from(path);
+    let refuse = || Err(format!("{} is outside the home folder", path));
+    if !candidate.is_absolute()
+        || candidate
+            .components()
+            .any(|component| matches!(component, Component::ParentDir))
+    {
+        return refuse();
+    }
+    let Ok(relative) = candidate.strip_prefix(home) else {
+        return refuse();
+    };
+    if relative.as_os_str().is_empty() {
+        return refuse();
+    }
+    Ok(relative.to_path_buf())
+}
+
+/// Remove `relative` inside `home`, which must belong to `owner`, without
+/// following symlinks: every folder on the way is opened from the one
+/// before it with `O_NOFOLLOW`, and the entry is removed relative to its
+/// open parent, so swapping a folder for a link between the check and the
+/// removal cannot redirect it. A path that is already gone counts as
+/// removed.
+pub(crate) fn remove_in_home(home: &Path, owner: u32, relative: &Path) -> Result<(), String> {
+    let display = home.join(relative);
+    let failed = |e: std::io::Error| format!("Failed to remove {}: {}", display.display(), e);
+    let home_dir = open_dir(None, home.as_os_str(), false).map_err(failed)?;
+    if fd_stat(&home_dir).map_err(failed)?.st_uid != owner {
+        return Err(format!("{} is not owned by uid {}", home.display(), owner));
+    }
+    let mut names: Vec<&OsStr> = relative
+        .components()
+        .filter_map(|component| match component {
+            Component::Normal(name) => Some(name),
+            _ => None,
+        })
+        .collect();
+    let Some(name) = names.pop() else {
+        return Err(format!("{} is outside the home folder", display.display()));
+    };
+    let mut parent = home_dir;
+    for folder in names {
+        parent = match open_dir(Some(&parent), folder, true) {
+            Ok(dir) => dir,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(()),
+            Err(e) if is_link_refusal(&e) => {
+                return Err(format!("{} leads through a symlink", display.display()))
+            }
+            Err(e) => return Err(failed(e)),
+        };
+    }
+    match remove_entry(&parent, name) {
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+        removed => removed.map_err(failed),
+    }
+}
+
+/// `O_NOFOLLOW` on a symlink fails with `ELOOP`, or `ENOTDIR` together
+/// with `O_DIRECTORY` on some systems.
+fn is_link_refusal(error: &std::io::Error) -> bool {
+    matches!(
+        error.raw_os_error(),
+        Some(libc::ELOOP) | Some(libc::ENOTDIR)
+    )
+}
+
+fn c_name(name: &OsStr) -> std::io::Result<CString> {
+    CString::new(name.as_bytes())
+        .map_err(|_| std::io::Error::from(std::io::ErrorKind::InvalidInput))
+}
+
+/// Open folder `name`, relative to `parent` when given.
+fn open_dir(parent: Option<&OwnedFd>, name: &OsStr, no_follow: bool) -> std::io::Result<OwnedFd> {
+    let name = c_name(name)?;
+    let mut flags = libc::O_RDONLY | libc::O_DIRECTORY | libc::O_CLOEXEC;
+    if no_follow {
+        flags |= libc::O_NOFOLLOW;
+    }
+    let at = parent.map_or(libc::AT_FDCWD, |fd| fd.as_raw_fd());
+    let fd = unsafe { libc::openat(at, name.as_ptr(), flags) };
+    if fd < 0 {
+        return Err(std::io::Error::last_os_error());
+    }
+    Ok(unsafe { OwnedFd::from_raw_fd(fd) })
+}
+
+fn fd_stat(fd: &OwnedFd) -> std::io::Result<libc::stat> {
+    let mut stat: libc::stat = unsafe { std::mem::zeroed() };
+    if unsafe { libc::fstat(fd.as_raw_fd(), &mut stat) } != 0 {
+        return Err(std::io::Error::last_os_error());
+    }
+    Ok(stat)
+}
+
+/// Remove `name` inside `parent`, emptying it first if it is a folder.
+fn remove_entry(parent: &OwnedFd, name: &OsStr) -> std::io::Result<()> {
+    let c = c_name(name)?;
+    let mut stat: libc::stat = unsafe { std::mem::zeroed() };
+    let status = unsafe {
+        libc::fstatat(
+            parent.as_raw_fd(),
+            c.as_ptr(),
+            &mut stat,
+            libc::AT_SYMLINK_NOFOLLOW,
+        )
+    };
+    if status != 0 {
+        return Err(std::io::Error::last_os_error());
+    }
+    let is_dir = stat.st_mode & libc::S_IFMT == libc::S_IFDIR;
+    if is_dir {
+        let dir = open_dir(Some(parent), name, true)?;
+        for entry in dir_entries(&dir)? {
+            remove_entry(&dir, &entry)?;
+        }
+    }
+    let flags = if is_dir { libc::AT_REMOVEDIR } else { 0 };
+    if unsafe { libc::unlinkat(parent.as_raw_fd(), c.as_ptr(), flags) } != 0 {
+        return Err(std::io::Error::last_os_error());
+    }
+    Ok(())
+}
+
+/// Names in the open folder `dir`, without `.` and `..`.
+fn dir_entries(dir: &OwnedFd) -> std::io::Result<Vec<OsString>> {
+    // `closedir` closes the descriptor it was given, so hand it a copy.
+    let copy = unsafe { libc::dup(dir.as_raw_fd()) };
+    if copy < 0 {
+        return Err(std::io::Error::last_os_error());
+    }
+    let stream = unsafe { libc::fdopendir(copy) };
+    if stream.is_null() {
+        let error = std::io::Error::last_os_error();
+        unsafe { libc::close(copy) };
+        return Err(error);
+    }
+    let mut names = Vec::new();
+    loop {
+        let entry = unsafe { libc::readdir(stream) };
+        if entry.is_null() {
+            break;
+        }
+        let name = unsafe { CStr::from_ptr((*entry).d_name.as_ptr()) };
+        if !matches!(name.to_bytes(), b"." | b"..") {
+            names.push(OsStr::from_bytes(name.to_bytes()).to_os_string());
+        }
+    }
+    unsafe { libc::closedir(stream) };
+    Ok(names)
+}
+
+/// The first line on `stream`, read by `deadline` and at most
+/// [`MAX_REQUEST_BYTES`] long, so a client that sends nothing or keeps
+/// sending cannot hold up the helper.
+fn read_line(stream: &UnixStream, deadline: Instant) -> Result<Vec<u8>, String> {
+    let timed_out = || "Timed out reading the request".to_string();
+    let mut line = Vec::new();
+    let mut chunk = [0u8; 8192];
+    loop {
+        let remaining = deadline
+            .checked_duration_since(Instant::now())
+            .filter(|remaining| !remaining.is_zero())
+            .ok_or_else(timed_out)?;
+        stream
+            .set_read_timeout(Some(remaining))
+            .map_err(|e| format!("Failed to read request: {}", e))?;
+        let read = match (&*stream).read(&mut chunk) {
+            Ok(read) => read,
+            Err(e)
+                if matches!(
+                    e.kind(),
+                    std::io::ErrorKind::WouldBlock | std::io::ErrorKind::TimedOut
+                ) =>
+            {
+                return Err(timed_out())
+            }
+            Err(e) => return Err(format!("Failed to read request: {}", e)),
+        };
+        line.extend_from_slice(&chunk[..read]);
+        if let Some(end) = line.iter().position(|byte| *byte == b'\n') {
+            line.truncate(end);
+            return Ok(line);
+        }
+        if read == 0 {
+            return Ok(line);
+        }
+        if line.len() > MAX_REQUEST_BYTES {
+            return Err("Request is too long".into());
+        }
+    }
+}
+
+fn run(program: &str, args: &[String]) -> Result<(), String> {
+    let output = std::process::Command::new(program)
+        .args(args)
+        .output()
+        .map_err(|e| format!("Failed to run {}: {}", program, e))?;
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(format!(
+            "{} failed: {}",
+            program,
+            String::from_utf8_lossy(&output.stderr).trim()
+        ))
+    }
+}
+
+#[cfg(any(target_os = "macos", target_os = "freebsd"))]
+fn peer_uid(stream: &UnixStream) -> Option<u32> {
+    let mut uid = 0;
+    let mut gid = 0;
+    let status = unsafe { libc::getpeereid(stream.as_raw_fd(), &mut uid, &mut gid) };
+    (status == 0).then_some(uid)
+}
+
+#[cfg(target_os = "linux")]
+fn peer_uid(stream: &UnixStream) -> Option<u32> {
+    let mut credentials: libc::ucred = unsafe { std::mem::zeroed() };
+    let mut length = std::mem::size_of::<libc::ucred>() as libc::socklen_t;
+    let status = unsafe {
+        libc::getsockopt(
+            stream.as_raw_fd(),
+            libc::SOL_SOCKET,
+            libc::SO_PEERCRED,
+            &mut credentials as *mut libc::ucred as *mut libc::c_void,
+            &mut length,
+        )
+    };
+    (status == 0).then_some(credentials.uid)
+}
+
+/// The home folder of `uid` from the user database.
+fn home_of(uid: u32) -> Option<PathBuf> {
+    let mut buffer = vec![0 as libc::c_char; 4096];
+    let mut passwd: libc::passwd = unsafe { std::mem::zeroed() };
+    let mut result: *mut libc::passwd = std::ptr::null_mut();
+    let status = unsafe {
+        libc::getpwuid_r(
+            uid,
+            &mut passwd,
+            buffer.as_mut_ptr(),
+            buffer.len(),
+            &mut result,
+        )
+    };
+    if status != 0 || result.is_null() || passwd.pw_dir.is_null() {
+        return None;
+    }
+    let dir = unsafe { CStr::from_ptr(passwd.pw_dir) };
+    Some(PathBuf::from(OsStr::from_bytes(dir.to_bytes())))
+}
@@ -0,0 +1,221 @@
+#[cfg(feature = "app")]
+use super::{install_steps, launchd_plist, INSTALL_PROMPT};
+use super::{read_audit_log, removable_path, remove_in_home, send_to, HelperCommand, HelperServer};
+#[cfg(feature = "app")]
+use crate::elevation::{admin_applescript, admin_applescript_with_prompt, marker_script};
+use crate::system_maintenance::MaintenanceTask;
+use std::fs;
+use std::io::Read;
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::Path;
+use std::time::{Duration, Instant};
+use tempfile::TempDir;
+use tokio_util::sync::CancellationToken;
+
+#[test]
+fn removals_stay_strictly_inside_the_home_folder() {
+    let home = TempDir::new().unwrap();
+    let home_path = home.path();
+    assert_eq!(
+        removable_path(
+            &home_path
+                .join("Library/Caches/com.example")
+                .to_string_lossy(),
+            home_path
+        )
+        .unwrap(),
+        Path::new("Library/Caches/com.example")
+    );
+
+    for refused in [
+        home_path.to_string_lossy().to_string(),
+        format!("{}/../etc", home_path.display()),
+        "/etc/hosts".to_string(),
+        "Library/Caches".to_string(),
+    ] {
+        assert!(removable_path(&refused, home_path).is_err(), "{}", refused);
+    }
+}
+
+#[test]
+fn removals_never_follow_a_symlink_out_of_the_home_folder() {
+    let home = TempDir::new().unwrap();
+    let outside = TempDir::new().unwrap();
+    let uid = unsafe { libc::getuid() };
+    let remove = |relative: &str| remove_in_home(home.path(), uid, Path::new(relative));
+
+    fs::create_dir_all(home.path().join("Caches/app/nested")).unwrap();
+    fs::write(home.path().join("Caches/app/nested/data"), b"cache").unwrap();
+    remove("Caches/app").unwrap();
+    assert!(!home.path().join("Caches/app").exists());
+    // Not yet created, or already gone, counts as removed.
+    remove("Caches/app").unwrap();
+    remove("Gone/file").unwrap();
+
+    // A folder swapped for a link after the request was checked is not
+    // followed.
+    fs::create_dir_all(outside.path().join("app")).unwrap();
+    fs::write(outside.path().join("app/victim"), b"keep").unwrap();
+    let relative = removable_path(
+        &home.path().join("Caches/app/victim").to_string_lossy(),
+        home.path(),
+    )
+    .unwrap();
+    fs::remove_dir_all(home.path().join("Caches")).unwrap();
+    std::os::unix::fs::symlink(outside.path(), home.path().join("Caches")).unwrap();
+    let refused = remove_in_home(home.path(), uid, &relative).unwrap_err();
+    assert!(refused.contains("symlink"), "{}", refused);
+    assert!(outside.path().join("app/victim").exists());
+
+    // The link itself may go, leaving what it points at; so may a link
+    // inside a removed folder.
+    fs::create_dir_all(home.path().join("Box")).unwrap();
+    std::os::unix::fs::symlink(outside.path(), home.path().join("Box/link")).unwrap();
+    remove("Caches").unwrap();
+    remove("Box").unwrap();
+    assert!(!home.path().join("Caches").exists());
+    assert!(outside.path().join("app/victim").exists());
+
+    // A home folder that is not the user's is refused.
+    assert!(remove_in_home(home.path(), uid.wrapping_add(1), Path::new("x")).is_err());
+}
+
+#[tokio::test]
+async fn requests_are_served_checked_and_audited() {
+    let home = TempDir::new().unwrap();
+    let run = TempDir::new().unwrap();
+    let socket = run.path().join("helper.sock");
+    let audit_log = run.path().join("audit.log");
+    let victim = home.path().join("Caches/old");
+    fs::create_dir_all(&victim).unwrap();
+    fs::write(victim.join("data"), b"cache").unwrap();
+
+    let listener = UnixListener::bind(&socket).unwrap();
+    let server = HelperServer {
+        allowed_uid: unsafe { libc::getuid() },
+        home: home.path().to_path_buf(),
+        audit_log: audit_log.clone(),
+        request_timeout: Duration::from_secs(5),
+    };
+    let serving = std::thread::spawn(move || {
+        for stream in listener.incoming().take(1).flatten() {
+            server.handle(stream);
+        }
+        server
+    });
+
+    let commands = vec![
+        HelperCommand::Ping,
+        HelperCommand::Remove {
+            path: victim.to_string_lossy().to_string(),
+        },
+        HelperCommand::Remove {
+            path: "/etc/hosts".into(),
+        },
+        HelperCommand::Maintenance {
+            task: MaintenanceTask::LaunchServices,
+        },
+    ];
+    let token = CancellationToken::new();
+    let outcomes = send_to(&socket, &commands, &token).await.unwrap().unwrap();
+    let ok: Vec<bool> = outcomes.iter().map(|outcome| outcome.ok).collect();
+    assert_eq!(ok, vec![true, true, false, false]);
+    assert!(!victim.exists());
+    assert!(Path::new("/etc/hosts").exists());
+    assert!(outcomes[2].error.as_deref().unwrap().contains("outside"));
+
+    // Everyone else is turned away before anything runs.
+    let mut server = serving.join().unwrap();
+    server.allowed_uid = server.allowed_uid.wrapping_add(1);
+    let listener = UnixListener::bind(run.path().join("other.sock")).unwrap();
+    let serving = std::thread::spawn(move || {
+        let (stream, _) = listener.accept().unwrap();
+        server.handle(stream);
+    });
+    let refused = send_to(
+        &run.path().join("other.sock"),
+        &[HelperCommand::Purge],
+        &token,
+    )
+    .await;
+    serving.join().unwrap();
+    assert!(refused.unwrap_err().contains("may not use this helper"));
+
+    // Nothing listening means the caller falls back.
+    assert!(send_to(&run.path().join("none.sock"), &commands, &token)
+        .await
+        .unwrap()
+        .is_none());
+
+    // Pings are not audited; the rest are, newest first.
+    let entries = read_audit_log(&audit_log, 10);
+    assert_eq!(entries.len(), 3);
+    assert_eq!(entries[0].command, commands[3]);
+    assert!(!entries[0].ok);
+    assert_eq!(entries[2].command, commands[1]);
+    assert!(entries[2].ok);
+    assert_eq!(read_audit_log(&audit_log, 1).len(), 1);
+}
+
+#[test]
+fn a_client_that_sends_nothing_cannot_hold_up_the_helper() {
+    let home = TempDir::new().unwrap();
+    let run = TempDir::new().unwrap();
+    let socket = run.path().join("helper.sock");
+    let listener = UnixListener::bind(&socket).unwrap();
+    let server = HelperServer {
+        allowed_uid: unsafe { libc::getuid() },
+        home: home.path().to_path_buf(),
+        audit_log: run.path().join("audit.log"),
+        request_timeout: Duration::from_millis(100),
+    };
+
+    let mut silent = UnixStream::connect(&socket).unwrap();
+    let (stream, _) = listener.accept().unwrap();
+    let started = Instant::now();
+    server.handle(stream);
+    assert!(started.elapsed() < Duration::from_secs(5));
+    let mut reply = String::new();
+    silent.read_to_string(&mut reply).unwrap();
+    assert!(reply.contains("Timed out"), "{}", reply);
+}
+
+#[cfg(feature = "app")]
+#[test]
+fn install_script_quotes_paths_for_the_shell_and_applescript() {
+    let steps = install_steps(
+        Path::new("/Applications/macOS \"Optimizer's\".app/Contents/MacOS/macos-optimizer-helper"),
+        Path::new("/tmp/back\\slash/helper.plist"),
+    );
+    let keys: Vec<&str> = steps.iter().map(|(key, _)| *key).collect();
+    assert_eq!(keys, vec!["unload", "binary", "plist", "load"]);
+    assert!(steps[1].1.contains(
+        "cp '/Applications/macOS \"Optimizer'\\''s\".app/Contents/MacOS/macos-optimizer-helper'"
+    ));
+    assert!(steps[2].1.contains("cp '/tmp/back\\slash/helper.plist'"));
+
+    // Inside the AppleScript string, quotes and backslashes are escaped so
+    // the shell gets the script as written.
+    let script = marker_script(steps.iter().map(|(key, command)| (*key, command.clone())));
+    let applescript = admin_applescript(&script);
+    let literal = applescript
+        .strip_prefix("do shell script \"")
+        .and_then(|rest| rest.strip_suffix("\" with administrator privileges"))
+        .unwrap();
+    let mut unescaped = String::new();
+    let mut chars = literal.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => unescaped.push(chars.next().unwrap()),
+            '"' => panic!("unescaped quote ends the string early: {}", applescript),
+            c => unescaped.push(c),
+        }
+    }
+    assert_eq!(unescaped, script);
+    let prompted = admin_applescript_with_prompt(&script, INSTALL_PROMPT);
+    assert!(prompted.ends_with("with administrator privileges"));
+    assert!(prompted.contains("Any app running as you"));
+
+    let plist = launchd_plist("/Library/PrivilegedHelperTools/helper", 501);
+    assert!(plist.contains("<string>--allowed-uid</string>\n    <string>501</string>"));
+}
@@ -0,0 +1,183 @@
+// src/scheduler.rs
+
+//! Recurring scan/clean jobs persisted to disk.
+//!
+//! The store only tracks what should run and when; the app layer polls
+//! [`ScheduleStore::due`] and executes jobs through the regular cleaner
+//! commands so they emit the same progress events as manual runs.
+//! Trash aging runs additionally leave a record of every entry they
+//! removed in the [`TrashPurgeLog`].
+
+use std::path::{Path, PathBuf};
+
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+
+use crate::persistence;
+
+mod purge_log;
+#[cfg(test)]
+mod tests;
+
+pub use purge_log::{TrashPurgeLog, TrashPurgeRecord};
+
+const SCHEDULES_FILE: &str = "schedules.json";
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ScheduleAction {
+    /// Refresh scan results without deleting anything.
+    Scan,
+    /// Scan, then clean auto-selectable files in the given categories
+    /// (all auto-selectable files when empty).
+    CleanCategories {
+        categories: Vec<String>,
+    },
+    EmptyTrash,
+    /// Permanently delete Trash entries that have been there longer than
+    /// `older_than_days`, logging each one to the [`TrashPurgeLog`].
+    PurgeOldTrash {
+        older_than_days: u32,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ScheduleInterval {
+    Daily,
+    Weekly,
+    Monthly,
+}
+
+impl ScheduleInterval {
+    pub fn as_secs(self) -> i64 {
+        const DAY: i64 = 24 * 60 * 60;
+        match self {
+            ScheduleInterval::Daily => DAY,
+            ScheduleInterval::Weekly => 7 * DAY,
+            ScheduleInterval::Monthly => 30 * DAY,
+        }
+    }
+}
+
+/// Request payload for creating a schedule.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NewSchedule {
+    pub name: String,
+    pub action: ScheduleAction,
+    pub interval: ScheduleInterval,
+    /// First run time (unix seconds); defaults to one interval from now.
+    pub first_run: Option<i64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Schedule {
+    pub id: String,
+    pub name: String,
+    pub action: ScheduleAction,
+    pub interval: ScheduleInterval,
+    pub enabled: bool,
+    pub created_at: i64,
+    pub next_run: i64,
+    pub last_run: Option<i64>,
+    pub last_result: Option<String>,
+}
+
+pub struct ScheduleStore {
+    path: Option<PathBuf>,
+    schedules: Vec<Schedule>,
+}
+
+impl ScheduleStore {
+    /// Load from `<data dir>/macos-optimizer/schedules.json`.
+    pub fn load() -> Self {
+        match persistence::data_file(SCHEDULES_FILE) {
+            Some(path) => Self::load_from(&path),
+            None => Self {
+                path: None,
+                schedules: Vec::new(),
+            },
+        }
+    }
+
+    /// Load from an explicit file; a missing or unreadable file yields an empty store.
+    pub fn load_from(path: &Path) -> Self {
+        let schedules = persistence::read_json(path).unwrap_or_default();
+        Self {
+            path: Some(path.to_path_buf()),
+            schedules,
+        }
+    }
+
+    pub fn list(&self) -> Vec<Schedule> {
+        self.schedules.clone()
+    }
+
+    pub fn create(&mut self, request: NewSchedule) -> Result<Schedule, String> {
+        let name = request.name.trim();
+        if name.is_empty() {
+            return Err("Schedule name cannot be empty".into());
+        }
+        if let ScheduleAction::PurgeOldTrash { older_than_days: 0 } = request.action {
+            return Err("Trash age limit must be at least one day".into());
+        }
+        let now = Utc::now().timestamp();
+        let schedule = Schedule {
+            id: uuid::Uuid::new_v4().to_string(),
+            name: name.to_string(),
+            action: request.action,
+            interval: request.interval,
+            enabled: true,
+            created_at: now,
+            next_run: request
+                .first_run
+                .unwrap_or(now + request.interval.as_secs()),
+            last_run: None,
+            last_result: None,
+        };
+        self.schedules.push(schedule.clone());
+        self.persist()?;
+        Ok(schedule)
+    }
+
+    /// Remove a schedule; returns whether it existed.
+    pub fn delete(&mut self, id: &str) -> Result<bool, String> {
+        let before = self.schedules.len();
+        self.schedules.retain(|schedule| schedule.id != id);
+        if self.schedules.len() == before {
+            return Ok(false);
+        }
+        self.persist()?;
+        Ok(true)
+    }
+
+    /// Enabled schedules whose next run is at or before `now`.
+    pub fn due(&self, now: i64) -> Vec<Schedule> {
+        self.schedules
+            .iter()
+            .filter(|schedule| schedule.enabled && schedule.next_run <= now)
+            .cloned()
+            .collect()
+    }
+
+    /// Record a run and move the schedule to its next slot after `now`,
+    /// skipping slots missed while the app wasn't running.
+    pub fn mark_run(&mut self, id: &str, now: i64, result: String) -> Result<(), String> {
+        let Some(schedule) = self.schedules.iter_mut().find(|s| s.id == id) else {
+            return Err("Unknown schedule".into());
+        };
+        let step = schedule.interval.as_secs();
+        let missed = (now - schedule.next_run).max(0) / step;
+        schedule.next_run += (missed + 1) * step;
+        schedule.last_run = Some(now);
+        schedule.last_result = Some(result);
+        self.persist()
+    }
+
+    fn persist(&self) -> Result<(), String> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        persistence::write_json(path, &self.schedules)
+    }
+}
@@ -0,0 +1,82 @@
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::file_cleaner::types::{TrashItem, TrashPurgeResult};
+use crate::persistence;
+
+const PURGE_LOG_FILE: &str = "trash_purges.json";
+/// Older runs are dropped when a new one is logged.
+const MAX_RECORDS: usize = 200;
+
+/// One Trash aging run and every entry it deleted.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TrashPurgeRecord {
+    pub ran_at: i64,
+    pub older_than_days: u32,
+    pub purged: Vec<TrashItem>,
+    pub freed: u64,
+    pub failed: usize,
+    pub kept: usize,
+    pub canceled: bool,
+}
+
+impl TrashPurgeRecord {
+    pub fn new(ran_at: i64, older_than_days: u32, result: TrashPurgeResult) -> Self {
+        Self {
+            ran_at,
+            older_than_days,
+            purged: result.purged,
+            freed: result.freed,
+            failed: result.failed,
+            kept: result.kept,
+            canceled: result.canceled,
+        }
+    }
+}
+
+/// History of Trash aging runs, newest last.
+pub struct TrashPurgeLog {
+    path: Option<PathBuf>,
+    records: Vec<TrashPurgeRecord>,
+}
+
+impl TrashPurgeLog {
+    /// Load from `<data dir>/macos-optimizer/trash_purges.json`.
+    pub fn load() -> Self {
+        match persistence::data_file(PURGE_LOG_FILE) {
+            Some(path) => Self::load_from(&path),
+            None => Self {
+                path: None,
+                records: Vec::new(),
+            },
+        }
+    }
+
+    /// Load from an explicit file; a missing or unreadable file yields an empty log.
+    pub fn load_from(path: &Path) -> Self {
+        let records = persistence::read_json(path).unwrap_or_default();
+        Self {
+            path: Some(path.to_path_buf()),
+            records,
+        }
+    }
+
+    pub fn list(&self) -> Vec<TrashPurgeRecord> {
+        self.records.clone()
+    }
+
+    pub fn append(&mut self, record: TrashPurgeRecord) -> Result<(), String> {
+        self.records.push(record);
+        let excess = self.records.len().saturating_sub(MAX_RECORDS);
+        self.records.drain(..excess);
+        self.persist()
+    }
+
+    fn persist(&self) -> Result<(), String> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        persistence::write_json(path, &self.records)
+    }
+}
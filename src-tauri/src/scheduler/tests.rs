@@ -0,0 +1,120 @@
+use super::{
+    NewSchedule, ScheduleAction, ScheduleInterval, ScheduleStore, TrashPurgeLog, TrashPurgeRecord,
+};
+use crate::file_cleaner::types::{TrashItem, TrashPurgeResult};
+use tempfile::TempDir;
+
+fn weekly_cache_clean(first_run: i64) -> NewSchedule {
+    NewSchedule {
+        name: "Clean caches weekly".into(),
+        action: ScheduleAction::CleanCategories {
+            categories: vec!["User Cache".into()],
+        },
+        interval: ScheduleInterval::Weekly,
+        first_run: Some(first_run),
+    }
+}
+
+#[test]
+fn schedules_persist_across_reloads() {
+    let temp_dir = TempDir::new().unwrap();
+    let path = temp_dir.path().join("schedules.json");
+
+    let mut store = ScheduleStore::load_from(&path);
+    let created = store.create(weekly_cache_clean(1_000)).unwrap();
+    store
+        .create(NewSchedule {
+            name: "Empty trash monthly".into(),
+            action: ScheduleAction::EmptyTrash,
+            interval: ScheduleInterval::Monthly,
+            first_run: None,
+        })
+        .unwrap();
+
+    let mut reloaded = ScheduleStore::load_from(&path);
+    assert_eq!(reloaded.list().len(), 2);
+    assert!(reloaded.delete(&created.id).unwrap());
+    assert!(!reloaded.delete(&created.id).unwrap());
+    assert_eq!(ScheduleStore::load_from(&path).list().len(), 1);
+    assert!(store.create(weekly_cache_clean(0)).is_ok());
+}
+
+#[test]
+fn due_jobs_advance_past_missed_slots() {
+    let temp_dir = TempDir::new().unwrap();
+    let mut store = ScheduleStore::load_from(&temp_dir.path().join("schedules.json"));
+    let week = ScheduleInterval::Weekly.as_secs();
+    let schedule = store.create(weekly_cache_clean(1_000)).unwrap();
+
+    assert!(store.due(999).is_empty());
+    assert_eq!(store.due(1_000).len(), 1);
+
+    // App was closed for two and a half weeks: run once, then resume the cadence.
+    let now = 1_000 + week * 5 / 2;
+    store.mark_run(&schedule.id, now, "ok".into()).unwrap();
+    let updated = &store.list()[0];
+    assert_eq!(updated.next_run, 1_000 + week * 3);
+    assert_eq!(updated.last_run, Some(now));
+    assert!(store.due(now).is_empty());
+    assert!(store.mark_run("missing", now, "ok".into()).is_err());
+}
+
+#[test]
+fn blank_names_are_rejected() {
+    let temp_dir = TempDir::new().unwrap();
+    let mut store = ScheduleStore::load_from(&temp_dir.path().join("schedules.json"));
+    let mut request = weekly_cache_clean(0);
+    request.name = "  ".into();
+    assert!(store.create(request).is_err());
+}
+
+#[test]
+fn trash_aging_needs_at_least_one_day() {
+    let temp_dir = TempDir::new().unwrap();
+    let mut store = ScheduleStore::load_from(&temp_dir.path().join("schedules.json"));
+    let purge = |older_than_days| NewSchedule {
+        name: "Age out Trash".into(),
+        action: ScheduleAction::PurgeOldTrash { older_than_days },
+        interval: ScheduleInterval::Daily,
+        first_run: None,
+    };
+    assert!(store.create(purge(0)).is_err());
+    let created = store.create(purge(14)).unwrap();
+    let json = serde_json::to_string(&created.action).unwrap();
+    assert_eq!(json, r#"{"kind":"purge_old_trash","older_than_days":14}"#);
+}
+
+#[test]
+fn purge_log_keeps_what_each_run_removed() {
+    let temp_dir = TempDir::new().unwrap();
+    let path = temp_dir.path().join("trash_purges.json");
+    let item = TrashItem {
+        path: "/Users/me/.Trash/old.dmg".into(),
+        name: "old.dmg".into(),
+        size: 2048,
+        is_dir: false,
+        trashed_at: 100,
+    };
+
+    let mut log = TrashPurgeLog::load_from(&path);
+    for ran_at in 0..205 {
+        let result = TrashPurgeResult {
+            purged: vec![item.clone()],
+            freed: item.size,
+            kept: 3,
+            ..Default::default()
+        };
+        log.append(TrashPurgeRecord::new(ran_at, 30, result))
+            .unwrap();
+    }
+
+    let records = TrashPurgeLog::load_from(&path).list();
+    assert_eq!(records.len(), 200);
+    assert_eq!(records[0].ran_at, 5);
+    let last = records.last().unwrap();
+    assert_eq!(last.ran_at, 204);
+    assert_eq!(last.older_than_days, 30);
+    assert_eq!(last.purged[0].name, "old.dmg");
+    assert_eq!(last.freed, 2048);
+    assert_eq!(last.kept, 3);
+}
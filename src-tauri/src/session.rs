@@ -0,0 +1,243 @@
+// src/session.rs
+
+//! Per-session activity totals and the weekly digest built from them.
+//!
+//! Commands report what they freed to a [`SessionRecorder`] while the app
+//! runs. When the main window closes the totals are appended to a
+//! [`SessionStore`], and [`SessionStore::weekly_digest`] aggregates the last
+//! seven days for the UI or a notification.
+
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+
+use crate::formatting::{format_count, format_eta, format_size};
+use crate::persistence;
+
+#[cfg(test)]
+mod tests;
+
+const SESSIONS_FILE: &str = "sessions.json";
+const WEEK_SECS: i64 = 7 * 24 * 60 * 60;
+/// Sessions older than this are dropped when a new one is stored.
+const RETENTION_SECS: i64 = 8 * WEEK_SECS;
+
+// Rough effort the same work would take by hand, for "time saved" figures.
+const MANUAL_SECS_PER_CLEANUP: u64 = 5 * 60;
+const MANUAL_SECS_PER_ITEM: u64 = 2;
+const MANUAL_SECS_PER_MEMORY_OPTIMIZATION: u64 = 60;
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SessionSummary {
+    pub started_at: i64,
+    pub ended_at: i64,
+    pub bytes_freed: u64,
+    pub items_removed: u64,
+    /// Cleaning runs: file cleans, Trash empties, uninstalls, app cache cleans.
+    pub cleanups_run: u64,
+    pub memory_optimizations: u64,
+    /// Physical memory released by those optimizations.
+    pub memory_freed: u64,
+    pub time_saved_secs: u64,
+}
+
+impl SessionSummary {
+    pub fn is_empty(&self) -> bool {
+        self.cleanups_run == 0 && self.memory_optimizations == 0
+    }
+}
+
+/// Totals for the session in progress.
+pub struct SessionRecorder {
+    current: Mutex<SessionSummary>,
+}
+
+impl Default for SessionRecorder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SessionRecorder {
+    pub fn new() -> Self {
+        Self {
+            current: Mutex::new(SessionSummary {
+                started_at: Utc::now().timestamp(),
+                ..Default::default()
+            }),
+        }
+    }
+
+    /// A cleaning run that removed `items` totalling `bytes`.
+    pub fn record_cleanup(&self, bytes: u64, items: u64) {
+        if let Ok(mut current) = self.current.lock() {
+            current.cleanups_run += 1;
+            current.bytes_freed = current.bytes_freed.saturating_add(bytes);
+            current.items_removed = current.items_removed.saturating_add(items);
+        }
+    }
+
+    pub fn record_memory_optimization(&self, freed: u64) {
+        if let Ok(mut current) = self.current.lock() {
+            current.memory_optimizations += 1;
+            current.memory_freed = current.memory_freed.saturating_add(freed);
+        }
+    }
+
+    /// Totals so far, without ending the session.
+    pub fn snapshot(&self) -> SessionSummary {
+        let mut summary = self
+            .current
+            .lock()
+            .map(|current| current.clone())
+            .unwrap_or_default();
+        summary.ended_at = Utc::now().timestamp();
+        summary.time_saved_secs = estimate_time_saved(&summary);
+        summary
+    }
+
+    /// Close the current session and start a new one.
+    pub fn finish(&self) -> SessionSummary {
+        let now = Utc::now().timestamp();
+        let Ok(mut current) = self.current.lock() else {
+            return SessionSummary::default();
+        };
+        let mut summary = std::mem::replace(
+            &mut *current,
+            SessionSummary {
+                started_at: now,
+                ..Default::default()
+            },
+        );
+        summary.ended_at = now;
+        summary.time_saved_secs = estimate_time_saved(&summary);
+        summary
+    }
+}
+
+fn estimate_time_saved(summary: &SessionSummary) -> u64 {
+    summary.cleanups_run * MANUAL_SECS_PER_CLEANUP
+        + summary.items_removed * MANUAL_SECS_PER_ITEM
+        + summary.memory_optimizations * MANUAL_SECS_PER_MEMORY_OPTIMIZATION
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WeeklyDigest {
+    pub from: i64,
+    pub to: i64,
+    pub sessions: usize,
+    pub bytes_freed: u64,
+    pub items_removed: u64,
+    pub cleanups_run: u64,
+    pub memory_optimizations: u64,
+    pub memory_freed: u64,
+    pub time_saved_secs: u64,
+    /// One line suitable for a notification body.
+    pub headline: String,
+}
+
+pub struct SessionStore {
+    path: Option<PathBuf>,
+    sessions: Vec<SessionSummary>,
+}
+
+impl SessionStore {
+    /// Load from `<data dir>/macos-optimizer/sessions.json`.
+    pub fn load() -> Self {
+        match persistence::data_file(SESSIONS_FILE) {
+            Some(path) => Self::load_from(&path),
+            None => Self {
+                path: None,
+                sessions: Vec::new(),
+            },
+        }
+    }
+
+    /// Load from an explicit file; a missing or unreadable file yields an empty store.
+    pub fn load_from(path: &Path) -> Self {
+        let sessions = persistence::read_json(path).unwrap_or_default();
+        Self {
+            path: Some(path.to_path_buf()),
+            sessions,
+        }
+    }
+
+    pub fn list(&self) -> Vec<SessionSummary> {
+        self.sessions.clone()
+    }
+
+    /// Store a finished session; sessions without any activity are skipped.
+    pub fn append(&mut self, summary: SessionSummary) -> Result<(), String> {
+        if summary.is_empty() {
+            return Ok(());
+        }
+        let cutoff = summary.ended_at - RETENTION_SECS;
+        self.sessions.retain(|session| session.ended_at >= cutoff);
+        self.sessions.push(summary);
+        self.persist()
+    }
+
+    /// Aggregate sessions that ended in the seven days before `now`, plus
+    /// the still-running `current` session if given.
+    pub fn weekly_digest(&self, now: i64, current: Option<&SessionSummary>) -> WeeklyDigest {
+        let from = now - WEEK_SECS;
+        let sessions: Vec<&SessionSummary> = self
+            .sessions
+            .iter()
+            .filter(|session| session.ended_at > from && session.ended_at <= now)
+            .chain(current.filter(|session| !session.is_empty()))
+            .collect();
+        let sum = |field: fn(&SessionSummary) -> u64| sessions.iter().map(|s| field(s)).sum();
+        let mut digest = WeeklyDigest {
+            from,
+            to: now,
+            sessions: sessions.len(),
+            bytes_freed: sum(|s| s.bytes_freed),
+            items_removed: sum(|s| s.items_removed),
+            cleanups_run: sum(|s| s.cleanups_run),
+            memory_optimizations: sum(|s| s.memory_optimizations),
+            memory_freed: sum(|s| s.memory_freed),
+            time_saved_secs: sum(|s| s.time_saved_secs),
+            headline: String::new(),
+        };
+        digest.headline = headline(&digest);
+        digest
+    }
+
+    fn persist(&self) -> Result<(), String> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        persistence::write_json(path, &self.sessions)
+    }
+}
+
+fn headline(digest: &WeeklyDigest) -> String {
+    if digest.cleanups_run == 0 && digest.memory_optimizations == 0 {
+        return "No cleanups this week".to_string();
+    }
+    let mut parts = vec![format!(
+        "Freed {} in {} cleanup{}",
+        format_size(digest.bytes_freed),
+        format_count(digest.cleanups_run),
+        if digest.cleanups_run == 1 { "" } else { "s" }
+    )];
+    if digest.memory_optimizations > 0 {
+        parts.push(format!(
+            "{} memory optimization{}",
+            format_count(digest.memory_optimizations),
+            if digest.memory_optimizations == 1 {
+                ""
+            } else {
+                "s"
+            }
+        ));
+    }
+    format!(
+        "{} this week, saving about {}",
+        parts.join(" and "),
+        format_eta(digest.time_saved_secs * 1000)
+    )
+}
@@ -0,0 +1,78 @@
+use super::{SessionRecorder, SessionStore, SessionSummary, WEEK_SECS};
+use tempfile::TempDir;
+
+fn session(ended_at: i64, bytes_freed: u64) -> SessionSummary {
+    SessionSummary {
+        started_at: ended_at - 600,
+        ended_at,
+        bytes_freed,
+        items_removed: 10,
+        cleanups_run: 1,
+        time_saved_secs: 320,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn recorder_totals_reset_after_finish() {
+    let recorder = SessionRecorder::new();
+    recorder.record_cleanup(1_000, 4);
+    recorder.record_cleanup(500, 1);
+    recorder.record_memory_optimization(2_048);
+
+    let summary = recorder.finish();
+    assert_eq!(summary.cleanups_run, 2);
+    assert_eq!(summary.bytes_freed, 1_500);
+    assert_eq!(summary.items_removed, 5);
+    assert_eq!(summary.memory_optimizations, 1);
+    assert_eq!(summary.memory_freed, 2_048);
+    assert_eq!(summary.time_saved_secs, 2 * 300 + 5 * 2 + 60);
+    assert!(summary.ended_at >= summary.started_at);
+
+    assert!(recorder.finish().is_empty());
+}
+
+#[test]
+fn weekly_digest_covers_last_seven_days() {
+    let temp_dir = TempDir::new().unwrap();
+    let path = temp_dir.path().join("sessions.json");
+    let now = 10 * WEEK_SECS;
+
+    let mut store = SessionStore::load_from(&path);
+    store.append(session(now - 2 * WEEK_SECS, 9_000)).unwrap();
+    store.append(session(now - 3_600, 1_000)).unwrap();
+    store.append(SessionSummary::default()).unwrap();
+    store.append(session(now - 60, 2_000)).unwrap();
+
+    let reloaded = SessionStore::load_from(&path);
+    assert_eq!(reloaded.list().len(), 3);
+
+    let current = SessionSummary {
+        memory_optimizations: 1,
+        memory_freed: 4_096,
+        ..Default::default()
+    };
+    let digest = reloaded.weekly_digest(now, Some(&current));
+    assert_eq!(digest.sessions, 3);
+    assert_eq!(digest.bytes_freed, 3_000);
+    assert_eq!(digest.cleanups_run, 2);
+    assert_eq!(digest.memory_optimizations, 1);
+    assert_eq!(digest.memory_freed, 4_096);
+    assert_eq!(digest.time_saved_secs, 640);
+    assert!(digest.headline.starts_with("Freed"));
+
+    let empty = reloaded.weekly_digest(now + 4 * WEEK_SECS, None);
+    assert_eq!(empty.sessions, 0);
+    assert_eq!(empty.headline, "No cleanups this week");
+}
+
+#[test]
+fn old_sessions_are_pruned_on_append() {
+    let temp_dir = TempDir::new().unwrap();
+    let path = temp_dir.path().join("sessions.json");
+
+    let mut store = SessionStore::load_from(&path);
+    store.append(session(0, 1)).unwrap();
+    store.append(session(20 * WEEK_SECS, 1)).unwrap();
+    assert_eq!(SessionStore::load_from(&path).list().len(), 1);
+}
@@ -0,0 +1,345 @@
+// src/simulation.rs
+
+//! End-to-end cleaning simulations against synthetic home directories.
+//!
+//! A fixture spec declares a file tree, optional cleaner rules, which of the
+//! reported items to clean and the expected outcome. [`run_simulation`]
+//! builds the tree in a temporary directory, points `HOME` at it and runs the
+//! real scan, validate and clean pipeline. Nothing outside the virtual root
+//! is ever selected for cleaning, even when rules name absolute paths.
+
+use std::env;
+use std::fs::{self, FileTimes};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+use lazy_static::lazy_static;
+use serde::{Deserialize, Serialize};
+use tokio::sync::Mutex;
+use tokio_util::sync::CancellationToken;
+
+use crate::file_cleaner::EnhancedFileCleaner;
+
+lazy_static! {
+    /// Simulations redirect process-wide environment variables.
+    static ref SIMULATION_LOCK: Mutex<()> = Mutex::new(());
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FixtureSpec {
+    pub name: String,
+    /// Cleaner rules for the run (same format as `cleaner_rules.json`);
+    /// the embedded rules are used when absent.
+    #[serde(default)]
+    pub rules: Option<serde_json::Value>,
+    pub files: Vec<FixtureFile>,
+    #[serde(default)]
+    pub select: Selection,
+    /// Same as the Risky Mode toggle: clean items that need confirmation.
+    #[serde(default)]
+    pub allow_low_safety: bool,
+    #[serde(default)]
+    pub expect: Expectations,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FixtureFile {
+    /// Relative to the virtual home directory.
+    pub path: String,
+    #[serde(default)]
+    pub size: u64,
+    /// Backdates the access and modification times.
+    #[serde(default)]
+    pub age_days: u64,
+    /// Create an empty directory instead of a file.
+    #[serde(default)]
+    pub dir: bool,
+}
+
+/// Which reported items are passed to the cleaner.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Selection {
+    /// What "Auto-Select Safe Files" would pick.
+    #[default]
+    AutoSelected,
+    /// Everything marked safe to delete.
+    Safe,
+    /// Everything reported, as if the user selected every item.
+    All,
+}
+
+/// Paths are relative to the virtual home directory.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Expectations {
+    #[serde(default)]
+    pub reported: Vec<String>,
+    #[serde(default)]
+    pub not_reported: Vec<String>,
+    #[serde(default)]
+    pub removed: Vec<String>,
+    #[serde(default)]
+    pub kept: Vec<String>,
+    /// Selected items the cleaner must refuse.
+    #[serde(default)]
+    pub blocked: Vec<String>,
+    #[serde(default)]
+    pub min_freed: Option<u64>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SimulationReport {
+    pub name: String,
+    pub reported: Vec<String>,
+    pub selected: Vec<String>,
+    pub removed: Vec<String>,
+    pub blocked: Vec<BlockedItem>,
+    pub freed: u64,
+    /// Unmet expectations; empty when the simulation passed.
+    pub failures: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BlockedItem {
+    pub path: String,
+    pub reason: String,
+}
+
+impl SimulationReport {
+    pub fn passed(&self) -> bool {
+        self.failures.is_empty()
+    }
+}
+
+impl FixtureSpec {
+    pub fn from_file(path: &Path) -> Result<Self, String> {
+        let data = fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read fixture {}: {}", path.display(), e))?;
+        serde_json::from_str(&data)
+            .map_err(|e| format!("Failed to parse fixture {}: {}", path.display(), e))
+    }
+}
+
+/// Restores the overridden environment variables on drop.
+struct EnvOverride {
+    previous: Vec<(&'static str, Option<String>)>,
+}
+
+impl EnvOverride {
+    fn set(vars: &[(&'static str, &Path)]) -> Self {
+        let previous = vars
+            .iter()
+            .map(|(key, value)| {
+                let old = env::var(key).ok();
+                env::set_var(key, value);
+                (*key, old)
+            })
+            .collect();
+        Self { previous }
+    }
+}
+
+impl Drop for EnvOverride {
+    fn drop(&mut self) {
+        for (key, value) in self.previous.drain(..) {
+            match value {
+                Some(value) => env::set_var(key, value),
+                None => env::remove_var(key),
+            }
+        }
+    }
+}
+
+pub async fn run_simulation(spec: &FixtureSpec) -> Result<SimulationReport, String> {
+    let _lock = SIMULATION_LOCK.lock().await;
+    let root = env::temp_dir().join(format!("macos-optimizer-sim-{}", uuid::Uuid::new_v4()));
+    fs::create_dir_all(root.join(".Trash"))
+        .map_err(|e| format!("Failed to create virtual root: {}", e))?;
+    let result = simulate_in(spec, &root).await;
+    if let Err(err) = fs::remove_dir_all(&root) {
+        log::warn!("Failed to remove {}: {}", root.display(), err);
+    }
+    result
+}
+
+async fn simulate_in(spec: &FixtureSpec, root: &Path) -> Result<SimulationReport, String> {
+    build_tree(root, &spec.files)?;
+
+    let mut overrides: Vec<(&'static str, &Path)> = vec![
+        ("HOME", root),
+        ("MACOS_OPTIMIZER_DISABLE_OSA", Path::new("1")),
+        ("MACOS_OPTIMIZER_DISABLE_NATIVE_TRASH", Path::new("1")),
+    ];
+    let rules_path = root.join(".simulation-rules.json");
+    if let Some(rules) = &spec.rules {
+        let data = serde_json::to_vec_pretty(rules)
+            .map_err(|e| format!("Failed to serialize fixture rules: {}", e))?;
+        fs::write(&rules_path, data).map_err(|e| format!("Failed to write rules: {}", e))?;
+        overrides.push(("MACOS_OPTIMIZER_RULES_OVERRIDE", &rules_path));
+    }
+    let _env = EnvOverride::set(&overrides);
+
+    let token = CancellationToken::new();
+    let mut cleaner = EnhancedFileCleaner::new();
+    let scan = cleaner
+        .scan_system_enhanced_with_cancel(&token, None)
+        .await?;
+
+    let roots = [root.to_path_buf(), root.canonicalize().unwrap_or_default()];
+    let relative = |path: &str| relative_to(&roots, Path::new(path));
+
+    let mut report = SimulationReport {
+        name: spec.name.clone(),
+        ..Default::default()
+    };
+    let mut selected_paths = Vec::new();
+    for file in &scan.enhanced_files {
+        let Some(rel) = relative(&file.base.path) else {
+            continue;
+        };
+        report.reported.push(rel.clone());
+        let selected = match spec.select {
+            Selection::AutoSelected => file.base.auto_select && file.base.safe_to_delete,
+            Selection::Safe => file.base.safe_to_delete,
+            Selection::All => true,
+        };
+        if selected {
+            report.selected.push(rel);
+            selected_paths.push(file.base.path.clone());
+        }
+    }
+    report.reported.sort();
+    report.selected.sort();
+
+    if selected_paths.is_empty() {
+        report.failures = check_expectations(&spec.expect, &report, root);
+        return Ok(report);
+    }
+
+    // Cleaning validates every selection first; a run where validation
+    // blocks everything is an outcome to check, not a harness error.
+    let result = match cleaner
+        .clean_files_enhanced(selected_paths, Some(&token), spec.allow_low_safety, None)
+        .await
+    {
+        Ok(result) => result,
+        Err(err) if err == "cancelled" => return Err(err),
+        Err(err) => {
+            report.blocked = report
+                .selected
+                .iter()
+                .map(|path| BlockedItem {
+                    path: path.clone(),
+                    reason: err.clone(),
+                })
+                .collect();
+            report.failures = check_expectations(&spec.expect, &report, root);
+            return Ok(report);
+        }
+    };
+    report.freed = result.total_freed;
+    report.removed = result
+        .deleted_files
+        .iter()
+        .filter_map(|path| relative(path))
+        .collect();
+    report.removed.sort();
+    report.blocked = result
+        .failed_files
+        .iter()
+        .map(|failed| BlockedItem {
+            path: relative(&failed.path).unwrap_or_else(|| failed.path.clone()),
+            reason: failed.reason.clone(),
+        })
+        .collect();
+
+    report.failures = check_expectations(&spec.expect, &report, root);
+    Ok(report)
+}
+
+fn build_tree(root: &Path, files: &[FixtureFile]) -> Result<(), String> {
+    for file in files {
+        if Path::new(&file.path).is_absolute() || file.path.split('/').any(|part| part == "..") {
+            return Err(format!(
+                "Fixture path {} must stay inside the virtual root",
+                file.path
+            ));
+        }
+        let path = root.join(&file.path);
+        if file.dir {
+            fs::create_dir_all(&path)
+                .map_err(|e| format!("Failed to create {}: {}", file.path, e))?;
+        } else {
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent)
+                    .map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+            }
+            fs::write(&path, vec![0u8; file.size as usize])
+                .map_err(|e| format!("Failed to write {}: {}", file.path, e))?;
+        }
+        if file.age_days > 0 {
+            let time =
+                SystemTime::now() - Duration::from_secs(file.age_days.saturating_mul(86_400));
+            let times = FileTimes::new().set_accessed(time).set_modified(time);
+            fs::File::open(&path)
+                .and_then(|handle| handle.set_times(times))
+                .map_err(|e| format!("Failed to backdate {}: {}", file.path, e))?;
+        }
+    }
+    Ok(())
+}
+
+fn relative_to(roots: &[PathBuf], path: &Path) -> Option<String> {
+    roots
+        .iter()
+        .filter(|root| !root.as_os_str().is_empty())
+        .find_map(|root| path.strip_prefix(root).ok())
+        .map(|rel| rel.to_string_lossy().to_string())
+}
+
+fn check_expectations(
+    expect: &Expectations,
+    report: &SimulationReport,
+    root: &Path,
+) -> Vec<String> {
+    let mut failures = Vec::new();
+    for path in &expect.reported {
+        if !report.reported.contains(path) {
+            failures.push(format!("expected {} to be reported", path));
+        }
+    }
+    for path in &expect.not_reported {
+        if report.reported.contains(path) {
+            failures.push(format!("expected {} not to be reported", path));
+        }
+    }
+    for path in &expect.removed {
+        if root.join(path).exists() {
+            failures.push(format!("expected {} to be moved to the Trash", path));
+        }
+    }
+    for path in &expect.kept {
+        if !root.join(path).exists() {
+            failures.push(format!("expected {} to be kept", path));
+        }
+    }
+    if let Some(min_freed) = expect.min_freed {
+        if report.freed < min_freed {
+            failures.push(format!(
+                "expected at least {} bytes freed, got {}",
+                min_freed, report.freed
+            ));
+        }
+    }
+    for path in &expect.blocked {
+        if !report.blocked.iter().any(|item| &item.path == path) {
+            failures.push(format!("expected {} to be blocked", path));
+        }
+    }
+    for item in &report.blocked {
+        if !expect.blocked.contains(&item.path) {
+            failures.push(format!("{} was blocked: {}", item.path, item.reason));
+        }
+    }
+    failures
+}
@@ -0,0 +1,374 @@
+// src/startup_manager.rs
+
+//! Launch agents, launch daemons and login items: what starts with the Mac,
+//! which app it belongs to and whether it is enabled.
+//!
+//! Only per-user launch agents can be changed. Enabling and disabling go
+//! through `launchctl enable|disable` so the override survives reboots, and
+//! removal unloads the agent before moving its plist to the Trash.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use tokio::process::Command;
+
+use crate::capabilities::{self, Tool};
+use crate::file_cleaner::{is_osascript_disabled, FileCleaner};
+use crate::system_snapshot::{plist_string, read_plist_xml};
+
+#[cfg(test)]
+mod tests;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StartupItemKind {
+    UserAgent,
+    GlobalAgent,
+    GlobalDaemon,
+    LoginItem,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StartupItem {
+    pub label: String,
+    pub kind: StartupItemKind,
+    /// The plist for launchd items, the app bundle for login items.
+    pub path: Option<String>,
+    pub program: Option<String>,
+    pub owning_app: Option<String>,
+    pub enabled: bool,
+    pub run_at_load: bool,
+    /// Set while the job is running.
+    pub pid: Option<u32>,
+    /// Exit status of the last run, as reported by `launchctl list`.
+    pub last_exit_status: Option<i32>,
+    /// Whether this item can be enabled, disabled or removed from the app.
+    pub user_manageable: bool,
+}
+
+/// What `launchctl` reports for the current user's domain.
+#[derive(Debug, Default)]
+struct LaunchdState {
+    /// label -> (pid, last exit status)
+    jobs: HashMap<String, (Option<u32>, Option<i32>)>,
+    /// label -> disabled
+    overrides: HashMap<String, bool>,
+}
+
+pub struct StartupManager {
+    home: PathBuf,
+    /// Prefix for `/Library/LaunchAgents` and `/Library/LaunchDaemons`.
+    system_root: PathBuf,
+}
+
+impl StartupManager {
+    pub fn new() -> Result<Self, String> {
+        let home = dirs::home_dir().ok_or("Home directory not found")?;
+        Ok(Self::with_roots(home, PathBuf::from("/")))
+    }
+
+    pub fn with_roots(home: PathBuf, system_root: PathBuf) -> Self {
+        Self { home, system_root }
+    }
+
+    fn user_agents_dir(&self) -> PathBuf {
+        self.home.join("Library/LaunchAgents")
+    }
+
+    pub async fn list_items(&self) -> Vec<StartupItem> {
+        let state = launchd_state().await;
+        let mut items = Vec::new();
+        for (dir, kind) in [
+            (self.user_agents_dir(), StartupItemKind::UserAgent),
+            (
+                self.system_root.join("Library/LaunchAgents"),
+                StartupItemKind::GlobalAgent,
+            ),
+            (
+                self.system_root.join("Library/LaunchDaemons"),
+                StartupItemKind::GlobalDaemon,
+            ),
+        ] {
+            items.extend(list_launchd_items(&dir, kind, &state));
+        }
+        items.extend(login_items().await);
+        items
+    }
+
+    pub async fn set_enabled(&self, plist_path: &Path, enabled: bool) -> Result<(), String> {
+        let plist = self.user_agent_plist(plist_path)?;
+        let label = launchd_label(&plist);
+        let domain = user_domain();
+        let service = format!("{}/{}", domain, label);
+        if enabled {
+            launchctl(&["enable", &service]).await?;
+            // Already-loaded agents make bootstrap fail; that is fine.
+            if let Err(err) = launchctl(&["bootstrap", &domain, &plist.to_string_lossy()]).await {
+                log::debug!("bootstrap {} skipped: {}", label, err);
+            }
+        } else {
+            launchctl(&["disable", &service]).await?;
+            if let Err(err) = launchctl(&["bootout", &service]).await {
+                log::debug!("bootout {} skipped: {}", label, err);
+            }
+        }
+        Ok(())
+    }
+
+    /// Unload the agent and move its plist to the Trash.
+    pub async fn remove(&self, plist_path: &Path) -> Result<(), String> {
+        let plist = self.user_agent_plist(plist_path)?;
+        if capabilities::tool_available(Tool::Launchctl) {
+            let service = format!("{}/{}", user_domain(), launchd_label(&plist));
+            if let Err(err) = launchctl(&["bootout", &service]).await {
+                log::debug!("bootout {} skipped: {}", service, err);
+            }
+        }
+        let cleaner = FileCleaner::new();
+        cleaner.move_to_trash(&plist).await
+    }
+
+    /// Resolve `path` to a plist directly inside ~/Library/LaunchAgents.
+    fn user_agent_plist(&self, path: &Path) -> Result<PathBuf, String> {
+        let not_manageable = || {
+            format!(
+                "{} is not a user launch agent; only items in ~/Library/LaunchAgents can be changed",
+                path.display()
+            )
+        };
+        if path.extension().is_none_or(|ext| ext != "plist") {
+            return Err(not_manageable());
+        }
+        let canonical = path
+            .canonicalize()
+            .map_err(|e| format!("Cannot read {}: {}", path.display(), e))?;
+        let agents_dir = self
+            .user_agents_dir()
+            .canonicalize()
+            .map_err(|_| not_manageable())?;
+        if canonical.parent() != Some(agents_dir.as_path()) {
+            return Err(not_manageable());
+        }
+        Ok(canonical)
+    }
+}
+
+fn list_launchd_items(dir: &Path, kind: StartupItemKind, state: &LaunchdState) -> Vec<StartupItem> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut paths: Vec<PathBuf> = entries
+        .filter_map(Result::ok)
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "plist"))
+        .collect();
+    paths.sort();
+    paths
+        .into_iter()
+        .map(|path| {
+            let plist = read_plist_xml(&path).unwrap_or_default();
+            let label = plist_string(&plist, "Label").unwrap_or_else(|| {
+                path.file_stem()
+                    .map(|s| s.to_string_lossy().to_string())
+                    .unwrap_or_default()
+            });
+            let program = plist_string(&plist, "Program")
+                .or_else(|| plist_first_array_string(&plist, "ProgramArguments"));
+            let owning_app = program
+                .as_deref()
+                .and_then(app_name_from_program)
+                .or_else(|| plist_first_array_string(&plist, "AssociatedBundleIdentifiers"));
+            // A launchctl override in the user's domain wins over the plist's
+            // own Disabled key.
+            let disabled = state
+                .overrides
+                .get(&label)
+                .filter(|_| kind == StartupItemKind::UserAgent)
+                .copied()
+                .unwrap_or_else(|| plist_bool(&plist, "Disabled").unwrap_or(false));
+            let (pid, last_exit_status) = state.jobs.get(&label).copied().unwrap_or_default();
+            StartupItem {
+                label,
+                kind,
+                path: Some(path.to_string_lossy().to_string()),
+                program,
+                owning_app,
+                enabled: !disabled,
+                run_at_load: plist_bool(&plist, "RunAtLoad").unwrap_or(false),
+                pid,
+                last_exit_status,
+                user_manageable: kind == StartupItemKind::UserAgent,
+            }
+        })
+        .collect()
+}
+
+/// The launchd label, falling back to the plist's file stem.
+fn launchd_label(plist_path: &Path) -> String {
+    read_plist_xml(plist_path)
+        .and_then(|plist| plist_string(&plist, "Label"))
+        .unwrap_or_else(|| {
+            plist_path
+                .file_stem()
+                .map(|s| s.to_string_lossy().to_string())
+                .unwrap_or_default()
+        })
+}
+
+/// `/Applications/Foo.app/Contents/MacOS/helper` -> `Foo`.
+fn app_name_from_program(program: &str) -> Option<String> {
+    program
+        .split('/')
+        .find_map(|component| component.strip_suffix(".app"))
+        .filter(|name| !name.is_empty())
+        .map(str::to_string)
+}
+
+/// The XML element following `<key>key</key>`.
+fn plist_value<'a>(plist: &'a str, key: &str) -> Option<&'a str> {
+    let marker = format!("<key>{}</key>", key);
+    let start = plist.find(&marker)? + marker.len();
+    Some(plist[start..].trim_start())
+}
+
+fn plist_bool(plist: &str, key: &str) -> Option<bool> {
+    let value = plist_value(plist, key)?;
+    if value.starts_with("<true/>") {
+        Some(true)
+    } else if value.starts_with("<false/>") {
+        Some(false)
+    } else {
+        None
+    }
+}
+
+fn plist_first_array_string(plist: &str, key: &str) -> Option<String> {
+    let value = plist_value(plist, key)?.strip_prefix("<array>")?;
+    let array = &value[..value.find("</array>")?];
+    let start = array.find("<string>")? + "<string>".len();
+    let end = array[start..].find("</string>")?;
+    Some(array[start..start + end].trim().to_string())
+}
+
+fn user_domain() -> String {
+    format!("gui/{}", unsafe { libc::getuid() })
+}
+
+async fn launchctl(args: &[&str]) -> Result<String, String> {
+    if !capabilities::tool_available(Tool::Launchctl) {
+        return Err("launchctl is unavailable".into());
+    }
+    let output = Command::new("launchctl")
+        .args(args)
+        .output()
+        .await
+        .map_err(|e| format!("Failed to run launchctl: {}", e))?;
+    if output.status.success() {
+        Ok(String::from_utf8_lossy(&output.stdout).to_string())
+    } else {
+        Err(format!(
+            "launchctl {} failed: {}",
+            args.first().unwrap_or(&""),
+            String::from_utf8_lossy(&output.stderr).trim()
+        ))
+    }
+}
+
+async fn launchd_state() -> LaunchdState {
+    let mut state = LaunchdState::default();
+    if let Ok(listing) = launchctl(&["list"]).await {
+        state.jobs = parse_launchctl_list(&listing);
+    }
+    if let Ok(disabled) = launchctl(&["print-disabled", &user_domain()]).await {
+        state.overrides = parse_print_disabled(&disabled);
+    }
+    state
+}
+
+/// `launchctl list` rows: `PID<TAB>Status<TAB>Label`, `-` when not running.
+fn parse_launchctl_list(output: &str) -> HashMap<String, (Option<u32>, Option<i32>)> {
+    output
+        .lines()
+        .skip(1)
+        .filter_map(|line| {
+            let mut fields = line.split('\t');
+            let pid = fields.next()?.trim().parse().ok();
+            let status = fields.next()?.trim().parse().ok();
+            let label = fields.next()?.trim();
+            (!label.is_empty()).then(|| (label.to_string(), (pid, status)))
+        })
+        .collect()
+}
+
+/// `launchctl print-disabled` rows: `"com.example.agent" => disabled` (or
+/// `=> true` on older releases).
+fn parse_print_disabled(output: &str) -> HashMap<String, bool> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let (label, value) = line.split_once("=>")?;
+            let label = label.trim().trim_matches('"');
+            let disabled = match value.trim() {
+                "disabled" | "true" => true,
+                "enabled" | "false" => false,
+                _ => return None,
+            };
+            (!label.is_empty()).then(|| (label.to_string(), disabled))
+        })
+        .collect()
+}
+
+/// Login items from System Events. Read-only: they are managed in System Settings.
+async fn login_items() -> Vec<StartupItem> {
+    if is_osascript_disabled() || !capabilities::tool_available(Tool::Osascript) {
+        return Vec::new();
+    }
+    let script = "tell application \"System Events\" to get the {name, path} of every login item";
+    let output = match Command::new("osascript")
+        .arg("-e")
+        .arg(script)
+        .output()
+        .await
+    {
+        Ok(output) if output.status.success() => output,
+        Ok(output) => {
+            log::debug!(
+                "Login items unavailable: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            );
+            return Vec::new();
+        }
+        Err(err) => {
+            log::debug!("Login items unavailable: {}", err);
+            return Vec::new();
+        }
+    };
+    parse_login_items(&String::from_utf8_lossy(&output.stdout))
+}
+
+/// AppleScript prints `{name, path}` lists flattened: all names, then all paths.
+fn parse_login_items(output: &str) -> Vec<StartupItem> {
+    let fields: Vec<&str> = output.trim().split(", ").collect();
+    if fields.len() < 2 || !fields.len().is_multiple_of(2) {
+        return Vec::new();
+    }
+    let (names, paths) = fields.split_at(fields.len() / 2);
+    names
+        .iter()
+        .zip(paths)
+        .map(|(name, path)| StartupItem {
+            label: name.to_string(),
+            kind: StartupItemKind::LoginItem,
+            path: Some(path.to_string()),
+            program: None,
+            owning_app: app_name_from_program(path).or_else(|| Some(name.to_string())),
+            enabled: true,
+            run_at_load: true,
+            pid: None,
+            last_exit_status: None,
+            user_manageable: false,
+        })
+        .collect()
+}
@@ -0,0 +1,114 @@
+use super::{
+    app_name_from_program, list_launchd_items, parse_launchctl_list, parse_login_items,
+    parse_print_disabled, LaunchdState, StartupItemKind, StartupManager,
+};
+use std::fs;
+use std::path::Path;
+use tempfile::TempDir;
+
+fn write_agent(dir: &Path, label: &str, program: &str, extra: &str) {
+    fs::create_dir_all(dir).unwrap();
+    fs::write(
+        dir.join(format!("{}.plist", label)),
+        format!(
+            "<?xml version=\"1.0\"?><plist><dict>\
+             <key>Label</key><string>{}</string>\
+             <key>ProgramArguments</key><array><string>{}</string><string>--daemon</string></array>\
+             {}</dict></plist>",
+            label, program, extra
+        ),
+    )
+    .unwrap();
+}
+
+#[test]
+fn lists_agents_with_owner_and_state() {
+    let root = TempDir::new().unwrap();
+    let agents = root.path().join("Library/LaunchAgents");
+    write_agent(
+        &agents,
+        "com.example.sync",
+        "/Applications/Sync Helper.app/Contents/MacOS/sync",
+        "<key>RunAtLoad</key><true/>",
+    );
+    write_agent(
+        &agents,
+        "com.example.updater",
+        "/usr/local/bin/updater",
+        "<key>Disabled</key>\n  <true/>",
+    );
+
+    let state = LaunchdState {
+        jobs: parse_launchctl_list(
+            "PID\tStatus\tLabel\n412\t0\tcom.example.sync\n-\t78\tcom.example.updater\n",
+        ),
+        // The launchctl override re-enables an agent its plist marks disabled.
+        overrides: parse_print_disabled(
+            "disabled services = {\n\t\"com.example.updater\" => enabled\n\t\"com.other\" => disabled\n}",
+        ),
+    };
+
+    let items = list_launchd_items(&agents, StartupItemKind::UserAgent, &state);
+    assert_eq!(items.len(), 2);
+    let sync = &items[0];
+    assert_eq!(sync.label, "com.example.sync");
+    assert_eq!(sync.owning_app.as_deref(), Some("Sync Helper"));
+    assert!(sync.run_at_load && sync.enabled && sync.user_manageable);
+    assert_eq!(sync.pid, Some(412));
+    let updater = &items[1];
+    assert!(updater.enabled);
+    assert_eq!(updater.pid, None);
+    assert_eq!(updater.last_exit_status, Some(78));
+    assert_eq!(updater.program.as_deref(), Some("/usr/local/bin/updater"));
+
+    let daemons = root.path().join("Library/LaunchDaemons");
+    write_agent(
+        &daemons,
+        "com.example.updater",
+        "/usr/local/bin/updater",
+        "<key>Disabled</key><true/>",
+    );
+    let global = list_launchd_items(&daemons, StartupItemKind::GlobalDaemon, &state);
+    assert!(!global[0].enabled && !global[0].user_manageable);
+}
+
+#[tokio::test]
+async fn only_user_agents_can_be_changed() {
+    let home = TempDir::new().unwrap();
+    let system = TempDir::new().unwrap();
+    write_agent(
+        &system.path().join("Library/LaunchDaemons"),
+        "com.example.root",
+        "/usr/local/bin/root",
+        "",
+    );
+    let manager = StartupManager::with_roots(home.path().into(), system.path().into());
+    let daemon = system
+        .path()
+        .join("Library/LaunchDaemons/com.example.root.plist");
+    let err = manager.remove(&daemon).await.unwrap_err();
+    assert!(err.contains("not a user launch agent"), "{}", err);
+
+    write_agent(
+        &home.path().join("Library/LaunchAgents"),
+        "com.example.user",
+        "/usr/local/bin/user",
+        "",
+    );
+    let escape = home
+        .path()
+        .join("Library/LaunchAgents/../../../com.example.root.plist");
+    assert!(manager.set_enabled(&escape, false).await.is_err());
+}
+
+#[test]
+fn parses_login_items_and_program_owner() {
+    let items = parse_login_items(
+        "Dropbox, Rectangle, /Applications/Dropbox.app, /Applications/Rectangle.app\n",
+    );
+    assert_eq!(items.len(), 2);
+    assert_eq!(items[1].label, "Rectangle");
+    assert_eq!(items[1].owning_app.as_deref(), Some("Rectangle"));
+    assert!(parse_login_items("").is_empty());
+    assert_eq!(app_name_from_program("/usr/bin/true"), None);
+}
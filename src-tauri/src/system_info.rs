@@ -1,7 +1,36 @@
-use libc::{kill as libc_kill, SIGKILL, SIGTERM};
+use libc::{kill as libc_kill, SIGCONT, SIGKILL, SIGSTOP, SIGTERM};
 use serde::{Deserialize, Serialize};
 use std::time::{Duration, Instant};
-use sysinfo::{Components, Networks, Pid, System};
+use sysinfo::{Components, Networks, Pid, Process, System};
+
+use apfs::ApfsVolumeInfo;
+use app_usage::{group_by_app, AppResourceUsage, ProcessSample};
+use process_detail::{child_tree, ProcessDetail, ProcessNode};
+
+/// Stopping or reprioritizing these stalls the whole session.
+const PROTECTED_PROCESSES: &[&str] = &[
+    "kernel_task",
+    "launchd",
+    "WindowServer",
+    "loginwindow",
+    "Finder",
+    "Dock",
+    "SystemUIServer",
+    "coreaudiod",
+];
+
+pub mod apfs;
+pub mod app_usage;
+#[cfg(feature = "app")]
+pub mod gpu_info;
+#[cfg(feature = "app")]
+pub mod network_usage;
+#[cfg(feature = "app")]
+pub mod power_info;
+pub mod process_detail;
+
+#[cfg(test)]
+mod tests;
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct SystemInfo {
@@ -41,6 +70,7 @@ pub struct DiskInfo {
     pub name: String,
     pub mount_point: String,
     pub total_space: u64,
+    /// What Finder shows as available: free plus purgeable space.
     pub available_space: u64,
     pub used_space: u64,
     pub file_system: String,
@@ -48,6 +78,15 @@ pub struct DiskInfo {
     pub device: String,
     pub kind: String,
     pub is_system: bool,
+    /// Free without purging anything.
+    #[serde(default)]
+    pub free_space: Option<u64>,
+    /// Space macOS frees on demand, such as purgeable local snapshots and
+    /// caches it manages.
+    #[serde(default)]
+    pub purgeable_space: Option<u64>,
+    #[serde(default)]
+    pub apfs: Option<ApfsVolumeInfo>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -141,16 +180,27 @@ impl SystemMonitor {
         self.system
             .processes()
             .iter()
-            .map(|(pid, process)| ProcessInfo {
+            .map(|(pid, process)| process_info(*pid, process))
+            .collect()
+    }
+
+    /// Processes rolled up per application, busiest first.
+    pub fn get_app_resource_usage(&mut self) -> Vec<AppResourceUsage> {
+        self.refresh_selective(RefreshComponent::Processes);
+        let samples: Vec<ProcessSample> = self
+            .system
+            .processes()
+            .iter()
+            .map(|(pid, process)| ProcessSample {
                 pid: pid.as_u32(),
+                parent_pid: process.parent().map(Pid::as_u32),
                 name: process.name().to_string(),
+                exe: process.exe().map(|exe| exe.to_path_buf()),
                 cpu_usage: process.cpu_usage(),
                 memory_usage: process.memory(),
-                virtual_memory: process.virtual_memory(),
-                status: format!("{:?}", process.status()),
-                parent_pid: process.parent().map(|p| p.as_u32()),
             })
-            .collect()
+            .collect();
+        group_by_app(&samples)
     }
 
     pub fn get_top_memory_processes(&mut self, limit: usize) -> Vec<ProcessInfo> {
@@ -213,4 +263,127 @@ impl SystemMonitor {
             ))
         }
     }
+
+    /// Stop a process until [`Self::resume_process`] continues it.
+    pub fn suspend_process(&mut self, pid: u32) -> Result<(), String> {
+        self.controllable_process(pid)?;
+        send_signal(pid, SIGSTOP, "suspend")
+    }
+
+    pub fn resume_process(&mut self, pid: u32) -> Result<(), String> {
+        self.controllable_process(pid)?;
+        send_signal(pid, SIGCONT, "resume")
+    }
+
+    /// Set the nice value, from -20 (highest priority) to 19 (lowest).
+    /// Raising priority needs administrator rights.
+    pub fn set_process_priority(&mut self, pid: u32, nice: i32) -> Result<(), String> {
+        if !(-20..=19).contains(&nice) {
+            return Err("Priority must be between -20 and 19".into());
+        }
+        self.controllable_process(pid)?;
+        // SAFETY: setpriority only reads its integer arguments.
+        if unsafe { libc::setpriority(libc::PRIO_PROCESS, pid, nice) } == 0 {
+            return Ok(());
+        }
+        let err = std::io::Error::last_os_error();
+        match err.raw_os_error() {
+            Some(libc::EACCES) | Some(libc::EPERM) => Err(format!(
+                "Not allowed to change the priority of process {}; raising priority requires administrator privileges",
+                pid
+            )),
+            _ => Err(format!("Failed to set priority of process {}: {}", pid, err)),
+        }
+    }
+
+    /// Identity, priority and parent/child tree of a process. Threads,
+    /// energy impact and open files are filled in by
+    /// [`ProcessDetail::with_activity`], which takes about a second.
+    pub fn get_process_detail(&mut self, pid: u32) -> Result<ProcessDetail, String> {
+        self.refresh_selective(RefreshComponent::Processes);
+        let process = self
+            .system
+            .process(Pid::from_u32(pid))
+            .ok_or_else(|| format!("No process with PID {}", pid))?;
+        let parent = process.parent().and_then(|parent| {
+            self.system.process(parent).map(|p| ProcessNode {
+                pid: parent.as_u32(),
+                name: p.name().to_string(),
+                children: Vec::new(),
+            })
+        });
+        let all: Vec<(u32, Option<u32>, String)> = self
+            .system
+            .processes()
+            .iter()
+            .map(|(pid, p)| {
+                (
+                    pid.as_u32(),
+                    p.parent().map(Pid::as_u32),
+                    p.name().to_string(),
+                )
+            })
+            .collect();
+        // SAFETY: getpriority only reads its integer arguments. -1 is also a
+        // valid nice value; the process was just seen, so it is one here.
+        let nice = unsafe { libc::getpriority(libc::PRIO_PROCESS, pid) };
+        Ok(ProcessDetail {
+            process: process_info(Pid::from_u32(pid), process),
+            command: process.cmd().to_vec(),
+            executable: process.exe().map(|exe| exe.to_string_lossy().to_string()),
+            start_time: process.start_time(),
+            nice: Some(nice),
+            threads: None,
+            open_files: None,
+            energy_impact: None,
+            parent,
+            children: child_tree(pid, &all),
+        })
+    }
+
+    /// The process behind `pid`, unless it is this app or one the session
+    /// depends on.
+    fn controllable_process(&mut self, pid: u32) -> Result<&Process, String> {
+        if pid <= 1 || pid == std::process::id() {
+            return Err(format!("Process {} cannot be changed", pid));
+        }
+        self.refresh_selective(RefreshComponent::Processes);
+        let process = self
+            .system
+            .process(Pid::from_u32(pid))
+            .ok_or_else(|| format!("No process with PID {}", pid))?;
+        if PROTECTED_PROCESSES.contains(&process.name()) {
+            return Err(format!(
+                "{} is a system process and cannot be changed",
+                process.name()
+            ));
+        }
+        Ok(process)
+    }
+}
+
+fn send_signal(pid: u32, signal: i32, action: &str) -> Result<(), String> {
+    // SAFETY: kill only reads its integer arguments.
+    if unsafe { libc_kill(pid as i32, signal) } == 0 {
+        Ok(())
+    } else {
+        Err(format!(
+            "Failed to {} process {}: {}",
+            action,
+            pid,
+            std::io::Error::last_os_error()
+        ))
+    }
+}
+
+fn process_info(pid: Pid, process: &Process) -> ProcessInfo {
+    ProcessInfo {
+        pid: pid.as_u32(),
+        name: process.name().to_string(),
+        cpu_usage: process.cpu_usage(),
+        memory_usage: process.memory(),
+        virtual_memory: process.virtual_memory(),
+        status: format!("{:?}", process.status()),
+        parent_pid: process.parent().map(|p| p.as_u32()),
+    }
 }
@@ -0,0 +1,259 @@
+use std::collections::HashMap;
+use std::ffi::CString;
+use std::process::Command;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+
+/// `diskutil apfs list` output is reused this long; the dashboard polls disks
+/// far more often than the layout changes.
+const LAYOUT_TTL: Duration = Duration::from_secs(60);
+
+/// Where a disk sits in its APFS container.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ApfsVolumeInfo {
+    /// Container reference, such as `disk3`.
+    pub container: String,
+    pub container_capacity: u64,
+    /// Space no volume of the container has allocated.
+    pub container_free: u64,
+    /// Volume disk, such as `disk3s5`.
+    pub volume: String,
+    /// Such as "System", "Data" or "VM"; empty for a volume without one.
+    pub roles: Vec<String>,
+    /// Space the volume takes up, its snapshots included.
+    pub capacity_consumed: u64,
+    /// Local snapshots of the volume, or of its Data volume for the System
+    /// volume. APFS does not report their sizes; the purgeable ones are part
+    /// of the disk's purgeable space.
+    pub snapshot_count: usize,
+    pub purgeable_snapshot_count: usize,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub(crate) struct ApfsContainer {
+    pub reference: String,
+    pub capacity: u64,
+    pub free: u64,
+    pub volumes: Vec<ApfsVolume>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub(crate) struct ApfsVolume {
+    pub disk: String,
+    pub roles: Vec<String>,
+    pub mount_point: Option<String>,
+    /// Set for a sealed System volume, which is mounted from a snapshot.
+    pub snapshot_disk: Option<String>,
+    pub snapshot_mount_point: Option<String>,
+    pub capacity_consumed: u64,
+}
+
+struct Layout {
+    read_at: Instant,
+    containers: Vec<ApfsContainer>,
+    /// `(snapshots, purgeable snapshots)` per volume disk, listed on demand.
+    snapshots: HashMap<String, (usize, usize)>,
+}
+
+static LAYOUT: Mutex<Option<Layout>> = Mutex::new(None);
+
+/// The APFS placement of the volume mounted at `mount_point`, or `None`
+/// when it is not APFS or `diskutil` is unavailable.
+pub fn volume_info(mount_point: &str, device: &str) -> Option<ApfsVolumeInfo> {
+    let mut layout = LAYOUT
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    if layout
+        .as_ref()
+        .is_none_or(|layout| layout.read_at.elapsed() >= LAYOUT_TTL)
+    {
+        let containers = match run("diskutil", &["apfs", "list"]) {
+            Ok(text) => parse_apfs_list(&text),
+            Err(err) => {
+                log::debug!("APFS layout unavailable: {}", err);
+                Vec::new()
+            }
+        };
+        *layout = Some(Layout {
+            read_at: Instant::now(),
+            containers,
+            snapshots: HashMap::new(),
+        });
+    }
+    let layout = layout.as_mut()?;
+    let (container, volume) = find_volume(&layout.containers, mount_point, device)?;
+    let snapshot_volume = snapshot_volume(container, volume);
+    let (snapshot_count, purgeable_snapshot_count) = *layout
+        .snapshots
+        .entry(snapshot_volume.to_string())
+        .or_insert_with(|| {
+            run("diskutil", &["apfs", "listSnapshots", snapshot_volume])
+                .map(|text| parse_snapshot_list(&text))
+                .unwrap_or_default()
+        });
+    Some(ApfsVolumeInfo {
+        container: container.reference.clone(),
+        container_capacity: container.capacity,
+        container_free: container.free,
+        volume: volume.disk.clone(),
+        roles: volume.roles.clone(),
+        capacity_consumed: volume.capacity_consumed,
+        snapshot_count,
+        purgeable_snapshot_count,
+    })
+}
+
+/// Bytes free on the file system at `mount_point` right now, without
+/// purging anything, from `statvfs`.
+pub fn free_space(mount_point: &str) -> Option<u64> {
+    let path = CString::new(mount_point).ok()?;
+    let mut stats = std::mem::MaybeUninit::<libc::statvfs>::uninit();
+    if unsafe { libc::statvfs(path.as_ptr(), stats.as_mut_ptr()) } != 0 {
+        return None;
+    }
+    let stats = unsafe { stats.assume_init() };
+    // The field types differ between platforms
+    #[allow(clippy::unnecessary_cast)]
+    let free = (stats.f_bavail as u64).saturating_mul(stats.f_frsize as u64);
+    Some(free)
+}
+
+pub(crate) fn find_volume<'a>(
+    containers: &'a [ApfsContainer],
+    mount_point: &str,
+    device: &str,
+) -> Option<(&'a ApfsContainer, &'a ApfsVolume)> {
+    let device = device.trim_start_matches("/dev/");
+    let volumes = || {
+        containers
+            .iter()
+            .flat_map(|container| container.volumes.iter().map(move |v| (container, v)))
+    };
+    volumes()
+        .find(|(_, volume)| {
+            volume.mount_point.as_deref() == Some(mount_point)
+                || volume.snapshot_mount_point.as_deref() == Some(mount_point)
+        })
+        .or_else(|| {
+            volumes().find(|(_, volume)| {
+                volume.disk == device || volume.snapshot_disk.as_deref() == Some(device)
+            })
+        })
+}
+
+/// Local snapshots of a System volume are taken of its Data volume.
+fn snapshot_volume<'a>(container: &'a ApfsContainer, volume: &'a ApfsVolume) -> &'a str {
+    if volume.roles.iter().any(|role| role == "System") {
+        if let Some(data) = container
+            .volumes
+            .iter()
+            .find(|other| other.roles.iter().any(|role| role == "Data"))
+        {
+            return &data.disk;
+        }
+    }
+    &volume.disk
+}
+
+/// Containers and volumes from `diskutil apfs list`.
+pub(crate) fn parse_apfs_list(text: &str) -> Vec<ApfsContainer> {
+    let mut containers: Vec<ApfsContainer> = Vec::new();
+    for raw in text.lines() {
+        let line = raw.trim_start_matches(['|', '+', '-', '<', '>', ' ']);
+        if raw.trim_start().starts_with("+-- Container ") {
+            containers.push(ApfsContainer::default());
+            continue;
+        }
+        if raw.trim_start().starts_with("+-> Volume ") {
+            if let Some(container) = containers.last_mut() {
+                container.volumes.push(ApfsVolume::default());
+            }
+            continue;
+        }
+        let Some((key, value)) = line.split_once(':') else {
+            continue;
+        };
+        let value = value.trim();
+        let Some(container) = containers.last_mut() else {
+            continue;
+        };
+        match key.trim() {
+            "APFS Container Reference" => container.reference = value.to_string(),
+            "Size (Capacity Ceiling)" => container.capacity = leading_bytes(value),
+            "Capacity Not Allocated" => container.free = leading_bytes(value),
+            key => {
+                let Some(volume) = container.volumes.last_mut() else {
+                    continue;
+                };
+                match key {
+                    "APFS Volume Disk (Role)" => {
+                        let (disk, roles) = value.split_once(' ').unwrap_or((value, ""));
+                        volume.disk = disk.to_string();
+                        volume.roles = parse_roles(roles);
+                    }
+                    "Mount Point" => volume.mount_point = mounted(value),
+                    "Snapshot Disk" => volume.snapshot_disk = Some(value.to_string()),
+                    "Snapshot Mount Point" => volume.snapshot_mount_point = mounted(value),
+                    "Capacity Consumed" => volume.capacity_consumed = leading_bytes(value),
+                    _ => {}
+                }
+            }
+        }
+    }
+    containers.retain(|container| !container.reference.is_empty());
+    containers
+}
+
+/// `(snapshots, purgeable snapshots)` from `diskutil apfs listSnapshots`.
+pub(crate) fn parse_snapshot_list(text: &str) -> (usize, usize) {
+    let mut count = 0;
+    let mut purgeable = 0;
+    for line in text.lines() {
+        let line = line.trim_start_matches(['|', ' ']);
+        if let Some((key, value)) = line.split_once(':') {
+            match key.trim() {
+                "Name" => count += 1,
+                "Purgeable" if value.trim() == "Yes" => purgeable += 1,
+                _ => {}
+            }
+        }
+    }
+    (count, purgeable)
+}
+
+/// `(System)` or `(Preboot, Recovery)`; `(No specific role)` has none.
+fn parse_roles(text: &str) -> Vec<String> {
+    let text = text.trim().trim_start_matches('(').trim_end_matches(')');
+    if text.is_empty() || text.eq_ignore_ascii_case("No specific role") {
+        return Vec::new();
+    }
+    text.split(',')
+        .map(|role| role.trim().to_string())
+        .collect()
+}
+
+fn mounted(value: &str) -> Option<String> {
+    (!value.is_empty() && value != "Not Mounted").then(|| value.to_string())
+}
+
+/// `494384795648 B (494.4 GB)` → 494384795648.
+fn leading_bytes(value: &str) -> u64 {
+    value
+        .split_whitespace()
+        .next()
+        .and_then(|number| number.parse().ok())
+        .unwrap_or(0)
+}
+
+fn run(program: &str, args: &[&str]) -> Result<String, String> {
+    let output = Command::new(program)
+        .args(args)
+        .output()
+        .map_err(|e| format!("Failed to execute {}: {}", program, e))?;
+    if !output.status.success() {
+        return Err(format!("{} exited with {}", program, output.status));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
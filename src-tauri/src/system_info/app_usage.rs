@@ -0,0 +1,152 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+/// Resource use of every process belonging to one application.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AppResourceUsage {
+    pub name: String,
+    /// Outermost `.app` bundle; `None` for processes outside any bundle,
+    /// which are grouped by name.
+    pub bundle_path: Option<String>,
+    pub cpu_usage: f32,
+    pub memory_usage: u64,
+    pub process_count: usize,
+    pub pids: Vec<u32>,
+}
+
+/// What grouping needs from a running process.
+pub(crate) struct ProcessSample {
+    pub pid: u32,
+    pub parent_pid: Option<u32>,
+    pub name: String,
+    pub exe: Option<PathBuf>,
+    pub cpu_usage: f32,
+    pub memory_usage: u64,
+}
+
+#[derive(Clone, PartialEq, Eq, Hash)]
+enum AppKey {
+    Bundle(PathBuf),
+    Name(String),
+}
+
+/// The outermost `.app` in `exe`, so helpers nested in frameworks count
+/// toward the app that ships them.
+fn bundle_of(exe: &Path) -> Option<PathBuf> {
+    let mut bundle = PathBuf::new();
+    for component in exe.components() {
+        bundle.push(component);
+        if component
+            .as_os_str()
+            .to_str()
+            .is_some_and(|name| name.ends_with(".app"))
+        {
+            return Some(bundle);
+        }
+    }
+    None
+}
+
+/// "Google Chrome Helper (Renderer)" -> "Google Chrome".
+fn helper_owner(name: &str) -> Option<&str> {
+    let index = name.find(" Helper")?;
+    let owner = name[..index].trim_end();
+    (!owner.is_empty()).then_some(owner)
+}
+
+/// Group processes by application, busiest first.
+///
+/// A process belongs to the bundle its executable lives in. When the
+/// executable is unknown (another user's process) it follows its nearest
+/// bundled ancestor, then a bundle whose name prefixes a "<App> Helper"
+/// process name; anything else is grouped by its own name.
+pub(crate) fn group_by_app(samples: &[ProcessSample]) -> Vec<AppResourceUsage> {
+    let bundles: HashMap<u32, PathBuf> = samples
+        .iter()
+        .filter_map(|sample| Some((sample.pid, bundle_of(sample.exe.as_deref()?)?)))
+        .collect();
+    let parents: HashMap<u32, u32> = samples
+        .iter()
+        .filter_map(|sample| Some((sample.pid, sample.parent_pid?)))
+        .collect();
+    let by_name: HashMap<String, PathBuf> = bundles
+        .values()
+        .filter_map(|bundle| Some((app_name(bundle)?, bundle.clone())))
+        .collect();
+
+    let mut groups: HashMap<AppKey, AppResourceUsage> = HashMap::new();
+    for sample in samples {
+        let key = key_for(sample, &bundles, &parents, &by_name);
+        let usage = groups.entry(key.clone()).or_insert_with(|| {
+            let (name, bundle_path) = match &key {
+                AppKey::Bundle(bundle) => (
+                    app_name(bundle).unwrap_or_else(|| sample.name.clone()),
+                    Some(bundle.to_string_lossy().to_string()),
+                ),
+                AppKey::Name(name) => (name.clone(), None),
+            };
+            AppResourceUsage {
+                name,
+                bundle_path,
+                cpu_usage: 0.0,
+                memory_usage: 0,
+                process_count: 0,
+                pids: Vec::new(),
+            }
+        });
+        usage.cpu_usage += sample.cpu_usage;
+        usage.memory_usage += sample.memory_usage;
+        usage.process_count += 1;
+        usage.pids.push(sample.pid);
+    }
+
+    let mut apps: Vec<AppResourceUsage> = groups.into_values().collect();
+    for app in &mut apps {
+        app.pids.sort_unstable();
+    }
+    apps.sort_by(|a, b| {
+        b.cpu_usage
+            .total_cmp(&a.cpu_usage)
+            .then(b.memory_usage.cmp(&a.memory_usage))
+            .then_with(|| a.name.cmp(&b.name))
+    });
+    apps
+}
+
+fn key_for(
+    sample: &ProcessSample,
+    bundles: &HashMap<u32, PathBuf>,
+    parents: &HashMap<u32, u32>,
+    by_name: &HashMap<String, PathBuf>,
+) -> AppKey {
+    if let Some(bundle) = bundles.get(&sample.pid) {
+        return AppKey::Bundle(bundle.clone());
+    }
+    if sample.exe.is_none() {
+        // launchd parents every app, so stop before reaching it.
+        let mut pid = sample.pid;
+        let mut hops = 0;
+        while let Some(&parent) = parents.get(&pid) {
+            if parent <= 1 || hops == parents.len() {
+                break;
+            }
+            if let Some(bundle) = bundles.get(&parent) {
+                return AppKey::Bundle(bundle.clone());
+            }
+            pid = parent;
+            hops += 1;
+        }
+    }
+    if let Some(bundle) = helper_owner(&sample.name).and_then(|owner| by_name.get(owner)) {
+        return AppKey::Bundle(bundle.clone());
+    }
+    AppKey::Name(sample.name.clone())
+}
+
+fn app_name(bundle: &Path) -> Option<String> {
+    bundle
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().to_string())
+}
@@ -0,0 +1,182 @@
+use std::process::Command;
+
+use serde::{Deserialize, Serialize};
+use tokio_util::sync::CancellationToken;
+
+use super::power_info::{dictionary_number, ioreg_properties};
+use crate::elevation;
+
+/// `powermetrics` sampling window; long enough to average out a frame or two.
+const POWERMETRICS_SAMPLE_MS: u32 = 500;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GpuInfo {
+    /// Such as "Apple M2 Pro" or "AMD Radeon Pro 5500M".
+    pub model: String,
+    pub core_count: Option<u32>,
+    /// Apple silicon and integrated GPUs use system memory instead of VRAM.
+    pub unified_memory: bool,
+    /// Share of time the GPU was busy.
+    pub utilization_percent: Option<f32>,
+    pub renderer_utilization_percent: Option<f32>,
+    pub tiler_utilization_percent: Option<f32>,
+    /// VRAM in use, or system memory the GPU has wired for unified memory.
+    pub memory_used: Option<u64>,
+    /// VRAM, or all of system memory for unified memory.
+    pub memory_total: Option<u64>,
+    /// `memory_used` as a percentage of `memory_total`.
+    pub memory_pressure_percent: Option<f32>,
+    /// Where utilization came from: "iokit" or "powermetrics".
+    pub source: String,
+}
+
+/// Every GPU IOKit knows about, with its performance statistics. With
+/// `allow_admin`, utilization the statistics lack is sampled with
+/// `powermetrics` behind the administrator prompt.
+pub async fn get_gpu_info(allow_admin: bool) -> Result<Vec<GpuInfo>, String> {
+    let mut gpus = tokio::task::spawn_blocking(read_gpus)
+        .await
+        .map_err(|e| format!("GPU info task failed: {}", e))??;
+    if allow_admin && gpus.iter().any(|gpu| gpu.utilization_percent.is_none()) {
+        let script = format!(
+            "/usr/bin/powermetrics --samplers gpu_power -i {} -n 1",
+            POWERMETRICS_SAMPLE_MS
+        );
+        match elevation::run_as_admin(&script, &CancellationToken::new()).await {
+            Ok(output) => apply_powermetrics(&mut gpus, &output),
+            Err(err) => log::warn!("powermetrics GPU sample failed: {}", err),
+        }
+    }
+    Ok(gpus)
+}
+
+/// GPUs from IOKit, without prompting; an empty list when there are none.
+pub fn read_gpus() -> Result<Vec<GpuInfo>, String> {
+    let ioreg = run(
+        "ioreg",
+        &["-r", "-d", "1", "-w", "0", "-c", "IOAccelerator"],
+    )?;
+    let system_memory = run("sysctl", &["-n", "hw.memsize"])
+        .ok()
+        .and_then(|text| text.trim().parse().ok());
+    let mut gpus = parse_ioreg_accelerators(&ioreg, system_memory);
+    if gpus.iter().any(|gpu| gpu.model.is_empty()) {
+        match run("system_profiler", &["SPDisplaysDataType"]) {
+            Ok(text) => name_from_chipsets(&mut gpus, &parse_chipset_models(&text)),
+            Err(err) => log::warn!("Failed to read GPU models: {}", err),
+        }
+    }
+    for gpu in gpus.iter_mut().filter(|gpu| gpu.model.is_empty()) {
+        gpu.model = "Unknown GPU".to_string();
+    }
+    Ok(gpus)
+}
+
+/// One [`GpuInfo`] per `IOAccelerator` entry of `ioreg -r -d 1 -c
+/// IOAccelerator`. `system_memory` is the total for unified memory. The
+/// model is left empty when IOKit has none, as on Intel Macs.
+pub(crate) fn parse_ioreg_accelerators(text: &str, system_memory: Option<u64>) -> Vec<GpuInfo> {
+    text.split("+-o ")
+        .skip(1)
+        .map(|entry| {
+            let properties = ioreg_properties(entry);
+            let string = |key: &str| {
+                properties
+                    .get(key)
+                    .map(|value| value.trim_matches('"').to_string())
+            };
+            let statistics = properties
+                .get("PerformanceStatistics")
+                .copied()
+                .unwrap_or("");
+            let statistic = |key: &str| dictionary_number(statistics, key);
+            let percent = |key: &str| statistic(key).map(|value| value.min(100) as f32);
+
+            let vram_used = statistic("vramUsedBytes");
+            let vram_total = vram_used
+                .zip(statistic("vramFreeBytes"))
+                .map(|(used, free)| used + free);
+            let unified_memory = vram_used.is_none();
+            let (memory_used, memory_total) = if unified_memory {
+                (statistic("In use system memory"), system_memory)
+            } else {
+                (vram_used, vram_total)
+            };
+            let memory_pressure_percent = memory_used
+                .zip(memory_total.filter(|total| *total > 0))
+                .map(|(used, total)| (used as f64 / total as f64 * 100.0) as f32);
+
+            GpuInfo {
+                model: string("model").unwrap_or_default(),
+                core_count: properties
+                    .get("gpu-core-count")
+                    .and_then(|value| value.parse().ok()),
+                unified_memory,
+                utilization_percent: percent("Device Utilization %")
+                    .or_else(|| percent("GPU Activity(%)")),
+                renderer_utilization_percent: percent("Renderer Utilization %"),
+                tiler_utilization_percent: percent("Tiler Utilization %"),
+                memory_used,
+                memory_total,
+                memory_pressure_percent,
+                source: "iokit".to_string(),
+            }
+        })
+        .collect()
+}
+
+/// `Chipset Model:` lines of `system_profiler SPDisplaysDataType`.
+pub(crate) fn parse_chipset_models(text: &str) -> Vec<String> {
+    text.lines()
+        .filter_map(|line| line.trim().strip_prefix("Chipset Model:"))
+        .map(|model| model.trim().to_string())
+        .collect()
+}
+
+/// Give GPUs without a model the chipset names IOKit did not already use,
+/// in order.
+fn name_from_chipsets(gpus: &mut [GpuInfo], chipsets: &[String]) {
+    let unused: Vec<String> = chipsets
+        .iter()
+        .filter(|chipset| !gpus.iter().any(|gpu| &gpu.model == *chipset))
+        .cloned()
+        .collect();
+    let mut unused = unused.into_iter();
+    for gpu in gpus.iter_mut().filter(|gpu| gpu.model.is_empty()) {
+        if let Some(model) = unused.next() {
+            gpu.model = model;
+        }
+    }
+}
+
+/// Fill in utilization IOKit did not report from `powermetrics --samplers
+/// gpu_power`, which samples a single GPU.
+pub(crate) fn apply_powermetrics(gpus: &mut [GpuInfo], text: &str) {
+    let residency = text.lines().find_map(|line| {
+        let (label, value) = line.split_once(':')?;
+        if !label.trim().ends_with("active residency") {
+            return None;
+        }
+        value.trim().split('%').next()?.trim().parse::<f32>().ok()
+    });
+    let (Some(residency), Some(gpu)) = (
+        residency,
+        gpus.iter_mut()
+            .find(|gpu| gpu.utilization_percent.is_none()),
+    ) else {
+        return;
+    };
+    gpu.utilization_percent = Some(residency.clamp(0.0, 100.0));
+    gpu.source = "powermetrics".to_string();
+}
+
+fn run(program: &str, args: &[&str]) -> Result<String, String> {
+    let output = Command::new(program)
+        .args(args)
+        .output()
+        .map_err(|e| format!("Failed to execute {}: {}", program, e))?;
+    if !output.status.success() {
+        return Err(format!("{} exited with {}", program, output.status));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
@@ -0,0 +1,108 @@
+use std::process::Command;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::capabilities::{self, Tool};
+
+/// Sampling windows outside this range are clamped into it.
+const MIN_WINDOW: Duration = Duration::from_secs(1);
+const MAX_WINDOW: Duration = Duration::from_secs(10);
+
+/// Bytes one process moved during the sampling window.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProcessNetworkUsage {
+    pub pid: u32,
+    /// As `nettop` prints it, which may be truncated.
+    pub name: String,
+    pub bytes_in: u64,
+    pub bytes_out: u64,
+    pub bytes_in_per_sec: f64,
+    pub bytes_out_per_sec: f64,
+}
+
+/// Per-process traffic over `window`, busiest first; processes that moved
+/// nothing are left out. Blocks for the length of the window.
+pub fn sample_process_network_usage(window: Duration) -> Result<Vec<ProcessNetworkUsage>, String> {
+    let nettop = capabilities::tool_path(Tool::Nettop).ok_or("nettop is not installed")?;
+    let window = window.clamp(MIN_WINDOW, MAX_WINDOW);
+    // Two samples in delta mode: the first is the running totals, the
+    // second what changed during the window.
+    let output = Command::new(nettop)
+        .args([
+            "-P",
+            "-d",
+            "-x",
+            "-L",
+            "2",
+            "-J",
+            "bytes_in,bytes_out",
+            "-s",
+        ])
+        .arg(window.as_secs().to_string())
+        .output()
+        .map_err(|e| format!("Failed to execute nettop: {}", e))?;
+    if !output.status.success() {
+        return Err(format!("nettop exited with {}", output.status));
+    }
+    Ok(parse_nettop_samples(
+        &String::from_utf8_lossy(&output.stdout),
+        window,
+    ))
+}
+
+/// Rows of the last sample in `nettop -L` CSV output. Each sample starts
+/// with a header naming the columns; the process column, `name.pid`, comes
+/// right before `bytes_in` and is unnamed.
+pub(crate) fn parse_nettop_samples(text: &str, window: Duration) -> Vec<ProcessNetworkUsage> {
+    let mut columns: Option<(usize, usize)> = None;
+    let mut usage: Vec<ProcessNetworkUsage> = Vec::new();
+    for line in text.lines() {
+        let fields: Vec<&str> = line.split(',').map(str::trim).collect();
+        if let Some(bytes_in) = fields.iter().position(|field| *field == "bytes_in") {
+            let Some(bytes_out) = fields.iter().position(|field| *field == "bytes_out") else {
+                continue;
+            };
+            // A new sample; only the last one covers the window alone.
+            columns = Some((bytes_in, bytes_out));
+            usage.clear();
+            continue;
+        }
+        let Some((bytes_in, bytes_out)) = columns else {
+            continue;
+        };
+        let Some((name, pid)) = bytes_in
+            .checked_sub(1)
+            .and_then(|index| fields.get(index))
+            .and_then(|process| process.rsplit_once('.'))
+            .and_then(|(name, pid)| Some((name, pid.parse::<u32>().ok()?)))
+        else {
+            continue;
+        };
+        let counter = |index: usize| -> u64 {
+            fields
+                .get(index)
+                .and_then(|value| value.parse().ok())
+                .unwrap_or(0)
+        };
+        let (bytes_in, bytes_out) = (counter(bytes_in), counter(bytes_out));
+        if bytes_in == 0 && bytes_out == 0 {
+            continue;
+        }
+        let seconds = window.as_secs_f64().max(f64::EPSILON);
+        usage.push(ProcessNetworkUsage {
+            pid,
+            name: name.to_string(),
+            bytes_in,
+            bytes_out,
+            bytes_in_per_sec: bytes_in as f64 / seconds,
+            bytes_out_per_sec: bytes_out as f64 / seconds,
+        });
+    }
+    usage.sort_by(|a, b| {
+        (b.bytes_in + b.bytes_out)
+            .cmp(&(a.bytes_in + a.bytes_out))
+            .then_with(|| a.pid.cmp(&b.pid))
+    });
+    usage
+}
@@ -0,0 +1,177 @@
+use std::collections::HashMap;
+use std::process::Command;
+
+use serde::{Deserialize, Serialize};
+
+/// IOKit reports an unknown time estimate as this sentinel.
+const UNKNOWN_MINUTES: u64 = 65535;
+/// Capacity keys hold mAh on Intel Macs but a percentage on Apple silicon;
+/// a percentage never exceeds this.
+const MAX_PERCENT_VALUE: u64 = 100;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BatteryInfo {
+    pub cycle_count: Option<u32>,
+    /// Full-charge capacity against design capacity, capped at 100.
+    pub health_percent: Option<f32>,
+    pub design_capacity_mah: Option<u32>,
+    pub full_charge_capacity_mah: Option<u32>,
+    pub charge_percent: Option<f32>,
+    pub is_charging: bool,
+    pub external_connected: bool,
+    pub fully_charged: bool,
+    /// Rating of the connected power adapter.
+    pub adapter_watts: Option<u32>,
+    /// Power flowing into the battery; negative while discharging.
+    pub battery_watts: Option<f32>,
+    pub time_to_full_minutes: Option<u32>,
+    pub time_to_empty_minutes: Option<u32>,
+    /// As reported by `pmset`, such as "AC Power" or "Battery Power".
+    pub power_source: Option<String>,
+}
+
+/// Battery state from `ioreg` and `pmset`; `None` on Macs without a battery.
+pub fn get_battery_info() -> Result<Option<BatteryInfo>, String> {
+    let ioreg = run("ioreg", &["-rn", "AppleSmartBattery"])?;
+    let Some(mut info) = parse_ioreg_battery(&ioreg) else {
+        return Ok(None);
+    };
+    match run("pmset", &["-g", "batt"]) {
+        Ok(pmset) => apply_pmset(&mut info, &pmset),
+        Err(err) => log::warn!("Failed to read pmset battery status: {}", err),
+    }
+    Ok(Some(info))
+}
+
+fn run(program: &str, args: &[&str]) -> Result<String, String> {
+    let output = Command::new(program)
+        .args(args)
+        .output()
+        .map_err(|e| format!("Failed to execute {}: {}", program, e))?;
+    if !output.status.success() {
+        return Err(format!("{} exited with {}", program, output.status));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// Top-level `"Key" = value` pairs of an ioreg entry.
+pub(crate) fn ioreg_properties(text: &str) -> HashMap<&str, &str> {
+    text.lines()
+        .filter_map(|line| {
+            let line = line.trim().strip_prefix('"')?;
+            let (key, value) = line.split_once("\" = ")?;
+            Some((key, value.trim()))
+        })
+        .collect()
+}
+
+pub(crate) fn parse_ioreg_battery(text: &str) -> Option<BatteryInfo> {
+    let properties = ioreg_properties(text);
+    if !properties.contains_key("DesignCapacity") && !properties.contains_key("CycleCount") {
+        return None;
+    }
+    let number = |key: &str| {
+        properties
+            .get(key)
+            .and_then(|value| value.parse::<u64>().ok())
+    };
+    let flag = |key: &str| properties.get(key) == Some(&"Yes");
+    let minutes = |key: &str| {
+        number(key)
+            .filter(|minutes| *minutes != UNKNOWN_MINUTES)
+            .map(|minutes| minutes as u32)
+    };
+
+    let design = number("DesignCapacity").filter(|capacity| *capacity > 0);
+    let max_capacity = number("MaxCapacity");
+    let full_charge = number("AppleRawMaxCapacity")
+        .or_else(|| number("NominalChargeCapacity"))
+        .or(max_capacity.filter(|capacity| *capacity > MAX_PERCENT_VALUE))
+        .filter(|capacity| *capacity > 0);
+    let health_percent = full_charge
+        .zip(design)
+        .map(|(full, design)| (full as f32 / design as f32 * 100.0).min(100.0));
+
+    let charge_percent = match (
+        number("AppleRawCurrentCapacity"),
+        number("AppleRawMaxCapacity"),
+    ) {
+        (Some(current), Some(max)) if max > 0 => Some(current as f32 / max as f32 * 100.0),
+        _ => number("CurrentCapacity")
+            .zip(max_capacity.filter(|max| *max > 0))
+            .map(|(current, max)| current as f32 / max as f32 * 100.0),
+    }
+    .map(|percent| percent.min(100.0));
+
+    // Negative currents are printed as their unsigned 64-bit representation.
+    let amperage = number("Amperage").map(|amperage| amperage as i64);
+    let battery_watts = number("Voltage")
+        .zip(amperage)
+        .map(|(millivolts, milliamps)| (millivolts as f64 * milliamps as f64 / 1e6) as f32);
+
+    let is_charging = flag("IsCharging");
+    let external_connected = flag("ExternalConnected");
+    Some(BatteryInfo {
+        cycle_count: number("CycleCount").map(|count| count as u32),
+        health_percent,
+        design_capacity_mah: design.map(|capacity| capacity as u32),
+        full_charge_capacity_mah: full_charge.map(|capacity| capacity as u32),
+        charge_percent,
+        is_charging,
+        external_connected,
+        fully_charged: flag("FullyCharged"),
+        adapter_watts: properties
+            .get("AdapterDetails")
+            .and_then(|details| dictionary_number(details, "Watts"))
+            .map(|watts| watts as u32),
+        battery_watts,
+        time_to_full_minutes: if is_charging {
+            minutes("AvgTimeToFull")
+        } else {
+            None
+        },
+        time_to_empty_minutes: if external_connected {
+            None
+        } else {
+            minutes("AvgTimeToEmpty").or_else(|| minutes("TimeRemaining"))
+        },
+        power_source: None,
+    })
+}
+
+/// `"Key"=value` inside an inline ioreg dictionary such as `{"Watts"=96}`.
+pub(crate) fn dictionary_number(dictionary: &str, key: &str) -> Option<u64> {
+    let needle = format!("\"{}\"=", key);
+    let start = dictionary.find(&needle)? + needle.len();
+    let digits: String = dictionary[start..]
+        .chars()
+        .take_while(|c| c.is_ascii_digit())
+        .collect();
+    digits.parse().ok()
+}
+
+/// Fill in the power source, and a time estimate when IOKit had none, from
+/// `pmset -g batt`.
+pub(crate) fn apply_pmset(info: &mut BatteryInfo, text: &str) {
+    info.power_source = text
+        .lines()
+        .next()
+        .and_then(|line| line.split('\'').nth(1))
+        .map(str::to_string);
+    let Some(estimate) = text
+        .split(';')
+        .find_map(|part| part.trim().split_once(" remaining").map(|(time, _)| time))
+        .and_then(|time| time.split_whitespace().last())
+        .and_then(|time| time.split_once(':'))
+        .and_then(|(hours, minutes)| {
+            Some(hours.parse::<u32>().ok()? * 60 + minutes.parse::<u32>().ok()?)
+        })
+    else {
+        return;
+    };
+    if info.is_charging {
+        info.time_to_full_minutes.get_or_insert(estimate);
+    } else if !info.external_connected {
+        info.time_to_empty_minutes.get_or_insert(estimate);
+    }
+}
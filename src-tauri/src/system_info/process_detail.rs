@@ -0,0 +1,138 @@
+use std::collections::{HashMap, HashSet};
+use std::process::Command;
+
+use serde::{Deserialize, Serialize};
+
+use super::ProcessInfo;
+use crate::capabilities::{tool_available, Tool};
+
+/// Deeper descendants are left out of the child tree.
+const MAX_TREE_DEPTH: usize = 8;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProcessNode {
+    pub pid: u32,
+    pub name: String,
+    pub children: Vec<ProcessNode>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProcessDetail {
+    pub process: ProcessInfo,
+    pub command: Vec<String>,
+    pub executable: Option<String>,
+    /// Unix seconds.
+    pub start_time: u64,
+    /// Scheduling priority from -20 (highest) to 19 (lowest).
+    pub nice: Option<i32>,
+    pub threads: Option<u32>,
+    /// Open file descriptors, including the working directory and mapped
+    /// executables, as `lsof` lists them.
+    pub open_files: Option<usize>,
+    /// Activity Monitor's Energy Impact, over a one-second sample.
+    pub energy_impact: Option<f32>,
+    pub parent: Option<ProcessNode>,
+    pub children: Vec<ProcessNode>,
+}
+
+impl ProcessDetail {
+    /// Fill in threads, energy impact and open files. Blocks for about a
+    /// second while `top` samples the process.
+    pub fn with_activity(mut self) -> Self {
+        let pid = self.process.pid;
+        if let Some(text) = run(
+            "top",
+            &[
+                "-l",
+                "2",
+                "-s",
+                "1",
+                "-pid",
+                &pid.to_string(),
+                "-stats",
+                "pid,th,power",
+            ],
+        ) {
+            if let Some((threads, energy)) = parse_top_stats(&text, pid) {
+                self.threads = Some(threads);
+                self.energy_impact = Some(energy);
+            }
+        }
+        if tool_available(Tool::Lsof) {
+            self.open_files = run("lsof", &["-n", "-P", "-p", &pid.to_string(), "-F", "f"])
+                .map(|text| count_lsof_descriptors(&text));
+        }
+        self
+    }
+}
+
+fn run(program: &str, args: &[&str]) -> Option<String> {
+    let output = Command::new(program).args(args).output().ok()?;
+    if !output.status.success() {
+        log::debug!("{} exited with {}", program, output.status);
+        return None;
+    }
+    Some(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// Threads and power from the last sample of
+/// `top -l 2 -pid <pid> -stats pid,th,power`. The first sample has no
+/// interval to measure power over, so only the last one counts.
+pub(crate) fn parse_top_stats(text: &str, pid: u32) -> Option<(u32, f32)> {
+    let pid = pid.to_string();
+    let row = text
+        .lines()
+        .rfind(|line| line.split_whitespace().next() == Some(pid.as_str()))?;
+    let mut columns = row.split_whitespace().skip(1);
+    // Reported as "total/running" while some threads are running.
+    let threads = columns.next()?.split('/').next()?.parse().ok()?;
+    let power = columns.next()?.parse().ok()?;
+    Some((threads, power))
+}
+
+/// Descriptor lines (`f...`) in `lsof -F f` output.
+pub(crate) fn count_lsof_descriptors(text: &str) -> usize {
+    text.lines().filter(|line| line.starts_with('f')).count()
+}
+
+/// Descendants of `pid` given every process as `(pid, parent, name)`.
+pub(crate) fn child_tree(pid: u32, processes: &[(u32, Option<u32>, String)]) -> Vec<ProcessNode> {
+    let mut children: HashMap<u32, Vec<(u32, &str)>> = HashMap::new();
+    for (child, parent, name) in processes {
+        if let Some(parent) = parent {
+            children.entry(*parent).or_default().push((*child, name));
+        }
+    }
+    for list in children.values_mut() {
+        list.sort_unstable();
+    }
+    let mut visited = HashSet::from([pid]);
+    build_nodes(pid, &children, &mut visited, 0)
+}
+
+fn build_nodes(
+    pid: u32,
+    children: &HashMap<u32, Vec<(u32, &str)>>,
+    visited: &mut HashSet<u32>,
+    depth: usize,
+) -> Vec<ProcessNode> {
+    if depth >= MAX_TREE_DEPTH {
+        return Vec::new();
+    }
+    let Some(list) = children.get(&pid) else {
+        return Vec::new();
+    };
+    let mut nodes = Vec::new();
+    for (child, name) in list {
+        // Reused pids can make a process look like its own ancestor.
+        if !visited.insert(*child) {
+            continue;
+        }
+        nodes.push(ProcessNode {
+            pid: *child,
+            name: name.to_string(),
+            children: build_nodes(*child, children, visited, depth + 1),
+        });
+    }
+    nodes
+}
@@ -0,0 +1,375 @@
+use std::path::PathBuf;
+use std::time::Duration;
+
+use super::apfs::{find_volume, free_space, parse_apfs_list, parse_snapshot_list};
+use super::app_usage::{group_by_app, ProcessSample};
+#[cfg(feature = "app")]
+use super::gpu_info::{apply_powermetrics, parse_chipset_models, parse_ioreg_accelerators};
+#[cfg(feature = "app")]
+use super::network_usage::parse_nettop_samples;
+#[cfg(feature = "app")]
+use super::power_info::{apply_pmset, parse_ioreg_battery};
+use super::process_detail::{child_tree, count_lsof_descriptors, parse_top_stats};
+
+#[cfg(feature = "app")]
+const APPLE_SILICON_IOREG: &str = r#"+-o AppleSmartBattery  <class AppleSmartBattery, id 0x100000254, registered, matched, active, busy 0 (0 ms), retain 7>
+    {
+      "TimeRemaining" = 0
+      "AvgTimeToEmpty" = 65535
+      "AdapterDetails" = {"AdapterVoltage"=20000,"Watts"=96,"Current"=4700,"FamilyCode"=18446744073172697098}
+      "AppleRawCurrentCapacity" = 3800
+      "AppleRawMaxCapacity" = 5100
+      "DesignCapacity" = 6000
+      "MaxCapacity" = 100
+      "CurrentCapacity" = 75
+      "CycleCount" = 312
+      "BatteryData" = {"CycleCount"=312,"DesignCapacity"=6000}
+      "IsCharging" = Yes
+      "ExternalConnected" = Yes
+      "FullyCharged" = No
+      "AvgTimeToFull" = 48
+      "Voltage" = 12600
+      "Amperage" = 2500
+    }
+"#;
+
+#[cfg(feature = "app")]
+#[test]
+fn parses_apple_silicon_battery_while_charging() {
+    let info = parse_ioreg_battery(APPLE_SILICON_IOREG).unwrap();
+    assert_eq!(info.cycle_count, Some(312));
+    assert_eq!(info.design_capacity_mah, Some(6000));
+    assert_eq!(info.full_charge_capacity_mah, Some(5100));
+    assert_eq!(info.health_percent, Some(85.0));
+    assert!((info.charge_percent.unwrap() - 74.5).abs() < 0.1);
+    assert!(info.is_charging && info.external_connected && !info.fully_charged);
+    assert_eq!(info.adapter_watts, Some(96));
+    assert!((info.battery_watts.unwrap() - 31.5).abs() < 0.01);
+    assert_eq!(info.time_to_full_minutes, Some(48));
+    assert_eq!(info.time_to_empty_minutes, None);
+}
+
+#[cfg(feature = "app")]
+#[test]
+fn parses_intel_battery_while_discharging() {
+    let ioreg = r#"
+      "MaxCapacity" = 4200
+      "CurrentCapacity" = 2100
+      "DesignCapacity" = 6600
+      "CycleCount" = 1004
+      "IsCharging" = No
+      "ExternalConnected" = No
+      "AvgTimeToEmpty" = 65535
+      "TimeRemaining" = 65535
+      "Voltage" = 11000
+      "Amperage" = 18446744073709550616
+    "#;
+    let mut info = parse_ioreg_battery(ioreg).unwrap();
+    assert_eq!(info.full_charge_capacity_mah, Some(4200));
+    assert!((info.health_percent.unwrap() - 63.6).abs() < 0.1);
+    assert_eq!(info.charge_percent, Some(50.0));
+    assert_eq!(info.adapter_watts, None);
+    // -1000 mA at 11 V.
+    assert!((info.battery_watts.unwrap() + 11.0).abs() < 0.01);
+    assert_eq!(info.time_to_empty_minutes, None);
+
+    apply_pmset(
+        &mut info,
+        "Now drawing from 'Battery Power'\n -InternalBattery-0 (id=4653155)\t50%; discharging; 2:35 remaining present: true\n",
+    );
+    assert_eq!(info.power_source.as_deref(), Some("Battery Power"));
+    assert_eq!(info.time_to_empty_minutes, Some(155));
+    assert_eq!(info.time_to_full_minutes, None);
+}
+
+#[cfg(feature = "app")]
+#[test]
+fn pmset_without_estimate_leaves_times_unknown() {
+    let mut info = parse_ioreg_battery(APPLE_SILICON_IOREG).unwrap();
+    info.time_to_full_minutes = None;
+    apply_pmset(
+        &mut info,
+        "Now drawing from 'AC Power'\n -InternalBattery-0 (id=1)\t75%; charging; (no estimate) present: true\n",
+    );
+    assert_eq!(info.power_source.as_deref(), Some("AC Power"));
+    assert_eq!(info.time_to_full_minutes, None);
+}
+
+#[cfg(feature = "app")]
+#[test]
+fn desktop_macs_report_no_battery() {
+    assert_eq!(parse_ioreg_battery(""), None);
+    assert_eq!(
+        parse_ioreg_battery("+-o Root  <class IORegistryEntry, id 0x100000100>\n"),
+        None
+    );
+}
+
+#[test]
+fn top_stats_come_from_the_last_sample() {
+    let output = "Processes: 612 total, 3 running, 609 sleeping, 3121 threads\n\
+PID    #TH    POWER\n\
+4242   31/2   0.0\n\
+\n\
+Processes: 611 total, 2 running, 609 sleeping, 3118 threads\n\
+PID    #TH    POWER\n\
+4242   30/1   12.7\n";
+    assert_eq!(parse_top_stats(output, 4242), Some((30, 12.7)));
+    assert_eq!(parse_top_stats(output, 42), None);
+}
+
+#[test]
+fn lsof_field_output_counts_descriptors() {
+    let output = "p4242\nfcwd\nftxt\nf0\nf1\nf2\nf3\n";
+    assert_eq!(count_lsof_descriptors(output), 6);
+}
+
+#[test]
+fn child_tree_nests_descendants_and_skips_cycles() {
+    let processes = vec![
+        (1, None, "launchd".to_string()),
+        (100, Some(1), "Safari".to_string()),
+        (120, Some(100), "WebContent".to_string()),
+        (110, Some(100), "Networking".to_string()),
+        (130, Some(120), "helper".to_string()),
+        (200, Some(1), "Mail".to_string()),
+        // A reused pid claiming the root as its child.
+        (100, Some(130), "Safari".to_string()),
+    ];
+    let tree = child_tree(100, &processes);
+    let pids: Vec<u32> = tree.iter().map(|node| node.pid).collect();
+    assert_eq!(pids, vec![110, 120]);
+    assert_eq!(tree[1].children.len(), 1);
+    assert_eq!(tree[1].children[0].pid, 130);
+    assert!(tree[1].children[0].children.is_empty());
+}
+
+fn sample(pid: u32, parent: u32, name: &str, exe: Option<&str>, cpu: f32) -> ProcessSample {
+    ProcessSample {
+        pid,
+        parent_pid: Some(parent),
+        name: name.to_string(),
+        exe: exe.map(PathBuf::from),
+        cpu_usage: cpu,
+        memory_usage: 100,
+    }
+}
+
+#[test]
+fn helper_processes_roll_up_into_their_app() {
+    let chrome = "/Applications/Google Chrome.app";
+    let samples = vec![
+        sample(500, 1, "Google Chrome", Some(&format!("{chrome}/Contents/MacOS/Google Chrome")), 5.0),
+        sample(
+            501,
+            500,
+            "Google Chrome Helper (GPU)",
+            Some(&format!(
+                "{chrome}/Contents/Frameworks/Google Chrome Framework.framework/Helpers/Google Chrome Helper (GPU).app/Contents/MacOS/Google Chrome Helper (GPU)"
+            )),
+            10.0,
+        ),
+        // Executable hidden: attributed through the parent.
+        sample(502, 501, "Google Chrome Helper (Renderer)", None, 20.0),
+        // Executable hidden and reparented to launchd: attributed by name.
+        sample(503, 1, "Google Chrome Helper", None, 1.0),
+        sample(600, 1, "mds_stores", Some("/System/Library/Frameworks/CoreServices.framework/mds_stores"), 2.0),
+        sample(601, 1, "mds_stores", None, 2.0),
+    ];
+    let apps = group_by_app(&samples);
+    assert_eq!(apps.len(), 2);
+    assert_eq!(apps[0].name, "Google Chrome");
+    assert_eq!(apps[0].bundle_path.as_deref(), Some(chrome));
+    assert_eq!(apps[0].pids, vec![500, 501, 502, 503]);
+    assert_eq!(apps[0].process_count, 4);
+    assert_eq!(apps[0].cpu_usage, 36.0);
+    assert_eq!(apps[0].memory_usage, 400);
+    assert_eq!(apps[1].name, "mds_stores");
+    assert_eq!(apps[1].bundle_path, None);
+    assert_eq!(apps[1].process_count, 2);
+}
+
+#[cfg(feature = "app")]
+#[test]
+fn nettop_last_sample_gives_per_process_rates() {
+    let text = "\
+time,,bytes_in,bytes_out,
+10:00:00.000000,Google Chrome H.812,90000000,4000000,
+10:00:00.000000,mDNSResponder.233,5000,6000,
+time,,bytes_in,bytes_out,
+10:00:02.000000,Google Chrome H.812,2000000,100000,
+10:00:02.000000,mDNSResponder.233,0,0,
+10:00:02.000000,com.apple.WebKit.Networking.900,40000,60000,
+";
+    let usage = parse_nettop_samples(text, Duration::from_secs(2));
+    assert_eq!(usage.len(), 2);
+    assert_eq!(usage[0].name, "Google Chrome H");
+    assert_eq!(usage[0].pid, 812);
+    assert_eq!(usage[0].bytes_in, 2_000_000);
+    assert_eq!(usage[0].bytes_in_per_sec, 1_000_000.0);
+    assert_eq!(usage[1].name, "com.apple.WebKit.Networking");
+    assert_eq!(usage[1].bytes_out_per_sec, 30_000.0);
+    assert!(parse_nettop_samples("nettop: no output\n", Duration::from_secs(2)).is_empty());
+}
+
+const APFS_LIST: &str = r#"APFS Container (1 found)
+|
++-- Container disk3 0B3F5A2E-7C0D-4E51-9C43-2F6B0C1D8E2A
+    ====================================================
+    APFS Container Reference:     disk3
+    Size (Capacity Ceiling):      494384795648 B (494.4 GB)
+    Capacity In Use By Volumes:   256123456789 B (256.1 GB) (51.8% used)
+    Capacity Not Allocated:       238261338859 B (238.3 GB) (48.2% free)
+    |
+    +-< Physical Store disk0s2 86D4E8A5-2E2B-4F5A-9C11-0A6C3E5B7D19
+    |   -----------------------------------------------------------
+    |   APFS Physical Store Disk:   disk0s2
+    |   Size:                       494384795648 B (494.4 GB)
+    |
+    +-> Volume disk3s1 7A2C4B1E-3D5F-4A60-8B72-9C8D0E1F2A3B
+    |   ---------------------------------------------------
+    |   APFS Volume Disk (Role):   disk3s1 (System)
+    |   Name:                      Macintosh HD (Case-insensitive)
+    |   Mount Point:               Not Mounted
+    |   Capacity Consumed:         10234567890 B (10.2 GB)
+    |   Sealed:                    Yes
+    |   FileVault:                 Yes (Unlocked)
+    |   Snapshot:                  E1A2B3C4-D5E6-4F70-8192-A3B4C5D6E7F8
+    |   Snapshot Disk:             disk3s1s1
+    |   Snapshot Mount Point:      /
+    |   Snapshot Sealed:           Yes
+    |
+    +-> Volume disk3s5 5B6C7D8E-9F0A-4B1C-8D2E-3F4A5B6C7D8E
+    |   ---------------------------------------------------
+    |   APFS Volume Disk (Role):   disk3s5 (Data)
+    |   Name:                      Macintosh HD - Data (Case-insensitive)
+    |   Mount Point:               /System/Volumes/Data
+    |   Capacity Consumed:         240123456789 B (240.1 GB)
+    |   Sealed:                    No
+    |   FileVault:                 Yes (Unlocked)
+    |
+    +-> Volume disk3s6 9A8B7C6D-5E4F-4A3B-8C2D-1E0F9A8B7C6D
+        ---------------------------------------------------
+        APFS Volume Disk (Role):   disk3s6 (No specific role)
+        Name:                      Scratch (Case-sensitive)
+        Mount Point:               /Volumes/Scratch
+        Capacity Consumed:         1048576 B (1.0 MB)
+"#;
+
+const APFS_SNAPSHOTS: &str = r#"Snapshots for disk3s5 (2 found)
+|
++-- 5F2F8B8A-1C3D-4E5F-8A9B-0C1D2E3F4A5B
+|   Name:        com.apple.TimeMachine.2024-05-01-101500.local
+|   XID:         1234567
+|   Purgeable:   Yes
+|
++-- 6A3B9C0D-2E4F-4A6B-9C8D-1E2F3A4B5C6D
+    Name:        com.apple.os.update-ABCDEF
+    XID:         1234000
+    Purgeable:   No
+    NOTE:        This snapshot limits the minimum size of APFS Container disk3
+"#;
+
+#[test]
+fn apfs_list_gives_containers_volumes_and_roles() {
+    let containers = parse_apfs_list(APFS_LIST);
+    assert_eq!(containers.len(), 1);
+    let container = &containers[0];
+    assert_eq!(container.reference, "disk3");
+    assert_eq!(container.capacity, 494_384_795_648);
+    assert_eq!(container.free, 238_261_338_859);
+    assert_eq!(container.volumes.len(), 3);
+
+    let system = &container.volumes[0];
+    assert_eq!(system.disk, "disk3s1");
+    assert_eq!(system.roles, ["System"]);
+    assert_eq!(system.mount_point, None);
+    assert_eq!(system.snapshot_mount_point.as_deref(), Some("/"));
+    assert_eq!(container.volumes[1].capacity_consumed, 240_123_456_789);
+    assert!(container.volumes[2].roles.is_empty());
+
+    let (_, root) = find_volume(&containers, "/", "Macintosh HD").unwrap();
+    assert_eq!(root.disk, "disk3s1");
+    let (_, data) = find_volume(&containers, "/nowhere", "/dev/disk3s5").unwrap();
+    assert_eq!(data.roles, ["Data"]);
+    assert!(find_volume(&containers, "/Volumes/USB", "disk4s1").is_none());
+}
+
+#[test]
+fn snapshot_list_counts_purgeable_snapshots() {
+    assert_eq!(parse_snapshot_list(APFS_SNAPSHOTS), (2, 1));
+    assert_eq!(parse_snapshot_list("No snapshots for disk3s5\n"), (0, 0));
+    assert!(free_space("/").is_some());
+    assert!(free_space("/definitely/not/mounted").is_none());
+}
+
+#[cfg(feature = "app")]
+const APPLE_SILICON_GPU_IOREG: &str = r#"+-o AGXAcceleratorG14X  <class AGXAcceleratorG14X, id 0x1000004b1, registered, matched, active, busy 0 (0 ms), retain 88>
+    {
+      "IOClass" = "AGXAcceleratorG14X"
+      "PerformanceStatistics" = {"In use system memory (driver)"=0,"Alloc system memory"=3221225472,"Tiler Utilization %"=12,"recoveryCount"=0,"Renderer Utilization %"=31,"Device Utilization %"=34,"SplitSceneCount"=0,"In use system memory"=1610612736}
+      "model" = "Apple M2 Pro"
+      "gpu-core-count" = 19
+    }
+"#;
+
+#[cfg(feature = "app")]
+const INTEL_MAC_GPU_IOREG: &str = r#"+-o AMDRadeonX6000_AMDNavi14GraphicsAccelerator  <class AMDRadeonX6000_AMDNavi14GraphicsAccelerator, id 0x100000563, registered, matched, active, busy 0 (0 ms), retain 41>
+    {
+      "IOClass" = "AMDRadeonX6000_AMDNavi14GraphicsAccelerator"
+      "PerformanceStatistics" = {"vramFreeBytes"=3221225472,"vramUsedBytes"=1073741824,"Device Utilization %"=8,"GPU Activity(%)"=8}
+    }
++-o IntelAccelerator  <class IntelAccelerator, id 0x1000005a2, registered, matched, active, busy 0 (0 ms), retain 23>
+    {
+      "IOClass" = "IntelAccelerator"
+      "PerformanceStatistics" = {"In use system memory"=268435456}
+    }
+"#;
+
+#[cfg(feature = "app")]
+#[test]
+fn apple_silicon_gpu_reports_unified_memory_and_utilization() {
+    let gpus = parse_ioreg_accelerators(APPLE_SILICON_GPU_IOREG, Some(16 * 1024 * 1024 * 1024));
+    assert_eq!(gpus.len(), 1);
+    let gpu = &gpus[0];
+    assert_eq!(gpu.model, "Apple M2 Pro");
+    assert_eq!(gpu.core_count, Some(19));
+    assert!(gpu.unified_memory);
+    assert_eq!(gpu.utilization_percent, Some(34.0));
+    assert_eq!(gpu.renderer_utilization_percent, Some(31.0));
+    assert_eq!(gpu.tiler_utilization_percent, Some(12.0));
+    assert_eq!(gpu.memory_used, Some(1_610_612_736));
+    assert_eq!(gpu.memory_pressure_percent, Some(9.375));
+    assert_eq!(gpu.source, "iokit");
+}
+
+#[cfg(feature = "app")]
+#[test]
+fn discrete_gpu_reports_vram_and_powermetrics_fills_missing_utilization() {
+    let mut gpus = parse_ioreg_accelerators(INTEL_MAC_GPU_IOREG, Some(32 * 1024 * 1024 * 1024));
+    assert_eq!(gpus.len(), 2);
+    assert!(!gpus[0].unified_memory);
+    assert_eq!(gpus[0].memory_total, Some(4 * 1024 * 1024 * 1024));
+    assert_eq!(gpus[0].memory_pressure_percent, Some(25.0));
+    assert_eq!(gpus[0].utilization_percent, Some(8.0));
+    // Intel Macs name neither GPU in IOKit.
+    assert!(gpus.iter().all(|gpu| gpu.model.is_empty()));
+    assert!(gpus[1].unified_memory);
+    assert_eq!(gpus[1].utilization_percent, None);
+
+    let chipsets = parse_chipset_models(
+        "Graphics/Displays:\n\n    AMD Radeon Pro 5500M:\n\n      Chipset Model: AMD Radeon Pro 5500M\n      Type: GPU\n\n    Intel UHD Graphics 630:\n\n      Chipset Model: Intel UHD Graphics 630\n",
+    );
+    assert_eq!(
+        chipsets,
+        vec!["AMD Radeon Pro 5500M", "Intel UHD Graphics 630"]
+    );
+
+    apply_powermetrics(
+        &mut gpus,
+        "**** GPU usage ****\n\nGPU HW active frequency: 350 MHz\nGPU HW active residency:  17.42% (350 MHz: 17%)\nGPU idle residency:  82.58%\n",
+    );
+    assert_eq!(gpus[0].source, "iokit");
+    assert_eq!(gpus[1].utilization_percent, Some(17.42));
+    assert_eq!(gpus[1].source, "powermetrics");
+}
@@ -0,0 +1,357 @@
+// src/system_maintenance.rs
+
+//! Rebuilding the caches and databases macOS maintains on its own: font
+//! caches, the Launch Services database, the periodic maintenance scripts
+//! and, on releases that still allow it, the dyld shared cache.
+//!
+//! Tasks that touch system files run as root behind one administrator
+//! prompt; the Launch Services rebuild is per user and runs without it.
+//! A task whose tool is missing from this release is skipped, not failed.
+
+#[cfg(feature = "app")]
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "app")]
+use tokio::process::Command;
+#[cfg(feature = "app")]
+use tokio_util::sync::CancellationToken;
+
+#[cfg(feature = "app")]
+use crate::elevation;
+#[cfg(feature = "app")]
+use crate::privileged_helper::{self, HelperCommand};
+
+#[cfg(all(test, feature = "app"))]
+mod tests;
+
+/// Maintenance progress callback: `(percent, message)`.
+#[cfg(feature = "app")]
+pub type MaintenanceProgressFn = dyn Fn(f32, &str) + Send + Sync;
+
+const ATSUTIL: &str = "/usr/bin/atsutil";
+const LSREGISTER: &str = "/System/Library/Frameworks/CoreServices.framework/Frameworks/LaunchServices.framework/Support/lsregister";
+const PERIODIC: &str = "/usr/sbin/periodic";
+/// Gone since macOS 11, where the shared cache lives on the sealed system
+/// volume and is only rebuilt by OS updates.
+const UPDATE_DYLD_SHARED_CACHE: &str = "/usr/bin/update_dyld_shared_cache";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MaintenanceTask {
+    FontCaches,
+    LaunchServices,
+    PeriodicScripts,
+    DyldCache,
+}
+
+impl MaintenanceTask {
+    #[cfg(feature = "app")]
+    pub const ALL: [MaintenanceTask; 4] = [
+        MaintenanceTask::FontCaches,
+        MaintenanceTask::LaunchServices,
+        MaintenanceTask::PeriodicScripts,
+        MaintenanceTask::DyldCache,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            MaintenanceTask::FontCaches => "Rebuild font caches",
+            MaintenanceTask::LaunchServices => "Rebuild Launch Services database",
+            MaintenanceTask::PeriodicScripts => "Run periodic maintenance scripts",
+            MaintenanceTask::DyldCache => "Rebuild dyld shared cache",
+        }
+    }
+
+    /// Marker the admin script echoes after the task's command, and the
+    /// suffix of its operation type.
+    #[cfg(feature = "app")]
+    pub fn key(self) -> &'static str {
+        match self {
+            MaintenanceTask::FontCaches => "font_caches",
+            MaintenanceTask::LaunchServices => "launch_services",
+            MaintenanceTask::PeriodicScripts => "periodic_scripts",
+            MaintenanceTask::DyldCache => "dyld_cache",
+        }
+    }
+
+    pub(crate) fn program(self) -> &'static str {
+        match self {
+            MaintenanceTask::FontCaches => ATSUTIL,
+            MaintenanceTask::LaunchServices => LSREGISTER,
+            MaintenanceTask::PeriodicScripts => PERIODIC,
+            MaintenanceTask::DyldCache => UPDATE_DYLD_SHARED_CACHE,
+        }
+    }
+
+    pub(crate) fn args(self) -> &'static [&'static str] {
+        match self {
+            MaintenanceTask::FontCaches => &["databases", "-remove"],
+            MaintenanceTask::LaunchServices => &[
+                "-kill", "-r", "-domain", "local", "-domain", "system", "-domain", "user",
+            ],
+            MaintenanceTask::PeriodicScripts => &["daily", "weekly", "monthly"],
+            MaintenanceTask::DyldCache => &["-force"],
+        }
+    }
+
+    /// Launch Services keeps a database per user, so rebuilding it as root
+    /// would rebuild root's instead.
+    pub fn needs_admin(self) -> bool {
+        !matches!(self, MaintenanceTask::LaunchServices)
+    }
+
+    /// Shell form of the command for the admin script.
+    #[cfg(feature = "app")]
+    fn command_line(self) -> String {
+        std::iter::once(self.program())
+            .chain(self.args().iter().copied())
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    /// Why the task can't run on this Mac, if it can't.
+    #[cfg(feature = "app")]
+    fn unavailable_reason(self, root: &Path) -> Option<String> {
+        let program = root.join(self.program().trim_start_matches('/'));
+        if program.exists() {
+            return None;
+        }
+        Some(match self {
+            MaintenanceTask::DyldCache => {
+                "The dyld shared cache is part of the sealed system volume on this macOS".into()
+            }
+            _ => format!("{} is not available on this macOS", self.program()),
+        })
+    }
+}
+
+/// A task and whether it can run here, for listing before running.
+#[cfg(feature = "app")]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MaintenanceTaskInfo {
+    pub task: MaintenanceTask,
+    pub label: String,
+    pub needs_admin: bool,
+    pub available: bool,
+    pub unavailable_reason: Option<String>,
+}
+
+#[cfg(feature = "app")]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MaintenanceStep {
+    pub task: MaintenanceTask,
+    pub success: bool,
+    /// Not run because the task does not apply to this macOS.
+    pub skipped: bool,
+    pub error: Option<String>,
+}
+
+#[cfg(feature = "app")]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct MaintenanceResult {
+    pub steps: Vec<MaintenanceStep>,
+    /// Set when inspector mode reported what would happen instead of doing it.
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+#[cfg(feature = "app")]
+impl MaintenanceResult {
+    pub fn failures(&self) -> Vec<String> {
+        self.steps
+            .iter()
+            .filter(|step| !step.success && !step.skipped)
+            .filter_map(|step| step.error.clone())
+            .collect()
+    }
+}
+
+/// Every task and whether it applies to this Mac.
+#[cfg(feature = "app")]
+pub fn list_maintenance_tasks() -> Vec<MaintenanceTaskInfo> {
+    task_infos(Path::new("/"))
+}
+
+#[cfg(feature = "app")]
+pub(crate) fn task_infos(root: &Path) -> Vec<MaintenanceTaskInfo> {
+    MaintenanceTask::ALL
+        .into_iter()
+        .map(|task| {
+            let unavailable_reason = task.unavailable_reason(root);
+            MaintenanceTaskInfo {
+                task,
+                label: task.label().to_string(),
+                needs_admin: task.needs_admin(),
+                available: unavailable_reason.is_none(),
+                unavailable_reason,
+            }
+        })
+        .collect()
+}
+
+/// What [`run_maintenance`] would run and skip, without running anything.
+#[cfg(feature = "app")]
+pub fn preview_maintenance(tasks: &[MaintenanceTask]) -> Result<MaintenanceResult, String> {
+    let plan = plan(tasks, Path::new("/"))?;
+    Ok(MaintenanceResult {
+        steps: plan
+            .into_iter()
+            .map(|(task, skip)| MaintenanceStep {
+                task,
+                success: skip.is_none(),
+                skipped: skip.is_some(),
+                error: skip,
+            })
+            .collect(),
+        dry_run: true,
+    })
+}
+
+/// Each selected task once, in the order given, with the reason it will
+/// be skipped.
+#[cfg(feature = "app")]
+fn plan(
+    tasks: &[MaintenanceTask],
+    root: &Path,
+) -> Result<Vec<(MaintenanceTask, Option<String>)>, String> {
+    if tasks.is_empty() {
+        return Err("No maintenance tasks selected".into());
+    }
+    let mut planned: Vec<(MaintenanceTask, Option<String>)> = Vec::new();
+    for &task in tasks {
+        if !planned.iter().any(|(seen, _)| *seen == task) {
+            planned.push((task, task.unavailable_reason(root)));
+        }
+    }
+    Ok(planned)
+}
+
+/// Run the selected tasks: the per-user ones directly, then the rest through
+/// the installed helper or behind one administrator prompt. A failed task
+/// is recorded and the others still run; cancelling stops before the next
+/// task.
+#[cfg(feature = "app")]
+pub async fn run_maintenance(
+    tasks: &[MaintenanceTask],
+    token: &CancellationToken,
+    progress: Option<&MaintenanceProgressFn>,
+) -> Result<MaintenanceResult, String> {
+    let planned = plan(tasks, Path::new("/"))?;
+    let mut steps: Vec<MaintenanceStep> = Vec::new();
+    let mut admin_tasks: Vec<MaintenanceTask> = Vec::new();
+    for (task, skip) in &planned {
+        match skip {
+            Some(reason) => steps.push(MaintenanceStep {
+                task: *task,
+                success: false,
+                skipped: true,
+                error: Some(reason.clone()),
+            }),
+            None if task.needs_admin() => admin_tasks.push(*task),
+            None => {
+                if let Some(cb) = progress {
+                    cb(
+                        steps.len() as f32 / planned.len() as f32 * 100.0,
+                        &format!("{}…", task.label()),
+                    );
+                }
+                let error = run_user_task(*task, token).await.err();
+                if error.as_deref() == Some("cancelled") {
+                    return Err("cancelled".into());
+                }
+                steps.push(MaintenanceStep {
+                    task: *task,
+                    success: error.is_none(),
+                    skipped: false,
+                    error,
+                });
+            }
+        }
+    }
+
+    if !admin_tasks.is_empty() {
+        if let Some(cb) = progress {
+            let labels: Vec<&str> = admin_tasks.iter().map(|task| task.label()).collect();
+            cb(
+                steps.len() as f32 / planned.len() as f32 * 100.0,
+                &format!("Requesting administrator privileges: {}", labels.join(", ")),
+            );
+        }
+        let commands: Vec<HelperCommand> = admin_tasks
+            .iter()
+            .map(|&task| HelperCommand::Maintenance { task })
+            .collect();
+        match privileged_helper::send(&commands, token).await? {
+            Some(outcomes) => {
+                steps.extend(admin_tasks.iter().zip(outcomes).map(|(&task, outcome)| {
+                    MaintenanceStep {
+                        task,
+                        success: outcome.ok,
+                        skipped: false,
+                        error: outcome.error,
+                    }
+                }))
+            }
+            None => {
+                let script = admin_script(&admin_tasks);
+                let output = elevation::run_as_admin(&script, token).await?;
+                steps.extend(parse_admin_output(&output, &admin_tasks));
+            }
+        }
+    }
+
+    // Report in the order the tasks were asked for.
+    steps.sort_by_key(|step| planned.iter().position(|(task, _)| *task == step.task));
+    Ok(MaintenanceResult {
+        steps,
+        dry_run: false,
+    })
+}
+
+#[cfg(feature = "app")]
+async fn run_user_task(task: MaintenanceTask, token: &CancellationToken) -> Result<(), String> {
+    let mut command = Command::new(task.program());
+    command.args(task.args()).kill_on_drop(true);
+    let output = tokio::select! {
+        _ = token.cancelled() => return Err("cancelled".into()),
+        output = command.output() => output
+            .map_err(|e| format!("Failed to run {}: {}", task.program(), e))?,
+    };
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(format!(
+            "{} failed: {}",
+            task.label(),
+            String::from_utf8_lossy(&output.stderr).trim()
+        ))
+    }
+}
+
+/// The admin script for `tasks`, in order.
+#[cfg(feature = "app")]
+pub(crate) fn admin_script(tasks: &[MaintenanceTask]) -> String {
+    elevation::marker_script(tasks.iter().map(|task| (task.key(), task.command_line())))
+}
+
+/// One step per admin task from the script's markers.
+#[cfg(feature = "app")]
+pub(crate) fn parse_admin_output(output: &str, tasks: &[MaintenanceTask]) -> Vec<MaintenanceStep> {
+    tasks
+        .iter()
+        .map(|&task| {
+            let error = match elevation::marker_status(output, task.key()) {
+                Some(true) => None,
+                Some(false) => Some(format!("{} failed", task.label())),
+                None => Some(format!("{} did not run", task.label())),
+            };
+            MaintenanceStep {
+                task,
+                success: error.is_none(),
+                skipped: false,
+                error,
+            }
+        })
+        .collect()
+}
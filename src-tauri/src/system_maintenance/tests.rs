@@ -0,0 +1,63 @@
+use std::fs;
+
+use super::{admin_script, parse_admin_output, plan, task_infos, MaintenanceTask};
+
+#[test]
+fn tasks_missing_from_this_release_are_skipped() {
+    let root = tempfile::tempdir().unwrap();
+    for program in ["usr/bin/atsutil", "usr/sbin/periodic"] {
+        let path = root.path().join(program);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, b"").unwrap();
+    }
+
+    let infos = task_infos(root.path());
+    assert!(infos[0].available && infos[0].needs_admin);
+    assert!(!infos[1].available);
+    assert!(!infos[1].needs_admin);
+    assert!(infos[3]
+        .unavailable_reason
+        .as_deref()
+        .unwrap()
+        .contains("sealed"));
+
+    let planned = plan(
+        &[
+            MaintenanceTask::DyldCache,
+            MaintenanceTask::FontCaches,
+            MaintenanceTask::DyldCache,
+        ],
+        root.path(),
+    )
+    .unwrap();
+    assert_eq!(planned.len(), 2);
+    assert_eq!(planned[0].0, MaintenanceTask::DyldCache);
+    assert!(planned[0].1.is_some());
+    assert!(planned[1].1.is_none());
+    assert!(plan(&[], root.path()).is_err());
+}
+
+#[test]
+fn admin_tasks_share_one_script_and_report_separately() {
+    let tasks = [
+        MaintenanceTask::FontCaches,
+        MaintenanceTask::PeriodicScripts,
+    ];
+    let script = admin_script(&tasks);
+    assert!(script.contains("/usr/bin/atsutil databases -remove"));
+    assert!(script.contains("/usr/sbin/periodic daily weekly monthly"));
+    assert!(!script.contains('"'));
+
+    let steps = parse_admin_output("OK:font_caches\rERR:periodic_scripts\r", &tasks);
+    assert!(steps[0].success);
+    assert!(!steps[1].success);
+    assert_eq!(
+        steps[1].error.as_deref(),
+        Some("Run periodic maintenance scripts failed")
+    );
+    let steps = parse_admin_output("OK:font_caches", &tasks);
+    assert_eq!(
+        steps[1].error.as_deref(),
+        Some("Run periodic maintenance scripts did not run")
+    );
+}
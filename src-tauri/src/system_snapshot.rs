@@ -0,0 +1,412 @@
+// src/system_snapshot.rs
+
+//! Point-in-time system profiles (installed apps, launch items, kernel
+//! extensions, disk usage by category) and diffs between them, for "what
+//! changed since my Mac got slow" investigations.
+
+#[cfg(feature = "app")]
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+#[cfg(feature = "app")]
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "app")]
+use crate::file_cleaner::types::CategoryReport;
+#[cfg(feature = "app")]
+use crate::file_cleaner::CleanableFile;
+#[cfg(feature = "app")]
+use crate::persistence;
+
+#[cfg(all(test, feature = "app"))]
+mod tests;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AppEntry {
+    pub name: String,
+    pub path: String,
+    pub bundle_id: Option<String>,
+    pub version: Option<String>,
+}
+
+#[cfg(feature = "app")]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LaunchItem {
+    pub label: String,
+    pub path: String,
+    /// `user_agent`, `global_agent` or `global_daemon`.
+    pub scope: String,
+}
+
+#[cfg(feature = "app")]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SystemSnapshot {
+    pub id: String,
+    pub label: String,
+    pub captured_at: i64,
+    pub installed_apps: Vec<AppEntry>,
+    pub launch_items: Vec<LaunchItem>,
+    pub kexts: Vec<String>,
+    pub disk_usage: Vec<CategoryReport>,
+}
+
+#[cfg(feature = "app")]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SnapshotSummary {
+    pub id: String,
+    pub label: String,
+    pub captured_at: i64,
+}
+
+#[cfg(feature = "app")]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AppVersionChange {
+    pub name: String,
+    pub from: Option<String>,
+    pub to: Option<String>,
+}
+
+#[cfg(feature = "app")]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UsageChange {
+    pub category: String,
+    pub before: u64,
+    pub after: u64,
+    pub delta: i64,
+}
+
+#[cfg(feature = "app")]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SnapshotDiff {
+    pub from: String,
+    pub to: String,
+    pub apps_added: Vec<AppEntry>,
+    pub apps_removed: Vec<AppEntry>,
+    pub apps_updated: Vec<AppVersionChange>,
+    pub launch_items_added: Vec<LaunchItem>,
+    pub launch_items_removed: Vec<LaunchItem>,
+    pub kexts_added: Vec<String>,
+    pub kexts_removed: Vec<String>,
+    /// Categories whose size changed, largest growth first.
+    pub disk_usage_changes: Vec<UsageChange>,
+}
+
+/// Where a snapshot reads its inventory from.
+#[cfg(feature = "app")]
+pub struct SnapshotSources {
+    pub app_dirs: Vec<PathBuf>,
+    pub launch_dirs: Vec<(PathBuf, &'static str)>,
+    pub kext_dirs: Vec<PathBuf>,
+}
+
+#[cfg(feature = "app")]
+impl SnapshotSources {
+    pub fn system() -> Self {
+        let mut app_dirs = vec![PathBuf::from("/Applications")];
+        let mut launch_dirs = vec![
+            (PathBuf::from("/Library/LaunchAgents"), "global_agent"),
+            (PathBuf::from("/Library/LaunchDaemons"), "global_daemon"),
+        ];
+        if let Some(home) = dirs::home_dir() {
+            app_dirs.push(home.join("Applications"));
+            launch_dirs.push((home.join("Library/LaunchAgents"), "user_agent"));
+        }
+        Self {
+            app_dirs,
+            launch_dirs,
+            kext_dirs: vec![PathBuf::from("/Library/Extensions")],
+        }
+    }
+}
+
+#[cfg(feature = "app")]
+pub fn capture_system_snapshot(
+    label: &str,
+    sources: &SnapshotSources,
+    disk_usage: Vec<CategoryReport>,
+) -> SystemSnapshot {
+    SystemSnapshot {
+        id: uuid::Uuid::new_v4().to_string(),
+        label: label.to_string(),
+        captured_at: Utc::now().timestamp(),
+        installed_apps: list_apps(&sources.app_dirs),
+        launch_items: list_launch_items(&sources.launch_dirs),
+        kexts: list_kexts(&sources.kext_dirs),
+        disk_usage,
+    }
+}
+
+/// Aggregate scanned files into per-category usage, sorted by name.
+#[cfg(feature = "app")]
+pub fn category_usage(files: &[CleanableFile]) -> Vec<CategoryReport> {
+    let mut totals: BTreeMap<&str, (u64, usize)> = BTreeMap::new();
+    for file in files {
+        let entry = totals.entry(file.category.as_str()).or_default();
+        entry.0 = entry.0.saturating_add(file.size);
+        entry.1 += 1;
+    }
+    totals
+        .into_iter()
+        .map(|(name, (size, count))| CategoryReport {
+            name: name.to_string(),
+            size,
+            count,
+            label: None,
+        })
+        .collect()
+}
+
+#[cfg(feature = "app")]
+pub fn compare_snapshots(before: &SystemSnapshot, after: &SystemSnapshot) -> SnapshotDiff {
+    let before_apps: BTreeMap<&str, &AppEntry> = before
+        .installed_apps
+        .iter()
+        .map(|app| (app.path.as_str(), app))
+        .collect();
+    let after_apps: BTreeMap<&str, &AppEntry> = after
+        .installed_apps
+        .iter()
+        .map(|app| (app.path.as_str(), app))
+        .collect();
+
+    let mut diff = SnapshotDiff {
+        from: before.label.clone(),
+        to: after.label.clone(),
+        ..Default::default()
+    };
+    for (path, app) in &after_apps {
+        match before_apps.get(path) {
+            None => diff.apps_added.push((*app).clone()),
+            Some(old) if old.version != app.version => diff.apps_updated.push(AppVersionChange {
+                name: app.name.clone(),
+                from: old.version.clone(),
+                to: app.version.clone(),
+            }),
+            Some(_) => {}
+        }
+    }
+    diff.apps_removed = before_apps
+        .iter()
+        .filter(|(path, _)| !after_apps.contains_key(*path))
+        .map(|(_, app)| (*app).clone())
+        .collect();
+
+    let (added, removed) = set_diff(&before.launch_items, &after.launch_items);
+    diff.launch_items_added = added;
+    diff.launch_items_removed = removed;
+    let (added, removed) = set_diff(&before.kexts, &after.kexts);
+    diff.kexts_added = added;
+    diff.kexts_removed = removed;
+
+    let before_usage: BTreeMap<&str, u64> = before
+        .disk_usage
+        .iter()
+        .map(|c| (c.name.as_str(), c.size))
+        .collect();
+    let after_usage: BTreeMap<&str, u64> = after
+        .disk_usage
+        .iter()
+        .map(|c| (c.name.as_str(), c.size))
+        .collect();
+    let categories: BTreeSet<&str> = before_usage
+        .keys()
+        .chain(after_usage.keys())
+        .copied()
+        .collect();
+    diff.disk_usage_changes = categories
+        .into_iter()
+        .filter_map(|category| {
+            let old = before_usage.get(category).copied().unwrap_or(0);
+            let new = after_usage.get(category).copied().unwrap_or(0);
+            (old != new).then(|| UsageChange {
+                category: category.to_string(),
+                before: old,
+                after: new,
+                delta: new as i64 - old as i64,
+            })
+        })
+        .collect();
+    diff.disk_usage_changes
+        .sort_by_key(|change| std::cmp::Reverse(change.delta));
+    diff
+}
+
+#[cfg(feature = "app")]
+fn set_diff<T: Clone + PartialEq>(before: &[T], after: &[T]) -> (Vec<T>, Vec<T>) {
+    let added = after
+        .iter()
+        .filter(|item| !before.contains(item))
+        .cloned()
+        .collect();
+    let removed = before
+        .iter()
+        .filter(|item| !after.contains(item))
+        .cloned()
+        .collect();
+    (added, removed)
+}
+
+pub(crate) fn list_apps(dirs: &[PathBuf]) -> Vec<AppEntry> {
+    let mut bundles = Vec::new();
+    for dir in dirs {
+        // Apps live at the top level or one folder down (e.g. /Applications/Utilities).
+        for path in child_paths(dir) {
+            if is_app_bundle(&path) {
+                bundles.push(path);
+            } else if path.is_dir() {
+                bundles.extend(child_paths(&path).into_iter().filter(|p| is_app_bundle(p)));
+            }
+        }
+    }
+    let mut apps: Vec<AppEntry> = bundles.iter().map(|path| app_entry(path)).collect();
+    apps.sort_by(|a, b| a.path.cmp(&b.path));
+    apps
+}
+
+/// Describe one `.app` bundle from its `Info.plist`.
+pub(crate) fn app_entry(path: &Path) -> AppEntry {
+    let info = read_plist_xml(&path.join("Contents/Info.plist")).unwrap_or_default();
+    AppEntry {
+        name: path
+            .file_stem()
+            .map(|s| s.to_string_lossy().to_string())
+            .unwrap_or_default(),
+        path: path.to_string_lossy().to_string(),
+        bundle_id: plist_string(&info, "CFBundleIdentifier"),
+        version: plist_string(&info, "CFBundleShortVersionString")
+            .or_else(|| plist_string(&info, "CFBundleVersion")),
+    }
+}
+
+fn child_paths(dir: &Path) -> Vec<PathBuf> {
+    fs::read_dir(dir)
+        .map(|entries| entries.filter_map(Result::ok).map(|e| e.path()).collect())
+        .unwrap_or_default()
+}
+
+fn is_app_bundle(path: &Path) -> bool {
+    path.extension().is_some_and(|ext| ext == "app") && path.is_dir()
+}
+
+#[cfg(feature = "app")]
+fn list_launch_items(dirs: &[(PathBuf, &'static str)]) -> Vec<LaunchItem> {
+    let mut items = Vec::new();
+    for (dir, scope) in dirs {
+        let Ok(entries) = fs::read_dir(dir) else {
+            continue;
+        };
+        for entry in entries.filter_map(Result::ok) {
+            let path = entry.path();
+            if path.extension().is_some_and(|ext| ext == "plist") {
+                items.push(LaunchItem {
+                    label: path
+                        .file_stem()
+                        .map(|s| s.to_string_lossy().to_string())
+                        .unwrap_or_default(),
+                    path: path.to_string_lossy().to_string(),
+                    scope: scope.to_string(),
+                });
+            }
+        }
+    }
+    items.sort_by(|a, b| a.path.cmp(&b.path));
+    items
+}
+
+#[cfg(feature = "app")]
+fn list_kexts(dirs: &[PathBuf]) -> Vec<String> {
+    let mut kexts: Vec<String> = dirs
+        .iter()
+        .filter_map(|dir| fs::read_dir(dir).ok())
+        .flat_map(|entries| entries.filter_map(Result::ok))
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "kext"))
+        .filter_map(|path| path.file_name().map(|n| n.to_string_lossy().to_string()))
+        .collect();
+    kexts.sort();
+    kexts
+}
+
+/// Read a plist as XML, converting binary plists with `plutil` when needed.
+pub(crate) fn read_plist_xml(path: &Path) -> Option<String> {
+    let data = fs::read(path).ok()?;
+    if !data.starts_with(b"bplist") {
+        return String::from_utf8(data).ok();
+    }
+    let output = Command::new("plutil")
+        .args(["-convert", "xml1", "-o", "-"])
+        .arg(path)
+        .output()
+        .ok()?;
+    output
+        .status
+        .success()
+        .then(|| String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+pub(crate) fn plist_string(plist: &str, key: &str) -> Option<String> {
+    let after_key = &plist[plist.find(&format!("<key>{}</key>", key))?..];
+    let start = after_key.find("<string>")? + "<string>".len();
+    let end = after_key[start..].find("</string>")?;
+    Some(after_key[start..start + end].trim().to_string())
+}
+
+/// Persists snapshots as `<dir>/<id>.json`.
+#[cfg(feature = "app")]
+pub struct SnapshotStore {
+    dir: PathBuf,
+}
+
+#[cfg(feature = "app")]
+impl SnapshotStore {
+    /// Store under `<data dir>/macos-optimizer/snapshots`.
+    pub fn open() -> Result<Self, String> {
+        let dir = persistence::data_dir()
+            .ok_or("Data directory not found")?
+            .join("snapshots");
+        Ok(Self::at(dir))
+    }
+
+    pub fn at(dir: PathBuf) -> Self {
+        Self { dir }
+    }
+
+    pub fn save(&self, snapshot: &SystemSnapshot) -> Result<(), String> {
+        persistence::write_json(&self.path_for(&snapshot.id)?, snapshot)
+    }
+
+    pub fn load(&self, id: &str) -> Result<SystemSnapshot, String> {
+        let data = fs::read(self.path_for(id)?)
+            .map_err(|e| format!("Snapshot {} not found: {}", id, e))?;
+        serde_json::from_slice(&data).map_err(|e| format!("Snapshot {} is corrupt: {}", id, e))
+    }
+
+    /// Saved snapshots, oldest first.
+    pub fn list(&self) -> Vec<SnapshotSummary> {
+        let Ok(entries) = fs::read_dir(&self.dir) else {
+            return Vec::new();
+        };
+        let mut summaries: Vec<SnapshotSummary> = entries
+            .filter_map(Result::ok)
+            .filter_map(|entry| fs::read(entry.path()).ok())
+            .filter_map(|data| serde_json::from_slice::<SystemSnapshot>(&data).ok())
+            .map(|snapshot| SnapshotSummary {
+                id: snapshot.id,
+                label: snapshot.label,
+                captured_at: snapshot.captured_at,
+            })
+            .collect();
+        summaries.sort_by_key(|summary| summary.captured_at);
+        summaries
+    }
+
+    fn path_for(&self, id: &str) -> Result<PathBuf, String> {
+        if id.is_empty() || !id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-') {
+            return Err(format!("Invalid snapshot id: {}", id));
+        }
+        Ok(self.dir.join(format!("{}.json", id)))
+    }
+}
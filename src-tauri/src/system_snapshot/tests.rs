@@ -0,0 +1,109 @@
+use super::{
+    capture_system_snapshot, category_usage, compare_snapshots, SnapshotSources, SnapshotStore,
+};
+use crate::file_cleaner::CleanableFile;
+use std::fs;
+use std::path::Path;
+use tempfile::TempDir;
+
+fn install_app(apps: &Path, name: &str, version: &str) {
+    let contents = apps.join(format!("{}.app/Contents", name));
+    fs::create_dir_all(&contents).unwrap();
+    fs::write(
+        contents.join("Info.plist"),
+        format!(
+            "<plist><dict><key>CFBundleIdentifier</key><string>com.example.{}</string>\
+             <key>CFBundleShortVersionString</key><string>{}</string></dict></plist>",
+            name.to_lowercase(),
+            version
+        ),
+    )
+    .unwrap();
+}
+
+fn cache_file(category: &str, size: u64) -> CleanableFile {
+    CleanableFile {
+        path: format!("/tmp/{}-{}", category, size),
+        size,
+        category: category.into(),
+        description: String::new(),
+        last_modified: 0,
+        safe_to_delete: true,
+        safety_score: 90,
+        auto_select: false,
+    }
+}
+
+#[test]
+fn snapshot_diff_reports_inventory_and_usage_changes() {
+    let root = TempDir::new().unwrap();
+    let apps = root.path().join("Applications");
+    let agents = root.path().join("LaunchAgents");
+    let kexts = root.path().join("Extensions");
+    fs::create_dir_all(apps.join("Utilities")).unwrap();
+    fs::create_dir_all(&agents).unwrap();
+    fs::create_dir_all(&kexts).unwrap();
+    install_app(&apps, "Editor", "1.0");
+    install_app(&apps, "Legacy", "2.0");
+    fs::write(agents.join("com.example.sync.plist"), b"").unwrap();
+
+    let sources = SnapshotSources {
+        app_dirs: vec![apps.clone()],
+        launch_dirs: vec![(agents.clone(), "user_agent")],
+        kext_dirs: vec![kexts.clone()],
+    };
+    let before = capture_system_snapshot(
+        "fast",
+        &sources,
+        category_usage(&[cache_file("User Cache", 100)]),
+    );
+    assert_eq!(before.installed_apps.len(), 2);
+    assert_eq!(
+        before.installed_apps[0].bundle_id.as_deref(),
+        Some("com.example.editor")
+    );
+
+    install_app(&apps, "Editor", "1.1");
+    fs::remove_dir_all(apps.join("Legacy.app")).unwrap();
+    install_app(&apps.join("Utilities"), "Monitor", "3.0");
+    fs::write(agents.join("com.example.updater.plist"), b"").unwrap();
+    fs::create_dir_all(kexts.join("Driver.kext")).unwrap();
+    let after = capture_system_snapshot(
+        "slow",
+        &sources,
+        category_usage(&[
+            cache_file("User Cache", 100),
+            cache_file("User Cache", 400),
+            cache_file("Logs", 50),
+        ]),
+    );
+
+    let diff = compare_snapshots(&before, &after);
+    assert_eq!(diff.apps_added.len(), 1);
+    assert_eq!(diff.apps_added[0].name, "Monitor");
+    assert_eq!(diff.apps_removed[0].name, "Legacy");
+    assert_eq!(diff.apps_updated[0].to.as_deref(), Some("1.1"));
+    assert_eq!(diff.launch_items_added[0].label, "com.example.updater");
+    assert!(diff.launch_items_removed.is_empty());
+    assert_eq!(diff.kexts_added, vec!["Driver.kext".to_string()]);
+    assert_eq!(diff.disk_usage_changes[0].category, "User Cache");
+    assert_eq!(diff.disk_usage_changes[0].delta, 400);
+    assert_eq!(diff.disk_usage_changes[1].category, "Logs");
+}
+
+#[test]
+fn snapshot_store_round_trips_and_rejects_bad_ids() {
+    let root = TempDir::new().unwrap();
+    let store = SnapshotStore::at(root.path().join("snapshots"));
+    let sources = SnapshotSources {
+        app_dirs: Vec::new(),
+        launch_dirs: Vec::new(),
+        kext_dirs: Vec::new(),
+    };
+    let snapshot = capture_system_snapshot("baseline", &sources, Vec::new());
+    store.save(&snapshot).unwrap();
+
+    assert_eq!(store.load(&snapshot.id).unwrap().label, "baseline");
+    assert_eq!(store.list().len(), 1);
+    assert!(store.load("../../etc/passwd").is_err());
+}
@@ -0,0 +1,193 @@
+// src/tray.rs
+
+//! Menu bar item for running the optimizer without its window.
+//!
+//! The tray shows memory pressure and free space on the system volume next
+//! to its icon and offers the quick actions that make sense unattended.
+//! Whether it is shown persists across launches; while it is, closing the
+//! window hides it instead of quitting.
+
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use tauri::menu::{Menu, MenuItem, PredefinedMenuItem};
+use tauri::tray::TrayIconBuilder;
+use tauri::AppHandle;
+
+use crate::metrics::HistoryPoint;
+use crate::persistence;
+
+const TRAY_FILE: &str = "tray.json";
+const TRAY_ID: &str = "main";
+
+/// How often the tray readings are refreshed.
+pub const REFRESH_INTERVAL: std::time::Duration = std::time::Duration::from_secs(10);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TrayAction {
+    OptimizeMemory,
+    QuickClean,
+    ShowWindow,
+    Quit,
+}
+
+impl TrayAction {
+    const ALL: [TrayAction; 4] = [
+        TrayAction::OptimizeMemory,
+        TrayAction::QuickClean,
+        TrayAction::ShowWindow,
+        TrayAction::Quit,
+    ];
+
+    fn menu_id(self) -> &'static str {
+        match self {
+            TrayAction::OptimizeMemory => "optimize-memory",
+            TrayAction::QuickClean => "quick-clean",
+            TrayAction::ShowWindow => "show-window",
+            TrayAction::Quit => "quit",
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            TrayAction::OptimizeMemory => "Optimize Memory",
+            TrayAction::QuickClean => "Quick Clean Caches",
+            TrayAction::ShowWindow => "Open macOS Optimizer",
+            TrayAction::Quit => "Quit",
+        }
+    }
+
+    pub(crate) fn from_menu_id(id: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|action| action.menu_id() == id)
+    }
+}
+
+/// What the tray displays; `None` while a sampler has no value.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct TrayReadings {
+    pub memory_pressure: Option<f32>,
+    pub disk_free_percent: Option<f32>,
+}
+
+impl TrayReadings {
+    pub fn from_point(point: &HistoryPoint) -> Self {
+        let disk_free_percent = match (point.disk_used, point.disk_total) {
+            (Some(used), Some(total)) if total > 0 => {
+                Some(total.saturating_sub(used) as f32 / total as f32 * 100.0)
+            }
+            _ => None,
+        };
+        Self {
+            memory_pressure: point.memory_pressure,
+            disk_free_percent,
+        }
+    }
+
+    /// Text beside the menu bar icon.
+    pub fn title(&self) -> String {
+        format!(
+            "{} mem · {} free",
+            percent(self.memory_pressure),
+            percent(self.disk_free_percent)
+        )
+    }
+
+    pub fn tooltip(&self) -> String {
+        format!(
+            "Memory pressure: {}\nDisk free: {}",
+            percent(self.memory_pressure),
+            percent(self.disk_free_percent)
+        )
+    }
+}
+
+fn percent(value: Option<f32>) -> String {
+    value.map_or_else(|| "--".to_string(), |value| format!("{:.0}%", value))
+}
+
+/// Whether the tray is shown, persisted across launches.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct TraySettings {
+    pub enabled: bool,
+    #[serde(skip)]
+    path: Option<PathBuf>,
+}
+
+impl TraySettings {
+    /// Load from `<data dir>/macos-optimizer/tray.json`.
+    pub fn load() -> Self {
+        match persistence::data_file(TRAY_FILE) {
+            Some(path) => Self::load_from(&path),
+            None => Self::default(),
+        }
+    }
+
+    /// Load from an explicit file; a missing or unreadable file leaves the tray off.
+    pub fn load_from(path: &Path) -> Self {
+        let mut settings = persistence::read_json::<TraySettings>(path).unwrap_or_default();
+        settings.path = Some(path.to_path_buf());
+        settings
+    }
+
+    pub fn set_enabled(&mut self, enabled: bool) -> Result<(), String> {
+        self.enabled = enabled;
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        persistence::write_json(path, self)
+    }
+}
+
+/// Add the tray to the menu bar; does nothing if it is already there.
+pub fn install(app: &AppHandle, on_action: fn(&AppHandle, TrayAction)) -> Result<(), String> {
+    if app.tray_by_id(TRAY_ID).is_some() {
+        return Ok(());
+    }
+    build(app, on_action).map_err(|e| format!("Failed to create menu bar item: {}", e))
+}
+
+fn build(app: &AppHandle, on_action: fn(&AppHandle, TrayAction)) -> tauri::Result<()> {
+    let item = |action: TrayAction| {
+        MenuItem::with_id(app, action.menu_id(), action.label(), true, None::<&str>)
+    };
+    let menu = Menu::with_items(
+        app,
+        &[
+            &item(TrayAction::OptimizeMemory)?,
+            &item(TrayAction::QuickClean)?,
+            &PredefinedMenuItem::separator(app)?,
+            &item(TrayAction::ShowWindow)?,
+            &item(TrayAction::Quit)?,
+        ],
+    )?;
+    let readings = TrayReadings::default();
+    let mut builder = TrayIconBuilder::with_id(TRAY_ID)
+        .menu(&menu)
+        .title(readings.title())
+        .tooltip(readings.tooltip())
+        .on_menu_event(move |app, event| {
+            if let Some(action) = TrayAction::from_menu_id(event.id().as_ref()) {
+                on_action(app, action);
+            }
+        });
+    if let Some(icon) = app.default_window_icon() {
+        builder = builder.icon(icon.clone());
+    }
+    builder.build(app)?;
+    Ok(())
+}
+
+pub fn remove(app: &AppHandle) {
+    app.remove_tray_by_id(TRAY_ID);
+}
+
+/// Show `readings` if the tray is installed.
+pub fn update(app: &AppHandle, readings: &TrayReadings) {
+    if let Some(tray) = app.tray_by_id(TRAY_ID) {
+        tray.set_title(Some(readings.title())).ok();
+        tray.set_tooltip(Some(readings.tooltip())).ok();
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,39 @@
+use super::{TrayAction, TrayReadings, TraySettings};
+use crate::metrics::HistoryPoint;
+
+#[test]
+fn readings_show_pressure_and_free_space_or_placeholders() {
+    let readings = TrayReadings::from_point(&HistoryPoint {
+        memory_pressure: Some(42.4),
+        disk_used: Some(300),
+        disk_total: Some(1000),
+        ..HistoryPoint::default()
+    });
+    assert_eq!(readings.title(), "42% mem · 70% free");
+    assert_eq!(readings.tooltip(), "Memory pressure: 42%\nDisk free: 70%");
+
+    let unknown = TrayReadings::from_point(&HistoryPoint {
+        disk_used: Some(0),
+        disk_total: Some(0),
+        ..HistoryPoint::default()
+    });
+    assert_eq!(unknown.title(), "-- mem · -- free");
+}
+
+#[test]
+fn menu_ids_map_back_to_actions() {
+    for action in TrayAction::ALL {
+        assert_eq!(TrayAction::from_menu_id(action.menu_id()), Some(action));
+    }
+    assert_eq!(TrayAction::from_menu_id("status"), None);
+}
+
+#[test]
+fn tray_preference_persists() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("tray.json");
+    let mut settings = TraySettings::load_from(&path);
+    assert!(!settings.enabled);
+    settings.set_enabled(true).unwrap();
+    assert!(TraySettings::load_from(&path).enabled);
+}
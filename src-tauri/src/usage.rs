@@ -0,0 +1,269 @@
+// src/usage.rs
+
+//! Opt-in usage metrics and the shareable summary exported from them.
+//!
+//! Nothing is recorded until the user opts in with
+//! [`UsageStore::set_opted_in`]; opting out deletes what was collected.
+//! [`UsageStore::export`] turns the local counts into a [`UsageSummary`] the
+//! user can read and choose to share. It never contains paths, file names or
+//! the names of custom rules.
+//!
+//! Summary schema, version 1 (JSON object):
+//!
+//! - `schema_version` (number): `1`.
+//! - `generated_on` (string): UTC date of the export, `YYYY-MM-DD`.
+//! - `collecting_since` (string): UTC date the user opted in, `YYYY-MM-DD`.
+//! - `epsilon` (number): differential privacy budget spent on each count.
+//! - `feature_usage` (object): [`UsageFeature`] name to the number of times
+//!   it completed, with Laplace noise of scale `1 / epsilon` added, rounded
+//!   and never negative. The noise is drawn once per count and kept with the
+//!   data, so exporting again reveals nothing new until the count changes.
+//! - `category_bytes` (object): built-in cleaning category, or `"Other"` for
+//!   custom rules, to the bucket of bytes cleaned: `"under 100 MB"`,
+//!   `"100 MB to 1 GB"`, `"1 GB to 10 GB"`, `"10 GB to 100 GB"` or
+//!   `"100 GB or more"`.
+
+use std::collections::{BTreeMap, HashSet};
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::file_cleaner::types::builtin_category_names;
+use crate::persistence;
+
+#[cfg(test)]
+mod tests;
+
+const USAGE_FILE: &str = "usage.json";
+pub const USAGE_SCHEMA_VERSION: u32 = 1;
+/// Privacy budget per count; one more or one fewer use of a feature changes
+/// the odds of any exported value by at most a factor of e.
+pub const USAGE_EPSILON: f64 = 1.0;
+/// Stands in for every category that is not one of the embedded rules.
+const OTHER_CATEGORY: &str = "Other";
+/// Upper bounds of the size buckets, in decimal units like `format_size`.
+const SIZE_BUCKETS: &[(u64, &str)] = &[
+    (100_000_000, "under 100 MB"),
+    (1_000_000_000, "100 MB to 1 GB"),
+    (10_000_000_000, "1 GB to 10 GB"),
+    (100_000_000_000, "10 GB to 100 GB"),
+];
+const LARGEST_BUCKET: &str = "100 GB or more";
+
+/// Features counted once per completed run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UsageFeature {
+    SystemScan,
+    FileClean,
+    QuickClean,
+    EmptyTrash,
+    DuplicateScan,
+    DuplicateResolution,
+    AppUninstall,
+    MemoryOptimization,
+    ScheduledJob,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct UsageData {
+    opted_in: bool,
+    since: i64,
+    features: BTreeMap<UsageFeature, u64>,
+    category_bytes: BTreeMap<String, u64>,
+    /// Noise already drawn for each feature's current count.
+    #[serde(default)]
+    noise: BTreeMap<UsageFeature, f64>,
+}
+
+/// Aggregate, anonymized usage, as written to the exported file.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UsageSummary {
+    pub schema_version: u32,
+    pub generated_on: String,
+    pub collecting_since: String,
+    pub epsilon: f64,
+    pub feature_usage: BTreeMap<UsageFeature, u64>,
+    pub category_bytes: BTreeMap<String, String>,
+}
+
+impl UsageSummary {
+    pub fn write_to(&self, path: &Path) -> Result<(), String> {
+        persistence::write_json(path, self)
+    }
+}
+
+/// Usage counts, kept only while the user has opted in.
+#[derive(Default)]
+pub struct UsageStore {
+    path: Option<PathBuf>,
+    data: UsageData,
+}
+
+impl UsageStore {
+    /// Load from `<data dir>/macos-optimizer/usage.json`.
+    pub fn load() -> Self {
+        match persistence::data_file(USAGE_FILE) {
+            Some(path) => Self::load_from(&path),
+            None => Self::default(),
+        }
+    }
+
+    /// Load from an explicit file; a missing or unreadable file yields an
+    /// empty store that has not been opted in.
+    pub fn load_from(path: &Path) -> Self {
+        let data = persistence::read_json(path).unwrap_or_default();
+        Self {
+            path: Some(path.to_path_buf()),
+            data,
+        }
+    }
+
+    pub fn opted_in(&self) -> bool {
+        self.data.opted_in
+    }
+
+    /// Start or stop collecting. Opting out discards everything collected.
+    pub fn set_opted_in(&mut self, opted_in: bool, now: i64) -> Result<(), String> {
+        if opted_in == self.data.opted_in {
+            return Ok(());
+        }
+        self.data = UsageData {
+            opted_in,
+            since: if opted_in { now } else { 0 },
+            ..Default::default()
+        };
+        self.persist()
+    }
+
+    /// Count one completed run of `feature`; a no-op unless opted in.
+    pub fn record_feature(&mut self, feature: UsageFeature) -> Result<(), String> {
+        if !self.data.opted_in {
+            return Ok(());
+        }
+        let count = self.data.features.entry(feature).or_default();
+        *count = count.saturating_add(1);
+        self.data.noise.remove(&feature);
+        self.persist()
+    }
+
+    /// Add bytes cleaned per category; a no-op unless opted in.
+    pub fn record_cleaned<'a>(
+        &mut self,
+        cleaned: impl IntoIterator<Item = (&'a str, u64)>,
+    ) -> Result<(), String> {
+        if !self.data.opted_in {
+            return Ok(());
+        }
+        for (category, bytes) in cleaned {
+            let total = self
+                .data
+                .category_bytes
+                .entry(category.to_string())
+                .or_default();
+            *total = total.saturating_add(bytes);
+        }
+        self.persist()
+    }
+
+    /// The shareable summary of what was collected. Noise is drawn only for
+    /// counts that changed since the last export and saved with the data.
+    pub fn export(&mut self, now: i64) -> Result<UsageSummary, String> {
+        self.export_with_noise(now, laplace_noise)
+    }
+
+    /// [`Self::export`] with the noise source supplied: `noise(scale)` must
+    /// sample a Laplace distribution centred on zero.
+    pub(crate) fn export_with_noise(
+        &mut self,
+        now: i64,
+        mut noise: impl FnMut(f64) -> f64,
+    ) -> Result<UsageSummary, String> {
+        if !self.data.opted_in {
+            return Err("Usage metrics are off; opt in before exporting".into());
+        }
+        let scale = 1.0 / USAGE_EPSILON;
+        let mut drawn = false;
+        for feature in self.data.features.keys() {
+            self.data.noise.entry(*feature).or_insert_with(|| {
+                drawn = true;
+                noise(scale)
+            });
+        }
+        if drawn {
+            self.persist()?;
+        }
+        let feature_usage = self
+            .data
+            .features
+            .iter()
+            .map(|(feature, count)| {
+                let noisy = (*count as f64 + self.data.noise[feature]).round().max(0.0);
+                (*feature, noisy as u64)
+            })
+            .collect();
+
+        let builtin: HashSet<String> = builtin_category_names().into_iter().collect();
+        let mut redacted: BTreeMap<String, u64> = BTreeMap::new();
+        for (category, bytes) in &self.data.category_bytes {
+            let name = if builtin.contains(category) {
+                category.clone()
+            } else {
+                OTHER_CATEGORY.to_string()
+            };
+            let total = redacted.entry(name).or_default();
+            *total = total.saturating_add(*bytes);
+        }
+        let category_bytes = redacted
+            .into_iter()
+            .map(|(category, bytes)| (category, size_bucket(bytes).to_string()))
+            .collect();
+
+        Ok(UsageSummary {
+            schema_version: USAGE_SCHEMA_VERSION,
+            generated_on: utc_date(now),
+            collecting_since: utc_date(self.data.since),
+            epsilon: USAGE_EPSILON,
+            feature_usage,
+            category_bytes,
+        })
+    }
+
+    fn persist(&self) -> Result<(), String> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        persistence::write_json(path, &self.data)
+    }
+}
+
+/// Where an export goes unless the user picks a file: their Downloads folder.
+pub fn default_export_path(now: i64) -> Option<PathBuf> {
+    dirs::download_dir()
+        .or_else(dirs::home_dir)
+        .map(|dir| dir.join(format!("macos-optimizer-usage-{}.json", utc_date(now))))
+}
+
+fn size_bucket(bytes: u64) -> &'static str {
+    SIZE_BUCKETS
+        .iter()
+        .find(|(limit, _)| bytes < *limit)
+        .map(|(_, label)| *label)
+        .unwrap_or(LARGEST_BUCKET)
+}
+
+fn utc_date(timestamp: i64) -> String {
+    DateTime::<Utc>::from_timestamp(timestamp, 0)
+        .unwrap_or_default()
+        .format("%Y-%m-%d")
+        .to_string()
+}
+
+/// One Laplace sample by inverse transform. The uniform draw comes from the
+/// 53 low bits of a random UUID, which carry no version or variant bits.
+fn laplace_noise(scale: f64) -> f64 {
+    let bits = uuid::Uuid::new_v4().as_u128() & ((1u128 << 53) - 1);
+    let uniform = (bits as f64 + 0.5) / (1u64 << 53) as f64 - 0.5;
+    -scale * uniform.signum() * (1.0 - 2.0 * uniform.abs()).ln()
+}
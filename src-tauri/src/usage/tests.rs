@@ -0,0 +1,141 @@
+use super::{laplace_noise, size_bucket, UsageFeature, UsageStore, USAGE_SCHEMA_VERSION};
+use tempfile::TempDir;
+
+const NOW: i64 = 1_760_000_000;
+
+fn opted_in_store(temp_dir: &TempDir) -> UsageStore {
+    let mut store = UsageStore::load_from(&temp_dir.path().join("usage.json"));
+    store.set_opted_in(true, NOW - 86_400).unwrap();
+    store
+}
+
+#[test]
+fn nothing_is_recorded_before_opting_in() {
+    let temp_dir = TempDir::new().unwrap();
+    let path = temp_dir.path().join("usage.json");
+    let mut store = UsageStore::load_from(&path);
+    store.record_feature(UsageFeature::SystemScan).unwrap();
+    store.record_cleaned([("User Cache", 1_000_000)]).unwrap();
+
+    assert!(!store.opted_in());
+    assert!(!path.exists());
+    assert!(store.export(NOW).is_err());
+}
+
+#[test]
+fn opting_out_discards_collected_usage() {
+    let temp_dir = TempDir::new().unwrap();
+    let path = temp_dir.path().join("usage.json");
+    let mut store = opted_in_store(&temp_dir);
+    store.record_feature(UsageFeature::FileClean).unwrap();
+    assert!(UsageStore::load_from(&path).opted_in());
+
+    store.set_opted_in(false, NOW).unwrap();
+    store.set_opted_in(true, NOW).unwrap();
+    let summary = store.export_with_noise(NOW, |_| 0.0).unwrap();
+    assert!(summary.feature_usage.is_empty());
+    assert_eq!(summary.collecting_since, "2025-10-09");
+}
+
+#[test]
+fn export_redacts_paths_and_custom_category_names() {
+    let temp_dir = TempDir::new().unwrap();
+    let mut store = opted_in_store(&temp_dir);
+    store.record_feature(UsageFeature::SystemScan).unwrap();
+    store.record_feature(UsageFeature::SystemScan).unwrap();
+    store.record_feature(UsageFeature::QuickClean).unwrap();
+    store
+        .record_cleaned([
+            ("User Cache", 250_000_000),
+            ("Trash", 12_000_000_000),
+            ("Jane's Thesis Drafts", 40_000_000),
+            ("/Users/jane/Projects/acme", 70_000_000),
+        ])
+        .unwrap();
+
+    let summary = store.export_with_noise(NOW, |_| 0.0).unwrap();
+    let json = serde_json::to_string(&summary).unwrap();
+    for private in ["jane", "Jane", "Thesis", "acme", "/Users", "250000000"] {
+        assert!(!json.contains(private), "{} leaked into {}", private, json);
+    }
+
+    assert_eq!(summary.schema_version, USAGE_SCHEMA_VERSION);
+    assert_eq!(summary.generated_on, "2025-10-09");
+    assert_eq!(summary.feature_usage[&UsageFeature::SystemScan], 2);
+    assert_eq!(summary.feature_usage[&UsageFeature::QuickClean], 1);
+    assert_eq!(summary.category_bytes["User Cache"], "100 MB to 1 GB");
+    assert_eq!(summary.category_bytes["Trash"], "10 GB to 100 GB");
+    // Custom categories are pooled before bucketing: 40 MB + 70 MB.
+    assert_eq!(summary.category_bytes["Other"], "100 MB to 1 GB");
+    assert_eq!(summary.category_bytes.len(), 3);
+    assert!(json.contains("\"system_scan\":2"));
+
+    let exported = temp_dir.path().join("summary.json");
+    summary.write_to(&exported).unwrap();
+    let written = std::fs::read_to_string(&exported).unwrap();
+    assert!(!written.contains("jane"));
+    assert!(written.contains("\"schema_version\": 1"));
+}
+
+#[test]
+fn noisy_counts_are_rounded_and_never_negative() {
+    let temp_dir = TempDir::new().unwrap();
+    let mut store = opted_in_store(&temp_dir);
+    store.record_feature(UsageFeature::EmptyTrash).unwrap();
+    store.record_feature(UsageFeature::AppUninstall).unwrap();
+    store.record_feature(UsageFeature::AppUninstall).unwrap();
+
+    let mut samples = [-5.0, 1.6].into_iter();
+    let summary = store
+        .export_with_noise(NOW, |scale| {
+            assert_eq!(scale, 1.0);
+            samples.next().unwrap()
+        })
+        .unwrap();
+    // Features are noised in declaration order: empty_trash, app_uninstall.
+    assert_eq!(summary.feature_usage[&UsageFeature::EmptyTrash], 0);
+    assert_eq!(summary.feature_usage[&UsageFeature::AppUninstall], 4);
+}
+
+#[test]
+fn laplace_noise_is_centred_with_the_requested_scale() {
+    let samples: Vec<f64> = (0..20_000).map(|_| laplace_noise(2.0)).collect();
+    assert!(samples.iter().all(|sample| sample.is_finite()));
+    let mean = samples.iter().sum::<f64>() / samples.len() as f64;
+    let mean_abs = samples.iter().map(|sample| sample.abs()).sum::<f64>() / samples.len() as f64;
+    assert!(mean.abs() < 0.15, "mean {}", mean);
+    // E|X| equals the scale for a Laplace distribution.
+    assert!((mean_abs - 2.0).abs() < 0.15, "mean |x| {}", mean_abs);
+}
+
+#[test]
+fn size_buckets_use_decimal_bounds() {
+    assert_eq!(size_bucket(0), "under 100 MB");
+    assert_eq!(size_bucket(99_999_999), "under 100 MB");
+    assert_eq!(size_bucket(100_000_000), "100 MB to 1 GB");
+    assert_eq!(size_bucket(5_000_000_000), "1 GB to 10 GB");
+    assert_eq!(size_bucket(100_000_000_000), "100 GB or more");
+}
+
+#[test]
+fn noise_is_kept_until_the_count_changes() {
+    let temp_dir = TempDir::new().unwrap();
+    let path = temp_dir.path().join("usage.json");
+    let mut store = opted_in_store(&temp_dir);
+    store.record_feature(UsageFeature::SystemScan).unwrap();
+    store.record_feature(UsageFeature::FileClean).unwrap();
+
+    let first = store.export_with_noise(NOW, |_| 3.0).unwrap();
+    assert_eq!(first.feature_usage[&UsageFeature::SystemScan], 4);
+    // Exporting again, even after a reload, reuses the saved draws.
+    let mut store = UsageStore::load_from(&path);
+    let again = store
+        .export_with_noise(NOW, |_| panic!("noise redrawn for an unchanged count"))
+        .unwrap();
+    assert_eq!(again.feature_usage, first.feature_usage);
+
+    store.record_feature(UsageFeature::SystemScan).unwrap();
+    let changed = store.export_with_noise(NOW, |_| -1.0).unwrap();
+    assert_eq!(changed.feature_usage[&UsageFeature::SystemScan], 1);
+    assert_eq!(changed.feature_usage[&UsageFeature::FileClean], 4);
+}
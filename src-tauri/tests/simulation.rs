@@ -0,0 +1,23 @@
+use std::fs;
+use std::path::Path;
+
+use macos_optimizer_lib::{run_simulation, FixtureSpec};
+
+#[tokio::test]
+async fn fixtures_produce_expected_outcomes() {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/simulation");
+    let mut fixtures: Vec<_> = fs::read_dir(&dir)
+        .expect("fixture dir")
+        .filter_map(Result::ok)
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+        .collect();
+    fixtures.sort();
+    assert!(!fixtures.is_empty(), "no fixtures in {}", dir.display());
+
+    for path in fixtures {
+        let spec = FixtureSpec::from_file(&path).expect("parse fixture");
+        let report = run_simulation(&spec).await.expect("simulation runs");
+        assert!(report.passed(), "{}: {:#?}", path.display(), report);
+    }
+}
@@ -1,10 +1,17 @@
+// Tests serialize access to process-wide env vars with a std mutex held for the
+// whole test body, including across awaits on the current-thread runtime.
+#![allow(clippy::await_holding_lock)]
+
 use std::fs;
 use std::io::Write;
 use std::path::{Path, PathBuf};
 use std::sync::Mutex;
 
 use lazy_static::lazy_static;
-use macos_optimizer_lib::StorageFileCleaner as FileCleaner;
+use macos_optimizer_lib::{
+    AppUninstaller, LeftoverKind, StorageFileCleaner as FileCleaner, StorageScanProgress,
+    TrashEmptyProgress,
+};
 use tempfile::TempDir;
 use tokio_util::sync::CancellationToken;
 
@@ -30,6 +37,7 @@ struct StorageTestEnv {
     prev_home: Option<String>,
     prev_rules_override: Option<String>,
     prev_disable_osa: Option<String>,
+    prev_disable_native_trash: Option<String>,
 }
 
 impl StorageTestEnv {
@@ -49,11 +57,15 @@ impl StorageTestEnv {
         let prev_disable_osa = std::env::var("MACOS_OPTIMIZER_DISABLE_OSA").ok();
         std::env::set_var("MACOS_OPTIMIZER_DISABLE_OSA", "1");
 
+        let prev_disable_native_trash = std::env::var("MACOS_OPTIMIZER_DISABLE_NATIVE_TRASH").ok();
+        std::env::set_var("MACOS_OPTIMIZER_DISABLE_NATIVE_TRASH", "1");
+
         StorageTestEnv {
             temp_home,
             prev_home,
             prev_rules_override,
             prev_disable_osa,
+            prev_disable_native_trash,
         }
     }
 
@@ -100,6 +112,12 @@ impl Drop for StorageTestEnv {
         } else {
             std::env::remove_var("MACOS_OPTIMIZER_DISABLE_OSA");
         }
+
+        if let Some(prev) = self.prev_disable_native_trash.take() {
+            std::env::set_var("MACOS_OPTIMIZER_DISABLE_NATIVE_TRASH", prev);
+        } else {
+            std::env::remove_var("MACOS_OPTIMIZER_DISABLE_NATIVE_TRASH");
+        }
     }
 }
 
@@ -118,7 +136,7 @@ async fn scan_detects_only_stale_downloads() {
     let token = CancellationToken::new();
 
     let _report = cleaner
-        .scan_system_with_cancel(&token)
+        .scan_system_with_cancel(&token, None)
         .await
         .expect("scan should succeed");
 
@@ -146,6 +164,30 @@ async fn scan_detects_only_stale_downloads() {
     assert_eq!(entry.path, large.to_string_lossy());
 }
 
+#[tokio::test]
+async fn scan_reports_progress_per_rule_path() {
+    let _guard = acquire_env_guard();
+    let env = StorageTestEnv::new();
+    env.create_file("Downloads/large.crdownload", 4096);
+
+    let mut cleaner = FileCleaner::new();
+    let token = CancellationToken::new();
+    let updates: Mutex<Vec<StorageScanProgress>> = Mutex::new(Vec::new());
+    let record = |update: StorageScanProgress| updates.lock().unwrap().push(update);
+    cleaner
+        .scan_system_with_cancel(&token, Some(&record))
+        .await
+        .expect("scan should succeed");
+
+    let updates = updates.into_inner().unwrap();
+    assert_eq!(updates.len(), 1, "one update per existing rule path");
+    let update = &updates[0];
+    assert_eq!(update.category, "Test Downloads");
+    assert_eq!((update.paths_done, update.paths_total), (1, 1));
+    assert!(update.entries_seen >= 1);
+    assert_eq!(update.bytes_found, 4096);
+}
+
 #[tokio::test]
 async fn clean_files_moves_items_to_trash() {
     let _guard = acquire_env_guard();
@@ -155,7 +197,7 @@ async fn clean_files_moves_items_to_trash() {
     let mut cleaner = FileCleaner::new();
     let token = CancellationToken::new();
     cleaner
-        .scan_system_with_cancel(&token)
+        .scan_system_with_cancel(&token, None)
         .await
         .expect("scan should succeed");
 
@@ -180,7 +222,7 @@ async fn empty_trash_removes_items() {
     let mut cleaner = FileCleaner::new();
     let token = CancellationToken::new();
     cleaner
-        .scan_system_with_cancel(&token)
+        .scan_system_with_cancel(&token, None)
         .await
         .expect("scan should succeed");
     cleaner
@@ -237,7 +279,7 @@ async fn empty_trash_invalidation_refreshes_home_aggregate_size() {
     let mut cleaner = FileCleaner::new();
     let token = CancellationToken::new();
     cleaner
-        .scan_system_with_cancel(&token)
+        .scan_system_with_cancel(&token, None)
         .await
         .expect("scan should succeed");
     cleaner
@@ -271,3 +313,162 @@ async fn empty_trash_invalidation_refreshes_home_aggregate_size() {
         home_size_after
     );
 }
+
+#[tokio::test]
+async fn enumerate_trash_lists_items_with_sizes() {
+    let _guard = acquire_env_guard();
+    let env = StorageTestEnv::new();
+    env.create_file(".Trash/loose.bin", 4096);
+    env.create_file(".Trash/folder/nested.bin", 8192);
+
+    let cleaner = FileCleaner::new();
+    let items = cleaner.enumerate_trash().await.expect("enumerate trash");
+
+    let names: Vec<_> = items.iter().map(|item| item.name.as_str()).collect();
+    assert_eq!(names, vec!["folder", "loose.bin"]);
+    let folder = &items[0];
+    assert!(folder.is_dir);
+    assert!(
+        folder.size >= 8192,
+        "folder size should include nested files"
+    );
+    assert!(!items[1].is_dir);
+    assert!(items[1].size >= 4096);
+}
+
+#[tokio::test]
+async fn empty_trash_batched_reports_progress() {
+    let _guard = acquire_env_guard();
+    let env = StorageTestEnv::new();
+    for idx in 0..150 {
+        env.create_file(&format!(".Trash/item-{idx:03}.bin"), 512);
+    }
+
+    let cleaner = FileCleaner::new();
+    let token = CancellationToken::new();
+    let updates = Mutex::new(Vec::new());
+    let progress = |update: TrashEmptyProgress| {
+        updates.lock().unwrap().push(update);
+    };
+
+    let result = cleaner
+        .empty_trash_batched(&token, Some(&progress))
+        .await
+        .expect("empty trash");
+
+    assert!(!result.canceled);
+    assert_eq!(result.total_items, 150);
+    assert_eq!(result.removed, 150);
+    assert_eq!(result.failed, 0);
+    assert_eq!(result.freed, result.total_bytes);
+
+    let updates = updates.into_inner().unwrap();
+    assert!(updates.len() >= 2, "expected one update per batch");
+    let last = updates.last().expect("final update");
+    assert_eq!(last.items_done, 150);
+    assert_eq!(last.items_total, 150);
+    assert_eq!(last.bytes_done, result.total_bytes);
+    assert_eq!(
+        fs::read_dir(env.trash_dir()).expect("read trash").count(),
+        0
+    );
+}
+
+#[tokio::test]
+async fn empty_trash_batched_returns_partial_result_on_cancel() {
+    let _guard = acquire_env_guard();
+    let env = StorageTestEnv::new();
+    for idx in 0..150 {
+        env.create_file(&format!(".Trash/item-{idx:03}.bin"), 512);
+    }
+
+    let cleaner = FileCleaner::new();
+    let token = CancellationToken::new();
+    let cancel_after_first = {
+        let token = token.clone();
+        move |_: TrashEmptyProgress| token.cancel()
+    };
+
+    let result = cleaner
+        .empty_trash_batched(&token, Some(&cancel_after_first))
+        .await
+        .expect("cancellation should yield a partial result");
+
+    assert!(result.canceled);
+    assert!(result.removed > 0 && result.removed < result.total_items);
+    let remaining = fs::read_dir(env.trash_dir()).expect("read trash").count();
+    assert_eq!(remaining, result.total_items - result.removed);
+
+    let err = cleaner
+        .empty_trash_with_cancel(&token)
+        .await
+        .expect_err("cancelled token should abort");
+    assert_eq!(err, "cancelled");
+}
+
+#[tokio::test]
+async fn empty_trash_fails_instead_of_reporting_nothing_when_trash_is_unreadable() {
+    let _guard = acquire_env_guard();
+    let env = StorageTestEnv::new();
+    fs::remove_dir(env.trash_dir()).expect("remove trash");
+    fs::write(env.trash_dir(), b"not a directory").expect("write trash file");
+
+    let cleaner = FileCleaner::new();
+    assert!(cleaner.enumerate_trash().await.is_err());
+    // The Finder fallback is disabled in tests, so the listing error surfaces.
+    assert!(cleaner
+        .empty_trash_batched(&CancellationToken::new(), None)
+        .await
+        .is_err());
+}
+
+#[tokio::test]
+async fn uninstall_app_moves_bundle_and_leftovers_to_trash() {
+    let _guard = acquire_env_guard();
+    let env = StorageTestEnv::new();
+    let system_root = env.home().join("system");
+    let plist = "<plist><dict><key>CFBundleIdentifier</key><string>com.example.zzdemo</string>\
+                 <key>CFBundleShortVersionString</key><string>1.0</string></dict></plist>";
+    let bundle = system_root.join("Applications/ZzDemo.app");
+    fs::create_dir_all(bundle.join("Contents")).expect("create bundle");
+    fs::write(bundle.join("Contents/Info.plist"), plist).expect("write plist");
+    env.create_file("Library/Caches/com.example.zzdemo/blob.bin", 4096);
+    env.create_file("Library/Preferences/com.example.zzdemo.plist", 128);
+    env.create_file("Library/Containers/com.example.zzdemo/Data/state.db", 1024);
+    env.create_file("Library/Caches/com.example.other/keep.bin", 64);
+
+    let uninstaller = AppUninstaller::with_roots(env.home().to_path_buf(), system_root);
+    let apps = uninstaller.list_installed_apps();
+    assert_eq!(apps.len(), 1);
+    assert_eq!(apps[0].bundle_id.as_deref(), Some("com.example.zzdemo"));
+
+    let footprint = uninstaller
+        .analyze_app_footprint(&bundle)
+        .await
+        .expect("footprint");
+    let kinds: Vec<LeftoverKind> = footprint.leftovers.iter().map(|l| l.kind).collect();
+    assert_eq!(
+        kinds,
+        vec![
+            LeftoverKind::Container,
+            LeftoverKind::Cache,
+            LeftoverKind::Preferences
+        ]
+    );
+    assert!(footprint.total_size >= 4096 + 128 + 1024);
+
+    let result = uninstaller
+        .uninstall_app(&bundle, true)
+        .await
+        .expect("uninstall");
+    assert!(result.skipped.is_empty(), "{:?}", result.skipped);
+    assert_eq!(result.moved_to_trash.len(), 4);
+    assert!(!bundle.exists());
+    assert!(env.trash_dir().join("ZzDemo.app").exists());
+    assert!(env.trash_dir().join("com.example.zzdemo.plist").exists());
+    assert!(env
+        .home()
+        .join("Library/Caches/com.example.other/keep.bin")
+        .exists());
+    assert!(uninstaller.analyze_app_footprint(&bundle).await.is_err());
+}
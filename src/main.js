@@ -433,12 +433,32 @@ let cleanableFiles = [];
 let lastReport = null;
 let showAdvanced = false;
 let currentCategoryFilter = null;
-const RISK_MODE_STORAGE_KEY = 'storageCleaner.allowRisky';
+// Risky Mode used to live in localStorage; it is now saved with the settings.
+const LEGACY_RISK_MODE_STORAGE_KEY = 'storageCleaner.allowRisky';
 const RISK_DESCRIPTION_DEFAULT = 'Allow manual selection of low-safety items after acknowledging the risks. Files are always moved to the Trash first.';
 const RISK_DESCRIPTION_EMPTY = 'All scanned items are currently considered safe. Risky Mode is optional right now.';
 let allowRiskySelections = false;
 let categorySafetySummary = new Map();
 
+async function loadRiskModeSetting() {
+    try {
+        const settings = await invoke('get_settings');
+        allowRiskySelections = settings.risky_mode;
+        if (localStorage.getItem(LEGACY_RISK_MODE_STORAGE_KEY) === 'true' && !allowRiskySelections) {
+            await saveRiskModeSetting(true);
+        }
+        localStorage.removeItem(LEGACY_RISK_MODE_STORAGE_KEY);
+    } catch (e) {
+        console.error('Failed to load Risky Mode setting:', e);
+    }
+}
+
+async function saveRiskModeSetting(enabled) {
+    const settings = await invoke('get_settings');
+    await invoke('update_settings', { settings: { ...settings, risky_mode: enabled } });
+    allowRiskySelections = enabled;
+}
+
 async function scanForCleanableFiles() {
     const scanProgress = document.getElementById('scan-progress');
     const cleaningReport = document.getElementById('cleaning-report');
@@ -462,6 +482,15 @@ async function scanForCleanableFiles() {
                 const filesCount = (report && report.base && typeof report.base.files_count === 'number') ? report.base.files_count : (report && typeof report.files_count === 'number') ? report.files_count : 0;
                 document.getElementById('total-cleanable').textContent = formatBytes(totalSize);
                 document.getElementById('files-found').textContent = filesCount;
+                const reclaimable = report && report.reclaimable;
+                const safeNowEl = document.getElementById('reclaimable-safe-now');
+                if (reclaimable) {
+                    safeNowEl.textContent = formatBytes(reclaimable.safe_now.size);
+                    safeNowEl.title = `After review: ${formatBytes(reclaimable.after_review.size)} · Advanced only: ${formatBytes(reclaimable.advanced_only.size)}`;
+                } else {
+                    safeNowEl.textContent = '--';
+                    safeNowEl.title = '';
+                }
                 
                 // Display categories
                 renderCategories();
@@ -1228,8 +1257,23 @@ function setupEventListeners() {
             const confirmed = await userConfirm('Empty the Trash now?\n\nThis permanently deletes all items in your Trash.', { title: 'Empty Trash', kind: 'warning' });
             if (!confirmed) return;
             try {
-                const [freed, count] = await invoke('empty_trash');
-                showNotification(`Emptied Trash: removed ${count} items, freed ${formatBytes(freed)}`, 'success');
+                // External drives keep their own Trash; include each only if asked
+                const summary = await invoke('get_trash_summary').catch(() => ({ volumes: [] }));
+                const volumes = [];
+                for (const trash of summary.volumes.filter(t => t.volume !== '/' && t.items > 0)) {
+                    const include = await userConfirm(`Also empty the Trash on ${trash.volume}?\n\n${trash.items} items, ${formatBytes(trash.size)}.`, { title: 'Empty Trash', kind: 'warning' });
+                    if (include) volumes.push(trash.volume);
+                }
+                const result = await invoke('empty_trash', { volumes });
+                if (result.canceled) {
+                    showNotification(`Emptying Trash canceled: removed ${result.removed} of ${result.total_items} items, freed ${formatBytes(result.freed)}`, 'warning');
+                } else if (result.emptied_by_finder) {
+                    showNotification('Emptied Trash through Finder', 'success');
+                } else if (result.failed > 0) {
+                    showNotification(`Emptied Trash: removed ${result.removed} items, ${result.failed} could not be removed, freed ${formatBytes(result.freed)}`, 'warning');
+                } else {
+                    showNotification(`Emptied Trash: removed ${result.removed} items, freed ${formatBytes(result.freed)}`, 'success');
+                }
                 // Refresh scan if results are visible
                 const cleaningReport = document.getElementById('cleaning-report');
                 if (cleaningReport && cleaningReport.style.display !== 'none') {
@@ -1242,7 +1286,7 @@ function setupEventListeners() {
         });
     }
 
-    // Basic restore from Trash action (restores selected file names into Downloads)
+    // Restore from Trash: items go back to where they were trashed from
     const restoreBtn = document.getElementById('restore-from-trash');
     if (restoreBtn) {
         restoreBtn.addEventListener('click', async () => {
@@ -1251,8 +1295,19 @@ function setupEventListeners() {
             const names = namesInput.split(',').map(s => s.trim()).filter(Boolean);
             if (names.length === 0) return;
             try {
-                const restored = await invoke('restore_from_trash', { fileNames: names });
-                showNotification(`Restored ${restored} item(s) to Downloads`, 'success');
+                let result = await invoke('restore_from_trash', { fileNames: names });
+                let restored = result.restored;
+                if (result.needs_destination.length > 0) {
+                    const destination = prompt(`No original location is known for ${result.needs_destination.length} item(s). Enter a folder to restore them to:`);
+                    if (destination) {
+                        const rest = await invoke('restore_from_trash', { fileNames: result.needs_destination, destination });
+                        restored += rest.restored;
+                        result = { ...result, failed: result.failed - result.needs_destination.length + rest.failed };
+                    }
+                }
+                const type = result.failed > 0 ? 'warning' : 'success';
+                const suffix = result.failed > 0 ? `, ${result.failed} could not be restored` : '';
+                showNotification(`Restored ${restored} item(s) from the Trash${suffix}`, type);
             } catch (e) {
                 console.error('Restore from Trash failed:', e);
                 showNotification('Failed to restore items from Trash', 'error');
@@ -1287,10 +1342,14 @@ function setupEventListeners() {
     const riskToggle = document.getElementById('enable-risk-mode');
     const initialHasRisky = Array.from(categorySafetySummary.values()).some(entry => entry.riskyCount > 0);
     if (riskToggle) {
-        const storedRiskSetting = localStorage.getItem(RISK_MODE_STORAGE_KEY);
-        allowRiskySelections = storedRiskSetting === 'true';
         riskToggle.checked = allowRiskySelections;
         updateRiskModeBannerState(initialHasRisky);
+        loadRiskModeSetting().then(() => {
+            riskToggle.checked = allowRiskySelections;
+            const hasRisky = Array.from(categorySafetySummary.values()).some(entry => entry.riskyCount > 0);
+            updateRiskModeBannerState(hasRisky);
+            refreshDisplayedFiles();
+        });
 
         riskToggle.addEventListener('change', async (event) => {
             if (event.target.checked) {
@@ -1302,12 +1361,22 @@ function setupEventListeners() {
                     event.target.checked = false;
                     return;
                 }
-                allowRiskySelections = true;
-                localStorage.setItem(RISK_MODE_STORAGE_KEY, 'true');
+                try {
+                    await saveRiskModeSetting(true);
+                } catch (e) {
+                    console.error('Failed to save Risky Mode setting:', e);
+                    event.target.checked = false;
+                    showNotification('Failed to enable Risky Mode', 'error');
+                    return;
+                }
                 showNotification('Risky Mode enabled. Low-safety items can now be selected manually.', 'warning', { duration: 5200 });
             } else {
-                allowRiskySelections = false;
-                localStorage.removeItem(RISK_MODE_STORAGE_KEY);
+                try {
+                    await saveRiskModeSetting(false);
+                } catch (e) {
+                    console.error('Failed to save Risky Mode setting:', e);
+                    allowRiskySelections = false;
+                }
                 document.querySelectorAll('#files-list input[type="checkbox"][data-risky="true"]').forEach(checkbox => {
                     checkbox.checked = false;
                     checkbox.disabled = true;
@@ -1321,8 +1390,8 @@ function setupEventListeners() {
             refreshDisplayedFiles();
         });
     } else {
-        allowRiskySelections = localStorage.getItem(RISK_MODE_STORAGE_KEY) === 'true';
         updateRiskModeBannerState(initialHasRisky);
+        loadRiskModeSetting().then(() => updateRiskModeBannerState(initialHasRisky));
     }
 
     const cleanSelectedBtn = document.getElementById('clean-selected');
@@ -1391,6 +1460,11 @@ window.addEventListener('DOMContentLoaded', () => {
     // Setup tab navigation
     setupTabNavigation();
     console.log('Tab navigation setup complete');
+
+    // Backend progress and completion messages format sizes in the UI locale
+    invoke('set_format_preferences', {
+        preferences: { locale: navigator.language.replace('-', '_'), units: 'decimal' }
+    }).catch((error) => console.warn('Failed to set format preferences:', error));
     
     // Use event delegation ONLY for optimize-memory button
     document.body.addEventListener('click', async (event) => {